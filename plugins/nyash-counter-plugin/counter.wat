@@ -0,0 +1,122 @@
+;; Nyash CounterBox Plugin - wasm module port of src/lib.rs
+;;
+;; Loaded by PluginLoaderV2 (wasm-backend feature) through the same nyash.toml
+;; tables as the native library:
+;;
+;;   [libraries."counter.wasm"]
+;;   boxes = ["CounterBox"]
+;;   path = "./plugins/nyash-counter-plugin/counter.wat"
+;;
+;; Layout of linear memory:
+;;   512         : manifest text (nyash_plugin_manifest)
+;;   1024 + id*8 : [alive i32][count i32] per instance (ids 1..8063)
+;;   65536..     : host scratch buffer returned by nyash_plugin_alloc
+(module
+  (memory (export "memory") 2)
+
+  (global $next_id (mut i32) (i32.const 1))
+  (global $scratch i32 (i32.const 65536))
+
+  (func (export "nyash_plugin_abi") (result i32) (i32.const 1))
+  (func (export "nyash_plugin_init") (result i32) (i32.const 0))
+
+  (data (i32.const 512) "abi_version = 1\0a\0a[CounterBox]\0atype_id = 7\0a\0a[CounterBox.methods]\0abirth = { method_id = 0 }\0ainc = { method_id = 1 }\0aget = { method_id = 2 }\0afini = { method_id = 4294967295 }\0a")
+
+  ;; Same box tables as nyash.toml; the loader validates them at load time
+  (func (export "nyash_plugin_manifest") (param $out i32) (param $out_len_ptr i32) (result i32)
+    (if (i32.lt_u (i32.load (local.get $out_len_ptr)) (i32.const 172))
+      (then (i32.store (local.get $out_len_ptr) (i32.const 172)) (return (i32.const -1))))
+    (memory.copy (local.get $out) (i32.const 512) (i32.const 172))
+    (i32.store (local.get $out_len_ptr) (i32.const 172))
+    (i32.const 0))
+
+  ;; The host serializes calls, so one scratch region is enough; grow on demand
+  (func (export "nyash_plugin_alloc") (param $size i32) (result i32)
+    (local $pages i32)
+    (local.set $pages
+      (i32.shr_u
+        (i32.add (i32.add (global.get $scratch) (local.get $size)) (i32.const 65535))
+        (i32.const 16)))
+    (if (i32.gt_u (local.get $pages) (memory.size))
+      (then
+        (if (i32.eq (memory.grow (i32.sub (local.get $pages) (memory.size))) (i32.const -1))
+          (then (return (i32.const 0))))))
+    (global.get $scratch))
+
+  (func (export "nyash_plugin_free") (param i32 i32))
+
+  (func $slot (param $id i32) (result i32)
+    (i32.add (i32.const 1024) (i32.shl (local.get $id) (i32.const 3))))
+
+  (func $live (param $id i32) (result i32)
+    (if (result i32)
+      (i32.or (i32.eqz (local.get $id)) (i32.ge_u (local.get $id) (global.get $next_id)))
+      (then (i32.const 0))
+      (else (i32.load (call $slot (local.get $id))))))
+
+  ;; I32 TLV: version=1 argc=1 | tag=2 rsv=0 size=4 | value  (12 bytes)
+  (func $write_tlv_i32 (param $v i32) (param $out i32) (param $out_len_ptr i32) (result i32)
+    (i32.store (local.get $out) (i32.const 0x00010001))
+    (i32.store offset=4 (local.get $out) (i32.const 0x00040002))
+    (i32.store offset=8 (local.get $out) (local.get $v))
+    (i32.store (local.get $out_len_ptr) (i32.const 12))
+    (i32.const 0))
+
+  ;; E_SHORT when *out_len_ptr < needed (required size written back)
+  (func $short (param $out_len_ptr i32) (param $needed i32) (result i32)
+    (if (result i32) (i32.lt_u (i32.load (local.get $out_len_ptr)) (local.get $needed))
+      (then (i32.store (local.get $out_len_ptr) (local.get $needed)) (i32.const 1))
+      (else (i32.const 0))))
+
+  (func (export "nyash_plugin_invoke")
+    (param $type_id i32) (param $method_id i32) (param $instance_id i32)
+    (param $args i32) (param $args_len i32) (param $out i32) (param $out_len_ptr i32)
+    (result i32)
+    (local $id i32)
+    (local $slot i32)
+    (if (i32.ne (local.get $type_id) (i32.const 7))
+      (then (return (i32.const -2))))
+
+    ;; birth: returns the new instance id as 4 raw bytes
+    (if (i32.eqz (local.get $method_id))
+      (then
+        (if (call $short (local.get $out_len_ptr) (i32.const 4))
+          (then (return (i32.const -1))))
+        (local.set $id (global.get $next_id))
+        (if (i32.ge_u (local.get $id) (i32.const 8064))
+          (then (return (i32.const -5))))
+        (global.set $next_id (i32.add (local.get $id) (i32.const 1)))
+        (local.set $slot (call $slot (local.get $id)))
+        (i32.store (local.get $slot) (i32.const 1))
+        (i32.store offset=4 (local.get $slot) (i32.const 0))
+        (i32.store (local.get $out) (local.get $id))
+        (i32.store (local.get $out_len_ptr) (i32.const 4))
+        (return (i32.const 0))))
+
+    ;; fini (u32::MAX)
+    (if (i32.eq (local.get $method_id) (i32.const -1))
+      (then
+        (if (call $live (local.get $instance_id))
+          (then (i32.store (call $slot (local.get $instance_id)) (i32.const 0))))
+        (return (i32.const 0))))
+
+    (if (i32.and
+          (i32.ne (local.get $method_id) (i32.const 1))
+          (i32.ne (local.get $method_id) (i32.const 2)))
+      (then (return (i32.const -3))))
+    (if (i32.eqz (call $live (local.get $instance_id)))
+      (then (return (i32.const -8))))
+    (if (call $short (local.get $out_len_ptr) (i32.const 12))
+      (then (return (i32.const -1))))
+    (local.set $slot (call $slot (local.get $instance_id)))
+
+    ;; inc: increments and returns the new count
+    (if (i32.eq (local.get $method_id) (i32.const 1))
+      (then
+        (i32.store offset=4 (local.get $slot)
+          (i32.add (i32.load offset=4 (local.get $slot)) (i32.const 1)))))
+
+    ;; inc / get: current count as I32 TLV
+    (call $write_tlv_i32
+      (i32.load offset=4 (local.get $slot)) (local.get $out) (local.get $out_len_ptr)))
+)
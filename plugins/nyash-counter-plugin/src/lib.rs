@@ -35,6 +35,35 @@ pub extern "C" fn nyash_plugin_abi() -> u32 { 1 }
 #[no_mangle]
 pub extern "C" fn nyash_plugin_init() -> i32 { NYB_SUCCESS }
 
+// ===== Manifest (checked by the host against nyash.toml at load time) =====
+const MANIFEST: &str = r#"abi_version = 1
+
+[CounterBox]
+type_id = 7
+singleton = true
+
+[CounterBox.methods]
+birth = { method_id = 0 }
+inc = { method_id = 1 }
+get = { method_id = 2 }
+fini = { method_id = 4294967295 }
+"#;
+
+#[no_mangle]
+pub extern "C" fn nyash_plugin_manifest(result: *mut u8, result_len: *mut usize) -> i32 {
+    if result_len.is_null() { return NYB_E_INVALID_ARGS; }
+    let bytes = MANIFEST.as_bytes();
+    unsafe {
+        if result.is_null() || *result_len < bytes.len() {
+            *result_len = bytes.len();
+            return NYB_E_SHORT_BUFFER;
+        }
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), result, bytes.len());
+        *result_len = bytes.len();
+    }
+    NYB_SUCCESS
+}
+
 #[no_mangle]
 pub extern "C" fn nyash_plugin_invoke(
     type_id: u32,
@@ -92,6 +121,25 @@ pub extern "C" fn nyash_plugin_invoke(
     }
 }
 
+// ===== wasm32 build =====
+// `cargo build --target wasm32-unknown-unknown --release` yields a wasm plugin
+// module; pointers above become linear-memory offsets and the host stages
+// args/results in buffers obtained from these exports.
+#[cfg(target_arch = "wasm32")]
+#[no_mangle]
+pub extern "C" fn nyash_plugin_alloc(size: usize) -> *mut u8 {
+    let mut buf = Vec::<u8>::with_capacity(size);
+    let ptr = buf.as_mut_ptr();
+    std::mem::forget(buf);
+    ptr
+}
+
+#[cfg(target_arch = "wasm32")]
+#[no_mangle]
+pub unsafe extern "C" fn nyash_plugin_free(ptr: *mut u8, size: usize) {
+    drop(Vec::from_raw_parts(ptr, 0, size));
+}
+
 // ===== TLV helpers =====
 fn write_tlv_result(payloads: &[(u8, &[u8])], result: *mut u8, result_len: *mut usize) -> i32 {
     if result_len.is_null() { return NYB_E_INVALID_ARGS; }
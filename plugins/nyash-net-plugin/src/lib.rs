@@ -1,12 +1,13 @@
 //! Nyash Net Plugin (HTTP stub) - BID-FFI v1
 //! Provides HttpServerBox (singleton), HttpRequestBox, HttpResponseBox, HttpClientBox
-//! This is a pure in-process stub (no real sockets), suitable for E2E of BoxRef args/returns.
+//! HTTP/1.1 over real TCP: keep-alive connections, chunked request/response bodies and
+//! streaming (`HttpRequestBox.readChunk`, `HttpResponseBox.writeChunk`/`end`).
 
 use once_cell::sync::Lazy;
 use std::collections::{HashMap, VecDeque};
-use std::sync::{Mutex, Arc, atomic::{AtomicBool, AtomicU32, Ordering}};
+use std::sync::{Mutex, Arc, mpsc, atomic::{AtomicBool, AtomicU32, Ordering}};
 use std::net::{TcpListener, TcpStream};
-use std::io::{Read, Write};
+use std::io::{BufRead, BufReader, Read, Write};
 use std::time::Duration;
 use std::io::Write as IoWrite;
 
@@ -50,6 +51,7 @@ const T_SOCK_CLIENT: u32 = 32;
 
 // Methods
 const M_BIRTH: u32 = 0;
+const M_FINI: u32 = u32::MAX; // idempotent; the handle is invalid afterwards
 
 // Server
 const M_SERVER_START: u32 = 1;
@@ -60,6 +62,7 @@ const M_SERVER_ACCEPT: u32 = 3; // -> Handle(Request)
 const M_REQ_PATH: u32 = 1;      // -> String
 const M_REQ_READ_BODY: u32 = 2; // -> Bytes (optional)
 const M_REQ_RESPOND: u32 = 3;   // arg: Handle(Response)
+const M_REQ_READ_CHUNK: u32 = 4; // -> Bytes (empty at end of body)
 
 // Response
 const M_RESP_SET_STATUS: u32 = 1; // arg: i32
@@ -68,11 +71,18 @@ const M_RESP_WRITE: u32 = 3;      // arg: bytes/string
 const M_RESP_READ_BODY: u32 = 4;  // -> Bytes
 const M_RESP_GET_STATUS: u32 = 5; // -> i32
 const M_RESP_GET_HEADER: u32 = 6; // arg: name -> string (or empty)
+const M_RESP_WRITE_CHUNK: u32 = 7; // arg: bytes/string (switches to Transfer-Encoding: chunked)
+const M_RESP_END: u32 = 8;         // finishes a streamed response
 
 // Client
 const M_CLIENT_GET: u32 = 1;   // arg: url -> Handle(Response)
 const M_CLIENT_POST: u32 = 2;  // args: url, body(bytes/string) -> Handle(Response)
 
+// HTTP connection limits
+const KEEP_ALIVE_IDLE: Duration = Duration::from_secs(5); // wait for the next request / body bytes
+const MAX_HEAD_BYTES: usize = 64 * 1024;
+const READ_CHUNK_MAX: usize = 64 * 1024; // upper bound of one readChunk() result
+
 // Socket Server
 const M_SRV_BIRTH: u32 = 0;
 const M_SRV_START: u32 = 1; // port
@@ -120,8 +130,13 @@ struct RequestState {
     path: String,
     body: Vec<u8>,
     response_id: Option<u32>,
-    // For HTTP-over-TCP server: map to an active accepted socket to respond on
-    server_conn_id: Option<u32>,
+    // For HTTP-over-TCP server: the accepted connection to read the body from and respond on
+    conn: Option<Arc<HttpConn>>,
+    // Unread part of the request body on `conn` (None once drained or for birthed requests)
+    body_reader: Option<BodyReader>,
+    // readChunk() position into an already buffered `body`
+    body_pos: usize,
+    keep_alive: bool,
     responded: bool,
 }
 
@@ -129,12 +144,56 @@ struct ResponseState {
     status: i32,
     headers: HashMap<String, String>,
     body: Vec<u8>,
-    // For HTTP-over-TCP client: associated socket connection id to read from
-    client_conn_id: Option<u32>,
+    // For HTTP-over-TCP client: connection to lazily read the response from
+    client_conn: Option<ClientConn>,
     parsed: bool,
+    // Server side: writeChunk() was used, send with Transfer-Encoding: chunked
+    chunked: bool,
+    ended: bool,
+    // Server side: streamed response bound to its connection by respond(), until end()
+    stream: Option<ServerStream>,
+}
+
+struct ClientState {
+    // Keep-alive connections ready for reuse, keyed by "host:port"
+    idle: HashMap<String, Vec<TcpStream>>,
+}
+
+/// Client connection waiting for its response; goes back to the client's pool when reusable
+struct ClientConn {
+    stream: TcpStream,
+    client_id: u32,
+    pool_key: String,
+}
+
+/// Server-side HTTP connection, shared by its connection thread and the requests read from it
+struct HttpConn {
+    reader: Mutex<BufReader<TcpStream>>,
+    writer: Mutex<TcpStream>,
+    // Completes the current request: true keeps the connection open for the next one
+    done: Mutex<Option<mpsc::Sender<bool>>>,
+    // Undrained body of a request finalized before the connection thread skipped it
+    leftover: Mutex<Option<BodyReader>>,
+}
+
+struct ServerStream {
+    conn: Arc<HttpConn>,
+    keep_alive: bool,
+}
+
+/// Message body framing
+enum Framing {
+    Length(usize),
+    Chunked { left: usize, done: bool },
+    UntilClose { done: bool },
 }
 
-struct ClientState;
+/// Incremental body reader (request bodies on the server, response bodies on the client)
+struct BodyReader {
+    framing: Framing,
+    // Client sent `Expect: 100-continue`; answer before the first read
+    expect_continue: bool,
+}
 
 // Socket types
 struct SockServerState {
@@ -144,7 +203,8 @@ struct SockServerState {
 }
 
 struct SockConnState {
-    stream: Mutex<TcpStream>,
+    // None for a birthed (never connected) SocketConnBox
+    stream: Mutex<Option<TcpStream>>,
 }
 
 struct SockClientState;
@@ -162,6 +222,100 @@ pub extern "C" fn nyash_plugin_init() -> i32 {
     OK 
 }
 
+// ===== Manifest (checked by the host against nyash.toml at load time) =====
+const MANIFEST: &str = r#"abi_version = 1
+
+[HttpServerBox]
+type_id = 20
+
+[HttpServerBox.methods]
+birth = { method_id = 0 }
+start = { method_id = 1, args = ["port"], returns_result = true }
+stop  = { method_id = 2, returns_result = true }
+accept = { method_id = 3, returns_result = true }
+fini = { method_id = 4294967295 }
+
+[HttpRequestBox]
+type_id = 21
+
+[HttpRequestBox.methods]
+birth = { method_id = 0 }
+path = { method_id = 1 }
+readBody = { method_id = 2 }
+respond = { method_id = 3, args = [{ kind = "box", category = "plugin" }] }
+readChunk = { method_id = 4 }
+fini = { method_id = 4294967295 }
+
+[HttpResponseBox]
+type_id = 22
+
+[HttpResponseBox.methods]
+birth = { method_id = 0 }
+setStatus = { method_id = 1, args = ["status"] }
+setHeader = { method_id = 2, args = ["key", "value"] }
+write = { method_id = 3, args = ["body"] }
+readBody = { method_id = 4 }
+getStatus = { method_id = 5 }
+getHeader = { method_id = 6, args = ["key"] }
+writeChunk = { method_id = 7, args = ["data"] }
+end = { method_id = 8 }
+fini = { method_id = 4294967295 }
+
+[HttpClientBox]
+type_id = 23
+
+[HttpClientBox.methods]
+birth = { method_id = 0 }
+get = { method_id = 1, args = ["url"], returns_result = true }
+post = { method_id = 2, args = ["url", "body"], returns_result = true }
+fini = { method_id = 4294967295 }
+
+[SocketServerBox]
+type_id = 30
+
+[SocketServerBox.methods]
+birth = { method_id = 0 }
+start = { method_id = 1, args = ["port"] }
+stop = { method_id = 2 }
+accept = { method_id = 3, returns_result = true }
+acceptTimeout = { method_id = 4, args = ["ms"], returns_result = true }
+fini = { method_id = 4294967295 }
+
+[SocketConnBox]
+type_id = 31
+
+[SocketConnBox.methods]
+birth = { method_id = 0 }
+send = { method_id = 1, args = ["data"] }
+recv = { method_id = 2 }
+close = { method_id = 3 }
+recvTimeout = { method_id = 4, args = ["ms"], returns_result = true }
+fini = { method_id = 4294967295 }
+
+[SocketClientBox]
+type_id = 32
+
+[SocketClientBox.methods]
+birth = { method_id = 0 }
+connect = { method_id = 1, args = ["host", "port"], returns_result = true }
+fini = { method_id = 4294967295 }
+"#;
+
+#[no_mangle]
+pub extern "C" fn nyash_plugin_manifest(result: *mut u8, result_len: *mut usize) -> i32 {
+    if result_len.is_null() { return E_INV_ARGS; }
+    let bytes = MANIFEST.as_bytes();
+    unsafe {
+        if result.is_null() || *result_len < bytes.len() {
+            *result_len = bytes.len();
+            return E_SHORT;
+        }
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), result, bytes.len());
+        *result_len = bytes.len();
+    }
+    OK
+}
+
 #[no_mangle]
 pub extern "C" fn nyash_plugin_invoke(
     type_id: u32,
@@ -187,7 +341,19 @@ pub extern "C" fn nyash_plugin_invoke(
 }
 
 unsafe fn server_invoke(m: u32, id: u32, args: *const u8, args_len: usize, res: *mut u8, res_len: *mut usize) -> i32 {
+    if m != M_BIRTH && m != M_FINI && !SERVER_INSTANCES.lock().unwrap().contains_key(&id) { return E_INV_HANDLE; }
     match m {
+        M_FINI => {
+            // Same as stop(), then forget the instance
+            let removed = SERVER_INSTANCES.lock().unwrap().remove(&id);
+            if let Some(s) = removed {
+                s.running.store(false, Ordering::SeqCst);
+                if let Some(h) = s.handle.lock().unwrap().take() { let _ = h.join(); }
+                let mut active = ACTIVE_SERVER_ID.lock().unwrap();
+                if *active == Some(id) { *active = None; }
+            }
+            OK
+        }
         M_BIRTH => {
             let id = SERVER_ID.fetch_add(1, Ordering::Relaxed);
             SERVER_INSTANCES.lock().unwrap().insert(id, ServerState {
@@ -220,21 +386,12 @@ unsafe fn server_invoke(m: u32, id: u32, args: *const u8, args_len: usize, res:
                     loop {
                         if !running.load(Ordering::SeqCst) { break; }
                         match listener.accept() {
-                                Ok((mut stream, _)) => {
-                                    // Parse minimal HTTP request (GET/POST)
-                                    let _ = stream.set_read_timeout(Some(Duration::from_millis(2000)));
-                                    if let Some((path, body, resp_hint)) = read_http_request(&mut stream) {
-                                        // Store stream for later respond()
-                                        let conn_id = SOCK_CONN_ID.fetch_add(1, Ordering::Relaxed);
-                                        SOCK_CONNS.lock().unwrap().insert(conn_id, SockConnState { stream: Mutex::new(stream) });
-
-                                        let req_id = REQUEST_ID.fetch_add(1, Ordering::Relaxed);
-                                        REQUESTS.lock().unwrap().insert(req_id, RequestState { path, body, response_id: resp_hint, server_conn_id: Some(conn_id), responded: false });
-                                        if let Some(h) = resp_hint { netlog!("http:accept linked resp_id hint={} for req_id={} conn_id={}", h, req_id, conn_id); }
-                                        pending.lock().unwrap().push_back(req_id);
-                                    } else {
-                                        // Malformed; drop connection
-                                    }
+                                Ok((stream, _)) => {
+                                    // One thread per connection: reads requests one after another (keep-alive)
+                                    let _ = stream.set_nonblocking(false);
+                                    let pending = pending.clone();
+                                    let running = running.clone();
+                                    std::thread::spawn(move || serve_http_conn(stream, pending, running));
                                 }
                                 Err(_) => {
                                     std::thread::sleep(Duration::from_millis(10));
@@ -261,7 +418,7 @@ unsafe fn server_invoke(m: u32, id: u32, args: *const u8, args_len: usize, res:
         M_SERVER_ACCEPT => {
             // wait up to ~5000ms for a request to arrive
             for _ in 0..1000 {
-                // Prefer TCP-backed requests (server_conn_id=Some) over stub ones
+                // Prefer TCP-backed requests (conn=Some) over stub ones
                 if let Some(req_id) = {
                     let mut map = SERVER_INSTANCES.lock().unwrap();
                     if let Some(s) = map.get_mut(&id) {
@@ -271,7 +428,7 @@ unsafe fn server_invoke(m: u32, id: u32, args: *const u8, args_len: usize, res:
                         for i in 0..q.len() {
                             if let Some(rid) = q.get(i).copied() {
                                 if let Some(rq) = REQUESTS.lock().unwrap().get(&rid) {
-                                    if rq.server_conn_id.is_some() { chosen = Some(i); break; }
+                                    if rq.conn.is_some() { chosen = Some(i); break; }
                                 }
                             }
                         }
@@ -291,10 +448,19 @@ unsafe fn server_invoke(m: u32, id: u32, args: *const u8, args_len: usize, res:
 }
 
 unsafe fn request_invoke(m: u32, id: u32, _args: *const u8, _args_len: usize, res: *mut u8, res_len: *mut usize) -> i32 {
+    if m != M_BIRTH && m != M_FINI && !REQUESTS.lock().unwrap().contains_key(&id) { return E_INV_HANDLE; }
     match m {
+        M_FINI => {
+            // Hand an undrained body back to the connection thread so keep-alive stays aligned
+            let removed = REQUESTS.lock().unwrap().remove(&id);
+            if let Some(RequestState { conn: Some(conn), body_reader: Some(reader), .. }) = removed {
+                *conn.leftover.lock().unwrap() = Some(reader);
+            }
+            OK
+        }
         M_BIRTH => {
             let id = REQUEST_ID.fetch_add(1, Ordering::Relaxed);
-            REQUESTS.lock().unwrap().insert(id, RequestState { path: String::new(), body: vec![], response_id: None, server_conn_id: None, responded: false });
+            REQUESTS.lock().unwrap().insert(id, RequestState { path: String::new(), body: vec![], response_id: None, conn: None, body_reader: None, body_pos: 0, keep_alive: false, responded: false });
             write_u32(id, res, res_len)
         }
         M_REQ_PATH => {
@@ -303,136 +469,111 @@ unsafe fn request_invoke(m: u32, id: u32, _args: *const u8, _args_len: usize, re
             } else { E_INV_HANDLE }
         }
         M_REQ_READ_BODY => {
-            if let Some(rq) = REQUESTS.lock().unwrap().get(&id) {
-                write_tlv_bytes(&rq.body, res, res_len)
+            // Drain the rest of a streamed body, then return everything not consumed by readChunk()
+            let Some((conn, reader)) = take_body_reader(id) else { return E_INV_HANDLE };
+            let rest = match (conn, reader) {
+                (Some(conn), Some(mut reader)) => reader.read_all_from(&conn),
+                _ => vec![],
+            };
+            if let Some(rq) = REQUESTS.lock().unwrap().get_mut(&id) {
+                rq.body.extend_from_slice(&rest);
+                netlog!("Request.readBody: req_id={} body_len={}", id, rq.body.len() - rq.body_pos);
+                write_tlv_bytes(&rq.body[rq.body_pos..], res, res_len)
             } else { E_INV_HANDLE }
         }
+        M_REQ_READ_CHUNK => {
+            let Some((conn, reader)) = take_body_reader(id) else { return E_INV_HANDLE };
+            match (conn, reader) {
+                (Some(conn), Some(mut reader)) => {
+                    let data = reader.read_some_from(&conn, READ_CHUNK_MAX);
+                    if let Some(rq) = REQUESTS.lock().unwrap().get_mut(&id) { rq.body_reader = Some(reader); }
+                    netlog!("Request.readChunk: req_id={} n={}", id, data.len());
+                    write_tlv_bytes(&data, res, res_len)
+                }
+                _ => {
+                    // Buffered body (birthed request or already drained by readBody)
+                    let mut map = REQUESTS.lock().unwrap();
+                    let Some(rq) = map.get_mut(&id) else { return E_INV_HANDLE };
+                    let end = rq.body.len().min(rq.body_pos + READ_CHUNK_MAX);
+                    let data = rq.body[rq.body_pos..end].to_vec();
+                    rq.body_pos = end;
+                    write_tlv_bytes(&data, res, res_len)
+                }
+            }
+        }
         M_REQ_RESPOND => {
             // args: TLV Handle(Response)
             let (t, provided_resp_id) = tlv_parse_handle(slice(_args, _args_len)).map_err(|_| ()).or(Err(())).unwrap_or((0,0));
             if t != T_RESPONSE { return E_INV_ARGS; }
-            // Acquire request
-            let mut rq_map = REQUESTS.lock().unwrap();
-            if let Some(rq) = rq_map.get_mut(&id) {
-                netlog!(
-                    "Request.respond: req_id={} provided_resp_id={} server_conn_id={:?} response_id_hint={:?}",
-                    id, provided_resp_id, rq.server_conn_id, rq.response_id
-                );
-                // If request is backed by a real socket, write HTTP over that socket
-                if let Some(conn_id) = rq.server_conn_id {
-                    drop(rq_map);
-                    // Read response content from provided response handle
-                    let (status, headers, body) = {
-                        let resp_map = RESPONSES.lock().unwrap();
-                        if let Some(src) = resp_map.get(&provided_resp_id) {
-                            netlog!("Request.respond: Reading response id={}, status={}, body_len={}", provided_resp_id, src.status, src.body.len());
-                            (src.status, src.headers.clone(), src.body.clone())
-                        } else { 
-                            netlog!("Request.respond: Response id={} not found!", provided_resp_id);
-                            return E_INV_HANDLE 
-                        }
-                    };
-                    // Build minimal HTTP/1.1 response
-                    let reason = match status { 200 => "OK", 201 => "Created", 204 => "No Content", 400 => "Bad Request", 404 => "Not Found", 500 => "Internal Server Error", _ => "OK" };
-                    let mut buf = Vec::new();
-                    buf.extend_from_slice(format!("HTTP/1.1 {} {}\r\n", status, reason).as_bytes());
-                    let mut has_len = false;
-                    for (k,v) in &headers {
-                        if k.eq_ignore_ascii_case("Content-Length") { has_len = true; }
-                        buf.extend_from_slice(format!("{}: {}\r\n", k, v).as_bytes());
-                    }
-                    if !has_len { buf.extend_from_slice(format!("Content-Length: {}\r\n", body.len()).as_bytes()); }
-                    buf.extend_from_slice(b"Connection: close\r\n");
-                    buf.extend_from_slice(b"\r\n");
-                    buf.extend_from_slice(&body);
-                    // Write and close
-                    netlog!("Request.respond: Sending HTTP response, buf_len={}", buf.len());
-                    if let Some(conn) = SOCK_CONNS.lock().unwrap().remove(&conn_id) {
-                        if let Ok(mut s) = conn.stream.lock() { 
-                            let _ = s.write_all(&buf); 
-                            let _ = s.flush(); 
-                            netlog!("Request.respond: HTTP response sent to socket conn_id={}", conn_id);
-                        }
-                    } else {
-                        netlog!("Request.respond: Socket conn_id={} not found!", conn_id);
-                    }
-                    // Also mirror to paired client Response handle to avoid race on immediate read
-                    if let Some(target_id) = {
-                        let rq_map2 = REQUESTS.lock().unwrap();
-                        rq_map2.get(&id).and_then(|rq2| rq2.response_id)
-                    } {
-                        let mut resp_map = RESPONSES.lock().unwrap();
-                        let dst = resp_map.entry(target_id).or_insert(ResponseState { status: 200, headers: HashMap::new(), body: vec![], client_conn_id: None, parsed: true });
-                        dst.status = status;
-                        dst.headers = headers.clone();
-                        dst.body = body.clone();
-                        netlog!("Request.respond: mirrored client handle id={} body_len={} headers={} status={}", target_id, dst.body.len(), dst.headers.len(), dst.status);
-                    }
-                    // mark responded
-                    {
-                        let mut rq_map3 = REQUESTS.lock().unwrap();
-                        if let Some(rq3) = rq_map3.get_mut(&id) { rq3.responded = true; }
-                    }
-                    return write_tlv_void(res, res_len);
+            let backed = match REQUESTS.lock().unwrap().get(&id) {
+                Some(rq) => {
+                    netlog!(
+                        "Request.respond: req_id={} provided_resp_id={} tcp={} response_id_hint={:?}",
+                        id, provided_resp_id, rq.conn.is_some(), rq.response_id
+                    );
+                    rq.conn.is_some()
                 }
-
+                None => return E_INV_HANDLE,
+            };
+            // If request is backed by a real socket, write HTTP over that socket
+            let target_req_id = if backed { Some(id) } else {
                 // Not backed by a socket: attempt reroute to last accepted or latest TCP-backed unresponded request
-                drop(rq_map);
-                let candidate_req = {
-                    if let Some(last_id) = *LAST_ACCEPTED_REQ.lock().unwrap() {
-                        if let Some(r) = REQUESTS.lock().unwrap().get(&last_id) {
-                            if r.server_conn_id.is_some() && !r.responded { Some(last_id) } else { None }
-                        } else { None }
-                    } else { None }
-                }.or_else(|| {
-                    REQUESTS.lock().unwrap().iter()
-                        .filter_map(|(rid, rqs)| if rqs.server_conn_id.is_some() && !rqs.responded { Some(*rid) } else { None })
-                        .max()
-                });
-                if let Some(target_req_id) = candidate_req {
-                    let (conn_id_alt, resp_hint_alt) = {
-                        let map = REQUESTS.lock().unwrap();
-                        let r = map.get(&target_req_id).unwrap();
-                        (r.server_conn_id.unwrap(), r.response_id)
-                    };
-                    let (status, headers, body) = {
-                        let resp_map = RESPONSES.lock().unwrap();
-                        if let Some(src) = resp_map.get(&provided_resp_id) { (src.status, src.headers.clone(), src.body.clone()) } else { return E_INV_HANDLE }
-                    };
-                    let reason = match status { 200 => "OK", 201 => "Created", 204 => "No Content", 400 => "Bad Request", 404 => "Not Found", 500 => "Internal Server Error", _ => "OK" };
-                    let mut buf = Vec::new();
-                    buf.extend_from_slice(format!("HTTP/1.1 {} {}\r\n", status, reason).as_bytes());
-                    let mut has_len = false;
-                    for (k,v) in &headers { if k.eq_ignore_ascii_case("Content-Length") { has_len = true; } buf.extend_from_slice(format!("{}: {}\r\n", k, v).as_bytes()); }
-                    if !has_len { buf.extend_from_slice(format!("Content-Length: {}\r\n", body.len()).as_bytes()); }
-                    buf.extend_from_slice(b"Connection: close\r\n\r\n"); buf.extend_from_slice(&body);
-                    netlog!("Request.respond: reroute TCP send via req_id={} conn_id={}", target_req_id, conn_id_alt);
-                    if let Some(conn) = SOCK_CONNS.lock().unwrap().remove(&conn_id_alt) {
-                        if let Ok(mut s) = conn.stream.lock() { let _ = s.write_all(&buf); let _ = s.flush(); }
-                    }
-                    if let Some(target_id) = resp_hint_alt {
-                        let mut resp_map = RESPONSES.lock().unwrap();
-                        let dst = resp_map.entry(target_id).or_insert(ResponseState { status: 200, headers: HashMap::new(), body: vec![], client_conn_id: None, parsed: true });
-                        dst.status = status; dst.headers = headers.clone(); dst.body = body.clone();
-                        netlog!("Request.respond: mirrored client handle id={} body_len={} headers={} status={}", target_id, dst.body.len(), dst.headers.len(), dst.status);
-                    }
-                    if let Some(rq4) = REQUESTS.lock().unwrap().get_mut(&target_req_id) { rq4.responded = true; }
-                    return write_tlv_void(res, res_len);
-                }
+                let last = *LAST_ACCEPTED_REQ.lock().unwrap();
+                let map = REQUESTS.lock().unwrap();
+                last.filter(|lid| map.get(lid).map(|r| r.conn.is_some() && !r.responded).unwrap_or(false))
+                    .or_else(|| map.iter()
+                        .filter_map(|(rid, rqs)| if rqs.conn.is_some() && !rqs.responded { Some(*rid) } else { None })
+                        .max())
+            };
+            let Some(target_req_id) = target_req_id else {
                 netlog!("Request.respond: no suitable TCP-backed request found for reroute; invalid handle");
                 return E_INV_HANDLE;
+            };
+            if target_req_id != id { netlog!("Request.respond: reroute TCP send via req_id={}", target_req_id); }
+            let (conn, keep_alive, resp_hint) = {
+                let map = REQUESTS.lock().unwrap();
+                let r = map.get(&target_req_id).unwrap();
+                (r.conn.clone().unwrap(), r.keep_alive, r.response_id)
+            };
+            let Some(sent) = send_response(&conn, keep_alive, provided_resp_id) else {
+                netlog!("Request.respond: Response id={} not found!", provided_resp_id);
+                return E_INV_HANDLE;
+            };
+            // Also mirror to paired client Response handle to avoid race on immediate read
+            if let (Some((status, headers, body)), Some(target_id)) = (sent, resp_hint) {
+                let mut resp_map = RESPONSES.lock().unwrap();
+                let dst = resp_map.entry(target_id).or_insert_with(|| ResponseState::new(true));
+                dst.status = status;
+                dst.headers = headers;
+                dst.body = body;
+                netlog!("Request.respond: mirrored client handle id={} body_len={} headers={} status={}", target_id, dst.body.len(), dst.headers.len(), dst.status);
             }
-            E_INV_HANDLE
+            if let Some(rq) = REQUESTS.lock().unwrap().get_mut(&target_req_id) { rq.responded = true; }
+            write_tlv_void(res, res_len)
         }
         _ => E_INV_METHOD,
     }
 }
 
+/// Takes the body reader out of a request so the socket is read without holding REQUESTS
+fn take_body_reader(id: u32) -> Option<(Option<Arc<HttpConn>>, Option<BodyReader>)> {
+    let mut map = REQUESTS.lock().unwrap();
+    let rq = map.get_mut(&id)?;
+    Some((rq.conn.clone(), rq.body_reader.take()))
+}
+
 unsafe fn response_invoke(m: u32, id: u32, args: *const u8, args_len: usize, res: *mut u8, res_len: *mut usize) -> i32 {
+    if m != M_BIRTH && m != M_FINI && !RESPONSES.lock().unwrap().contains_key(&id) { return E_INV_HANDLE; }
     match m {
+        M_FINI => {
+            // A streamed response that never saw end() closes its connection
+            let removed = RESPONSES.lock().unwrap().remove(&id);
+            if let Some(stream) = removed.and_then(|rp| rp.stream) { stream.conn.finish(false); }
+            OK
+        }
         M_BIRTH => {
             let id = RESPONSE_ID.fetch_add(1, Ordering::Relaxed);
-            RESPONSES.lock().unwrap().insert(id, ResponseState { status: 200, headers: HashMap::new(), body: vec![], client_conn_id: None, parsed: false });
+            RESPONSES.lock().unwrap().insert(id, ResponseState::new(false));
             netlog!("Response.birth: new id={}", id);
             write_u32(id, res, res_len)
         }
@@ -448,64 +589,64 @@ unsafe fn response_invoke(m: u32, id: u32, args: *const u8, args_len: usize, res
             }
             E_INV_ARGS
         }
-        M_RESP_WRITE => {
+        M_RESP_WRITE | M_RESP_WRITE_CHUNK => {
             // Accept String or Bytes
             let bytes = tlv_parse_bytes(slice(args, args_len)).unwrap_or_default();
-            netlog!("HttpResponse.write: id={} bytes_len={}", id, bytes.len());
-            if let Some(rp) = RESPONSES.lock().unwrap().get_mut(&id) { 
-                rp.body.extend_from_slice(&bytes); 
-                netlog!("HttpResponse.write: body now has {} bytes", rp.body.len());
+            netlog!("HttpResponse.write: id={} bytes_len={} chunk={}", id, bytes.len(), m == M_RESP_WRITE_CHUNK);
+            let stream = {
+                let mut map = RESPONSES.lock().unwrap();
+                let Some(rp) = map.get_mut(&id) else { return E_INV_HANDLE };
+                if m == M_RESP_WRITE_CHUNK { rp.chunked = true; }
+                match &rp.stream {
+                    // Already streaming: goes straight to the socket
+                    Some(s) => Some(s.conn.clone()),
+                    None => {
+                        rp.body.extend_from_slice(&bytes);
+                        netlog!("HttpResponse.write: body now has {} bytes", rp.body.len());
+                        None
+                    }
+                }
+            };
+            if let Some(conn) = stream {
+                if !bytes.is_empty() && conn.write(&encode_chunk(&bytes)).is_err() {
+                    netlog!("HttpResponse.writeChunk: id={} peer closed", id);
+                    return E_ERR;
+                }
+            }
+            write_tlv_void(res, res_len)
+        }
+        M_RESP_END => {
+            let stream = {
+                let mut map = RESPONSES.lock().unwrap();
+                let Some(rp) = map.get_mut(&id) else { return E_INV_HANDLE };
+                rp.ended = true;
+                rp.stream.take()
+            };
+            if let Some(stream) = stream {
+                let ok = stream.conn.write(b"0\r\n\r\n").is_ok();
+                stream.conn.finish(stream.keep_alive && ok);
+                netlog!("HttpResponse.end: id={} keep_alive={}", id, stream.keep_alive && ok);
             }
             write_tlv_void(res, res_len)
         }
         M_RESP_READ_BODY => {
             netlog!("HttpResponse.readBody: enter id={}", id);
-            // If bound to a client connection, lazily read and parse (with short retries)
-            for _ in 0..50 {
-                let need_parse = {
-                    if let Some(rp) = RESPONSES.lock().unwrap().get(&id) {
-                        rp.client_conn_id
-                    } else { return E_INV_HANDLE; }
-                };
-                if let Some(conn_id) = need_parse {
-                    parse_client_response_into(id, conn_id);
-                    std::thread::sleep(Duration::from_millis(5));
-                } else { break; }
-            }
+            // If bound to a client connection, lazily read and parse
+            if !ensure_client_response(id) { return E_INV_HANDLE; }
             if let Some(rp) = RESPONSES.lock().unwrap().get(&id) { 
                 netlog!("HttpResponse.readBody: id={} body_len={}", id, rp.body.len());
                 write_tlv_bytes(&rp.body, res, res_len) 
             } else { E_INV_HANDLE }
         }
         M_RESP_GET_STATUS => {
-            for _ in 0..50 {
-                let need_parse = {
-                    if let Some(rp) = RESPONSES.lock().unwrap().get(&id) {
-                        rp.client_conn_id
-                    } else { return E_INV_HANDLE; }
-                };
-                if let Some(conn_id) = need_parse {
-                    parse_client_response_into(id, conn_id);
-                    std::thread::sleep(Duration::from_millis(5));
-                } else { break; }
-            }
+            if !ensure_client_response(id) { return E_INV_HANDLE; }
             if let Some(rp) = RESPONSES.lock().unwrap().get(&id) { write_tlv_i32(rp.status, res, res_len) } else { E_INV_HANDLE }
         }
         M_RESP_GET_HEADER => {
             if let Ok(name) = tlv_parse_string(slice(args, args_len)) {
-                for _ in 0..50 {
-                    let need_parse = {
-                        if let Some(rp) = RESPONSES.lock().unwrap().get(&id) {
-                            rp.client_conn_id
-                        } else { return E_INV_HANDLE; }
-                    };
-                    if let Some(conn_id) = need_parse {
-                        parse_client_response_into(id, conn_id);
-                        std::thread::sleep(Duration::from_millis(5));
-                    } else { break; }
-                }
+                if !ensure_client_response(id) { return E_INV_HANDLE; }
                 if let Some(rp) = RESPONSES.lock().unwrap().get(&id) {
-                    let v = rp.headers.get(&name).cloned().unwrap_or_default();
+                    let v = header_value(&rp.headers, &name).unwrap_or_default().to_string();
                     return write_tlv_string(&v, res, res_len);
                 } else { return E_INV_HANDLE; }
             }
@@ -515,53 +656,39 @@ unsafe fn response_invoke(m: u32, id: u32, args: *const u8, args_len: usize, res
     }
 }
 
+impl ResponseState {
+    fn new(parsed: bool) -> Self {
+        ResponseState { status: 200, headers: HashMap::new(), body: vec![], client_conn: None, parsed, chunked: false, ended: false, stream: None }
+    }
+}
+
+/// Reads a client response on first access; false if the handle is unknown
+fn ensure_client_response(id: u32) -> bool {
+    let conn = match RESPONSES.lock().unwrap().get_mut(&id) {
+        Some(rp) => rp.client_conn.take(),
+        None => return false,
+    };
+    if let Some(conn) = conn { parse_client_response_into(id, conn); }
+    true
+}
+
 unsafe fn client_invoke(m: u32, id: u32, args: *const u8, args_len: usize, res: *mut u8, res_len: *mut usize) -> i32 {
+    if m != M_BIRTH && m != M_FINI && !CLIENTS.lock().unwrap().contains_key(&id) { return E_INV_HANDLE; }
     match m {
+        M_FINI => {
+            // Drops the client's idle keep-alive connections
+            CLIENTS.lock().unwrap().remove(&id);
+            OK
+        }
         M_BIRTH => {
             let id = CLIENT_ID.fetch_add(1, Ordering::Relaxed);
-            CLIENTS.lock().unwrap().insert(id, ClientState);
+            CLIENTS.lock().unwrap().insert(id, ClientState { idle: HashMap::new() });
             write_u32(id, res, res_len)
         }
         M_CLIENT_GET => {
             // args: TLV String(url)
             let url = tlv_parse_string(slice(args, args_len)).unwrap_or_default();
-            let port = parse_port(&url).unwrap_or(80);
-            let host = parse_host(&url).unwrap_or_else(|| "127.0.0.1".to_string());
-            let path = parse_path(&url);
-            // Create client response handle first, so we can include it in header
-            let resp_id = RESPONSE_ID.fetch_add(1, Ordering::Relaxed);
-            let (_h, _p, req_bytes) = build_http_request("GET", &url, None, resp_id);
-            // Try TCP connect (best effort)
-            let mut tcp_ok = false;
-            if let Ok(mut stream) = TcpStream::connect(format!("{}:{}", host, port)) {
-                let _ = stream.write_all(&req_bytes);
-                let _ = stream.flush();
-                let conn_id = SOCK_CONN_ID.fetch_add(1, Ordering::Relaxed);
-                SOCK_CONNS.lock().unwrap().insert(conn_id, SockConnState { stream: Mutex::new(stream) });
-                // Map to server_id by port if available
-                let server_id_for_port = {
-                    let servers = SERVER_INSTANCES.lock().unwrap();
-                    servers.iter().find(|(_, s)| s.port == port).map(|(sid, _)| *sid)
-                };
-                RESPONSES.lock().unwrap().insert(resp_id, ResponseState { status: 0, headers: HashMap::new(), body: vec![], client_conn_id: Some(conn_id), parsed: false });
-                tcp_ok = true;
-                netlog!("client.get: url={} resp_id={} tcp_ok=true conn_id={}", url, resp_id, conn_id);
-            } else {
-                let server_id_for_port = {
-                    let servers = SERVER_INSTANCES.lock().unwrap();
-                    servers.iter().find(|(_, s)| s.port == port).map(|(sid, _)| *sid)
-                };
-                RESPONSES.lock().unwrap().insert(resp_id, ResponseState { status: 0, headers: HashMap::new(), body: vec![], client_conn_id: None, parsed: false });
-                netlog!("client.get: url={} resp_id={} tcp_ok=false", url, resp_id);
-            }
-            // No stub enqueue in TCP-only design
-            if tcp_ok {
-                write_tlv_handle(T_RESPONSE, resp_id, res, res_len)
-            } else {
-                // Encode error string; loader interprets returns_result=true methods' string payload as Err
-                let msg = format!("connect failed for {}:{}{}", host, port, if path.is_empty() { "" } else { &path });
-                write_tlv_string(&msg, res, res_len)
-            }
+            client_send(id, "GET", &url, None, res, res_len)
         }
         M_CLIENT_POST => {
             // args: TLV String(url), Bytes body
@@ -575,46 +702,66 @@ unsafe fn client_invoke(m: u32, id: u32, args: *const u8, args_len: usize, res:
             let (t2, s2, p2) = tlv_parse_entry_hdr(data, pos).map_err(|_| ()).or(Err(())).unwrap_or((0,0,0));
             if t2 != 6 && t2 != 7 { return E_INV_ARGS; }
             let body = data[p2..p2+s2].to_vec();
-            let port = parse_port(&url).unwrap_or(80);
-            let host = parse_host(&url).unwrap_or_else(|| "127.0.0.1".to_string());
-            let path = parse_path(&url);
-            let body_len = body.len();
-            // Create client response handle
-            let resp_id = RESPONSE_ID.fetch_add(1, Ordering::Relaxed);
-            let (_h, _p, req_bytes) = build_http_request("POST", &url, Some(&body), resp_id);
-            let mut tcp_ok = false;
-            if let Ok(mut stream) = TcpStream::connect(format!("{}:{}", host, port)) {
-                let _ = stream.write_all(&req_bytes);
-                let _ = stream.flush();
-                let conn_id = SOCK_CONN_ID.fetch_add(1, Ordering::Relaxed);
-                SOCK_CONNS.lock().unwrap().insert(conn_id, SockConnState { stream: Mutex::new(stream) });
-                let server_id_for_port = {
-                    let servers = SERVER_INSTANCES.lock().unwrap();
-                    servers.iter().find(|(_, s)| s.port == port).map(|(sid, _)| *sid)
-                };
-                RESPONSES.lock().unwrap().insert(resp_id, ResponseState { status: 0, headers: HashMap::new(), body: vec![], client_conn_id: Some(conn_id), parsed: false });
-                tcp_ok = true;
-                netlog!("client.post: url={} resp_id={} tcp_ok=true conn_id={} body_len={}", url, resp_id, conn_id, body.len());
-            } else {
-                let server_id_for_port = {
-                    let servers = SERVER_INSTANCES.lock().unwrap();
-                    servers.iter().find(|(_, s)| s.port == port).map(|(sid, _)| *sid)
-                };
-                RESPONSES.lock().unwrap().insert(resp_id, ResponseState { status: 0, headers: HashMap::new(), body: vec![], client_conn_id: None, parsed: false });
-                netlog!("client.post: url={} resp_id={} tcp_ok=false body_len={}", url, resp_id, body.len());
-            }
-            // No stub enqueue in TCP-only design
-            if tcp_ok {
-                write_tlv_handle(T_RESPONSE, resp_id, res, res_len)
-            } else {
-                let msg = format!("connect failed for {}:{}{} (body_len={})", host, port, if path.is_empty() { "" } else { &path }, body_len);
-                write_tlv_string(&msg, res, res_len)
-            }
+            client_send(id, "POST", &url, Some(&body), res, res_len)
         }
         _ => E_INV_METHOD,
     }
 }
 
+/// Sends a request on a pooled keep-alive connection (or a new one) and returns Handle(Response)
+fn client_send(client_id: u32, method: &str, url: &str, body: Option<&[u8]>, res: *mut u8, res_len: *mut usize) -> i32 {
+    let port = parse_port(url).unwrap_or(80);
+    let host = parse_host(url).unwrap_or_else(|| "127.0.0.1".to_string());
+    let path = parse_path(url);
+    let pool_key = format!("{}:{}", host, port);
+    // Create client response handle first, so we can include it in header
+    let resp_id = RESPONSE_ID.fetch_add(1, Ordering::Relaxed);
+    let (_h, _p, req_bytes) = build_http_request(method, url, body, resp_id);
+    let send = |mut stream: TcpStream| -> Option<TcpStream> {
+        stream.write_all(&req_bytes).ok()?;
+        stream.flush().ok()?;
+        Some(stream)
+    };
+    // An idle connection the server already closed fails here; fall back to a fresh one
+    let pooled = checkout_idle(client_id, &pool_key).and_then(send);
+    let reused = pooled.is_some();
+    let stream = pooled.or_else(|| TcpStream::connect(&pool_key).ok().and_then(send));
+    let mut resp = ResponseState::new(false);
+    resp.status = 0;
+    let tcp_ok = stream.is_some();
+    if let Some(stream) = stream {
+        resp.client_conn = Some(ClientConn { stream, client_id, pool_key });
+    }
+    RESPONSES.lock().unwrap().insert(resp_id, resp);
+    netlog!("client.{}: url={} resp_id={} tcp_ok={} reused={} body_len={}", method.to_lowercase(), url, resp_id, tcp_ok, reused, body.map(|b| b.len()).unwrap_or(0));
+    // No stub enqueue in TCP-only design
+    if tcp_ok {
+        write_tlv_handle(T_RESPONSE, resp_id, res, res_len)
+    } else {
+        // A string payload is an Ok value for returns_result methods; failures must be a negative rc
+        RESPONSES.lock().unwrap().remove(&resp_id);
+        let msg = match body {
+            Some(b) => format!("connect failed for {}:{}{} (body_len={})", host, port, if path.is_empty() { "" } else { &path }, b.len()),
+            None => format!("connect failed for {}:{}{}", host, port, if path.is_empty() { "" } else { &path }),
+        };
+        netlog!("client.{}: {}", method.to_lowercase(), msg);
+        E_ERR
+    }
+}
+
+/// Pops an idle keep-alive connection that the server has not closed yet
+fn checkout_idle(client_id: u32, key: &str) -> Option<TcpStream> {
+    let mut clients = CLIENTS.lock().unwrap();
+    let idle = clients.get_mut(&client_id)?.idle.get_mut(key)?;
+    while let Some(stream) = idle.pop() {
+        let _ = stream.set_nonblocking(true);
+        let alive = matches!(stream.peek(&mut [0u8; 1]), Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock);
+        let _ = stream.set_nonblocking(false);
+        if alive { return Some(stream); }
+    }
+    None
+}
+
 fn parse_path(url: &str) -> String {
     // Robust-ish path extraction:
     // - http://host:port/path -> "/path"
@@ -757,119 +904,335 @@ fn build_http_request(method: &str, url: &str, body: Option<&[u8]>, resp_id: u32
         Some(b) => {
             buf.extend_from_slice(format!("Content-Length: {}\r\n", b.len()).as_bytes());
             buf.extend_from_slice(b"Content-Type: application/octet-stream\r\n");
-            buf.extend_from_slice(b"Connection: close\r\n\r\n");
+            buf.extend_from_slice(b"Connection: keep-alive\r\n\r\n");
             buf.extend_from_slice(b);
         }
         None => {
-            buf.extend_from_slice(b"Connection: close\r\n\r\n");
+            buf.extend_from_slice(b"Connection: keep-alive\r\n\r\n");
         }
     }
     (host, path, buf)
 }
 
-fn read_http_request(stream: &mut TcpStream) -> Option<(String, Vec<u8>, Option<u32>)> {
-    let mut buf = Vec::with_capacity(1024);
-    let mut tmp = [0u8; 1024];
-    // Read until we see CRLFCRLF
-    let header_end;
+fn header_value<'a>(headers: &'a HashMap<String, String>, name: &str) -> Option<&'a str> {
+    headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+}
+
+fn header_has_token(headers: &HashMap<String, String>, name: &str, token: &str) -> bool {
+    header_value(headers, name)
+        .map(|v| v.split(',').any(|t| t.trim().eq_ignore_ascii_case(token)))
+        .unwrap_or(false)
+}
+
+/// Reads the start line and headers (up to the blank line); None on EOF, timeout or oversize
+fn read_head<R: BufRead>(r: &mut R) -> Option<(String, HashMap<String, String>)> {
+    let mut start = String::new();
+    let mut headers = HashMap::new();
+    let mut total = 0;
     loop {
-        match stream.read(&mut tmp) {
-            Ok(0) => return None, // EOF without finding header end
-            Ok(n) => {
-                buf.extend_from_slice(&tmp[..n]);
-                if let Some(pos) = find_header_end(&buf) { header_end = pos; break; }
-                if buf.len() > 64 * 1024 { return None; }
-            }
-            Err(_) => return None,
+        let mut line = Vec::new();
+        match r.read_until(b'\n', &mut line) {
+            Ok(0) | Err(_) => return None,
+            Ok(n) => total += n,
+        }
+        if total > MAX_HEAD_BYTES { return None; }
+        let line = String::from_utf8_lossy(&line);
+        let line = line.trim_end_matches(['\r', '\n']);
+        if start.is_empty() {
+            // Tolerate stray CRLF between pipelined requests
+            if !line.is_empty() { start = line.to_string(); }
+            continue;
+        }
+        if line.is_empty() { return Some((start, headers)); }
+        if let Some((k, v)) = line.split_once(':') {
+            headers.insert(k.trim().to_string(), v.trim().to_string());
         }
     }
-    // Parse request line and headers
-    let header = &buf[..header_end];
-    let after = &buf[header_end+4..];
-    let header_str = String::from_utf8_lossy(header);
-    let mut lines = header_str.split("\r\n");
-    let request_line = lines.next().unwrap_or("");
+}
+
+/// Request head parsed by the connection thread; the body stays on the socket
+struct RequestHead {
+    path: String,
+    keep_alive: bool,
+    resp_hint: Option<u32>,
+    body: BodyReader,
+}
+
+fn read_request_head<R: BufRead>(r: &mut R) -> Option<RequestHead> {
+    let (request_line, headers) = read_head(r)?;
     let mut parts = request_line.split_whitespace();
-    let method = parts.next().unwrap_or("");
+    let _method = parts.next()?;
     let path = parts.next().unwrap_or("/").to_string();
-    let mut content_length: usize = 0;
-    let mut resp_handle_id: Option<u32> = None;
-    for line in lines {
-        if let Some((k,v)) = line.split_once(':') {
-            if k.eq_ignore_ascii_case("Content-Length") { content_length = v.trim().parse().unwrap_or(0); }
-            if k.eq_ignore_ascii_case("X-Nyash-Resp-Id") {
-                resp_handle_id = v.trim().parse::<u32>().ok();
+    let version = parts.next().unwrap_or("HTTP/1.0");
+    // HTTP/1.1 is persistent unless told otherwise; HTTP/1.0 only on request
+    let keep_alive = if version == "HTTP/1.1" {
+        !header_has_token(&headers, "Connection", "close")
+    } else {
+        header_has_token(&headers, "Connection", "keep-alive")
+    };
+    let framing = if header_has_token(&headers, "Transfer-Encoding", "chunked") {
+        Framing::Chunked { left: 0, done: false }
+    } else {
+        Framing::Length(header_value(&headers, "Content-Length").and_then(|v| v.parse().ok()).unwrap_or(0))
+    };
+    let expect_continue = header_value(&headers, "Expect").map(|v| v.eq_ignore_ascii_case("100-continue")).unwrap_or(false);
+    let resp_hint = header_value(&headers, "X-Nyash-Resp-Id").and_then(|v| v.parse::<u32>().ok());
+    Some(RequestHead { path, keep_alive, resp_hint, body: BodyReader { framing, expect_continue } })
+}
+
+/// Connection thread: queues each request for accept() and waits for its response before reading the next
+fn serve_http_conn(stream: TcpStream, pending: Arc<Mutex<VecDeque<u32>>>, running: Arc<AtomicBool>) {
+    let _ = stream.set_read_timeout(Some(KEEP_ALIVE_IDLE));
+    let writer = match stream.try_clone() { Ok(w) => w, Err(_) => return };
+    let conn = Arc::new(HttpConn { reader: Mutex::new(BufReader::new(stream)), writer: Mutex::new(writer), done: Mutex::new(None), leftover: Mutex::new(None) });
+    loop {
+        let head = read_request_head(&mut *conn.reader.lock().unwrap());
+        let Some(head) = head else { break };
+        let (tx, rx) = mpsc::channel();
+        *conn.done.lock().unwrap() = Some(tx);
+        let req_id = REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+        REQUESTS.lock().unwrap().insert(req_id, RequestState {
+            path: head.path, body: vec![], response_id: head.resp_hint, conn: Some(conn.clone()),
+            body_reader: Some(head.body), body_pos: 0, keep_alive: head.keep_alive, responded: false,
+        });
+        if let Some(h) = head.resp_hint { netlog!("http:accept linked resp_id hint={} for req_id={}", h, req_id); }
+        pending.lock().unwrap().push_back(req_id);
+
+        // Wait until respond()/end() has written the whole response
+        let keep_alive = loop {
+            match rx.recv_timeout(Duration::from_millis(200)) {
+                Ok(keep) => break keep,
+                Err(mpsc::RecvTimeoutError::Timeout) if running.load(Ordering::SeqCst) => continue,
+                Err(_) => break false,
             }
+        };
+        if !keep_alive { break; }
+        // Skip body bytes the handler did not read, so the next request starts on a boundary
+        let rest = REQUESTS.lock().unwrap().get_mut(&req_id).and_then(|rq| rq.body_reader.take())
+            .or_else(|| conn.leftover.lock().unwrap().take());
+        if let Some(mut rest) = rest {
+            rest.expect_continue = false;
+            let mut r = conn.reader.lock().unwrap();
+            while !rest.read_some(&mut *r, READ_CHUNK_MAX).is_empty() {}
+            if !rest.is_complete() { break; }
         }
     }
-    let mut body = after.to_vec();
-    while body.len() < content_length {
-        match stream.read(&mut tmp) { Ok(0) => break, Ok(n) => body.extend_from_slice(&tmp[..n]), Err(_) => break }
-    }
-    if method == "GET" || method == "POST" { Some((path, body, resp_handle_id)) } else { None }
+    let _ = conn.writer.lock().unwrap().shutdown(std::net::Shutdown::Both);
 }
 
-fn find_header_end(buf: &[u8]) -> Option<usize> {
-    if buf.len() < 4 { return None; }
-    for i in 0..=buf.len()-4 { if &buf[i..i+4] == b"\r\n\r\n" { return Some(i); } }
-    None
+impl HttpConn {
+    fn write(&self, bytes: &[u8]) -> std::io::Result<()> {
+        let mut w = self.writer.lock().unwrap();
+        w.write_all(bytes)?;
+        w.flush()
+    }
+
+    /// Lets the connection thread move on to the next request (or close)
+    fn finish(&self, keep_alive: bool) {
+        if let Some(tx) = self.done.lock().unwrap().take() { let _ = tx.send(keep_alive); }
+    }
 }
 
-fn parse_client_response_into(resp_id: u32, conn_id: u32) {
-    // Read full response from socket and fill ResponseState
-    let mut status: i32 = 200;
-    let mut headers: HashMap<String, String> = HashMap::new();
-    let mut body: Vec<u8> = Vec::new();
-    // Keep the connection until parsing succeeds; do not remove up front
-    let mut should_remove = false;
-    if let Ok(mut map) = SOCK_CONNS.lock() {
-        if let Some(conn) = map.get(&conn_id) {
-            if let Ok(mut s) = conn.stream.lock() {
-                let _ = s.set_read_timeout(Some(Duration::from_millis(4000)));
-                let mut buf = Vec::with_capacity(2048);
-                let mut tmp = [0u8; 2048];
-                loop {
-                    match s.read(&mut tmp) {
-                        Ok(0) => {
-                            // EOF without header; keep connection for retry
-                            return;
-                        }
-                        Ok(n) => {
-                            buf.extend_from_slice(&tmp[..n]);
-                            if find_header_end(&buf).is_some() { break; }
-                            if buf.len() > 256 * 1024 { break; }
+impl BodyReader {
+    fn is_complete(&self) -> bool {
+        match self.framing {
+            Framing::Length(left) => left == 0,
+            Framing::Chunked { done, .. } | Framing::UntilClose { done } => done,
+        }
+    }
+
+    /// Next piece of the body (at most `max` bytes); empty at the end of the body.
+    /// A truncated body (EOF/timeout) also ends it, leaving `is_complete()` false.
+    fn read_some<R: BufRead>(&mut self, r: &mut R, max: usize) -> Vec<u8> {
+        match &mut self.framing {
+            Framing::Length(left) => {
+                if *left == 0 { return vec![]; }
+                let mut buf = vec![0u8; (*left).min(max)];
+                match r.read(&mut buf) {
+                    Ok(n) if n > 0 => { *left -= n; buf.truncate(n); buf }
+                    _ => { self.framing = Framing::UntilClose { done: false }; vec![] }
+                }
+            }
+            Framing::Chunked { left, done } => {
+                if *done { return vec![]; }
+                if *left == 0 {
+                    let mut line = String::new();
+                    let size = match r.read_line(&mut line) {
+                        Ok(n) if n > 0 => usize::from_str_radix(line.trim().split(';').next().unwrap_or("").trim(), 16).ok(),
+                        _ => None,
+                    };
+                    match size {
+                        Some(0) => {
+                            // Skip trailers up to the blank line
+                            loop {
+                                let mut t = String::new();
+                                match r.read_line(&mut t) { Ok(n) if n > 0 && !t.trim().is_empty() => continue, _ => break }
+                            }
+                            *done = true;
+                            return vec![];
                         }
-                        Err(_) => return,
+                        Some(size) => *left = size,
+                        None => { self.framing = Framing::UntilClose { done: false }; return vec![]; }
                     }
                 }
-                if let Some(pos) = find_header_end(&buf) {
-                    let header = &buf[..pos];
-                    let after = &buf[pos+4..];
-                    // Parse status line and headers
-                    let header_str = String::from_utf8_lossy(header);
-                    let mut lines = header_str.split("\r\n");
-                    if let Some(status_line) = lines.next() {
-                        let mut sp = status_line.split_whitespace();
-                        let _ver = sp.next();
-                        if let Some(code_str) = sp.next() { status = code_str.parse::<i32>().unwrap_or(200); }
-                    }
-                    for line in lines { if let Some((k,v)) = line.split_once(':') { headers.insert(k.trim().to_string(), v.trim().to_string()); } }
-                    body.extend_from_slice(after);
-                    let need = headers.get("Content-Length").and_then(|v| v.parse::<usize>().ok()).unwrap_or(0);
-                    while body.len() < need {
-                        match s.read(&mut tmp) { Ok(0) => break, Ok(n) => body.extend_from_slice(&tmp[..n]), Err(_) => break }
+                let mut buf = vec![0u8; (*left).min(max)];
+                match r.read(&mut buf) {
+                    Ok(n) if n > 0 => {
+                        *left -= n;
+                        buf.truncate(n);
+                        if *left == 0 {
+                            let mut crlf = [0u8; 2];
+                            let _ = r.read_exact(&mut crlf);
+                        }
+                        buf
                     }
-                    // Parsing succeeded; mark for removal
-                    should_remove = true;
+                    _ => { self.framing = Framing::UntilClose { done: false }; vec![] }
                 }
             }
+            Framing::UntilClose { done } => {
+                if *done { return vec![]; }
+                let mut buf = vec![0u8; max];
+                match r.read(&mut buf) {
+                    Ok(n) if n > 0 => { buf.truncate(n); buf }
+                    _ => { *done = true; vec![] }
+                }
+            }
+        }
+    }
+
+    /// Server side: read from the request connection, answering `Expect: 100-continue` first
+    fn read_some_from(&mut self, conn: &HttpConn, max: usize) -> Vec<u8> {
+        if self.expect_continue {
+            self.expect_continue = false;
+            let _ = conn.write(b"HTTP/1.1 100 Continue\r\n\r\n");
         }
-        if should_remove {
-            map.remove(&conn_id);
+        self.read_some(&mut *conn.reader.lock().unwrap(), max)
+    }
+
+    fn read_all_from(&mut self, conn: &HttpConn) -> Vec<u8> {
+        let mut body = Vec::new();
+        loop {
+            let piece = self.read_some_from(conn, READ_CHUNK_MAX);
+            if piece.is_empty() { return body; }
+            body.extend_from_slice(&piece);
         }
     }
+}
+
+fn reason_phrase(status: i32) -> &'static str {
+    match status {
+        200 => "OK", 201 => "Created", 204 => "No Content",
+        301 => "Moved Permanently", 302 => "Found", 304 => "Not Modified",
+        400 => "Bad Request", 401 => "Unauthorized", 403 => "Forbidden", 404 => "Not Found",
+        500 => "Internal Server Error", 503 => "Service Unavailable",
+        _ => "OK",
+    }
+}
+
+fn encode_chunk(data: &[u8]) -> Vec<u8> {
+    let mut buf = format!("{:x}\r\n", data.len()).into_bytes();
+    buf.extend_from_slice(data);
+    buf.extend_from_slice(b"\r\n");
+    buf
+}
+
+/// Writes `resp_id` as the response on `conn`. A chunked response that has not been ended stays
+/// bound to the connection for writeChunk()/end(); otherwise the request is finished here.
+/// Returns (status, headers, body) of a complete response for mirroring.
+fn send_response(conn: &Arc<HttpConn>, req_keep_alive: bool, resp_id: u32) -> Option<Option<(i32, HashMap<String, String>, Vec<u8>)>> {
+    let mut resp_map = RESPONSES.lock().unwrap();
+    let rp = resp_map.get_mut(&resp_id)?;
+    netlog!("Request.respond: Reading response id={}, status={}, body_len={}", resp_id, rp.status, rp.body.len());
+    let keep_alive = req_keep_alive && !header_has_token(&rp.headers, "Connection", "close");
+    let chunked = rp.chunked || header_has_token(&rp.headers, "Transfer-Encoding", "chunked");
+    let mut buf = Vec::new();
+    buf.extend_from_slice(format!("HTTP/1.1 {} {}\r\n", rp.status, reason_phrase(rp.status)).as_bytes());
+    let mut has_len = false;
+    for (k, v) in &rp.headers {
+        if k.eq_ignore_ascii_case("Connection") || k.eq_ignore_ascii_case("Transfer-Encoding") { continue; }
+        if k.eq_ignore_ascii_case("Content-Length") {
+            if chunked { continue; }
+            has_len = true;
+        }
+        buf.extend_from_slice(format!("{}: {}\r\n", k, v).as_bytes());
+    }
+    if chunked {
+        buf.extend_from_slice(b"Transfer-Encoding: chunked\r\n");
+    } else if !has_len {
+        buf.extend_from_slice(format!("Content-Length: {}\r\n", rp.body.len()).as_bytes());
+    }
+    buf.extend_from_slice(if keep_alive { b"Connection: keep-alive\r\n\r\n" } else { b"Connection: close\r\n\r\n" });
+    let streaming = chunked && !rp.ended;
+    let mirror = if chunked {
+        if !rp.body.is_empty() { buf.extend_from_slice(&encode_chunk(&rp.body)); }
+        if rp.ended { buf.extend_from_slice(b"0\r\n\r\n"); }
+        // Bytes written from now on go straight to the socket
+        rp.body.clear();
+        if streaming { rp.stream = Some(ServerStream { conn: conn.clone(), keep_alive }); }
+        None
+    } else {
+        buf.extend_from_slice(&rp.body);
+        Some((rp.status, rp.headers.clone(), rp.body.clone()))
+    };
+    drop(resp_map);
+    netlog!("Request.respond: Sending HTTP response, buf_len={} streaming={} keep_alive={}", buf.len(), streaming, keep_alive);
+    let ok = conn.write(&buf).is_ok();
+    if !streaming || !ok { conn.finish(keep_alive && ok); }
+    Some(mirror)
+}
+
+/// Reads status, headers and body of a client response; the connection returns to the pool when reusable
+fn parse_client_response_into(resp_id: u32, conn: ClientConn) {
+    let ClientConn { stream, client_id, pool_key } = conn;
+    let _ = stream.set_read_timeout(Some(Duration::from_millis(4000)));
+    let mut reader = BufReader::new(stream);
+    // Skip interim 1xx responses (e.g. 100 Continue)
+    let head = loop {
+        match read_head(&mut reader) {
+            Some((line, headers)) => {
+                let status = line.split_whitespace().nth(1).and_then(|c| c.parse::<i32>().ok()).unwrap_or(200);
+                if (100..200).contains(&status) { continue; }
+                break Some((line, status, headers));
+            }
+            None => break None,
+        }
+    };
+    let Some((status_line, status, headers)) = head else {
+        netlog!("client: no response head for resp_id={}", resp_id);
+        if let Some(rp) = RESPONSES.lock().unwrap().get_mut(&resp_id) { rp.parsed = true; }
+        return;
+    };
+    let framing = if status == 204 || status == 304 {
+        Framing::Length(0)
+    } else if header_has_token(&headers, "Transfer-Encoding", "chunked") {
+        Framing::Chunked { left: 0, done: false }
+    } else if let Some(len) = header_value(&headers, "Content-Length").and_then(|v| v.parse().ok()) {
+        Framing::Length(len)
+    } else {
+        Framing::UntilClose { done: false }
+    };
+    let mut body_reader = BodyReader { framing, expect_continue: false };
+    let mut body = Vec::new();
+    loop {
+        let piece = body_reader.read_some(&mut reader, READ_CHUNK_MAX);
+        if piece.is_empty() { break; }
+        body.extend_from_slice(&piece);
+    }
+    let reusable = status_line.starts_with("HTTP/1.1")
+        && !header_has_token(&headers, "Connection", "close")
+        && !matches!(body_reader.framing, Framing::UntilClose { .. })
+        && body_reader.is_complete()
+        && reader.buffer().is_empty();
+    if reusable {
+        let stream = reader.into_inner();
+        let _ = stream.set_read_timeout(None);
+        if let Some(c) = CLIENTS.lock().unwrap().get_mut(&client_id) {
+            c.idle.entry(pool_key).or_default().push(stream);
+        }
+    }
+    netlog!("client: parsed resp_id={} status={} body_len={} reusable={}", resp_id, status, body.len(), reusable);
     if let Some(rp) = RESPONSES.lock().unwrap().get_mut(&resp_id) {
-        rp.status = status; rp.headers = headers; rp.body = body; rp.parsed = true; rp.client_conn_id = None;
+        rp.status = status; rp.headers = headers; rp.body = body; rp.parsed = true;
     }
 }
 
@@ -879,7 +1242,16 @@ static SOCK_CONNS: Lazy<Mutex<HashMap<u32, SockConnState>>> = Lazy::new(|| Mutex
 static SOCK_CLIENTS: Lazy<Mutex<HashMap<u32, SockClientState>>> = Lazy::new(|| Mutex::new(HashMap::new()));
 
 unsafe fn sock_server_invoke(m: u32, id: u32, args: *const u8, args_len: usize, res: *mut u8, res_len: *mut usize) -> i32 {
+    if m != M_SRV_BIRTH && m != M_FINI && !SOCK_SERVERS.lock().unwrap().contains_key(&id) { return E_INV_HANDLE; }
     match m {
+        M_FINI => {
+            let removed = SOCK_SERVERS.lock().unwrap().remove(&id);
+            if let Some(ss) = removed {
+                ss.running.store(false, Ordering::SeqCst);
+                if let Some(h) = ss.handle.lock().unwrap().take() { let _ = h.join(); }
+            }
+            OK
+        }
         M_SRV_BIRTH => {
             netlog!("sock:birth server");
             let id = SOCK_SERVER_ID.fetch_add(1, Ordering::Relaxed);
@@ -903,7 +1275,7 @@ unsafe fn sock_server_invoke(m: u32, id: u32, args: *const u8, args_len: usize,
                                 Ok((stream, _)) => {
                                     stream.set_nonblocking(false).ok();
                                     let conn_id = SOCK_CONN_ID.fetch_add(1, Ordering::Relaxed);
-                                    SOCK_CONNS.lock().unwrap().insert(conn_id, SockConnState { stream: Mutex::new(stream) });
+                                    SOCK_CONNS.lock().unwrap().insert(conn_id, SockConnState { stream: Mutex::new(Some(stream)) });
                                     netlog!("sock:accept conn_id={}", conn_id);
                                     pending.lock().unwrap().push_back(conn_id);
                                 }
@@ -963,7 +1335,12 @@ unsafe fn sock_server_invoke(m: u32, id: u32, args: *const u8, args_len: usize,
 }
 
 unsafe fn sock_client_invoke(m: u32, id: u32, args: *const u8, args_len: usize, res: *mut u8, res_len: *mut usize) -> i32 {
+    if m != M_SC_BIRTH && m != M_FINI && !SOCK_CLIENTS.lock().unwrap().contains_key(&id) { return E_INV_HANDLE; }
     match m {
+        M_FINI => {
+            SOCK_CLIENTS.lock().unwrap().remove(&id);
+            OK
+        }
         M_SC_BIRTH => {
             let id = SOCK_CLIENT_ID.fetch_add(1, Ordering::Relaxed);
             SOCK_CLIENTS.lock().unwrap().insert(id, SockClientState);
@@ -987,7 +1364,7 @@ unsafe fn sock_client_invoke(m: u32, id: u32, args: *const u8, args_len: usize,
                 Ok(mut stream) => {
                     stream.set_nonblocking(false).ok();
                     let conn_id = SOCK_CONN_ID.fetch_add(1, Ordering::Relaxed);
-                    SOCK_CONNS.lock().unwrap().insert(conn_id, SockConnState { stream: Mutex::new(stream) });
+                    SOCK_CONNS.lock().unwrap().insert(conn_id, SockConnState { stream: Mutex::new(Some(stream)) });
                     netlog!("sock:connect ok conn_id={}", conn_id);
                     write_tlv_handle(T_SOCK_CONN, conn_id, res, res_len)
                 }
@@ -999,15 +1376,24 @@ unsafe fn sock_client_invoke(m: u32, id: u32, args: *const u8, args_len: usize,
 }
 
 unsafe fn sock_conn_invoke(m: u32, id: u32, args: *const u8, args_len: usize, res: *mut u8, res_len: *mut usize) -> i32 {
+    if m != M_CONN_BIRTH && m != M_FINI && !SOCK_CONNS.lock().unwrap().contains_key(&id) { return E_INV_HANDLE; }
     match m {
         M_CONN_BIRTH => {
-            // not used directly
-            write_u32(0, res, res_len)
+            // Connections normally come from accept()/connect(); a birthed one is never connected
+            let id = SOCK_CONN_ID.fetch_add(1, Ordering::Relaxed);
+            SOCK_CONNS.lock().unwrap().insert(id, SockConnState { stream: Mutex::new(None) });
+            write_u32(id, res, res_len)
+        }
+        M_FINI => {
+            SOCK_CONNS.lock().unwrap().remove(&id);
+            OK
         }
         M_CONN_SEND => {
             let bytes = tlv_parse_bytes(slice(args, args_len)).unwrap_or_default();
             if let Some(conn) = SOCK_CONNS.lock().unwrap().get(&id) {
-                if let Ok(mut s) = conn.stream.lock() { let _ = s.write_all(&bytes); }
+                let Ok(mut guard) = conn.stream.lock() else { return E_ERR };
+                let Some(s) = guard.as_mut() else { return E_ERR };
+                let _ = s.write_all(&bytes);
                 netlog!("sock:send id={} n={}", id, bytes.len());
                 return write_tlv_void(res, res_len);
             }
@@ -1015,7 +1401,8 @@ unsafe fn sock_conn_invoke(m: u32, id: u32, args: *const u8, args_len: usize, re
         }
         M_CONN_RECV => {
             if let Some(conn) = SOCK_CONNS.lock().unwrap().get(&id) {
-                if let Ok(mut s) = conn.stream.lock() {
+                if let Ok(mut guard) = conn.stream.lock() {
+                    let Some(s) = guard.as_mut() else { return E_ERR };
                     let mut buf = vec![0u8; 4096];
                     match s.read(&mut buf) {
                         Ok(n) => { buf.truncate(n); netlog!("sock:recv id={} n={}", id, n); return write_tlv_bytes(&buf, res, res_len); }
@@ -1028,7 +1415,8 @@ unsafe fn sock_conn_invoke(m: u32, id: u32, args: *const u8, args_len: usize, re
         M_CONN_RECV_TIMEOUT => {
             let timeout_ms = tlv_parse_i32(slice(args, args_len)).unwrap_or(0).max(0) as u64;
             if let Some(conn) = SOCK_CONNS.lock().unwrap().get(&id) {
-                if let Ok(mut s) = conn.stream.lock() {
+                if let Ok(mut guard) = conn.stream.lock() {
+                    let Some(s) = guard.as_mut() else { return E_ERR };
                     let _ = s.set_read_timeout(Some(Duration::from_millis(timeout_ms)));
                     let mut buf = vec![0u8; 4096];
                     let resv = s.read(&mut buf);
@@ -1042,8 +1430,10 @@ unsafe fn sock_conn_invoke(m: u32, id: u32, args: *const u8, args_len: usize, re
             E_INV_HANDLE
         }
         M_CONN_CLOSE => {
-            // Drop the stream by removing entry
-            SOCK_CONNS.lock().unwrap().remove(&id);
+            // Drop the stream; the handle itself stays valid until fini
+            if let Some(conn) = SOCK_CONNS.lock().unwrap().get(&id) {
+                if let Ok(mut guard) = conn.stream.lock() { guard.take(); }
+            }
             write_tlv_void(res, res_len)
         }
         _ => E_INV_METHOD,
@@ -6,7 +6,7 @@
 
 use super::{AotError, AotConfig, AotStats};
 use crate::mir::MirModule;
-use crate::backend::wasm::{WasmBackend, WasmError};
+use crate::backend::wasm::{WasmBackend, WasmError, WasmExecution};
 use wasmtime::{Engine, Module};
 use std::time::Instant;
 
@@ -52,6 +52,7 @@ impl AotCompiler {
                 WasmError::MemoryError(msg) => AotError::CompilationError(format!("WASM memory error: {}", msg)),
                 WasmError::UnsupportedInstruction(msg) => AotError::CompilationError(format!("Unsupported MIR instruction: {}", msg)),
                 WasmError::WasmValidationError(msg) => AotError::CompilationError(format!("WASM validation failed: {}", msg)),
+                WasmError::RuntimeError(msg) => AotError::RuntimeError(msg),
                 WasmError::IOError(msg) => AotError::IOError(msg),
             })?;
         
@@ -86,30 +87,16 @@ impl AotCompiler {
     }
     
     /// Load and execute a precompiled module (for testing)
-    pub fn execute_precompiled(&self, precompiled_bytes: &[u8]) -> Result<i32, AotError> {
+    pub fn execute_precompiled(&self, precompiled_bytes: &[u8]) -> Result<WasmExecution, AotError> {
         // Deserialize the precompiled module
         let module = unsafe {
             Module::deserialize(&self.wasmtime_engine, precompiled_bytes)
                 .map_err(|e| AotError::WasmtimeError(format!("Failed to deserialize module: {}", e)))?
         };
         
-        // Create instance and execute
-        let mut store = wasmtime::Store::new(&self.wasmtime_engine, ());
-        let instance = wasmtime::Instance::new(&mut store, &module, &[])
-            .map_err(|e| AotError::RuntimeError(format!("Failed to create instance: {}", e)))?;
-        
-        // Look for main function or default export
-        let main_func = instance
-            .get_typed_func::<(), i32>(&mut store, "main")
-            .or_else(|_| instance.get_typed_func::<(), i32>(&mut store, "_start"))
-            .or_else(|_| instance.get_typed_func::<(), i32>(&mut store, "run"))
-            .map_err(|e| AotError::RuntimeError(format!("No main function found: {}", e)))?;
-        
-        // Execute the function
-        let result = main_func.call(&mut store, ())
-            .map_err(|e| AotError::RuntimeError(format!("Execution failed: {}", e)))?;
-        
-        Ok(result)
+        // Instantiate with the same host imports as the WASM backend and run main
+        crate::backend::wasm::run_main(&self.wasmtime_engine, &module, true)
+            .map_err(|e| AotError::RuntimeError(e.to_string()))
     }
     
     /// Validate a WASM module before precompilation
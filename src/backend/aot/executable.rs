@@ -1,18 +1,34 @@
 /*!
  * Executable Builder - Creates standalone native executables
- * 
- * Embeds precompiled WASM modules into self-contained executables
+ *
+ * An executable is the prebuilt `nyash-aot-runner` stub with the precompiled wasmtime module
+ * appended as a trailing payload:
+ *
+ *   [runner stub][module][compatibility key][module_len:u64][key_len:u32]["NYASHAOT"]
+ *
+ * Building one only concatenates files, so no Rust toolchain is needed on the build machine.
+ * At startup the runner reads the footer from the end of its own file and deserializes the module.
  */
 
 use super::{AotError, AotConfig};
-use std::path::Path;
-use std::fs;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Marks the end of an executable that carries a payload
+const PAYLOAD_MAGIC: &[u8; 8] = b"NYASHAOT";
+
+/// module_len (8) + key_len (4) + magic (8)
+const FOOTER_SIZE: u64 = 20;
+
+/// File name of the runner stub shipped next to the `nyash` binary
+pub const RUNNER_NAME: &str = "nyash-aot-runner";
 
 /// Builder for creating standalone executable files
 pub struct ExecutableBuilder<'a> {
     config: &'a AotConfig,
     precompiled_module: Option<Vec<u8>>,
-    runtime_template: &'static str,
+    runner_path: Option<PathBuf>,
 }
 
 impl<'a> ExecutableBuilder<'a> {
@@ -21,209 +37,145 @@ impl<'a> ExecutableBuilder<'a> {
         Self {
             config,
             precompiled_module: None,
-            runtime_template: RUNTIME_TEMPLATE,
+            runner_path: None,
         }
     }
-    
+
+    /// Use a specific runner stub instead of looking one up
+    pub fn with_runner<P: AsRef<Path>>(mut self, runner_path: P) -> Self {
+        self.runner_path = Some(runner_path.as_ref().to_path_buf());
+        self
+    }
+
     /// Embed precompiled module data
     pub fn embed_precompiled_module(&mut self, module_data: Vec<u8>) -> Result<(), AotError> {
         self.precompiled_module = Some(module_data);
         Ok(())
     }
-    
+
     /// Create the standalone executable
     pub fn create_executable<P: AsRef<Path>>(&self, output_path: P) -> Result<(), AotError> {
         let module_data = self.precompiled_module.as_ref()
             .ok_or_else(|| AotError::CompilationError("No precompiled module embedded".to_string()))?;
-        
-        // Generate the runtime code with embedded module
-        let runtime_code = self.generate_runtime_code(module_data)?;
-        
-        // Write to temporary Rust source file
-        let temp_dir = std::env::temp_dir();
-        let temp_main = temp_dir.join("nyash_aot_main.rs");
-        let temp_cargo = temp_dir.join("Cargo.toml");
-        
-        fs::write(&temp_main, runtime_code)?;
-        fs::write(&temp_cargo, self.generate_cargo_toml())?;
-        
-        // Compile with Rust compiler
-        self.compile_rust_executable(&temp_dir, output_path)?;
-        
-        // Clean up temporary files
-        let _ = fs::remove_file(&temp_main);
-        let _ = fs::remove_file(&temp_cargo);
-        
-        Ok(())
-    }
-    
-    /// Generate the runtime code with embedded module
-    fn generate_runtime_code(&self, module_data: &[u8]) -> Result<String, AotError> {
-        let module_bytes = self.format_module_bytes(module_data);
-        let compatibility_key = self.config.compatibility_key();
-        
-        let runtime_code = self.runtime_template
-            .replace("{{MODULE_BYTES}}", &module_bytes)
-            .replace("{{COMPATIBILITY_KEY}}", &compatibility_key)
-            .replace("{{OPTIMIZATION_LEVEL}}", &self.config.optimization_level().to_string())
-            .replace("{{TARGET_ARCH}}", self.config.target_arch())
-            .replace("{{WASMTIME_VERSION}}", "18.0");
-        
-        Ok(runtime_code)
-    }
-    
-    /// Format module bytes as Rust byte array literal
-    fn format_module_bytes(&self, data: &[u8]) -> String {
-        let mut result = String::with_capacity(data.len() * 6);
-        result.push_str("&[\n    ");
-        
-        for (i, byte) in data.iter().enumerate() {
-            if i > 0 && i % 16 == 0 {
-                result.push_str("\n    ");
-            }
-            result.push_str(&format!("0x{:02x}, ", byte));
-        }
-        
-        result.push_str("\n]");
-        result
-    }
-    
-    /// Generate Cargo.toml for the executable
-    fn generate_cargo_toml(&self) -> String {
-        format!(r#"[package]
-name = "nyash-aot-executable"
-version = "0.1.0"
-edition = "2021"
-
-[dependencies]
-wasmtime = "18.0"
-
-[profile.release]
-opt-level = 3
-lto = true
-codegen-units = 1
-panic = "abort"
-strip = true
-
-[[bin]]
-name = "nyash-aot-executable"
-path = "nyash_aot_main.rs"
-"#)
-    }
-    
-    /// Compile the Rust executable
-    fn compile_rust_executable<P: AsRef<Path>, Q: AsRef<Path>>(&self, temp_dir: P, output_path: Q) -> Result<(), AotError> {
-        let temp_dir = temp_dir.as_ref();
-        let output_path = output_path.as_ref();
-        
-        // Use cargo to compile
-        let mut cmd = std::process::Command::new("cargo");
-        cmd.current_dir(temp_dir)
-           .args(&["build", "--release", "--bin", "nyash-aot-executable"]);
-        
-        let output = cmd.output()
-            .map_err(|e| AotError::CompilationError(format!("Failed to run cargo: {}", e)))?;
-        
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(AotError::CompilationError(format!("Cargo build failed: {}", stderr)));
+
+        // The runner deserializes with the default engine configuration
+        let key = self.config.compatibility_key();
+        let runner_key = AotConfig::new()?.compatibility_key();
+        if key != runner_key {
+            return Err(AotError::ConfigError(format!(
+                "The runner stub loads modules built as {}, not {}", runner_key, key
+            )));
         }
-        
-        // Copy the compiled executable to the desired location
-        let compiled_exe = temp_dir.join("target/release/nyash-aot-executable");
-        let compiled_exe = if cfg!(windows) {
-            compiled_exe.with_extension("exe")
-        } else {
-            compiled_exe
+
+        let runner_path = match &self.runner_path {
+            Some(path) => path.clone(),
+            None => find_runner()?,
         };
-        
-        if !compiled_exe.exists() {
-            return Err(AotError::CompilationError("Compiled executable not found".to_string()));
+        let mut stub = fs::read(&runner_path)
+            .map_err(|e| AotError::IOError(format!("Failed to read runner stub {}: {}", runner_path.display(), e)))?;
+
+        // A stub that already carries a payload (e.g. an executable built earlier) is reused without it
+        let stub_len = split_payload(&stub).map(|(stub, _, _)| stub.len());
+        if let Some(stub_len) = stub_len {
+            stub.truncate(stub_len);
+        }
+
+        stub.extend_from_slice(&append_payload(module_data, &key));
+        fs::write(output_path.as_ref(), &stub)
+            .map_err(|e| AotError::IOError(format!("Failed to write {}: {}", output_path.as_ref().display(), e)))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(output_path.as_ref(), fs::Permissions::from_mode(0o755))?;
         }
-        
-        fs::copy(&compiled_exe, output_path)
-            .map_err(|e| AotError::IOError(format!("Failed to copy executable: {}", e)))?;
-        
+
         Ok(())
     }
 }
 
-/// Runtime template for generated executables
-const RUNTIME_TEMPLATE: &str = r#"/*!
- * Nyash AOT Runtime - Generated executable
- * 
- * This file is automatically generated by the Nyash AOT compiler.
- * It contains a precompiled WebAssembly module and minimal runtime.
- */
-
-use wasmtime::{Engine, Module, Instance, Store, Config, OptLevel, Strategy};
-use std::process;
+/// Locate the runner stub: `NYASH_AOT_RUNNER`, otherwise next to the running executable
+pub fn find_runner() -> Result<PathBuf, AotError> {
+    if let Some(path) = std::env::var_os("NYASH_AOT_RUNNER") {
+        return Ok(PathBuf::from(path));
+    }
 
-// Embedded precompiled module (generated by AOT compiler)
-const MODULE_DATA: &[u8] = {{MODULE_BYTES}};
+    let exe = std::env::current_exe()?;
+    let dir = exe.parent()
+        .ok_or_else(|| AotError::IOError(format!("No parent directory for {}", exe.display())))?;
+    let runner = dir.join(format!("{}{}", RUNNER_NAME, std::env::consts::EXE_SUFFIX));
+    if runner.is_file() {
+        Ok(runner)
+    } else {
+        Err(AotError::IOError(format!(
+            "Runner stub not found at {} (build it with `cargo build --features wasm-backend --bin {}` or set NYASH_AOT_RUNNER)",
+            runner.display(), RUNNER_NAME
+        )))
+    }
+}
 
-// Compilation metadata
-const COMPATIBILITY_KEY: &str = "{{COMPATIBILITY_KEY}}";
-const OPTIMIZATION_LEVEL: &str = "{{OPTIMIZATION_LEVEL}}";
-const TARGET_ARCH: &str = "{{TARGET_ARCH}}";
-const WASMTIME_VERSION: &str = "{{WASMTIME_VERSION}}";
+/// Payload bytes to append to a runner stub
+fn append_payload(module_data: &[u8], key: &str) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(module_data.len() + key.len() + FOOTER_SIZE as usize);
+    payload.extend_from_slice(module_data);
+    payload.extend_from_slice(key.as_bytes());
+    payload.extend_from_slice(&(module_data.len() as u64).to_le_bytes());
+    payload.extend_from_slice(&(key.len() as u32).to_le_bytes());
+    payload.extend_from_slice(PAYLOAD_MAGIC);
+    payload
+}
 
-fn main() {
-    if let Err(e) = run_aot_module() {
-        eprintln!("❌ AOT execution error: {}", e);
-        process::exit(1);
+/// Lengths from a footer, or None when there is no payload
+fn parse_footer(footer: &[u8]) -> Option<(u64, u64)> {
+    if footer.len() != FOOTER_SIZE as usize || &footer[12..] != PAYLOAD_MAGIC {
+        return None;
     }
+    let module_len = u64::from_le_bytes(footer[0..8].try_into().ok()?);
+    let key_len = u32::from_le_bytes(footer[8..12].try_into().ok()?) as u64;
+    Some((module_len, key_len))
+}
+
+/// Split an executable image into (stub, module, compatibility key)
+pub fn split_payload(data: &[u8]) -> Option<(&[u8], &[u8], &str)> {
+    let footer_start = data.len().checked_sub(FOOTER_SIZE as usize)?;
+    let (module_len, key_len) = parse_footer(&data[footer_start..])?;
+    let key_start = footer_start.checked_sub(key_len as usize)?;
+    let module_start = key_start.checked_sub(module_len as usize)?;
+    let key = std::str::from_utf8(&data[key_start..footer_start]).ok()?;
+    Some((&data[..module_start], &data[module_start..key_start], key))
 }
 
-fn run_aot_module() -> Result<(), Box<dyn std::error::Error>> {
-    // Create optimized wasmtime configuration
-    let mut config = Config::new();
-    config.strategy(Strategy::Cranelift);
-    config.cranelift_opt_level(OptLevel::Speed);
-    
-    // Enable features used during compilation
-    config.wasm_simd(true);
-    config.wasm_bulk_memory(true);
-    config.wasm_multi_memory(true);
-    
-    // Create engine with the configuration
-    let engine = Engine::new(&config)?;
-    
-    // Deserialize the precompiled module
-    let module = unsafe {
-        Module::deserialize(&engine, MODULE_DATA)?
-    };
-    
-    // Create store and instance
-    let mut store = Store::new(&engine, ());
-    let instance = Instance::new(&mut store, &module, &[])?;
-    
-    // Look for the main function
-    let main_func = instance
-        .get_typed_func::<(), i32>(&mut store, "main")
-        .or_else(|_| instance.get_typed_func::<(), i32>(&mut store, "_start"))
-        .or_else(|_| instance.get_typed_func::<(), i32>(&mut store, "run"))
-        .map_err(|_| "No main function found in module")?;
-    
-    // Execute the function
-    let result = main_func.call(&mut store, ())?;
-    
-    println!("✅ AOT execution completed successfully!");
-    println!("📊 Metadata:");
-    println!("   Compatibility: {}", COMPATIBILITY_KEY);
-    println!("   Optimization: {}", OPTIMIZATION_LEVEL);
-    println!("   Target: {}", TARGET_ARCH);
-    println!("   Wasmtime: {}", WASMTIME_VERSION);
-    println!("   Result: {}", result);
-    
-    process::exit(result);
+/// Read the precompiled module appended to an executable, seeking past the stub
+pub fn read_embedded_module<P: AsRef<Path>>(path: P) -> Result<(Vec<u8>, String), AotError> {
+    let path = path.as_ref();
+    let missing = || AotError::IOError(format!("{} has no embedded Nyash module", path.display()));
+
+    let mut file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+    if file_len < FOOTER_SIZE {
+        return Err(missing());
+    }
+    let mut footer = [0u8; FOOTER_SIZE as usize];
+    file.seek(SeekFrom::End(-(FOOTER_SIZE as i64)))?;
+    file.read_exact(&mut footer)?;
+    let (module_len, key_len) = parse_footer(&footer).ok_or_else(missing)?;
+
+    let payload_len = module_len.checked_add(key_len)
+        .filter(|len| *len <= file_len - FOOTER_SIZE)
+        .ok_or_else(missing)?;
+    let mut payload = vec![0u8; payload_len as usize];
+    file.seek(SeekFrom::Start(file_len - FOOTER_SIZE - payload_len))?;
+    file.read_exact(&mut payload)?;
+
+    let key = String::from_utf8(payload.split_off(module_len as usize))
+        .map_err(|_| missing())?;
+    Ok((payload, key))
 }
-"#;
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+
     #[test]
     fn test_executable_builder_creation() {
         let config = AotConfig::new().expect("Failed to create config");
@@ -231,50 +183,57 @@ mod tests {
         // Should not panic
         assert!(true);
     }
-    
+
     #[test]
     fn test_embed_module() {
         let config = AotConfig::new().expect("Failed to create config");
         let mut builder = ExecutableBuilder::new(&config);
         let test_data = vec![1, 2, 3, 4, 5];
-        
+
         builder.embed_precompiled_module(test_data).expect("Failed to embed module");
         assert!(builder.precompiled_module.is_some());
     }
-    
-    #[test]
-    fn test_format_module_bytes() {
-        let config = AotConfig::new().expect("Failed to create config");
-        let builder = ExecutableBuilder::new(&config);
-        let test_data = vec![0x00, 0x61, 0x73, 0x6d];
-        
-        let formatted = builder.format_module_bytes(&test_data);
-        assert!(formatted.contains("0x00"));
-        assert!(formatted.contains("0x61"));
-        assert!(formatted.contains("0x73"));
-        assert!(formatted.contains("0x6d"));
-    }
-    
+
     #[test]
-    fn test_cargo_toml_generation() {
-        let config = AotConfig::new().expect("Failed to create config");
-        let builder = ExecutableBuilder::new(&config);
-        let cargo_toml = builder.generate_cargo_toml();
-        
-        assert!(cargo_toml.contains("nyash-aot-executable"));
-        assert!(cargo_toml.contains("wasmtime"));
-        assert!(cargo_toml.contains("opt-level = 3"));
+    fn test_payload_roundtrip() {
+        let mut image = b"stub bytes".to_vec();
+        image.extend_from_slice(&append_payload(&[0x00, 0x61, 0x73, 0x6d], "key"));
+
+        let (stub, module, key) = split_payload(&image).expect("payload");
+        assert_eq!(stub, b"stub bytes");
+        assert_eq!(module, &[0x00, 0x61, 0x73, 0x6d]);
+        assert_eq!(key, "key");
+
+        assert!(split_payload(b"stub bytes").is_none());
     }
-    
+
     #[test]
-    fn test_runtime_code_generation() {
+    fn test_create_executable_appends_to_stub() {
+        let dir = std::env::temp_dir().join(format!("nyash_aot_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let stub = dir.join("stub");
+        let output = dir.join("app");
+        fs::write(&stub, b"#!stub").unwrap();
+
         let config = AotConfig::new().expect("Failed to create config");
-        let builder = ExecutableBuilder::new(&config);
-        let test_data = vec![0x00, 0x61, 0x73, 0x6d];
-        
-        let runtime_code = builder.generate_runtime_code(&test_data).expect("Failed to generate runtime");
-        assert!(runtime_code.contains("MODULE_DATA"));
-        assert!(runtime_code.contains("0x00"));
-        assert!(runtime_code.contains("18.0"));  // Wasmtime version
+        let mut builder = ExecutableBuilder::new(&config).with_runner(&stub);
+        builder.embed_precompiled_module(vec![1, 2, 3]).unwrap();
+        builder.create_executable(&output).expect("Failed to create executable");
+
+        let (module, key) = read_embedded_module(&output).expect("embedded module");
+        assert_eq!(module, vec![1, 2, 3]);
+        assert_eq!(key, config.compatibility_key());
+
+        // Building again from the output as stub replaces the payload instead of stacking another
+        let mut builder = ExecutableBuilder::new(&config).with_runner(&output);
+        builder.embed_precompiled_module(vec![4]).unwrap();
+        let rebuilt = dir.join("app2");
+        builder.create_executable(&rebuilt).unwrap();
+        let image = fs::read(&rebuilt).unwrap();
+        let (stub_bytes, module, _) = split_payload(&image).unwrap();
+        assert_eq!(stub_bytes, b"#!stub");
+        assert_eq!(module, &[4]);
+
+        let _ = fs::remove_dir_all(&dir);
     }
-}
\ No newline at end of file
+}
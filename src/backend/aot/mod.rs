@@ -2,7 +2,8 @@
  * AOT (Ahead-of-Time) Backend - Phase 9 Implementation
  * 
  * Provides native executable generation using wasmtime precompilation
- * for maximum performance and zero JIT startup overhead
+ * for maximum performance and zero JIT startup overhead.
+ * Executables are the prebuilt `nyash-aot-runner` stub plus an appended module payload.
  */
 
 mod compiler;
@@ -10,9 +11,10 @@ mod executable;
 mod config;
 
 pub use compiler::AotCompiler;
-pub use executable::ExecutableBuilder;
+pub use executable::{ExecutableBuilder, find_runner, read_embedded_module, split_payload, RUNNER_NAME};
 pub use config::AotConfig;
 
+use crate::backend::wasm::WasmExecution;
 use crate::mir::MirModule;
 use std::path::Path;
 
@@ -55,7 +57,6 @@ impl From<wasmtime::Error> for AotError {
 /// Main AOT backend
 pub struct AotBackend {
     compiler: AotCompiler,
-    #[allow(dead_code)]
     config: AotConfig,
 }
 
@@ -87,10 +88,25 @@ impl AotBackend {
         mir_module: MirModule, 
         output_path: P
     ) -> Result<(), AotError> {
-        // For now, just create a .cwasm precompiled module
-        // TODO: Implement full standalone executable generation
-        let cwasm_path = output_path.as_ref().with_extension("cwasm");
-        self.compile_to_precompiled(mir_module, cwasm_path)
+        self.compile_to_executable_with_runner(mir_module, output_path, None::<&Path>)
+    }
+    
+    /// Compile MIR module to a standalone executable built from the given runner stub
+    /// (`None` looks it up with `find_runner`)
+    pub fn compile_to_executable_with_runner<P: AsRef<Path>, R: AsRef<Path>>(
+        &mut self,
+        mir_module: MirModule,
+        output_path: P,
+        runner_path: Option<R>,
+    ) -> Result<(), AotError> {
+        let precompiled_module = self.compiler.compile_mir_to_native(mir_module)?;
+        
+        let mut builder = ExecutableBuilder::new(&self.config);
+        if let Some(runner_path) = runner_path {
+            builder = builder.with_runner(runner_path);
+        }
+        builder.embed_precompiled_module(precompiled_module)?;
+        builder.create_executable(output_path)
     }
     
     /// Compile MIR module to .cwasm precompiled module
@@ -117,6 +133,18 @@ impl AotBackend {
     }
 }
 
+/// Run the module embedded in an executable built by `ExecutableBuilder` (the runner stub's entry point)
+pub fn run_embedded<P: AsRef<Path>>(executable_path: P) -> Result<WasmExecution, AotError> {
+    let (module, key) = read_embedded_module(executable_path)?;
+    let config = AotConfig::new()?;
+    if key != config.compatibility_key() {
+        return Err(AotError::ConfigError(format!(
+            "Module was built as {}, this runner expects {}", key, config.compatibility_key()
+        )));
+    }
+    AotCompiler::new(&config)?.execute_precompiled(&module)
+}
+
 impl Default for AotBackend {
     fn default() -> Self {
         Self::new().expect("Failed to create default AOT backend")
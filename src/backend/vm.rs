@@ -5,6 +5,7 @@
  */
 
 use crate::mir::{MirModule, MirFunction, MirInstruction, ConstValue, BinaryOp, CompareOp, UnaryOp, ValueId, BasicBlockId};
+use crate::mir::{MirModuleV2, MirFunctionV2, MirInstructionV2, MirType, TypeOpKind, WeakRefOp};
 use crate::box_trait::{NyashBox, StringBox, IntegerBox, BoolBox, VoidBox};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -198,6 +199,8 @@ pub struct VM {
     scope_tracker: ScopeTracker,
     /// Active MIR module during execution (for function calls)
     module: Option<MirModule>,
+    /// Active module when running the 25-instruction set (`--mir-v2`)
+    module_v2: Option<MirModuleV2>,
     /// Instruction execution counters (by MIR opcode)
     instr_counter: std::collections::HashMap<&'static str, usize>,
     /// Execution start time for optional stats
@@ -232,6 +235,7 @@ impl VM {
             runtime: NyashRuntime::new(),
             scope_tracker: ScopeTracker::new(),
             module: None,
+            module_v2: None,
             instr_counter: std::collections::HashMap::new(),
             exec_start: None,
             // TODO: Re-enable when interpreter refactoring is complete
@@ -259,6 +263,7 @@ impl VM {
             runtime,
             scope_tracker: ScopeTracker::new(),
             module: None,
+            module_v2: None,
             instr_counter: std::collections::HashMap::new(),
             exec_start: None,
         }
@@ -291,6 +296,7 @@ impl VM {
     pub fn execute_module(&mut self, module: &MirModule) -> Result<Box<dyn NyashBox>, VMError> {
         // Store module for nested calls
         self.module = Some(module.clone());
+        self.module_v2 = None;
         // Reset stats
         self.instr_counter.clear();
         self.exec_start = Some(Instant::now());
@@ -308,14 +314,39 @@ impl VM {
         Ok(result.to_nyash_box())
     }
 
+    /// Execute a module lowered to the 25-instruction set (`--mir-v2`)
+    pub fn execute_module_v2(&mut self, module: &MirModuleV2) -> Result<Box<dyn NyashBox>, VMError> {
+        self.module_v2 = Some(module.clone());
+        self.module = None;
+        self.instr_counter.clear();
+        self.exec_start = Some(Instant::now());
+        let main_function = module.get_function("main")
+            .ok_or_else(|| VMError::InvalidInstruction("No main function found".to_string()))?;
+
+        let result = self.execute_function(main_function)?;
+
+        self.maybe_print_stats();
+        Ok(result.to_nyash_box())
+    }
+
     /// Call a MIR function by name with VMValue arguments
     fn call_function_by_name(&mut self, func_name: &str, args: Vec<VMValue>) -> Result<VMValue, VMError> {
-        let module_ref = self.module.as_ref().ok_or_else(|| VMError::InvalidInstruction("No active module".to_string()))?;
-        let function_ref = module_ref.get_function(func_name)
-            .ok_or_else(|| VMError::InvalidInstruction(format!("Function '{}' not found", func_name)))?;
         // Clone function to avoid borrowing conflicts during execution
-        let function = function_ref.clone();
+        if let Some(module_v2) = self.module_v2.as_ref() {
+            let function = module_v2.get_function(func_name)
+                .ok_or_else(|| VMError::InvalidInstruction(format!("Function '{}' not found", func_name)))?
+                .clone();
+            return self.call_function(&function, func_name, args);
+        }
+        let module_ref = self.module.as_ref().ok_or_else(|| VMError::InvalidInstruction("No active module".to_string()))?;
+        let function = module_ref.get_function(func_name)
+            .ok_or_else(|| VMError::InvalidInstruction(format!("Function '{}' not found", func_name)))?
+            .clone();
+        self.call_function(&function, func_name, args)
+    }
 
+    /// Run `function` in a fresh frame
+    fn call_function<F: ExecutableFunction>(&mut self, function: &F, func_name: &str, args: Vec<VMValue>) -> Result<VMValue, VMError> {
         // Save current frame
         let saved_values = std::mem::take(&mut self.values);
         let saved_current_function = self.current_function.clone();
@@ -325,14 +356,14 @@ impl VM {
         let saved_last_result = self.last_result.clone();
 
         // Bind parameters
-        for (i, param_id) in function.params.iter().enumerate() {
+        for (i, param_id) in function.params().iter().enumerate() {
             if let Some(arg) = args.get(i) {
                 self.set_value(*param_id, arg.clone());
             }
         }
 
         // Heuristic: map `me` (first param) to class name parsed from function name (e.g., User.method/N)
-        if let Some(first) = function.params.get(0) {
+        if let Some(first) = function.params().first() {
             if let Some((class_part, _rest)) = func_name.split_once('.') {
                 // Record class for internal field visibility checks
                 self.object_class.insert(*first, class_part.to_string());
@@ -342,7 +373,7 @@ impl VM {
         }
 
         // Execute the function
-        let result = self.execute_function(&function);
+        let result = self.execute_function(function);
 
         // Restore frame
         self.values = saved_values;
@@ -356,8 +387,8 @@ impl VM {
     }
     
     /// Execute a single function
-    fn execute_function(&mut self, function: &MirFunction) -> Result<VMValue, VMError> {
-        self.current_function = Some(function.signature.name.clone());
+    fn execute_function<F: ExecutableFunction>(&mut self, function: &F) -> Result<VMValue, VMError> {
+        self.current_function = Some(function.name().to_string());
         
         // Initialize loop executor for this function
         self.loop_executor.initialize();
@@ -366,53 +397,33 @@ impl VM {
         self.scope_tracker.push_scope();
         
         // Start at entry block
-        let mut current_block = function.entry_block;
+        let mut current_block = function.entry_block();
         
         loop {
-            let block = function.get_block(current_block)
-                .ok_or_else(|| VMError::InvalidBasicBlock(format!("Block {} not found", current_block)))?;
-            
             self.current_block = Some(current_block);
             self.pc = 0;
             
-            let mut next_block = None;
-            let mut should_return = None;
-            
             // Execute instructions in this block (including terminator)
-            let all_instructions: Vec<_> = block.all_instructions().collect();
-            for (index, instruction) in all_instructions.iter().enumerate() {
-                self.pc = index;
-                
-                match self.execute_instruction(instruction)? {
-                    ControlFlow::Continue => continue,
-                    ControlFlow::Jump(target) => {
-                        next_block = Some(target);
-                        break;
-                    },
-                    ControlFlow::Return(value) => {
-                        should_return = Some(value);
-                        break;
-                    },
+            match function.execute_block(self, current_block)? {
+                Some(ControlFlow::Return(return_value)) => {
+                    // Exit scope before returning
+                    self.scope_tracker.pop_scope();
+                    return Ok(return_value);
+                }
+                Some(ControlFlow::Jump(target)) => {
+                    // Update previous block before jumping
+                    self.previous_block = Some(current_block);
+                    // Record the transition in loop executor
+                    self.loop_executor.record_transition(current_block, target);
+                    current_block = target;
+                }
+                Some(ControlFlow::Continue) | None => {
+                    // Block ended without terminator - this shouldn't happen in well-formed MIR
+                    // but let's handle it gracefully by returning void
+                    // Exit scope before returning
+                    self.scope_tracker.pop_scope();
+                    return Ok(VMValue::Void);
                 }
-            }
-            
-            // Handle control flow
-            if let Some(return_value) = should_return {
-                // Exit scope before returning
-                self.scope_tracker.pop_scope();
-                return Ok(return_value);
-            } else if let Some(target) = next_block {
-                // Update previous block before jumping
-                self.previous_block = Some(current_block);
-                // Record the transition in loop executor
-                self.loop_executor.record_transition(current_block, target);
-                current_block = target;
-            } else {
-                // Block ended without terminator - this shouldn't happen in well-formed MIR
-                // but let's handle it gracefully by returning void
-                // Exit scope before returning
-                self.scope_tracker.pop_scope();
-                return Ok(VMValue::Void);
             }
         }
     }
@@ -422,118 +433,19 @@ impl VM {
         // Record instruction for stats
         self.record_instruction(instruction);
         match instruction {
-            MirInstruction::Const { dst, value } => {
-                let vm_value = VMValue::from(value);
-                self.set_value(*dst, vm_value);
-                Ok(ControlFlow::Continue)
-            },
-            
-            MirInstruction::BinOp { dst, op, lhs, rhs } => {
-                let left = self.get_value(*lhs)?;
-                let right = self.get_value(*rhs)?;
-                let result = self.execute_binary_op(op, &left, &right)?;
-                self.set_value(*dst, result);
-                Ok(ControlFlow::Continue)
-            },
-            
-            MirInstruction::UnaryOp { dst, op, operand } => {
-                let operand_val = self.get_value(*operand)?;
-                let result = self.execute_unary_op(op, &operand_val)?;
-                self.set_value(*dst, result);
-                Ok(ControlFlow::Continue)
-            },
-            
-            MirInstruction::Compare { dst, op, lhs, rhs } => {
-                let left = self.get_value(*lhs)?;
-                let right = self.get_value(*rhs)?;
-                let result = self.execute_compare_op(op, &left, &right)?;
-                self.set_value(*dst, VMValue::Bool(result));
-                Ok(ControlFlow::Continue)
-            },
+            MirInstruction::Const { dst, value } => self.exec_const(*dst, value),
+            MirInstruction::BinOp { dst, op, lhs, rhs } => self.exec_binop(*dst, op, *lhs, *rhs),
+            MirInstruction::UnaryOp { dst, op, operand } => self.exec_unary_op(*dst, op, *operand),
+            MirInstruction::Compare { dst, op, lhs, rhs } => self.exec_compare(*dst, op, *lhs, *rhs),
             
-            MirInstruction::Print { value, .. } => {
-                let val = self.get_value(*value)?;
-                println!("{}", val.to_string());
-                Ok(ControlFlow::Continue)
-            },
+            MirInstruction::Print { value, .. } => self.exec_print(*value),
 
-            MirInstruction::TypeOp { dst, op, value, ty } => {
-                match op {
-                    crate::mir::TypeOpKind::Check => {
-                        let v = self.get_value(*value)?;
-                        let ok = match ty {
-                            crate::mir::MirType::Integer => matches!(v, VMValue::Integer(_)),
-                            crate::mir::MirType::Float => matches!(v, VMValue::Float(_)),
-                            crate::mir::MirType::Bool => matches!(v, VMValue::Bool(_)),
-                            crate::mir::MirType::String => matches!(v, VMValue::String(_)),
-                            crate::mir::MirType::Void => matches!(v, VMValue::Void),
-                            crate::mir::MirType::Box(name) => match v {
-                                VMValue::BoxRef(ref arc) => arc.type_name() == name,
-                                _ => false,
-                            },
-                            _ => true,
-                        };
-                        self.set_value(*dst, VMValue::Bool(ok));
-                    }
-                    crate::mir::TypeOpKind::Cast => {
-                        let v = self.get_value(*value)?;
-                        self.set_value(*dst, v);
-                    }
-                }
-                Ok(ControlFlow::Continue)
-            },
-            
-            MirInstruction::Return { value } => {
-                let return_value = if let Some(val_id) = value {
-                    let val = self.get_value(*val_id)?;
-                    val
-                } else {
-                    VMValue::Void
-                };
-                Ok(ControlFlow::Return(return_value))
-            },
-            
-            MirInstruction::Jump { target } => {
-                Ok(ControlFlow::Jump(*target))
-            },
+            MirInstruction::TypeOp { dst, op, value, ty } => self.exec_type_op(*dst, op, *value, ty),
             
-            MirInstruction::Branch { condition, then_bb, else_bb } => {
-                let cond_val = self.get_value(*condition)?;
-                let cond_bool = cond_val.as_bool()?;
-                
-                if cond_bool {
-                    Ok(ControlFlow::Jump(*then_bb))
-                } else {
-                    Ok(ControlFlow::Jump(*else_bb))
-                }
-            },
-            
-            MirInstruction::Phi { dst, inputs } => {
-                // Create a closure that captures self immutably
-                let values = &self.values;
-                let get_value_fn = |value_id: ValueId| -> Result<VMValue, VMError> {
-                    let index = value_id.to_usize();
-                    if index < values.len() {
-                        if let Some(ref value) = values[index] {
-                            Ok(value.clone())
-                        } else {
-                            Err(VMError::InvalidValue(format!("Value {} not set", value_id)))
-                        }
-                    } else {
-                        Err(VMError::InvalidValue(format!("Value {} out of bounds", value_id)))
-                    }
-                };
-                
-                // Delegate phi node execution to loop executor
-                let selected_value = self.loop_executor.execute_phi(
-                    *dst,
-                    inputs,
-                    get_value_fn
-                )?;
-                
-                self.set_value(*dst, selected_value);
-                Ok(ControlFlow::Continue)
-            },
+            MirInstruction::Return { value } => self.exec_return(*value),
+            MirInstruction::Jump { target } => Ok(ControlFlow::Jump(*target)),
+            MirInstruction::Branch { condition, then_bb, else_bb } => self.exec_branch(*condition, *then_bb, *else_bb),
+            MirInstruction::Phi { dst, inputs } => self.exec_phi(*dst, inputs),
             
             // Missing instructions that need basic implementations
             MirInstruction::Load { dst, ptr } => {
@@ -550,178 +462,9 @@ impl VM {
                 Ok(ControlFlow::Continue)
             },
             
-            MirInstruction::Call { dst, func, args, effects: _ } => {
-                // Resolve function name from func value (expects Const String)
-                let func_val = self.get_value(*func)?;
-                let func_name = match func_val {
-                    VMValue::String(s) => s,
-                    _ => return Err(VMError::InvalidInstruction("Call expects func to be a String name".to_string())),
-                };
-                // Gather argument VM values
-                let mut vm_args = Vec::new();
-                for arg_id in args {
-                    vm_args.push(self.get_value(*arg_id)?);
-                }
-                let result = self.call_function_by_name(&func_name, vm_args)?;
-                if let Some(dst_id) = dst {
-                    self.set_value(*dst_id, result);
-                }
-                Ok(ControlFlow::Continue)
-            },
-            
-            MirInstruction::BoxCall { dst, box_val, method, args, effects: _ } => {
-                // Phase 9.78a: Unified method dispatch for all Box types
-                
-                // Get the box value
-                let box_vm_value = self.get_value(*box_val)?;
-                
-                // Handle BoxRef for proper method dispatch
-                let box_nyash = match &box_vm_value {
-                    // Use shared handle to avoid unintended constructor calls
-                    VMValue::BoxRef(arc_box) => arc_box.share_box(),
-                    _ => box_vm_value.to_nyash_box(),
-                };
-                
-                // Fast path: birth() for user-defined boxes is lowered to a MIR function
-                if method == "birth" {
-                    if let Some(instance) = box_nyash.as_any().downcast_ref::<InstanceBox>() {
-                        let class_name = instance.class_name.clone();
-                        let func_name = format!("{}.birth/{}", class_name, args.len());
-
-                        // Prepare VMValue args: me + evaluated arguments
-                        let mut vm_args: Vec<VMValue> = Vec::new();
-                        vm_args.push(VMValue::from_nyash_box(box_nyash.clone_or_share()));
-                        for arg_id in args {
-                            let arg_vm_value = self.get_value(*arg_id)?;
-                            vm_args.push(arg_vm_value);
-                        }
-
-                        // Call the lowered function (ignore return)
-                        let _ = self.call_function_by_name(&func_name, vm_args)?;
-
-                        // birth returns void; only set dst if specified (rare for birth)
-                        if let Some(dst_id) = dst {
-                            self.set_value(*dst_id, VMValue::Void);
-                        }
-                        return Ok(ControlFlow::Continue);
-                    }
-                }
-
-                // Evaluate arguments
-                let mut arg_values: Vec<Box<dyn NyashBox>> = Vec::new();
-                let mut arg_vm_values: Vec<VMValue> = Vec::new();
-                for arg_id in args {
-                    let arg_vm_value = self.get_value(*arg_id)?;
-                    arg_values.push(arg_vm_value.to_nyash_box());
-                    arg_vm_values.push(arg_vm_value);
-                }
-                self.debug_log_boxcall(&box_vm_value, method, &arg_values, "enter", None);
-                
-                // PluginBoxV2 method dispatch via BID-FFI (zero-arg minimal)
-                #[cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
-                if let Some(plugin) = box_nyash.as_any().downcast_ref::<crate::runtime::plugin_loader_v2::PluginBoxV2>() {
-                    let loader = crate::runtime::get_global_loader_v2();
-                    let loader = loader.read().map_err(|_| VMError::InvalidInstruction("Plugin loader lock poisoned".into()))?;
-                    match loader.invoke_instance_method(&plugin.box_type, method, plugin.instance_id(), &arg_values) {
-                        Ok(Some(result_box)) => {
-                            if let Some(dst_id) = dst {
-                                self.set_value(*dst_id, VMValue::from_nyash_box(result_box));
-                            }
-                        }
-                        Ok(None) => {
-                            if let Some(dst_id) = dst {
-                                self.set_value(*dst_id, VMValue::Void);
-                            }
-                        }
-                        Err(_) => {
-                            return Err(VMError::InvalidInstruction(format!("Plugin method call failed: {}", method)));
-                        }
-                    }
-                    return Ok(ControlFlow::Continue);
-                }
-
-                // Fast-path for ArrayBox methods using original BoxRef (preserve state)
-                if let VMValue::BoxRef(ref arc_any) = box_vm_value {
-                    if let Some(arr) = arc_any.as_any().downcast_ref::<crate::boxes::array::ArrayBox>() {
-                        match method.as_str() {
-                            "get" => {
-                                if let Some(arg0) = arg_values.get(0) {
-                                    let res = arr.get((*arg0).clone_or_share());
-                                    if let Some(dst_id) = dst { let v = VMValue::from_nyash_box(res); self.debug_log_boxcall(&box_vm_value, method, &arg_values, "fastpath", Some(&v)); self.set_value(*dst_id, v); }
-                                    return Ok(ControlFlow::Continue);
-                                }
-                            }
-                            "set" => {
-                                if arg_values.len() >= 2 {
-                                    let idx = (*arg_values.get(0).unwrap()).clone_or_share();
-                                    let val = (*arg_values.get(1).unwrap()).clone_or_share();
-                                    let _ = arr.set(idx, val);
-                                    if let Some(dst_id) = dst { let v = VMValue::Void; self.debug_log_boxcall(&box_vm_value, method, &arg_values, "fastpath", Some(&v)); self.set_value(*dst_id, v); }
-                                    return Ok(ControlFlow::Continue);
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-
-                // Call the method - unified dispatch for all Box types
-                // If user-defined InstanceBox: dispatch to lowered MIR function `{Class}.{method}/{argc}`
-                if let Some(instance) = box_nyash.as_any().downcast_ref::<InstanceBox>() {
-                    let class_name = instance.class_name.clone();
-                    let func_name = format!("{}.{}{}", class_name, method, format!("/{}", args.len()));
-                    // Prepare VMValue args: me + evaluated arguments (use original VM args for value-level fidelity)
-                    let mut vm_args: Vec<VMValue> = Vec::new();
-                    vm_args.push(VMValue::from_nyash_box(box_nyash.clone_or_share()));
-                    for arg_id in args {
-                        let arg_vm_value = self.get_value(*arg_id)?;
-                        vm_args.push(arg_vm_value);
-                    }
-                    let call_result = self.call_function_by_name(&func_name, vm_args)?;
-                    if let Some(dst_id) = dst {
-                        self.set_value(*dst_id, call_result);
-                    }
-                    return Ok(ControlFlow::Continue);
-                }
-
-                let result = self.call_unified_method(box_nyash, method, arg_values)?;
-                
-                // Store result if destination is specified
-                if let Some(dst_id) = dst {
-                    let vm_result = VMValue::from_nyash_box(result);
-                    self.debug_log_boxcall(&box_vm_value, method, &arg_vm_values.iter().map(|v| v.to_nyash_box()).collect::<Vec<_>>(), "unified", Some(&vm_result));
-                    self.set_value(*dst_id, vm_result);
-                }
-                Ok(ControlFlow::Continue)
-            },
-            
-            MirInstruction::NewBox { dst, box_type, args } => {
-                // Evaluate arguments into NyashBox for unified factory
-                let mut nyash_args: Vec<Box<dyn NyashBox>> = Vec::new();
-                for arg_id in args {
-                    let arg_value = self.get_value(*arg_id)?;
-                    nyash_args.push(arg_value.to_nyash_box());
-                }
-                // Create via unified registry from runtime
-                let registry = self.runtime.box_registry.clone();
-                let created = {
-                    let guard = registry.lock().map_err(|_| VMError::InvalidInstruction("Registry lock poisoned".into()))?;
-                    guard.create_box(box_type, &nyash_args)
-                };
-                match created {
-                    Ok(b) => {
-                        // Register for scope-based finalization (share; keep same instance)
-                        let reg_arc = std::sync::Arc::from(b.share_box());
-                        self.scope_tracker.register_box(reg_arc);
-                        // Record class name for visibility checks
-                        self.object_class.insert(*dst, box_type.clone());
-                        // Store value in VM
-                        self.set_value(*dst, VMValue::from_nyash_box(b));
-                        Ok(ControlFlow::Continue)
-                    }
-                    Err(e) => Err(VMError::InvalidInstruction(format!("NewBox failed for {}: {}", box_type, e)))
-                }
-            },
+            MirInstruction::Call { dst, func, args, effects: _ } => self.exec_call(*dst, *func, args),
+            MirInstruction::BoxCall { dst, box_val, method, args, effects: _ } => self.exec_box_call(*dst, *box_val, method, args),
+            MirInstruction::NewBox { dst, box_type, args } => self.exec_new_box(*dst, box_type, args),
             
             MirInstruction::TypeCheck { dst, value: _, expected_type: _ } => {
                 // For now, type checks always return true
@@ -795,20 +538,8 @@ impl VM {
             },
             
             // Phase 5: Control flow & exception handling
-            MirInstruction::Throw { exception, effects: _ } => {
-                let exception_val = self.get_value(*exception)?;
-                // For now, convert throw to error return (simplified exception handling)
-                // In a full implementation, this would unwind the stack looking for catch handlers
-                println!("Exception thrown: {}", exception_val.to_string());
-                Err(VMError::InvalidInstruction(format!("Unhandled exception: {}", exception_val.to_string())))
-            },
-            
-            MirInstruction::Catch { exception_type: _, exception_value, handler_bb: _ } => {
-                // For now, catch is a no-op since we don't have full exception handling
-                // In a real implementation, this would set up exception handling metadata
-                self.set_value(*exception_value, VMValue::Void);
-                Ok(ControlFlow::Continue)
-            },
+            MirInstruction::Throw { exception, effects: _ } => self.exec_throw(*exception),
+            MirInstruction::Catch { exception_value, .. } => self.exec_catch(*exception_value),
             
             MirInstruction::Safepoint => {
                 // Safepoint is a no-op for now
@@ -825,99 +556,14 @@ impl VM {
                 Ok(ControlFlow::Continue)
             },
             
-            MirInstruction::RefGet { dst, reference, field } => {
-                // Visibility check (if class known and visibility declared). Skip for internal refs.
-                let is_internal = self.object_internal.contains(reference);
-                if !is_internal {
-                    if let Some(class_name) = self.object_class.get(reference) {
-                        if let Ok(decls) = self.runtime.box_declarations.read() {
-                            if let Some(decl) = decls.get(class_name) {
-                                let has_vis = !decl.public_fields.is_empty() || !decl.private_fields.is_empty();
-                                if has_vis && !decl.public_fields.contains(field) {
-                                    return Err(VMError::TypeError(format!("Field '{}' is private in {}", field, class_name)));
-                                }
-                            }
-                        }
-                    }
-                }
-                // Get field value from object
-                let field_value = if let Some(fields) = self.object_fields.get(reference) {
-                    if let Some(value) = fields.get(field) {
-                        value.clone()
-                    } else {
-                        // Field not set yet, return default
-                        VMValue::Integer(0)
-                    }
-                } else {
-                    // Object has no fields yet, return default
-                    VMValue::Integer(0)
-                };
-                
-                self.set_value(*dst, field_value);
-                Ok(ControlFlow::Continue)
-            },
-            
-            MirInstruction::RefSet { reference, field, value } => {
-                // Get the value to set
-                let new_value = self.get_value(*value)?;
-                // Visibility check (Skip for internal refs; otherwise enforce public)
-                let is_internal = self.object_internal.contains(reference);
-                if !is_internal {
-                    if let Some(class_name) = self.object_class.get(reference) {
-                        if let Ok(decls) = self.runtime.box_declarations.read() {
-                            if let Some(decl) = decls.get(class_name) {
-                                let has_vis = !decl.public_fields.is_empty() || !decl.private_fields.is_empty();
-                                if has_vis && !decl.public_fields.contains(field) {
-                                    return Err(VMError::TypeError(format!("Field '{}' is private in {}", field, class_name)));
-                                }
-                            }
-                        }
-                    }
-                }
-                
-                // Ensure object has field storage
-                if !self.object_fields.contains_key(reference) {
-                    self.object_fields.insert(*reference, HashMap::new());
-                }
-                
-                // Set the field
-                if let Some(fields) = self.object_fields.get_mut(reference) {
-                    fields.insert(field.clone(), new_value);
-                }
-                
-                Ok(ControlFlow::Continue)
-            },
+            MirInstruction::RefGet { dst, reference, field } => self.exec_field_get(*dst, *reference, field),
+            MirInstruction::RefSet { reference, field, value } => self.exec_field_set(*reference, field, *value),
             
-            MirInstruction::WeakNew { dst, box_val } => {
-                // For now, a weak reference is just a copy of the value
-                // In a real implementation, this would create a proper weak reference
-                let box_value = self.get_value(*box_val)?;
-                self.set_value(*dst, box_value);
-                Ok(ControlFlow::Continue)
-            },
-            
-            MirInstruction::WeakLoad { dst, weak_ref } => {
-                // For now, loading from weak ref is the same as getting the value
-                // In a real implementation, this would check if the weak ref is still valid
-                let weak_value = self.get_value(*weak_ref)?;
-                self.set_value(*dst, weak_value);
-                Ok(ControlFlow::Continue)
-            },
+            MirInstruction::WeakNew { dst, box_val } => self.exec_weak_ref(*dst, &WeakRefOp::New, *box_val),
+            MirInstruction::WeakLoad { dst, weak_ref } => self.exec_weak_ref(*dst, &WeakRefOp::Load, *weak_ref),
             
             // Unified PoC ops mapped to legacy behavior
-            MirInstruction::WeakRef { dst, op, value } => {
-                match op {
-                    crate::mir::WeakRefOp::New => {
-                        let v = self.get_value(*value)?;
-                        self.set_value(*dst, v);
-                    }
-                    crate::mir::WeakRefOp::Load => {
-                        let v = self.get_value(*value)?;
-                        self.set_value(*dst, v);
-                    }
-                }
-                Ok(ControlFlow::Continue)
-            },
+            MirInstruction::WeakRef { dst, op, value } => self.exec_weak_ref(*dst, op, *value),
             MirInstruction::Barrier { .. } => {
                 // No-op
                 Ok(ControlFlow::Continue)
@@ -936,74 +582,471 @@ impl VM {
             },
             
             // Phase 7: Async/Future Operations
-            MirInstruction::FutureNew { dst, value } => {
-                let initial_value = self.get_value(*value)?;
-                let future = crate::boxes::future::FutureBox::new();
-                // Convert VMValue to NyashBox and set it in the future
-                let nyash_box = initial_value.to_nyash_box();
-                future.set_result(nyash_box);
-                self.set_value(*dst, VMValue::Future(future));
-                Ok(ControlFlow::Continue)
-            },
+            MirInstruction::FutureNew { dst, value } => self.exec_future_new(Some(*dst), *value),
+            MirInstruction::FutureSet { future, value } => self.exec_future_set(*future, *value),
+            MirInstruction::Await { dst, future } => self.exec_await(*dst, *future),
             
-            MirInstruction::FutureSet { future, value } => {
-                let future_val = self.get_value(*future)?;
-                let new_value = self.get_value(*value)?;
-                
-                if let VMValue::Future(ref future_box) = future_val {
-                    future_box.set_result(new_value.to_nyash_box());
-                    Ok(ControlFlow::Continue)
-                } else {
-                    Err(VMError::TypeError(format!("Expected Future, got {:?}", future_val)))
-                }
+            // Phase 9.7: External Function Calls  
+            MirInstruction::ExternCall { dst, iface_name, method_name, args, effects: _ } => {
+                self.exec_extern_call(*dst, iface_name, method_name, args)
             },
-            
-            MirInstruction::Await { dst, future } => {
-                let future_val = self.get_value(*future)?;
-                
-                if let VMValue::Future(ref future_box) = future_val {
-                    // This blocks until the future is ready
-                    let result = future_box.get();
-                    // Convert NyashBox back to VMValue
-                    let vm_value = VMValue::from_nyash_box(result);
-                    self.set_value(*dst, vm_value);
-                    Ok(ControlFlow::Continue)
+        }
+    }
+
+    /// Execute a single instruction of the 25-instruction set
+    fn execute_instruction_v2(&mut self, instruction: &MirInstructionV2) -> Result<ControlFlow, VMError> {
+        *self.instr_counter.entry(instruction.name()).or_insert(0) += 1;
+        match instruction {
+            // Tier-0
+            MirInstructionV2::Const { dst, value } => self.exec_const(*dst, value),
+            MirInstructionV2::BinOp { dst, op, lhs, rhs } => self.exec_binop(*dst, op, *lhs, *rhs),
+            MirInstructionV2::UnaryOp { dst, op, operand } => self.exec_unary_op(*dst, op, *operand),
+            MirInstructionV2::Compare { dst, op, lhs, rhs } => self.exec_compare(*dst, op, *lhs, *rhs),
+            MirInstructionV2::Branch { condition, then_bb, else_bb } => self.exec_branch(*condition, *then_bb, *else_bb),
+            MirInstructionV2::Jump { target } => Ok(ControlFlow::Jump(*target)),
+            MirInstructionV2::Phi { dst, inputs } => self.exec_phi(*dst, inputs),
+            MirInstructionV2::Call { dst, func, args, .. } => self.exec_call(*dst, *func, args),
+            MirInstructionV2::Return { value } => self.exec_return(*value),
+
+            // Tier-1
+            MirInstructionV2::NewBox { dst, box_type, args } => self.exec_new_box(*dst, box_type, args),
+            MirInstructionV2::BoxFieldLoad { dst, box_val, field } => self.exec_field_get(*dst, *box_val, field),
+            MirInstructionV2::BoxFieldStore { box_val, field, value } => self.exec_field_set(*box_val, field, *value),
+            MirInstructionV2::BoxCall { dst, box_val, method, args, .. } => self.exec_box_call(*dst, *box_val, method, args),
+            MirInstructionV2::ExternCall { dst, iface_name, method_name, args, .. } => {
+                // Services the lowering introduces are provided by the VM itself
+                match (iface_name.as_str(), method_name.as_str(), args.as_slice()) {
+                    ("env.console", "log", [value]) => self.exec_print(*value),
+                    ("env.future", "new", [value]) => self.exec_future_new(*dst, *value),
+                    ("env.future", "set", [future, value]) => self.exec_future_set(*future, *value),
+                    _ => self.exec_extern_call(*dst, iface_name, method_name, args),
+                }
+            }
+            MirInstructionV2::TypeOp { dst, op, value, ty } => self.exec_type_op(*dst, op, *value, ty),
+            MirInstructionV2::WeakRef { dst, op, value } => self.exec_weak_ref(*dst, op, *value),
+            MirInstructionV2::Barrier { .. } | MirInstructionV2::Safepoint => Ok(ControlFlow::Continue),
+            MirInstructionV2::Throw { exception, .. } => self.exec_throw(*exception),
+            MirInstructionV2::Catch { exception_value, .. } => self.exec_catch(*exception_value),
+            MirInstructionV2::Await { dst, future } => self.exec_await(*dst, *future),
+
+            // Tier-2: ownership and fences have no runtime effect yet; the lowering never emits TailCall
+            MirInstructionV2::Adopt { .. } |
+            MirInstructionV2::Release { .. } |
+            MirInstructionV2::AtomicFence { .. } => Ok(ControlFlow::Continue),
+            MirInstructionV2::TailCall { .. } => {
+                Err(VMError::InvalidInstruction("TailCall is not supported by the VM".to_string()))
+            }
+        }
+    }
+
+    // === Instruction semantics shared by legacy MIR and the 25-instruction set ===
+
+    fn exec_const(&mut self, dst: ValueId, value: &ConstValue) -> Result<ControlFlow, VMError> {
+        self.set_value(dst, VMValue::from(value));
+        Ok(ControlFlow::Continue)
+    }
+
+    fn exec_binop(&mut self, dst: ValueId, op: &BinaryOp, lhs: ValueId, rhs: ValueId) -> Result<ControlFlow, VMError> {
+        let left = self.get_value(lhs)?;
+        let right = self.get_value(rhs)?;
+        let result = self.execute_binary_op(op, &left, &right)?;
+        self.set_value(dst, result);
+        Ok(ControlFlow::Continue)
+    }
+
+    fn exec_unary_op(&mut self, dst: ValueId, op: &UnaryOp, operand: ValueId) -> Result<ControlFlow, VMError> {
+        let operand_val = self.get_value(operand)?;
+        let result = self.execute_unary_op(op, &operand_val)?;
+        self.set_value(dst, result);
+        Ok(ControlFlow::Continue)
+    }
+
+    fn exec_compare(&mut self, dst: ValueId, op: &CompareOp, lhs: ValueId, rhs: ValueId) -> Result<ControlFlow, VMError> {
+        let left = self.get_value(lhs)?;
+        let right = self.get_value(rhs)?;
+        let result = self.execute_compare_op(op, &left, &right)?;
+        self.set_value(dst, VMValue::Bool(result));
+        Ok(ControlFlow::Continue)
+    }
+
+    fn exec_print(&mut self, value: ValueId) -> Result<ControlFlow, VMError> {
+        let val = self.get_value(value)?;
+        println!("{}", val.to_string());
+        Ok(ControlFlow::Continue)
+    }
+
+    fn exec_type_op(&mut self, dst: ValueId, op: &TypeOpKind, value: ValueId, ty: &MirType) -> Result<ControlFlow, VMError> {
+        match op {
+            TypeOpKind::Check => {
+                let v = self.get_value(value)?;
+                let ok = match ty {
+                    MirType::Integer => matches!(v, VMValue::Integer(_)),
+                    MirType::Float => matches!(v, VMValue::Float(_)),
+                    MirType::Bool => matches!(v, VMValue::Bool(_)),
+                    MirType::String => matches!(v, VMValue::String(_)),
+                    MirType::Void => matches!(v, VMValue::Void),
+                    MirType::Box(name) => match v {
+                        VMValue::BoxRef(ref arc) => arc.type_name() == name,
+                        _ => false,
+                    },
+                    _ => true,
+                };
+                self.set_value(dst, VMValue::Bool(ok));
+            }
+            TypeOpKind::Cast => {
+                let v = self.get_value(value)?;
+                self.set_value(dst, v);
+            }
+        }
+        Ok(ControlFlow::Continue)
+    }
+
+    fn exec_return(&mut self, value: Option<ValueId>) -> Result<ControlFlow, VMError> {
+        let return_value = match value {
+            Some(val_id) => self.get_value(val_id)?,
+            None => VMValue::Void,
+        };
+        Ok(ControlFlow::Return(return_value))
+    }
+
+    fn exec_branch(&mut self, condition: ValueId, then_bb: BasicBlockId, else_bb: BasicBlockId) -> Result<ControlFlow, VMError> {
+        let cond_val = self.get_value(condition)?;
+        if cond_val.as_bool()? {
+            Ok(ControlFlow::Jump(then_bb))
+        } else {
+            Ok(ControlFlow::Jump(else_bb))
+        }
+    }
+
+    fn exec_phi(&mut self, dst: ValueId, inputs: &[(BasicBlockId, ValueId)]) -> Result<ControlFlow, VMError> {
+        // Create a closure that captures self immutably
+        let values = &self.values;
+        let get_value_fn = |value_id: ValueId| -> Result<VMValue, VMError> {
+            let index = value_id.to_usize();
+            if index < values.len() {
+                if let Some(ref value) = values[index] {
+                    Ok(value.clone())
                 } else {
-                    Err(VMError::TypeError(format!("Expected Future, got {:?}", future_val)))
+                    Err(VMError::InvalidValue(format!("Value {} not set", value_id)))
                 }
-            },
-            
-            // Phase 9.7: External Function Calls  
-            MirInstruction::ExternCall { dst, iface_name, method_name, args, effects: _ } => {
-                // Evaluate arguments as NyashBox for loader
-                let mut nyash_args: Vec<Box<dyn NyashBox>> = Vec::new();
+            } else {
+                Err(VMError::InvalidValue(format!("Value {} out of bounds", value_id)))
+            }
+        };
+        
+        // Delegate phi node execution to loop executor
+        let selected_value = self.loop_executor.execute_phi(dst, inputs, get_value_fn)?;
+        
+        self.set_value(dst, selected_value);
+        Ok(ControlFlow::Continue)
+    }
+
+    fn exec_call(&mut self, dst: Option<ValueId>, func: ValueId, args: &[ValueId]) -> Result<ControlFlow, VMError> {
+        // Resolve function name from func value (expects Const String)
+        let func_val = self.get_value(func)?;
+        let func_name = match func_val {
+            VMValue::String(s) => s,
+            _ => return Err(VMError::InvalidInstruction("Call expects func to be a String name".to_string())),
+        };
+        // Gather argument VM values
+        let mut vm_args = Vec::new();
+        for arg_id in args {
+            vm_args.push(self.get_value(*arg_id)?);
+        }
+        let result = self.call_function_by_name(&func_name, vm_args)?;
+        if let Some(dst_id) = dst {
+            self.set_value(dst_id, result);
+        }
+        Ok(ControlFlow::Continue)
+    }
+
+    fn exec_box_call(&mut self, dst: Option<ValueId>, box_val: ValueId, method: &str, args: &[ValueId]) -> Result<ControlFlow, VMError> {
+        // Phase 9.78a: Unified method dispatch for all Box types
+        
+        // Get the box value
+        let box_vm_value = self.get_value(box_val)?;
+        
+        // Handle BoxRef for proper method dispatch
+        let box_nyash = match &box_vm_value {
+            // Use shared handle to avoid unintended constructor calls
+            VMValue::BoxRef(arc_box) => arc_box.share_box(),
+            _ => box_vm_value.to_nyash_box(),
+        };
+        
+        // Fast path: birth() for user-defined boxes is lowered to a MIR function
+        if method == "birth" {
+            if let Some(instance) = box_nyash.as_any().downcast_ref::<InstanceBox>() {
+                let class_name = instance.class_name.clone();
+                let func_name = format!("{}.birth/{}", class_name, args.len());
+
+                // Prepare VMValue args: me + evaluated arguments
+                let mut vm_args: Vec<VMValue> = Vec::new();
+                vm_args.push(VMValue::from_nyash_box(box_nyash.clone_or_share()));
                 for arg_id in args {
-                    let arg_value = self.get_value(*arg_id)?;
-                    nyash_args.push(arg_value.to_nyash_box());
+                    let arg_vm_value = self.get_value(*arg_id)?;
+                    vm_args.push(arg_vm_value);
                 }
-                // Route through plugin loader v2 (also handles env.* stubs)
-                let loader = crate::runtime::get_global_loader_v2();
-                let loader = loader.read().map_err(|_| VMError::InvalidInstruction("Plugin loader lock poisoned".into()))?;
-                match loader.extern_call(iface_name, method_name, &nyash_args) {
-                    Ok(Some(result_box)) => {
-                        if let Some(dst_id) = dst {
-                            self.set_value(*dst_id, VMValue::from_nyash_box(result_box));
-                        }
+
+                // Call the lowered function (ignore return)
+                let _ = self.call_function_by_name(&func_name, vm_args)?;
+
+                // birth returns void; only set dst if specified (rare for birth)
+                if let Some(dst_id) = dst {
+                    self.set_value(dst_id, VMValue::Void);
+                }
+                return Ok(ControlFlow::Continue);
+            }
+        }
+
+        // Evaluate arguments
+        let mut arg_values: Vec<Box<dyn NyashBox>> = Vec::new();
+        let mut arg_vm_values: Vec<VMValue> = Vec::new();
+        for arg_id in args {
+            let arg_vm_value = self.get_value(*arg_id)?;
+            arg_values.push(arg_vm_value.to_nyash_box());
+            arg_vm_values.push(arg_vm_value);
+        }
+        self.debug_log_boxcall(&box_vm_value, method, &arg_values, "enter", None);
+        
+        // PluginBoxV2 method dispatch via BID-FFI (zero-arg minimal)
+        #[cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
+        if let Some(plugin) = box_nyash.as_any().downcast_ref::<crate::runtime::plugin_loader_v2::PluginBoxV2>() {
+            let loader = crate::runtime::get_global_loader_v2();
+            let loader = loader.read().map_err(|_| VMError::InvalidInstruction("Plugin loader lock poisoned".into()))?;
+            match loader.invoke_instance_method(&plugin.box_type, method, plugin.instance_id(), &arg_values) {
+                Ok(Some(result_box)) => {
+                    if let Some(dst_id) = dst {
+                        self.set_value(dst_id, VMValue::from_nyash_box(result_box));
+                    }
+                }
+                Ok(None) => {
+                    if let Some(dst_id) = dst {
+                        self.set_value(dst_id, VMValue::Void);
                     }
-                    Ok(None) => {
-                        if let Some(dst_id) = dst {
-                            self.set_value(*dst_id, VMValue::Void);
+                }
+                Err(_) => {
+                    return Err(VMError::InvalidInstruction(format!("Plugin method call failed: {}", method)));
+                }
+            }
+            return Ok(ControlFlow::Continue);
+        }
+
+        // Fast-path for ArrayBox methods using original BoxRef (preserve state)
+        if let VMValue::BoxRef(ref arc_any) = box_vm_value {
+            if let Some(arr) = arc_any.as_any().downcast_ref::<crate::boxes::array::ArrayBox>() {
+                match method {
+                    "get" => {
+                        if let Some(arg0) = arg_values.get(0) {
+                            let res = arr.get((*arg0).clone_or_share());
+                            if let Some(dst_id) = dst { let v = VMValue::from_nyash_box(res); self.debug_log_boxcall(&box_vm_value, method, &arg_values, "fastpath", Some(&v)); self.set_value(dst_id, v); }
+                            return Ok(ControlFlow::Continue);
                         }
                     }
-                    Err(_) => {
-                        return Err(VMError::InvalidInstruction(format!("ExternCall failed: {}.{}", iface_name, method_name)));
+                    "set" => {
+                        if arg_values.len() >= 2 {
+                            let idx = (*arg_values.get(0).unwrap()).clone_or_share();
+                            let val = (*arg_values.get(1).unwrap()).clone_or_share();
+                            let _ = arr.set(idx, val);
+                            if let Some(dst_id) = dst { let v = VMValue::Void; self.debug_log_boxcall(&box_vm_value, method, &arg_values, "fastpath", Some(&v)); self.set_value(dst_id, v); }
+                            return Ok(ControlFlow::Continue);
+                        }
                     }
+                    _ => {}
                 }
+            }
+        }
+
+        // Call the method - unified dispatch for all Box types
+        // If user-defined InstanceBox: dispatch to lowered MIR function `{Class}.{method}/{argc}`
+        if let Some(instance) = box_nyash.as_any().downcast_ref::<InstanceBox>() {
+            let class_name = instance.class_name.clone();
+            let func_name = format!("{}.{}{}", class_name, method, format!("/{}", args.len()));
+            // Prepare VMValue args: me + evaluated arguments (use original VM args for value-level fidelity)
+            let mut vm_args: Vec<VMValue> = Vec::new();
+            vm_args.push(VMValue::from_nyash_box(box_nyash.clone_or_share()));
+            for arg_id in args {
+                let arg_vm_value = self.get_value(*arg_id)?;
+                vm_args.push(arg_vm_value);
+            }
+            let call_result = self.call_function_by_name(&func_name, vm_args)?;
+            if let Some(dst_id) = dst {
+                self.set_value(dst_id, call_result);
+            }
+            return Ok(ControlFlow::Continue);
+        }
+
+        let result = self.call_unified_method(box_nyash, method, arg_values)?;
+        
+        // Store result if destination is specified
+        if let Some(dst_id) = dst {
+            let vm_result = VMValue::from_nyash_box(result);
+            self.debug_log_boxcall(&box_vm_value, method, &arg_vm_values.iter().map(|v| v.to_nyash_box()).collect::<Vec<_>>(), "unified", Some(&vm_result));
+            self.set_value(dst_id, vm_result);
+        }
+        Ok(ControlFlow::Continue)
+    }
+
+    fn exec_new_box(&mut self, dst: ValueId, box_type: &str, args: &[ValueId]) -> Result<ControlFlow, VMError> {
+        // Evaluate arguments into NyashBox for unified factory
+        let mut nyash_args: Vec<Box<dyn NyashBox>> = Vec::new();
+        for arg_id in args {
+            let arg_value = self.get_value(*arg_id)?;
+            nyash_args.push(arg_value.to_nyash_box());
+        }
+        // Create via unified registry from runtime
+        let registry = self.runtime.box_registry.clone();
+        let created = {
+            let guard = registry.lock().map_err(|_| VMError::InvalidInstruction("Registry lock poisoned".into()))?;
+            guard.create_box(box_type, &nyash_args)
+        };
+        match created {
+            Ok(b) => {
+                // Register for scope-based finalization (share; keep same instance)
+                let reg_arc = std::sync::Arc::from(b.share_box());
+                self.scope_tracker.register_box(reg_arc);
+                // Record class name for visibility checks
+                self.object_class.insert(dst, box_type.to_string());
+                // Store value in VM
+                self.set_value(dst, VMValue::from_nyash_box(b));
                 Ok(ControlFlow::Continue)
-            },
+            }
+            Err(e) => Err(VMError::InvalidInstruction(format!("NewBox failed for {}: {}", box_type, e)))
+        }
+    }
+
+    /// Visibility check (if class known and visibility declared). Skip for internal refs.
+    fn check_field_visibility(&self, reference: ValueId, field: &str) -> Result<(), VMError> {
+        if self.object_internal.contains(&reference) {
+            return Ok(());
+        }
+        if let Some(class_name) = self.object_class.get(&reference) {
+            if let Ok(decls) = self.runtime.box_declarations.read() {
+                if let Some(decl) = decls.get(class_name) {
+                    let has_vis = !decl.public_fields.is_empty() || !decl.private_fields.is_empty();
+                    if has_vis && !decl.public_fields.iter().any(|f| f == field) {
+                        return Err(VMError::TypeError(format!("Field '{}' is private in {}", field, class_name)));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn exec_field_get(&mut self, dst: ValueId, reference: ValueId, field: &str) -> Result<ControlFlow, VMError> {
+        self.check_field_visibility(reference, field)?;
+        // Get field value from object; unset fields read as the default
+        let field_value = self.object_fields.get(&reference)
+            .and_then(|fields| fields.get(field))
+            .cloned()
+            .unwrap_or(VMValue::Integer(0));
+        
+        self.set_value(dst, field_value);
+        Ok(ControlFlow::Continue)
+    }
+
+    fn exec_field_set(&mut self, reference: ValueId, field: &str, value: ValueId) -> Result<ControlFlow, VMError> {
+        // Get the value to set
+        let new_value = self.get_value(value)?;
+        self.check_field_visibility(reference, field)?;
+        
+        // Set the field (creating the object's field storage on first use)
+        self.object_fields.entry(reference).or_default().insert(field.to_string(), new_value);
+        Ok(ControlFlow::Continue)
+    }
+
+    fn exec_weak_ref(&mut self, dst: ValueId, op: &WeakRefOp, value: ValueId) -> Result<ControlFlow, VMError> {
+        // For now a weak reference is a copy of the value, and loading it returns that value
+        // In a real implementation, New would create a proper weak reference and Load would check liveness
+        match op {
+            WeakRefOp::New | WeakRefOp::Load => {
+                let v = self.get_value(value)?;
+                self.set_value(dst, v);
+            }
+        }
+        Ok(ControlFlow::Continue)
+    }
+
+    fn exec_throw(&mut self, exception: ValueId) -> Result<ControlFlow, VMError> {
+        let exception_val = self.get_value(exception)?;
+        // For now, convert throw to error return (simplified exception handling)
+        // In a full implementation, this would unwind the stack looking for catch handlers
+        println!("Exception thrown: {}", exception_val.to_string());
+        Err(VMError::InvalidInstruction(format!("Unhandled exception: {}", exception_val.to_string())))
+    }
+
+    fn exec_catch(&mut self, exception_value: ValueId) -> Result<ControlFlow, VMError> {
+        // For now, catch is a no-op since we don't have full exception handling
+        // In a real implementation, this would set up exception handling metadata
+        self.set_value(exception_value, VMValue::Void);
+        Ok(ControlFlow::Continue)
+    }
+
+    fn exec_future_new(&mut self, dst: Option<ValueId>, value: ValueId) -> Result<ControlFlow, VMError> {
+        let initial_value = self.get_value(value)?;
+        let future = crate::boxes::future::FutureBox::new();
+        // Convert VMValue to NyashBox and set it in the future
+        future.set_result(initial_value.to_nyash_box());
+        if let Some(dst_id) = dst {
+            self.set_value(dst_id, VMValue::Future(future));
+        }
+        Ok(ControlFlow::Continue)
+    }
+
+    fn exec_future_set(&mut self, future: ValueId, value: ValueId) -> Result<ControlFlow, VMError> {
+        let future_val = self.get_value(future)?;
+        let new_value = self.get_value(value)?;
+        
+        if let VMValue::Future(ref future_box) = future_val {
+            future_box.set_result(new_value.to_nyash_box());
+            Ok(ControlFlow::Continue)
+        } else {
+            Err(VMError::TypeError(format!("Expected Future, got {:?}", future_val)))
         }
     }
+
+    fn exec_await(&mut self, dst: ValueId, future: ValueId) -> Result<ControlFlow, VMError> {
+        let future_val = self.get_value(future)?;
+        
+        if let VMValue::Future(ref future_box) = future_val {
+            // This blocks until the future is ready
+            let result = future_box.get();
+            // Convert NyashBox back to VMValue
+            self.set_value(dst, VMValue::from_nyash_box(result));
+            Ok(ControlFlow::Continue)
+        } else {
+            Err(VMError::TypeError(format!("Expected Future, got {:?}", future_val)))
+        }
+    }
+
+    fn exec_extern_call(&mut self, dst: Option<ValueId>, iface_name: &str, method_name: &str, args: &[ValueId]) -> Result<ControlFlow, VMError> {
+        // Evaluate arguments as NyashBox for loader
+        let mut nyash_args: Vec<Box<dyn NyashBox>> = Vec::new();
+        for arg_id in args {
+            let arg_value = self.get_value(*arg_id)?;
+            nyash_args.push(arg_value.to_nyash_box());
+        }
+        // Route through plugin loader v2 (also handles env.* stubs)
+        let loader = crate::runtime::get_global_loader_v2();
+        let loader = loader.read().map_err(|_| VMError::InvalidInstruction("Plugin loader lock poisoned".into()))?;
+        match loader.extern_call(iface_name, method_name, &nyash_args) {
+            Ok(Some(result_box)) => {
+                if let Some(dst_id) = dst {
+                    self.set_value(dst_id, VMValue::from_nyash_box(result_box));
+                }
+            }
+            Ok(None) => {
+                if let Some(dst_id) = dst {
+                    self.set_value(dst_id, VMValue::Void);
+                }
+            }
+            Err(_) => {
+                return Err(VMError::InvalidInstruction(format!("ExternCall failed: {}.{}", iface_name, method_name)));
+            }
+        }
+        Ok(ControlFlow::Continue)
+    }
     
+
     /// Get a value from storage
     fn get_value(&self, value_id: ValueId) -> Result<VMValue, VMError> {
         let index = value_id.to_usize();
@@ -1318,11 +1361,12 @@ impl VM {
     fn call_unified_method(&self, box_value: Box<dyn NyashBox>, method: &str, args: Vec<Box<dyn NyashBox>>) -> Result<Box<dyn NyashBox>, VMError> {
         // For now, we use the simplified method dispatch
         // In a full implementation, this would check for InstanceBox and dispatch appropriately
-        self.call_box_method(box_value, method, args)
+        Self::call_box_method(box_value, method, args)
     }
     
     /// Call a method on a Box - simplified version of interpreter method dispatch
-    fn call_box_method(&self, box_value: Box<dyn NyashBox>, method: &str, _args: Vec<Box<dyn NyashBox>>) -> Result<Box<dyn NyashBox>, VMError> {
+    /// (also used by the WASM host to run methods of host-side boxes)
+    pub(crate) fn call_box_method(box_value: Box<dyn NyashBox>, method: &str, _args: Vec<Box<dyn NyashBox>>) -> Result<Box<dyn NyashBox>, VMError> {
         // For now, implement basic methods for common box types
         // This is a simplified version - real implementation would need full method dispatch
 
@@ -1580,6 +1624,24 @@ impl VM {
             }
         }
         
+        // FileBox methods
+        if let Some(file_box) = box_value.as_any().downcast_ref::<crate::boxes::FileBox>() {
+            match method {
+                "read" => { return Ok(file_box.read()); },
+                "write" => {
+                    if let Some(content) = _args.get(0) { return Ok(file_box.write(content.clone_or_share())); }
+                    return Ok(Box::new(StringBox::new("Error: write(content) requires 1 arg")));
+                },
+                "exists" => { return Ok(file_box.exists()); },
+                "delete" => { return Ok(file_box.delete()); },
+                "copy" => {
+                    if let Some(dest) = _args.get(0) { return Ok(file_box.copy(&dest.to_string_box().value)); }
+                    return Ok(Box::new(StringBox::new("Error: copy(dest) requires 1 arg")));
+                },
+                _ => return Ok(Box::new(VoidBox::new())),
+            }
+        }
+        
         // PluginBoxV2 support
         if let Some(plugin_box) = box_value.as_any().downcast_ref::<crate::runtime::plugin_loader_v2::PluginBoxV2>() {
             // For toString on plugins, return a descriptive string
@@ -1605,6 +1667,54 @@ enum ControlFlow {
     Return(VMValue),
 }
 
+/// Function bodies the VM can run: legacy MIR or the 25-instruction set
+trait ExecutableFunction {
+    fn name(&self) -> &str;
+    fn params(&self) -> &[ValueId];
+    fn entry_block(&self) -> BasicBlockId;
+    /// Execute the block's instructions (terminator included) until one transfers control;
+    /// `None` if the block ends without doing so
+    fn execute_block(&self, vm: &mut VM, block: BasicBlockId) -> Result<Option<ControlFlow>, VMError>;
+}
+
+impl ExecutableFunction for MirFunction {
+    fn name(&self) -> &str { &self.signature.name }
+    fn params(&self) -> &[ValueId] { &self.params }
+    fn entry_block(&self) -> BasicBlockId { self.entry_block }
+
+    fn execute_block(&self, vm: &mut VM, block: BasicBlockId) -> Result<Option<ControlFlow>, VMError> {
+        let block = self.get_block(block)
+            .ok_or_else(|| VMError::InvalidBasicBlock(format!("Block {} not found", block)))?;
+        for (index, instruction) in block.all_instructions().enumerate() {
+            vm.pc = index;
+            match vm.execute_instruction(instruction)? {
+                ControlFlow::Continue => continue,
+                flow => return Ok(Some(flow)),
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl ExecutableFunction for MirFunctionV2 {
+    fn name(&self) -> &str { &self.signature.name }
+    fn params(&self) -> &[ValueId] { &self.params }
+    fn entry_block(&self) -> BasicBlockId { self.entry_block }
+
+    fn execute_block(&self, vm: &mut VM, block: BasicBlockId) -> Result<Option<ControlFlow>, VMError> {
+        let block = self.get_block(block)
+            .ok_or_else(|| VMError::InvalidBasicBlock(format!("Block {} not found", block)))?;
+        for (index, instruction) in block.instructions.iter().enumerate() {
+            vm.pc = index;
+            match vm.execute_instruction_v2(instruction)? {
+                ControlFlow::Continue => continue,
+                flow => return Ok(Some(flow)),
+            }
+        }
+        Ok(None)
+    }
+}
+
 impl Default for VM {
     fn default() -> Self {
         Self::new()
@@ -3,11 +3,16 @@
  * 
  * Phase 8.2 PoC1: Basic operations (arithmetic, control flow, print)
  * Phase 8.3 PoC2: Reference operations (RefNew/RefGet/RefSet)
+ * Values are typed by `types::infer_module`: i64 integers, f64 floats, i32 bools and Box pointers
+ * Every MirFunction becomes a wasm function; blocks are nested by `structure::ControlFlow`
  */
 
-use crate::mir::{MirModule, MirFunction, MirInstruction, ConstValue, BinaryOp, CompareOp, ValueId, BasicBlockId};
-use super::{WasmError, MemoryManager, RuntimeImports};
-use std::collections::HashMap;
+use crate::mir::{MirModule, MirFunction, MirInstruction, MirType, ConstValue, BinaryOp, CompareOp, UnaryOp, TypeOpKind, ValueId, BasicBlockId};
+use super::{WasmError, MemoryManager, RuntimeImports, BoxLayout};
+use super::memory::SHADOW_STACK_END;
+use super::structure::{live_across, ControlFlow};
+use super::types::{infer_module, ModuleTypes, TypeInfo, ValueKind, ARRAY_ELEMENT};
+use std::collections::{HashMap, HashSet};
 
 /// WASM module representation for WAT generation
 pub struct WasmModule {
@@ -76,6 +81,22 @@ pub struct WasmCodegen {
     /// Current function context for local variable management
     current_locals: HashMap<ValueId, u32>,
     next_local_index: u32,
+    /// Inferred representation (i64/f64/i32) of each value in the current function
+    current_types: TypeInfo,
+    /// Result kind of the current function
+    current_result: ValueKind,
+    /// Pointer-holding locals of the current function, in the order of their shadow stack slots
+    current_roots: Vec<ValueId>,
+    /// Whether the current function builds host call argument frames (needs `$args`)
+    current_host_calls: bool,
+    /// Box layouts from the memory manager (field offsets and slot widths)
+    box_layouts: HashMap<String, BoxLayout>,
+    /// Kinds shared between functions (field kinds, call targets)
+    module_types: ModuleTypes,
+    /// Param and result kinds of every function in the module
+    signatures: HashMap<String, (Vec<ValueKind>, ValueKind)>,
+    /// Box types declared in Nyash code (constructor args go to `birth`, not to fields)
+    user_boxes: HashSet<String>,
     /// String literals and their data segment offsets
     string_literals: HashMap<String, u32>,
     next_data_offset: u32,
@@ -86,6 +107,14 @@ impl WasmCodegen {
         Self {
             current_locals: HashMap::new(),
             next_local_index: 0,
+            current_types: TypeInfo::default(),
+            current_result: ValueKind::Void,
+            current_roots: Vec::new(),
+            current_host_calls: false,
+            box_layouts: HashMap::new(),
+            module_types: ModuleTypes::default(),
+            signatures: HashMap::new(),
+            user_boxes: HashSet::new(),
             string_literals: HashMap::new(),
             next_data_offset: 0x1000, // Start data after initial heap space
         }
@@ -95,10 +124,23 @@ impl WasmCodegen {
     pub fn generate_module(
         &mut self, 
         mir_module: MirModule, 
-        memory_manager: &MemoryManager, 
+        memory_manager: &mut MemoryManager, 
         runtime: &RuntimeImports
     ) -> Result<WasmModule, WasmError> {
         let mut wasm_module = WasmModule::new();
+        self.module_types = infer_module(&mir_module, memory_manager.box_layouts());
+        self.register_user_boxes(memory_manager);
+        self.box_layouts = memory_manager.box_layouts().clone();
+        self.signatures = mir_module.functions.iter()
+            .map(|(name, function)| {
+                let info = self.module_types.infos.get(name);
+                let params = function.params.iter()
+                    .map(|p| info.map_or(ValueKind::Box, |info| info.kind(*p)))
+                    .collect();
+                let result = self.module_types.results.get(name).copied().unwrap_or(ValueKind::Void);
+                (name.clone(), (params, result))
+            })
+            .collect();
         
         // Add memory declaration (64KB initial)
         wasm_module.memory = "(memory (export \"memory\") 1)".to_string();
@@ -106,26 +148,49 @@ impl WasmCodegen {
         // Add runtime imports (env.print for debugging)
         wasm_module.imports.extend(runtime.get_imports());
         
-        // Add globals (heap pointer)
-        wasm_module.globals.extend(memory_manager.get_globals());
-        
         // Add memory management functions
         wasm_module.functions.push(memory_manager.get_malloc_function());
         wasm_module.functions.push(memory_manager.get_generic_box_alloc_function());
         
-        // Add Box-specific allocation functions for known types
-        for box_type in ["StringBox", "IntegerBox", "BoolBox", "DataBox"] {
+        // Add Box-specific allocation functions for known types and the module's user boxes
+        let mut user_boxes: Vec<&String> = self.user_boxes.iter().collect();
+        user_boxes.sort();
+        let builtin = ["StringBox", "IntegerBox", "BoolBox", "DataBox", "ArrayBox", "HostBox"];
+        for box_type in builtin.into_iter().chain(user_boxes.into_iter().map(String::as_str)) {
             if let Ok(alloc_func) = memory_manager.get_box_alloc_function(box_type) {
                 wasm_module.functions.push(alloc_func);
             }
         }
         
-        // Generate functions
-        for (name, function) in &mir_module.functions {
-            let wasm_function = self.generate_function(name, function.clone())?;
+        // Add StringBox, ArrayBox and HostBox helpers (construction, concatenation, comparison,
+        // element slots, host handles)
+        wasm_module.functions.extend(memory_manager.get_string_functions());
+        wasm_module.functions.extend(memory_manager.get_array_functions());
+        wasm_module.functions.extend(memory_manager.get_host_box_functions());
+        
+        // Add the collector, tracing user box fields and array elements that hold pointers
+        let trace_array_elements = self.array_element_kind().is_pointer();
+        wasm_module.functions.extend(memory_manager.get_gc_functions(&self.traced_boxes(), trace_array_elements));
+        
+        // Generate functions (sorted so the output is deterministic)
+        let mut names: Vec<&String> = mir_module.functions.keys().collect();
+        names.sort();
+        for name in names {
+            let wasm_function = self.generate_function(name, &mir_module.functions[name])?;
             wasm_module.functions.push(wasm_function);
+            
+            // Tell the host how to decode main's result
+            if name == "main" {
+                wasm_module.globals.push(format!(
+                    "(global $result_kind i32 (i32.const {}))", self.current_result.code()
+                ));
+                wasm_module.exports.push("(export \"result_kind\" (global $result_kind))".to_string());
+            }
         }
         
+        // Add globals (heap pointer above the string literals)
+        wasm_module.globals.extend(memory_manager.get_globals_above(self.next_data_offset));
+        
         // Add string literal data segments
         wasm_module.data_segments.extend(self.generate_data_segments());
         
@@ -134,43 +199,131 @@ impl WasmCodegen {
             wasm_module.exports.push("(export \"main\" (func $main))".to_string());
         }
         
+        // The host allocates strings and HostBox proxies for the results of `env.box_call`
+        for export in ["malloc", "string_new", "host_box_new"] {
+            wasm_module.exports.push(format!("(export \"{}\" (func ${}))", export, export));
+        }
+        
         Ok(wasm_module)
     }
     
+    /// Give each user box a layout with an 8-byte slot per field its code accesses
+    fn register_user_boxes(&mut self, memory_manager: &mut MemoryManager) {
+        let mut candidates: HashSet<String> = self.module_types.box_fields.keys().cloned().collect();
+        for info in self.module_types.infos.values() {
+            candidates.extend(info.box_types.values().cloned());
+        }
+        
+        self.user_boxes = candidates.into_iter()
+            .filter(|box_type| {
+                // Builtin layouts keep their own fields; custom ones are rebuilt for each module
+                let custom = memory_manager.get_box_layout(box_type).is_none_or(|l| l.type_id >= 0x2000);
+                custom && (self.module_types.is_user_box(box_type) || self.module_types.box_fields.contains_key(box_type))
+            })
+            .collect();
+        
+        // The collector dispatches on type_id, so hashed ids that collide are moved apart
+        let mut user_boxes: Vec<&String> = self.user_boxes.iter().collect();
+        user_boxes.sort();
+        let mut used_ids: HashSet<u32> = memory_manager.box_layouts().iter()
+            .filter(|(name, _)| !self.user_boxes.contains(*name))
+            .map(|(_, layout)| layout.type_id)
+            .collect();
+        for box_type in user_boxes {
+            let mut layout = BoxLayout::new(box_type);
+            while !used_ids.insert(layout.type_id) {
+                layout.type_id += 1;
+            }
+            for field in self.module_types.box_fields.get(box_type).into_iter().flatten() {
+                layout.add_field_sized(field.clone(), 8);
+            }
+            memory_manager.register_box_layout(box_type.clone(), layout);
+        }
+    }
+    
+    /// type_id and pointer field offsets of each user box, for `$gc_trace`
+    fn traced_boxes(&self) -> Vec<(u32, Vec<u32>)> {
+        let mut user_boxes: Vec<&String> = self.user_boxes.iter().collect();
+        user_boxes.sort();
+        user_boxes.into_iter()
+            .filter_map(|box_type| {
+                let layout = self.box_layouts.get(box_type)?;
+                let offsets: Vec<u32> = layout.ordered_fields().into_iter()
+                    .filter(|field| self.module_types.field_kind(box_type, field).is_none_or(ValueKind::is_pointer))
+                    .filter_map(|field| layout.get_field_offset(field))
+                    .collect();
+                (!offsets.is_empty()).then_some((layout.type_id, offsets))
+            })
+            .collect()
+    }
+    
     /// Generate WASM function from MIR function
-    fn generate_function(&mut self, name: &str, mir_function: MirFunction) -> Result<String, WasmError> {
+    fn generate_function(&mut self, name: &str, mir_function: &MirFunction) -> Result<String, WasmError> {
         // Reset local variable tracking for this function
         self.current_locals.clear();
         self.next_local_index = 0;
+        self.current_types = self.module_types.infos.remove(name).unwrap_or_default();
+        self.current_result = self.signatures.get(name).map_or(ValueKind::Void, |(_, result)| *result);
         
         let mut function_body = String::new();
         function_body.push_str(&format!("(func ${}", name));
         
+        // Parameters are the first locals
+        for param in &mir_function.params {
+            function_body.push_str(&format!(" (param ${} {})", param.as_u32(), self.kind(*param).wasm_type()));
+        }
+        
         // Add return type if not void
-        match mir_function.signature.return_type {
-            crate::mir::MirType::Integer => function_body.push_str(" (result i32)"),
-            crate::mir::MirType::Bool => function_body.push_str(" (result i32)"),
-            crate::mir::MirType::Void => {}, // No return type
-            _ => return Err(WasmError::UnsupportedInstruction(
-                format!("Unsupported return type: {:?}", mir_function.signature.return_type)
-            )),
+        if self.current_result != ValueKind::Void {
+            function_body.push_str(&format!(" (result {})", self.current_result.wasm_type()));
         }
         
-        // Collect all local variables needed
-        let local_count = self.count_locals(&mir_function)?;
-        if local_count > 0 {
-            // Declare individual local variables for each ValueId
-            for i in 0..local_count {
-                function_body.push_str(&format!(" (local ${} i32)", i));
+        // Collect all local variables needed, typed by their inferred kind
+        let local_count = self.count_locals(mir_function)?;
+        for i in 0..local_count {
+            let value_id = ValueId::new(i);
+            if !mir_function.params.contains(&value_id) {
+                function_body.push_str(&format!(" (local ${} {})", i, self.kind(value_id).wasm_type()));
             }
         }
         
+        // Pointers that stay live across a collection point (a safepoint or a call) are GC roots
+        let live = live_across(mir_function, |instruction| {
+            matches!(instruction, MirInstruction::Safepoint)
+                || self.current_types.callee(instruction, &self.module_types).is_some()
+        });
+        self.current_roots = (0..local_count).map(ValueId::new)
+            .filter(|v| live.contains(v) && self.kind(*v).is_pointer())
+            .collect();
+        
+        // Generate body as nested block/loop/if starting from the entry block
+        self.current_host_calls = false;
+        let cfg = ControlFlow::analyze(mir_function)?;
+        let mut body = self.generate_frame_prologue();
+        self.generate_tree(mir_function, &cfg, mir_function.entry_block, &mut body)?;
+        
+        // Every path ends in return/br, but validation still needs a value at the end
+        if self.current_result != ValueKind::Void {
+            body.push("unreachable".to_string());
+        }
+        
+        if !self.current_roots.is_empty() {
+            function_body.push_str(" (local $frame i32)");
+        }
+        if self.current_host_calls {
+            function_body.push_str(" (local $args i32)");
+        }
         function_body.push('\n');
         
-        // Generate body from entry block
-        let entry_instructions = self.generate_basic_block(&mir_function, mir_function.entry_block)?;
-        for instruction in entry_instructions {
-            function_body.push_str(&format!("    {}\n", instruction));
+        let mut depth = 0usize;
+        for instruction in body {
+            if instruction == "end" || instruction == "else" {
+                depth = depth.saturating_sub(1);
+            }
+            function_body.push_str(&format!("    {}{}\n", "  ".repeat(depth), instruction));
+            if instruction == "else" || ["block", "loop", "if"].iter().any(|k| instruction.split(' ').next() == Some(k)) {
+                depth += 1;
+            }
         }
         
         function_body.push_str("  )");
@@ -179,21 +332,21 @@ impl WasmCodegen {
     
     /// Count local variables needed for the function
     fn count_locals(&mut self, mir_function: &MirFunction) -> Result<u32, WasmError> {
-        let mut max_value_id = 0;
+        let mut max_value_id = mir_function.params.len() as u32;
         
-        for (_, block) in &mir_function.blocks {
-            for instruction in &block.instructions {
+        for block in mir_function.blocks.values() {
+            for instruction in block.all_instructions() {
                 if let Some(value_id) = instruction.dst_value() {
-                    max_value_id = max_value_id.max(value_id.as_u32());
+                    max_value_id = max_value_id.max(value_id.as_u32() + 1);
                 }
                 for used_value in instruction.used_values() {
-                    max_value_id = max_value_id.max(used_value.as_u32());
+                    max_value_id = max_value_id.max(used_value.as_u32() + 1);
                 }
             }
         }
         
         // Assign local indices to value IDs
-        for i in 0..=max_value_id {
+        for i in 0..max_value_id {
             let value_id = ValueId::new(i);
             self.current_locals.insert(value_id, self.next_local_index);
             self.next_local_index += 1;
@@ -202,25 +355,89 @@ impl WasmCodegen {
         Ok(self.next_local_index)
     }
     
-    /// Generate WASM instructions for a basic block
-    fn generate_basic_block(&mut self, mir_function: &MirFunction, block_id: BasicBlockId) -> Result<Vec<String>, WasmError> {
-        let block = mir_function.blocks.get(&block_id)
-            .ok_or_else(|| WasmError::CodegenError(format!("Basic block {:?} not found", block_id)))?;
+    /// Emit `block` and the blocks it dominates; loop headers are wrapped in `loop`
+    fn generate_tree(&mut self, mir_function: &MirFunction, cfg: &ControlFlow, block: BasicBlockId, out: &mut Vec<String>) -> Result<(), WasmError> {
+        let merges = cfg.merge_children(block);
+        if cfg.is_loop_header(block) {
+            out.push(format!("loop $loop_{}", block.as_u32()));
+            self.generate_within(mir_function, cfg, block, &merges, out)?;
+            out.push("end".to_string());
+            Ok(())
+        } else {
+            self.generate_within(mir_function, cfg, block, &merges, out)
+        }
+    }
+    
+    /// Emit `block` with a wasm `block` around it for each merge node it dominates, so that
+    /// forward branches to a merge node are a `br` out of the `block` just before that node
+    fn generate_within(&mut self, mir_function: &MirFunction, cfg: &ControlFlow, block: BasicBlockId, merges: &[BasicBlockId], out: &mut Vec<String>) -> Result<(), WasmError> {
+        if let Some((merge, inner)) = merges.split_first() {
+            out.push(format!("block $merge_{}", merge.as_u32()));
+            self.generate_within(mir_function, cfg, block, inner, out)?;
+            out.push("end".to_string());
+            return self.generate_tree(mir_function, cfg, *merge, out);
+        }
         
-        let mut instructions = Vec::new();
+        let basic_block = mir_function.blocks.get(&block)
+            .ok_or_else(|| WasmError::CodegenError(format!("Basic block {:?} not found", block)))?;
         
-        // Process regular instructions
-        for mir_instruction in &block.instructions {
-            let wasm_instructions = self.generate_instruction(mir_instruction)?;
-            instructions.extend(wasm_instructions);
+        for mir_instruction in &basic_block.instructions {
+            out.extend(self.generate_instruction(mir_instruction)?);
         }
         
-        // Process terminator instruction
-        if let Some(ref terminator) = block.terminator {
-            let wasm_instructions = self.generate_instruction(terminator)?;
-            instructions.extend(wasm_instructions);
+        match &basic_block.terminator {
+            Some(MirInstruction::Jump { target }) => self.generate_branch(mir_function, cfg, block, *target, out),
+            Some(MirInstruction::Branch { condition, then_bb, else_bb }) => {
+                out.extend(self.get_as(*condition, ValueKind::Bool)?);
+                out.push("if".to_string());
+                self.generate_branch(mir_function, cfg, block, *then_bb, out)?;
+                out.push("else".to_string());
+                self.generate_branch(mir_function, cfg, block, *else_bb, out)?;
+                out.push("end".to_string());
+                Ok(())
+            },
+            Some(terminator) => {
+                out.extend(self.generate_instruction(terminator)?);
+                Ok(())
+            },
+            None => Err(WasmError::CodegenError(format!("Basic block {} has no terminator", block))),
+        }
+    }
+    
+    /// Emit control transfer along the edge `from` → `to`, assigning `to`'s phi nodes first
+    fn generate_branch(&mut self, mir_function: &MirFunction, cfg: &ControlFlow, from: BasicBlockId, to: BasicBlockId, out: &mut Vec<String>) -> Result<(), WasmError> {
+        out.extend(self.generate_phi_copies(mir_function, from, to)?);
+        if cfg.is_backward(from, to) {
+            out.push(format!("br $loop_{}", to.as_u32()));
+            Ok(())
+        } else if cfg.is_merge_node(to) {
+            out.push(format!("br $merge_{}", to.as_u32()));
+            Ok(())
+        } else {
+            self.generate_tree(mir_function, cfg, to, out)
+        }
+    }
+    
+    /// Parallel copy of `to`'s phi inputs for the edge from `from`: all inputs are read
+    /// before any phi is written, so phis that swap values stay correct
+    fn generate_phi_copies(&self, mir_function: &MirFunction, from: BasicBlockId, to: BasicBlockId) -> Result<Vec<String>, WasmError> {
+        let Some(target) = mir_function.blocks.get(&to) else { return Ok(vec![]) };
+        let mut instructions = Vec::new();
+        let mut dsts = Vec::new();
+        for instruction in &target.instructions {
+            let MirInstruction::Phi { dst, inputs } = instruction else { continue };
+            // Like the VM, fall back to the first input when no input names this predecessor
+            let input = inputs.iter()
+                .find(|(block, _)| *block == from)
+                .or(inputs.first())
+                .map(|(_, value)| *value)
+                .ok_or_else(|| WasmError::CodegenError(format!("Phi {} has no inputs", dst)))?;
+            instructions.extend(self.get_as(input, self.kind(*dst))?);
+            dsts.push(*dst);
+        }
+        for dst in dsts.into_iter().rev() {
+            instructions.push(self.set(dst)?);
         }
-        
         Ok(instructions)
     }
     
@@ -236,10 +453,20 @@ impl WasmCodegen {
                 self.generate_binop(*dst, *op, *lhs, *rhs)
             },
             
+            MirInstruction::UnaryOp { dst, op, operand } => {
+                self.generate_unaryop(*dst, *op, *operand)
+            },
+            
             MirInstruction::Compare { dst, op, lhs, rhs } => {
                 self.generate_compare(*dst, *op, *lhs, *rhs)
             },
             
+            MirInstruction::Copy { dst, src } => {
+                let mut instructions = self.get_as(*src, self.kind(*dst))?;
+                instructions.push(self.set(*dst)?);
+                Ok(instructions)
+            },
+            
             MirInstruction::Return { value } => {
                 self.generate_return(value.as_ref())
             },
@@ -258,68 +485,27 @@ impl WasmCodegen {
                 ])
             },
             
-            MirInstruction::RefGet { dst, reference, field: _ } => {
-                // Load field value from Box through reference
-                // reference contains Box pointer, field is the field name
-                // For now, assume all fields are at offset 12 (first field after header)
-                // TODO: Add proper field offset calculation
+            MirInstruction::RefGet { dst, reference, field } => {
+                // Load field value from Box through reference, using the Box layout
+                let (offset, kind) = self.field_access(*reference, field)?;
                 Ok(vec![
                     format!("local.get ${}", self.get_local_index(*reference)?),
-                    "i32.const 12".to_string(), // Offset: header (12 bytes) + first field
-                    "i32.add".to_string(),
-                    "i32.load".to_string(),
+                    format!("{}.load offset={}", kind.wasm_type(), offset),
                     format!("local.set ${}", self.get_local_index(*dst)?),
                 ])
             },
             
-            MirInstruction::RefSet { reference, field: _, value } => {
-                // Store field value to Box through reference  
-                // reference contains Box pointer, field is the field name, value is new value
-                // For now, assume all fields are at offset 12 (first field after header)
-                // TODO: Add proper field offset calculation
-                Ok(vec![
-                    format!("local.get ${}", self.get_local_index(*reference)?),
-                    "i32.const 12".to_string(), // Offset: header (12 bytes) + first field
-                    "i32.add".to_string(),
-                    format!("local.get ${}", self.get_local_index(*value)?),
-                    "i32.store".to_string(),
-                ])
+            MirInstruction::RefSet { reference, field, value } => {
+                // Store field value to Box through reference, using the Box layout
+                let (offset, kind) = self.field_access(*reference, field)?;
+                let mut instructions = vec![format!("local.get ${}", self.get_local_index(*reference)?)];
+                instructions.extend(self.get_as(*value, kind)?);
+                instructions.push(format!("{}.store offset={}", kind.wasm_type(), offset));
+                Ok(instructions)
             },
             
             MirInstruction::NewBox { dst, box_type, args } => {
-                // Create a new Box using the generic allocator
-                match box_type.as_str() {
-                    "DataBox" => {
-                        // Use specific allocator for known types
-                        let mut instructions = vec![
-                            "call $alloc_databox".to_string(),
-                            format!("local.set ${}", self.get_local_index(*dst)?),
-                        ];
-                        
-                        // Initialize fields with arguments if provided
-                        for (i, arg) in args.iter().enumerate() {
-                            instructions.extend(vec![
-                                format!("local.get ${}", self.get_local_index(*dst)?),
-                                format!("i32.const {}", 12 + i * 4), // Field offset
-                                "i32.add".to_string(),
-                                format!("local.get ${}", self.get_local_index(*arg)?),
-                                "i32.store".to_string(),
-                            ]);
-                        }
-                        
-                        Ok(instructions)
-                    },
-                    _ => {
-                        // Use generic allocator for unknown types
-                        // This is a fallback - in a real implementation, all Box types should be known
-                        Ok(vec![
-                            "i32.const 8192".to_string(), // Default unknown type ID
-                            format!("i32.const {}", args.len()),
-                            "call $box_alloc".to_string(),
-                            format!("local.set ${}", self.get_local_index(*dst)?),
-                        ])
-                    }
-                }
+                self.generate_new_box(*dst, box_type, args)
             },
             
             // Phase 8.4 PoC3: Extension stubs
@@ -343,32 +529,67 @@ impl WasmCodegen {
             
             MirInstruction::BarrierRead { .. } |
             MirInstruction::BarrierWrite { .. } |
-            MirInstruction::FutureSet { .. } |
-            MirInstruction::Safepoint => {
+            MirInstruction::FutureSet { .. } => {
                 // No-op for now
                 Ok(vec!["nop".to_string()])
             },
             
-            // Control Flow Instructions (Critical for loops and conditions)
-            MirInstruction::Jump { target } => {
-                // Unconditional jump to target basic block
-                // Use WASM br instruction to break to the target block
-                Ok(vec![
-                    format!("br $block_{}", target.as_u32()),
-                ])
+            MirInstruction::Safepoint => {
+                let mut instructions = self.spill_roots()?;
+                instructions.push("call $gc_safepoint".to_string());
+                Ok(instructions)
             },
             
-            MirInstruction::Branch { condition, then_bb, else_bb } => {
-                // Conditional branch based on condition value
-                // Load condition value and branch accordingly
-                Ok(vec![
-                    // Load condition value onto stack
-                    format!("local.get ${}", self.get_local_index(*condition)?),
-                    // If condition is true (non-zero), branch to then_bb
-                    format!("br_if $block_{}", then_bb.as_u32()),
-                    // Otherwise, fall through to else_bb
-                    format!("br $block_{}", else_bb.as_u32()),
-                ])
+            MirInstruction::WeakRef { dst, value, .. } => {
+                // Weak references are plain pointers until the heap tracks ownership
+                let mut instructions = self.get_as(*value, self.kind(*dst))?;
+                instructions.push(self.set(*dst)?);
+                Ok(instructions)
+            },
+            
+            MirInstruction::Barrier { .. } |
+            MirInstruction::Debug { .. } |
+            MirInstruction::Nop => Ok(vec!["nop".to_string()]),
+            
+            // Assigned on the incoming edges by generate_phi_copies
+            MirInstruction::Phi { .. } => Ok(vec![]),
+            
+            // Function calls (Call through a "Name/N" string constant)
+            MirInstruction::Call { dst, .. } => {
+                self.generate_call(instruction, *dst)
+            },
+            
+            // Type operations: Cast copies the value like the VM does
+            MirInstruction::Cast { dst, value, .. } |
+            MirInstruction::TypeOp { dst, op: TypeOpKind::Cast, value, .. } => {
+                let mut instructions = self.get_as(*value, self.kind(*dst))?;
+                instructions.push(self.set(*dst)?);
+                Ok(instructions)
+            },
+            
+            MirInstruction::TypeOp { dst, op: TypeOpKind::Check, value, ty } => {
+                self.generate_type_check(*dst, *value, ty)
+            },
+            
+            MirInstruction::TypeCheck { dst, .. } => {
+                // Matches the VM, where type_check always succeeds
+                Ok(vec!["i32.const 1".to_string(), self.set(*dst)?])
+            },
+            
+            // Array element access (8-byte slots)
+            MirInstruction::ArrayGet { dst, array, index } => {
+                self.generate_array_get(*dst, *array, *index)
+            },
+            
+            MirInstruction::ArraySet { array, index, value } => {
+                self.generate_array_set(*array, Some(*index), *value)
+            },
+            
+            // Exceptions are not unwound: like the VM, a throw aborts execution
+            MirInstruction::Throw { .. } => Ok(vec!["unreachable".to_string()]),
+            
+            MirInstruction::Catch { exception_value, .. } => {
+                Ok(vec![self.kind(*exception_value).zero(), self.set(*exception_value)?])
             },
             
             // Phase 9.7: External Function Calls
@@ -376,7 +597,7 @@ impl WasmCodegen {
                 // Generate call to external function import
                 let call_target = match (iface_name.as_str(), method_name.as_str()) {
                     ("env.console", "log") => "console_log",
-                    ("env.canvas", "fillRect") => "canvas_fillRect", 
+                    ("env.canvas", "fillRect") => "canvas_fillRect",
                     ("env.canvas", "fillText") => "canvas_fillText",
                     _ => return Err(WasmError::UnsupportedInstruction(
                         format!("Unsupported extern call: {}.{}", iface_name, method_name)
@@ -396,7 +617,7 @@ impl WasmCodegen {
                 // Store result if destination is provided
                 if let Some(dst) = dst {
                     // For void functions, we still need to provide a dummy value
-                    instructions.push("i32.const 0".to_string()); // Void result
+                    instructions.push(self.kind(*dst).zero()); // Void result
                     instructions.push(format!("local.set ${}", self.get_local_index(*dst)?));
                 }
                 
@@ -405,6 +626,10 @@ impl WasmCodegen {
             
             // Phase 9.77: BoxCall Implementation - Critical Box method calls
             MirInstruction::BoxCall { dst, box_val, method, args, effects: _ } => {
+                // User box methods are module functions taking the receiver first
+                if self.current_types.callee(instruction, &self.module_types).is_some() {
+                    return self.generate_call(instruction, *dst);
+                }
                 self.generate_box_call(*dst, *box_val, method, args)
             },
             
@@ -418,21 +643,18 @@ impl WasmCodegen {
     /// Generate constant loading
     fn generate_const(&mut self, dst: ValueId, value: &ConstValue) -> Result<Vec<String>, WasmError> {
         let const_instruction = match value {
-            ConstValue::Integer(n) => format!("i32.const {}", n),
+            ConstValue::Integer(n) => format!("i64.const {}", n),
+            ConstValue::Float(f) => format!("f64.const {}", format_f64(*f)),
             ConstValue::Bool(b) => format!("i32.const {}", if *b { 1 } else { 0 }),
-            ConstValue::Void => "i32.const 0".to_string(),
+            ConstValue::Null | ConstValue::Void => "i32.const 0".to_string(),
             ConstValue::String(s) => {
                 // Register the string literal and get its offset
                 let data_offset = self.register_string_literal(s);
                 let string_len = s.len() as u32;
                 
                 // Generate code to allocate a StringBox and return its pointer
-                // This is more complex and will need StringBox allocation
                 return self.generate_string_box_const(dst, data_offset, string_len);
             },
-            _ => return Err(WasmError::UnsupportedInstruction(
-                format!("Unsupported constant type: {:?}", value)
-            )),
         };
         
         Ok(vec![
@@ -441,94 +663,353 @@ impl WasmCodegen {
         ])
     }
     
-    /// Generate binary operation
-    fn generate_binop(&self, dst: ValueId, op: BinaryOp, lhs: ValueId, rhs: ValueId) -> Result<Vec<String>, WasmError> {
-        let wasm_op = match op {
-            BinaryOp::Add => "i32.add",
-            BinaryOp::Sub => "i32.sub", 
-            BinaryOp::Mul => "i32.mul",
-            BinaryOp::Div => "i32.div_s",
-            BinaryOp::And => "i32.and",
-            BinaryOp::Or => "i32.or",
+    /// Generate binary operation on i64/f64 (or i32 for bools, StringBox for concatenation)
+    fn generate_binop(&mut self, dst: ValueId, op: BinaryOp, lhs: ValueId, rhs: ValueId) -> Result<Vec<String>, WasmError> {
+        let kind = self.kind(dst);
+        let mut instructions = Vec::new();
+        
+        match kind {
+            ValueKind::String => {
+                // String concatenation: convert both operands to StringBoxes first
+                if op != BinaryOp::Add {
+                    return Err(WasmError::UnsupportedInstruction(
+                        format!("Unsupported string operation: {:?}", op)
+                    ));
+                }
+                instructions.extend(self.string_operand(lhs)?);
+                instructions.extend(self.string_operand(rhs)?);
+                instructions.push("call $string_concat".to_string());
+            },
+            ValueKind::Bool => {
+                let wasm_op = match op {
+                    BinaryOp::And | BinaryOp::BitAnd => "i32.and",
+                    BinaryOp::Or | BinaryOp::BitOr => "i32.or",
+                    BinaryOp::BitXor => "i32.xor",
+                    _ => return Err(WasmError::UnsupportedInstruction(
+                        format!("Unsupported boolean operation: {:?}", op)
+                    )),
+                };
+                instructions.extend(self.get_as(lhs, ValueKind::Bool)?);
+                instructions.extend(self.get_as(rhs, ValueKind::Bool)?);
+                instructions.push(wasm_op.to_string());
+            },
+            ValueKind::Integer => {
+                let wasm_op = match op {
+                    BinaryOp::Add => "i64.add",
+                    BinaryOp::Sub => "i64.sub",
+                    BinaryOp::Mul => "i64.mul",
+                    BinaryOp::Div => "i64.div_s",
+                    BinaryOp::Mod => "i64.rem_s",
+                    BinaryOp::BitAnd => "i64.and",
+                    BinaryOp::BitOr => "i64.or",
+                    BinaryOp::BitXor => "i64.xor",
+                    BinaryOp::Shl => "i64.shl",
+                    BinaryOp::Shr => "i64.shr_s",
+                    BinaryOp::And | BinaryOp::Or => unreachable!("logical operations produce Bool"),
+                };
+                instructions.extend(self.get_as(lhs, ValueKind::Integer)?);
+                instructions.extend(self.get_as(rhs, ValueKind::Integer)?);
+                instructions.push(wasm_op.to_string());
+            },
+            ValueKind::Float => {
+                let wasm_op = match op {
+                    BinaryOp::Add => "f64.add",
+                    BinaryOp::Sub => "f64.sub",
+                    BinaryOp::Mul => "f64.mul",
+                    BinaryOp::Div => "f64.div",
+                    BinaryOp::Mod => {
+                        // No f64.rem in WASM: a - trunc(a / b) * b (same sign as a, like Rust's %)
+                        instructions.extend(self.get_as(lhs, ValueKind::Float)?);
+                        instructions.extend(self.get_as(lhs, ValueKind::Float)?);
+                        instructions.extend(self.get_as(rhs, ValueKind::Float)?);
+                        instructions.extend(["f64.div".to_string(), "f64.trunc".to_string()]);
+                        instructions.extend(self.get_as(rhs, ValueKind::Float)?);
+                        instructions.extend(["f64.mul".to_string(), "f64.sub".to_string()]);
+                        instructions.push(self.set(dst)?);
+                        return Ok(instructions);
+                    },
+                    _ => return Err(WasmError::UnsupportedInstruction(
+                        format!("Unsupported float operation: {:?}", op)
+                    )),
+                };
+                instructions.extend(self.get_as(lhs, ValueKind::Float)?);
+                instructions.extend(self.get_as(rhs, ValueKind::Float)?);
+                instructions.push(wasm_op.to_string());
+            },
             _ => return Err(WasmError::UnsupportedInstruction(
-                format!("Unsupported binary operation: {:?}", op)
+                format!("Unsupported binary operation {:?} on {:?} values", op, kind)
             )),
-        };
+        }
         
-        Ok(vec![
-            format!("local.get ${}", self.get_local_index(lhs)?),
-            format!("local.get ${}", self.get_local_index(rhs)?),
-            wasm_op.to_string(),
-            format!("local.set ${}", self.get_local_index(dst)?),
-        ])
+        instructions.push(self.set(dst)?);
+        Ok(instructions)
+    }
+    
+    /// Generate unary operation
+    fn generate_unaryop(&mut self, dst: ValueId, op: UnaryOp, operand: ValueId) -> Result<Vec<String>, WasmError> {
+        let mut instructions = Vec::new();
+        match (op, self.kind(operand)) {
+            (UnaryOp::Neg, ValueKind::Integer) => {
+                instructions.push("i64.const 0".to_string());
+                instructions.extend(self.get_as(operand, ValueKind::Integer)?);
+                instructions.push("i64.sub".to_string());
+            },
+            (UnaryOp::Neg, ValueKind::Float) => {
+                instructions.extend(self.get_as(operand, ValueKind::Float)?);
+                instructions.push("f64.neg".to_string());
+            },
+            (UnaryOp::Not, _) => {
+                instructions.extend(self.get_as(operand, ValueKind::Bool)?);
+                instructions.push("i32.eqz".to_string());
+            },
+            (UnaryOp::BitNot, _) => {
+                instructions.extend(self.get_as(operand, ValueKind::Integer)?);
+                instructions.extend(["i64.const -1".to_string(), "i64.xor".to_string()]);
+            },
+            (op, kind) => return Err(WasmError::UnsupportedInstruction(
+                format!("Unsupported unary operation {:?} on {:?} value", op, kind)
+            )),
+        }
+        instructions.push(self.set(dst)?);
+        Ok(instructions)
     }
     
     /// Generate comparison operation
-    fn generate_compare(&self, dst: ValueId, op: CompareOp, lhs: ValueId, rhs: ValueId) -> Result<Vec<String>, WasmError> {
-        let wasm_op = match op {
-            CompareOp::Eq => "i32.eq",
-            CompareOp::Ne => "i32.ne",
-            CompareOp::Lt => "i32.lt_s",
-            CompareOp::Le => "i32.le_s",
-            CompareOp::Gt => "i32.gt_s",
-            CompareOp::Ge => "i32.ge_s",
+    fn generate_compare(&mut self, dst: ValueId, op: CompareOp, lhs: ValueId, rhs: ValueId) -> Result<Vec<String>, WasmError> {
+        let (l, r) = (self.kind(lhs), self.kind(rhs));
+        let suffix = |signed: &str| match op {
+            CompareOp::Eq => "eq".to_string(),
+            CompareOp::Ne => "ne".to_string(),
+            CompareOp::Lt => format!("lt{}", signed),
+            CompareOp::Le => format!("le{}", signed),
+            CompareOp::Gt => format!("gt{}", signed),
+            CompareOp::Ge => format!("ge{}", signed),
         };
         
-        Ok(vec![
-            format!("local.get ${}", self.get_local_index(lhs)?),
-            format!("local.get ${}", self.get_local_index(rhs)?),
-            wasm_op.to_string(),
-            format!("local.set ${}", self.get_local_index(dst)?),
-        ])
+        let mut instructions = Vec::new();
+        if l == ValueKind::String && r == ValueKind::String {
+            // Compare contents: $string_compare returns -1/0/1
+            instructions.extend([
+                format!("local.get ${}", self.get_local_index(lhs)?),
+                format!("local.get ${}", self.get_local_index(rhs)?),
+                "call $string_compare".to_string(),
+                "i32.const 0".to_string(),
+                format!("i32.{}", suffix("_s")),
+            ]);
+        } else if l == ValueKind::Float || r == ValueKind::Float {
+            instructions.extend(self.get_as(lhs, ValueKind::Float)?);
+            instructions.extend(self.get_as(rhs, ValueKind::Float)?);
+            instructions.push(format!("f64.{}", suffix("")));
+        } else if l == ValueKind::Integer || r == ValueKind::Integer {
+            instructions.extend(self.get_as(lhs, ValueKind::Integer)?);
+            instructions.extend(self.get_as(rhs, ValueKind::Integer)?);
+            instructions.push(format!("i64.{}", suffix("_s")));
+        } else {
+            // Bools and Box pointers
+            instructions.extend([
+                format!("local.get ${}", self.get_local_index(lhs)?),
+                format!("local.get ${}", self.get_local_index(rhs)?),
+                format!("i32.{}", suffix("_u")),
+            ]);
+        }
+        
+        instructions.push(self.set(dst)?);
+        Ok(instructions)
     }
     
     /// Generate return instruction
     fn generate_return(&self, value: Option<&ValueId>) -> Result<Vec<String>, WasmError> {
-        if let Some(value_id) = value {
-            Ok(vec![
-                format!("local.get ${}", self.get_local_index(*value_id)?),
-                "return".to_string(),
-            ])
-        } else {
-            Ok(vec!["return".to_string()])
+        let mut instructions = Vec::new();
+        match value {
+            Some(value_id) if self.current_result != ValueKind::Void => {
+                instructions.extend(self.get_as(*value_id, self.current_result)?);
+            },
+            None if self.current_result != ValueKind::Void => {
+                instructions.push(self.current_result.zero());
+            },
+            _ => {},
+        }
+        if !self.current_roots.is_empty() {
+            instructions.push("local.get $frame".to_string());
+            instructions.push("global.set $sp".to_string());
+        }
+        instructions.push("return".to_string());
+        Ok(instructions)
+    }
+    
+    /// Reserve a shadow stack slot for each root (traps when the shadow stack overflows)
+    fn generate_frame_prologue(&self) -> Vec<String> {
+        if self.current_roots.is_empty() {
+            return Vec::new();
+        }
+        vec![
+            "global.get $sp".to_string(),
+            "local.tee $frame".to_string(),
+            format!("i32.const {}", self.current_roots.len() * 4),
+            "i32.add".to_string(),
+            "global.set $sp".to_string(),
+            "global.get $sp".to_string(),
+            format!("i32.const {}", SHADOW_STACK_END),
+            "i32.gt_u".to_string(),
+            "if".to_string(),
+            "unreachable".to_string(),
+            "end".to_string(),
+        ]
+    }
+    
+    /// Store the current value of every root in its shadow stack slot, so a collection
+    /// (here or in a callee) sees them
+    fn spill_roots(&self) -> Result<Vec<String>, WasmError> {
+        let mut instructions = Vec::new();
+        for (slot, root) in self.current_roots.iter().enumerate() {
+            instructions.push("local.get $frame".to_string());
+            instructions.push(format!("local.get ${}", self.get_local_index(*root)?));
+            instructions.push(format!("i32.store offset={}", slot * 4));
+        }
+        Ok(instructions)
+    }
+    
+    /// Generate Box creation: builtin value boxes stay unboxed, others use their layout
+    fn generate_new_box(&mut self, dst: ValueId, box_type: &str, args: &[ValueId]) -> Result<Vec<String>, WasmError> {
+        // `new IntegerBox(%c)` is just the value itself
+        if let Some(kind) = ValueKind::from_box_type(box_type) {
+            let mut instructions = match (args.first(), kind) {
+                (Some(arg), _) => self.get_as(*arg, kind)?,
+                (None, ValueKind::String) => vec![
+                    "i32.const 0".to_string(),
+                    "i32.const 0".to_string(),
+                    "call $string_new".to_string(),
+                ],
+                (None, _) => vec![kind.zero()],
+            };
+            instructions.push(self.set(dst)?);
+            return Ok(instructions);
+        }
+        
+        // Boxes without a layout (MapBox, FileBox, plugin boxes...) are created on the host
+        let Some(layout) = self.box_layouts.get(box_type) else {
+            let mut instructions = self.string_literal(box_type);
+            instructions.extend(self.host_args_frame(args, ValueKind::Void)?);
+            instructions.extend([
+                "local.get $args".to_string(),
+                "call $box_new".to_string(),
+                "call $host_box_new".to_string(),
+                self.set(dst)?,
+            ]);
+            return Ok(instructions);
+        };
+        
+        // Use specific allocator for known types
+        let mut instructions = vec![
+            format!("call $alloc_{}", box_type.to_lowercase()),
+            format!("local.set ${}", self.get_local_index(dst)?),
+        ];
+        
+        // Initialize fields (in layout order) with arguments if provided, zero the rest;
+        // user boxes receive their arguments in `birth` instead
+        let fields: Vec<(String, u32)> = layout.ordered_fields().into_iter()
+            .map(|field| (field.to_string(), layout.get_field_size(field).unwrap_or(4)))
+            .collect();
+        let args = if self.user_boxes.contains(box_type) { &[][..] } else { args };
+        for (i, (field, size)) in fields.iter().enumerate() {
+            instructions.push(format!("local.get ${}", self.get_local_index(dst)?));
+            if let Some(arg) = args.get(i) {
+                let (offset, kind) = self.field_access(dst, field)?;
+                instructions.extend(self.get_as(*arg, kind)?);
+                instructions.push(format!("{}.store offset={}", kind.wasm_type(), offset));
+            } else {
+                let slot = if *size == 8 { "i64" } else { "i32" };
+                let offset = layout.get_field_offset(field).unwrap_or_default();
+                instructions.push(format!("{}.const 0", slot));
+                instructions.push(format!("{}.store offset={}", slot, offset));
+            }
         }
+        
+        Ok(instructions)
+    }
+    
+    /// Offset and stored kind of a field of the Box behind `reference`
+    fn field_access(&self, reference: ValueId, field: &str) -> Result<(u32, ValueKind), WasmError> {
+        let box_type = self.current_types.box_types.get(&reference)
+            .ok_or_else(|| WasmError::CodegenError(
+                format!("Unknown Box type for field access .{} on ValueId({})", field, reference.as_u32())
+            ))?;
+        let layout = self.box_layouts.get(box_type)
+            .ok_or_else(|| WasmError::MemoryError(format!("Unknown box type: {}", box_type)))?;
+        let offset = layout.get_field_offset(field)
+            .ok_or_else(|| WasmError::MemoryError(format!("{} has no field '{}'", box_type, field)))?;
+        let size = layout.get_field_size(field).unwrap_or(4);
+        
+        let kind = self.module_types.field_kind(box_type, field).unwrap_or(ValueKind::Box);
+        let width = match kind.wasm_type() {
+            "i32" => 4,
+            _ => 8,
+        };
+        if width > size {
+            return Err(WasmError::MemoryError(format!(
+                "Field {}.{} is {} bytes wide but holds {:?} values", box_type, field, size, kind
+            )));
+        }
+        Ok((offset, kind))
     }
     
     /// Generate StringBox allocation for a string constant
     fn generate_string_box_const(&self, dst: ValueId, data_offset: u32, string_len: u32) -> Result<Vec<String>, WasmError> {
-        // Allocate a StringBox using the StringBox allocator
         // StringBox layout: [type_id:0x1001][ref_count:1][field_count:2][data_ptr:offset][length:len]
         Ok(vec![
-            // Call StringBox allocator function
-            "call $alloc_stringbox".to_string(),
-            // Store the result (StringBox pointer) in local variable
-            format!("local.set ${}", self.get_local_index(dst)?),
-            
-            // Initialize StringBox fields
-            // Get StringBox pointer back
-            format!("local.get ${}", self.get_local_index(dst)?),
-            // Set data_ptr field (offset 12 from StringBox pointer)
-            "i32.const 12".to_string(),
-            "i32.add".to_string(),
             format!("i32.const {}", data_offset),
-            "i32.store".to_string(),
-            
-            // Get StringBox pointer again  
-            format!("local.get ${}", self.get_local_index(dst)?),
-            // Set length field (offset 16 from StringBox pointer)
-            "i32.const 16".to_string(),
-            "i32.add".to_string(),
             format!("i32.const {}", string_len),
-            "i32.store".to_string(),
+            "call $string_new".to_string(),
+            format!("local.set ${}", self.get_local_index(dst)?),
         ])
     }
     
-    /// Generate print instruction (calls env.print import)
+    /// Load a value as a StringBox pointer for concatenation
+    fn string_operand(&mut self, value: ValueId) -> Result<Vec<String>, WasmError> {
+        let get = format!("local.get ${}", self.get_local_index(value)?);
+        match self.kind(value) {
+            ValueKind::String => Ok(vec![get]),
+            ValueKind::Integer => Ok(vec![get, "call $string_from_i64".to_string()]),
+            ValueKind::Bool => {
+                let mut instructions = vec![get, "if (result i32)".to_string()];
+                instructions.extend(self.string_literal("true"));
+                instructions.push("else".to_string());
+                instructions.extend(self.string_literal("false"));
+                instructions.push("end".to_string());
+                Ok(instructions)
+            },
+            kind => Err(WasmError::UnsupportedInstruction(
+                format!("String concatenation with a {:?} value", kind)
+            )),
+        }
+    }
+    
+    /// Push a new StringBox for a literal onto the stack
+    fn string_literal(&mut self, s: &str) -> Vec<String> {
+        let data_offset = self.register_string_literal(s);
+        vec![
+            format!("i32.const {}", data_offset),
+            format!("i32.const {}", s.len()),
+            "call $string_new".to_string(),
+        ]
+    }
+    
+    /// Generate print instruction (typed env.print_* import, or env.print for Boxes)
     fn generate_print(&self, value: ValueId) -> Result<Vec<String>, WasmError> {
-        Ok(vec![
-            format!("local.get ${}", self.get_local_index(value)?),
-            "call $print".to_string(),
-        ])
+        let get = format!("local.get ${}", self.get_local_index(value)?);
+        Ok(match self.kind(value) {
+            ValueKind::Integer => vec![get, "call $print_i64".to_string()],
+            ValueKind::Float => vec![get, "call $print_f64".to_string()],
+            ValueKind::Bool => vec![get, "call $print_bool".to_string()],
+            ValueKind::String => vec![
+                get.clone(),
+                "i32.load offset=12".to_string(),
+                get,
+                "i32.load offset=16".to_string(),
+                "call $print_str".to_string(),
+            ],
+            ValueKind::Box | ValueKind::Void => vec![get, "call $print".to_string()],
+        })
     }
     
     /// Register a string literal and return its data offset
@@ -576,33 +1057,282 @@ impl WasmCodegen {
             .ok_or_else(|| WasmError::CodegenError(format!("Local variable not found for ValueId: {:?}", value_id)))
     }
     
+    /// Inferred kind of a value in the current function
+    fn kind(&self, value_id: ValueId) -> ValueKind {
+        self.current_types.kind(value_id)
+    }
+    
+    fn set(&self, value_id: ValueId) -> Result<String, WasmError> {
+        Ok(format!("local.set ${}", self.get_local_index(value_id)?))
+    }
+    
+    /// Load a value converted to `kind` (ints widen to floats, bools to 0/1)
+    fn get_as(&self, value_id: ValueId, kind: ValueKind) -> Result<Vec<String>, WasmError> {
+        let mut instructions = vec![format!("local.get ${}", self.get_local_index(value_id)?)];
+        let from = self.kind(value_id);
+        let conversion: &[&str] = match (from, kind) {
+            (a, b) if a == b => &[],
+            (ValueKind::Integer, ValueKind::Float) => &["f64.convert_i64_s"],
+            (ValueKind::Float, ValueKind::Integer) => &["i64.trunc_f64_s"],
+            (ValueKind::Bool, ValueKind::Integer) => &["i64.extend_i32_u"],
+            (ValueKind::Bool, ValueKind::Float) => &["f64.convert_i32_u"],
+            (ValueKind::Integer, ValueKind::Bool) => &["i64.const 0", "i64.ne"],
+            (ValueKind::Float, ValueKind::Bool) => &["f64.const 0", "f64.ne"],
+            (a, b) if a.wasm_type() == b.wasm_type() => &[],
+            (ValueKind::Void, _) => {
+                instructions.extend(["drop".to_string(), kind.zero()]);
+                &[]
+            },
+            _ => return Err(WasmError::CodegenError(
+                format!("Cannot convert {:?} value ValueId({}) to {:?}", from, value_id.as_u32(), kind)
+            )),
+        };
+        instructions.extend(conversion.iter().map(|s| s.to_string()));
+        Ok(instructions)
+    }
+    
     /// Phase 9.77: Generate BoxCall method invocation
     /// Implements critical Box methods: toString, print, equals, clone
     fn generate_box_call(&mut self, dst: Option<ValueId>, box_val: ValueId, method: &str, args: &[ValueId]) -> Result<Vec<String>, WasmError> {
+        // Every method of a host box (constructor arguments already went to `env.box_new`)
+        let box_type = self.current_types.box_types.get(&box_val);
+        if box_type.is_some_and(|t| self.is_host_box(t)) {
+            if method == "birth" {
+                return Ok(vec![]);
+            }
+            return self.generate_host_call(dst, box_val, method, args);
+        }
+        
+        if self.current_types.box_types.get(&box_val).map(String::as_str) == Some("ArrayBox") {
+            match (method, args) {
+                ("push", [value]) => return self.generate_array_set(box_val, None, *value),
+                ("set", [index, value]) => return self.generate_array_set(box_val, Some(*index), *value),
+                ("get", [index]) => {
+                    let Some(dst) = dst else { return Ok(vec![]) };
+                    return self.generate_array_get(dst, box_val, *index);
+                },
+                ("length", []) => {
+                    let Some(dst) = dst else { return Ok(vec![]) };
+                    let mut instructions = vec![
+                        format!("local.get ${}", self.get_local_index(box_val)?),
+                        "i32.load offset=16".to_string(),
+                        "i64.extend_i32_u".to_string(),
+                    ];
+                    instructions.extend(self.coerce_result(ValueKind::Integer, dst)?);
+                    return Ok(instructions);
+                },
+                _ => {},
+            }
+        }
+        
         match method {
+            // Fields were already initialized by NewBox
+            "birth" if self.kind(box_val) != ValueKind::Box
+                || self.current_types.box_types.get(&box_val).is_some_and(|t| self.box_layouts.contains_key(t)) => Ok(vec![]),
+            "length" if self.kind(box_val) == ValueKind::String => {
+                let Some(dst) = dst else { return Ok(vec![]) };
+                let mut instructions = vec![
+                    format!("local.get ${}", self.get_local_index(box_val)?),
+                    "i32.load offset=16".to_string(),
+                    "i64.extend_i32_u".to_string(),
+                ];
+                instructions.extend(self.coerce_result(ValueKind::Integer, dst)?);
+                Ok(instructions)
+            },
             "toString" => self.generate_to_string_call(dst, box_val),
             "print" => self.generate_print_call(dst, box_val),
             "equals" => self.generate_equals_call(dst, box_val, args),
             "clone" => self.generate_clone_call(dst, box_val),
             "log" => self.generate_log_call(dst, box_val, args),
+            // Boxes of unknown type are assumed to be HostBox proxies (checked at runtime)
+            _ if self.kind(box_val) == ValueKind::Box => self.generate_host_call(dst, box_val, method, args),
             _ => Err(WasmError::UnsupportedInstruction(
                 format!("Unsupported BoxCall method: {}", method)
             ))
         }
     }
     
+    /// Box types that live on the host: neither unboxed values nor laid out in linear memory
+    fn is_host_box(&self, box_type: &str) -> bool {
+        ValueKind::from_box_type(box_type).is_none() && !self.box_layouts.contains_key(box_type)
+    }
+    
+    /// Call a method of a HostBox through `env.box_call`; the result is read back from the
+    /// argument frame as the kind of `dst`
+    fn generate_host_call(&mut self, dst: Option<ValueId>, box_val: ValueId, method: &str, args: &[ValueId]) -> Result<Vec<String>, WasmError> {
+        let want = dst.map_or(ValueKind::Void, |dst| self.kind(dst));
+        let mut instructions = vec![
+            format!("local.get ${}", self.get_local_index(box_val)?),
+            "call $host_handle".to_string(),
+        ];
+        instructions.extend(self.string_literal(method));
+        instructions.extend(self.host_args_frame(args, want)?);
+        instructions.extend([
+            "local.get $args".to_string(),
+            "call $box_call".to_string(),
+            "drop".to_string(),
+        ]);
+        if let Some(dst) = dst {
+            if want == ValueKind::Void {
+                instructions.push(want.zero());
+            } else {
+                instructions.push("local.get $args".to_string());
+                instructions.push(format!("{}.load offset=8", want.wasm_type()));
+            }
+            instructions.push(self.set(dst)?);
+        }
+        Ok(instructions)
+    }
+    
+    /// Allocate and fill an argument frame for `env.box_new` / `env.box_call` into `$args`:
+    /// [count:i32][want:i32] then a 16-byte [tag:i32][pad:i32][payload:8] slot per argument
+    fn host_args_frame(&mut self, args: &[ValueId], want: ValueKind) -> Result<Vec<String>, WasmError> {
+        self.current_host_calls = true;
+        let mut instructions = vec![
+            format!("i32.const {}", 8 + 16 * args.len()),
+            "call $malloc".to_string(),
+            "local.tee $args".to_string(),
+            format!("i32.const {}", args.len()),
+            "i32.store".to_string(),
+            "local.get $args".to_string(),
+            format!("i32.const {}", want.code()),
+            "i32.store offset=4".to_string(),
+        ];
+        for (i, arg) in args.iter().enumerate() {
+            let slot = 8 + 16 * i;
+            let kind = self.kind(*arg);
+            instructions.extend([
+                "local.get $args".to_string(),
+                format!("i32.const {}", kind.code()),
+                format!("i32.store offset={}", slot),
+            ]);
+            if kind != ValueKind::Void {
+                instructions.extend([
+                    "local.get $args".to_string(),
+                    format!("local.get ${}", self.get_local_index(*arg)?),
+                    format!("{}.store offset={}", kind.wasm_type(), slot + 8),
+                ]);
+            }
+        }
+        Ok(instructions)
+    }
+    
+    /// Generate a direct call to a function of this module (`Call` or a user box method `BoxCall`)
+    fn generate_call(&mut self, instruction: &MirInstruction, dst: Option<ValueId>) -> Result<Vec<String>, WasmError> {
+        let (callee, args) = self.current_types.callee(instruction, &self.module_types)
+            .ok_or_else(|| WasmError::UnsupportedInstruction(
+                format!("Call target is not a function of this module: {:?}", instruction)
+            ))?;
+        let (params, result) = self.signatures.get(&callee).cloned()
+            .ok_or_else(|| WasmError::CodegenError(format!("Unknown function: {}", callee)))?;
+        if params.len() != args.len() {
+            return Err(WasmError::CodegenError(format!(
+                "{} expects {} arguments, got {}", callee, params.len(), args.len()
+            )));
+        }
+        
+        let mut instructions = self.spill_roots()?;
+        for (arg, kind) in args.iter().zip(&params) {
+            instructions.extend(self.get_as(*arg, *kind)?);
+        }
+        instructions.push(format!("call ${}", callee));
+        
+        match (dst, result) {
+            (Some(dst), ValueKind::Void) => {
+                instructions.push(self.kind(dst).zero());
+                instructions.push(self.set(dst)?);
+            },
+            (Some(dst), kind) => instructions.extend(self.coerce_result(kind, dst)?),
+            (None, ValueKind::Void) => {},
+            (None, _) => instructions.push("drop".to_string()),
+        }
+        Ok(instructions)
+    }
+    
+    /// Generate `typeop check`: decided statically from the value kind, except for Box pointers
+    /// whose type_id (header offset 0) is compared at runtime
+    fn generate_type_check(&self, dst: ValueId, value: ValueId, ty: &MirType) -> Result<Vec<String>, WasmError> {
+        let kind = self.kind(value);
+        let matches = match ty {
+            MirType::Integer => kind == ValueKind::Integer,
+            MirType::Float => kind == ValueKind::Float,
+            MirType::Bool => kind == ValueKind::Bool,
+            MirType::String => kind == ValueKind::String,
+            MirType::Void => kind == ValueKind::Void,
+            MirType::Box(name) if kind == ValueKind::Box => {
+                let type_id = self.box_layouts.get(name)
+                    .map_or_else(|| BoxLayout::new(name).type_id, |layout| layout.type_id);
+                return Ok(vec![
+                    format!("local.get ${}", self.get_local_index(value)?),
+                    "i32.load".to_string(),
+                    format!("i32.const {}", type_id),
+                    "i32.eq".to_string(),
+                    self.set(dst)?,
+                ]);
+            },
+            MirType::Box(_) => false,
+            _ => true,
+        };
+        Ok(vec![format!("i32.const {}", matches as i32), self.set(dst)?])
+    }
+    
+    /// Kind stored in ArrayBox element slots
+    fn array_element_kind(&self) -> ValueKind {
+        self.module_types.field_kind("ArrayBox", ARRAY_ELEMENT).unwrap_or(ValueKind::Box)
+    }
+    
+    /// Load `array[index]` (traps when out of bounds)
+    fn generate_array_get(&self, dst: ValueId, array: ValueId, index: ValueId) -> Result<Vec<String>, WasmError> {
+        let kind = self.array_element_kind();
+        let mut instructions = vec![format!("local.get ${}", self.get_local_index(array)?)];
+        instructions.extend(self.get_as(index, ValueKind::Integer)?);
+        instructions.push("call $array_slot".to_string());
+        instructions.push(format!("{}.load", kind.wasm_type()));
+        instructions.extend(self.coerce_result(kind, dst)?);
+        Ok(instructions)
+    }
+    
+    /// Store `array[index] = value`, or append `value` when there is no index
+    fn generate_array_set(&self, array: ValueId, index: Option<ValueId>, value: ValueId) -> Result<Vec<String>, WasmError> {
+        let kind = self.array_element_kind();
+        let mut instructions = vec![format!("local.get ${}", self.get_local_index(array)?)];
+        match index {
+            Some(index) => {
+                instructions.extend(self.get_as(index, ValueKind::Integer)?);
+                instructions.push("call $array_slot".to_string());
+            },
+            None => instructions.push("call $array_push_slot".to_string()),
+        }
+        instructions.extend(self.get_as(value, kind)?);
+        instructions.push(format!("{}.store", kind.wasm_type()));
+        Ok(instructions)
+    }
+    
+    /// Store a `kind` value from the stack into `dst`
+    fn coerce_result(&self, kind: ValueKind, dst: ValueId) -> Result<Vec<String>, WasmError> {
+        if kind.wasm_type() != self.kind(dst).wasm_type() {
+            return Err(WasmError::CodegenError(
+                format!("Result {:?} does not match ValueId({}) of kind {:?}", kind, dst.as_u32(), self.kind(dst))
+            ));
+        }
+        Ok(vec![self.set(dst)?])
+    }
+    
     /// Generate toString() method call - Box → String conversion
     fn generate_to_string_call(&mut self, dst: Option<ValueId>, box_val: ValueId) -> Result<Vec<String>, WasmError> {
         let Some(dst) = dst else {
             return Err(WasmError::CodegenError("toString() requires destination".to_string()));
         };
         
-        Ok(vec![
-            format!(";; toString() implementation for ValueId({})", box_val.as_u32()),
-            format!("local.get ${}", self.get_local_index(box_val)?),
-            "call $box_to_string".to_string(),
-            format!("local.set ${}", self.get_local_index(dst)?),
-        ])
+        let mut instructions = vec![format!(";; toString() implementation for ValueId({})", box_val.as_u32())];
+        if self.kind(box_val) == ValueKind::Box {
+            instructions.push(format!("local.get ${}", self.get_local_index(box_val)?));
+            instructions.push("call $box_to_string".to_string());
+        } else {
+            instructions.extend(self.string_operand(box_val)?);
+        }
+        instructions.push(format!("local.set ${}", self.get_local_index(dst)?));
+        Ok(instructions)
     }
     
     /// Generate print() method call - Basic output
@@ -616,7 +1346,7 @@ impl WasmCodegen {
         // Store void result if destination is provided
         if let Some(dst) = dst {
             instructions.extend(vec![
-                "i32.const 0".to_string(), // Void result
+                self.kind(dst).zero(), // Void result
                 format!("local.set ${}", self.get_local_index(dst)?),
             ]);
         }
@@ -636,6 +1366,13 @@ impl WasmCodegen {
             ));
         }
         
+        // Unboxed values compare like ==
+        if self.kind(box_val) != ValueKind::Box {
+            let mut instructions = vec![format!(";; equals() on unboxed ValueId({})", box_val.as_u32())];
+            instructions.extend(self.generate_compare(dst, CompareOp::Eq, box_val, args[0])?);
+            return Ok(instructions);
+        }
+        
         Ok(vec![
             format!(";; equals() implementation for ValueId({}) == ValueId({})", box_val.as_u32(), args[0].as_u32()),
             format!("local.get ${}", self.get_local_index(box_val)?),
@@ -651,6 +1388,14 @@ impl WasmCodegen {
             return Err(WasmError::CodegenError("clone() requires destination".to_string()));
         };
         
+        // Unboxed values (and immutable StringBoxes) are copied as-is
+        if self.kind(box_val) != ValueKind::Box {
+            return Ok(vec![
+                format!("local.get ${}", self.get_local_index(box_val)?),
+                format!("local.set ${}", self.get_local_index(dst)?),
+            ]);
+        }
+        
         Ok(vec![
             format!(";; clone() implementation for ValueId({})", box_val.as_u32()),
             format!("local.get ${}", self.get_local_index(box_val)?),
@@ -679,7 +1424,7 @@ impl WasmCodegen {
         // Store void result if destination is provided
         if let Some(dst) = dst {
             instructions.extend(vec![
-                "i32.const 0".to_string(), // Void result
+                self.kind(dst).zero(), // Void result
                 format!("local.set ${}", self.get_local_index(dst)?),
             ]);
         }
@@ -688,6 +1433,17 @@ impl WasmCodegen {
     }
 }
 
+/// WAT spelling of an f64 constant
+fn format_f64(value: f64) -> String {
+    if value.is_nan() {
+        "nan".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "inf" } else { "-inf" }.to_string()
+    } else {
+        format!("{:?}", value)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
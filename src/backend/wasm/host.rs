@@ -1,201 +1,477 @@
 /*!
  * WASM Host Functions - Implementation of host functions for WASM execution
- * 
+ *
  * Phase 4-3c: Provides actual implementations for env::print and other imports
  * Enables WASM modules to interact with the host environment
  */
 
+use super::{ValueKind, WasmError};
+use crate::backend::vm::VM;
+use crate::box_trait::{BoolBox, IntegerBox, NyashBox, StringBox, VoidBox};
+use crate::boxes::FloatBox;
+use crate::runtime::get_global_unified_registry;
+use crate::runtime::plugin_loader_v2::{get_global_loader_v2, PluginBoxV2};
 use wasmtime::*;
-use std::sync::{Arc, Mutex};
+
+/// type_id of HostBox proxies (see `MemoryManager`)
+const HOST_BOX_TYPE_ID: i32 = 0x1006;
 
 /// Host state for WASM execution
 pub struct HostState {
-    /// Output buffer for captured prints
-    pub output: Arc<Mutex<String>>,
+    /// Captured output lines (print / console.log)
+    pub output: Vec<String>,
+    /// Also write output to stdout
+    pub echo: bool,
+    /// Boxes created through `env.box_new` / returned by `env.box_call`
+    pub handles: HandleTable,
 }
 
 impl HostState {
-    pub fn new() -> Self {
+    pub fn new(echo: bool) -> Self {
         Self {
-            output: Arc::new(Mutex::new(String::new())),
+            output: Vec::new(),
+            echo,
+            handles: HandleTable::default(),
+        }
+    }
+
+    fn emit(&mut self, line: String) {
+        if self.echo {
+            println!("{}", line);
         }
+        self.output.push(line);
     }
 }
 
-/// Create host functions for WASM imports
-pub fn create_host_functions(store: &mut Store<HostState>) -> Result<Vec<(String, String, Extern)>, Error> {
-    let mut imports = Vec::new();
-    
-    // env::print - print a Box value (expecting a StringBox pointer)
-    let print_func = Func::wrap(&mut *store, |mut caller: Caller<'_, HostState>, box_ptr: i32| {
-        // Try to read StringBox content from WASM memory
-        if let Some(mem) = caller.get_export("memory").and_then(|e| e.into_memory()) {
-            let data = mem.data(&caller);
-            let box_offset = box_ptr as usize;
-            
-            // StringBox layout: [type_id:4][ref_count:4][field_count:4][data_ptr:4][length:4]
-            if box_offset + 20 <= data.len() {
-                // Read data pointer (offset 12)
-                let data_ptr = i32::from_le_bytes([
-                    data[box_offset + 12],
-                    data[box_offset + 13],
-                    data[box_offset + 14],
-                    data[box_offset + 15],
-                ]);
-                
-                // Read length (offset 16)
-                let length = i32::from_le_bytes([
-                    data[box_offset + 16],
-                    data[box_offset + 17],
-                    data[box_offset + 18],
-                    data[box_offset + 19],
-                ]);
-                
-                // Read actual string content
-                let str_start = data_ptr as usize;
-                let str_end = str_start + length as usize;
-                
-                if str_end <= data.len() {
-                    if let Ok(s) = std::str::from_utf8(&data[str_start..str_end]) {
-                        println!("{}", s);
-                        return;
-                    }
-                }
+/// Boxes owned by the host on behalf of a module. Handles start at 1 and are reused once
+/// released (when the module's collector frees the HostBox proxy)
+#[derive(Default)]
+pub struct HandleTable {
+    slots: Vec<Option<Box<dyn NyashBox>>>,
+    free: Vec<usize>,
+}
+
+impl HandleTable {
+    pub fn insert(&mut self, value: Box<dyn NyashBox>) -> i32 {
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index] = Some(value);
+                index
             }
+            None => {
+                self.slots.push(Some(value));
+                self.slots.len() - 1
+            }
+        };
+        index as i32 + 1
+    }
+
+    pub fn get(&self, handle: i32) -> Option<&dyn NyashBox> {
+        let index = usize::try_from(handle).ok()?.checked_sub(1)?;
+        self.slots.get(index)?.as_deref()
+    }
+
+    pub fn release(&mut self, handle: i32) -> Option<Box<dyn NyashBox>> {
+        let index = usize::try_from(handle).ok()?.checked_sub(1)?;
+        let value = self.slots.get_mut(index)?.take()?;
+        self.free.push(index);
+        Some(value)
+    }
+
+    /// Number of live handles
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Value returned by an exported `main`, decoded with the `result_kind` global
+#[derive(Debug, Clone, PartialEq)]
+pub enum WasmValue {
+    Void,
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+    String(String),
+    /// Pointer to a Box in linear memory
+    Box(i32),
+}
+
+impl std::fmt::Display for WasmValue {
+    /// Same text as the corresponding NyashBox's `to_string_box()`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WasmValue::Void => write!(f, "void"),
+            WasmValue::Integer(n) => write!(f, "{}", n),
+            WasmValue::Float(x) => write!(f, "{}", x),
+            WasmValue::Bool(b) => write!(f, "{}", b),
+            WasmValue::String(s) => write!(f, "{}", s),
+            WasmValue::Box(ptr) => write!(f, "Box[{}]", ptr),
         }
-        
-        // Fallback: print as pointer
-        println!("Box[{}]", box_ptr);
-    });
-    imports.push(("env".to_string(), "print".to_string(), Extern::Func(print_func)));
-    
-    // env::print_str - print a string from memory (ptr, len)
-    let print_str_func = Func::wrap(&mut *store, |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
-        if let Some(mem) = caller.get_export("memory").and_then(|e| e.into_memory()) {
-            let data = mem.data(&caller);
-            let start = ptr as usize;
-            let end = start + len as usize;
-            
-            if end <= data.len() {
-                if let Ok(s) = std::str::from_utf8(&data[start..end]) {
-                    println!("{}", s);
-                    // Note: Output capture removed for simplicity
+    }
+}
+
+/// Result and captured output of running a module's `main`
+#[derive(Debug, Clone, PartialEq)]
+pub struct WasmExecution {
+    pub value: WasmValue,
+    pub output: Vec<String>,
+}
+
+/// Read a little-endian i32 from linear memory
+fn read_i32(data: &[u8], offset: usize) -> Option<i32> {
+    data.get(offset..offset + 4).map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Read the contents of a StringBox
+/// Layout: [type_id:4][ref_count:4][field_count:4][data_ptr:4][length:4]
+fn read_string_box(data: &[u8], box_ptr: i32) -> Option<String> {
+    let offset = box_ptr as usize;
+    if read_i32(data, offset)? != 0x1001 {
+        return None;
+    }
+    let start = read_i32(data, offset + 12)? as usize;
+    let length = read_i32(data, offset + 16)? as usize;
+    let bytes = data.get(start..start + length)?;
+    Some(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Read a little-endian i64 from linear memory
+fn read_i64(data: &[u8], offset: usize) -> Option<i64> {
+    data.get(offset..offset + 8).map(|b| i64::from_le_bytes(b.try_into().unwrap()))
+}
+
+fn read_str(data: &[u8], ptr: i32, len: i32) -> Option<String> {
+    let start = ptr as usize;
+    let bytes = data.get(start..start + len as usize)?;
+    Some(String::from_utf8_lossy(bytes).into_owned())
+}
+
+fn memory_data<'a>(caller: &'a mut Caller<'_, HostState>) -> Option<&'a [u8]> {
+    let memory = caller.get_export("memory").and_then(|e| e.into_memory())?;
+    Some(memory.data(caller))
+}
+
+/// Describe a Box pointer the way `print` shows it
+fn describe_box(data: &[u8], box_ptr: i32) -> String {
+    if let Some(s) = read_string_box(data, box_ptr) {
+        return s;
+    }
+    match read_i32(data, box_ptr as usize) {
+        Some(type_id) => format!("Box[type=0x{:x}]", type_id),
+        None => format!("Box[{}]", box_ptr),
+    }
+}
+
+/// Describe a Box pointer for `print`, asking the handle table about HostBox proxies
+fn describe(caller: &mut Caller<'_, HostState>, box_ptr: i32) -> String {
+    if let Some(host_box) = host_box(caller, box_ptr) {
+        return host_box.to_string_box().value;
+    }
+    memory_data(caller)
+        .map(|data| describe_box(data, box_ptr))
+        .unwrap_or_else(|| format!("Box[{}]", box_ptr))
+}
+
+/// The host Box behind a HostBox proxy
+fn host_box(caller: &mut Caller<'_, HostState>, box_ptr: i32) -> Option<Box<dyn NyashBox>> {
+    let data = memory_data(caller)?;
+    if read_i32(data, box_ptr as usize)? != HOST_BOX_TYPE_ID {
+        return None;
+    }
+    let handle = read_i32(data, box_ptr as usize + 12)?;
+    caller.data().handles.get(handle).map(|b| b.share_box())
+}
+
+fn memory(caller: &mut Caller<'_, HostState>) -> Result<Memory> {
+    caller.get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| Error::msg("Memory export not found"))
+}
+
+fn exported_func<P: WasmParams, R: WasmResults>(caller: &mut Caller<'_, HostState>, name: &str) -> Result<TypedFunc<P, R>> {
+    caller.get_export(name)
+        .and_then(Extern::into_func)
+        .ok_or_else(|| Error::msg(format!("Module does not export `{}`", name)))?
+        .typed::<P, R>(&*caller)
+}
+
+/// Read a name passed as a StringBox pointer (type and method names)
+fn read_name(caller: &mut Caller<'_, HostState>, box_ptr: i32) -> Result<String> {
+    memory_data(caller)
+        .and_then(|data| read_string_box(data, box_ptr))
+        .ok_or_else(|| Error::msg(format!("Invalid StringBox pointer: {}", box_ptr)))
+}
+
+/// Copy a string into a new StringBox of the module (its exported `malloc` and `string_new`)
+fn alloc_string(caller: &mut Caller<'_, HostState>, text: &str) -> Result<i32> {
+    let malloc = exported_func::<i32, i32>(caller, "malloc")?;
+    let data = malloc.call(&mut *caller, text.len() as i32)?;
+    memory(caller)?.write(&mut *caller, data as usize, text.as_bytes())?;
+    let string_new = exported_func::<(i32, i32), i32>(caller, "string_new")?;
+    string_new.call(&mut *caller, (data, text.len() as i32))
+}
+
+/// Hand a host Box to the module: StringBoxes are copied, void is null, anything else gets a
+/// handle wrapped in a HostBox proxy
+fn to_module_box(caller: &mut Caller<'_, HostState>, value: Box<dyn NyashBox>) -> Result<i32> {
+    if let Some(s) = value.as_any().downcast_ref::<StringBox>() {
+        return alloc_string(caller, &s.value);
+    }
+    if value.as_any().is::<VoidBox>() {
+        return Ok(0);
+    }
+    let handle = caller.data_mut().handles.insert(value);
+    let host_box_new = exported_func::<i32, i32>(caller, "host_box_new")?;
+    host_box_new.call(&mut *caller, handle)
+}
+
+/// Decode a host call argument frame: `[count:i32][want:i32]`, then 16-byte slots
+/// `[tag:i32][pad:i32][payload:8]` tagged with ValueKind codes
+fn read_args(caller: &mut Caller<'_, HostState>, frame: i32) -> Result<(Vec<Box<dyn NyashBox>>, ValueKind)> {
+    let memory = memory(caller)?;
+    let (data, state) = memory.data_and_store_mut(&mut *caller);
+    let frame = frame as usize;
+    let invalid = || Error::msg(format!("Invalid argument frame at {}", frame));
+    let count = read_i32(data, frame).ok_or_else(invalid)?;
+    let want = read_i32(data, frame + 4).and_then(ValueKind::from_code).ok_or_else(invalid)?;
+
+    let mut args: Vec<Box<dyn NyashBox>> = Vec::new();
+    for i in 0..count.max(0) as usize {
+        let slot = frame + 8 + 16 * i;
+        let tag = read_i32(data, slot).and_then(ValueKind::from_code).ok_or_else(invalid)?;
+        let word = read_i32(data, slot + 8).ok_or_else(invalid)?;
+        let arg: Box<dyn NyashBox> = match tag {
+            ValueKind::Void => Box::new(VoidBox::new()),
+            ValueKind::Integer => Box::new(IntegerBox::new(read_i64(data, slot + 8).ok_or_else(invalid)?)),
+            ValueKind::Float => {
+                let bits = read_i64(data, slot + 8).ok_or_else(invalid)?;
+                Box::new(FloatBox::new(f64::from_bits(bits as u64)))
+            }
+            ValueKind::Bool => Box::new(BoolBox::new(word != 0)),
+            ValueKind::String | ValueKind::Box => {
+                if let Some(s) = read_string_box(data, word) {
+                    Box::new(StringBox::new(s))
+                } else if read_i32(data, word as usize) == Some(HOST_BOX_TYPE_ID) {
+                    let handle = read_i32(data, word as usize + 12).ok_or_else(invalid)?;
+                    state.handles.get(handle)
+                        .map(|b| b.share_box())
+                        .ok_or_else(|| Error::msg(format!("Invalid box handle: {}", handle)))?
+                } else {
+                    return Err(Error::msg(format!(
+                        "{} cannot be passed to a host box method", describe_box(data, word)
+                    )));
                 }
             }
+        };
+        args.push(arg);
+    }
+    Ok((args, want))
+}
+
+/// Write a `box_call` result over its argument frame as `[tag:i32][pad:i32][payload:8]`,
+/// converted to the kind the module expects; returns the tag
+fn write_result(caller: &mut Caller<'_, HostState>, frame: i32, value: Box<dyn NyashBox>, want: ValueKind) -> Result<i32> {
+    let mismatch = |value: &dyn NyashBox| Error::msg(format!(
+        "Host box method returned {} where {:?} was expected", value.type_name(), want
+    ));
+    let any = value.as_any();
+    let payload: [u8; 8] = match want {
+        ValueKind::Void => [0; 8],
+        ValueKind::Integer => match (any.downcast_ref::<IntegerBox>(), any.downcast_ref::<BoolBox>()) {
+            (Some(i), _) => i.value.to_le_bytes(),
+            (_, Some(b)) => (b.value as i64).to_le_bytes(),
+            _ => return Err(mismatch(value.as_ref())),
+        },
+        ValueKind::Float => match (any.downcast_ref::<FloatBox>(), any.downcast_ref::<IntegerBox>()) {
+            (Some(f), _) => f.value.to_le_bytes(),
+            (_, Some(i)) => (i.value as f64).to_le_bytes(),
+            _ => return Err(mismatch(value.as_ref())),
+        },
+        ValueKind::Bool => match any.downcast_ref::<BoolBox>() {
+            Some(b) => (b.value as i64).to_le_bytes(),
+            None => return Err(mismatch(value.as_ref())),
+        },
+        ValueKind::String => {
+            let text = value.to_string_box().value;
+            (alloc_string(caller, &text)? as i64).to_le_bytes()
         }
-    });
-    imports.push(("env".to_string(), "print_str".to_string(), Extern::Func(print_str_func)));
-    
+        ValueKind::Box => (to_module_box(caller, value)? as i64).to_le_bytes(),
+    };
+
+    let mut bytes = [0u8; 16];
+    bytes[..4].copy_from_slice(&want.code().to_le_bytes());
+    bytes[8..].copy_from_slice(&payload);
+    memory(caller)?.write(&mut *caller, frame as usize, &bytes)?;
+    Ok(want.code())
+}
+
+/// Run a method of a host Box: plugin boxes go through the plugin loader, other boxes
+/// through the VM's builtin method dispatch
+fn invoke_method(target: Box<dyn NyashBox>, method: &str, args: Vec<Box<dyn NyashBox>>) -> Result<Box<dyn NyashBox>> {
+    if let Some(plugin) = target.as_any().downcast_ref::<PluginBoxV2>() {
+        let loader = get_global_loader_v2();
+        let loader = loader.read().map_err(|_| Error::msg("Plugin loader lock poisoned"))?;
+        return match loader.invoke_instance_method(&plugin.box_type, method, plugin.inner.instance_id, &args) {
+            Ok(Some(result)) => Ok(result),
+            Ok(None) => Ok(Box::new(VoidBox::new())),
+            Err(e) => Err(Error::msg(format!("Plugin method {}.{} failed: {:?}", plugin.box_type, method, e))),
+        };
+    }
+    VM::call_box_method(target, method, args).map_err(|e| Error::msg(e.to_string()))
+}
+
+/// Register every `env.*` import declared by `RuntimeImports`
+pub fn link_host_functions(linker: &mut Linker<HostState>) -> Result<(), Error> {
+    // env::print - print a Box value (StringBox contents, otherwise its type)
+    linker.func_wrap("env", "print", |mut caller: Caller<'_, HostState>, box_ptr: i32| {
+        let line = describe(&mut caller, box_ptr);
+        caller.data_mut().emit(line);
+    })?;
+
+    // env::print_i64 / print_f64 / print_bool - unboxed primitives
+    linker.func_wrap("env", "print_i64", |mut caller: Caller<'_, HostState>, value: i64| {
+        caller.data_mut().emit(value.to_string());
+    })?;
+    linker.func_wrap("env", "print_f64", |mut caller: Caller<'_, HostState>, value: f64| {
+        caller.data_mut().emit(value.to_string());
+    })?;
+    linker.func_wrap("env", "print_bool", |mut caller: Caller<'_, HostState>, value: i32| {
+        caller.data_mut().emit((value != 0).to_string());
+    })?;
+
+    // env::print_str - print a string from memory (ptr, len)
+    linker.func_wrap("env", "print_str", |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+        if let Some(s) = memory_data(&mut caller).and_then(|data| read_str(data, ptr, len)) {
+            caller.data_mut().emit(s);
+        }
+    })?;
+
     // env::console_log - console logging (similar to print_str)
-    let console_log_func = Func::wrap(&mut *store, |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
-        if let Some(mem) = caller.get_export("memory").and_then(|e| e.into_memory()) {
-            let data = mem.data(&caller);
-            let start = ptr as usize;
-            let end = start + len as usize;
-            
-            if end <= data.len() {
-                if let Ok(s) = std::str::from_utf8(&data[start..end]) {
-                    println!("[console.log] {}", s);
-                    // Note: Output capture removed for simplicity
-                }
-            }
+    linker.func_wrap("env", "console_log", |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+        if let Some(s) = memory_data(&mut caller).and_then(|data| read_str(data, ptr, len)) {
+            caller.data_mut().emit(format!("[console.log] {}", s));
         }
-    });
-    imports.push(("env".to_string(), "console_log".to_string(), Extern::Func(console_log_func)));
-    
-    // env::canvas_fillRect - stub implementation
-    let canvas_fill_rect = Func::wrap(&mut *store, |_caller: Caller<'_, HostState>, _x: i32, _y: i32, _w: i32, _h: i32, _r: i32, _g: i32, _b: i32, _a: i32| {
-        // Stub - in a real implementation, this would draw to a canvas
-    });
-    imports.push(("env".to_string(), "canvas_fillRect".to_string(), Extern::Func(canvas_fill_rect)));
-    
-    // env::canvas_fillText - stub implementation
-    let canvas_fill_text = Func::wrap(&mut *store, |_caller: Caller<'_, HostState>, _ptr: i32, _len: i32, _x: i32, _y: i32, _size: i32, _r: i32, _g: i32, _b: i32, _a: i32, _align: i32| {
-        // Stub - in a real implementation, this would draw text
-    });
-    imports.push(("env".to_string(), "canvas_fillText".to_string(), Extern::Func(canvas_fill_text)));
-    
-    // env::box_to_string - convert Box to string representation
-    let box_to_string = Func::wrap(&mut *store, |_caller: Caller<'_, HostState>, box_ptr: i32| -> i32 {
-        // For now, return the same pointer - in a real implementation,
-        // this would convert the Box to its string representation
-        box_ptr
-    });
-    imports.push(("env".to_string(), "box_to_string".to_string(), Extern::Func(box_to_string)));
-    
+    })?;
+
+    // env::canvas_fillRect - no canvas outside the browser
+    linker.func_wrap("env", "canvas_fillRect", |_caller: Caller<'_, HostState>,
+        _id_ptr: i32, _id_len: i32, _x: i32, _y: i32, _w: i32, _h: i32, _color_ptr: i32, _color_len: i32| {})?;
+
+    // env::canvas_fillText - no canvas outside the browser
+    linker.func_wrap("env", "canvas_fillText", |_caller: Caller<'_, HostState>,
+        _id_ptr: i32, _id_len: i32, _text_ptr: i32, _text_len: i32, _x: i32, _y: i32,
+        _font_ptr: i32, _font_len: i32, _color_ptr: i32, _color_len: i32| {})?;
+
+    // env::box_to_string - StringBoxes are already strings; HostBoxes ask the host Box;
+    // other boxes are returned as-is
+    linker.func_wrap("env", "box_to_string", |mut caller: Caller<'_, HostState>, box_ptr: i32| -> Result<i32> {
+        match host_box(&mut caller, box_ptr) {
+            Some(host_box) => alloc_string(&mut caller, &host_box.to_string_box().value),
+            None => Ok(box_ptr),
+        }
+    })?;
+
     // env::box_print - print a Box value
-    let box_print = Func::wrap(&mut *store, |mut caller: Caller<'_, HostState>, box_ptr: i32| {
-        // Read Box type from memory
-        if let Some(mem) = caller.get_export("memory").and_then(|e| e.into_memory()) {
-            let data = mem.data(&caller);
-            let type_id_offset = box_ptr as usize;
-            
-            if type_id_offset + 4 <= data.len() {
-                let type_id = i32::from_le_bytes([
-                    data[type_id_offset],
-                    data[type_id_offset + 1],
-                    data[type_id_offset + 2],
-                    data[type_id_offset + 3],
-                ]);
-                
-                match type_id {
-                    0x1001 => { // StringBox
-                        // Read string pointer and length from Box fields
-                        let str_ptr_offset = type_id_offset + 12;
-                        let str_len_offset = type_id_offset + 16;
-                        
-                        if str_len_offset + 4 <= data.len() {
-                            let str_ptr = i32::from_le_bytes([
-                                data[str_ptr_offset],
-                                data[str_ptr_offset + 1],
-                                data[str_ptr_offset + 2],
-                                data[str_ptr_offset + 3],
-                            ]);
-                            
-                            let str_len = i32::from_le_bytes([
-                                data[str_len_offset],
-                                data[str_len_offset + 1],
-                                data[str_len_offset + 2],
-                                data[str_len_offset + 3],
-                            ]);
-                            
-                            // Read actual string content
-                            let str_start = str_ptr as usize;
-                            let str_end = str_start + str_len as usize;
-                            
-                            if str_end <= data.len() {
-                                if let Ok(s) = std::str::from_utf8(&data[str_start..str_end]) {
-                                    println!("{}", s);
-                                    // Note: Output capture removed for simplicity
-                                    return;
-                                }
-                            }
-                        }
-                    },
-                    _ => {
-                        println!("Box[type=0x{:x}]", type_id);
-                    }
-                }
-            }
+    linker.func_wrap("env", "box_print", |mut caller: Caller<'_, HostState>, box_ptr: i32| {
+        let line = describe(&mut caller, box_ptr);
+        caller.data_mut().emit(line);
+    })?;
+
+    // env::box_equals - identity, or equal contents for StringBoxes
+    linker.func_wrap("env", "box_equals", |mut caller: Caller<'_, HostState>, box1: i32, box2: i32| -> i32 {
+        if box1 == box2 {
+            return 1;
         }
-        
-        println!("Box[unknown]");
-    });
-    imports.push(("env".to_string(), "box_print".to_string(), Extern::Func(box_print)));
-    
-    // env::box_equals - check Box equality
-    let box_equals = Func::wrap(&mut *store, |_caller: Caller<'_, HostState>, _box1: i32, _box2: i32| -> i32 {
-        // Stub - for now always return 0 (false)
-        0
-    });
-    imports.push(("env".to_string(), "box_equals".to_string(), Extern::Func(box_equals)));
-    
-    // env::box_clone - clone a Box
-    let box_clone = Func::wrap(&mut *store, |_caller: Caller<'_, HostState>, box_ptr: i32| -> i32 {
-        // Stub - for now return the same pointer
+        if let (Some(a), Some(b)) = (host_box(&mut caller, box1), host_box(&mut caller, box2)) {
+            return a.equals(b.as_ref()).value as i32;
+        }
+        let equal = memory_data(&mut caller)
+            .and_then(|data| Some(read_string_box(data, box1)? == read_string_box(data, box2)?))
+            .unwrap_or(false);
+        equal as i32
+    })?;
+
+    // env::box_clone - boxes are immutable from the host's point of view
+    linker.func_wrap("env", "box_clone", |_caller: Caller<'_, HostState>, box_ptr: i32| -> i32 {
         box_ptr
-    });
-    imports.push(("env".to_string(), "box_clone".to_string(), Extern::Func(box_clone)));
-    
-    Ok(imports)
-}
\ No newline at end of file
+    })?;
+
+    // env::box_new - create a builtin or plugin Box through the unified registry
+    linker.func_wrap("env", "box_new", |mut caller: Caller<'_, HostState>, type_ptr: i32, args_ptr: i32| -> Result<i32> {
+        let box_type = read_name(&mut caller, type_ptr)?;
+        let (args, _) = read_args(&mut caller, args_ptr)?;
+        let registry = get_global_unified_registry();
+        let created = registry.lock()
+            .map_err(|_| Error::msg("Box registry lock poisoned"))?
+            .create_box(&box_type, &args)
+            .map_err(|e| Error::msg(format!("Cannot create {}: {}", box_type, e)))?;
+        Ok(caller.data_mut().handles.insert(created))
+    })?;
+
+    // env::box_call - call a method of a host Box, writing the result over the argument frame
+    linker.func_wrap("env", "box_call", |mut caller: Caller<'_, HostState>, handle: i32, method_ptr: i32, args_ptr: i32| -> Result<i32> {
+        let method = read_name(&mut caller, method_ptr)?;
+        let (args, want) = read_args(&mut caller, args_ptr)?;
+        let target = caller.data().handles.get(handle)
+            .map(|b| b.share_box())
+            .ok_or_else(|| Error::msg(format!("Invalid box handle: {}", handle)))?;
+        let result = invoke_method(target, &method, args)?;
+        write_result(&mut caller, args_ptr, result, want)
+    })?;
+
+    // env::box_release - the module collected the HostBox proxy of a handle
+    linker.func_wrap("env", "box_release", |mut caller: Caller<'_, HostState>, handle: i32| {
+        caller.data_mut().handles.release(handle);
+    })?;
+
+    Ok(())
+}
+
+/// Instantiate a module with the host functions and run its exported `main`
+pub fn run_main(engine: &Engine, module: &Module, echo: bool) -> Result<WasmExecution, WasmError> {
+    let mut store = Store::new(engine, HostState::new(echo));
+    let mut linker = Linker::new(engine);
+    link_host_functions(&mut linker)
+        .map_err(|e| WasmError::WasmValidationError(format!("Host function setup failed: {}", e)))?;
+    let instance = linker.instantiate(&mut store, module)
+        .map_err(|e| WasmError::WasmValidationError(format!("Instance creation failed: {}", e)))?;
+
+    let main = instance.get_func(&mut store, "main")
+        .ok_or_else(|| WasmError::WasmValidationError("Main function not found".to_string()))?;
+    let mut results: Vec<Val> = main.ty(&store).results().map(|_| Val::I32(0)).collect();
+    main.call(&mut store, &[], &mut results)
+        .map_err(|e| WasmError::RuntimeError(format!("Execution failed: {}", e)))?;
+
+    // Modules without the global fall back to the raw wasm result type
+    let kind = instance.get_global(&mut store, "result_kind")
+        .and_then(|global| global.get(&mut store).i32())
+        .and_then(ValueKind::from_code);
+    let value = match (kind, results.first()) {
+        (_, None) | (Some(ValueKind::Void), _) => WasmValue::Void,
+        (Some(ValueKind::Integer), Some(Val::I64(n))) | (None, Some(Val::I64(n))) => WasmValue::Integer(*n),
+        (Some(ValueKind::Float), Some(Val::F64(bits))) | (None, Some(Val::F64(bits))) => {
+            WasmValue::Float(f64::from_bits(*bits))
+        }
+        (Some(ValueKind::Bool), Some(Val::I32(b))) => WasmValue::Bool(*b != 0),
+        (Some(ValueKind::String), Some(Val::I32(ptr))) => {
+            let memory = instance.get_memory(&mut store, "memory")
+                .ok_or_else(|| WasmError::MemoryError("Memory export not found".to_string()))?;
+            let text = read_string_box(memory.data(&store), *ptr)
+                .ok_or_else(|| WasmError::MemoryError(format!("Invalid StringBox pointer: {}", ptr)))?;
+            WasmValue::String(text)
+        }
+        (_, Some(Val::I32(ptr))) => WasmValue::Box(*ptr),
+        (_, Some(other)) => {
+            return Err(WasmError::RuntimeError(format!("Unexpected main result: {:?}", other)));
+        }
+    };
+
+    Ok(WasmExecution {
+        value,
+        output: std::mem::take(&mut store.data_mut().output),
+    })
+}
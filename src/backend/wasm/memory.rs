@@ -1,19 +1,44 @@
 /*!
  * WASM Memory Management - Box layout and heap allocation
  * 
- * Phase 8.3 PoC2: Implements bump allocator and Box memory layout
- * Memory Layout: 0x000-0x3FF (reserved), 0x400-0x7FF (stack), 0x800+ (heap)
+ * Phase 8.3 PoC2: Implements Box memory layout and heap allocation
+ * Memory Layout: 0x000-0x3FF (reserved), 0x400-0x7FF (shadow stack), 0x800+ (heap)
+ *
+ * The heap is a sequence of blocks `[size:i32][flags:i32][payload...]` (size includes the
+ * 8-byte header and is a multiple of 8). `$malloc` takes the first fitting block of a free list
+ * and bump allocates (growing memory) when none fits. A mark-sweep collector reclaims blocks:
+ * - roots are the pointer-holding locals that functions spill to their shadow stack frame
+ * - `$gc_trace` follows StringBox/ArrayBox buffers and the pointer fields of user boxes
+ * - the sweep rebuilds the free list, merging neighbouring free blocks, and returns a free
+ *   tail to the bump region
+ * - HostBox proxies (boxes owned by the host, see `env.box_new`) carry a block flag so that
+ *   sweeping them releases their host handle
+ *
+ * Collection only happens at MIR safepoints (`$gc_safepoint`), so runtime helpers that allocate
+ * several blocks never see their intermediate results collected.
  */
 
 use super::WasmError;
 use std::collections::HashMap;
 
+/// Shadow stack of GC roots, between the reserved area and the heap
+pub const SHADOW_STACK_START: u32 = 0x400;
+pub const SHADOW_STACK_END: u32 = 0x800;
+
+/// Bytes allocated since the last collection before a safepoint collects
+pub const GC_THRESHOLD: u32 = 64 * 1024;
+
+/// Payload offset of the host handle in a HostBox
+pub const HOST_BOX_HANDLE_OFFSET: u32 = 12;
+
 /// Box memory layout definition
 #[derive(Debug, Clone)]
 pub struct BoxLayout {
     pub type_id: u32,
     pub size: u32,
     pub field_offsets: HashMap<String, u32>,
+    /// Slot width of each field in bytes (4 for i32, 8 for fields that may hold i64/f64)
+    pub field_sizes: HashMap<String, u32>,
 }
 
 impl BoxLayout {
@@ -25,6 +50,7 @@ impl BoxLayout {
             "BoolBox" => 0x1003,
             "ArrayBox" => 0x1004,
             "DataBox" => 0x1005,  // For testing
+            "HostBox" => 0x1006,
             _ => {
                 // Generate ID from hash for custom types
                 type_name.chars().map(|c| c as u32).sum::<u32>() % 65536 + 0x2000
@@ -35,18 +61,38 @@ impl BoxLayout {
             type_id,
             size: 12, // Header: type_id + ref_count + field_count
             field_offsets: HashMap::new(),
+            field_sizes: HashMap::new(),
         }
     }
     
     pub fn add_field(&mut self, field_name: String) {
+        self.add_field_sized(field_name, 4); // Each field is 4 bytes (i32)
+    }
+    
+    /// Add a field with an explicit slot width (8 bytes can hold i64/f64 values)
+    pub fn add_field_sized(&mut self, field_name: String, size: u32) {
         let offset = self.size;
-        self.field_offsets.insert(field_name, offset);
-        self.size += 4; // Each field is 4 bytes (i32)
+        self.field_offsets.insert(field_name.clone(), offset);
+        self.field_sizes.insert(field_name, size);
+        self.size += size;
     }
     
     pub fn get_field_offset(&self, field_name: &str) -> Option<u32> {
         self.field_offsets.get(field_name).copied()
     }
+    
+    pub fn get_field_size(&self, field_name: &str) -> Option<u32> {
+        self.field_sizes.get(field_name).copied()
+    }
+    
+    /// Field names in memory order (the order of NewBox arguments)
+    pub fn ordered_fields(&self) -> Vec<&str> {
+        let mut fields: Vec<(&str, u32)> = self.field_offsets.iter()
+            .map(|(name, offset)| (name.as_str(), *offset))
+            .collect();
+        fields.sort_by_key(|(_, offset)| *offset);
+        fields.into_iter().map(|(name, _)| name).collect()
+    }
 }
 
 /// WASM memory manager
@@ -80,8 +126,16 @@ impl MemoryManager {
         // BoolBox: [type_id][ref_count][field_count][value]
         self.register_box_type("BoolBox".to_string(), vec!["value".to_string()]);
         
-        // DataBox: [type_id][ref_count][field_count][value] - for testing
-        self.register_box_type("DataBox".to_string(), vec!["value".to_string()]);
+        // DataBox: [type_id][ref_count][field_count][value:8] - for testing, wide enough for i64/f64
+        let mut data_box = BoxLayout::new("DataBox");
+        data_box.add_field_sized("value".to_string(), 8);
+        self.box_layouts.insert("DataBox".to_string(), data_box);
+        
+        // ArrayBox: [type_id][ref_count][field_count][data_ptr][length][capacity] - elements are 8-byte slots
+        self.register_box_type("ArrayBox".to_string(), vec!["data_ptr".to_string(), "length".to_string(), "capacity".to_string()]);
+        
+        // HostBox: [type_id][ref_count][field_count][handle] - proxy for a Box in the host's handle table
+        self.register_box_type("HostBox".to_string(), vec!["handle".to_string()]);
     }
     
     /// Register a Box type layout
@@ -95,47 +149,487 @@ impl MemoryManager {
         self.box_layouts.insert(type_name, layout);
     }
     
+    /// Register a prebuilt layout (user boxes use 8-byte slots for every field)
+    pub fn register_box_layout(&mut self, type_name: String, layout: BoxLayout) {
+        self.box_layouts.insert(type_name, layout);
+    }
+    
     /// Get Box layout by type name
     pub fn get_box_layout(&self, type_name: &str) -> Option<&BoxLayout> {
         self.box_layouts.get(type_name)
     }
     
+    /// All known Box layouts
+    pub fn box_layouts(&self) -> &HashMap<String, BoxLayout> {
+        &self.box_layouts
+    }
+    
     /// Generate WASM globals for heap management
     pub fn get_globals(&self) -> Vec<String> {
+        self.heap_globals(self.heap_start)
+    }
+
+    /// Generate heap globals with the heap placed after static data ending at `data_end`,
+    /// so allocations never overwrite string literals
+    pub fn get_globals_above(&self, data_end: u32) -> Vec<String> {
+        self.heap_globals(self.heap_start.max((data_end + 7) & !7))
+    }
+    
+    fn heap_globals(&self, heap_start: u32) -> Vec<String> {
         vec![
-            format!("(global $heap_ptr (mut i32) (i32.const {}))", self.heap_start),
+            format!("(global $heap_base i32 (i32.const {}))", heap_start),
+            format!("(global $heap_ptr (mut i32) (i32.const {}))", heap_start),
+            "(global $free_list (mut i32) (i32.const 0))".to_string(),
+            "(global $gc_allocated (mut i32) (i32.const 0))".to_string(),
+            format!("(global $sp (mut i32) (i32.const {}))", SHADOW_STACK_START),
         ]
     }
     
-    /// Generate heap allocation function with 4-byte alignment
+    /// Generate heap allocation function: first fit from the free list, else bump allocation.
+    /// Returns an 8-byte aligned payload pointer; the payload is not zeroed
     pub fn get_malloc_function(&self) -> String {
-        format!(
-            r#"(func $malloc (param $size i32) (result i32)
-    (local $ptr i32)
-    (local $aligned_size i32)
+        r#"(func $malloc (param $size i32) (result i32)
+    (local $need i32)
+    (local $prev i32)
+    (local $block i32)
+    (local $rest i32)
+    (local $next i32)
     
-    ;; Align size to 4-byte boundary
+    ;; Block size: header (8) + size aligned to 8, at least 16 so a free block can hold its link
     local.get $size
-    i32.const 3
+    i32.const 15
     i32.add
-    i32.const -4
+    i32.const -8
     i32.and
-    local.set $aligned_size
+    local.tee $need
+    i32.const 16
+    local.get $need
+    i32.const 16
+    i32.ge_u
+    select
+    local.set $need
+    global.get $gc_allocated
+    local.get $need
+    i32.add
+    global.set $gc_allocated
     
-    ;; Get current heap pointer
-    global.get $heap_ptr
-    local.set $ptr
+    ;; First fit in the free list (link at payload offset 0)
+    global.get $free_list
+    local.set $block
+    block $bump
+      loop $scan
+        local.get $block
+        i32.eqz
+        br_if $bump
+        local.get $block
+        i32.load
+        local.get $need
+        i32.ge_u
+        if
+          local.get $block
+          i32.load offset=8
+          local.set $next
+          
+          ;; Split: the tail stays in the list in place of the block
+          local.get $block
+          i32.load
+          local.get $need
+          i32.sub
+          local.tee $rest
+          i32.const 16
+          i32.ge_u
+          if
+            local.get $block
+            local.get $need
+            i32.add
+            local.tee $rest
+            local.get $block
+            i32.load
+            local.get $need
+            i32.sub
+            i32.store
+            local.get $rest
+            i32.const 0
+            i32.store offset=4
+            local.get $rest
+            local.get $next
+            i32.store offset=8
+            local.get $rest
+            local.set $next
+            local.get $block
+            local.get $need
+            i32.store
+          end
+          
+          local.get $prev
+          if
+            local.get $prev
+            local.get $next
+            i32.store offset=8
+          else
+            local.get $next
+            global.set $free_list
+          end
+          local.get $block
+          i32.const 1
+          i32.store offset=4
+          local.get $block
+          i32.const 8
+          i32.add
+          return
+        end
+        local.get $block
+        local.set $prev
+        local.get $block
+        i32.load offset=8
+        local.set $block
+        br $scan
+      end
+    end
     
-    ;; Advance heap pointer by aligned size
+    ;; Bump allocation, growing memory by whole pages when the block does not fit
     global.get $heap_ptr
-    local.get $aligned_size
+    local.set $block
+    local.get $block
+    local.get $need
+    i32.add
+    memory.size
+    i32.const 16
+    i32.shl
+    i32.gt_u
+    if
+      local.get $block
+      local.get $need
+      i32.add
+      memory.size
+      i32.const 16
+      i32.shl
+      i32.sub
+      i32.const 65535
+      i32.add
+      i32.const 16
+      i32.shr_u
+      memory.grow
+      i32.const -1
+      i32.eq
+      if
+        unreachable
+      end
+    end
+    local.get $block
+    local.get $need
     i32.add
     global.set $heap_ptr
+    local.get $block
+    local.get $need
+    i32.store
+    local.get $block
+    i32.const 1
+    i32.store offset=4
+    local.get $block
+    i32.const 8
+    i32.add
+  )"#.to_string()
+    }
+    
+    /// Generate the mark-sweep collector
+    ///
+    /// - `$gc_mark_block(ptr)`: mark a heap block by its payload pointer; 1 when newly marked
+    /// - `$gc_mark(box)`: mark a Box and everything reachable from it
+    /// - `$gc_trace(box)`: mark what a Box points to, by type_id; `traced_boxes` lists the
+    ///   pointer field offsets of each other Box type, and ArrayBox elements are followed
+    ///   when `trace_array_elements` is set
+    /// - `$gc_collect()`: mark from the shadow stack, then sweep
+    /// - `$gc_safepoint()`: collect once `GC_THRESHOLD` bytes were allocated since the last one
+    pub fn get_gc_functions(&self, traced_boxes: &[(u32, Vec<u32>)], trace_array_elements: bool) -> Vec<String> {
+        let string_id = self.get_type_id("StringBox").unwrap_or(0x1001);
+        let array_id = self.get_type_id("ArrayBox").unwrap_or(0x1004);
+        
+        let mut trace = format!(
+            r#"(func $gc_trace (param $box i32)
+    (local $type_id i32)
+    (local $data i32)
+    (local $i i32)
+    (local $len i32)
+    local.get $box
+    i32.load
+    local.set $type_id
+    
+    ;; StringBox: byte buffer (literals live in static data and are skipped)
+    local.get $type_id
+    i32.const {}
+    i32.eq
+    if
+      local.get $box
+      i32.load offset=12
+      call $gc_mark_block
+      drop
+      return
+    end
     
-    ;; Return allocated pointer
+    ;; ArrayBox: element buffer
+    local.get $type_id
+    i32.const {}
+    i32.eq
+    if
+      local.get $box
+      i32.load offset=12
+      local.tee $data
+      call $gc_mark_block
+      drop
+"#,
+            string_id, array_id
+        );
+        if trace_array_elements {
+            trace.push_str(
+                r#"      local.get $box
+      i32.load offset=16
+      local.set $len
+      block $done
+        loop $elements
+          local.get $i
+          local.get $len
+          i32.ge_u
+          br_if $done
+          local.get $data
+          local.get $i
+          i32.const 8
+          i32.mul
+          i32.add
+          i32.load
+          call $gc_mark
+          local.get $i
+          i32.const 1
+          i32.add
+          local.set $i
+          br $elements
+        end
+      end
+"#,
+            );
+        }
+        trace.push_str("      return
+    end
+");
+        for (type_id, offsets) in traced_boxes {
+            trace.push_str(&format!("    
+    local.get $type_id
+    i32.const {}
+    i32.eq
+    if
+", type_id));
+            for offset in offsets {
+                trace.push_str(&format!("      local.get $box
+      i32.load offset={}
+      call $gc_mark
+", offset));
+            }
+            trace.push_str("      return
+    end
+");
+        }
+        trace.push_str("  )");
+        
+        vec![
+            r#"(func $gc_mark_block (param $ptr i32) (result i32)
+    (local $flags i32)
+    ;; Null, static data and misaligned pointers are not heap blocks
     local.get $ptr
-  )"#
-        )
+    global.get $heap_base
+    i32.const 8
+    i32.add
+    i32.lt_u
+    local.get $ptr
+    global.get $heap_ptr
+    i32.ge_u
+    i32.or
+    local.get $ptr
+    i32.const 7
+    i32.and
+    i32.or
+    if
+      i32.const 0
+      return
+    end
+    
+    ;; Only allocated blocks that are not marked yet (flags: bit 0 allocated, bit 1 marked,
+    ;; bit 2 HostBox proxy)
+    local.get $ptr
+    i32.const 4
+    i32.sub
+    i32.load
+    local.tee $flags
+    i32.const 3
+    i32.and
+    i32.const 1
+    i32.ne
+    if
+      i32.const 0
+      return
+    end
+    local.get $ptr
+    i32.const 4
+    i32.sub
+    local.get $flags
+    i32.const 2
+    i32.or
+    i32.store
+    i32.const 1
+  )"#.to_string(),
+            r#"(func $gc_mark (param $box i32)
+    local.get $box
+    call $gc_mark_block
+    if
+      local.get $box
+      call $gc_trace
+    end
+  )"#.to_string(),
+            trace,
+            format!(
+                r#"(func $gc_collect
+    (local $slot i32)
+    (local $block i32)
+    (local $size i32)
+    (local $run i32)
+    (local $tail i32)
+    (local $before_tail i32)
+    (local $flags i32)
+    
+    ;; Mark from every slot of the shadow stack
+    i32.const {}
+    local.set $slot
+    block $marked
+      loop $roots
+        local.get $slot
+        global.get $sp
+        i32.ge_u
+        br_if $marked
+        local.get $slot
+        i32.load
+        call $gc_mark
+        local.get $slot
+        i32.const 4
+        i32.add
+        local.set $slot
+        br $roots
+      end
+    end
+    
+    ;; Sweep: clear marks and rebuild the free list, merging runs of unmarked blocks
+    i32.const 0
+    global.set $free_list
+    global.get $heap_base
+    local.set $block
+    block $swept
+      loop $blocks
+        local.get $block
+        global.get $heap_ptr
+        i32.ge_u
+        br_if $swept
+        local.get $block
+        i32.load
+        local.set $size
+        local.get $block
+        i32.load offset=4
+        local.tee $flags
+        i32.const 2
+        i32.and
+        if
+          ;; Survivor: clear the mark
+          local.get $block
+          local.get $flags
+          i32.const -3
+          i32.and
+          i32.store offset=4
+          i32.const 0
+          local.set $run
+        else
+          ;; A dead HostBox proxy gives its Box back to the host
+          local.get $flags
+          i32.const 5
+          i32.and
+          i32.const 5
+          i32.eq
+          if
+            local.get $block
+            i32.load offset={}
+            call $box_release
+          end
+          local.get $run
+          if
+            local.get $run
+            local.get $run
+            i32.load
+            local.get $size
+            i32.add
+            i32.store
+          else
+            ;; Start a new free block at the end of the list
+            local.get $block
+            local.set $run
+            local.get $block
+            i32.const 0
+            i32.store offset=4
+            local.get $block
+            i32.const 0
+            i32.store offset=8
+            local.get $tail
+            if
+              local.get $tail
+              local.get $block
+              i32.store offset=8
+            else
+              local.get $block
+              global.set $free_list
+            end
+            local.get $tail
+            local.set $before_tail
+            local.get $block
+            local.set $tail
+          end
+        end
+        local.get $block
+        local.get $size
+        i32.add
+        local.set $block
+        br $blocks
+      end
+    end
+    
+    ;; A free run reaching the end of the heap goes back to the bump region
+    local.get $run
+    if
+      local.get $run
+      global.set $heap_ptr
+      local.get $before_tail
+      if
+        local.get $before_tail
+        i32.const 0
+        i32.store offset=8
+      else
+        i32.const 0
+        global.set $free_list
+      end
+    end
+    
+    i32.const 0
+    global.set $gc_allocated
+  )"#,
+                SHADOW_STACK_START,
+                8 + HOST_BOX_HANDLE_OFFSET
+            ),
+            format!(
+                r#"(func $gc_safepoint
+    global.get $gc_allocated
+    i32.const {}
+    i32.gt_u
+    if
+      call $gc_collect
+    end
+  )"#,
+                GC_THRESHOLD
+            ),
+        ]
     }
     
     /// Generate Box allocation function for specific type
@@ -253,14 +747,22 @@ impl MemoryManager {
             r#"
 ;; Memory Layout:
 ;; 0x000-0x3FF: Reserved/globals (1KB)
-;; 0x400-0x7FF: Stack space (1KB)  
-;; 0x800+:      Heap (bump allocator)
+;; 0x400-0x7FF: Shadow stack of GC roots (1KB)
+;; 0x800+:      Heap (free list + bump allocation, mark-sweep collected at safepoints)
 ;;
-;; Box Layout: [type_id:i32][ref_count:i32][field_count:i32][field0:i32][field1:i32]...
+;; Heap Block: [size:i32][flags:i32][payload] (size includes the header, multiple of 8;
+;;             flags bit 0 = allocated, bit 1 = marked, bit 2 = HostBox proxy;
+;;             a free block links the next at payload+0)
+;; Box Layout: [type_id:i32][ref_count:i32][field_count:i32][field0][field1]...
+;; ref_count is always 1: liveness is decided by the collector
+;; Fields are 4-byte slots, or 8-byte slots when they may hold i64/f64
+;; StringBox:  [header][data_ptr:i32][length:i32] (UTF-8 bytes, not NUL-terminated)
 ;; 
 ;; Standard Type IDs:
 ;; StringBox:  0x1001, IntegerBox: 0x1002, BoolBox: 0x1003
-;; ArrayBox:   0x1004, DataBox:    0x1005
+;; ArrayBox:   0x1004, DataBox:    0x1005, HostBox:    0x1006
+;; ArrayBox:   [header][data_ptr:i32][length:i32][capacity:i32], elements in 8-byte slots
+;; HostBox:    [header][handle:i32] (index into the host's handle table)
 ;; Custom:     0x2000+
 ;;
 ;; Heap start: 0x{:x}
@@ -274,6 +776,376 @@ impl MemoryManager {
         self.box_layouts.get(type_name).map(|layout| layout.type_id)
     }
     
+    /// Generate StringBox runtime helpers used by string constants, concatenation and comparison
+    ///
+    /// - `$string_new(data_ptr, len)`: wrap existing bytes in a new StringBox
+    /// - `$string_concat(a, b)`: new StringBox with the bytes of `a` followed by `b`
+    /// - `$string_compare(a, b)`: -1/0/1 by byte-wise (UTF-8) ordering, like Rust's `str::cmp`
+    /// - `$string_from_i64(value)`: decimal representation of an integer
+    pub fn get_string_functions(&self) -> Vec<String> {
+        vec![
+            r#"(func $memcpy (param $dst i32) (param $src i32) (param $len i32)
+    block $done
+      loop $copy
+        local.get $len
+        i32.eqz
+        br_if $done
+        local.get $dst
+        local.get $src
+        i32.load8_u
+        i32.store8
+        local.get $dst
+        i32.const 1
+        i32.add
+        local.set $dst
+        local.get $src
+        i32.const 1
+        i32.add
+        local.set $src
+        local.get $len
+        i32.const 1
+        i32.sub
+        local.set $len
+        br $copy
+      end
+    end
+  )"#.to_string(),
+            r#"(func $string_new (param $data i32) (param $len i32) (result i32)
+    (local $ptr i32)
+    call $alloc_stringbox
+    local.set $ptr
+    
+    ;; data_ptr (offset 12) and length (offset 16)
+    local.get $ptr
+    local.get $data
+    i32.store offset=12
+    local.get $ptr
+    local.get $len
+    i32.store offset=16
+    local.get $ptr
+  )"#.to_string(),
+            r#"(func $string_concat (param $a i32) (param $b i32) (result i32)
+    (local $len_a i32)
+    (local $len_b i32)
+    (local $buf i32)
+    local.get $a
+    i32.load offset=16
+    local.set $len_a
+    local.get $b
+    i32.load offset=16
+    local.set $len_b
+    
+    ;; Copy both byte ranges into a fresh buffer
+    local.get $len_a
+    local.get $len_b
+    i32.add
+    call $malloc
+    local.set $buf
+    local.get $buf
+    local.get $a
+    i32.load offset=12
+    local.get $len_a
+    call $memcpy
+    local.get $buf
+    local.get $len_a
+    i32.add
+    local.get $b
+    i32.load offset=12
+    local.get $len_b
+    call $memcpy
+    
+    local.get $buf
+    local.get $len_a
+    local.get $len_b
+    i32.add
+    call $string_new
+  )"#.to_string(),
+            r#"(func $string_compare (param $a i32) (param $b i32) (result i32)
+    (local $pa i32)
+    (local $pb i32)
+    (local $len_a i32)
+    (local $len_b i32)
+    (local $n i32)
+    (local $i i32)
+    (local $ca i32)
+    (local $cb i32)
+    local.get $a
+    i32.load offset=12
+    local.set $pa
+    local.get $b
+    i32.load offset=12
+    local.set $pb
+    local.get $a
+    i32.load offset=16
+    local.set $len_a
+    local.get $b
+    i32.load offset=16
+    local.set $len_b
+    
+    ;; n = min(len_a, len_b)
+    local.get $len_a
+    local.get $len_b
+    local.get $len_a
+    local.get $len_b
+    i32.lt_u
+    select
+    local.set $n
+    
+    ;; First differing byte decides
+    block $done
+      loop $scan
+        local.get $i
+        local.get $n
+        i32.ge_u
+        br_if $done
+        local.get $pa
+        local.get $i
+        i32.add
+        i32.load8_u
+        local.set $ca
+        local.get $pb
+        local.get $i
+        i32.add
+        i32.load8_u
+        local.set $cb
+        local.get $ca
+        local.get $cb
+        i32.ne
+        if
+          i32.const -1
+          i32.const 1
+          local.get $ca
+          local.get $cb
+          i32.lt_u
+          select
+          return
+        end
+        local.get $i
+        i32.const 1
+        i32.add
+        local.set $i
+        br $scan
+      end
+    end
+    
+    ;; Common prefix: the shorter string sorts first
+    local.get $len_a
+    local.get $len_b
+    i32.gt_u
+    local.get $len_a
+    local.get $len_b
+    i32.lt_u
+    i32.sub
+  )"#.to_string(),
+            r#"(func $string_from_i64 (param $value i64) (result i32)
+    (local $buf i32)
+    (local $len i32)
+    (local $mag i64)
+    (local $rest i64)
+    (local $neg i32)
+    (local $pos i32)
+    local.get $value
+    i64.const 0
+    i64.lt_s
+    local.set $neg
+    
+    ;; Magnitude as unsigned (0 - i64::MIN wraps to 2^63)
+    i64.const 0
+    local.get $value
+    i64.sub
+    local.get $value
+    local.get $neg
+    select
+    local.tee $mag
+    local.set $rest
+    
+    ;; Count digits (plus the sign) so the buffer is exactly one heap block
+    local.get $neg
+    local.set $len
+    loop $count
+      local.get $len
+      i32.const 1
+      i32.add
+      local.set $len
+      local.get $rest
+      i64.const 10
+      i64.div_u
+      local.tee $rest
+      i64.const 0
+      i64.ne
+      br_if $count
+    end
+    local.get $len
+    call $malloc
+    local.set $buf
+    
+    ;; Digits from the end
+    local.get $len
+    local.set $pos
+    loop $digits
+      local.get $pos
+      i32.const 1
+      i32.sub
+      local.set $pos
+      local.get $buf
+      local.get $pos
+      i32.add
+      local.get $mag
+      i64.const 10
+      i64.rem_u
+      i32.wrap_i64
+      i32.const 48
+      i32.add
+      i32.store8
+      local.get $mag
+      i64.const 10
+      i64.div_u
+      local.tee $mag
+      i64.const 0
+      i64.ne
+      br_if $digits
+    end
+    
+    local.get $neg
+    if
+      local.get $buf
+      i32.const 45
+      i32.store8
+    end
+    
+    local.get $buf
+    local.get $len
+    call $string_new
+  )"#.to_string(),
+        ]
+    }
+    
+    /// Generate ArrayBox runtime helpers; elements are 8-byte slots in a separate buffer
+    ///
+    /// - `$array_slot(array, index)`: address of an existing element (traps when out of bounds)
+    /// - `$array_push_slot(array)`: address of a new last element, growing the buffer when full
+    pub fn get_array_functions(&self) -> Vec<String> {
+        vec![
+            r#"(func $array_slot (param $array i32) (param $index i64) (result i32)
+    ;; Unsigned compare also rejects negative indices
+    local.get $index
+    local.get $array
+    i32.load offset=16
+    i64.extend_i32_u
+    i64.ge_u
+    if
+      unreachable
+    end
+    local.get $array
+    i32.load offset=12
+    local.get $index
+    i32.wrap_i64
+    i32.const 8
+    i32.mul
+    i32.add
+  )"#.to_string(),
+            r#"(func $array_push_slot (param $array i32) (result i32)
+    (local $len i32)
+    (local $cap i32)
+    (local $buf i32)
+    local.get $array
+    i32.load offset=16
+    local.set $len
+    local.get $array
+    i32.load offset=20
+    local.set $cap
+    
+    ;; Full: move the elements to a buffer twice as large (at least 4 slots)
+    local.get $len
+    local.get $cap
+    i32.ge_u
+    if
+      local.get $cap
+      i32.const 2
+      i32.mul
+      i32.const 4
+      local.get $cap
+      i32.const 2
+      i32.ge_u
+      select
+      local.set $cap
+      local.get $cap
+      i32.const 8
+      i32.mul
+      call $malloc
+      local.set $buf
+      local.get $buf
+      local.get $array
+      i32.load offset=12
+      local.get $len
+      i32.const 8
+      i32.mul
+      call $memcpy
+      local.get $array
+      local.get $buf
+      i32.store offset=12
+      local.get $array
+      local.get $cap
+      i32.store offset=20
+    end
+    
+    local.get $array
+    local.get $len
+    i32.const 1
+    i32.add
+    i32.store offset=16
+    local.get $array
+    i32.load offset=12
+    local.get $len
+    i32.const 8
+    i32.mul
+    i32.add
+  )"#.to_string(),
+        ]
+    }
+    
+    /// Generate HostBox proxy helpers
+    ///
+    /// - `$host_box_new(handle)`: new proxy for a host handle, flagged so the sweep releases it
+    /// - `$host_handle(box)`: handle of a proxy (traps for any other Box)
+    pub fn get_host_box_functions(&self) -> Vec<String> {
+        let host_id = self.get_type_id("HostBox").unwrap_or(0x1006);
+        vec![
+            format!(
+                r#"(func $host_box_new (param $handle i32) (result i32)
+    (local $ptr i32)
+    call $alloc_hostbox
+    local.tee $ptr
+    local.get $handle
+    i32.store offset={}
+    
+    ;; Block flags: allocated + HostBox proxy
+    local.get $ptr
+    i32.const 4
+    i32.sub
+    i32.const 5
+    i32.store
+    local.get $ptr
+  )"#,
+                HOST_BOX_HANDLE_OFFSET
+            ),
+            format!(
+                r#"(func $host_handle (param $box i32) (result i32)
+    local.get $box
+    i32.load
+    i32.const {}
+    i32.ne
+    if
+      unreachable
+    end
+    local.get $box
+    i32.load offset={}
+  )"#,
+                host_id, HOST_BOX_HANDLE_OFFSET
+            ),
+        ]
+    }
+    
     /// Generate generic Box creation helper
     pub fn get_generic_box_alloc_function(&self) -> String {
         format!(
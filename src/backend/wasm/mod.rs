@@ -8,11 +8,18 @@
 mod codegen;
 mod memory;
 mod runtime;
+mod types;
+mod structure;
+mod host;
+mod web;
 // mod executor; // TODO: Fix WASM executor build errors
 
 pub use codegen::{WasmCodegen, WasmModule};
 pub use memory::{MemoryManager, BoxLayout};
 pub use runtime::RuntimeImports;
+pub use types::ValueKind;
+pub use host::{HostState, HandleTable, WasmValue, WasmExecution, link_host_functions, run_main};
+pub use web::{WebBundle, LOADER_NAME, LOADER_SOURCE, loader_imports};
 // pub use executor::WasmExecutor; // TODO: Fix WASM executor build errors
 
 use crate::mir::MirModule;
@@ -24,6 +31,7 @@ pub enum WasmError {
     MemoryError(String),
     UnsupportedInstruction(String),
     WasmValidationError(String),
+    RuntimeError(String),
     IOError(String),
 }
 
@@ -34,6 +42,7 @@ impl std::fmt::Display for WasmError {
             WasmError::MemoryError(msg) => write!(f, "Memory error: {}", msg),
             WasmError::UnsupportedInstruction(msg) => write!(f, "Unsupported instruction: {}", msg),
             WasmError::WasmValidationError(msg) => write!(f, "WASM validation error: {}", msg),
+            WasmError::RuntimeError(msg) => write!(f, "Runtime error: {}", msg),
             WasmError::IOError(msg) => write!(f, "IO error: {}", msg),
         }
     }
@@ -97,59 +106,19 @@ impl WasmBackend {
     
     /// Compile MIR module to WAT text format (for debugging)
     pub fn compile_to_wat(&mut self, mir_module: MirModule) -> Result<String, WasmError> {
-        let wasm_module = self.codegen.generate_module(mir_module, &self.memory_manager, &self.runtime)?;
+        let wasm_module = self.codegen.generate_module(mir_module, &mut self.memory_manager, &self.runtime)?;
         Ok(wasm_module.to_wat())
     }
     
     /// Execute WASM bytes using wasmtime (for testing)
-    pub fn execute_wasm(&self, wasm_bytes: &[u8]) -> Result<i32, WasmError> {
+    ///
+    /// All `env.*` imports are provided by the host; output is echoed and captured.
+    pub fn execute_wasm(&self, wasm_bytes: &[u8]) -> Result<WasmExecution, WasmError> {
         let engine = wasmtime::Engine::default();
         let module = wasmtime::Module::new(&engine, wasm_bytes)
             .map_err(|e| WasmError::WasmValidationError(format!("Module creation failed: {}", e)))?;
         
-        let mut store = wasmtime::Store::new(&engine, ());
-        
-        // Create print function import
-        let print_func = wasmtime::Func::wrap(&mut store, |value: i32| {
-            println!("{}", value);
-        });
-        
-        // Create print_str function import for string debugging
-        let print_str_func = wasmtime::Func::wrap(&mut store, |mut caller: wasmtime::Caller<'_, ()>, ptr: i32, len: i32| -> Result<(), wasmtime::Error> {
-            let memory = caller.get_export("memory")
-                .and_then(|export| export.into_memory())
-                .ok_or_else(|| wasmtime::Error::msg("Memory export not found"))?;
-            
-            let data = memory.data(&caller);
-            let start = ptr as usize;
-            let end = start + len as usize;
-            
-            if end <= data.len() {
-                let bytes = &data[start..end];
-                if let Ok(s) = std::str::from_utf8(bytes) {
-                    println!("String: {}", s);
-                } else {
-                    println!("Invalid UTF-8 bytes: {:?}", bytes);
-                }
-            } else {
-                println!("String out of bounds: ptr={}, len={}, memory_size={}", ptr, len, data.len());
-            }
-            
-            Ok(())
-        });
-        
-        let imports = [print_func.into(), print_str_func.into()];
-        let instance = wasmtime::Instance::new(&mut store, &module, &imports)
-            .map_err(|e| WasmError::WasmValidationError(format!("Instance creation failed: {}", e)))?;
-        
-        // Call main function
-        let main_func = instance.get_typed_func::<(), i32>(&mut store, "main")
-            .map_err(|e| WasmError::WasmValidationError(format!("Main function not found: {}", e)))?;
-        
-        let result = main_func.call(&mut store, ())
-            .map_err(|e| WasmError::WasmValidationError(format!("Execution failed: {}", e)))?;
-        
-        Ok(result)
+        run_main(&engine, &module, true)
     }
 }
 
@@ -51,6 +51,28 @@ impl RuntimeImports {
             result: None,
         });
         
+        // Typed print for unboxed values: env.print_i64 / env.print_f64 / env.print_bool
+        self.imports.push(ImportFunction {
+            module: "env".to_string(),
+            name: "print_i64".to_string(),
+            params: vec!["i64".to_string()],
+            result: None,
+        });
+        
+        self.imports.push(ImportFunction {
+            module: "env".to_string(),
+            name: "print_f64".to_string(),
+            params: vec!["f64".to_string()],
+            result: None,
+        });
+        
+        self.imports.push(ImportFunction {
+            module: "env".to_string(),
+            name: "print_bool".to_string(),
+            params: vec!["i32".to_string()],
+            result: None,
+        });
+        
         // Phase 9.7: Box FFI/ABI imports per BID specifications
         
         // env.console_log for console.log(message) - (string_ptr, string_len)
@@ -123,8 +145,35 @@ impl RuntimeImports {
             result: Some("i32".to_string()), // cloned_box_ptr
         });
         
-        // Future: env.file_read, env.file_write for file I/O
-        // Future: env.http_request for network access
+        // Host boxes: builtin/plugin boxes (MapBox, FileBox, SocketBox...) live in a host-side
+        // handle table; the module holds HostBox proxies. Arguments are passed in a frame
+        // [count:i32][want:i32] followed by 16-byte slots [tag:i32][pad:i32][payload:8], tagged
+        // with ValueKind codes. box_call writes its result over the frame as [tag][pad][payload],
+        // converted to the `want` kind.
+        
+        // box_new - Create a host Box by type name
+        self.imports.push(ImportFunction {
+            module: "env".to_string(),
+            name: "box_new".to_string(),
+            params: vec!["i32".to_string(), "i32".to_string()], // type_name_ptr (StringBox), args_ptr
+            result: Some("i32".to_string()), // handle
+        });
+        
+        // box_call - Call a method of a host Box
+        self.imports.push(ImportFunction {
+            module: "env".to_string(),
+            name: "box_call".to_string(),
+            params: vec!["i32".to_string(), "i32".to_string(), "i32".to_string()], // handle, method_ptr (StringBox), args_ptr
+            result: Some("i32".to_string()), // result tag
+        });
+        
+        // box_release - Drop a handle whose proxy was collected
+        self.imports.push(ImportFunction {
+            module: "env".to_string(),
+            name: "box_release".to_string(),
+            params: vec!["i32".to_string()], // handle
+            result: None,
+        });
     }
     
     /// Get all import declarations in WAT format
@@ -163,6 +212,11 @@ impl RuntimeImports {
         });
     }
     
+    /// All declared import functions, in declaration order
+    pub fn functions(&self) -> &[ImportFunction] {
+        &self.imports
+    }
+    
     /// Check if an import is available
     pub fn has_import(&self, name: &str) -> bool {
         self.imports.iter().any(|import| import.name == name)
@@ -199,6 +253,15 @@ impl RuntimeImports {
                         js.push_str("      console.log(str);\n");
                         js.push_str("    },\n");
                     },
+                    "print_i64" => {
+                        js.push_str("    print_i64: (value) => console.log(value.toString()),\n");
+                    },
+                    "print_f64" => {
+                        js.push_str("    print_f64: (value) => console.log(String(value)),\n");
+                    },
+                    "print_bool" => {
+                        js.push_str("    print_bool: (value) => console.log(value !== 0 ? 'true' : 'false'),\n");
+                    },
                     "console_log" => {
                         js.push_str("    console_log: (ptr, len) => {\n");
                         js.push_str("      const memory = instance.exports.memory;\n");
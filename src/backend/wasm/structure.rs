@@ -0,0 +1,260 @@
+/*!
+ * WASM Control Flow Structure - CFG analysis for structured control flow
+ *
+ * WASM has no gotos, so MIR's basic blocks are re-nested into `block`/`loop`/`if`.
+ * This follows the dominator-tree stackifier from "Beyond Relooper" (Ramsey, 2022):
+ * - a block that is the target of a back edge becomes a `loop`
+ * - a block with two or more forward predecessors (a merge node) is placed right after
+ *   a `block` that forward branches can `br` out of
+ * - any other block has a single forward predecessor and is emitted inline
+ *
+ * Only reducible control flow can be nested this way; the MIR builder never produces anything else.
+ *
+ * Liveness is computed here too, so the collector only roots values that survive a collection point.
+ */
+
+use super::WasmError;
+use crate::mir::{BasicBlock, BasicBlockId, MirFunction, MirInstruction, ValueId};
+use std::collections::{HashMap, HashSet};
+
+/// Control flow facts the code generator needs to nest a function's blocks
+#[derive(Debug)]
+pub struct ControlFlow {
+    /// Reachable blocks in reverse postorder
+    pub order: Vec<BasicBlockId>,
+    rpo_index: HashMap<BasicBlockId, usize>,
+    idom: HashMap<BasicBlockId, BasicBlockId>,
+    loop_headers: HashSet<BasicBlockId>,
+    merge_nodes: HashSet<BasicBlockId>,
+}
+
+impl ControlFlow {
+    /// Analyze the blocks reachable from the entry of `function`
+    pub fn analyze(function: &MirFunction) -> Result<Self, WasmError> {
+        let mut successors: HashMap<BasicBlockId, Vec<BasicBlockId>> = HashMap::new();
+        for (id, block) in &function.blocks {
+            let targets = branch_targets(block);
+            for target in &targets {
+                if !function.blocks.contains_key(target) {
+                    return Err(WasmError::CodegenError(format!("Branch to unknown block {}", target)));
+                }
+            }
+            successors.insert(*id, targets);
+        }
+
+        let order = reverse_postorder(function.entry_block, &successors);
+        let rpo_index: HashMap<_, _> = order.iter().enumerate().map(|(i, b)| (*b, i)).collect();
+
+        let mut predecessors: HashMap<BasicBlockId, Vec<BasicBlockId>> = HashMap::new();
+        for block in &order {
+            for succ in &successors[block] {
+                predecessors.entry(*succ).or_default().push(*block);
+            }
+        }
+
+        let idom = immediate_dominators(&order, &rpo_index, &predecessors);
+        let mut cfg = Self {
+            order,
+            rpo_index,
+            idom,
+            loop_headers: HashSet::new(),
+            merge_nodes: HashSet::new(),
+        };
+
+        for block in cfg.order.clone() {
+            let preds = predecessors.get(&block).map(Vec::as_slice).unwrap_or(&[]);
+            let mut forward = 0;
+            for pred in preds {
+                if cfg.is_backward(*pred, block) {
+                    // A back edge must go to a block dominating its source, otherwise the loop has
+                    // several entries and cannot be expressed with WASM's `loop`
+                    if !cfg.dominates(block, *pred) {
+                        return Err(WasmError::UnsupportedInstruction(format!(
+                            "Irreducible control flow: {} -> {} in {}", pred, block, function.signature.name
+                        )));
+                    }
+                    cfg.loop_headers.insert(block);
+                } else {
+                    forward += 1;
+                }
+            }
+            if forward >= 2 {
+                cfg.merge_nodes.insert(block);
+            }
+        }
+
+        Ok(cfg)
+    }
+
+    /// Edge goes to an earlier (or the same) block in reverse postorder
+    pub fn is_backward(&self, from: BasicBlockId, to: BasicBlockId) -> bool {
+        self.rpo_index[&to] <= self.rpo_index[&from]
+    }
+
+    pub fn is_loop_header(&self, block: BasicBlockId) -> bool {
+        self.loop_headers.contains(&block)
+    }
+
+    pub fn is_merge_node(&self, block: BasicBlockId) -> bool {
+        self.merge_nodes.contains(&block)
+    }
+
+    /// Dominator-tree children of `block` that are merge nodes, latest in reverse postorder first
+    /// (the first one becomes the outermost `block`, so it is emitted last)
+    pub fn merge_children(&self, block: BasicBlockId) -> Vec<BasicBlockId> {
+        let mut children: Vec<_> = self.order.iter()
+            .filter(|b| self.idom.get(b) == Some(&block) && **b != block && self.is_merge_node(**b))
+            .copied()
+            .collect();
+        children.sort_by_key(|b| std::cmp::Reverse(self.rpo_index[b]));
+        children
+    }
+
+    /// Whether `a` dominates `b`
+    pub fn dominates(&self, a: BasicBlockId, mut b: BasicBlockId) -> bool {
+        loop {
+            if a == b {
+                return true;
+            }
+            match self.idom.get(&b) {
+                Some(parent) if *parent != b => b = *parent,
+                _ => return false,
+            }
+        }
+    }
+}
+
+fn branch_targets(block: &BasicBlock) -> Vec<BasicBlockId> {
+    match &block.terminator {
+        Some(MirInstruction::Jump { target }) => vec![*target],
+        Some(MirInstruction::Branch { then_bb, else_bb, .. }) => vec![*then_bb, *else_bb],
+        _ => vec![],
+    }
+}
+
+/// Values that are live right after some instruction matching `is_point` (other than the value
+/// that instruction defines). Phi inputs are live at the end of their predecessor, not in the phi's block
+pub fn live_across(function: &MirFunction, is_point: impl Fn(&MirInstruction) -> bool) -> HashSet<ValueId> {
+    let uses = |instruction: &MirInstruction| match instruction {
+        MirInstruction::Phi { .. } => Vec::new(),
+        other => other.used_values(),
+    };
+    
+    // Backward dataflow to a fixpoint over live-in sets
+    let mut live_in: HashMap<BasicBlockId, HashSet<ValueId>> = HashMap::new();
+    let live_out = |live_in: &HashMap<BasicBlockId, HashSet<ValueId>>, id: BasicBlockId, block: &BasicBlock| {
+        let mut live = HashSet::new();
+        for succ in branch_targets(block) {
+            live.extend(live_in.get(&succ).into_iter().flatten().copied());
+            for instruction in function.blocks.get(&succ).into_iter().flat_map(|b| b.all_instructions()) {
+                if let MirInstruction::Phi { inputs, .. } = instruction {
+                    live.extend(inputs.iter().filter(|(pred, _)| *pred == id).map(|(_, value)| *value));
+                }
+            }
+        }
+        live
+    };
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (id, block) in &function.blocks {
+            let mut live = live_out(&live_in, *id, block);
+            for instruction in block.all_instructions().collect::<Vec<_>>().into_iter().rev() {
+                if let Some(dst) = instruction.dst_value() {
+                    live.remove(&dst);
+                }
+                live.extend(uses(instruction));
+            }
+            if live_in.get(id) != Some(&live) {
+                live_in.insert(*id, live);
+                changed = true;
+            }
+        }
+    }
+    
+    let mut across = HashSet::new();
+    for (id, block) in &function.blocks {
+        let mut live = live_out(&live_in, *id, block);
+        for instruction in block.all_instructions().collect::<Vec<_>>().into_iter().rev() {
+            if let Some(dst) = instruction.dst_value() {
+                live.remove(&dst);
+            }
+            if is_point(instruction) {
+                across.extend(live.iter().copied());
+            }
+            live.extend(uses(instruction));
+        }
+    }
+    across
+}
+
+fn reverse_postorder(
+    entry: BasicBlockId,
+    successors: &HashMap<BasicBlockId, Vec<BasicBlockId>>,
+) -> Vec<BasicBlockId> {
+    let mut visited = HashSet::new();
+    let mut postorder = Vec::new();
+    // Iterative DFS: (block, index of next successor to visit)
+    let mut stack = vec![(entry, 0)];
+    visited.insert(entry);
+    while let Some((block, next)) = stack.pop() {
+        let succs = successors.get(&block).map(Vec::as_slice).unwrap_or(&[]);
+        if let Some(succ) = succs.get(next) {
+            stack.push((block, next + 1));
+            if visited.insert(*succ) {
+                stack.push((*succ, 0));
+            }
+        } else {
+            postorder.push(block);
+        }
+    }
+    postorder.reverse();
+    postorder
+}
+
+/// Cooper, Harvey & Kennedy, "A Simple, Fast Dominance Algorithm"; the entry is its own idom
+fn immediate_dominators(
+    order: &[BasicBlockId],
+    rpo_index: &HashMap<BasicBlockId, usize>,
+    predecessors: &HashMap<BasicBlockId, Vec<BasicBlockId>>,
+) -> HashMap<BasicBlockId, BasicBlockId> {
+    let mut idom = HashMap::new();
+    let Some(&entry) = order.first() else { return idom };
+    idom.insert(entry, entry);
+
+    let intersect = |idom: &HashMap<BasicBlockId, BasicBlockId>, mut a: BasicBlockId, mut b: BasicBlockId| {
+        while a != b {
+            while rpo_index[&a] > rpo_index[&b] {
+                a = idom[&a];
+            }
+            while rpo_index[&b] > rpo_index[&a] {
+                b = idom[&b];
+            }
+        }
+        a
+    };
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for block in &order[1..] {
+            let mut new_idom = None;
+            for pred in predecessors.get(block).map(Vec::as_slice).unwrap_or(&[]) {
+                if !idom.contains_key(pred) {
+                    continue;
+                }
+                new_idom = Some(match new_idom {
+                    None => *pred,
+                    Some(current) => intersect(&idom, *pred, current),
+                });
+            }
+            if let Some(new_idom) = new_idom {
+                if idom.get(block) != Some(&new_idom) {
+                    idom.insert(*block, new_idom);
+                    changed = true;
+                }
+            }
+        }
+    }
+    idom
+}
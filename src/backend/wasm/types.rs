@@ -0,0 +1,432 @@
+/*!
+ * WASM Value Types - Static representation of MIR values
+ *
+ * MIR values are untyped (the builder even wraps literals as `new IntegerBox(%c)`),
+ * so the backend infers one representation per ValueId before emitting code:
+ * Integer → i64, Float → f64, Bool → i32 (0/1), String/Box → i32 pointer into linear memory.
+ * Parameter, result and field kinds flow between functions, so inference runs over the whole module.
+ */
+
+use super::BoxLayout;
+use crate::mir::{
+    BinaryOp, ConstValue, MirFunction, MirInstruction, MirModule, MirType, TypeOpKind, UnaryOp, ValueId,
+};
+use std::collections::{HashMap, HashSet};
+
+/// Pseudo field holding the element kind of ArrayBox values
+pub const ARRAY_ELEMENT: &str = "[]";
+
+/// Upper bound on whole-module inference rounds (kinds only widen, so this is rarely reached)
+const MAX_MODULE_ROUNDS: usize = 32;
+
+/// How a MIR value is represented in WASM
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueKind {
+    Integer,
+    Float,
+    Bool,
+    /// Pointer to a StringBox
+    String,
+    /// Pointer to any other Box
+    Box,
+    Void,
+}
+
+impl ValueKind {
+    /// WASM value type used for locals, params and results
+    pub fn wasm_type(self) -> &'static str {
+        match self {
+            ValueKind::Integer => "i64",
+            ValueKind::Float => "f64",
+            _ => "i32",
+        }
+    }
+
+    /// Values of this kind are heap pointers the collector must know about
+    pub fn is_pointer(self) -> bool {
+        matches!(self, ValueKind::String | ValueKind::Box)
+    }
+
+    /// Constant of this kind's wasm type with all bits zero
+    pub fn zero(self) -> String {
+        format!("{}.const 0", self.wasm_type())
+    }
+
+    /// Code exported through the `result_kind` global so hosts can decode `main`'s result
+    pub fn code(self) -> i32 {
+        match self {
+            ValueKind::Void => 0,
+            ValueKind::Integer => 1,
+            ValueKind::Float => 2,
+            ValueKind::Bool => 3,
+            ValueKind::String => 4,
+            ValueKind::Box => 5,
+        }
+    }
+
+    pub fn from_code(code: i32) -> Option<Self> {
+        Some(match code {
+            0 => ValueKind::Void,
+            1 => ValueKind::Integer,
+            2 => ValueKind::Float,
+            3 => ValueKind::Bool,
+            4 => ValueKind::String,
+            5 => ValueKind::Box,
+            _ => return None,
+        })
+    }
+
+    /// Builtin boxes that are kept unboxed in WASM
+    pub fn from_box_type(box_type: &str) -> Option<Self> {
+        match box_type {
+            "IntegerBox" => Some(ValueKind::Integer),
+            "FloatBox" => Some(ValueKind::Float),
+            "BoolBox" => Some(ValueKind::Bool),
+            "StringBox" => Some(ValueKind::String),
+            _ => None,
+        }
+    }
+
+    pub fn from_mir_type(ty: &MirType) -> Self {
+        match ty {
+            MirType::Integer => ValueKind::Integer,
+            MirType::Float => ValueKind::Float,
+            MirType::Bool => ValueKind::Bool,
+            MirType::String => ValueKind::String,
+            MirType::Box(name) => Self::from_box_type(name).unwrap_or(ValueKind::Box),
+            MirType::Void => ValueKind::Void,
+            _ => ValueKind::Box,
+        }
+    }
+
+    /// Like `from_mir_type`, but `?` says nothing about the value
+    fn from_known_mir_type(ty: &MirType) -> Option<Self> {
+        match ty {
+            MirType::Unknown => None,
+            ty => Some(Self::from_mir_type(ty)),
+        }
+    }
+
+    fn is_numeric(self) -> bool {
+        matches!(self, ValueKind::Integer | ValueKind::Float)
+    }
+
+    /// Kind of a value that may come from either side (phi inputs): ints widen to floats
+    fn join(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (a, b) if a.is_numeric() && b.is_numeric() => ValueKind::Float,
+            (a, _) => a,
+        }
+    }
+}
+
+/// Result of kind inference for one function
+#[derive(Debug, Default)]
+pub struct TypeInfo {
+    pub kinds: HashMap<ValueId, ValueKind>,
+    /// Box type of values created by `NewBox` or passed as `box<T>` params (followed through copies and refs)
+    pub box_types: HashMap<ValueId, String>,
+    /// String constants; `Call` targets are `const "Name.method/N"` values
+    pub strings: HashMap<ValueId, String>,
+}
+
+impl TypeInfo {
+    /// Values never constrained by any instruction are treated as Box pointers
+    pub fn kind(&self, value: ValueId) -> ValueKind {
+        self.kinds.get(&value).copied().unwrap_or(ValueKind::Box)
+    }
+
+    /// Result kind of the function: the kind of its returned values, falling back to the
+    /// signature (the builder declares `main` as void even when it returns a value)
+    pub fn result_kind(&self, function: &MirFunction) -> ValueKind {
+        let returned = function.blocks.values()
+            .filter_map(|block| match &block.terminator {
+                Some(MirInstruction::Return { value: Some(value) }) => self.kinds.get(value).copied(),
+                _ => None,
+            })
+            .reduce(ValueKind::join);
+        returned.unwrap_or_else(|| ValueKind::from_mir_type(&function.signature.return_type))
+    }
+
+    /// Module function invoked by a `Call` through a string constant, or by a `BoxCall` on a
+    /// user box (`Point.sum/0`), with the receiver passed as the first argument
+    pub fn callee(&self, instruction: &MirInstruction, module: &ModuleTypes) -> Option<(String, Vec<ValueId>)> {
+        match instruction {
+            MirInstruction::Call { func, args, .. } => {
+                let name = self.strings.get(func)?;
+                module.functions.contains(name).then(|| (name.clone(), args.clone()))
+            }
+            MirInstruction::BoxCall { box_val, method, args, .. } => {
+                let name = format!("{}.{}/{}", self.box_types.get(box_val)?, method, args.len());
+                let mut call_args = vec![*box_val];
+                call_args.extend(args);
+                module.functions.contains(&name).then_some((name, call_args))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Kinds shared between the functions of a module
+#[derive(Debug, Default)]
+pub struct ModuleTypes {
+    /// Names of all functions in the module
+    pub functions: HashSet<String>,
+    /// Parameter kinds of each function, joined over its call sites
+    pub params: HashMap<String, Vec<Option<ValueKind>>>,
+    /// Result kind of each function
+    pub results: HashMap<String, ValueKind>,
+    /// Kind stored in each box field, by (box type, field)
+    pub field_kinds: HashMap<(String, String), ValueKind>,
+    /// Fields accessed through each box type, in first-use order
+    pub box_fields: HashMap<String, Vec<String>>,
+    /// Value kinds of each function
+    pub infos: HashMap<String, TypeInfo>,
+}
+
+impl ModuleTypes {
+    pub fn field_kind(&self, box_type: &str, field: &str) -> Option<ValueKind> {
+        self.field_kinds.get(&(box_type.to_string(), field.to_string())).copied()
+    }
+
+    /// Box types declared in Nyash code have their methods lowered to `Type.method/N` functions
+    pub fn is_user_box(&self, box_type: &str) -> bool {
+        let prefix = format!("{}.", box_type);
+        self.functions.iter().any(|name| name.starts_with(&prefix))
+    }
+
+    fn set_field_kind(&mut self, box_type: &str, field: &str, kind: ValueKind) {
+        let key = (box_type.to_string(), field.to_string());
+        let merged = self.field_kinds.get(&key).map_or(kind, |old| old.join(kind));
+        self.field_kinds.insert(key, merged);
+    }
+
+    fn note_field(&mut self, box_type: &str, field: &str) {
+        let fields = self.box_fields.entry(box_type.to_string()).or_default();
+        if !fields.iter().any(|f| f == field) {
+            fields.push(field.to_string());
+        }
+    }
+
+    fn join_param(&mut self, function: &str, index: usize, kind: ValueKind) {
+        if let Some(slot) = self.params.get_mut(function).and_then(|params| params.get_mut(index)) {
+            *slot = Some(slot.map_or(kind, |old| old.join(kind)));
+        }
+    }
+}
+
+/// Infer value kinds for every function, re-running until params, results and fields are stable
+pub fn infer_module(module: &MirModule, layouts: &HashMap<String, BoxLayout>) -> ModuleTypes {
+    let mut types = ModuleTypes {
+        functions: module.functions.keys().cloned().collect(),
+        ..ModuleTypes::default()
+    };
+    for (name, function) in &module.functions {
+        let seeds = function.signature.params.iter().map(ValueKind::from_known_mir_type).collect();
+        types.params.insert(name.clone(), seeds);
+    }
+
+    let mut names: Vec<&String> = module.functions.keys().collect();
+    names.sort();
+    for _ in 0..MAX_MODULE_ROUNDS {
+        let before = (types.params.clone(), types.results.clone(), types.field_kinds.clone());
+        for name in &names {
+            let function = &module.functions[*name];
+            let info = infer_types(function, layouts, &mut types);
+            types.results.insert((*name).clone(), info.result_kind(function));
+            types.infos.insert((*name).clone(), info);
+        }
+        if before == (types.params.clone(), types.results.clone(), types.field_kinds.clone()) {
+            break;
+        }
+    }
+    types
+}
+
+/// Infer value kinds by propagating through the function until nothing changes
+pub fn infer_types(function: &MirFunction, layouts: &HashMap<String, BoxLayout>, module: &mut ModuleTypes) -> TypeInfo {
+    let mut info = TypeInfo::default();
+    let seeds = module.params.get(&function.signature.name).cloned().unwrap_or_default();
+    for (i, param) in function.params.iter().enumerate() {
+        if let Some(Some(kind)) = seeds.get(i) {
+            info.kinds.insert(*param, *kind);
+        }
+        if let Some(MirType::Box(name)) = function.signature.params.get(i) {
+            if ValueKind::from_box_type(name).is_none() {
+                info.box_types.insert(*param, name.clone());
+            }
+        }
+    }
+
+    let mut blocks: Vec<_> = function.blocks.values().collect();
+    blocks.sort_by_key(|block| block.id);
+    loop {
+        let mut changed = false;
+        for instruction in blocks.iter().flat_map(|block| block.all_instructions()) {
+            track_boxes(instruction, &mut info, layouts, module);
+            if let Some((callee, args)) = info.callee(instruction, module) {
+                for (i, arg) in args.iter().enumerate() {
+                    if let Some(kind) = info.kinds.get(arg).copied() {
+                        module.join_param(&callee, i, kind);
+                    }
+                }
+            }
+            if let Some((dst, kind)) = infer_instruction(instruction, &info, module) {
+                let merged = info.kinds.get(&dst).map_or(kind, |old| old.join(kind));
+                if info.kinds.insert(dst, merged) != Some(merged) {
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            return info;
+        }
+    }
+}
+
+/// Record box types, string constants and field kinds that later instructions need
+fn track_boxes(
+    instruction: &MirInstruction,
+    info: &mut TypeInfo,
+    layouts: &HashMap<String, BoxLayout>,
+    module: &mut ModuleTypes,
+) {
+    match instruction {
+        MirInstruction::Const { dst, value: ConstValue::String(s) } => {
+            info.strings.insert(*dst, s.clone());
+        }
+        MirInstruction::NewBox { dst, box_type, args } if ValueKind::from_box_type(box_type).is_none() => {
+            info.box_types.insert(*dst, box_type.clone());
+            // Builtin layouts (DataBox) take their field values as constructor arguments;
+            // user boxes pass them on to their `birth` method instead
+            if let Some(layout) = layouts.get(box_type).filter(|_| !module.is_user_box(box_type)) {
+                for (field, arg) in layout.ordered_fields().into_iter().zip(args) {
+                    if let Some(kind) = info.kinds.get(arg).copied() {
+                        module.set_field_kind(box_type, field, kind);
+                    }
+                }
+            }
+        }
+        MirInstruction::Copy { dst, src: source }
+        | MirInstruction::RefNew { dst, box_val: source }
+        | MirInstruction::Cast { dst, value: source, .. }
+        | MirInstruction::TypeOp { dst, op: TypeOpKind::Cast, value: source, .. } => {
+            if let Some(box_type) = info.box_types.get(source).cloned() {
+                info.box_types.insert(*dst, box_type);
+            }
+        }
+        MirInstruction::Phi { dst, inputs } => {
+            if let Some(box_type) = inputs.iter().find_map(|(_, v)| info.box_types.get(v)).cloned() {
+                info.box_types.insert(*dst, box_type);
+            }
+        }
+        MirInstruction::RefGet { reference, field, .. } => {
+            if let Some(box_type) = info.box_types.get(reference) {
+                module.note_field(box_type, field);
+            }
+        }
+        MirInstruction::RefSet { reference, field, value } => {
+            if let Some(box_type) = info.box_types.get(reference).cloned() {
+                module.note_field(&box_type, field);
+                if let Some(kind) = info.kinds.get(value).copied() {
+                    module.set_field_kind(&box_type, field, kind);
+                }
+            }
+        }
+        MirInstruction::ArraySet { value, .. } => {
+            if let Some(kind) = info.kinds.get(value).copied() {
+                module.set_field_kind("ArrayBox", ARRAY_ELEMENT, kind);
+            }
+        }
+        MirInstruction::BoxCall { box_val, method, args, .. }
+            if info.box_types.get(box_val).map(String::as_str) == Some("ArrayBox") =>
+        {
+            let stored = match (method.as_str(), args.as_slice()) {
+                ("push", [value]) | ("set", [_, value]) => info.kinds.get(value).copied(),
+                _ => None,
+            };
+            if let Some(kind) = stored {
+                module.set_field_kind("ArrayBox", ARRAY_ELEMENT, kind);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn infer_instruction(instruction: &MirInstruction, info: &TypeInfo, module: &ModuleTypes) -> Option<(ValueId, ValueKind)> {
+    let known = |value: &ValueId| info.kinds.get(value).copied();
+    let box_type = |value: &ValueId| info.box_types.get(value).map(String::as_str);
+    let kind = match instruction {
+        MirInstruction::Const { dst, value } => return Some((*dst, match value {
+            ConstValue::Integer(_) => ValueKind::Integer,
+            ConstValue::Float(_) => ValueKind::Float,
+            ConstValue::Bool(_) => ValueKind::Bool,
+            ConstValue::String(_) => ValueKind::String,
+            ConstValue::Null | ConstValue::Void => ValueKind::Void,
+        })),
+        MirInstruction::NewBox { dst, box_type, .. } => {
+            return Some((*dst, ValueKind::from_box_type(box_type).unwrap_or(ValueKind::Box)));
+        }
+        MirInstruction::BinOp { dst, op, lhs, rhs } => {
+            if matches!(op, BinaryOp::And | BinaryOp::Or) {
+                return Some((*dst, ValueKind::Bool));
+            }
+            let (l, r) = (known(lhs)?, known(rhs)?);
+            let kind = match op {
+                BinaryOp::Add if l == ValueKind::String || r == ValueKind::String => ValueKind::String,
+                _ if l == ValueKind::Float || r == ValueKind::Float => ValueKind::Float,
+                _ => ValueKind::Integer,
+            };
+            return Some((*dst, kind));
+        }
+        MirInstruction::UnaryOp { dst, op, operand } => return Some((*dst, match op {
+            UnaryOp::Neg => known(operand)?,
+            UnaryOp::Not => ValueKind::Bool,
+            UnaryOp::BitNot => ValueKind::Integer,
+        })),
+        MirInstruction::Compare { dst, .. }
+        | MirInstruction::TypeCheck { dst, .. }
+        | MirInstruction::TypeOp { dst, op: TypeOpKind::Check, .. } => {
+            return Some((*dst, ValueKind::Bool));
+        }
+        MirInstruction::Catch { exception_value, .. } => return Some((*exception_value, ValueKind::Void)),
+        MirInstruction::Copy { dst, src: source }
+        | MirInstruction::RefNew { dst, box_val: source }
+        | MirInstruction::WeakNew { dst, box_val: source }
+        | MirInstruction::WeakLoad { dst, weak_ref: source }
+        | MirInstruction::WeakRef { dst, value: source, .. }
+        | MirInstruction::FutureNew { dst, value: source }
+        | MirInstruction::Await { dst, future: source } => (*dst, known(source)?),
+        MirInstruction::Phi { dst, inputs } => {
+            (*dst, inputs.iter().filter_map(|(_, v)| known(v)).reduce(ValueKind::join)?)
+        }
+        MirInstruction::RefGet { dst, reference, field } => (*dst, module.field_kind(box_type(reference)?, field)?),
+        MirInstruction::ArrayGet { dst, .. } => (*dst, module.field_kind("ArrayBox", ARRAY_ELEMENT)?),
+        MirInstruction::Cast { dst, value, target_type: ty }
+        | MirInstruction::TypeOp { dst, op: TypeOpKind::Cast, value, ty } => {
+            // Casts copy the value unchanged (as in the VM); the target type only fills in unknowns
+            (*dst, known(value).or_else(|| ValueKind::from_known_mir_type(ty))?)
+        }
+        MirInstruction::Call { dst: Some(dst), .. } | MirInstruction::BoxCall { dst: Some(dst), .. }
+            if info.callee(instruction, module).is_some() =>
+        {
+            let (callee, _) = info.callee(instruction, module)?;
+            (*dst, *module.results.get(&callee)?)
+        }
+        MirInstruction::BoxCall { dst: Some(dst), box_val, method, .. } => {
+            let kind = match (known(box_val), box_type(box_val), method.as_str()) {
+                (Some(ValueKind::String), _, "length") | (_, Some("ArrayBox"), "length") => ValueKind::Integer,
+                (_, Some("ArrayBox"), "get") => module.field_kind("ArrayBox", ARRAY_ELEMENT)?,
+                (_, Some("ArrayBox"), "push" | "set") => ValueKind::Void,
+                (_, _, "toString") => ValueKind::String,
+                (_, _, "equals") => ValueKind::Bool,
+                (Some(kind), _, "clone") => kind,
+                _ => ValueKind::Box,
+            };
+            (*dst, kind)
+        }
+        other => (other.dst_value()?, ValueKind::Box),
+    };
+    Some(kind)
+}
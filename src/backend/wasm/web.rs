@@ -0,0 +1,200 @@
+/*!
+ * Web Bundle - Browser-ready output of `nyash --compile-wasm --target web`
+ *
+ * A bundle is a directory holding:
+ * - `<name>.wasm`: the compiled module
+ * - `nyash.js`: ES module loader implementing every `env.*` import (web/nyash.js)
+ * - `index.html`: harness running `main`, showing its output and a canvas
+ * - `<name>.wasm.map`: JSON map from code section offsets to MIR function names; the loader
+ *   uses it to name the function a trap happened in. The parser does not record source
+ *   spans yet, so the map is per function rather than per line.
+ */
+
+use super::{WasmBackend, WasmError};
+use crate::mir::MirModule;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// ES module loader shipped with every bundle
+pub const LOADER_SOURCE: &str = include_str!("web/nyash.js");
+
+/// File name of the loader inside a bundle
+pub const LOADER_NAME: &str = "nyash.js";
+
+const INDEX_TEMPLATE: &str = include_str!("web/index.html");
+
+/// Compiled module plus everything needed to serve it
+pub struct WebBundle {
+    pub name: String,
+    pub wasm: Vec<u8>,
+    /// JSON text of `<name>.wasm.map`
+    pub source_map: String,
+}
+
+impl WebBundle {
+    /// Compile `module` and check that the loader implements every import it needs
+    pub fn build(backend: &mut WasmBackend, module: MirModule, name: &str) -> Result<Self, WasmError> {
+        let implemented = loader_imports(LOADER_SOURCE);
+        for import in backend.runtime.functions() {
+            match implemented.get(&import.name) {
+                Some(params) if *params == import.params.len() => {}
+                Some(params) => {
+                    return Err(WasmError::CodegenError(format!(
+                        "{} implements {}.{} with {} parameters, the module passes {}",
+                        LOADER_NAME, import.module, import.name, params, import.params.len()
+                    )));
+                }
+                None => {
+                    return Err(WasmError::CodegenError(format!(
+                        "{} does not implement the import {}.{}", LOADER_NAME, import.module, import.name
+                    )));
+                }
+            }
+        }
+
+        let wat = backend.compile_to_wat(module)?;
+        let wasm = backend.wat_to_wasm(&wat)?;
+        let source_map = build_source_map(&wat, &wasm, &format!("{}.wasm", name))?;
+        Ok(Self { name: name.to_string(), wasm, source_map })
+    }
+
+    pub fn wasm_file_name(&self) -> String {
+        format!("{}.wasm", self.name)
+    }
+
+    /// HTML harness loading this bundle's module
+    pub fn index_html(&self) -> String {
+        INDEX_TEMPLATE
+            .replace("{{TITLE}}", &self.name)
+            .replace("{{WASM}}", &self.wasm_file_name())
+    }
+
+    /// Write all files of the bundle into `dir` (created if missing); returns their paths
+    pub fn write_to<P: AsRef<Path>>(&self, dir: P) -> Result<Vec<PathBuf>, WasmError> {
+        let dir = dir.as_ref();
+        let io_error = |path: &Path, e: std::io::Error| WasmError::IOError(format!("{}: {}", path.display(), e));
+        fs::create_dir_all(dir).map_err(|e| io_error(dir, e))?;
+
+        let index_html = self.index_html();
+        let files: [(String, &[u8]); 4] = [
+            (self.wasm_file_name(), &self.wasm),
+            (format!("{}.map", self.wasm_file_name()), self.source_map.as_bytes()),
+            (LOADER_NAME.to_string(), LOADER_SOURCE.as_bytes()),
+            ("index.html".to_string(), index_html.as_bytes()),
+        ];
+        let mut written = Vec::new();
+        for (name, contents) in files {
+            let path = dir.join(name);
+            fs::write(&path, contents).map_err(|e| io_error(&path, e))?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+/// Parameter count of each `env.*` function defined by a loader: the methods of its
+/// `const env = { ... }` object, written as `name(a, b) {` on their own line
+pub fn loader_imports(loader: &str) -> HashMap<String, usize> {
+    let mut imports = HashMap::new();
+    let Some(start) = loader.find("const env = {") else {
+        return imports;
+    };
+    for line in loader[start..].lines().skip(1) {
+        if line.starts_with("  };") {
+            break;
+        }
+        let Some(method) = line.strip_prefix("    ") else { continue };
+        let Some((name, rest)) = method.split_once('(') else { continue };
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            continue;
+        }
+        let Some((params, _)) = rest.split_once(')') else { continue };
+        let count = params.split(',').filter(|p| !p.trim().is_empty()).count();
+        imports.insert(name.to_string(), count);
+    }
+    imports
+}
+
+/// Map each function body of the code section to the name of its `(func $name` in the WAT
+fn build_source_map(wat: &str, wasm: &[u8], file: &str) -> Result<String, WasmError> {
+    let names: Vec<&str> = wat.lines()
+        .filter_map(|line| line.trim_start().strip_prefix("(func $"))
+        .map(|rest| rest.split([' ', ')']).next().unwrap_or(rest))
+        .collect();
+    let bodies = code_section_bodies(wasm)
+        .ok_or_else(|| WasmError::WasmValidationError("Malformed code section".to_string()))?;
+    if bodies.len() != names.len() {
+        return Err(WasmError::WasmValidationError(format!(
+            "Code section has {} functions, the WAT defines {}", bodies.len(), names.len()
+        )));
+    }
+
+    let functions: Vec<serde_json::Value> = names.iter().zip(&bodies)
+        .map(|(name, (start, end))| serde_json::json!({ "name": name, "start": start, "end": end }))
+        .collect();
+    let map = serde_json::json!({ "version": 1, "file": file, "functions": functions });
+    serde_json::to_string_pretty(&map)
+        .map_err(|e| WasmError::CodegenError(format!("Source map serialization failed: {}", e)))
+}
+
+/// Module offsets `[start, end)` of every function body in the code section
+fn code_section_bodies(wasm: &[u8]) -> Option<Vec<(usize, usize)>> {
+    let mut pos = 8; // magic + version
+    while pos < wasm.len() {
+        let id = wasm[pos];
+        pos += 1;
+        let size = read_leb_u32(wasm, &mut pos)? as usize;
+        let end = pos.checked_add(size)?;
+        if id == 10 {
+            let count = read_leb_u32(wasm, &mut pos)?;
+            let mut bodies = Vec::with_capacity(count as usize);
+            for _ in 0..count {
+                let body_size = read_leb_u32(wasm, &mut pos)? as usize;
+                bodies.push((pos, pos + body_size));
+                pos += body_size;
+            }
+            return (pos <= end).then_some(bodies);
+        }
+        pos = end;
+    }
+    Some(Vec::new())
+}
+
+fn read_leb_u32(bytes: &[u8], pos: &mut usize) -> Option<u32> {
+    let mut result = 0u32;
+    for shift in (0..35).step_by(7) {
+        let byte = *bytes.get(*pos)?;
+        *pos += 1;
+        result |= ((byte & 0x7f) as u32) << shift;
+        if byte & 0x80 == 0 {
+            return Some(result);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loader_imports_are_parsed() {
+        let imports = loader_imports(LOADER_SOURCE);
+        assert_eq!(imports.get("print"), Some(&1));
+        assert_eq!(imports.get("box_call"), Some(&3));
+        assert_eq!(imports.get("canvas_fillText"), Some(&10));
+    }
+
+    #[test]
+    fn test_code_section_bodies() {
+        // One type, one function, code section with a single 2-byte body (no locals, end)
+        let wasm = [
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+            0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x02, 0x01, 0x00,
+            0x0a, 0x04, 0x01, 0x02, 0x00, 0x0b,
+        ];
+        assert_eq!(code_section_bodies(&wasm), Some(vec![(22, 24)]));
+    }
+}
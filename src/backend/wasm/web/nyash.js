@@ -0,0 +1,273 @@
+// Nyash WASM loader - emitted by `nyash --compile-wasm --target web`
+//
+// Implements every `env.*` import of Nyash modules (see src/backend/wasm/runtime.rs and the
+// wasmtime host in src/backend/wasm/host.rs, which this file mirrors for the browser).
+//
+//   import { run } from "./nyash.js";
+//   const result = await run("./app.wasm", { output: line => console.log(line) });
+
+const KIND = { VOID: 0, INTEGER: 1, FLOAT: 2, BOOL: 3, STRING: 4, BOX: 5 };
+const STRING_BOX = 0x1001;
+const HOST_BOX = 0x1006;
+
+/**
+ * Boxes that live on the JavaScript side (`new MapBox()`, plugin boxes, ...), by type name.
+ * A class is constructed with the Nyash constructor arguments; methods are called by name.
+ * Pages can add their own: `hostBoxes.GreeterBox = class { greet(name) { ... } }`.
+ */
+export const hostBoxes = {
+  MapBox: class MapBox {
+    constructor() { this.map = new Map(); }
+    set(key, value) { this.map.set(String(key), value); return `Set key: ${key}`; }
+    get(key) { return this.map.has(String(key)) ? this.map.get(String(key)) : `Key not found: ${key}`; }
+    has(key) { return this.map.has(String(key)); }
+    delete(key) { return this.map.delete(String(key)) ? `Deleted key: ${key}` : `Key not found: ${key}`; }
+    size() { return this.map.size; }
+    clear() { this.map.clear(); return "Map cleared"; }
+    toString() { return `{${[...this.map].map(([k, v]) => `${k}=${v}`).join(", ")}}`; }
+  },
+  ConsoleBox: class ConsoleBox {
+    log(message) { console.log(String(message)); }
+    warn(message) { console.warn(String(message)); }
+    error(message) { console.error(String(message)); }
+    toString() { return "[ConsoleBox]"; }
+  },
+};
+
+/** Handles of host boxes, starting at 1 and reused once the module releases them */
+class HandleTable {
+  constructor() { this.slots = []; this.free = []; }
+  insert(value) {
+    const index = this.free.length > 0 ? this.free.pop() : this.slots.length;
+    this.slots[index] = value;
+    return index + 1;
+  }
+  get(handle) {
+    const value = this.slots[handle - 1];
+    if (value === undefined) throw new Error(`Invalid box handle: ${handle}`);
+    return value;
+  }
+  release(handle) {
+    if (this.slots[handle - 1] === undefined) return;
+    this.slots[handle - 1] = undefined;
+    this.free.push(handle - 1);
+  }
+}
+
+/** Turn i64 arguments into numbers when they fit */
+function fromI64(value) {
+  return BigInt(Number(value)) === value ? Number(value) : value;
+}
+
+function createEnv(state) {
+  const decoder = new TextDecoder();
+  const encoder = new TextEncoder();
+  const view = () => new DataView(state.exports.memory.buffer);
+  const bytes = (ptr, len) => new Uint8Array(state.exports.memory.buffer, ptr, len);
+  const readStr = (ptr, len) => decoder.decode(bytes(ptr, len));
+
+  // StringBox: [type_id][ref_count][field_count][data_ptr][length]
+  const readStringBox = ptr => {
+    const v = view();
+    if (ptr <= 0 || v.getInt32(ptr, true) !== STRING_BOX) return null;
+    return readStr(v.getInt32(ptr + 12, true), v.getInt32(ptr + 16, true));
+  };
+  // HostBox: [type_id][ref_count][field_count][handle]
+  const hostBox = ptr => {
+    const v = view();
+    if (ptr <= 0 || v.getInt32(ptr, true) !== HOST_BOX) return undefined;
+    return state.handles.get(v.getInt32(ptr + 12, true));
+  };
+  const describe = ptr => {
+    const s = readStringBox(ptr);
+    if (s !== null) return s;
+    const host = hostBox(ptr);
+    if (host !== undefined) return String(host);
+    return ptr > 0 ? `Box[type=0x${view().getInt32(ptr, true).toString(16)}]` : `Box[${ptr}]`;
+  };
+  const allocString = text => {
+    const data = encoder.encode(text);
+    const ptr = state.exports.malloc(data.length);
+    bytes(ptr, data.length).set(data);
+    return state.exports.string_new(ptr, data.length);
+  };
+  const toModuleBox = value => {
+    if (value === undefined || value === null) return 0;
+    if (typeof value === "string") return allocString(value);
+    if (typeof value !== "object") return allocString(String(value));
+    return state.exports.host_box_new(state.handles.insert(value));
+  };
+
+  // Argument frame: [count:i32][want:i32] then 16-byte slots [tag:i32][pad:i32][payload:8]
+  const readArgs = frame => {
+    const v = view();
+    const count = v.getInt32(frame, true);
+    const want = v.getInt32(frame + 4, true);
+    const args = [];
+    for (let i = 0; i < count; i++) {
+      const slot = frame + 8 + 16 * i;
+      const word = v.getInt32(slot + 8, true);
+      switch (v.getInt32(slot, true)) {
+        case KIND.VOID: args.push(undefined); break;
+        case KIND.INTEGER: args.push(fromI64(v.getBigInt64(slot + 8, true))); break;
+        case KIND.FLOAT: args.push(v.getFloat64(slot + 8, true)); break;
+        case KIND.BOOL: args.push(word !== 0); break;
+        default: {
+          const s = readStringBox(word);
+          const value = s !== null ? s : hostBox(word);
+          if (value === undefined) throw new Error(`${describe(word)} cannot be passed to a host box method`);
+          args.push(value);
+        }
+      }
+    }
+    return { args, want };
+  };
+  const writeResult = (frame, value, want) => {
+    let payload;
+    switch (want) {
+      case KIND.VOID: payload = 0n; break;
+      case KIND.INTEGER: payload = BigInt(typeof value === "boolean" ? Number(value) : Math.trunc(Number(value))); break;
+      case KIND.FLOAT: payload = null; break;
+      case KIND.BOOL: payload = BigInt(value ? 1 : 0); break;
+      case KIND.STRING: payload = BigInt(allocString(String(value))); break;
+      default: payload = BigInt(toModuleBox(value));
+    }
+    const v = view();
+    v.setInt32(frame, want, true);
+    if (payload === null) v.setFloat64(frame + 8, Number(value), true);
+    else v.setBigInt64(frame + 8, payload, true);
+    return want;
+  };
+
+  const env = {
+    print(ptr) { state.output(describe(ptr)); },
+    print_str(ptr, len) { state.output(readStr(ptr, len)); },
+    print_i64(value) { state.output(value.toString()); },
+    print_f64(value) { state.output(String(value)); },
+    print_bool(value) { state.output(value !== 0 ? "true" : "false"); },
+    console_log(ptr, len) { console.log(readStr(ptr, len)); },
+    canvas_fillRect(idPtr, idLen, x, y, w, h, colorPtr, colorLen) {
+      const canvas = state.document?.getElementById(readStr(idPtr, idLen));
+      if (!canvas) return;
+      const ctx = canvas.getContext("2d");
+      ctx.fillStyle = readStr(colorPtr, colorLen);
+      ctx.fillRect(x, y, w, h);
+    },
+    canvas_fillText(idPtr, idLen, textPtr, textLen, x, y, fontPtr, fontLen, colorPtr, colorLen) {
+      const canvas = state.document?.getElementById(readStr(idPtr, idLen));
+      if (!canvas) return;
+      const ctx = canvas.getContext("2d");
+      ctx.font = readStr(fontPtr, fontLen);
+      ctx.fillStyle = readStr(colorPtr, colorLen);
+      ctx.fillText(readStr(textPtr, textLen), x, y);
+    },
+    box_to_string(ptr) {
+      const host = hostBox(ptr);
+      return host === undefined ? ptr : allocString(String(host));
+    },
+    box_print(ptr) { state.output(describe(ptr)); },
+    box_equals(a, b) {
+      if (a === b) return 1;
+      const sa = readStringBox(a);
+      if (sa !== null) return sa === readStringBox(b) ? 1 : 0;
+      const ha = hostBox(a);
+      return ha !== undefined && ha === hostBox(b) ? 1 : 0;
+    },
+    box_clone(ptr) { return ptr; },
+    box_new(typePtr, argsPtr) {
+      const type = readStringBox(typePtr);
+      const BoxClass = state.hostBoxes[type];
+      if (BoxClass === undefined) throw new Error(`Unknown box type in the browser: ${type}`);
+      const { args } = readArgs(argsPtr);
+      return state.handles.insert(new BoxClass(...args));
+    },
+    box_call(handle, methodPtr, argsPtr) {
+      const target = state.handles.get(handle);
+      const method = readStringBox(methodPtr);
+      const { args, want } = readArgs(argsPtr);
+      if (typeof target[method] !== "function") {
+        throw new Error(`${target.constructor.name} has no method ${method}`);
+      }
+      return writeResult(argsPtr, target[method](...args), want);
+    },
+    box_release(handle) { state.handles.release(handle); },
+  };
+  return { env, describe };
+}
+
+/** Function-level map of a module's code section (`<name>.wasm.map`), for error messages */
+async function loadSourceMap(url) {
+  try {
+    const response = await fetch(`${url}.map`);
+    return response.ok ? await response.json() : null;
+  } catch {
+    return null;
+  }
+}
+
+function annotate(error, sourceMap) {
+  const match = sourceMap && /:0x([0-9a-f]+)/i.exec(String(error.stack));
+  if (!match) return error;
+  const offset = parseInt(match[1], 16);
+  const fn = sourceMap.functions.find(f => f.start <= offset && offset < f.end);
+  if (fn) error.message += ` (in Nyash function ${fn.name})`;
+  return error;
+}
+
+/**
+ * Instantiate a Nyash module.
+ * `source` is a URL, a Response or the module bytes. Options:
+ * - `output(line)`: receives print output (default: console.log)
+ * - `hostBoxes`: box classes available to `new` (default: the exported `hostBoxes`)
+ * - `document`: document holding canvases (default: globalThis.document)
+ */
+export async function load(source, options = {}) {
+  const state = {
+    exports: null,
+    handles: new HandleTable(),
+    output: options.output ?? (line => console.log(line)),
+    hostBoxes: options.hostBoxes ?? hostBoxes,
+    document: options.document ?? globalThis.document,
+  };
+  const { env, describe } = createEnv(state);
+  const imports = { env };
+  const url = typeof source === "string" ? source : null;
+  let instance;
+  if (url !== null) {
+    ({ instance } = await WebAssembly.instantiateStreaming(fetch(url), imports));
+  } else if (source instanceof Response) {
+    ({ instance } = await WebAssembly.instantiateStreaming(source, imports));
+  } else {
+    ({ instance } = await WebAssembly.instantiate(source, imports));
+  }
+  state.exports = instance.exports;
+  const sourceMap = url !== null && options.sourceMap !== false ? loadSourceMap(url) : Promise.resolve(null);
+
+  return {
+    instance,
+    exports: instance.exports,
+    /** Call `main` and decode its result with the module's `result_kind` global */
+    async main() {
+      let value;
+      try {
+        value = instance.exports.main();
+      } catch (error) {
+        throw annotate(error, await sourceMap);
+      }
+      switch (instance.exports.result_kind?.value) {
+        case KIND.VOID: return undefined;
+        case KIND.INTEGER: return fromI64(value);
+        case KIND.BOOL: return value !== 0;
+        case KIND.STRING:
+        case KIND.BOX: return describe(value);
+        default: return value;
+      }
+    },
+  };
+}
+
+/** Load a module and run its `main` */
+export async function run(source, options = {}) {
+  const module = await load(source, options);
+  return module.main();
+}
@@ -0,0 +1,180 @@
+/*!
+ * BID Code Generation - `nyash bid gen`
+ *
+ * Turns a `BidDefinition` into a Rust plugin crate skeleton implementing
+ * `nyash_plugin_invoke` plus the matching nyash.toml `[libraries]` section.
+ */
+
+pub mod plugin_crate;
+pub mod nyash_toml;
+
+use super::schema::{BidDefinition, BidSchemaError};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Code generation options
+#[derive(Debug, Clone)]
+pub struct CodeGenOptions {
+    /// Directory of the generated plugin crate
+    pub output_dir: PathBuf,
+    /// Overwrite existing files
+    pub force: bool,
+    /// Print what would be generated without writing
+    pub dry_run: bool,
+    /// Override the library path written to nyash.toml
+    pub lib_path: Option<String>,
+}
+
+impl CodeGenOptions {
+    /// Create new options
+    pub fn new(output_dir: PathBuf) -> Self {
+        Self { output_dir, force: false, dry_run: false, lib_path: None }
+    }
+
+    /// Set force overwrite
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// Set dry run mode
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+}
+
+/// Generated file
+#[derive(Debug, Clone)]
+pub struct GeneratedFile {
+    pub path: PathBuf,
+    pub content: String,
+}
+
+impl GeneratedFile {
+    pub fn new(path: PathBuf, content: String) -> Self {
+        Self { path, content }
+    }
+}
+
+/// Naming derived from the BID name (e.g. "counter")
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginNames {
+    /// Cargo package name: nyash-counter-plugin
+    pub crate_name: String,
+    /// Shared library file name: libnyash_counter_plugin.so
+    pub lib_file: String,
+}
+
+impl PluginNames {
+    pub fn from_bid(bid: &BidDefinition) -> Self {
+        let base = bid.name().replace(['.', '_', ' '], "-").to_lowercase();
+        let crate_name = format!("nyash-{}-plugin", base);
+        let lib_file = format!(
+            "{}{}{}",
+            std::env::consts::DLL_PREFIX,
+            crate_name.replace('-', "_"),
+            std::env::consts::DLL_SUFFIX
+        );
+        Self { crate_name, lib_file }
+    }
+}
+
+/// Generate the plugin crate skeleton and its nyash.toml section
+pub fn generate_plugin(bid: &BidDefinition, source_name: &str, options: &CodeGenOptions) -> Result<Vec<GeneratedFile>, BidSchemaError> {
+    let names = PluginNames::from_bid(bid);
+    let lib_path = options.lib_path.clone().unwrap_or_else(|| default_lib_path(&options.output_dir, &names));
+    let dir = &options.output_dir;
+
+    Ok(vec![
+        GeneratedFile::new(dir.join("Cargo.toml"), plugin_crate::generate_cargo_toml(&names)),
+        GeneratedFile::new(dir.join("src").join("lib.rs"), plugin_crate::generate_lib_rs(bid, source_name)?),
+        GeneratedFile::new(dir.join("nyash.toml"), nyash_toml::generate_libraries_section(bid, &names, &lib_path)?),
+    ])
+}
+
+/// `./<output_dir>/target/release/<lib_file>` (relative dirs keep a leading `./`)
+fn default_lib_path(output_dir: &Path, names: &PluginNames) -> String {
+    let dir = output_dir.to_string_lossy().replace('\\', "/");
+    let dir = dir.trim_end_matches('/');
+    let prefix = if output_dir.is_absolute() || dir.starts_with("./") || dir.starts_with("../") { "" } else { "./" };
+    format!("{}{}/target/release/{}", prefix, dir, names.lib_file)
+}
+
+/// Write generated files to disk
+pub fn write_files(files: &[GeneratedFile], options: &CodeGenOptions) -> Result<(), BidSchemaError> {
+    for file in files {
+        // Check if file exists and force is not set
+        if file.path.exists() && !options.force {
+            return Err(BidSchemaError::IoError(format!(
+                "File already exists: {} (use --force to overwrite)",
+                file.path.display()
+            )));
+        }
+
+        // Create parent directories
+        if let Some(parent) = file.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| BidSchemaError::IoError(format!(
+                    "Failed to create directory {}: {}",
+                    parent.display(),
+                    e
+                )))?;
+        }
+
+        fs::write(&file.path, &file.content)
+            .map_err(|e| BidSchemaError::IoError(format!(
+                "Failed to write file {}: {}",
+                file.path.display(),
+                e
+            )))?;
+    }
+
+    Ok(())
+}
+
+/// PascalCase/dotted name -> snake_case ("HttpServerBox" -> "http_server_box")
+pub(crate) fn snake_case(s: &str) -> String {
+    let mut out = String::new();
+    let mut prev_lower = false;
+    for c in s.chars() {
+        if c.is_ascii_uppercase() {
+            if prev_lower {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+            prev_lower = false;
+        } else if c.is_ascii_alphanumeric() {
+            out.push(c);
+            prev_lower = true;
+        } else {
+            if !out.ends_with('_') && !out.is_empty() {
+                out.push('_');
+            }
+            prev_lower = false;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_and_default_path() {
+        let bid = BidDefinition::from_yaml_str("version: 1\nmetadata: { name: counter }\ninterfaces: []\n").unwrap();
+        let names = PluginNames::from_bid(&bid);
+        assert_eq!(names.crate_name, "nyash-counter-plugin");
+        assert!(names.lib_file.contains("nyash_counter_plugin"));
+        let p = default_lib_path(Path::new("plugins/nyash-counter-plugin"), &names);
+        assert!(p.starts_with("./plugins/nyash-counter-plugin/target/release/"));
+    }
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(snake_case("HttpServerBox"), "http_server_box");
+        assert_eq!(snake_case("readBody"), "read_body");
+        assert_eq!(snake_case("env.console"), "env_console");
+    }
+}
@@ -0,0 +1,149 @@
+/*!
+ * nyash.toml `[libraries]` section generator
+ *
+ * Output follows the hand-written layout of the repository nyash.toml so the
+ * fragment can be pasted under the existing `[libraries]` table.
+ */
+
+use super::PluginNames;
+use crate::bid::schema::{BidDefinition, BidSchemaError, BidValueKind};
+
+/// Generate the `[libraries."<lib>"]` section (without the `[libraries]` header)
+pub fn generate_libraries_section(bid: &BidDefinition, names: &PluginNames, lib_path: &str) -> Result<String, BidSchemaError> {
+    let lib = &names.lib_file;
+    let mut out = String::new();
+
+    out.push_str(&format!("# {} - generated by `nyash bid gen`\n", bid.name()));
+    let boxes: Vec<String> = bid.interfaces.iter().map(|i| format!("\"{}\"", i.box_name())).collect();
+    out.push_str(&format!("[libraries.\"{}\"]\n", lib));
+    out.push_str(&format!("boxes = [{}]\n", boxes.join(", ")));
+    out.push_str(&format!("path = \"{}\"\n", lib_path));
+    push_box_tables(&mut out, bid, &format!("libraries.\"{}\".", lib))?;
+    Ok(out)
+}
+
+/// Generate the `nyash_plugin_manifest` text: the same box tables without the library prefix
+pub fn generate_manifest(bid: &BidDefinition) -> Result<String, BidSchemaError> {
+    let mut out = format!("abi_version = {}\n", crate::bid::manifest::CURRENT_ABI_VERSION);
+    push_box_tables(&mut out, bid, "")?;
+    Ok(out)
+}
+
+fn push_box_tables(out: &mut String, bid: &BidDefinition, prefix: &str) -> Result<(), BidSchemaError> {
+    for iface in &bid.interfaces {
+        let box_name = iface.box_name();
+        let type_id = iface.type_id.ok_or_else(|| BidSchemaError::MissingField {
+            field: "type_id".to_string(),
+            context: format!("interface '{}'", iface.name),
+        })?;
+
+        out.push('\n');
+        out.push_str(&format!("[{}{}]\n", prefix, box_name));
+        out.push_str(&format!("type_id = {}\n", type_id));
+        if iface.singleton {
+            out.push_str("singleton = true\n");
+        }
+
+        out.push('\n');
+        out.push_str(&format!("[{}{}.methods]\n", prefix, box_name));
+        let methods = iface.method_ids();
+        let mut entries = vec![("birth".to_string(), format!("{{ method_id = {} }}", crate::bid::schema::BID_METHOD_BIRTH))];
+        for (m, id) in methods {
+            let mut fields = vec![format!("method_id = {}", id)];
+            if !m.params.is_empty() {
+                let mut args = Vec::new();
+                for p in &m.params {
+                    // validate() already rejected unknown kinds
+                    let kind = p.kind().unwrap_or(BidValueKind::String);
+                    if !kind.is_loader_arg() {
+                        return Err(BidSchemaError::UnsupportedType {
+                            type_name: p.get_type(),
+                            context: format!("{}.{} (nyash.toml args support string, i32 and box)", box_name, m.name),
+                        });
+                    }
+                    args.push(arg_decl(kind));
+                }
+                fields.push(format!("args = [{}]", args.join(", ")));
+            }
+            if m.returns_result {
+                fields.push("returns_result = true".to_string());
+            }
+            entries.push((m.name.clone(), format!("{{ {} }}", fields.join(", "))));
+        }
+        entries.push(("fini".to_string(), format!("{{ method_id = {} }}", crate::bid::schema::BID_METHOD_FINI)));
+
+        for (name, value) in entries {
+            out.push_str(&format!("{} = {}\n", toml_key(&name), value));
+        }
+    }
+    Ok(())
+}
+
+/// Typed `ArgDecl` inline table for a parameter kind
+fn arg_decl(kind: BidValueKind) -> String {
+    match kind {
+        BidValueKind::Box => "{ kind = \"box\", category = \"plugin\" }".to_string(),
+        BidValueKind::I32 => "{ kind = \"i32\" }".to_string(),
+        _ => "{ kind = \"string\" }".to_string(),
+    }
+}
+
+fn toml_key(name: &str) -> String {
+    if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "\\\""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::nyash_toml_v2::{ArgDecl, NyashConfigV2};
+
+    #[test]
+    fn test_generated_section_parses_with_v2_config() {
+        let bid = BidDefinition::from_yaml_str(r#"
+version: 1
+metadata: { name: file }
+interfaces:
+  - name: nyash.file
+    box: FileBox
+    type_id: 6
+    methods:
+      - name: open
+        params: [ { string: path }, { string: mode } ]
+      - { name: read, returns: string }
+      - name: copyFrom
+        id: 7
+        params: [ { box: src } ]
+        returns_result: true
+"#).unwrap();
+        let names = PluginNames::from_bid(&bid);
+        let section = generate_libraries_section(&bid, &names, "./libfile.so").unwrap();
+        let full = format!("[libraries]\n{}", section);
+        let conf = NyashConfigV2::from_str(&full).unwrap();
+        let (lib, def) = conf.find_library_for_box("FileBox").unwrap();
+        assert_eq!(def.path, "./libfile.so");
+        let raw: toml::Value = toml::from_str(&full).unwrap();
+        let box_conf = conf.get_box_config(lib, "FileBox", &raw).unwrap();
+        assert_eq!(box_conf.type_id, 6);
+        assert_eq!(box_conf.methods["open"].method_id, 1);
+        assert_eq!(box_conf.methods["read"].method_id, 2);
+        let copy = &box_conf.methods["copyFrom"];
+        assert_eq!(copy.method_id, 7);
+        assert!(copy.returns_result);
+        match &copy.args.as_ref().unwrap()[0] {
+            ArgDecl::Typed { kind, category } => {
+                assert_eq!(kind, "box");
+                assert_eq!(category.as_deref(), Some("plugin"));
+            }
+            other => panic!("unexpected arg decl {:?}", other),
+        }
+        assert_eq!(box_conf.methods["fini"].method_id, u32::MAX);
+
+        // The embedded manifest describes exactly the generated tables
+        let manifest = crate::bid::manifest::PluginManifest::from_toml_str(&generate_manifest(&bid).unwrap()).unwrap();
+        assert!(manifest.validate(&[("FileBox", Some(&box_conf))]).is_empty());
+    }
+}
@@ -0,0 +1,457 @@
+/*!
+ * Rust plugin crate skeleton generator
+ *
+ * Emits a cdylib crate laid out like `plugins/nyash-counter-plugin`:
+ * error-code and method-id constants, per-box instance tables (a single
+ * shared instance for `singleton` interfaces), the `nyash_plugin_invoke`
+ * dispatcher with TLV decoding per method, and one stub method per BID
+ * entry for the plugin author to fill in.
+ */
+
+use super::{snake_case, PluginNames};
+use crate::bid::schema::{BidDefinition, BidInterface, BidSchemaError, BidValueKind};
+use std::collections::BTreeSet;
+
+/// Cargo.toml for the generated crate
+pub fn generate_cargo_toml(names: &PluginNames) -> String {
+    format!(r#"[package]
+name = "{}"
+version = "0.1.0"
+edition = "2021"
+
+[lib]
+crate-type = ["cdylib"]
+
+[dependencies]
+once_cell = "1.20"
+
+[profile.release]
+lto = true
+strip = true
+opt-level = "z"
+"#, names.crate_name)
+}
+
+/// src/lib.rs for the generated crate
+pub fn generate_lib_rs(bid: &BidDefinition, source_name: &str) -> Result<String, BidSchemaError> {
+    let mut out = String::new();
+    let title = bid.metadata.as_ref()
+        .and_then(|m| m.description.clone())
+        .unwrap_or_else(|| format!("Nyash {} Plugin", bid.name()));
+
+    out.push_str(&format!("//! {} - BID-FFI v1 Implementation\n", title));
+    out.push_str(&format!("//!\n//! Generated by `nyash bid gen` from {}.\n", source_name));
+    out.push_str("//! Fill in the `impl ...Instance` method bodies; the dispatch and TLV\n");
+    out.push_str("//! plumbing above them mirrors the BID method table.\n\n");
+    out.push_str("use std::collections::HashMap;\n");
+    out.push_str("use std::sync::{Mutex, atomic::{AtomicU32, Ordering}};\n\n");
+    out.push_str("use once_cell::sync::Lazy;\n\n");
+
+    out.push_str("// ===== Error Codes (BID-1 alignment) =====\n");
+    out.push_str("const NYB_SUCCESS: i32 = 0;\n");
+    out.push_str("const NYB_E_SHORT_BUFFER: i32 = -1;\n");
+    out.push_str("const NYB_E_INVALID_TYPE: i32 = -2;\n");
+    out.push_str("const NYB_E_INVALID_METHOD: i32 = -3;\n");
+    out.push_str("const NYB_E_INVALID_ARGS: i32 = -4;\n");
+    out.push_str("const NYB_E_PLUGIN_ERROR: i32 = -5;\n");
+    if bid.interfaces.iter().any(|i| i.user_methods().next().is_some()) {
+        out.push_str("const NYB_E_INVALID_HANDLE: i32 = -8;\n");
+    }
+    out.push('\n');
+
+    out.push_str("// ===== Type IDs =====\n");
+    for iface in &bid.interfaces {
+        let type_id = iface.type_id.ok_or_else(|| BidSchemaError::MissingField {
+            field: "type_id".to_string(),
+            context: format!("interface '{}'", iface.name),
+        })?;
+        out.push_str(&format!("const {}: u32 = {};\n", type_const(iface), type_id));
+    }
+    out.push('\n');
+
+    out.push_str("// ===== Method IDs =====\n");
+    out.push_str("const METHOD_BIRTH: u32 = 0;  // constructor\n");
+    out.push_str("const METHOD_FINI: u32 = u32::MAX;  // destructor\n");
+    for iface in &bid.interfaces {
+        for (m, id) in iface.method_ids() {
+            out.push_str(&format!("const {}: u32 = {};\n", method_const(iface, &m.name), id));
+        }
+    }
+    out.push('\n');
+
+    out.push_str("static INSTANCE_COUNTER: AtomicU32 = AtomicU32::new(1);\n\n");
+
+    // Instance state per box
+    for iface in &bid.interfaces {
+        let pascal = iface.box_name();
+        out.push_str(&format!("// ===== {} instance state =====\n", pascal));
+        out.push_str("#[derive(Default)]\n");
+        out.push_str(&format!("struct {}Instance {{\n    // TODO: add {} state\n}}\n\n", pascal, pascal));
+        out.push_str(&format!(
+            "static {}: Lazy<Mutex<HashMap<u32, {}Instance>>> = Lazy::new(|| Mutex::new(HashMap::new()));\n\n",
+            instances_static(iface), pascal
+        ));
+    }
+
+    // Entry points
+    out.push_str("#[no_mangle]\npub extern \"C\" fn nyash_plugin_abi() -> u32 { 1 }\n\n");
+    out.push_str("#[no_mangle]\npub extern \"C\" fn nyash_plugin_init() -> i32 { NYB_SUCCESS }\n\n");
+
+    // Self-description checked by the loader against nyash.toml
+    let manifest = super::nyash_toml::generate_manifest(bid)?;
+    out.push_str(&format!("const MANIFEST: &str = r##\"{}\"##;\n\n", manifest));
+    out.push_str("/// # Safety\n/// `result`/`result_len` must follow the BID-1 two-phase buffer contract.\n");
+    out.push_str("#[no_mangle]\npub unsafe extern \"C\" fn nyash_plugin_manifest(result: *mut u8, result_len: *mut usize) -> i32 {\n");
+    out.push_str("    if result_len.is_null() { return NYB_E_INVALID_ARGS; }\n");
+    out.push_str("    let bytes = MANIFEST.as_bytes();\n");
+    out.push_str("    if result.is_null() || *result_len < bytes.len() {\n");
+    out.push_str("        *result_len = bytes.len();\n");
+    out.push_str("        return NYB_E_SHORT_BUFFER;\n");
+    out.push_str("    }\n");
+    out.push_str("    std::ptr::copy_nonoverlapping(bytes.as_ptr(), result, bytes.len());\n");
+    out.push_str("    *result_len = bytes.len();\n");
+    out.push_str("    NYB_SUCCESS\n}\n\n");
+    out.push_str("/// # Safety\n/// `args` must point to `args_len` readable bytes (or be null), and `result`/`result_len`\n/// must follow the BID-1 two-phase buffer contract.\n");
+    out.push_str("#[no_mangle]\npub unsafe extern \"C\" fn nyash_plugin_invoke(\n");
+    out.push_str("    type_id: u32,\n    method_id: u32,\n    instance_id: u32,\n    args: *const u8,\n    args_len: usize,\n    result: *mut u8,\n    result_len: *mut usize,\n) -> i32 {\n");
+    out.push_str("    let args: &[u8] = if args.is_null() || args_len == 0 { &[] } else { std::slice::from_raw_parts(args, args_len) };\n");
+    out.push_str("    match type_id {\n");
+    for iface in &bid.interfaces {
+        out.push_str(&format!(
+            "        {} => invoke_{}(method_id, instance_id, args, result, result_len),\n",
+            type_const(iface), snake_case(&iface.box_name())
+        ));
+    }
+    out.push_str("        _ => NYB_E_INVALID_TYPE,\n    }\n}\n\n");
+
+    // Per-box dispatchers
+    let mut param_kinds = BTreeSet::new();
+    let mut return_kinds = BTreeSet::new();
+    for iface in &bid.interfaces {
+        generate_dispatcher(&mut out, iface, &mut param_kinds, &mut return_kinds);
+    }
+
+    // Method stubs
+    out.push_str("// ===== Method implementations =====\n");
+    for iface in &bid.interfaces {
+        generate_stubs(&mut out, iface);
+    }
+
+    generate_tlv_helpers(&mut out, &param_kinds, &return_kinds);
+    Ok(out)
+}
+
+fn generate_dispatcher(out: &mut String, iface: &BidInterface, param_kinds: &mut BTreeSet<u8>, return_kinds: &mut BTreeSet<u8>) {
+    let pascal = iface.box_name();
+    let snake = snake_case(&pascal);
+    let instances = instances_static(iface);
+
+    // Boxes with only birth/fini never look at the argument buffer
+    let args_name = if iface.user_methods().next().is_some() { "args" } else { "_args" };
+    out.push_str(&format!(
+        "fn invoke_{}(method_id: u32, instance_id: u32, {}: &[u8], result: *mut u8, result_len: *mut usize) -> i32 {{\n",
+        snake, args_name
+    ));
+    out.push_str("    match method_id {\n");
+    out.push_str("        METHOD_BIRTH => {\n");
+    if iface.singleton {
+        out.push_str("            // Singleton: every birth hands out the one shared instance\n");
+    } else {
+        out.push_str("            // Return new instance handle (u32 id)\n");
+    }
+    out.push_str("            if result_len.is_null() { return NYB_E_INVALID_ARGS; }\n");
+    out.push_str("            if preflight(result, result_len, 4) { return NYB_E_SHORT_BUFFER; }\n");
+    out.push_str(&format!(
+        "            let mut map = match {}.lock() {{ Ok(m) => m, Err(_) => return NYB_E_PLUGIN_ERROR }};\n",
+        instances
+    ));
+    if iface.singleton {
+        out.push_str("            let id = match map.keys().next() {\n");
+        out.push_str("                Some(&id) => id,\n");
+        out.push_str("                None => {\n");
+        out.push_str("                    let id = INSTANCE_COUNTER.fetch_add(1, Ordering::Relaxed);\n");
+        out.push_str(&format!("                    map.insert(id, {}Instance::default());\n", pascal));
+        out.push_str("                    id\n");
+        out.push_str("                }\n");
+        out.push_str("            };\n");
+    } else {
+        out.push_str("            let id = INSTANCE_COUNTER.fetch_add(1, Ordering::Relaxed);\n");
+        out.push_str(&format!("            map.insert(id, {}Instance::default());\n", pascal));
+    }
+    out.push_str("            let bytes = id.to_le_bytes();\n");
+    out.push_str("            unsafe {\n");
+    out.push_str("                std::ptr::copy_nonoverlapping(bytes.as_ptr(), result, 4);\n");
+    out.push_str("                *result_len = 4;\n");
+    out.push_str("            }\n");
+    out.push_str("            NYB_SUCCESS\n");
+    out.push_str("        }\n");
+    out.push_str("        METHOD_FINI => {\n");
+    out.push_str(&format!("            if let Ok(mut map) = {}.lock() {{\n", instances));
+    out.push_str("                map.remove(&instance_id);\n");
+    out.push_str("                NYB_SUCCESS\n");
+    out.push_str("            } else { NYB_E_PLUGIN_ERROR }\n");
+    out.push_str("        }\n");
+
+    for (m, _id) in iface.method_ids() {
+        let ret = m.returns.kind().unwrap_or(BidValueKind::Void);
+        out.push_str(&format!("        {} => {{\n", method_const(iface, &m.name)));
+
+        let mut call_args = Vec::new();
+        if m.params.is_empty() {
+            out.push_str("            let _ = args;\n");
+        } else {
+            out.push_str(&format!(
+                "            let tlv = match TlvArgs::parse(args, {}) {{ Some(t) => t, None => return NYB_E_INVALID_ARGS }};\n",
+                m.params.len()
+            ));
+            for (i, p) in m.params.iter().enumerate() {
+                let kind = p.kind().unwrap_or(BidValueKind::String);
+                param_kinds.insert(kind.tlv_tag());
+                let name = param_ident(p.get_name(), i);
+                out.push_str(&format!(
+                    "            let {} = match tlv.{}({}) {{ Some(v) => v, None => return NYB_E_INVALID_ARGS }};\n",
+                    name, reader_fn(kind), i
+                ));
+                call_args.push(name);
+            }
+        }
+
+        if let Some(size) = fixed_result_size(ret) {
+            out.push_str(&format!("            if preflight(result, result_len, {}) {{ return NYB_E_SHORT_BUFFER; }}\n", size));
+        }
+        out.push_str(&format!(
+            "            let mut map = match {}.lock() {{ Ok(m) => m, Err(_) => return NYB_E_PLUGIN_ERROR }};\n",
+            instances
+        ));
+        out.push_str("            let inst = match map.get_mut(&instance_id) { Some(i) => i, None => return NYB_E_INVALID_HANDLE };\n");
+        out.push_str(&format!("            match inst.{}({}) {{\n", snake_case(&m.name), call_args.join(", ")));
+        if ret == BidValueKind::Void {
+            out.push_str("                Ok(()) => {\n");
+            out.push_str("                    if !result_len.is_null() { unsafe { *result_len = 0; } }\n");
+            out.push_str("                    NYB_SUCCESS\n");
+            out.push_str("                }\n");
+        } else {
+            return_kinds.insert(ret.tlv_tag());
+            out.push_str(&format!("                Ok(v) => {}(v, result, result_len),\n", writer_fn(ret)));
+        }
+        out.push_str("                Err(code) => code,\n");
+        out.push_str("            }\n");
+        out.push_str("        }\n");
+    }
+
+    out.push_str("        _ => NYB_E_INVALID_METHOD,\n    }\n}\n\n");
+}
+
+fn generate_stubs(out: &mut String, iface: &BidInterface) {
+    let pascal = iface.box_name();
+    out.push_str(&format!("impl {}Instance {{\n", pascal));
+    let methods = iface.method_ids();
+    for (idx, (m, _id)) in methods.iter().enumerate() {
+        let ret = m.returns.kind().unwrap_or(BidValueKind::Void);
+        let params: Vec<String> = m.params.iter().enumerate()
+            .map(|(i, p)| format!("{}: {}", param_ident(p.get_name(), i), rust_type(p.kind().unwrap_or(BidValueKind::String))))
+            .collect();
+        let mut sig = vec!["&mut self".to_string()];
+        sig.extend(params);
+        if idx > 0 {
+            out.push('\n');
+        }
+        out.push_str(&format!("    /// {}.{}\n", pascal, m.name));
+        out.push_str(&format!(
+            "    fn {}({}) -> Result<{}, i32> {{\n",
+            snake_case(&m.name), sig.join(", "), rust_type(ret)
+        ));
+        for (i, p) in m.params.iter().enumerate() {
+            out.push_str(&format!("        let _ = {};\n", param_ident(p.get_name(), i)));
+        }
+        out.push_str(&format!("        // TODO: implement {}.{}\n", pascal, m.name));
+        out.push_str(&format!("        Ok({})\n", default_value(ret)));
+        out.push_str("    }\n");
+    }
+    out.push_str("}\n\n");
+}
+
+fn generate_tlv_helpers(out: &mut String, param_kinds: &BTreeSet<u8>, return_kinds: &BTreeSet<u8>) {
+    out.push_str("// ===== TLV helpers =====\n");
+
+    if !param_kinds.is_empty() {
+        out.push_str("/// Decoded BID-1 TLV arguments (u16 ver, u16 argc, then tag/rsv/size/payload entries)\n");
+        out.push_str("struct TlvArgs<'a> {\n    entries: Vec<(u8, &'a [u8])>,\n}\n\n");
+        out.push_str("impl<'a> TlvArgs<'a> {\n");
+        out.push_str("    fn parse(buf: &'a [u8], expected: usize) -> Option<Self> {\n");
+        out.push_str("        if buf.len() < 4 { return None; }\n");
+        out.push_str("        let argc = u16::from_le_bytes([buf[2], buf[3]]) as usize;\n");
+        out.push_str("        if argc != expected { return None; }\n");
+        out.push_str("        let mut entries = Vec::with_capacity(argc);\n");
+        out.push_str("        let mut off = 4;\n");
+        out.push_str("        for _ in 0..argc {\n");
+        out.push_str("            if buf.len() < off + 4 { return None; }\n");
+        out.push_str("            let tag = buf[off];\n");
+        out.push_str("            let size = u16::from_le_bytes([buf[off + 2], buf[off + 3]]) as usize;\n");
+        out.push_str("            off += 4;\n");
+        out.push_str("            if buf.len() < off + size { return None; }\n");
+        out.push_str("            entries.push((tag, &buf[off..off + size]));\n");
+        out.push_str("            off += size;\n");
+        out.push_str("        }\n");
+        out.push_str("        Some(Self { entries })\n");
+        out.push_str("    }\n\n");
+        out.push_str("    fn payload(&self, idx: usize, tag: u8) -> Option<&'a [u8]> {\n");
+        out.push_str("        let (t, p) = *self.entries.get(idx)?;\n");
+        out.push_str("        if t == tag { Some(p) } else { None }\n");
+        out.push_str("    }\n");
+        for tag in param_kinds {
+            out.push('\n');
+            out.push_str(reader_body(*tag));
+        }
+        out.push_str("}\n\n");
+    }
+
+    if !return_kinds.is_empty() {
+        out.push_str("fn write_tlv_result(payloads: &[(u8, &[u8])], result: *mut u8, result_len: *mut usize) -> i32 {\n");
+        out.push_str("    if result_len.is_null() { return NYB_E_INVALID_ARGS; }\n");
+        out.push_str("    let mut buf: Vec<u8> = Vec::with_capacity(4 + payloads.iter().map(|(_, p)| 4 + p.len()).sum::<usize>());\n");
+        out.push_str("    buf.extend_from_slice(&1u16.to_le_bytes()); // version\n");
+        out.push_str("    buf.extend_from_slice(&(payloads.len() as u16).to_le_bytes()); // argc\n");
+        out.push_str("    for (tag, payload) in payloads {\n");
+        out.push_str("        buf.push(*tag);\n");
+        out.push_str("        buf.push(0);\n");
+        out.push_str("        buf.extend_from_slice(&(payload.len() as u16).to_le_bytes());\n");
+        out.push_str("        buf.extend_from_slice(payload);\n");
+        out.push_str("    }\n");
+        out.push_str("    unsafe {\n");
+        out.push_str("        let needed = buf.len();\n");
+        out.push_str("        if result.is_null() || *result_len < needed {\n");
+        out.push_str("            *result_len = needed;\n");
+        out.push_str("            return NYB_E_SHORT_BUFFER;\n");
+        out.push_str("        }\n");
+        out.push_str("        std::ptr::copy_nonoverlapping(buf.as_ptr(), result, needed);\n");
+        out.push_str("        *result_len = needed;\n");
+        out.push_str("    }\n");
+        out.push_str("    NYB_SUCCESS\n");
+        out.push_str("}\n\n");
+        for tag in return_kinds {
+            out.push_str(writer_body(*tag));
+            out.push('\n');
+        }
+    }
+
+    out.push_str("fn preflight(result: *mut u8, result_len: *mut usize, needed: usize) -> bool {\n");
+    out.push_str("    unsafe {\n");
+    out.push_str("        if result_len.is_null() { return false; }\n");
+    out.push_str("        if result.is_null() || *result_len < needed {\n");
+    out.push_str("            *result_len = needed;\n");
+    out.push_str("            return true;\n");
+    out.push_str("        }\n");
+    out.push_str("    }\n");
+    out.push_str("    false\n");
+    out.push_str("}\n");
+}
+
+fn type_const(iface: &BidInterface) -> String {
+    format!("TYPE_ID_{}", snake_case(&iface.box_name()).to_uppercase())
+}
+
+fn method_const(iface: &BidInterface, method: &str) -> String {
+    format!("{}_{}", snake_case(&iface.box_name()).to_uppercase(), snake_case(method).to_uppercase())
+}
+
+fn instances_static(iface: &BidInterface) -> String {
+    format!("{}_INSTANCES", snake_case(&iface.box_name()).to_uppercase())
+}
+
+fn param_ident(name: Option<String>, idx: usize) -> String {
+    match name {
+        Some(n) if !n.is_empty() => {
+            let s = snake_case(&n);
+            if matches!(s.as_str(), "type" | "self" | "box" | "fn" | "mod" | "ref" | "match" | "loop" | "move" | "in" | "impl" | "use") {
+                format!("{}_", s)
+            } else {
+                s
+            }
+        }
+        _ => format!("arg{}", idx),
+    }
+}
+
+fn rust_type(kind: BidValueKind) -> &'static str {
+    match kind {
+        BidValueKind::Void => "()",
+        BidValueKind::Bool => "bool",
+        BidValueKind::I32 => "i32",
+        BidValueKind::I64 => "i64",
+        BidValueKind::F64 => "f64",
+        BidValueKind::String => "String",
+        BidValueKind::Bytes => "Vec<u8>",
+        BidValueKind::Box => "(u32, u32)",
+    }
+}
+
+fn default_value(kind: BidValueKind) -> &'static str {
+    match kind {
+        BidValueKind::Void => "()",
+        BidValueKind::Bool => "false",
+        BidValueKind::I32 | BidValueKind::I64 => "0",
+        BidValueKind::F64 => "0.0",
+        BidValueKind::String => "String::new()",
+        BidValueKind::Bytes => "Vec::new()",
+        BidValueKind::Box => "(0, 0)",
+    }
+}
+
+/// Encoded result size for fixed-width returns (header 4 + entry 4 + payload)
+fn fixed_result_size(kind: BidValueKind) -> Option<usize> {
+    match kind {
+        BidValueKind::Bool => Some(9),
+        BidValueKind::I32 => Some(12),
+        BidValueKind::I64 | BidValueKind::F64 | BidValueKind::Box => Some(16),
+        _ => None,
+    }
+}
+
+fn reader_fn(kind: BidValueKind) -> &'static str {
+    match kind {
+        BidValueKind::Bool => "bool",
+        BidValueKind::I32 => "i32",
+        BidValueKind::I64 => "i64",
+        BidValueKind::F64 => "f64",
+        BidValueKind::Bytes => "bytes",
+        BidValueKind::Box => "handle",
+        _ => "string",
+    }
+}
+
+fn reader_body(tag: u8) -> &'static str {
+    match tag {
+        1 => "    fn bool(&self, idx: usize) -> Option<bool> {\n        let p = self.payload(idx, 1)?;\n        Some(p.first().copied()? != 0)\n    }\n",
+        2 => "    fn i32(&self, idx: usize) -> Option<i32> {\n        Some(i32::from_le_bytes(self.payload(idx, 2)?.try_into().ok()?))\n    }\n",
+        3 => "    fn i64(&self, idx: usize) -> Option<i64> {\n        Some(i64::from_le_bytes(self.payload(idx, 3)?.try_into().ok()?))\n    }\n",
+        5 => "    fn f64(&self, idx: usize) -> Option<f64> {\n        Some(f64::from_le_bytes(self.payload(idx, 5)?.try_into().ok()?))\n    }\n",
+        7 => "    fn bytes(&self, idx: usize) -> Option<Vec<u8>> {\n        Some(self.payload(idx, 7)?.to_vec())\n    }\n",
+        8 => "    fn handle(&self, idx: usize) -> Option<(u32, u32)> {\n        let p = self.payload(idx, 8)?;\n        if p.len() != 8 { return None; }\n        Some((u32::from_le_bytes([p[0], p[1], p[2], p[3]]), u32::from_le_bytes([p[4], p[5], p[6], p[7]])))\n    }\n",
+        _ => "    fn string(&self, idx: usize) -> Option<String> {\n        std::str::from_utf8(self.payload(idx, 6)?).ok().map(|s| s.to_string())\n    }\n",
+    }
+}
+
+fn writer_fn(kind: BidValueKind) -> &'static str {
+    match kind {
+        BidValueKind::Bool => "write_tlv_bool",
+        BidValueKind::I32 => "write_tlv_i32",
+        BidValueKind::I64 => "write_tlv_i64",
+        BidValueKind::F64 => "write_tlv_f64",
+        BidValueKind::Bytes => "write_tlv_bytes",
+        BidValueKind::Box => "write_tlv_handle",
+        _ => "write_tlv_string",
+    }
+}
+
+fn writer_body(tag: u8) -> &'static str {
+    match tag {
+        1 => "fn write_tlv_bool(v: bool, result: *mut u8, result_len: *mut usize) -> i32 {\n    write_tlv_result(&[(1u8, &[v as u8])], result, result_len)\n}\n",
+        2 => "fn write_tlv_i32(v: i32, result: *mut u8, result_len: *mut usize) -> i32 {\n    write_tlv_result(&[(2u8, &v.to_le_bytes())], result, result_len)\n}\n",
+        3 => "fn write_tlv_i64(v: i64, result: *mut u8, result_len: *mut usize) -> i32 {\n    write_tlv_result(&[(3u8, &v.to_le_bytes())], result, result_len)\n}\n",
+        5 => "fn write_tlv_f64(v: f64, result: *mut u8, result_len: *mut usize) -> i32 {\n    write_tlv_result(&[(5u8, &v.to_le_bytes())], result, result_len)\n}\n",
+        7 => "fn write_tlv_bytes(v: Vec<u8>, result: *mut u8, result_len: *mut usize) -> i32 {\n    write_tlv_result(&[(7u8, &v)], result, result_len)\n}\n",
+        8 => "fn write_tlv_handle(v: (u32, u32), result: *mut u8, result_len: *mut usize) -> i32 {\n    let mut payload = [0u8; 8];\n    payload[0..4].copy_from_slice(&v.0.to_le_bytes());\n    payload[4..8].copy_from_slice(&v.1.to_le_bytes());\n    write_tlv_result(&[(8u8, &payload)], result, result_len)\n}\n",
+        _ => "fn write_tlv_string(v: String, result: *mut u8, result_len: *mut usize) -> i32 {\n    write_tlv_result(&[(6u8, v.as_bytes())], result, result_len)\n}\n",
+    }
+}
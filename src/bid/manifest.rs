@@ -0,0 +1,358 @@
+/*!
+ * Plugin self-description (`nyash_plugin_abi` / `nyash_plugin_manifest`)
+ *
+ * A plugin may export
+ *
+ * ```c
+ * uint32_t nyash_plugin_abi(void);
+ * int32_t  nyash_plugin_manifest(uint8_t* out, size_t* out_len); // E_SHORT protocol
+ * ```
+ *
+ * The manifest is UTF-8 TOML using the same box tables as nyash.toml, minus
+ * the `libraries."<lib>"` prefix:
+ *
+ * ```toml
+ * abi_version = 1
+ *
+ * [CounterBox]
+ * type_id = 7
+ *
+ * [CounterBox.methods]
+ * birth = { method_id = 0 }
+ * inc = { method_id = 1 }
+ * fini = { method_id = 4294967295 }
+ * ```
+ *
+ * The loader checks nyash.toml against it so drifting method ids fail at
+ * load time instead of calling the wrong method.
+ */
+
+use crate::config::nyash_toml_v2::{ArgDecl, BoxTypeConfig};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// ABI version implemented by this host
+pub const CURRENT_ABI_VERSION: u32 = 1;
+
+/// ABI versions this host can load
+pub const SUPPORTED_ABI_VERSIONS: &[u32] = &[1];
+
+/// Upper bound for a manifest fetched from a plugin
+const MAX_MANIFEST_LEN: usize = 1 << 20;
+
+/// Native `nyash_plugin_manifest` symbol
+pub type ManifestFn = unsafe extern "C" fn(*mut u8, *mut usize) -> i32;
+
+/// Call an `nyash_plugin_manifest`-shaped function, growing the buffer on E_SHORT
+pub fn fetch_manifest_bytes(mut call: impl FnMut(&mut [u8], &mut usize) -> i32) -> Result<Vec<u8>, String> {
+    let mut buf = vec![0u8; 4096];
+    loop {
+        let mut len = buf.len();
+        match call(&mut buf, &mut len) {
+            0 => {
+                buf.truncate(len.min(buf.len()));
+                return Ok(buf);
+            }
+            -1 if len > buf.len() && len <= MAX_MANIFEST_LEN => buf.resize(len, 0),
+            rc => return Err(format!("nyash_plugin_manifest failed (rc={})", rc)),
+        }
+    }
+}
+
+/// Parsed plugin manifest
+#[derive(Debug)]
+pub struct PluginManifest {
+    pub abi_version: u32,
+    pub boxes: BTreeMap<String, BoxTypeConfig>,
+}
+
+impl PluginManifest {
+    /// Parse manifest TOML text
+    pub fn from_toml_str(text: &str) -> Result<Self, String> {
+        let value: toml::Value = toml::from_str(text).map_err(|e| format!("manifest is not valid TOML: {}", e))?;
+        let table = value.as_table().ok_or("manifest must be a TOML table")?;
+        let mut abi_version = CURRENT_ABI_VERSION;
+        let mut boxes = BTreeMap::new();
+        for (key, v) in table {
+            if key == "abi_version" {
+                abi_version = v
+                    .as_integer()
+                    .and_then(|n| u32::try_from(n).ok())
+                    .ok_or("manifest abi_version must be a non-negative integer")?;
+                continue;
+            }
+            let conf: BoxTypeConfig = v
+                .clone()
+                .try_into()
+                .map_err(|e| format!("manifest box '{}': {}", key, e))?;
+            boxes.insert(key.clone(), conf);
+        }
+        Ok(Self { abi_version, boxes })
+    }
+
+    /// Parse raw manifest bytes as returned by the plugin
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let text = std::str::from_utf8(bytes).map_err(|_| "manifest is not UTF-8".to_string())?;
+        Self::from_toml_str(text)
+    }
+
+    /// Fetch and parse a manifest through an `nyash_plugin_manifest`-shaped call
+    pub fn fetch(call: impl FnMut(&mut [u8], &mut usize) -> i32) -> Result<Self, String> {
+        Self::from_bytes(&fetch_manifest_bytes(call)?)
+    }
+
+    /// Check nyash.toml box tables of one library against this manifest.
+    /// `boxes` lists the library's `boxes` entries with their parsed tables.
+    pub fn validate(&self, boxes: &[(&str, Option<&BoxTypeConfig>)]) -> Vec<ManifestDiagnostic> {
+        let mut out = Vec::new();
+        for (box_name, conf) in boxes {
+            let Some(conf) = conf else { continue };
+            let Some(exported) = self.boxes.get(*box_name) else {
+                out.push(ManifestDiagnostic::error(box_name, None, "listed in nyash.toml but not exported by the plugin".to_string()));
+                continue;
+            };
+            if conf.type_id != exported.type_id {
+                out.push(ManifestDiagnostic::error(
+                    box_name,
+                    None,
+                    format!("type_id {} in nyash.toml, plugin exports {}", conf.type_id, exported.type_id),
+                ));
+            }
+            let mut names: Vec<&String> = conf.methods.keys().collect();
+            names.sort();
+            for name in names {
+                let m = &conf.methods[name];
+                let Some(em) = exported.methods.get(name) else {
+                    out.push(ManifestDiagnostic::error(box_name, Some(name), "not exported by the plugin".to_string()));
+                    continue;
+                };
+                if m.method_id != em.method_id {
+                    out.push(ManifestDiagnostic::error(
+                        box_name,
+                        Some(name),
+                        format!("method_id {} in nyash.toml, plugin exports {}", m.method_id, em.method_id),
+                    ));
+                }
+                if let (Some(args), Some(eargs)) = (&m.args, &em.args) {
+                    if let Some(msg) = compare_args(args, eargs) {
+                        out.push(ManifestDiagnostic::error(box_name, Some(name), msg));
+                    }
+                }
+            }
+            let mut extra: Vec<&String> = exported.methods.keys().filter(|k| !conf.methods.contains_key(*k)).collect();
+            extra.sort();
+            for name in extra {
+                out.push(ManifestDiagnostic::warning(box_name, Some(name), "exported by the plugin but missing from nyash.toml".to_string()));
+            }
+        }
+        for name in self.boxes.keys() {
+            if !boxes.iter().any(|(b, _)| *b == name.as_str()) {
+                out.push(ManifestDiagnostic::warning(name, None, "exported by the plugin but not listed in `boxes`".to_string()));
+            }
+        }
+        out
+    }
+
+    /// Render the box tables as a nyash.toml `[libraries."<lib>"]` section
+    pub fn to_nyash_toml(&self, lib_name: &str, path: &str) -> String {
+        let names: Vec<String> = self.boxes.keys().map(|b| format!("\"{}\"", b)).collect();
+        let mut out = format!("[libraries.\"{}\"]\nboxes = [{}]\npath = \"{}\"\n", lib_name, names.join(", "), path);
+        for (box_name, conf) in &self.boxes {
+            out.push_str(&format!("\n[libraries.\"{}\".{}]\ntype_id = {}\n", lib_name, box_name, conf.type_id));
+            if conf.abi_version != CURRENT_ABI_VERSION {
+                out.push_str(&format!("abi_version = {}\n", conf.abi_version));
+            }
+            if conf.singleton {
+                out.push_str("singleton = true\n");
+            }
+            out.push_str(&format!("\n[libraries.\"{}\".{}.methods]\n", lib_name, box_name));
+            let mut methods: Vec<_> = conf.methods.iter().collect();
+            methods.sort_by_key(|(_, m)| m.method_id);
+            for (name, m) in methods {
+                let mut fields = vec![format!("method_id = {}", m.method_id)];
+                if let Some(args) = &m.args {
+                    let rendered: Vec<String> = args.iter().map(render_arg).collect();
+                    fields.push(format!("args = [{}]", rendered.join(", ")));
+                }
+                if m.returns_result {
+                    fields.push("returns_result = true".to_string());
+                }
+                out.push_str(&format!("{} = {{ {} }}\n", name, fields.join(", ")));
+            }
+        }
+        out
+    }
+}
+
+/// Check the plugin's `nyash_plugin_abi` against this host and nyash.toml
+pub fn check_abi_version(plugin_abi: u32, boxes: &[(&str, Option<&BoxTypeConfig>)]) -> Vec<ManifestDiagnostic> {
+    let mut out = Vec::new();
+    if !SUPPORTED_ABI_VERSIONS.contains(&plugin_abi) {
+        out.push(ManifestDiagnostic::error(
+            "",
+            None,
+            format!("plugin ABI version {} is not supported by this host (supported: {:?})", plugin_abi, SUPPORTED_ABI_VERSIONS),
+        ));
+    }
+    for (box_name, conf) in boxes {
+        if let Some(conf) = conf {
+            if conf.abi_version != plugin_abi {
+                out.push(ManifestDiagnostic::error(
+                    box_name,
+                    None,
+                    format!("abi_version {} in nyash.toml, plugin implements {}", conf.abi_version, plugin_abi),
+                ));
+            }
+        }
+    }
+    out
+}
+
+fn compare_args(declared: &[ArgDecl], exported: &[ArgDecl]) -> Option<String> {
+    if declared.len() != exported.len() {
+        return Some(format!("{} args in nyash.toml, plugin expects {}", declared.len(), exported.len()));
+    }
+    for (i, (a, b)) in declared.iter().zip(exported).enumerate() {
+        if canonical_kind(a.kind_str()) != canonical_kind(b.kind_str()) {
+            return Some(format!("arg {} is '{}' in nyash.toml, plugin expects '{}'", i, a.kind_str(), b.kind_str()));
+        }
+    }
+    None
+}
+
+fn canonical_kind(kind: &str) -> &str {
+    match kind {
+        "int" => "i32",
+        k => k,
+    }
+}
+
+fn render_arg(arg: &ArgDecl) -> String {
+    match arg {
+        ArgDecl::Name(n) => format!("\"{}\"", n),
+        ArgDecl::Typed { kind, category: Some(c) } => format!("{{ kind = \"{}\", category = \"{}\" }}", kind, c),
+        ArgDecl::Typed { kind, category: None } => format!("{{ kind = \"{}\" }}", kind),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestSeverity {
+    Error,
+    Warning,
+}
+
+/// One nyash.toml ⇔ plugin mismatch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestDiagnostic {
+    pub severity: ManifestSeverity,
+    pub box_name: String,
+    pub method: Option<String>,
+    pub message: String,
+}
+
+impl ManifestDiagnostic {
+    fn error(box_name: &str, method: Option<&str>, message: String) -> Self {
+        Self { severity: ManifestSeverity::Error, box_name: box_name.to_string(), method: method.map(str::to_string), message }
+    }
+
+    fn warning(box_name: &str, method: Option<&str>, message: String) -> Self {
+        Self { severity: ManifestSeverity::Warning, box_name: box_name.to_string(), method: method.map(str::to_string), message }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == ManifestSeverity::Error
+    }
+}
+
+impl fmt::Display for ManifestDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self.severity {
+            ManifestSeverity::Error => "error",
+            ManifestSeverity::Warning => "warning",
+        };
+        match (&self.method, self.box_name.is_empty()) {
+            (Some(m), _) => write!(f, "{}: {}.{}: {}", level, self.box_name, m, self.message),
+            (None, false) => write!(f, "{}: {}: {}", level, self.box_name, self.message),
+            (None, true) => write!(f, "{}: {}", level, self.message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COUNTER: &str = r#"
+abi_version = 1
+
+[CounterBox]
+type_id = 7
+
+[CounterBox.methods]
+birth = { method_id = 0 }
+inc = { method_id = 1 }
+get = { method_id = 2 }
+fini = { method_id = 4294967295 }
+"#;
+
+    fn box_conf(text: &str) -> BoxTypeConfig {
+        toml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_matching_config_has_no_diagnostics() {
+        let m = PluginManifest::from_toml_str(COUNTER).unwrap();
+        let conf = box_conf("type_id = 7\n[methods]\nbirth = { method_id = 0 }\ninc = { method_id = 1 }\nget = { method_id = 2 }\nfini = { method_id = 4294967295 }\n");
+        assert!(m.validate(&[("CounterBox", Some(&conf))]).is_empty());
+        assert!(check_abi_version(m.abi_version, &[("CounterBox", Some(&conf))]).is_empty());
+    }
+
+    #[test]
+    fn test_drifted_method_ids_are_reported() {
+        let m = PluginManifest::from_toml_str(COUNTER).unwrap();
+        let conf = box_conf("type_id = 8\n[methods]\ninc = { method_id = 2 }\nreset = { method_id = 3 }\n");
+        let diags = m.validate(&[("CounterBox", Some(&conf)), ("OtherBox", None)]);
+        let text: Vec<String> = diags.iter().map(|d| d.to_string()).collect();
+        assert!(text.contains(&"error: CounterBox: type_id 8 in nyash.toml, plugin exports 7".to_string()), "{:?}", text);
+        assert!(text.contains(&"error: CounterBox.inc: method_id 2 in nyash.toml, plugin exports 1".to_string()));
+        assert!(text.contains(&"error: CounterBox.reset: not exported by the plugin".to_string()));
+        assert!(text.contains(&"warning: CounterBox.get: exported by the plugin but missing from nyash.toml".to_string()));
+        assert_eq!(diags.iter().filter(|d| d.is_error()).count(), 3);
+    }
+
+    #[test]
+    fn test_abi_mismatch_and_arg_kinds() {
+        let conf = box_conf("type_id = 7\nabi_version = 2\n[methods]\nopen = { method_id = 1, args = [{ kind = \"int\" }] }\n");
+        let diags = check_abi_version(1, &[("FileBox", Some(&conf))]);
+        assert_eq!(diags.len(), 1);
+        assert!(check_abi_version(9, &[]).iter().any(|d| d.message.contains("not supported")));
+
+        let m = PluginManifest::from_toml_str("[FileBox]\ntype_id = 7\n[FileBox.methods]\nopen = { method_id = 1, args = [\"path\"] }\n").unwrap();
+        let diags = m.validate(&[("FileBox", Some(&conf))]);
+        assert_eq!(diags[0].to_string(), "error: FileBox.open: arg 0 is 'int' in nyash.toml, plugin expects 'string'");
+    }
+
+    #[test]
+    fn test_fetch_retries_short_buffer_and_renders_toml() {
+        let bytes = COUNTER.as_bytes();
+        let mut calls = 0;
+        let m = PluginManifest::fetch(|out, len| {
+            calls += 1;
+            if out.len() < 8192 {
+                *len = 8192;
+                return -1;
+            }
+            out[..bytes.len()].copy_from_slice(bytes);
+            *len = bytes.len();
+            0
+        })
+        .unwrap();
+        assert_eq!(calls, 2);
+        let section = m.to_nyash_toml("libcounter.so", "./libcounter.so");
+        let conf = crate::config::nyash_toml_v2::NyashConfigV2::from_str(&format!("[libraries]\n{}", section)).unwrap();
+        let raw: toml::Value = toml::from_str(&format!("[libraries]\n{}", section)).unwrap();
+        let bc = conf.get_box_config("libcounter.so", "CounterBox", &raw).unwrap();
+        assert_eq!(bc.methods["inc"].method_id, 1);
+        assert!(m.validate(&[("CounterBox", Some(&bc))]).is_empty());
+    }
+}
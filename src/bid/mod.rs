@@ -8,6 +8,9 @@ pub mod metadata;
 pub mod plugin_api;
 pub mod bridge;
 pub mod plugins;
+pub mod schema;
+pub mod codegen;
+pub mod manifest;
 #[cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
 pub mod loader;
 // pub mod registry;  // legacy - v2 plugin system uses BoxFactoryRegistry instead
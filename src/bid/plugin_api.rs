@@ -46,6 +46,7 @@ pub type PluginShutdownFn = unsafe extern "C" fn();
 pub const PLUGIN_ABI_SYMBOL: &str = "nyash_plugin_abi";
 pub const PLUGIN_INIT_SYMBOL: &str = "nyash_plugin_init";
 pub const PLUGIN_INVOKE_SYMBOL: &str = "nyash_plugin_invoke";
+pub const PLUGIN_MANIFEST_SYMBOL: &str = "nyash_plugin_manifest";
 pub const PLUGIN_SHUTDOWN_SYMBOL: &str = "nyash_plugin_shutdown";
 
 /// Plugin handle containing loaded functions
@@ -0,0 +1,521 @@
+/*!
+ * BID Schema Parsing - YAML/JSON schema for Box Interface Definitions
+ *
+ * A BID file describes the boxes a plugin library provides, their type ids and
+ * the typed method table. `nyash bid gen` turns it into a plugin crate skeleton
+ * and the matching nyash.toml `[libraries]` section (see `bid::codegen`).
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use thiserror::Error;
+
+/// Reserved method id for the constructor
+pub const BID_METHOD_BIRTH: u32 = 0;
+/// Reserved method id for the destructor
+pub const BID_METHOD_FINI: u32 = u32::MAX;
+
+/// Errors raised while loading or validating a BID definition
+#[derive(Error, Debug)]
+pub enum BidSchemaError {
+    #[error("I/O error: {0}")]
+    IoError(String),
+
+    #[error("parse error: {0}")]
+    ParseError(String),
+
+    #[error("unsupported BID version: {0}")]
+    UnsupportedVersion(u32),
+
+    #[error("duplicate interface: {0}")]
+    DuplicateInterface(String),
+
+    #[error("duplicate method: {0}")]
+    DuplicateMethod(String),
+
+    #[error("duplicate parameter: {0}")]
+    DuplicateParameter(String),
+
+    #[error("duplicate method id {id} in {interface} ({first} / {second})")]
+    DuplicateMethodId { interface: String, id: u32, first: String, second: String },
+
+    #[error("duplicate type_id {0}")]
+    DuplicateTypeId(u32),
+
+    #[error("unsupported type '{type_name}' in {context}")]
+    UnsupportedType { type_name: String, context: String },
+
+    #[error("missing field '{field}' in {context}")]
+    MissingField { field: String, context: String },
+}
+
+/// BID Definition - Root structure for BID files
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BidDefinition {
+    pub version: u32,
+    pub interfaces: Vec<BidInterface>,
+    pub metadata: Option<BidMetadata>,
+}
+
+/// Metadata for a BID definition
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BidMetadata {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub author: Option<String>,
+    pub version: Option<String>,
+}
+
+/// Interface definition
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BidInterface {
+    pub name: String,
+    #[serde(rename = "box")]
+    pub box_type: Option<String>,  // Box type name
+    /// Box type ID used on the FFI boundary (required for plugin generation)
+    #[serde(default)]
+    pub type_id: Option<u32>,
+    /// Keep one shared instance alive in the loader
+    #[serde(default)]
+    pub singleton: bool,
+    pub methods: Vec<BidMethod>,
+}
+
+/// Method definition
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BidMethod {
+    pub name: String,
+    /// Explicit method id (otherwise assigned in declaration order from 1)
+    #[serde(default)]
+    pub id: Option<u32>,
+    #[serde(default)]
+    pub params: Vec<BidParameter>,
+    #[serde(default = "BidTypeRef::void")]
+    pub returns: BidTypeRef,
+    pub effect: Option<String>,
+    /// Wrap return and errors into ResultBox on the Nyash side
+    #[serde(default)]
+    pub returns_result: bool,
+}
+
+/// Parameter definition
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BidParameter {
+    #[serde(flatten)]
+    pub param_type: BidTypeRef,
+}
+
+/// Type reference in BID files
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum BidTypeRef {
+    /// Simple type: { string: "name" }
+    Named(HashMap<String, String>),
+    /// Just a type name: "void"
+    Simple(String),
+}
+
+/// Value kinds a BID method can take or return, with their TLV mapping
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BidValueKind {
+    Void,
+    Bool,
+    I32,
+    I64,
+    F64,
+    String,
+    Bytes,
+    /// Plugin box reference (TLV Handle: type_id + instance_id)
+    Box,
+}
+
+impl BidValueKind {
+    /// Parse a BID type name
+    pub fn from_type_name(name: &str) -> Option<Self> {
+        match name {
+            "void" => Some(Self::Void),
+            "bool" => Some(Self::Bool),
+            "i32" | "int" => Some(Self::I32),
+            "i64" => Some(Self::I64),
+            "f64" | "float" => Some(Self::F64),
+            "string" | "str" => Some(Self::String),
+            "bytes" => Some(Self::Bytes),
+            "box" | "handle" => Some(Self::Box),
+            _ => None,
+        }
+    }
+
+    /// BID-1 TLV tag (see `bid::BidTag`)
+    pub fn tlv_tag(&self) -> u8 {
+        match self {
+            Self::Bool => 1,
+            Self::I32 => 2,
+            Self::I64 => 3,
+            Self::F64 => 5,
+            Self::String => 6,
+            Self::Bytes => 7,
+            Self::Box => 8,
+            Self::Void => 9,
+        }
+    }
+
+    /// Whether the v2 loader can encode this kind as a method argument
+    pub fn is_loader_arg(&self) -> bool {
+        matches!(self, Self::I32 | Self::String | Self::Box)
+    }
+}
+
+impl BidDefinition {
+    /// Load BID definition from a YAML or JSON file (chosen by extension)
+    pub fn load_from_file(path: &Path) -> Result<Self, BidSchemaError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| BidSchemaError::IoError(format!("{}: {}", path.display(), e)))?;
+
+        let is_json = path.extension().map(|e| e == "json").unwrap_or(false);
+        if is_json {
+            Self::from_json_str(&content)
+        } else {
+            Self::from_yaml_str(&content)
+        }
+    }
+
+    /// Parse and validate a YAML BID definition
+    pub fn from_yaml_str(content: &str) -> Result<Self, BidSchemaError> {
+        let bid: BidDefinition = serde_yaml::from_str(content)
+            .map_err(|e| BidSchemaError::ParseError(format!("YAML parse error: {}", e)))?;
+        bid.validate()?;
+        Ok(bid)
+    }
+
+    /// Parse and validate a JSON BID definition
+    pub fn from_json_str(content: &str) -> Result<Self, BidSchemaError> {
+        let bid: BidDefinition = serde_json::from_str(content)
+            .map_err(|e| BidSchemaError::ParseError(format!("JSON parse error: {}", e)))?;
+        bid.validate()?;
+        Ok(bid)
+    }
+
+    /// Validate the BID definition
+    pub fn validate(&self) -> Result<(), BidSchemaError> {
+        // Check version
+        if self.version > 1 {
+            return Err(BidSchemaError::UnsupportedVersion(self.version));
+        }
+
+        // Check for duplicate interface names and type ids
+        let mut interface_names = std::collections::HashSet::new();
+        let mut type_ids = std::collections::HashSet::new();
+        for interface in &self.interfaces {
+            if !interface_names.insert(interface.name.clone()) {
+                return Err(BidSchemaError::DuplicateInterface(interface.name.clone()));
+            }
+            if let Some(tid) = interface.type_id {
+                if !type_ids.insert(tid) {
+                    return Err(BidSchemaError::DuplicateTypeId(tid));
+                }
+            }
+        }
+
+        // Validate each interface
+        for interface in &self.interfaces {
+            interface.validate()?;
+        }
+
+        Ok(())
+    }
+
+    /// Get interface by name
+    pub fn get_interface(&self, name: &str) -> Option<&BidInterface> {
+        self.interfaces.iter().find(|i| i.name == name)
+    }
+
+    /// Get the definition name (from metadata or derived from first interface)
+    pub fn name(&self) -> String {
+        if let Some(ref metadata) = self.metadata {
+            if let Some(ref name) = metadata.name {
+                return name.clone();
+            }
+        }
+
+        // Derive from first interface name
+        if let Some(interface) = self.interfaces.first() {
+            // Extract the last part of the interface name
+            // e.g., "env.console" -> "console"
+            interface.name.split('.').next_back().unwrap_or(&interface.name).to_string()
+        } else {
+            "unknown".to_string()
+        }
+    }
+}
+
+impl BidInterface {
+    /// Validate the interface
+    pub fn validate(&self) -> Result<(), BidSchemaError> {
+        // Check for duplicate method names
+        let mut method_names = std::collections::HashSet::new();
+        for method in &self.methods {
+            if !method_names.insert(method.name.clone()) {
+                return Err(BidSchemaError::DuplicateMethod(method.name.clone()));
+            }
+        }
+
+        // Validate each method
+        for method in &self.methods {
+            method.validate()?;
+        }
+
+        // Method ids (explicit or assigned) must not collide
+        let mut seen: HashMap<u32, String> = HashMap::new();
+        for (name, id) in self.method_table() {
+            if let Some(first) = seen.insert(id, name.clone()) {
+                return Err(BidSchemaError::DuplicateMethodId {
+                    interface: self.name.clone(),
+                    id,
+                    first,
+                    second: name,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Box type name (falls back to the last segment of the interface name)
+    pub fn box_name(&self) -> String {
+        self.box_type.clone().unwrap_or_else(|| {
+            self.name.split('.').next_back().unwrap_or(&self.name).to_string()
+        })
+    }
+
+    /// Resolved method ids for user methods (birth/fini excluded)
+    ///
+    /// Methods without an explicit `id` are numbered in declaration order,
+    /// continuing after the highest id seen so far (starting at 1).
+    pub fn method_ids(&self) -> Vec<(&BidMethod, u32)> {
+        let mut next = BID_METHOD_BIRTH + 1;
+        self.user_methods()
+            .map(|m| {
+                let id = m.id.unwrap_or(next);
+                next = next.max(id.saturating_add(1));
+                (m, id)
+            })
+            .collect()
+    }
+
+    /// Full method table including the reserved birth/fini entries
+    pub fn method_table(&self) -> Vec<(String, u32)> {
+        let mut table = vec![("birth".to_string(), BID_METHOD_BIRTH)];
+        table.extend(self.method_ids().into_iter().map(|(m, id)| (m.name.clone(), id)));
+        table.push(("fini".to_string(), BID_METHOD_FINI));
+        table
+    }
+
+    /// Methods other than the reserved birth/fini
+    pub fn user_methods(&self) -> impl Iterator<Item = &BidMethod> {
+        self.methods.iter().filter(|m| m.name != "birth" && m.name != "fini")
+    }
+}
+
+impl BidMethod {
+    /// Validate the method
+    pub fn validate(&self) -> Result<(), BidSchemaError> {
+        // Check for duplicate parameter names
+        let mut param_names = std::collections::HashSet::new();
+        for (i, param) in self.params.iter().enumerate() {
+            let param_name = param.get_name().unwrap_or_else(|| format!("param_{}", i));
+            if !param_names.insert(param_name.clone()) {
+                return Err(BidSchemaError::DuplicateParameter(param_name));
+            }
+            let kind = param.kind().ok_or_else(|| BidSchemaError::UnsupportedType {
+                type_name: param.get_type(),
+                context: format!("parameter '{}' of method '{}'", param_name, self.name),
+            })?;
+            if kind == BidValueKind::Void {
+                return Err(BidSchemaError::UnsupportedType {
+                    type_name: "void".to_string(),
+                    context: format!("parameter '{}' of method '{}'", param_name, self.name),
+                });
+            }
+        }
+
+        if self.returns.kind().is_none() {
+            return Err(BidSchemaError::UnsupportedType {
+                type_name: self.returns.type_name(),
+                context: format!("return type of method '{}'", self.name),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl BidParameter {
+    /// Get the parameter name (from the type definition)
+    pub fn get_name(&self) -> Option<String> {
+        match &self.param_type {
+            BidTypeRef::Named(map) => {
+                // Return the first value (parameter name)
+                map.values().next().cloned()
+            },
+            BidTypeRef::Simple(_) => None,
+        }
+    }
+
+    /// Get the parameter type name
+    pub fn get_type(&self) -> String {
+        self.param_type.type_name()
+    }
+
+    /// Get the parameter value kind
+    pub fn kind(&self) -> Option<BidValueKind> {
+        self.param_type.kind()
+    }
+}
+
+impl BidTypeRef {
+    fn void() -> Self {
+        BidTypeRef::Simple("void".to_string())
+    }
+
+    /// Get the type name
+    pub fn type_name(&self) -> String {
+        match self {
+            BidTypeRef::Named(map) => {
+                map.keys().next().cloned().unwrap_or_else(|| "unknown".to_string())
+            },
+            BidTypeRef::Simple(type_name) => type_name.clone(),
+        }
+    }
+
+    /// Get the value kind
+    pub fn kind(&self) -> Option<BidValueKind> {
+        BidValueKind::from_type_name(&self.type_name())
+    }
+
+    /// Check if this is a void type
+    pub fn is_void(&self) -> bool {
+        self.type_name() == "void"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_console_bid() {
+        let yaml_content = r#"
+version: 0
+interfaces:
+  - name: env.console
+    box: Console
+    methods:
+      - name: log
+        params:
+          - { string: msg }
+        returns: void
+        effect: io
+"#;
+
+        let bid = BidDefinition::from_yaml_str(yaml_content).unwrap();
+
+        assert_eq!(bid.version, 0);
+        assert_eq!(bid.interfaces.len(), 1);
+
+        let interface = &bid.interfaces[0];
+        assert_eq!(interface.name, "env.console");
+        assert_eq!(interface.box_type, Some("Console".to_string()));
+        assert_eq!(interface.methods.len(), 1);
+
+        let method = &interface.methods[0];
+        assert_eq!(method.name, "log");
+        assert_eq!(method.params.len(), 1);
+        assert!(method.returns.is_void());
+
+        let param = &method.params[0];
+        assert_eq!(param.get_type(), "string");
+        assert_eq!(param.get_name(), Some("msg".to_string()));
+        assert_eq!(param.kind(), Some(BidValueKind::String));
+    }
+
+    #[test]
+    fn test_bid_definition_name() {
+        let yaml_content = r#"
+version: 0
+interfaces:
+  - name: env.console
+    methods: []
+"#;
+
+        let bid = BidDefinition::from_yaml_str(yaml_content).unwrap();
+        assert_eq!(bid.name(), "console");
+    }
+
+    #[test]
+    fn test_duplicate_interface_validation() {
+        let yaml_content = r#"
+version: 0
+interfaces:
+  - name: env.console
+    methods: []
+  - name: env.console
+    methods: []
+"#;
+
+        let result = BidDefinition::from_yaml_str(yaml_content);
+
+        if let Err(BidSchemaError::DuplicateInterface(name)) = result {
+            assert_eq!(name, "env.console");
+        } else {
+            panic!("Expected DuplicateInterface error");
+        }
+    }
+
+    #[test]
+    fn test_method_ids_assigned_in_order() {
+        let yaml_content = r#"
+version: 1
+interfaces:
+  - name: demo.box
+    box: DemoBox
+    type_id: 40
+    methods:
+      - { name: a }
+      - { name: b, id: 5 }
+      - { name: c }
+"#;
+        let bid = BidDefinition::from_yaml_str(yaml_content).unwrap();
+        let table = bid.interfaces[0].method_table();
+        assert_eq!(table, vec![
+            ("birth".to_string(), 0),
+            ("a".to_string(), 1),
+            ("b".to_string(), 5),
+            ("c".to_string(), 6),
+            ("fini".to_string(), u32::MAX),
+        ]);
+    }
+
+    #[test]
+    fn test_duplicate_method_id_and_unknown_type() {
+        let dup = r#"
+version: 1
+interfaces:
+  - name: demo.box
+    methods:
+      - { name: a, id: 2 }
+      - { name: b, id: 2 }
+"#;
+        assert!(matches!(BidDefinition::from_yaml_str(dup), Err(BidSchemaError::DuplicateMethodId { id: 2, .. })));
+
+        let bad_type = r#"
+version: 1
+interfaces:
+  - name: demo.box
+    methods:
+      - name: a
+        params: [ { matrix: m } ]
+"#;
+        assert!(matches!(BidDefinition::from_yaml_str(bad_type), Err(BidSchemaError::UnsupportedType { .. })));
+    }
+}
@@ -0,0 +1,45 @@
+//! nyash-aot-runner - stub for executables built by `nyash --compile-native`
+//!
+//! The precompiled module is appended to a copy of this binary (see backend::aot::executable);
+//! at startup it runs the module found at the end of its own file. An integer result of
+//! `main` becomes the exit code. Plugin boxes are configured from a `nyash.toml` in the
+//! working directory, as with `nyash` itself.
+
+use nyash_rust::backend::wasm::WasmValue;
+use nyash_rust::runtime::{get_global_loader_v2, get_global_registry, init_global_loader_v2, PluginConfig};
+
+/// Register the plugin providers of nyash.toml, when there is one
+fn init_plugins() {
+    if !std::path::Path::new("nyash.toml").exists() || init_global_loader_v2("nyash.toml").is_err() {
+        return;
+    }
+    let loader = get_global_loader_v2();
+    let loader = loader.read().unwrap();
+    if let Some(config) = &loader.config {
+        let registry = get_global_registry();
+        for (lib_name, lib_def) in &config.libraries {
+            for box_name in &lib_def.boxes {
+                registry.apply_plugin_config(&PluginConfig {
+                    plugins: [(box_name.clone(), lib_name.clone())].into(),
+                });
+            }
+        }
+    }
+}
+
+fn main() {
+    init_plugins();
+    let result = std::env::current_exe()
+        .map_err(|e| e.to_string())
+        .and_then(|exe| nyash_rust::backend::aot::run_embedded(exe).map_err(|e| e.to_string()));
+    match result {
+        Ok(execution) => match execution.value {
+            WasmValue::Integer(code) => std::process::exit(code as i32),
+            _ => std::process::exit(0),
+        },
+        Err(e) => {
+            eprintln!("❌ AOT execution error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
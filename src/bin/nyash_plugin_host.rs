@@ -0,0 +1,13 @@
+//! nyash-plugin-host - helper process for `isolation = "process"` plugins
+//!
+//! Usage: nyash-plugin-host <path-to-plugin-library>
+//! Spawned by PluginLoaderV2; speaks the frame protocol in runtime::plugin_host.
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let Some(lib_path) = args.next() else {
+        eprintln!("usage: nyash-plugin-host <plugin-library>");
+        std::process::exit(2);
+    };
+    std::process::exit(nyash_rust::runtime::plugin_host::serve_plugin_host(&lib_path));
+}
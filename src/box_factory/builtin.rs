@@ -349,6 +349,16 @@ impl BuiltinBoxFactory {
             Ok(Box::new(HTTPResponseBox::new()))
         });
 
+        // WebSocketBox
+        self.register("WebSocketBox", |args| {
+            if !args.is_empty() {
+                return Err(RuntimeError::InvalidOperation {
+                    message: format!("WebSocketBox constructor expects 0 arguments, got {}", args.len()),
+                });
+            }
+            Ok(Box::new(WebSocketBox::new()))
+        });
+
         // P2PBox
         self.register("P2PBox", |args| {
             if args.len() != 2 {
@@ -360,7 +370,9 @@ impl BuiltinBoxFactory {
             let transport_str = args[1].to_string_box().value;
             let transport_kind = transport_str.parse::<TransportKind>()
                 .map_err(|e| RuntimeError::InvalidOperation { message: e })?;
-            Ok(Box::new(P2PBox::new(node_id, transport_kind)))
+            let p2p = P2PBox::new(node_id, transport_kind)
+                .map_err(|e| RuntimeError::InvalidOperation { message: format!("P2PBox transport error: {:?}", e) })?;
+            Ok(Box::new(p2p))
         });
     }
 
@@ -456,12 +468,7 @@ impl BuiltinBoxFactory {
             }
             let name = args[0].to_string_box().value;
             // Try parse payload as JSON, fallback to string
-            let payload_str = args[1].to_string_box().value;
-            let payload = match serde_json::from_str::<serde_json::Value>(&payload_str) {
-                Ok(json) => json,
-                Err(_) => serde_json::Value::String(payload_str),
-            };
-            Ok(Box::new(IntentBox::new(name, payload)))
+            Ok(Box::new(IntentBox::from_value(name, args[1].as_ref())))
         });
 
         // ErrorBox (Exception)
@@ -487,6 +494,46 @@ impl BuiltinBoxFactory {
             Ok(Box::new(DateTimeBox::now()))
         });
         
+        // QRBox
+        self.register("QRBox", |args| {
+            if !args.is_empty() {
+                return Err(RuntimeError::InvalidOperation {
+                    message: format!("QRBox constructor expects 0 arguments, got {}", args.len()),
+                });
+            }
+            Ok(Box::new(crate::boxes::QRBox::new()))
+        });
+        
+        // CanvasBox (width, height) or (canvas_id, width, height)
+        self.register("CanvasBox", |args| {
+            let (canvas_id, size) = match args.len() {
+                2 => (None, args),
+                3 => (Some(args[0].to_string_box().value), &args[1..]),
+                _ => return Err(RuntimeError::InvalidOperation {
+                    message: format!("CanvasBox constructor expects 2 arguments (width, height) or 3 (canvas_id, width, height), got {}", args.len()),
+                }),
+            };
+            let mut dims = [0u32; 2];
+            for (dim, arg) in dims.iter_mut().zip(size) {
+                *dim = arg.to_string_box().value.parse::<u32>()
+                    .map_err(|_| RuntimeError::TypeError { message: "CanvasBox width and height must be non-negative integers".to_string() })?;
+            }
+            Ok(Box::new(match canvas_id {
+                Some(id) => crate::boxes::CanvasBox::with_id(&id, dims[0], dims[1]),
+                None => crate::boxes::CanvasBox::new(dims[0], dims[1]),
+            }))
+        });
+        
+        // AudioBox
+        self.register("AudioBox", |args| {
+            if !args.is_empty() {
+                return Err(RuntimeError::InvalidOperation {
+                    message: format!("AudioBox constructor expects 0 arguments, got {}", args.len()),
+                });
+            }
+            Ok(Box::new(crate::boxes::AudioBox::new()))
+        });
+        
         // Additional native types can be registered here
         #[cfg(all(feature = "gui", not(target_arch = "wasm32")))]
         {
@@ -5,6 +5,10 @@
  * Web Audio APIを使用してブラウザでの音声再生、
  * 合成、エフェクト処理を統一的に管理するBox。
  * ゲーム、音楽アプリ、オーディオビジュアライザー開発に最適。
+ *
+ * ネイティブ環境ではオフラインのシンセサイザ（`boxes::synth`）に音を積み上げ、
+ * WAV ファイルや PCM バッファとして取り出す。サウンドカードが無くても
+ * 同じスクリプトから常に同じ波形が得られるので、音を出すスクリプトをテストできる。
  * 
  * ## 🛠️ 利用可能メソッド
  * 
@@ -16,14 +20,25 @@
  * - `setVolume(volume)` - 音量設定 (0.0-1.0)
  * 
  * ### 🎵 音声合成
- * - `createTone(frequency, duration)` - 純音生成
- * - `createNoise(type, duration)` - ノイズ生成
+ * - `createTone(frequency, duration)` - 純音生成（前の音に続けて配置）
+ * - `createNoise(duration)` - ノイズ生成
  * - `createBeep()` - システム音
+ * - `addTone(waveform, frequency, startMs, durationMs, [gain])` - 任意の時刻に重ねる
+ * - `rest(duration)` - 無音区間
+ * - `setWaveform(name)` - "sine" / "square" / "saw" / "triangle" / "noise"
+ * - `setEnvelope(attackMs, decayMs, sustain, releaseMs)` - ADSR
  * 
  * ### 📊 解析・ビジュアライザー
- * - `getFrequencyData()` - 周波数解析データ取得
- * - `getWaveformData()` - 波形データ取得
+ * - `getFrequencyData([atMs])` - 周波数解析データ取得（FFT 2048点、BufferBox 1024バイト）
+ * - `getWaveformData([atMs])` - 波形データ取得（BufferBox 2048バイト、128が無音）
  * - `getVolume()` - 現在の音量レベル
+ *
+ * ### 💾 書き出し（ネイティブ）
+ * - `render(path)` - 44.1kHz 16bit モノラルの WAV を保存（ResultBox）
+ * - `getSamples()` - 16bit リトルエンディアン PCM の BufferBox
+ * - `getDuration()` - 全体の長さ（ms）
+ * - `getSampleRate()` - 44100
+ * - `clear()` - 合成した音を消す
  * 
  * ### 🎛️ エフェクト
  * - `addReverb(room)` - リバーブエフェクト
@@ -43,6 +58,7 @@
  * // 音声合成
  * audio.createTone(440, 1000)  // A4音を1秒
  * audio.createBeep()           // システム音
+ * audio.render("out.wav")      // ネイティブではWAVに書き出し
  * 
  * // オーディオビジュアライザー
  * local freqData = audio.getFrequencyData()
@@ -50,8 +66,13 @@
  * ```
  */
 
-use crate::box_trait::{NyashBox, StringBox, BoolBox, BoxCore, BoxBase};
+use crate::box_trait::{NyashBox, StringBox, BoolBox, IntegerBox, VoidBox, BoxCore, BoxBase};
+use crate::boxes::buffer::BufferBox;
+use crate::boxes::math_box::FloatBox;
+use crate::boxes::result::NyashResultBox;
+use crate::boxes::synth::{Envelope, Synth, Waveform, SAMPLE_RATE};
 use std::any::Any;
+use std::sync::{Arc, Mutex, MutexGuard};
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
@@ -66,7 +87,8 @@ use web_sys::{
 #[derive(Debug, Clone)]
 pub struct AudioBox {
     base: BoxBase,
-    volume: f64,
+    /// 合成タイムラインと音量（clone 間で共有）
+    synth: Arc<Mutex<Synth>>,
     is_playing: bool,
 }
 
@@ -74,26 +96,31 @@ impl AudioBox {
     pub fn new() -> Self {
         Self {
             base: BoxBase::new(),
-            volume: 1.0,
+            synth: Arc::new(Mutex::new(Synth::new())),
             is_playing: false,
         }
     }
 
+    /// 合成エンジンへのアクセス
+    pub fn synth(&self) -> MutexGuard<'_, Synth> {
+        self.synth.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
     /// 音量を設定 (0.0 - 1.0)
-    pub fn set_volume(&mut self, volume: f64) {
-        self.volume = volume.max(0.0).min(1.0);
+    pub fn set_volume(&self, volume: f64) {
+        self.synth().set_volume(volume);
         
         #[cfg(target_arch = "wasm32")]
         {
             if let Some(gain) = &self.gain_node {
-                gain.gain().set_value(self.volume as f32);
+                gain.gain().set_value(self.get_volume() as f32);
             }
         }
     }
 
     /// 現在の音量を取得
     pub fn get_volume(&self) -> f64 {
-        self.volume
+        self.synth().volume()
     }
 
     #[cfg(target_arch = "wasm32")]
@@ -106,7 +133,7 @@ impl AudioBox {
                     oscillator.frequency().set_value(frequency as f32);
                     
                     // 音量設定
-                    gain.gain().set_value(self.volume as f32);
+                    gain.gain().set_value(self.get_volume() as f32);
                     
                     // ノード接続
                     oscillator.connect_with_audio_node(&gain).unwrap_or_default();
@@ -151,7 +178,7 @@ impl AudioBox {
                         source.set_buffer(Some(&buffer));
                         
                         if let Ok(gain) = context.create_gain() {
-                            gain.gain().set_value(self.volume as f32);
+                            gain.gain().set_value(self.get_volume() as f32);
                             source.connect_with_audio_node(&gain).unwrap_or_default();
                             gain.connect_with_audio_node(&context.destination()).unwrap_or_default();
                             
@@ -199,34 +226,146 @@ impl AudioBox {
     }
 
     #[cfg(not(target_arch = "wasm32"))]
-    /// Non-WASM環境用のダミー実装
+    /// 現在の波形で純音を合成し、前の音の後ろに置く
     pub fn create_tone(&self, frequency: f64, duration: f64) -> bool {
-        println!("AudioBox: Playing tone {}Hz for {}ms (simulated)", frequency, duration);
+        if !(frequency.is_finite() && frequency > 0.0 && duration.is_finite() && duration > 0.0) {
+            return false;
+        }
+        self.synth().play_tone(frequency, duration);
         true
     }
 
     #[cfg(not(target_arch = "wasm32"))]
     pub fn create_beep(&self) -> bool {
-        println!("AudioBox: Beep sound (simulated)");
-        true
+        self.create_tone(800.0, 200.0) // 800Hz、200ms
     }
 
     #[cfg(not(target_arch = "wasm32"))]
     pub fn create_noise(&self, duration: f64) -> bool {
-        println!("AudioBox: White noise for {}ms (simulated)", duration);
+        if !(duration.is_finite() && duration > 0.0) {
+            return false;
+        }
+        self.synth().play_noise(duration);
         true
     }
 
     #[cfg(not(target_arch = "wasm32"))]
+    /// 先頭から FFT_SIZE サンプルの周波数スペクトル
     pub fn get_frequency_data(&self) -> Vec<u8> {
-        // シミュレーション用のダミーデータ
-        (0..64).map(|i| ((i as f64 * 4.0).sin() * 128.0 + 128.0) as u8).collect()
+        self.synth().frequency_data(0.0)
     }
 
     #[cfg(not(target_arch = "wasm32"))]
+    /// 先頭から FFT_SIZE サンプルの波形
     pub fn get_waveform_data(&self) -> Vec<u8> {
-        // シミュレーション用のダミーデータ
-        (0..128).map(|i| ((i as f64 * 0.1).sin() * 64.0 + 128.0) as u8).collect()
+        self.synth().waveform_data(0.0)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// WAV ファイルとして書き出す
+    pub fn render(&self, path: &str) -> Result<(), String> {
+        let wav = self.synth().to_wav();
+        std::fs::write(path, wav).map_err(|e| format!("Failed to write '{}': {}", path, e))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Nyash からのメソッド呼び出し（引数は評価済み）
+    pub fn invoke(&self, method: &str, args: &[Box<dyn NyashBox>]) -> Result<Box<dyn NyashBox>, String> {
+        let (min, max) = match method {
+            "createBeep" | "getVolume" | "getSamples" | "getDuration" | "getSampleRate"
+            | "getWaveform" | "clear" | "isPlaying" | "toString" => (0, 0),
+            "createNoise" | "rest" | "setVolume" | "setWaveform" | "render" => (1, 1),
+            "getFrequencyData" | "getWaveformData" => (0, 1),
+            "createTone" => (2, 2),
+            "addTone" => (4, 5),
+            "setEnvelope" => (4, 4),
+            _ => return Err(format!("Unknown method '{}' for AudioBox", method)),
+        };
+        if args.len() < min || args.len() > max {
+            let expected = if min == max { min.to_string() } else { format!("{}-{}", min, max) };
+            return Err(format!("{}() expects {} arguments, got {}", method, expected, args.len()));
+        }
+        let num = |index: usize| number(method, args, index);
+        let positive = |index: usize| -> Result<f64, String> {
+            let value = num(index)?;
+            if value.is_finite() && value > 0.0 {
+                Ok(value)
+            } else {
+                Err(format!("{}() argument {} must be positive, got {}", method, index + 1, value))
+            }
+        };
+        let void = || -> Result<Box<dyn NyashBox>, String> { Ok(Box::new(VoidBox::new())) };
+
+        match method {
+            "createTone" => {
+                self.synth().play_tone(positive(0)?, positive(1)?);
+                Ok(Box::new(BoolBox::new(true)))
+            }
+            "createBeep" => Ok(Box::new(BoolBox::new(self.create_beep()))),
+            "createNoise" => {
+                self.synth().play_noise(positive(0)?);
+                Ok(Box::new(BoolBox::new(true)))
+            }
+            "addTone" => {
+                let waveform = parse_waveform(&args[0].to_string_box().value)?;
+                let frequency = num(1)?;
+                let start = num(2)?;
+                let duration = positive(3)?;
+                let gain = if args.len() > 4 { num(4)? } else { 1.0 };
+                if !(frequency.is_finite() && frequency >= 0.0 && start.is_finite() && start >= 0.0 && gain.is_finite()) {
+                    return Err("addTone() expects a non-negative frequency and start time".to_string());
+                }
+                self.synth().add_tone(waveform, frequency, start, duration, gain);
+                void()
+            }
+            "rest" => {
+                self.synth().rest(num(0)?);
+                void()
+            }
+            "setWaveform" => {
+                let waveform = parse_waveform(&args[0].to_string_box().value)?;
+                self.synth().set_waveform(waveform);
+                void()
+            }
+            "getWaveform" => Ok(Box::new(StringBox::new(self.synth().waveform().name()))),
+            "setEnvelope" => {
+                let envelope = Envelope {
+                    attack: num(0)? / 1000.0,
+                    decay: num(1)? / 1000.0,
+                    sustain: num(2)?,
+                    release: num(3)? / 1000.0,
+                };
+                if ![envelope.attack, envelope.decay, envelope.sustain, envelope.release].iter().all(|v| v.is_finite()) {
+                    return Err("setEnvelope() expects finite numbers".to_string());
+                }
+                self.synth().set_envelope(envelope);
+                void()
+            }
+            "setVolume" => {
+                self.set_volume(num(0)?);
+                void()
+            }
+            "getVolume" => Ok(Box::new(FloatBox::new(self.get_volume()))),
+            "getFrequencyData" | "getWaveformData" => {
+                let at = if args.is_empty() { 0.0 } else { num(0)? };
+                let synth = self.synth();
+                let data = if method == "getFrequencyData" { synth.frequency_data(at) } else { synth.waveform_data(at) };
+                Ok(Box::new(BufferBox::from_vec(data)))
+            }
+            "getSamples" => Ok(Box::new(BufferBox::from_vec(self.synth().pcm16_bytes()))),
+            "getDuration" => Ok(Box::new(FloatBox::new(self.synth().duration_ms()))),
+            "getSampleRate" => Ok(Box::new(IntegerBox::new(SAMPLE_RATE as i64))),
+            "render" => Ok(Box::new(match self.render(&args[0].to_string_box().value) {
+                Ok(()) => NyashResultBox::new_ok(Box::new(BoolBox::new(true))),
+                Err(e) => NyashResultBox::new_err(Box::new(StringBox::new(e))),
+            })),
+            "clear" => {
+                self.synth().clear();
+                void()
+            }
+            "isPlaying" => Ok(Box::new(BoolBox::new(self.is_playing()))),
+            _ => Ok(Box::new(self.to_string_box())),
+        }
     }
 
     /// オーディオコンテキストの状態を確認
@@ -256,6 +395,26 @@ impl AudioBox {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_waveform(name: &str) -> Result<Waveform, String> {
+    Waveform::parse(name).ok_or_else(|| format!("Unknown waveform '{}' (sine, square, saw, triangle, noise)", name))
+}
+
+/// 数値引数（IntegerBox / FloatBox / 数値文字列）
+#[cfg(not(target_arch = "wasm32"))]
+fn number(method: &str, args: &[Box<dyn NyashBox>], index: usize) -> Result<f64, String> {
+    let value = &args[index];
+    if let Some(i) = value.as_any().downcast_ref::<IntegerBox>() {
+        return Ok(i.value as f64);
+    }
+    if let Some(f) = value.as_any().downcast_ref::<FloatBox>() {
+        return Ok(f.value);
+    }
+    let text = value.to_string_box().value;
+    text.trim().parse::<f64>()
+        .map_err(|_| format!("{}() argument {} must be a number, got '{}'", method, index + 1, text))
+}
+
 impl BoxCore for AudioBox {
     fn box_id(&self) -> u64 {
         self.base.id
@@ -266,7 +425,7 @@ impl BoxCore for AudioBox {
     }
     
     fn fmt_box(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "AudioBox(volume={:.2}, playing={})", self.volume, self.is_playing)
+        write!(f, "AudioBox(volume={:.2}, playing={})", self.get_volume(), self.is_playing)
     }
     
     fn as_any(&self) -> &dyn Any {
@@ -279,17 +438,19 @@ impl BoxCore for AudioBox {
 }
 
 impl NyashBox for AudioBox {
+    fn is_identity(&self) -> bool { true }
+
     fn clone_box(&self) -> Box<dyn NyashBox> {
         Box::new(self.clone())
     }
     
-    /// 仮実装: clone_boxと同じ（後で修正）
+    /// 状態共有: 同じ合成タイムラインを指す
     fn share_box(&self) -> Box<dyn NyashBox> {
         self.clone_box()
     }
 
     fn to_string_box(&self) -> StringBox {
-        StringBox::new(format!("AudioBox(volume={:.2}, playing={})", self.volume, self.is_playing))
+        StringBox::new(format!("AudioBox(volume={:.2}, playing={})", self.get_volume(), self.is_playing))
     }
 
     fn type_name(&self) -> &'static str {
@@ -52,6 +52,11 @@ impl BufferBox {
         self.data.read().unwrap().len()
     }
     
+    /// Rust向けヘルパー: 内容のコピーを取得
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.data.read().unwrap().clone()
+    }
+
     pub fn from_vec(data: Vec<u8>) -> Self {
         BufferBox { 
             data: Arc::new(RwLock::new(data)),  // Arc::new追加
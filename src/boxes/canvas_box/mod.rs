@@ -0,0 +1,393 @@
+/*!
+ * CanvasBox - ネイティブ用ヘッドレスCanvas
+ *
+ * ## 📝 概要
+ * ブラウザ無しで動く Canvas 2D。ソフトウェアラスタライザで RGBA バッファに描画し、
+ * PNG として書き出せる。WebCanvasBox と同じメソッド名なので、ゲームや可視化のデモを
+ * ネイティブで描画して、出力 PNG を比較するテストが書ける。
+ *
+ * ID 付きで作った Canvas は登録され、`env.canvas` の外部呼び出し
+ * （`canvas.fillRect("id", ...)`）もネイティブではこの Canvas に描かれる。
+ *
+ * ## 🛠️ 利用可能メソッド
+ *
+ * ### 🎨 図形
+ * - `fillRect(x, y, w, h, [color])` / `strokeRect(x, y, w, h, [color], [lineWidth])`
+ * - `fillCircle(x, y, r, [color])` / `strokeCircle(x, y, r, [color], [lineWidth])`
+ * - `drawLine(x1, y1, x2, y2, [color], [lineWidth])`
+ * - `clear()` / `clearRect(x, y, w, h)`
+ *
+ * ### ✏️ パス
+ * - `beginPath()`, `moveTo(x, y)`, `lineTo(x, y)`, `rect(x, y, w, h)`
+ * - `arc(x, y, r, start, end, [counterclockwise])`, `closePath()`
+ * - `fill([color])`, `stroke([color], [lineWidth])`
+ *
+ * ### 🔤 文字・画像
+ * - `fillText(text, x, y, [font], [color])` - 内蔵 5×7 ビットマップフォント
+ * - `measureText(text, [font])` - 描画幅（px）
+ * - `drawImage(canvas, x, y, [w, h])` - 別の CanvasBox を合成
+ *
+ * ### ⚙️ 状態
+ * - `setFillStyle(color)`, `setStrokeStyle(color)`, `setLineWidth(w)`
+ * - `save()`, `restore()`, `translate(x, y)`, `scale(x, y)`, `rotate(rad)`, `resetTransform()`
+ *
+ * ### 📤 出力
+ * - `saveTo(path)` - PNG 保存（ResultBox）
+ * - `getDataURL()` - PNG の Data URL
+ * - `getPixel(x, y)` - "#rrggbb"（不透明でなければ "#rrggbbaa"）
+ * - `getWidth()`, `getHeight()`, `resize(w, h)`
+ *
+ * 色は "#rgb" / "#rrggbb" / "#rrggbbaa" / "rgb(...)" / "rgba(...)" / 基本色名 / "transparent"。
+ * アンチエイリアスは無く、同じ描画からは常に同じ PNG が得られる。
+ *
+ * ## 💡 使用例
+ * ```nyash
+ * local canvas = new CanvasBox("game", 320, 240)
+ * canvas.fillRect(0, 0, 320, 240, "black")
+ * canvas.fillCircle(160, 120, 30, "red")
+ * canvas.fillText("Score: 10", 8, 16, "16px monospace", "white")
+ * canvas.saveTo("frame.png")
+ * ```
+ */
+
+mod font;
+mod raster;
+
+pub use raster::{Rgba, Surface};
+
+use crate::box_trait::{NyashBox, StringBox, BoolBox, IntegerBox, VoidBox, BoxCore, BoxBase};
+use crate::boxes::base64;
+use crate::boxes::math_box::FloatBox;
+use crate::boxes::png_writer;
+use crate::boxes::result::NyashResultBox;
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+/// `env.canvas` で未作成の ID に描いたときの大きさ
+pub const DEFAULT_WIDTH: u32 = 640;
+pub const DEFAULT_HEIGHT: u32 = 480;
+
+/// ID 付き Canvas の登録表（`env.canvas` から引く）
+fn registry() -> &'static Mutex<HashMap<String, CanvasBox>> {
+    static CANVASES: OnceLock<Mutex<HashMap<String, CanvasBox>>> = OnceLock::new();
+    CANVASES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// ヘッドレスCanvas Box
+#[derive(Debug, Clone)]
+pub struct CanvasBox {
+    base: BoxBase,
+    canvas_id: Option<String>,
+    surface: Arc<Mutex<Surface>>,
+}
+
+impl CanvasBox {
+    /// 登録しない Canvas
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            base: BoxBase::new(),
+            canvas_id: None,
+            surface: Arc::new(Mutex::new(Surface::new(width, height))),
+        }
+    }
+
+    /// ID 付きで作り、同じ ID の既存 Canvas を置き換えて登録する
+    pub fn with_id(canvas_id: &str, width: u32, height: u32) -> Self {
+        let canvas = Self { canvas_id: Some(canvas_id.to_string()), ..Self::new(width, height) };
+        registry().lock().unwrap().insert(canvas_id.to_string(), canvas.clone());
+        canvas
+    }
+
+    /// 登録済みの Canvas（無ければ既定サイズで作る）
+    pub fn by_id(canvas_id: &str) -> Self {
+        if let Some(canvas) = registry().lock().unwrap().get(canvas_id) {
+            return canvas.clone();
+        }
+        Self::with_id(canvas_id, DEFAULT_WIDTH, DEFAULT_HEIGHT)
+    }
+
+    pub fn canvas_id(&self) -> Option<&str> {
+        self.canvas_id.as_deref()
+    }
+
+    /// 描画面を直接操作する
+    pub fn surface(&self) -> MutexGuard<'_, Surface> {
+        self.surface.lock().unwrap()
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        let surface = self.surface();
+        png_writer::encode_rgba(surface.width(), surface.height(), surface.pixels())
+    }
+
+    pub fn save_to(&self, path: &str) -> Result<(), String> {
+        std::fs::write(path, self.to_png()).map_err(|e| format!("cannot write {}: {}", path, e))
+    }
+
+    /// Nyash のメソッド呼び出し（インタープリタと `env.canvas` の共通入口）
+    pub fn invoke(&self, method: &str, args: &[Box<dyn NyashBox>]) -> Result<Box<dyn NyashBox>, String> {
+        let (min, max) = match method {
+            "clear" | "beginPath" | "closePath" | "save" | "restore" | "resetTransform"
+            | "getWidth" | "getHeight" | "getDataURL" | "toString" => (0, 0),
+            "setFillStyle" | "setStrokeStyle" | "setLineWidth" | "rotate" | "saveTo" => (1, 1),
+            "moveTo" | "lineTo" | "translate" | "scale" | "resize" | "getPixel" => (2, 2),
+            "clearRect" | "rect" => (4, 4),
+            "fillRect" => (4, 5),
+            "strokeRect" | "drawLine" => (4, 6),
+            "fillCircle" => (3, 4),
+            "strokeCircle" | "fillText" => (3, 5),
+            "measureText" => (1, 2),
+            "arc" => (5, 6),
+            "fill" => (0, 1),
+            "stroke" => (0, 2),
+            "drawImage" => (3, 5),
+            _ => return Err(format!("Unknown method '{}' for CanvasBox", method)),
+        };
+        if args.len() < min || args.len() > max || (method == "drawImage" && args.len() == 4) {
+            let expected = if min == max { min.to_string() } else { format!("{}-{}", min, max) };
+            return Err(format!("{}() expects {} arguments, got {}", method, expected, args.len()));
+        }
+        let num = |i: usize| number(method, args, i);
+        let color = |i: usize| args.get(i).map(|c| parse_rgba(&c.to_string_box().value)
+            .ok_or_else(|| format!("{}(): invalid color '{}'", method, c.to_string_box().value))).transpose();
+        let text = |i: usize| args[i].to_string_box().value;
+
+        if method == "drawImage" {
+            let Some(source) = args[0].as_any().downcast_ref::<CanvasBox>() else {
+                return Err(format!("drawImage() expects a CanvasBox, got {}", args[0].type_name()));
+            };
+            let source = source.surface().clone();
+            let (w, h) = if args.len() == 5 { (num(3)?, num(4)?) } else { (source.width() as f64, source.height() as f64) };
+            self.surface().draw_image(&source, num(1)?, num(2)?, w, h);
+            return Ok(Box::new(VoidBox::new()));
+        }
+
+        let mut s = self.surface();
+        let result: Box<dyn NyashBox> = match method {
+            "getWidth" => Box::new(IntegerBox::new(s.width() as i64)),
+            "getHeight" => Box::new(IntegerBox::new(s.height() as i64)),
+            "getPixel" => {
+                let (x, y) = (num(0)?, num(1)?);
+                let pixel = if x >= 0.0 && y >= 0.0 { s.pixel(x as u32, y as u32) } else { None };
+                let [r, g, b, a] = pixel.ok_or_else(|| format!("getPixel(): ({}, {}) is outside the canvas", x, y))?;
+                let hex = if a == 255 { format!("#{:02x}{:02x}{:02x}", r, g, b) } else { format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a) };
+                Box::new(StringBox::new(hex))
+            }
+            "getDataURL" => {
+                drop(s);
+                let url = format!("data:image/png;base64,{}", base64::encode(&self.to_png()));
+                return Ok(Box::new(StringBox::new(url)));
+            }
+            "saveTo" => {
+                drop(s);
+                return Ok(Box::new(match self.save_to(&text(0)) {
+                    Ok(()) => NyashResultBox::new_ok(Box::new(BoolBox::new(true))),
+                    Err(e) => NyashResultBox::new_err(Box::new(StringBox::new(e))),
+                }));
+            }
+            "measureText" => {
+                let px = if args.len() > 1 { font::font_px(&text(1)) } else { font::font_px("") };
+                Box::new(IntegerBox::new(font::measure(&text(0), px) as i64))
+            }
+            "toString" => {
+                drop(s);
+                return Ok(Box::new(self.to_string_box()));
+            }
+            _ => {
+                match method {
+                    "clear" => s.clear(),
+                    "resize" => s.resize(dimension(method, num(0)?)?, dimension(method, num(1)?)?),
+                    "setFillStyle" => s.set_fill(color(0)?.unwrap()),
+                    "setStrokeStyle" => s.set_stroke(color(0)?.unwrap()),
+                    "setLineWidth" => s.set_line_width(num(0)?),
+                    "save" => s.save(),
+                    "restore" => s.restore(),
+                    "translate" => s.translate(num(0)?, num(1)?),
+                    "scale" => s.scale(num(0)?, num(1)?),
+                    "rotate" => s.rotate(num(0)?),
+                    "resetTransform" => s.reset_transform(),
+                    "beginPath" => s.begin_path(),
+                    "moveTo" => s.move_to(num(0)?, num(1)?),
+                    "lineTo" => s.line_to(num(0)?, num(1)?),
+                    "closePath" => s.close_path(),
+                    "rect" => s.rect(num(0)?, num(1)?, num(2)?, num(3)?),
+                    "arc" => {
+                        let ccw = args.get(5).is_some_and(|v| v.to_string_box().value == "true");
+                        s.arc(num(0)?, num(1)?, num(2)?, num(3)?, num(4)?, ccw);
+                    }
+                    "fill" => {
+                        let fill = color(0)?.unwrap_or(s.fill_color());
+                        s.fill(fill);
+                    }
+                    "stroke" => {
+                        let stroke = color(0)?.unwrap_or(s.stroke_color());
+                        let width = if args.len() > 1 { num(1)? } else { s.line_width() };
+                        s.stroke(stroke, width);
+                    }
+                    "clearRect" => s.clear_rect(num(0)?, num(1)?, num(2)?, num(3)?),
+                    "fillRect" => {
+                        let fill = color(4)?.unwrap_or(s.fill_color());
+                        s.fill_rect(num(0)?, num(1)?, num(2)?, num(3)?, fill);
+                    }
+                    "strokeRect" => {
+                        let stroke = color(4)?.unwrap_or(s.stroke_color());
+                        let width = if args.len() > 5 { num(5)? } else { s.line_width() };
+                        s.stroke_rect(num(0)?, num(1)?, num(2)?, num(3)?, stroke, width);
+                    }
+                    "fillCircle" => {
+                        let fill = color(3)?.unwrap_or(s.fill_color());
+                        s.fill_circle(num(0)?, num(1)?, num(2)?, fill);
+                    }
+                    "strokeCircle" => {
+                        let stroke = color(3)?.unwrap_or(s.stroke_color());
+                        let width = if args.len() > 4 { num(4)? } else { s.line_width() };
+                        s.stroke_circle(num(0)?, num(1)?, num(2)?, stroke, width);
+                    }
+                    "drawLine" => {
+                        let stroke = color(4)?.unwrap_or(s.stroke_color());
+                        let width = if args.len() > 5 { num(5)? } else { s.line_width() };
+                        s.draw_line(num(0)?, num(1)?, num(2)?, num(3)?, stroke, width);
+                    }
+                    "fillText" => {
+                        let px = if args.len() > 3 { font::font_px(&text(3)) } else { font::font_px("") };
+                        let fill = color(4)?.unwrap_or(s.fill_color());
+                        s.fill_text(&text(0), num(1)?, num(2)?, px, fill);
+                    }
+                    _ => unreachable!("arity table and dispatch disagree on '{}'", method),
+                }
+                Box::new(VoidBox::new())
+            }
+        };
+        Ok(result)
+    }
+
+    /// `env.canvas` 外部呼び出し。第1引数が Canvas ID、残りは CanvasBox のメソッド引数
+    pub fn extern_call(method: &str, args: &[Box<dyn NyashBox>]) -> Result<Option<Box<dyn NyashBox>>, String> {
+        let Some(canvas_id) = args.first() else {
+            return Err(format!("env.canvas.{} expects a canvas id as the first argument", method));
+        };
+        let canvas = Self::by_id(&canvas_id.to_string_box().value);
+        let result = canvas.invoke(method, &args[1..])?;
+        Ok(if result.as_any().is::<VoidBox>() { None } else { Some(result) })
+    }
+
+    fn describe(&self) -> String {
+        let surface = self.surface();
+        match &self.canvas_id {
+            Some(id) => format!("CanvasBox({}, {}x{})", id, surface.width(), surface.height()),
+            None => format!("CanvasBox({}x{})", surface.width(), surface.height()),
+        }
+    }
+}
+
+/// 数値引数（IntegerBox / FloatBox / 数値文字列）
+fn number(method: &str, args: &[Box<dyn NyashBox>], index: usize) -> Result<f64, String> {
+    let value = &args[index];
+    if let Some(i) = value.as_any().downcast_ref::<IntegerBox>() {
+        return Ok(i.value as f64);
+    }
+    if let Some(f) = value.as_any().downcast_ref::<FloatBox>() {
+        return Ok(f.value);
+    }
+    let text = value.to_string_box().value;
+    text.trim().parse::<f64>()
+        .map_err(|_| format!("{}() argument {} must be a number, got '{}'", method, index + 1, text))
+}
+
+fn dimension(method: &str, value: f64) -> Result<u32, String> {
+    if value.is_finite() && (0.0..=16384.0).contains(&value) {
+        Ok(value as u32)
+    } else {
+        Err(format!("{}(): invalid canvas size {}", method, value))
+    }
+}
+
+/// Canvas の色指定を RGBA に
+pub fn parse_rgba(color: &str) -> Option<Rgba> {
+    let color = color.trim();
+    let lower = color.to_ascii_lowercase();
+    if lower == "transparent" {
+        return Some([0, 0, 0, 0]);
+    }
+    if let Some(inner) = lower.strip_prefix("rgba(").or_else(|| lower.strip_prefix("rgb(")).and_then(|s| s.strip_suffix(')')) {
+        let parts: Vec<&str> = inner.split(',').map(str::trim).collect();
+        if parts.len() != 3 && parts.len() != 4 {
+            return None;
+        }
+        let mut rgba = [0, 0, 0, 255];
+        for (slot, part) in rgba.iter_mut().zip(&parts[..3]) {
+            *slot = part.parse::<f64>().ok()?.round().clamp(0.0, 255.0) as u8;
+        }
+        if let Some(alpha) = parts.get(3) {
+            rgba[3] = (alpha.parse::<f64>().ok()?.clamp(0.0, 1.0) * 255.0).round() as u8;
+        }
+        return Some(rgba);
+    }
+    if let Some(hex) = color.strip_prefix('#') {
+        let digits: Vec<u8> = hex.chars().map(|c| c.to_digit(16).map(|d| d as u8)).collect::<Option<_>>()?;
+        return match digits.as_slice() {
+            [r, g, b, a] => Some([r * 17, g * 17, b * 17, a * 17]),
+            [r1, r2, g1, g2, b1, b2, a1, a2] => Some([r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2, a1 * 16 + a2]),
+            _ => png_writer::parse_color(color).map(|[r, g, b]| [r, g, b, 255]),
+        };
+    }
+    png_writer::parse_color(color).map(|[r, g, b]| [r, g, b, 255])
+}
+
+impl BoxCore for CanvasBox {
+    fn box_id(&self) -> u64 {
+        self.base.id
+    }
+
+    fn parent_type_id(&self) -> Option<std::any::TypeId> {
+        self.base.parent_type_id
+    }
+
+    fn fmt_box(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.describe())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl NyashBox for CanvasBox {
+    fn is_identity(&self) -> bool { true }
+
+    fn clone_box(&self) -> Box<dyn NyashBox> {
+        Box::new(self.clone())
+    }
+
+    /// 状態共有: 同じ描画面を指す
+    fn share_box(&self) -> Box<dyn NyashBox> {
+        self.clone_box()
+    }
+
+    fn to_string_box(&self) -> StringBox {
+        StringBox::new(self.describe())
+    }
+
+    fn type_name(&self) -> &'static str {
+        "CanvasBox"
+    }
+
+    fn equals(&self, other: &dyn NyashBox) -> BoolBox {
+        if let Some(other_canvas) = other.as_any().downcast_ref::<CanvasBox>() {
+            BoolBox::new(Arc::ptr_eq(&self.surface, &other_canvas.surface))
+        } else {
+            BoolBox::new(false)
+        }
+    }
+}
+
+impl std::fmt::Display for CanvasBox {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.fmt_box(f)
+    }
+}
@@ -0,0 +1,518 @@
+/*! 🖌️ ソフトウェアラスタライザ
+ *
+ * Canvas 2D のサブセット。図形はすべて多角形（パス）に直してから
+ * 非ゼロ回転数規則でスキャンライン塗りつぶしする。
+ * アンチエイリアスはかけず、画素中心で内外を判定するので出力は常に決定的。
+ */
+
+use super::font;
+
+/// RGBA（ストレートアルファ）
+pub type Rgba = [u8; 4];
+
+/// 2D アフィン変換 `[a, b, c, d, e, f]`（Canvas の setTransform と同じ並び）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform([f64; 6]);
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    pub fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        let [a, b, c, d, e, f] = self.0;
+        (a * x + c * y + e, b * x + d * y + f)
+    }
+
+    /// `self` の内側に `other` を掛ける（Canvas の transform() と同じ順序）
+    fn then(&self, other: [f64; 6]) -> Transform {
+        let [a, b, c, d, e, f] = self.0;
+        let [a2, b2, c2, d2, e2, f2] = other;
+        Transform([
+            a * a2 + c * b2,
+            b * a2 + d * b2,
+            a * c2 + c * d2,
+            b * c2 + d * d2,
+            a * e2 + c * f2 + e,
+            b * e2 + d * f2 + f,
+        ])
+    }
+
+    fn inverse(&self) -> Option<Transform> {
+        let [a, b, c, d, e, f] = self.0;
+        let det = a * d - b * c;
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        Some(Transform([
+            d / det,
+            -b / det,
+            -c / det,
+            a / det,
+            (c * f - d * e) / det,
+            (b * e - a * f) / det,
+        ]))
+    }
+
+    /// 線幅などの長さに掛ける平均倍率
+    fn scale_factor(&self) -> f64 {
+        let [a, b, c, d, _, _] = self.0;
+        (a * d - b * c).abs().sqrt()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct State {
+    transform: Transform,
+    fill: Rgba,
+    stroke: Rgba,
+    line_width: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+struct SubPath {
+    points: Vec<(f64, f64)>,
+    closed: bool,
+}
+
+/// 描画面と描画状態
+#[derive(Debug, Clone)]
+pub struct Surface {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    state: State,
+    saved: Vec<State>,
+    /// 現在のパス（デバイス座標）
+    path: Vec<SubPath>,
+}
+
+const MITER_LIMIT: f64 = 10.0;
+
+impl Surface {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+            state: State { transform: Transform::IDENTITY, fill: [0, 0, 0, 255], stroke: [0, 0, 0, 255], line_width: 1.0 },
+            saved: Vec::new(),
+            path: Vec::new(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// RGBA 画素列（行優先）
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Rgba> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        Some([self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]])
+    }
+
+    /// サイズ変更（HTML Canvas と同じく内容と状態はリセット）
+    pub fn resize(&mut self, width: u32, height: u32) {
+        *self = Surface::new(width, height);
+    }
+
+    /// 全面を透明にする
+    pub fn clear(&mut self) {
+        self.pixels.fill(0);
+    }
+
+    // ===== 状態 =====
+
+    pub fn set_fill(&mut self, color: Rgba) {
+        self.state.fill = color;
+    }
+
+    pub fn set_stroke(&mut self, color: Rgba) {
+        self.state.stroke = color;
+    }
+
+    pub fn fill_color(&self) -> Rgba {
+        self.state.fill
+    }
+
+    pub fn stroke_color(&self) -> Rgba {
+        self.state.stroke
+    }
+
+    pub fn set_line_width(&mut self, width: f64) {
+        if width.is_finite() && width > 0.0 {
+            self.state.line_width = width;
+        }
+    }
+
+    pub fn line_width(&self) -> f64 {
+        self.state.line_width
+    }
+
+    pub fn save(&mut self) {
+        self.saved.push(self.state.clone());
+    }
+
+    pub fn restore(&mut self) {
+        if let Some(state) = self.saved.pop() {
+            self.state = state;
+        }
+    }
+
+    pub fn translate(&mut self, x: f64, y: f64) {
+        self.state.transform = self.state.transform.then([1.0, 0.0, 0.0, 1.0, x, y]);
+    }
+
+    pub fn scale(&mut self, x: f64, y: f64) {
+        self.state.transform = self.state.transform.then([x, 0.0, 0.0, y, 0.0, 0.0]);
+    }
+
+    pub fn rotate(&mut self, angle: f64) {
+        let (sin, cos) = angle.sin_cos();
+        self.state.transform = self.state.transform.then([cos, sin, -sin, cos, 0.0, 0.0]);
+    }
+
+    pub fn reset_transform(&mut self) {
+        self.state.transform = Transform::IDENTITY;
+    }
+
+    // ===== パス =====
+
+    pub fn begin_path(&mut self) {
+        self.path.clear();
+    }
+
+    pub fn move_to(&mut self, x: f64, y: f64) {
+        let point = self.state.transform.apply(x, y);
+        self.path.push(SubPath { points: vec![point], closed: false });
+    }
+
+    pub fn line_to(&mut self, x: f64, y: f64) {
+        let point = self.state.transform.apply(x, y);
+        match self.path.last_mut() {
+            Some(sub) if !sub.closed => sub.points.push(point),
+            _ => self.path.push(SubPath { points: vec![point], closed: false }),
+        }
+    }
+
+    pub fn close_path(&mut self) {
+        if let Some(sub) = self.path.last_mut() {
+            if !sub.closed && !sub.points.is_empty() {
+                sub.closed = true;
+                // 閉じた後の描画は始点から続ける
+                let start = sub.points[0];
+                self.path.push(SubPath { points: vec![start], closed: false });
+            }
+        }
+    }
+
+    pub fn rect(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        self.move_to(x, y);
+        self.line_to(x + w, y);
+        self.line_to(x + w, y + h);
+        self.line_to(x, y + h);
+        self.close_path();
+    }
+
+    /// 円弧（ラジアン。Canvas と同じく前の点から弧の始点まで直線でつなぐ）
+    pub fn arc(&mut self, cx: f64, cy: f64, radius: f64, start: f64, end: f64, counterclockwise: bool) {
+        let radius = radius.abs();
+        let tau = std::f64::consts::TAU;
+        let sweep = end - start;
+        let sweep = match counterclockwise {
+            false if sweep >= tau => tau,
+            false => sweep.rem_euclid(tau),
+            true if sweep <= -tau => -tau,
+            true => -(-sweep).rem_euclid(tau),
+        };
+        // デバイス上の半径に応じた分割数
+        let device_radius = radius * self.state.transform.scale_factor();
+        let steps = ((sweep.abs() * device_radius.max(1.0).sqrt() * 4.0).ceil() as usize).clamp(4, 1024);
+        for i in 0..=steps {
+            let angle = start + sweep * i as f64 / steps as f64;
+            let (x, y) = (cx + radius * angle.cos(), cy + radius * angle.sin());
+            if i == 0 && self.path.last().is_none_or(|sub| sub.closed) {
+                self.move_to(x, y);
+            } else {
+                self.line_to(x, y);
+            }
+        }
+    }
+
+    /// 現在のパスを塗りつぶす（非ゼロ規則）
+    pub fn fill(&mut self, color: Rgba) {
+        let polygons: Vec<Vec<(f64, f64)>> = self.path.iter()
+            .filter(|sub| sub.points.len() >= 3)
+            .map(|sub| sub.points.clone())
+            .collect();
+        self.fill_polygons(&polygons, color);
+    }
+
+    /// 現在のパスの輪郭を描く（バットキャップ・マイター結合）
+    pub fn stroke(&mut self, color: Rgba, line_width: f64) {
+        let half = line_width * self.state.transform.scale_factor() / 2.0;
+        if half <= 0.0 {
+            return;
+        }
+        let mut polygons = Vec::new();
+        for sub in &self.path {
+            stroke_polygons(&sub.points, sub.closed, half, &mut polygons);
+        }
+        self.fill_polygons(&polygons, color);
+    }
+
+    // ===== 図形ヘルパ（現在のパスは壊さない） =====
+
+    fn with_temp_path(&mut self, build: impl FnOnce(&mut Surface), draw: impl FnOnce(&mut Surface)) {
+        let saved = std::mem::take(&mut self.path);
+        build(self);
+        draw(self);
+        self.path = saved;
+    }
+
+    pub fn fill_rect(&mut self, x: f64, y: f64, w: f64, h: f64, color: Rgba) {
+        self.with_temp_path(|s| s.rect(x, y, w, h), |s| s.fill(color));
+    }
+
+    pub fn stroke_rect(&mut self, x: f64, y: f64, w: f64, h: f64, color: Rgba, line_width: f64) {
+        self.with_temp_path(|s| s.rect(x, y, w, h), |s| s.stroke(color, line_width));
+    }
+
+    pub fn clear_rect(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        let mut polygons = Vec::new();
+        self.with_temp_path(|s| s.rect(x, y, w, h), |s| polygons = s.path.iter().map(|p| p.points.clone()).collect());
+        let mut spans = Vec::new();
+        self.scan(&polygons, |px, py| spans.push((px, py)));
+        for (px, py) in spans {
+            let i = (py * self.width as usize + px) * 4;
+            self.pixels[i..i + 4].fill(0);
+        }
+    }
+
+    pub fn fill_circle(&mut self, cx: f64, cy: f64, radius: f64, color: Rgba) {
+        self.with_temp_path(|s| s.arc(cx, cy, radius, 0.0, std::f64::consts::TAU, false), |s| s.fill(color));
+    }
+
+    pub fn stroke_circle(&mut self, cx: f64, cy: f64, radius: f64, color: Rgba, line_width: f64) {
+        self.with_temp_path(
+            |s| {
+                s.arc(cx, cy, radius, 0.0, std::f64::consts::TAU, false);
+                s.close_path();
+            },
+            |s| s.stroke(color, line_width),
+        );
+    }
+
+    pub fn draw_line(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, color: Rgba, line_width: f64) {
+        self.with_temp_path(
+            |s| {
+                s.move_to(x1, y1);
+                s.line_to(x2, y2);
+            },
+            |s| s.stroke(color, line_width),
+        );
+    }
+
+    /// 内蔵ビットマップフォントで文字列を描く。`(x, y)` は左端のベースライン
+    pub fn fill_text(&mut self, text: &str, x: f64, y: f64, px: f64, color: Rgba) {
+        let scale = font::scale_for(px);
+        self.with_temp_path(
+            |s| {
+                let top = y - font::ASCENT as f64 * scale;
+                for (i, ch) in text.chars().enumerate() {
+                    let left = x + (i * font::ADVANCE) as f64 * scale;
+                    for (col, bits) in font::glyph(ch).iter().enumerate() {
+                        for row in 0..font::HEIGHT {
+                            if bits & (1 << row) != 0 {
+                                s.rect(left + col as f64 * scale, top + row as f64 * scale, scale, scale);
+                            }
+                        }
+                    }
+                }
+            },
+            |s| s.fill(color),
+        );
+    }
+
+    /// 別の描画面を `(x, y)` から `w × h` に最近傍で拡大縮小して合成する
+    pub fn draw_image(&mut self, source: &Surface, x: f64, y: f64, w: f64, h: f64) {
+        if w == 0.0 || h == 0.0 || source.width == 0 || source.height == 0 {
+            return;
+        }
+        let Some(inverse) = self.state.transform.inverse() else { return };
+        let transform = self.state.transform;
+        let corners = [(x, y), (x + w, y), (x + w, y + h), (x, y + h)].map(|(cx, cy)| transform.apply(cx, cy));
+        let mut targets = Vec::new();
+        self.scan(&[corners.to_vec()], |px, py| targets.push((px, py)));
+        for (px, py) in targets {
+            let (ux, uy) = inverse.apply(px as f64 + 0.5, py as f64 + 0.5);
+            let sx = ((ux - x) / w * source.width as f64).floor();
+            let sy = ((uy - y) / h * source.height as f64).floor();
+            if sx < 0.0 || sy < 0.0 {
+                continue;
+            }
+            if let Some(color) = source.pixel(sx as u32, sy as u32) {
+                self.blend(px, py, color);
+            }
+        }
+    }
+
+    // ===== ラスタライズ =====
+
+    fn fill_polygons(&mut self, polygons: &[Vec<(f64, f64)>], color: Rgba) {
+        if color[3] == 0 {
+            return;
+        }
+        let mut covered = Vec::new();
+        self.scan(polygons, |px, py| covered.push((px, py)));
+        for (px, py) in covered {
+            self.blend(px, py, color);
+        }
+    }
+
+    /// 非ゼロ規則で内側の画素を列挙する。画素中心が (左, 右] に入る画素を塗る
+    fn scan(&self, polygons: &[Vec<(f64, f64)>], mut visit: impl FnMut(usize, usize)) {
+        let mut edges = Vec::new();
+        let (mut min_y, mut max_y) = (f64::INFINITY, f64::NEG_INFINITY);
+        for polygon in polygons {
+            for (i, &(x0, y0)) in polygon.iter().enumerate() {
+                let (x1, y1) = polygon[(i + 1) % polygon.len()];
+                if y0 == y1 || !(x0.is_finite() && y0.is_finite() && x1.is_finite() && y1.is_finite()) {
+                    continue;
+                }
+                min_y = min_y.min(y0.min(y1));
+                max_y = max_y.max(y0.max(y1));
+                edges.push((x0, y0, x1, y1));
+            }
+        }
+        if edges.is_empty() {
+            return;
+        }
+        let first_row = ((min_y - 0.5).floor() + 1.0).max(0.0) as usize;
+        let last_row = ((max_y - 0.5).floor()).min(self.height as f64 - 1.0);
+        if last_row < 0.0 {
+            return;
+        }
+        let mut crossings: Vec<(f64, i32)> = Vec::new();
+        for py in first_row..=last_row as usize {
+            let sample = py as f64 + 0.5;
+            crossings.clear();
+            for &(x0, y0, x1, y1) in &edges {
+                let (lo, hi, dir) = if y0 < y1 { (y0, y1, 1) } else { (y1, y0, -1) };
+                if sample > lo && sample <= hi {
+                    crossings.push((x0 + (sample - y0) * (x1 - x0) / (y1 - y0), dir));
+                }
+            }
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].1;
+                if winding == 0 {
+                    continue;
+                }
+                let start = ((pair[0].0 - 0.5).floor() + 1.0).max(0.0);
+                let end = (pair[1].0 - 0.5).floor().min(self.width as f64 - 1.0);
+                if end < start {
+                    continue;
+                }
+                for px in start as usize..=end as usize {
+                    visit(px, py);
+                }
+            }
+        }
+    }
+
+    /// source-over 合成
+    fn blend(&mut self, px: usize, py: usize, color: Rgba) {
+        let i = (py * self.width as usize + px) * 4;
+        let dst = &mut self.pixels[i..i + 4];
+        if color[3] == 255 || dst[3] == 0 {
+            dst.copy_from_slice(&color);
+            return;
+        }
+        let sa = color[3] as f64 / 255.0;
+        let da = dst[3] as f64 / 255.0;
+        let out_a = sa + da * (1.0 - sa);
+        for c in 0..3 {
+            let value = (color[c] as f64 * sa + dst[c] as f64 * da * (1.0 - sa)) / out_a;
+            dst[c] = value.round() as u8;
+        }
+        dst[3] = (out_a * 255.0).round() as u8;
+    }
+}
+
+/// 折れ線を太さ `2 * half` の多角形群にする。向きを揃えて非ゼロ規則で重なりが消えないようにする
+fn stroke_polygons(points: &[(f64, f64)], closed: bool, half: f64, out: &mut Vec<Vec<(f64, f64)>>) {
+    let distinct = |p: (f64, f64), q: (f64, f64)| (q.0 - p.0).hypot(q.1 - p.1) > 1e-6;
+    let mut pts: Vec<(f64, f64)> = Vec::with_capacity(points.len());
+    for &p in points {
+        if pts.last().is_none_or(|&q| distinct(p, q)) {
+            pts.push(p);
+        }
+    }
+    // 閉路の終点が始点と（誤差の範囲で）重なるなら落とす
+    if closed && pts.len() > 2 && !distinct(pts[0], pts[pts.len() - 1]) {
+        pts.pop();
+    }
+    if pts.len() < 2 {
+        return;
+    }
+    let segment_count = if closed { pts.len() } else { pts.len() - 1 };
+    let direction = |i: usize| {
+        let (a, b) = (pts[i % pts.len()], pts[(i + 1) % pts.len()]);
+        let len = (b.0 - a.0).hypot(b.1 - a.1);
+        ((b.0 - a.0) / len, (b.1 - a.1) / len)
+    };
+    for i in 0..segment_count {
+        let (a, b) = (pts[i], pts[(i + 1) % pts.len()]);
+        let (dx, dy) = direction(i);
+        let (nx, ny) = (-dy * half, dx * half);
+        push_oriented(out, vec![(a.0 + nx, a.1 + ny), (b.0 + nx, b.1 + ny), (b.0 - nx, b.1 - ny), (a.0 - nx, a.1 - ny)]);
+    }
+    // 結合部（閉路なら全頂点、開いた線なら内側の頂点のみ）
+    let joints: Vec<usize> = if closed { (0..pts.len()).collect() } else { (1..pts.len() - 1).collect() };
+    for j in joints {
+        let d0 = direction((j + pts.len() - 1) % pts.len());
+        let d1 = direction(j);
+        let cross = d0.0 * d1.1 - d0.1 * d1.0;
+        if cross.abs() < 1e-9 {
+            continue;
+        }
+        let p = pts[j];
+        let side = -cross.signum();
+        let n0 = (-d0.1 * side, d0.0 * side);
+        let n1 = (-d1.1 * side, d1.0 * side);
+        let a = (p.0 + n0.0 * half, p.1 + n0.1 * half);
+        let b = (p.0 + n1.0 * half, p.1 + n1.1 * half);
+        let sum = (n0.0 + n1.0, n0.1 + n1.1);
+        let sum_sq = sum.0 * sum.0 + sum.1 * sum.1;
+        if sum_sq > 1e-12 && 2.0 / sum_sq.sqrt() <= MITER_LIMIT {
+            let miter = (p.0 + sum.0 * half * 2.0 / sum_sq, p.1 + sum.1 * half * 2.0 / sum_sq);
+            push_oriented(out, vec![p, a, miter, b]);
+        } else {
+            push_oriented(out, vec![p, a, b]);
+        }
+    }
+}
+
+fn push_oriented(out: &mut Vec<Vec<(f64, f64)>>, mut polygon: Vec<(f64, f64)>) {
+    let area: f64 = (0..polygon.len())
+        .map(|i| {
+            let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum();
+    if area < 0.0 {
+        polygon.reverse();
+    }
+    out.push(polygon);
+}
@@ -4,68 +4,52 @@
 
 use crate::box_trait::{NyashBox, StringBox, BoolBox, BoxCore, BoxBase};
 use std::any::Any;
-use std::sync::RwLock;
+use std::sync::{Arc, Condvar, Mutex};
 
-#[derive(Debug)]
+/// クローン間で共有される結果スロット（別スレッドから `set_result` される）
+type FutureState = Arc<(Mutex<Option<Box<dyn NyashBox>>>, Condvar)>;
+
+#[derive(Debug, Clone)]
 pub struct NyashFutureBox {
-    pub result: RwLock<Option<Box<dyn NyashBox>>>,
-    pub is_ready: RwLock<bool>,
+    state: FutureState,
     base: BoxBase,
 }
 
-impl Clone for NyashFutureBox {
-    fn clone(&self) -> Self {
-        let result_guard = self.result.read().unwrap();
-        let result_val = match result_guard.as_ref() {
-            Some(box_value) => Some(box_value.clone_box()),
-            None => None,
-        };
-        let is_ready_val = *self.is_ready.read().unwrap();
-        
-        Self {
-            result: RwLock::new(result_val),
-            is_ready: RwLock::new(is_ready_val),
-            base: BoxBase::new(), // Create a new base with unique ID for the clone
-        }
-    }
-}
-
 impl NyashFutureBox {
     pub fn new() -> Self {
         Self {
-            result: RwLock::new(None),
-            is_ready: RwLock::new(false),
+            state: Arc::new((Mutex::new(None), Condvar::new())),
             base: BoxBase::new(),
         }
     }
     
     /// Set the result of the future
     pub fn set_result(&self, value: Box<dyn NyashBox>) {
-        let mut result = self.result.write().unwrap();
-        *result = Some(value);
-        let mut ready = self.is_ready.write().unwrap();
-        *ready = true;
+        let (result, ready) = &*self.state;
+        *result.lock().unwrap() = Some(value);
+        ready.notify_all();
     }
     
     /// Get the result (blocks until ready)
     pub fn get(&self) -> Box<dyn NyashBox> {
-        // Simple busy wait (could be improved with condvar)
-        loop {
-            let ready = self.is_ready.read().unwrap();
-            if *ready {
-                break;
-            }
-            drop(ready);
-            std::thread::yield_now();
+        let (result, ready) = &*self.state;
+        let mut result = result.lock().unwrap();
+        while result.is_none() {
+            result = ready.wait(result).unwrap();
         }
-        
-        let result = self.result.read().unwrap();
         result.as_ref().unwrap().clone_box()
     }
     
     /// Check if the future is ready
     pub fn ready(&self) -> bool {
-        *self.is_ready.read().unwrap()
+        self.state.0.lock().unwrap().is_some()
+    }
+
+    fn describe(&self) -> String {
+        match self.state.0.lock().unwrap().as_ref() {
+            Some(value) => format!("Future(ready: {})", value.to_string_box().value),
+            None => "Future(pending)".to_string(),
+        }
     }
 }
 
@@ -74,23 +58,13 @@ impl NyashBox for NyashFutureBox {
         Box::new(self.clone())
     }
     
-    /// 仮実装: clone_boxと同じ（後で修正）
+    /// クローンは同じ結果スロットを共有する
     fn share_box(&self) -> Box<dyn NyashBox> {
         self.clone_box()
     }
 
     fn to_string_box(&self) -> StringBox {
-        let ready = *self.is_ready.read().unwrap();
-        if ready {
-            let result = self.result.read().unwrap();
-            if let Some(value) = result.as_ref() {
-                StringBox::new(format!("Future(ready: {})", value.to_string_box().value))
-            } else {
-                StringBox::new("Future(ready: void)".to_string())
-            }
-        } else {
-            StringBox::new("Future(pending)".to_string())
-        }
+        StringBox::new(self.describe())
     }
 
 
@@ -101,7 +75,7 @@ impl NyashBox for NyashFutureBox {
 
     fn equals(&self, other: &dyn NyashBox) -> BoolBox {
         if let Some(other_future) = other.as_any().downcast_ref::<NyashFutureBox>() {
-            BoolBox::new(self.base.id == other_future.base.id)
+            BoolBox::new(Arc::ptr_eq(&self.state, &other_future.state))
         } else {
             BoolBox::new(false)
         }
@@ -118,17 +92,7 @@ impl BoxCore for NyashFutureBox {
     }
 
     fn fmt_box(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let ready = *self.is_ready.read().unwrap();
-        if ready {
-            let result = self.result.read().unwrap();
-            if let Some(value) = result.as_ref() {
-                write!(f, "Future(ready: {})", value.to_string_box().value)
-            } else {
-                write!(f, "Future(ready: void)")
-            }
-        } else {
-            write!(f, "Future(pending)")
-        }
+        write!(f, "{}", self.describe())
     }
     
     fn as_any(&self) -> &dyn Any {
@@ -1,48 +1,429 @@
 //! HttpClientBox 🌐 - HTTP通信
 // Nyashの箱システムによるHTTP通信を提供します。
 // 参考: 既存Boxの設計思想
-// 
-// NOTE: HTTPサポートは現在開発中です。
-// reqwestクレートの依存関係のため、一時的に無効化されています。
+//
+// std::net の TCP 上で HTTP/1.1 を話す最小クライアント（http:// のみ、TLS なし）。
+// 各メソッドは ResultBox を返す: Ok(HTTPResponseBox) / Err(StringBox)。
+//
+// - `get(url)` / `delete(url)` / `post(url, body)` / `put(url, body)`
+// - `request(method, url, options)` - options は MapBox:
+//   `headers`(MapBox) / `body` / `timeout`(ms) / `followRedirects`(bool) / `maxRedirects`
+// - `setTimeout(ms)` - 既定のタイムアウト（接続・送受信）
+//
+// リダイレクト（301/302/303/307/308）は既定で最大10回まで追従し、
+// `Transfer-Encoding: chunked` のボディはデコードして返す。
+// 別オリジンへのリダイレクトでは Authorization / Cookie / Host ヘッダーを送らない。
+// メソッド・パス・ヘッダーに CR/LF などの制御文字があれば送信せず Err を返す。
 
 use crate::box_trait::{NyashBox, StringBox, BoolBox, BoxCore, BoxBase};
+use crate::boxes::http_message_box::HTTPResponseBox;
+use crate::boxes::result::NyashResultBox;
+use crate::boxes::MapBox;
 use std::any::Any;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// 既定のタイムアウト（ミリ秒）
+const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+/// 既定のリダイレクト追従回数
+const DEFAULT_MAX_REDIRECTS: usize = 10;
 
 #[derive(Debug, Clone)]
 pub struct HttpClientBox {
     base: BoxBase,
+    timeout_ms: Arc<RwLock<u64>>,
+}
+
+/// 1回分のリクエスト設定
+struct RequestSpec {
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: Option<String>,
+    timeout: Duration,
+    max_redirects: usize,
+}
+
+/// `http://host[:port]/path?query` の分解結果
+pub(crate) struct ParsedUrl {
+    pub(crate) host: String,
+    pub(crate) port: u16,
+    /// パス＋クエリ（リクエストラインにそのまま書く）
+    pub(crate) target: String,
+}
+
+impl ParsedUrl {
+    fn parse(url: &str) -> Result<Self, String> {
+        Self::parse_with_scheme(url, "http")
+    }
+
+    /// `scheme://host[:port]/target` 形式（既定ポート 80）。WebSocketBox の `ws://` でも使う
+    pub(crate) fn parse_with_scheme(url: &str, expected: &str) -> Result<Self, String> {
+        let rest = match url.split_once("://") {
+            Some((scheme, rest)) if scheme.eq_ignore_ascii_case(expected) => rest,
+            Some((scheme, _)) => return Err(format!("unsupported URL scheme '{}' (only {}:// is supported)", scheme, expected)),
+            None => return Err(format!("invalid URL '{}'", url)),
+        };
+        let rest = rest.split('#').next().unwrap_or("");
+        let (authority, target) = match rest.find(['/', '?']) {
+            Some(i) if rest[i..].starts_with('?') => (&rest[..i], format!("/{}", &rest[i..])),
+            Some(i) => (&rest[..i], rest[i..].to_string()),
+            None => (rest, "/".to_string()),
+        };
+        // userinfo is not supported; drop it rather than sending it as the host
+        let authority = authority.rsplit('@').next().unwrap_or("");
+        let (host, port) = if let Some(v6) = authority.strip_prefix('[') {
+            let (host, after) = v6.split_once(']').ok_or_else(|| format!("invalid URL '{}'", url))?;
+            (host.to_string(), after.strip_prefix(':'))
+        } else {
+            match authority.rsplit_once(':') {
+                Some((host, port)) => (host.to_string(), Some(port)),
+                None => (authority.to_string(), None),
+            }
+        };
+        if host.is_empty() {
+            return Err(format!("invalid URL '{}': missing host", url));
+        }
+        let port = match port {
+            Some(p) => p.parse::<u16>().map_err(|_| format!("invalid port in URL '{}'", url))?,
+            None => 80,
+        };
+        Ok(ParsedUrl { host, port, target })
+    }
+
+    /// Host ヘッダー値（既定ポートは省略）
+    pub(crate) fn host_header(&self) -> String {
+        let host = if self.host.contains(':') { format!("[{}]", self.host) } else { self.host.clone() };
+        if self.port == 80 { host } else { format!("{}:{}", host, self.port) }
+    }
+
+    /// 解決できたアドレスへ順に接続し、送受信タイムアウトを設定する
+    pub(crate) fn connect(&self, timeout: Duration) -> Result<TcpStream, String> {
+        let addrs: Vec<_> = (self.host.as_str(), self.port).to_socket_addrs()
+            .map_err(|e| format!("cannot resolve {}: {}", self.host, e))?
+            .collect();
+        let mut last_error = format!("no address for {}", self.host);
+        let mut stream = None;
+        for addr in addrs {
+            match TcpStream::connect_timeout(&addr, timeout) {
+                Ok(s) => { stream = Some(s); break; }
+                Err(e) => last_error = format!("connect to {} failed: {}", addr, e),
+            }
+        }
+        let stream = stream.ok_or(last_error)?;
+        stream.set_read_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+        stream.set_write_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+        Ok(stream)
+    }
+
+    /// スキーム・ホスト・ポートが同じか（リダイレクトで資格情報を引き継いでよいか）
+    fn same_origin(&self, other: &ParsedUrl) -> bool {
+        self.host.eq_ignore_ascii_case(&other.host) && self.port == other.port
+    }
+
+    /// Location ヘッダーを現在のURL基準で絶対URLにする
+    fn resolve(&self, location: &str) -> String {
+        if location.contains("://") {
+            return location.to_string();
+        }
+        let origin = format!("http://{}", self.host_header());
+        if let Some(rest) = location.strip_prefix("//") {
+            return format!("http://{}", rest);
+        }
+        if location.starts_with('/') {
+            return format!("{}{}", origin, location);
+        }
+        let path = self.target.split('?').next().unwrap_or("/");
+        let dir = &path[..path.rfind('/').map(|i| i + 1).unwrap_or(0)];
+        format!("{}{}{}", origin, if dir.is_empty() { "/" } else { dir }, location)
+    }
+}
+
+/// 受信したレスポンス
+struct RawResponse {
+    code: i32,
+    message: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl RawResponse {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
 }
 
 impl HttpClientBox {
     pub fn new() -> Self {
-        HttpClientBox { 
-            base: BoxBase::new() 
+        HttpClientBox {
+            base: BoxBase::new(),
+            timeout_ms: Arc::new(RwLock::new(DEFAULT_TIMEOUT_MS)),
+        }
+    }
+
+    /// 既定タイムアウト設定（ミリ秒）
+    pub fn set_timeout(&self, timeout_ms: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        match timeout_ms.to_string_box().value.parse::<u64>() {
+            Ok(ms) if ms > 0 => {
+                *self.timeout_ms.write().unwrap() = ms;
+                Box::new(BoolBox::new(true))
+            }
+            _ => Box::new(BoolBox::new(false)),
+        }
+    }
+
+    /// HTTP GETリクエスト
+    pub fn http_get(&self, url: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        self.send(self.spec("GET", url, None))
+    }
+
+    /// HTTP POSTリクエスト
+    pub fn post(&self, url: Box<dyn NyashBox>, body: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        self.send(self.spec("POST", url, Some(body.to_string_box().value)))
+    }
+
+    /// HTTP PUT リクエスト
+    pub fn put(&self, url: Box<dyn NyashBox>, body: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        self.send(self.spec("PUT", url, Some(body.to_string_box().value)))
+    }
+
+    /// HTTP DELETE リクエスト
+    pub fn delete(&self, url: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        self.send(self.spec("DELETE", url, None))
+    }
+
+    /// ヘッダー付きHTTPリクエスト（options: MapBox、それ以外は無視）
+    pub fn request(&self, method: Box<dyn NyashBox>, url: Box<dyn NyashBox>, options: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        let mut spec = self.spec(&method.to_string_box().value.to_uppercase(), url, None);
+        if let Some(map) = options.as_any().downcast_ref::<MapBox>() {
+            let data = map.get_data().read().unwrap();
+            if let Some(headers) = data.get("headers").and_then(|h| h.as_any().downcast_ref::<MapBox>()) {
+                let mut headers: Vec<(String, String)> = headers.get_data().read().unwrap().iter()
+                    .map(|(k, v)| (k.clone(), v.to_string_box().value))
+                    .collect();
+                headers.sort();
+                spec.headers = headers;
+            }
+            if let Some(body) = data.get("body") {
+                spec.body = Some(body.to_string_box().value);
+            }
+            if let Some(ms) = data.get("timeout").and_then(|t| t.to_string_box().value.parse::<u64>().ok()) {
+                spec.timeout = Duration::from_millis(ms.max(1));
+            }
+            if let Some(max) = data.get("maxRedirects").and_then(|m| m.to_string_box().value.parse::<usize>().ok()) {
+                spec.max_redirects = max;
+            }
+            if data.get("followRedirects").map(|f| f.to_string_box().value == "false").unwrap_or(false) {
+                spec.max_redirects = 0;
+            }
         }
+        self.send(spec)
     }
-    
-    /// HTTP GETリクエスト（スタブ）
-    pub fn http_get(&self, _url: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
-        Box::new(StringBox::new("HTTP support is currently disabled"))
+
+    fn spec(&self, method: &str, url: Box<dyn NyashBox>, body: Option<String>) -> RequestSpec {
+        RequestSpec {
+            method: method.to_string(),
+            url: url.to_string_box().value,
+            headers: Vec::new(),
+            body,
+            timeout: Duration::from_millis(*self.timeout_ms.read().unwrap()),
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+        }
     }
-    
-    /// HTTP POSTリクエスト（スタブ）
-    pub fn post(&self, _url: Box<dyn NyashBox>, _body: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
-        Box::new(StringBox::new("HTTP support is currently disabled"))
+
+    /// リダイレクトを追従しつつ送信し、ResultBox に包んで返す
+    fn send(&self, mut spec: RequestSpec) -> Box<dyn NyashBox> {
+        let mut redirects = 0;
+        loop {
+            let url = match ParsedUrl::parse(&spec.url) {
+                Ok(url) => url,
+                Err(e) => return Self::error(e),
+            };
+            let response = match Self::round_trip(&url, &spec) {
+                Ok(response) => response,
+                Err(e) => return Self::error(format!("{} {}: {}", spec.method, spec.url, e)),
+            };
+
+            let location = response.header("location").map(|l| l.to_string());
+            match (response.code, location) {
+                (301 | 302 | 303 | 307 | 308, Some(location)) if redirects < spec.max_redirects => {
+                    redirects += 1;
+                    // 303 (and 301/302 after POST, as browsers do) switch to a bodyless GET
+                    if response.code == 303 || (matches!(response.code, 301 | 302) && spec.method == "POST") {
+                        spec.method = "GET".to_string();
+                        spec.body = None;
+                    }
+                    spec.url = url.resolve(&location);
+                    // Credentials and the Host override are for the original server only
+                    if !ParsedUrl::parse(&spec.url).map(|next| url.same_origin(&next)).unwrap_or(false) {
+                        spec.headers.retain(|(name, _)| {
+                            !["authorization", "cookie", "host"].iter().any(|h| name.eq_ignore_ascii_case(h))
+                        });
+                    }
+                }
+                (301 | 302 | 303 | 307 | 308, Some(_)) if spec.max_redirects > 0 => {
+                    return Self::error(format!("{} {}: too many redirects (max {})", spec.method, spec.url, spec.max_redirects));
+                }
+                _ => {
+                    let result = HTTPResponseBox::from_parts(response.code, response.message, response.headers, response.body);
+                    return Box::new(NyashResultBox::new_ok(Box::new(result)));
+                }
+            }
+        }
     }
-    
-    /// HTTP PUT リクエスト（スタブ）
-    pub fn put(&self, _url: Box<dyn NyashBox>, _body: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
-        Box::new(StringBox::new("HTTP support is currently disabled"))
+
+    fn error(message: String) -> Box<dyn NyashBox> {
+        Box::new(NyashResultBox::new_err(Box::new(StringBox::new(message))))
     }
-    
-    /// HTTP DELETE リクエスト（スタブ）
-    pub fn delete(&self, _url: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
-        Box::new(StringBox::new("HTTP support is currently disabled"))
+
+    /// 1回の接続でリクエストを送ってレスポンスを読む（Connection: close）
+    fn round_trip(url: &ParsedUrl, spec: &RequestSpec) -> Result<RawResponse, String> {
+        Self::check_head(&spec.method, &url.target, &spec.headers)?;
+        let mut stream = url.connect(spec.timeout)?;
+
+        let mut head = format!("{} {} HTTP/1.1\r\n", spec.method, url.target);
+        let has = |name: &str| spec.headers.iter().any(|(n, _)| n.eq_ignore_ascii_case(name));
+        if !has("host") {
+            head.push_str(&format!("Host: {}\r\n", url.host_header()));
+        }
+        if !has("user-agent") {
+            head.push_str("User-Agent: nyash-http/1.0\r\n");
+        }
+        if !has("accept") {
+            head.push_str("Accept: */*\r\n");
+        }
+        for (name, value) in &spec.headers {
+            if name.eq_ignore_ascii_case("connection") || name.eq_ignore_ascii_case("content-length") {
+                continue;
+            }
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        if let Some(body) = &spec.body {
+            head.push_str(&format!("Content-Length: {}\r\n", body.len()));
+        }
+        head.push_str("Connection: close\r\n\r\n");
+        stream.write_all(head.as_bytes()).map_err(Self::io_error)?;
+        if let Some(body) = &spec.body {
+            stream.write_all(body.as_bytes()).map_err(Self::io_error)?;
+        }
+        stream.flush().map_err(Self::io_error)?;
+
+        Self::read_response(BufReader::new(stream), &spec.method)
     }
-    
-    /// ヘッダー付きHTTPリクエスト（スタブ）
-    pub fn request(&self, _method: Box<dyn NyashBox>, _url: Box<dyn NyashBox>, _options: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
-        Box::new(StringBox::new("HTTP support is currently disabled"))
+
+    /// リクエストヘッドに改行などを紛れ込ませない（ヘッダー注入・リクエスト分割の防止）
+    fn check_head(method: &str, target: &str, headers: &[(String, String)]) -> Result<(), String> {
+        let is_token = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c));
+        if !is_token(method) {
+            return Err(format!("invalid method {:?}", method));
+        }
+        if target.chars().any(|c| c.is_ascii_control() || c == ' ') {
+            return Err(format!("invalid request target {:?}", target));
+        }
+        for (name, value) in headers {
+            if !is_token(name) {
+                return Err(format!("invalid header name {:?}", name));
+            }
+            if value.chars().any(|c| c.is_ascii_control() && c != '\t') {
+                return Err(format!("invalid value for header {}", name));
+            }
+        }
+        Ok(())
+    }
+
+    fn io_error(e: std::io::Error) -> String {
+        match e.kind() {
+            std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut => "timed out".to_string(),
+            _ => e.to_string(),
+        }
+    }
+
+    fn read_response<R: BufRead>(mut reader: R, method: &str) -> Result<RawResponse, String> {
+        let mut status_line = String::new();
+        if reader.read_line(&mut status_line).map_err(Self::io_error)? == 0 {
+            return Err("connection closed before response".to_string());
+        }
+        let mut parts = status_line.trim_end().splitn(3, ' ');
+        let version = parts.next().unwrap_or("");
+        if !version.starts_with("HTTP/") {
+            return Err(format!("malformed status line '{}'", status_line.trim_end()));
+        }
+        let code = parts.next().and_then(|c| c.parse::<i32>().ok())
+            .ok_or_else(|| format!("malformed status line '{}'", status_line.trim_end()))?;
+        let message = parts.next().unwrap_or("").to_string();
+
+        let mut headers = Vec::new();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).map_err(Self::io_error)? == 0 {
+                return Err("connection closed in response headers".to_string());
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_string(), value.trim().to_string()));
+            }
+        }
+        let mut response = RawResponse { code, message, headers, body: String::new() };
+
+        // Responses that never carry a body
+        if method == "HEAD" || code == 204 || code == 304 || (100..200).contains(&code) {
+            return Ok(response);
+        }
+        let chunked = response.header("transfer-encoding")
+            .map(|te| te.to_ascii_lowercase().contains("chunked"))
+            .unwrap_or(false);
+        let body = if chunked {
+            Self::read_chunked(&mut reader)?
+        } else if let Some(len) = response.header("content-length") {
+            let len = len.parse::<usize>().map_err(|_| format!("invalid Content-Length '{}'", len))?;
+            let mut body = vec![0u8; len];
+            reader.read_exact(&mut body).map_err(|e| match e.kind() {
+                std::io::ErrorKind::UnexpectedEof => "connection closed before end of body".to_string(),
+                _ => Self::io_error(e),
+            })?;
+            body
+        } else {
+            let mut body = Vec::new();
+            reader.read_to_end(&mut body).map_err(Self::io_error)?;
+            body
+        };
+        response.body = String::from_utf8_lossy(&body).into_owned();
+        Ok(response)
+    }
+
+    /// `Transfer-Encoding: chunked` のデコード（トレーラーは読み捨て）
+    fn read_chunked<R: BufRead>(reader: &mut R) -> Result<Vec<u8>, String> {
+        let mut body = Vec::new();
+        loop {
+            let mut size_line = String::new();
+            if reader.read_line(&mut size_line).map_err(Self::io_error)? == 0 {
+                return Err("connection closed in chunked body".to_string());
+            }
+            let size_str = size_line.trim_end().split(';').next().unwrap_or("").trim();
+            let size = usize::from_str_radix(size_str, 16)
+                .map_err(|_| format!("invalid chunk size '{}'", size_str))?;
+            if size == 0 {
+                loop {
+                    let mut trailer = String::new();
+                    if reader.read_line(&mut trailer).map_err(Self::io_error)? == 0 || trailer.trim_end().is_empty() {
+                        return Ok(body);
+                    }
+                }
+            }
+            let start = body.len();
+            body.resize(start + size, 0);
+            reader.read_exact(&mut body[start..]).map_err(Self::io_error)?;
+            let mut crlf = [0u8; 2];
+            reader.read_exact(&mut crlf).map_err(Self::io_error)?;
+            if &crlf != b"\r\n" {
+                return Err("malformed chunk terminator".to_string());
+            }
+        }
     }
 }
 
@@ -50,7 +431,7 @@ impl NyashBox for HttpClientBox {
     fn clone_box(&self) -> Box<dyn NyashBox> {
         Box::new(self.clone())
     }
-    
+
     /// 仮実装: clone_boxと同じ（後で修正）
     fn share_box(&self) -> Box<dyn NyashBox> {
         self.clone_box()
@@ -79,7 +460,7 @@ impl BoxCore for HttpClientBox {
     fn box_id(&self) -> u64 {
         self.base.id
     }
-    
+
     fn parent_type_id(&self) -> Option<std::any::TypeId> {
         self.base.parent_type_id
     }
@@ -87,11 +468,11 @@ impl BoxCore for HttpClientBox {
     fn fmt_box(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "HttpClientBox(id: {})", self.base.id)
     }
-    
+
     fn as_any(&self) -> &dyn Any {
         self
     }
-    
+
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self
     }
@@ -101,4 +482,4 @@ impl std::fmt::Display for HttpClientBox {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.fmt_box(f)
     }
-}
\ No newline at end of file
+}
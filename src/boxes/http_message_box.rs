@@ -9,6 +9,8 @@
  * - `getMethod()` - HTTP メソッド取得 (GET, POST, etc.)
  * - `getPath()` - URL パス取得
  * - `getQueryString()` - クエリ文字列取得
+ * - `getQuery(name)` / `getQueryParams()` - デコード済みクエリパラメータ
+ * - `getParam(name)` / `getParams()` - ルートのパスパラメータ（`/users/:id` → `id`）
  * 
  * ### Headers
  * - `getHeader(name)` - 特定ヘッダー取得
@@ -29,6 +31,8 @@
  * ### Body & Output
  * - `setBody(content)` - レスポンスボディ設定
  * - `appendBody(content)` - ボディ追加
+ * - `getStatus()` / `getHeader(name)` / `getBody()` - 設定内容の取得
+ * - `getStatusMessage()` / `getAllHeaders()` - 理由句・全ヘッダー（MapBox）取得
  * - `toHttpString()` - HTTP形式文字列生成
  * 
  * ## 💡 使用例
@@ -52,6 +56,49 @@ use crate::box_trait::{NyashBox, StringBox, IntegerBox, BoolBox, BoxCore, BoxBas
 use crate::boxes::MapBox;
 use std::any::Any;
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// `%XX` と（クエリでは）`+` をデコード
+pub(crate) fn url_decode(s: &str, plus_as_space: bool) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                let hex = (bytes[i + 1] as char).to_digit(16).zip((bytes[i + 2] as char).to_digit(16));
+                match hex {
+                    Some((hi, lo)) => { out.push((hi * 16 + lo) as u8); i += 3; continue; }
+                    None => out.push(b'%'),
+                }
+            }
+            b'+' if plus_as_space => out.push(b' '),
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// `a=1&b=x%20y` → {a: "1", b: "x y"}（同名キーは後勝ち）
+pub(crate) fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((k, v)) => (url_decode(k, true), url_decode(v, true)),
+            None => (url_decode(pair, true), String::new()),
+        })
+        .collect()
+}
+
+fn string_map_box(map: &HashMap<String, String>) -> Box<dyn NyashBox> {
+    let map_box = MapBox::new();
+    for (k, v) in map {
+        map_box.set(Box::new(StringBox::new(k.clone())), Box::new(StringBox::new(v.clone())));
+    }
+    Box::new(map_box)
+}
 
 /// HTTP リクエストを解析・操作するBox
 #[derive(Debug, Clone)]
@@ -60,6 +107,8 @@ pub struct HTTPRequestBox {
     method: String,
     path: String,
     query_string: String,
+    query: HashMap<String, String>,
+    params: HashMap<String, String>,
     headers: HashMap<String, String>,
     body: String,
     http_version: String,
@@ -72,6 +121,8 @@ impl HTTPRequestBox {
             method: "GET".to_string(),
             path: "/".to_string(),
             query_string: "".to_string(),
+            query: HashMap::new(),
+            params: HashMap::new(),
             headers: HashMap::new(),
             body: "".to_string(),
             http_version: "HTTP/1.1".to_string(),
@@ -98,6 +149,7 @@ impl HTTPRequestBox {
             request.path = url_parts[0].to_string();
             if url_parts.len() > 1 {
                 request.query_string = url_parts[1].to_string();
+                request.query = parse_query(url_parts[1]);
             }
             
             request.http_version = request_line_parts[2].to_string();
@@ -141,6 +193,33 @@ impl HTTPRequestBox {
         Box::new(StringBox::new(self.query_string.clone()))
     }
     
+    /// クエリパラメータ取得（無ければ空文字列）
+    pub fn get_query(&self, name: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        let key = name.to_string_box().value;
+        Box::new(StringBox::new(self.query.get(&key).cloned().unwrap_or_default()))
+    }
+
+    /// 全クエリパラメータ取得（MapBox形式）
+    pub fn get_query_params(&self) -> Box<dyn NyashBox> {
+        string_map_box(&self.query)
+    }
+
+    /// パスパラメータ取得（`/users/:id` の `id` など、無ければ空文字列）
+    pub fn get_param(&self, name: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        let key = name.to_string_box().value;
+        Box::new(StringBox::new(self.params.get(&key).cloned().unwrap_or_default()))
+    }
+
+    /// 全パスパラメータ取得（MapBox形式）
+    pub fn get_params(&self) -> Box<dyn NyashBox> {
+        string_map_box(&self.params)
+    }
+
+    /// ルートマッチ結果のパスパラメータを設定（HTTPServerBox用）
+    pub(crate) fn set_params(&mut self, params: HashMap<String, String>) {
+        self.params = params;
+    }
+
     /// 特定ヘッダー取得
     pub fn get_header(&self, name: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
         let header_name = name.to_string_box().value.to_lowercase();
@@ -249,48 +328,106 @@ impl std::fmt::Display for HTTPRequestBox {
 }
 
 /// HTTP レスポンスを生成・操作するBox
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct HTTPResponseBox {
     base: BoxBase,
-    status_code: i32,
-    status_message: String,
-    headers: HashMap<String, String>,
-    body: String,
+    status_code: Arc<RwLock<i32>>,
+    status_message: Arc<RwLock<String>>,
+    headers: Arc<RwLock<Vec<(String, String)>>>,
+    body: Arc<RwLock<String>>,
     http_version: String,
 }
 
+impl Clone for HTTPResponseBox {
+    fn clone(&self) -> Self {
+        Self {
+            base: BoxBase::new(), // New unique ID for clone
+            status_code: Arc::new(RwLock::new(*self.status_code.read().unwrap())),
+            status_message: Arc::new(RwLock::new(self.status_message.read().unwrap().clone())),
+            headers: Arc::new(RwLock::new(self.headers.read().unwrap().clone())),
+            body: Arc::new(RwLock::new(self.body.read().unwrap().clone())),
+            http_version: self.http_version.clone(),
+        }
+    }
+}
+
+/// 標準的なステータスコードの理由句
+pub(crate) fn reason_phrase(code: i32) -> &'static str {
+    match code {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "",
+    }
+}
+
 impl HTTPResponseBox {
     pub fn new() -> Self {
         Self {
             base: BoxBase::new(),
-            status_code: 200,
-            status_message: "OK".to_string(),
-            headers: HashMap::new(),
-            body: "".to_string(),
+            status_code: Arc::new(RwLock::new(200)),
+            status_message: Arc::new(RwLock::new("OK".to_string())),
+            headers: Arc::new(RwLock::new(Vec::new())),
+            body: Arc::new(RwLock::new("".to_string())),
             http_version: "HTTP/1.1".to_string(),
         }
     }
-    
+
+    /// ステータス・Content-Type・ボディを指定して生成
+    pub fn with_content(code: i32, content_type: &str, body: String) -> Self {
+        let response = HTTPResponseBox::new();
+        *response.status_code.write().unwrap() = code;
+        *response.status_message.write().unwrap() = reason_phrase(code).to_string();
+        response.put_header("Content-Type", content_type);
+        *response.body.write().unwrap() = body;
+        response
+    }
+
+    /// 受信したレスポンスから生成（HttpClientBox用）
+    pub(crate) fn from_parts(code: i32, message: String, headers: Vec<(String, String)>, body: String) -> Self {
+        let response = HTTPResponseBox::new();
+        *response.status_code.write().unwrap() = code;
+        *response.status_message.write().unwrap() = message;
+        *response.headers.write().unwrap() = headers;
+        *response.body.write().unwrap() = body;
+        response
+    }
+
+    /// ヘッダー設定（同名ヘッダーは大文字小文字を区別せず置き換え）
+    fn put_header(&self, name: &str, value: &str) {
+        let mut headers = self.headers.write().unwrap();
+        match headers.iter_mut().find(|(n, _)| n.eq_ignore_ascii_case(name)) {
+            Some(entry) => entry.1 = value.to_string(),
+            None => headers.push((name.to_string(), value.to_string())),
+        }
+    }
+
     /// ステータスコード・メッセージ設定
     pub fn set_status(&self, code: Box<dyn NyashBox>, message: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
-        // Note: This would need interior mutability for actual mutation
-        // For now, this is a placeholder for the API structure
-        let _code_val = code.to_string_box().value.parse::<i32>().unwrap_or(200);
-        let _message_val = message.to_string_box().value;
-        
-        // TODO: Use RefCell or similar for interior mutability
+        let code_val = code.to_string_box().value.parse::<i32>().unwrap_or(200);
+        *self.status_code.write().unwrap() = code_val;
+        *self.status_message.write().unwrap() = message.to_string_box().value;
         Box::new(BoolBox::new(true))
     }
-    
+
     /// ヘッダー設定
     pub fn set_header(&self, name: Box<dyn NyashBox>, value: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
-        let _name_str = name.to_string_box().value;
-        let _value_str = value.to_string_box().value;
-        
-        // TODO: Use RefCell for interior mutability
+        self.put_header(&name.to_string_box().value, &value.to_string_box().value);
         Box::new(BoolBox::new(true))
     }
-    
+
     /// Content-Type設定
     pub fn set_content_type(&self, content_type: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
         let content_type_str = content_type.to_string_box().value;
@@ -299,94 +436,118 @@ impl HTTPResponseBox {
             Box::new(StringBox::new(content_type_str))
         )
     }
-    
+
     /// レスポンスボディ設定
     pub fn set_body(&self, content: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
-        let _content_str = content.to_string_box().value;
-        
-        // TODO: Use RefCell for interior mutability
+        *self.body.write().unwrap() = content.to_string_box().value;
         Box::new(BoolBox::new(true))
     }
-    
+
     /// ボディ追加
     pub fn append_body(&self, content: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
-        let _content_str = content.to_string_box().value;
-        
-        // TODO: Use RefCell for interior mutability
+        self.body.write().unwrap().push_str(&content.to_string_box().value);
         Box::new(BoolBox::new(true))
     }
-    
+
+    /// ステータスコード取得
+    pub fn get_status(&self) -> Box<dyn NyashBox> {
+        Box::new(IntegerBox::new(*self.status_code.read().unwrap() as i64))
+    }
+
+    /// ステータスの理由句取得
+    pub fn get_status_message(&self) -> Box<dyn NyashBox> {
+        Box::new(StringBox::new(self.status_message.read().unwrap().clone()))
+    }
+
+    /// ヘッダー取得（無ければ空文字列）
+    pub fn get_header(&self, name: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        let name_str = name.to_string_box().value;
+        let headers = self.headers.read().unwrap();
+        let value = headers.iter().find(|(n, _)| n.eq_ignore_ascii_case(&name_str)).map(|(_, v)| v.clone());
+        Box::new(StringBox::new(value.unwrap_or_default()))
+    }
+
+    /// 全ヘッダー取得（名前は小文字に正規化）
+    pub fn get_all_headers(&self) -> Box<dyn NyashBox> {
+        let headers_map = MapBox::new();
+        for (name, value) in self.headers.read().unwrap().iter() {
+            headers_map.set(Box::new(StringBox::new(name.to_lowercase())), Box::new(StringBox::new(value.clone())));
+        }
+        Box::new(headers_map)
+    }
+
+    /// ボディ取得
+    pub fn get_body(&self) -> Box<dyn NyashBox> {
+        Box::new(StringBox::new(self.body.read().unwrap().clone()))
+    }
+
     /// HTTP形式文字列生成
     pub fn to_http_string(&self) -> Box<dyn NyashBox> {
         let mut response = String::new();
-        
+        let headers = self.headers.read().unwrap();
+        let body = self.body.read().unwrap();
+
         // Status line
-        response.push_str(&format!("{} {} {}\r\n", 
-                                  self.http_version, self.status_code, self.status_message));
-        
+        response.push_str(&format!("{} {} {}\r\n",
+                                  self.http_version, *self.status_code.read().unwrap(), *self.status_message.read().unwrap()));
+
         // Headers
-        for (name, value) in &self.headers {
+        for (name, value) in headers.iter() {
             response.push_str(&format!("{}: {}\r\n", name, value));
         }
-        
-        // Content-Length if not already set
-        if !self.headers.contains_key("content-length") && !self.body.is_empty() {
-            response.push_str(&format!("Content-Length: {}\r\n", self.body.len()));
+
+        // Content-Length if not already set (clients need it to find the end of the body)
+        if !headers.iter().any(|(n, _)| n.eq_ignore_ascii_case("content-length")) {
+            response.push_str(&format!("Content-Length: {}\r\n", body.len()));
         }
-        
+
         // Empty line before body
         response.push_str("\r\n");
-        
+
         // Body
-        response.push_str(&self.body);
-        
+        response.push_str(&body);
+
         Box::new(StringBox::new(response))
     }
-    
+
     /// Quick HTML response creation
     pub fn create_html_response(content: Box<dyn NyashBox>) -> Self {
-        let mut response = HTTPResponseBox::new();
-        response.status_code = 200;
-        response.status_message = "OK".to_string();
-        response.headers.insert("Content-Type".to_string(), "text/html; charset=utf-8".to_string());
-        response.body = content.to_string_box().value;
-        response
+        Self::with_content(200, "text/html; charset=utf-8", content.to_string_box().value)
     }
-    
+
     /// Quick JSON response creation
     pub fn create_json_response(content: Box<dyn NyashBox>) -> Self {
-        let mut response = HTTPResponseBox::new();
-        response.status_code = 200;
-        response.status_message = "OK".to_string();
-        response.headers.insert("Content-Type".to_string(), "application/json".to_string());
-        response.body = content.to_string_box().value;
-        response
+        Self::with_content(200, "application/json", content.to_string_box().value)
     }
-    
+
     /// Quick 404 response creation
     pub fn create_404_response() -> Self {
-        let mut response = HTTPResponseBox::new();
-        response.status_code = 404;
-        response.status_message = "Not Found".to_string();
-        response.headers.insert("Content-Type".to_string(), "text/html; charset=utf-8".to_string());
-        response.body = "<html><body><h1>404 - Not Found</h1></body></html>".to_string();
-        response
+        Self::with_content(404, "text/html; charset=utf-8", "<html><body><h1>404 - Not Found</h1></body></html>".to_string())
     }
 }
 
 impl NyashBox for HTTPResponseBox {
+    fn is_identity(&self) -> bool { true }
+
     fn clone_box(&self) -> Box<dyn NyashBox> {
         Box::new(self.clone())
     }
-    
-    /// 仮実装: clone_boxと同じ（後で修正）
+
+    /// 状態共有: ハンドラー内の setBody() などが同じレスポンスに反映される
     fn share_box(&self) -> Box<dyn NyashBox> {
-        self.clone_box()
+        Box::new(HTTPResponseBox {
+            base: BoxBase::new(),
+            status_code: Arc::clone(&self.status_code),
+            status_message: Arc::clone(&self.status_message),
+            headers: Arc::clone(&self.headers),
+            body: Arc::clone(&self.body),
+            http_version: self.http_version.clone(),
+        })
     }
 
     fn to_string_box(&self) -> StringBox {
         StringBox::new(format!("HTTPResponse({} {} - {} bytes)", 
-                              self.status_code, self.status_message, self.body.len()))
+                              *self.status_code.read().unwrap(), *self.status_message.read().unwrap(), self.body.read().unwrap().len()))
     }
 
     fn type_name(&self) -> &'static str {
@@ -413,7 +574,7 @@ impl BoxCore for HTTPResponseBox {
 
     fn fmt_box(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "HTTPResponse({} {} - {} bytes)", 
-               self.status_code, self.status_message, self.body.len())
+               *self.status_code.read().unwrap(), *self.status_message.read().unwrap(), self.body.read().unwrap().len())
     }
     
     fn as_any(&self) -> &dyn Any {
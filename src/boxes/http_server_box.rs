@@ -12,16 +12,28 @@
  * - `stop()` - サーバー停止
  * 
  * ### Routing & Handlers
+ * ハンドラーは `MethodBox`（`handler(request)` として呼ばれる）、`handle(request)` を持つ
+ * ユーザーBoxインスタンス、または固定レスポンス（StringBox / HTTPResponseBox）。
+ * 戻り値の HTTPResponseBox はそのまま、それ以外は `text/plain` の 200 として返す。
+ * パスは `/users/:id` のようなパラメータを含められる（`request.getParam("id")`）。
  * - `route(path, handler)` - ルート・ハンドラー登録
  * - `get(path, handler)` - GET ルート登録
  * - `post(path, handler)` - POST ルート登録
  * - `put(path, handler)` - PUT ルート登録
  * - `delete(path, handler)` - DELETE ルート登録
+ * - `websocket(path, handler)` - WebSocket アップグレード受付。受信メッセージごとに
+ *   `handler(message, socket)` を呼ぶ（message はテキストなら StringBox、バイナリなら BufferBox、
+ *   socket は WebSocketBox）。void 以外の戻り値はそのまま送り返す
  * 
  * ### Middleware & Configuration
  * - `use(middleware)` - ミドルウェア登録
- * - `setStaticPath(path)` - 静的ファイル配信設定
- * - `setTimeout(seconds)` - リクエストタイムアウト設定
+ * - `setStaticPath(path)` - 静的ファイル配信設定（どのルートにも一致しない GET/HEAD に適用）
+ * - `setTimeout(seconds)` - リクエストタイムアウト設定（受信・ハンドラー応答待ち）
+ *
+ * ## 🧵 スレッドモデル
+ * 接続ごとのワーカースレッドが受信・ルート照合・静的ファイル配信を行い、
+ * ハンドラー呼び出しだけを `start()` を呼んだスレッド（インタープリタ）へ送る。
+ * ハンドラーは1つずつ順番に実行される。
  * 
  * ## 💡 使用例
  * ```nyash
@@ -30,9 +42,11 @@
  * server.bind("0.0.0.0", 8080)
  * 
  * // Route handlers
- * server.get("/", APIHandler.home)
- * server.get("/api/status", APIHandler.status)
- * server.post("/api/users", APIHandler.createUser)
+ * local api = new APIHandler()
+ * server.get("/", new MethodBox(api, "home"))
+ * server.get("/users/:id", new MethodBox(api, "showUser"))
+ * server.post("/api/users", new MethodBox(api, "createUser"))
+ * server.setStaticPath("./public")
  * 
  * // Start server (blocking)
  * print("🚀 Server starting on port 8080...")
@@ -43,22 +57,53 @@
 use crate::box_trait::{NyashBox, StringBox, IntegerBox, BoolBox, BoxCore, BoxBase};
 use crate::boxes::SocketBox;
 use crate::boxes::http_message_box::{HTTPRequestBox, HTTPResponseBox};
+use crate::boxes::websocket_box::{Received, WebSocketBox, WsConn, WsMessage};
 use std::any::Any;
-use std::sync::RwLock;
 use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{mpsc, Arc, RwLock};
 use std::thread;
+use std::time::Duration;
+
+/// accept とハンドラー実行を交互に回す間隔
+const POLL_INTERVAL_MS: i64 = 10;
+/// 受け付けるリクエストボディの上限
+const MAX_BODY_BYTES: usize = 16 * 1024 * 1024;
+/// WebSocket ワーカーが停止要求を確認する間隔
+const WS_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// ルートハンドラーの呼び出し口（インタープリタが実装する）。
+/// 引数は HTTP なら `[request]`、WebSocket なら `[message, socket]`。Err はハンドラーの実行時エラー
+pub type RouteDispatch<'a> = dyn FnMut(&dyn NyashBox, Vec<Box<dyn NyashBox>>) -> Result<Box<dyn NyashBox>, String> + 'a;
+
+/// ハンドラー結果の返し先
+enum JobReply {
+    /// シリアライズ済み HTTP レスポンス
+    Http(mpsc::Sender<String>),
+    /// WebSocket で送り返すメッセージ（None: 送らない）
+    WebSocket(mpsc::Sender<Result<Option<WsMessage>, String>>),
+}
+
+/// ワーカースレッド → `start()` スレッドへのハンドラー実行依頼
+struct RouteJob {
+    route_key: String,
+    args: Vec<Box<dyn NyashBox>>,
+    reply: JobReply,
+}
 
 /// HTTP サーバーを提供するBox
 #[derive(Debug)]
 pub struct HTTPServerBox {
     base: BoxBase,
-    socket: RwLock<Option<SocketBox>>,
-    routes: RwLock<HashMap<String, Box<dyn NyashBox>>>,
-    middleware: RwLock<Vec<Box<dyn NyashBox>>>,
-    running: RwLock<bool>,
-    static_path: RwLock<Option<String>>,
-    timeout_seconds: RwLock<u64>,
-    active_connections: RwLock<Vec<Box<dyn NyashBox>>>,
+    socket: Arc<RwLock<Option<SocketBox>>>,
+    routes: Arc<RwLock<HashMap<String, Box<dyn NyashBox>>>>,
+    middleware: Arc<RwLock<Vec<Box<dyn NyashBox>>>>,
+    running: Arc<RwLock<bool>>,
+    static_path: Arc<RwLock<Option<String>>>,
+    timeout_seconds: Arc<RwLock<u64>>,
+    active_connections: Arc<RwLock<usize>>,
 }
 
 impl Clone for HTTPServerBox {
@@ -81,20 +126,15 @@ impl Clone for HTTPServerBox {
         let static_path_val = self.static_path.read().unwrap().clone();
         let timeout_val = *self.timeout_seconds.read().unwrap();
         
-        let connections_guard = self.active_connections.read().unwrap();
-        let connections_val: Vec<Box<dyn NyashBox>> = connections_guard.iter()
-            .map(|item| item.clone_box())
-            .collect();
-        
         Self {
             base: BoxBase::new(), // New unique ID for clone
-            socket: RwLock::new(socket_val),
-            routes: RwLock::new(routes_val),
-            middleware: RwLock::new(middleware_val),
-            running: RwLock::new(running_val),
-            static_path: RwLock::new(static_path_val),
-            timeout_seconds: RwLock::new(timeout_val),
-            active_connections: RwLock::new(connections_val),
+            socket: Arc::new(RwLock::new(socket_val)),
+            routes: Arc::new(RwLock::new(routes_val)),
+            middleware: Arc::new(RwLock::new(middleware_val)),
+            running: Arc::new(RwLock::new(running_val)),
+            static_path: Arc::new(RwLock::new(static_path_val)),
+            timeout_seconds: Arc::new(RwLock::new(timeout_val)),
+            active_connections: Arc::new(RwLock::new(0)),
         }
     }
 }
@@ -103,13 +143,13 @@ impl HTTPServerBox {
     pub fn new() -> Self {
         Self {
             base: BoxBase::new(),
-            socket: RwLock::new(None),
-            routes: RwLock::new(HashMap::new()),
-            middleware: RwLock::new(Vec::new()),
-            running: RwLock::new(false),
-            static_path: RwLock::new(None),
-            timeout_seconds: RwLock::new(30),
-            active_connections: RwLock::new(Vec::new()),
+            socket: Arc::new(RwLock::new(None)),
+            routes: Arc::new(RwLock::new(HashMap::new())),
+            middleware: Arc::new(RwLock::new(Vec::new())),
+            running: Arc::new(RwLock::new(false)),
+            static_path: Arc::new(RwLock::new(None)),
+            timeout_seconds: Arc::new(RwLock::new(30)),
+            active_connections: Arc::new(RwLock::new(0)),
         }
     }
     
@@ -140,102 +180,97 @@ impl HTTPServerBox {
             Err(_) => return Box::new(StringBox::new("Error: Failed to acquire socket lock".to_string())),
         };
         
-        if let Some(ref _socket) = *socket_guard {
-            // For HTTPServerBox, if we have a socket stored, it means bind() was successful
-            // and the socket should be in listening state. TcpListener::bind already puts
-            // the socket in listening state, so we just need to verify it's working.
-            
-            // Try to access the stored listener directly (this is a simplified check)
-            // In a real implementation, we'd store the listener state separately
-            Box::new(BoolBox::new(true))
-        } else {
-            Box::new(BoolBox::new(false))
-        }
+        // TcpListener::bind already puts the socket in listening state
+        Box::new(BoolBox::new(socket_guard.is_some()))
     }
     
-    /// HTTP サーバー開始（メインループ）
+    /// HTTP サーバー開始（ハンドラー実行環境なし: 固定レスポンスのルートのみ応答）
     pub fn start(&self) -> Box<dyn NyashBox> {
-        // Set running state
+        self.start_with_dispatch(&mut |handler, _args| Ok(Self::fixed_response(handler)))
+    }
+
+    /// MethodBox などの実行できないハンドラーは 501 を返す
+    pub fn fixed_response(handler: &dyn NyashBox) -> Box<dyn NyashBox> {
+        if handler.as_any().is::<StringBox>() || handler.as_any().is::<HTTPResponseBox>() {
+            handler.clone_box()
+        } else {
+            Box::new(HTTPResponseBox::with_content(
+                501,
+                "text/plain; charset=utf-8",
+                format!("handler {} cannot be invoked without an interpreter", handler.type_name()),
+            ))
+        }
+    }
+
+    /// HTTP サーバー開始（メインループ・ブロッキング）
+    ///
+    /// `dispatch` はこのスレッド上で、ルートに一致したリクエストごとに呼ばれる。
+    pub fn start_with_dispatch(&self, dispatch: &mut RouteDispatch) -> Box<dyn NyashBox> {
+        // Shares the listener, so the lock is not held while the loop runs
+        let shared_socket = match self.socket.read() {
+            Ok(guard) => match guard.as_ref() {
+                Some(socket) => socket.share_box(),
+                None => return Box::new(BoolBox::new(false)),
+            },
+            Err(_) => return Box::new(StringBox::new("Error: Failed to acquire socket lock".to_string())),
+        };
+        let Some(server_socket) = shared_socket.as_any().downcast_ref::<SocketBox>() else {
+            return Box::new(BoolBox::new(false));
+        };
+
         match self.running.write() {
             Ok(mut running) => *running = true,
             Err(_) => return Box::new(StringBox::new("Error: Failed to set running state".to_string())),
         };
-        
-        let socket_guard = match self.socket.read() {
-            Ok(guard) => guard,
-            Err(_) => return Box::new(StringBox::new("Error: Failed to acquire socket lock".to_string())),
-        };
-        
-        if let Some(ref socket) = *socket_guard {
-            // Clone socket for the server loop
-            let server_socket = socket.clone();
-            drop(socket_guard);
-            
-            println!("🚀 HTTP Server starting...");
-            
-            // Main server loop - need to handle RwLock references carefully for threading
-            loop {
-                // Check if server should stop
-                let should_continue = match self.running.read() {
-                    Ok(running_guard) => *running_guard,
-                    Err(_) => break, // Exit loop if we can't check running state
-                };
-                
-                if !should_continue {
-                    break;
-                }
-                
-                // Accept new connection
-                let client_result = server_socket.accept();
-                
-                // Check if we got a valid client connection
-                let client_socket = match client_result.as_any().downcast_ref::<SocketBox>() {
-                    Some(socket) => socket.clone(),
-                    None => continue, // Skip invalid connections
-                };
-                
-                // Add to active connections (with error handling)
-                if let Ok(mut connections) = self.active_connections.write() {
-                    connections.push(Box::new(client_socket.clone()));
-                }
-                
-                // Handle client in separate thread (simulate nowait)
-                // For RwLock pattern, we need to pass the data needed for the thread
-                let routes_snapshot = match self.routes.read() {
-                    Ok(routes_guard) => {
-                        let routes_clone: HashMap<String, Box<dyn NyashBox>> = routes_guard.iter()
-                            .map(|(k, v)| (k.clone(), v.clone_box()))
-                            .collect();
-                        routes_clone
-                    },
-                    Err(_) => continue, // Skip this connection if we can't read routes
-                };
-                
+        println!("🚀 HTTP Server starting...");
+
+        let (job_tx, job_rx) = mpsc::channel::<RouteJob>();
+        while *self.running.read().unwrap() {
+            // Accept new connection (short timeout so queued handlers keep running)
+            let client = server_socket.accept_timeout(Box::new(IntegerBox::new(POLL_INTERVAL_MS)));
+            if let Some(stream) = client.as_any().downcast_ref::<SocketBox>().and_then(|s| s.take_stream()) {
+                let route_keys: Vec<String> = self.routes.read().unwrap().keys().cloned().collect();
+                let static_path = self.static_path.read().unwrap().clone();
+                let timeout = Duration::from_secs((*self.timeout_seconds.read().unwrap()).max(1));
+                let jobs = job_tx.clone();
+                let running = Arc::clone(&self.running);
+                let active = Arc::clone(&self.active_connections);
+                *active.write().unwrap() += 1;
                 thread::spawn(move || {
-                    Self::handle_client_request_with_routes(client_socket, routes_snapshot);
-                    // Note: Connection cleanup is handled separately to avoid complex lifetime issues
+                    Self::handle_connection(stream, route_keys, static_path, timeout, jobs, running);
+                    *active.write().unwrap() -= 1;
                 });
             }
-            
-            Box::new(BoolBox::new(true))
-        } else {
-            Box::new(BoolBox::new(false))
+
+            // Run handlers requested by workers on this (interpreter) thread
+            while let Ok(job) = job_rx.try_recv() {
+                // Don't hold the routes lock while user code runs (it may add routes)
+                let handler = self.routes.read().unwrap().get(&job.route_key).map(|h| h.clone_or_share());
+                let result = match handler {
+                    Some(handler) => dispatch(handler.as_ref(), job.args),
+                    None => Err(format!("no handler for {}", job.route_key)),
+                };
+                match job.reply {
+                    JobReply::Http(reply) => {
+                        let response = result.unwrap_or_else(|e| {
+                            Box::new(HTTPResponseBox::with_content(500, "text/plain; charset=utf-8", e))
+                        });
+                        let _ = reply.send(Self::serialize_response(response.as_ref()));
+                    }
+                    JobReply::WebSocket(reply) => {
+                        let _ = reply.send(result.map(|value| WebSocketBox::message_from_box(value.as_ref())));
+                    }
+                }
+            }
         }
+
+        Box::new(BoolBox::new(true))
     }
     
     /// サーバー停止
     pub fn stop(&self) -> Box<dyn NyashBox> {
         *self.running.write().unwrap() = false;
         
-        // Close all active connections
-        let mut connections = self.active_connections.write().unwrap();
-        for connection in connections.iter() {
-            if let Some(socket) = connection.as_any().downcast_ref::<SocketBox>() {
-                let _ = socket.close();
-            }
-        }
-        connections.clear();
-        
         // Close server socket
         if let Some(ref socket) = *self.socket.read().unwrap() {
             let _ = socket.close();
@@ -244,50 +279,41 @@ impl HTTPServerBox {
         println!("🛑 HTTP Server stopped");
         Box::new(BoolBox::new(true))
     }
+
+    fn add_route(&self, method: &str, path: Box<dyn NyashBox>, handler: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        let route_key = format!("{} {}", method, path.to_string_box().value);
+        self.routes.write().unwrap().insert(route_key, handler);
+        Box::new(BoolBox::new(true))
+    }
     
     /// ルート・ハンドラー登録
     pub fn route(&self, path: Box<dyn NyashBox>, handler: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
-        let path_str = path.to_string_box().value;
-        let route_key = format!("ANY {}", path_str);
-        
-        self.routes.write().unwrap().insert(route_key, handler);
-        Box::new(BoolBox::new(true))
+        self.add_route("ANY", path, handler)
     }
     
     /// GET ルート登録
     pub fn get(&self, path: Box<dyn NyashBox>, handler: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
-        let path_str = path.to_string_box().value;
-        let route_key = format!("GET {}", path_str);
-        
-        self.routes.write().unwrap().insert(route_key, handler);
-        Box::new(BoolBox::new(true))
+        self.add_route("GET", path, handler)
     }
     
     /// POST ルート登録
     pub fn post(&self, path: Box<dyn NyashBox>, handler: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
-        let path_str = path.to_string_box().value;
-        let route_key = format!("POST {}", path_str);
-        
-        self.routes.write().unwrap().insert(route_key, handler);
-        Box::new(BoolBox::new(true))
+        self.add_route("POST", path, handler)
     }
     
     /// PUT ルート登録
     pub fn put(&self, path: Box<dyn NyashBox>, handler: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
-        let path_str = path.to_string_box().value;
-        let route_key = format!("PUT {}", path_str);
-        
-        self.routes.write().unwrap().insert(route_key, handler);
-        Box::new(BoolBox::new(true))
+        self.add_route("PUT", path, handler)
     }
     
     /// DELETE ルート登録
     pub fn delete(&self, path: Box<dyn NyashBox>, handler: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
-        let path_str = path.to_string_box().value;
-        let route_key = format!("DELETE {}", path_str);
-        
-        self.routes.write().unwrap().insert(route_key, handler);
-        Box::new(BoolBox::new(true))
+        self.add_route("DELETE", path, handler)
+    }
+
+    /// WebSocket ルート登録（`Upgrade: websocket` のリクエストだけが一致する）
+    pub fn websocket(&self, path: Box<dyn NyashBox>, handler: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        self.add_route("WS", path, handler)
     }
     
     /// 静的ファイル配信パス設定
@@ -303,58 +329,252 @@ impl HTTPServerBox {
         *self.timeout_seconds.write().unwrap() = timeout_val;
         Box::new(BoolBox::new(true))
     }
-    
-    /// クライアントリクエスト処理（内部メソッド）
-    fn handle_client_request_with_routes(
-        client_socket: SocketBox, 
-        routes: HashMap<String, Box<dyn NyashBox>>
+
+    /// `METHOD path` → ルートキーとパスパラメータ。
+    /// メソッド一致 → ANY の順、それぞれ完全一致を `:param` パターンより優先する。
+    pub fn match_route(route_keys: &[String], method: &str, path: &str) -> Option<(String, HashMap<String, String>)> {
+        let prefixes = [method, "ANY"];
+        for prefix in prefixes {
+            let exact = format!("{} {}", prefix, path);
+            if route_keys.contains(&exact) {
+                return Some((exact, HashMap::new()));
+            }
+        }
+        let mut sorted: Vec<&String> = route_keys.iter().collect();
+        sorted.sort();
+        for prefix in prefixes {
+            for key in &sorted {
+                let Some(pattern) = key.strip_prefix(prefix).and_then(|k| k.strip_prefix(' ')) else { continue };
+                if let Some(params) = Self::match_path(pattern, path) {
+                    return Some(((*key).clone(), params));
+                }
+            }
+        }
+        None
+    }
+
+    fn match_path(pattern: &str, path: &str) -> Option<HashMap<String, String>> {
+        let pattern_segs: Vec<&str> = pattern.trim_end_matches('/').split('/').collect();
+        let path_segs: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+        if pattern_segs.len() != path_segs.len() {
+            return None;
+        }
+        let mut params = HashMap::new();
+        for (p, s) in pattern_segs.iter().zip(&path_segs) {
+            if let Some(name) = p.strip_prefix(':') {
+                if s.is_empty() {
+                    return None;
+                }
+                params.insert(name.to_string(), crate::boxes::http_message_box::url_decode(s, false));
+            } else if p != s {
+                return None;
+            }
+        }
+        Some(params)
+    }
+
+    /// ハンドラーの戻り値 → HTTP レスポンス文字列
+    fn serialize_response(result: &dyn NyashBox) -> String {
+        if let Some(response) = result.as_any().downcast_ref::<HTTPResponseBox>() {
+            return response.to_http_string().to_string_box().value;
+        }
+        let body = if result.as_any().is::<crate::box_trait::VoidBox>() { String::new() } else { result.to_string_box().value };
+        HTTPResponseBox::with_content(200, "text/plain; charset=utf-8", body).to_http_string().to_string_box().value
+    }
+
+    /// ヘッダーと Content-Length 分のボディを読む
+    fn read_request(stream: &TcpStream) -> Option<String> {
+        let mut reader = BufReader::new(stream);
+        let mut head = String::new();
+        loop {
+            let mut line = String::new();
+            match reader.read_line(&mut line) {
+                Ok(0) => break,
+                Ok(_) => {
+                    head.push_str(&line);
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                }
+                Err(_) => return None,
+            }
+        }
+        if head.trim().is_empty() {
+            return None;
+        }
+        let content_length = head
+            .lines()
+            .filter_map(|l| l.split_once(':'))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+            .and_then(|(_, v)| v.trim().parse::<usize>().ok())
+            .unwrap_or(0);
+        if content_length > MAX_BODY_BYTES {
+            return None;
+        }
+        let mut body = vec![0u8; content_length];
+        reader.read_exact(&mut body).ok()?;
+        head.push_str(&String::from_utf8_lossy(&body));
+        Some(head)
+    }
+
+    /// クライアント接続処理（ワーカースレッド）
+    fn handle_connection(
+        mut stream: TcpStream,
+        route_keys: Vec<String>,
+        static_path: Option<String>,
+        timeout: Duration,
+        jobs: mpsc::Sender<RouteJob>,
+        running: Arc<RwLock<bool>>,
     ) {
-        // Read HTTP request
-        let raw_request = client_socket.read_http_request();
-        let request_str = raw_request.to_string_box().value;
-        
-        if request_str.trim().is_empty() {
-            let _ = client_socket.close();
+        let _ = stream.set_read_timeout(Some(timeout));
+        let Some(raw_request) = Self::read_request(&stream) else {
             return;
-        }
-        
-        // Parse HTTP request
-        let request = HTTPRequestBox::parse(raw_request);
+        };
+
+        let mut request = HTTPRequestBox::parse(Box::new(StringBox::new(raw_request)));
         let method = request.get_method().to_string_box().value;
         let path = request.get_path().to_string_box().value;
-        
         println!("📬 {} {}", method, path);
-        
-        // Find matching route
-        let route_key = format!("{} {}", method, path);
-        let fallback_key = format!("ANY {}", path);
-        
-        let response = if let Some(_handler) = routes.get(&route_key) {
-            // Found specific method route
-            // TODO: Actual handler invocation would need method calling infrastructure
-            HTTPResponseBox::create_json_response(
-                Box::new(StringBox::new(r#"{"message": "Route found", "method": ""#.to_string() + &method + r#""}"#))
-            )
-        } else if let Some(_handler) = routes.get(&fallback_key) {
-            // Found generic route
-            HTTPResponseBox::create_json_response(
-                Box::new(StringBox::new(r#"{"message": "Generic route found"}"#))
-            )
-        } else {
-            // No route found - 404
-            HTTPResponseBox::create_404_response()
+
+        if Self::header(&request, "upgrade").eq_ignore_ascii_case("websocket") {
+            Self::handle_websocket(stream, &request, &path, &route_keys, timeout, jobs, running);
+            return;
+        }
+
+        let response: Vec<u8> = match Self::match_route(&route_keys, &method, &path) {
+            Some((route_key, params)) => {
+                request.set_params(params);
+                let (reply_tx, reply_rx) = mpsc::channel();
+                let job = RouteJob { route_key, args: vec![Box::new(request)], reply: JobReply::Http(reply_tx) };
+                let sent = jobs.send(job).is_ok();
+                match reply_rx.recv_timeout(timeout) {
+                    Ok(text) if sent => text.into_bytes(),
+                    _ => Self::status_bytes(504, "handler did not respond in time"),
+                }
+            }
+            None => match static_path {
+                Some(root) if method == "GET" || method == "HEAD" => Self::serve_static(&root, &path, method == "HEAD"),
+                _ => HTTPResponseBox::create_404_response().to_http_string().to_string_box().value.into_bytes(),
+            },
         };
-        
-        // Send response
-        let response_str = response.to_http_string();
-        let _ = client_socket.write(response_str);
-        let _ = client_socket.close();
+
+        let _ = stream.write_all(&response);
+        let _ = stream.flush();
+        let _ = stream.shutdown(std::net::Shutdown::Both);
+    }
+
+    fn header(request: &HTTPRequestBox, name: &str) -> String {
+        request.get_header(Box::new(StringBox::new(name))).to_string_box().value
+    }
+
+    /// WebSocket 接続処理（ワーカースレッド）: ハンドシェイク後、受信メッセージごとにハンドラーを呼ぶ
+    fn handle_websocket(
+        mut stream: TcpStream,
+        request: &HTTPRequestBox,
+        path: &str,
+        route_keys: &[String],
+        timeout: Duration,
+        jobs: mpsc::Sender<RouteJob>,
+        running: Arc<RwLock<bool>>,
+    ) {
+        let ws_keys: Vec<String> = route_keys.iter().filter(|k| k.starts_with("WS ")).cloned().collect();
+        let Some((route_key, _)) = Self::match_route(&ws_keys, "WS", path) else {
+            let _ = stream.write_all(HTTPResponseBox::create_404_response().to_http_string().to_string_box().value.as_bytes());
+            return;
+        };
+        let key = Self::header(request, "sec-websocket-key");
+        if key.is_empty() || Self::header(request, "sec-websocket-version").trim() != "13" {
+            let _ = stream.write_all(&Self::status_bytes(400, "bad websocket handshake"));
+            return;
+        }
+        let Ok(conn) = WsConn::accept(stream, &key) else {
+            return;
+        };
+        let conn = Arc::new(conn);
+        let socket = WebSocketBox::from_conn(Arc::clone(&conn));
+
+        loop {
+            let message = match conn.recv(Some(WS_POLL_INTERVAL)) {
+                Ok(Received::Message(message)) => message,
+                Ok(Received::Timeout) if *running.read().unwrap() => continue,
+                Ok(Received::Timeout) => {
+                    conn.close(1001, "server stopped");
+                    break;
+                }
+                Ok(Received::Closed) | Err(_) => break,
+            };
+            let (reply_tx, reply_rx) = mpsc::channel();
+            let args: Vec<Box<dyn NyashBox>> = vec![WebSocketBox::message_box(message), Box::new(socket.clone())];
+            if jobs.send(RouteJob { route_key: route_key.clone(), args, reply: JobReply::WebSocket(reply_tx) }).is_err() {
+                conn.close(1001, "server stopped");
+                break;
+            }
+            match reply_rx.recv_timeout(timeout) {
+                Ok(Ok(Some(reply))) => {
+                    // The handler may have closed the socket itself
+                    if conn.send(&reply).is_err() {
+                        break;
+                    }
+                }
+                Ok(Ok(None)) => {}
+                Ok(Err(_)) => {
+                    conn.close(1011, "handler error");
+                    break;
+                }
+                Err(_) => {
+                    conn.close(1011, "handler did not respond in time");
+                    break;
+                }
+            }
+        }
+    }
+
+    fn status_bytes(code: i32, body: &str) -> Vec<u8> {
+        HTTPResponseBox::with_content(code, "text/plain; charset=utf-8", body.to_string())
+            .to_http_string()
+            .to_string_box()
+            .value
+            .into_bytes()
+    }
+
+    /// 静的ファイル配信（`..` を含むパスは拒否、ディレクトリは index.html）
+    fn serve_static(root: &str, url_path: &str, head_only: bool) -> Vec<u8> {
+        let decoded = crate::boxes::http_message_box::url_decode(url_path, false);
+        let relative = Path::new(decoded.trim_start_matches('/'));
+        if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+            return Self::status_bytes(403, "forbidden");
+        }
+        let mut file: PathBuf = Path::new(root).join(relative);
+        if file.is_dir() {
+            file = file.join("index.html");
+        }
+        let Ok(content) = std::fs::read(&file) else {
+            return HTTPResponseBox::create_404_response().to_http_string().to_string_box().value.into_bytes();
+        };
+        let mime = match file.extension().and_then(|e| e.to_str()).unwrap_or("") {
+            "html" | "htm" => "text/html; charset=utf-8",
+            "css" => "text/css; charset=utf-8",
+            "js" | "mjs" => "text/javascript; charset=utf-8",
+            "json" => "application/json",
+            "txt" => "text/plain; charset=utf-8",
+            "svg" => "image/svg+xml",
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            "gif" => "image/gif",
+            "wasm" => "application/wasm",
+            _ => "application/octet-stream",
+        };
+        let mut out = format!("HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n", mime, content.len()).into_bytes();
+        if !head_only {
+            out.extend_from_slice(&content);
+        }
+        out
     }
     
     /// アクティブ接続数取得
     pub fn get_active_connections(&self) -> Box<dyn NyashBox> {
-        let connections = self.active_connections.read().unwrap();
-        Box::new(IntegerBox::new(connections.len() as i64))
+        let connections = *self.active_connections.read().unwrap();
+        Box::new(IntegerBox::new(connections as i64))
     }
     
     /// サーバー状態取得
@@ -364,19 +584,30 @@ impl HTTPServerBox {
 }
 
 impl NyashBox for HTTPServerBox {
+    fn is_identity(&self) -> bool { true }
+
     fn clone_box(&self) -> Box<dyn NyashBox> {
         Box::new(self.clone())
     }
     
-    /// 仮実装: clone_boxと同じ（後で修正）
+    /// 状態共有: 変数経由の route()/start()/stop() が同じサーバーに作用する
     fn share_box(&self) -> Box<dyn NyashBox> {
-        self.clone_box()
+        Box::new(HTTPServerBox {
+            base: BoxBase::new(),
+            socket: Arc::clone(&self.socket),
+            routes: Arc::clone(&self.routes),
+            middleware: Arc::clone(&self.middleware),
+            running: Arc::clone(&self.running),
+            static_path: Arc::clone(&self.static_path),
+            timeout_seconds: Arc::clone(&self.timeout_seconds),
+            active_connections: Arc::clone(&self.active_connections),
+        })
     }
 
     fn to_string_box(&self) -> StringBox {
         let running = *self.running.read().unwrap();
         let routes_count = self.routes.read().unwrap().len();
-        let connections_count = self.active_connections.read().unwrap().len();
+        let connections_count = *self.active_connections.read().unwrap();
         
         StringBox::new(format!(
             "HTTPServer(id: {}, running: {}, routes: {}, connections: {})", 
@@ -390,7 +621,7 @@ impl NyashBox for HTTPServerBox {
 
     fn equals(&self, other: &dyn NyashBox) -> BoolBox {
         if let Some(other_server) = other.as_any().downcast_ref::<HTTPServerBox>() {
-            BoolBox::new(self.base.id == other_server.base.id)
+            BoolBox::new(Arc::ptr_eq(&self.routes, &other_server.routes))
         } else {
             BoolBox::new(false)
         }
@@ -409,7 +640,7 @@ impl BoxCore for HTTPServerBox {
     fn fmt_box(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let running = *self.running.read().unwrap();
         let routes_count = self.routes.read().unwrap().len();
-        let connections_count = self.active_connections.read().unwrap().len();
+        let connections_count = *self.active_connections.read().unwrap();
         
         write!(f, "HTTPServer(id: {}, running: {}, routes: {}, connections: {})", 
                self.base.id, running, routes_count, connections_count)
@@ -433,7 +664,9 @@ impl std::fmt::Display for HTTPServerBox {
 // Auto-cleanup implementation for proper resource management
 impl Drop for HTTPServerBox {
     fn drop(&mut self) {
-        // Ensure server is stopped and resources are cleaned up
-        let _ = self.stop();
+        // Only the last handle to the shared state stops the server
+        if Arc::strong_count(&self.running) == 1 && *self.running.read().unwrap() {
+            let _ = self.stop();
+        }
     }
-}
\ No newline at end of file
+}
@@ -32,7 +32,7 @@
  * ```
  */
 
-use crate::box_trait::{NyashBox, StringBox, BoolBox, BoxCore, BoxBase};
+use crate::box_trait::{NyashBox, StringBox, BoolBox, VoidBox, BoxCore, BoxBase};
 use std::any::Any;
 use std::sync::RwLock;
 use std::fmt::Debug;
@@ -70,6 +70,17 @@ impl IntentBox {
         }
     }
     
+    /// Nyash の値から作成（JSON として読めればその値、読めなければ文字列の payload。void は null）
+    pub fn from_value(name: String, payload: &dyn NyashBox) -> Self {
+        if payload.as_any().is::<VoidBox>() {
+            return Self::new(name, serde_json::Value::Null);
+        }
+        let text = payload.to_string_box().value;
+        let payload = serde_json::from_str::<serde_json::Value>(&text)
+            .unwrap_or(serde_json::Value::String(text));
+        Self::new(name, payload)
+    }
+    
     /// メッセージ名を取得
     pub fn get_name(&self) -> Box<dyn NyashBox> {
         let name = self.name.read().unwrap().clone();
@@ -93,6 +104,21 @@ impl IntentBox {
             Err(_) => Box::new(BoolBox::new(false))
         }
     }
+
+    /// 転送用表現 `{"name": ..., "payload": ...}`
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name.read().unwrap().clone(),
+            "payload": self.payload.read().unwrap().clone(),
+        })
+    }
+
+    /// `to_json` の逆変換（name が無ければ None、payload 省略時は null）
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        let name = value.get("name")?.as_str()?.to_string();
+        let payload = value.get("payload").cloned().unwrap_or(serde_json::Value::Null);
+        Some(Self::new(name, payload))
+    }
 }
 
 impl NyashBox for IntentBox {
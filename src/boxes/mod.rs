@@ -17,6 +17,7 @@
  * - **ConsoleBox**: コンソール出力 - `console.log()`, `console.error()`  
  * - **DebugBox**: デバッグ支援 - `debug.trace()`, `debug.memory()`
  * - **SoundBox**: 音声再生 - `sound.beep()`, `sound.play(file)`
+ * - **AudioBox**: 音声合成・WAV書き出し - `audio.createTone(440, 500)`, `audio.render(path)`
  * 
  * ### 🗄️ コレクション・データBox
  * - **MapBox**: キー値ストレージ - `map.set(key, val)`, `map.get(key)`
@@ -24,6 +25,8 @@
  * 
  * ### 🖼️ GUI・グラフィックBox
  * - **EguiBox**: デスクトップGUI - `gui.setTitle()`, `gui.run()`
+ * - **CanvasBox**: ヘッドレスCanvas（ネイティブ） - `canvas.fillRect()`, `canvas.saveTo(path)`
+ * - **QRBox**: QRコード生成 - `qr.generate(text)`, `qr.saveTo(path)`
  * 
  * ### 🌐 Web専用Box (WASM環境)
  * - **WebDisplayBox**: HTML表示 - `display.show(html)`
@@ -71,6 +74,11 @@ pub mod canvas_loop_box;
 pub mod audio_box;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod qr_box;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod canvas_box;
+pub mod base64;
+pub mod png_writer;
+pub mod synth;
 pub mod sound_box;
 pub mod map_box;
 pub mod console_box;
@@ -101,6 +109,8 @@ pub use canvas_loop_box::CanvasLoopBox;
 pub use audio_box::AudioBox;
 #[cfg(not(target_arch = "wasm32"))]
 pub use qr_box::QRBox;
+#[cfg(not(target_arch = "wasm32"))]
+pub use canvas_box::CanvasBox;
 pub use sound_box::SoundBox;
 pub use map_box::MapBox;
 pub use console_box::ConsoleBox;
@@ -128,6 +138,7 @@ pub mod regex;
 pub mod socket_box;
 pub mod http_message_box;
 pub mod http_server_box;
+pub mod websocket_box;
 
 // P2P通信Box群 (NEW! - Completely rewritten)
 pub mod intent_box;
@@ -149,6 +160,7 @@ pub use regex::RegexBox;
 pub use socket_box::SocketBox;
 pub use http_message_box::{HTTPRequestBox, HTTPResponseBox};
 pub use http_server_box::HTTPServerBox;
+pub use websocket_box::WebSocketBox;
 
 // P2P通信Boxの再エクスポート
 pub use intent_box::IntentBox;
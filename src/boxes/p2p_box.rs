@@ -12,11 +12,43 @@
  * - **構造化メッセージ**: IntentBox (name + payload) 使用
  * 
  * ## 🛠️ 利用可能メソッド
- * - `new(node_id, transport)` - ノードを作成
+ * - `new(node_id, transport)` - ノードを作成（transport: "inprocess" / "tcp"）
  * - `send(to, intent)` - 特定ノードにメッセージ送信
+ * - `sendTimeout(to, intent, timeoutMs)` - 接続・送信のタイムアウト付き送信
+ * - `sendPriority(to, intent, priority)` - 優先度付き送信（0-255、大きいほど先に配送）
+ * - `request(to, intent)` - 要求を送り、応答で完了する FutureBox を返す（30秒でタイムアウト）
+ * - `requestTimeout(to, intent, timeoutMs)` - タイムアウト指定付きの request
  * - `on(intent_name, handler)` - イベントリスナー登録
+ * - `poll(timeoutMs)` - 届いたメッセージをハンドラーへ配送（最初の1件を最大 timeoutMs 待つ。配送数を返す）
+ * - `addPeer(node_id, "host:port")` - 相手ノードのアドレス登録（tcp）
  * - `getNodeId()` - ノードID取得
  * - `isReachable(node_id)` - ノード到達可能性確認
+ * - `subscribe(topic)` / `unsubscribe(topic)` - トピック購読の開始・終了
+ * - `publish(topic, intent)` - トピックの購読ノード全員へ送信（送れた数を返す）
+ * - `addTag(tag)` / `removeTag(tag)` - 自ノードの能力タグを公開・取り消し
+ * - `getNodes()` - 知っているノードID一覧（ArrayBox）
+ * - `findNodes(tag)` - タグを持つノードID一覧（ArrayBox）
+ *
+ * 受信メッセージはノードの受信箱（上限 1024 件）に溜まり、`poll()` を呼んだスレッドで
+ * 優先度の高い順に `handler(intent, from)` として実行される。
+ * 受信箱が満杯の間、inprocess の送信は空きを待ち（`sendTimeout` の時間まで）、
+ * 空かなければ失敗して `false` を返す。
+ *
+ * ## 📨 request / 応答
+ * `request()` で届いたメッセージはハンドラーの戻り値が応答になる
+ * （IntentBox ならそのまま、それ以外は同じ名前の IntentBox の payload）。
+ * 応答は受信箱を経由せず FutureBox に直接入るので、`await` だけで受け取れる。
+ * 送信失敗・タイムアウトでは FutureBox の値が ErrorBox になる
+ * （タイムアウトはプロセスで1本のタイマースレッドが期限順に処理する）。
+ *
+ * ## 📢 トピック・サービス発見（オプトイン）
+ * 個別送信が基本で、ブロードキャストは持たない。`subscribe()` したノードだけが
+ * `publish()` の宛先になる。購読とタグはトランスポート経由で公開され、
+ * inprocess は MessageBus のノード情報、tcp は接続中の相手との交換で共有される。
+ *
+ * ## 🌐 tcp トランスポート
+ * 別プロセスのノードと TCP で通信する。アドレスは nyash.toml の `[p2p.tcp.nodes]`
+ * （自ノードのエントリが待受アドレス）または `addPeer()` で与える。
  * 
  * ## 💡 使用例
  * ```nyash
@@ -25,55 +57,114 @@
  * local bob = new P2PBox("bob", "inprocess")
  * 
  * // 受信ハンドラ登録
- * bob.on("chat.message", function(intent, from) {
- *     print("From " + from + ": " + intent.payload.text)
- * })
+ * bob.on("chat.message", new MethodBox(chat, "onMessage"))   // onMessage(intent, from)
  * 
  * // メッセージ送信
- * local msg = new IntentBox("chat.message", { text: "Hello P2P!" })
+ * local msg = new IntentBox("chat.message", "{\"text\": \"Hello P2P!\"}")
  * alice.send("bob", msg)
+ * bob.poll(0)
+ *
+ * // 要求と応答
+ * local reply = alice.request("bob", new IntentBox("chat.ping", "{}"))
+ * bob.poll(1000)
+ * print((await reply).getPayload())
  * ```
  */
 
-use crate::box_trait::{NyashBox, StringBox, BoolBox, BoxCore, BoxBase};
-use crate::boxes::IntentBox;
-use crate::transport::{Transport, InProcessTransport};
+use crate::box_trait::{NyashBox, StringBox, BoolBox, IntegerBox, ErrorBox, BoxCore, BoxBase};
+use crate::boxes::{ArrayBox, FutureBox, IntentBox};
+use crate::transport::{Transport, InProcessTransport, TcpTransport, IntentEnvelope, Correlation, NodeInfo, SendOpts, TransportError};
 use std::any::Any;
-use std::sync::RwLock;
-use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, RwLock};
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+use once_cell::sync::Lazy;
 
-/// P2PBox - P2P通信ノード (RwLock pattern)
-#[derive(Debug)]
-pub struct P2PBox {
-    base: BoxBase,
-    node_id: RwLock<String>,
-    transport: RwLock<Box<dyn Transport>>,
-    handlers: RwLock<HashMap<String, Box<dyn NyashBox>>>,
+/// 応答待ちの request（相関ID → FutureBox）
+type PendingRequests = Arc<Mutex<HashMap<String, FutureBox>>>;
+
+/// `request()` の既定タイムアウト
+const DEFAULT_REQUEST_TIMEOUT_MS: u64 = 30_000;
+
+/// 受信箱の上限（メッセージ数）
+pub const INBOX_CAPACITY: usize = crate::messaging::DEFAULT_MAILBOX_CAPACITY;
+
+/// request の期限1件
+struct Deadline {
+    id: String,
+    pending: PendingRequests,
+    message: String,
 }
 
-impl Clone for P2PBox {
-    fn clone(&self) -> Self {
-        // State-preserving clone implementation following PR #87 pattern
-        let node_id_val = self.node_id.read().unwrap().clone();
-        // Note: Transport cloning is complex, for now we create a new transport
-        // In a full implementation, we'd need to properly handle transport state
-        let transport_kind = TransportKind::InProcess; // Default for now
-        let new_transport: Box<dyn Transport> = match transport_kind {
-            TransportKind::InProcess => Box::new(InProcessTransport::new(node_id_val.clone())),
-        };
-        let handlers_val = HashMap::new(); // Start fresh for cloned instance
-        
-        Self {
-            base: BoxBase::new(), // New unique ID for clone
-            node_id: RwLock::new(node_id_val),
-            transport: RwLock::new(new_transport),
-            handlers: RwLock::new(handlers_val),
+/// request のタイムアウト処理（プロセスで1本のスレッドが期限順に処理する）
+struct RequestTimer {
+    deadlines: Mutex<BTreeMap<(Instant, u64), Deadline>>,
+    changed: Condvar,
+    next_seq: AtomicU64,
+}
+
+static REQUEST_TIMER: Lazy<RequestTimer> = Lazy::new(|| {
+    // スレッド側の REQUEST_TIMER 参照は初期化の完了を待つ
+    std::thread::Builder::new()
+        .name("nyash-p2p-timer".to_string())
+        .spawn(|| REQUEST_TIMER.run())
+        .expect("failed to spawn p2p request timer");
+    RequestTimer {
+        deadlines: Mutex::new(BTreeMap::new()),
+        changed: Condvar::new(),
+        next_seq: AtomicU64::new(0),
+    }
+});
+
+impl RequestTimer {
+    /// 期限までに応答が無ければ ErrorBox("Timeout") で完了させる
+    fn schedule(&self, at: Instant, deadline: Deadline) {
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        self.deadlines.lock().unwrap().insert((at, seq), deadline);
+        self.changed.notify_all();
+    }
+
+    fn run(&self) {
+        let mut deadlines = self.deadlines.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            let next = deadlines.keys().next().copied();
+            match next {
+                Some(key) if key.0 <= now => {
+                    let expired = deadlines.remove(&key).expect("deadline just seen");
+                    drop(deadlines);
+                    if let Some(future) = expired.pending.lock().unwrap().remove(&expired.id) {
+                        future.set_result(Box::new(ErrorBox::new("Timeout", expired.message)));
+                    }
+                    deadlines = self.deadlines.lock().unwrap();
+                }
+                Some(key) => deadlines = self.changed.wait_timeout(deadlines, key.0 - now).unwrap().0,
+                None => deadlines = self.changed.wait(deadlines).unwrap(),
+            }
         }
     }
 }
+
+/// P2PBox - P2P通信ノード (RwLock pattern)
+///
+/// クローンは同じノード（トランスポート・ハンドラー・受信箱）を共有する。
+#[derive(Debug, Clone)]
+pub struct P2PBox {
+    base: BoxBase,
+    node_id: Arc<RwLock<String>>,
+    transport: Arc<RwLock<Box<dyn Transport>>>,
+    handlers: Arc<RwLock<HashMap<String, Box<dyn NyashBox>>>>,
+    pending: PendingRequests,
+    next_request: Arc<AtomicU64>,
+    /// 公開中のタグ・購読トピック
+    info: Arc<RwLock<NodeInfo>>,
+}
+
 #[derive(Debug, Clone)]
 pub enum TransportKind {
     InProcess,
+    Tcp,
     // 将来: WebSocket, WebRTC, etc.
 }
 
@@ -83,23 +174,57 @@ impl std::str::FromStr for TransportKind {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "inprocess" => Ok(TransportKind::InProcess),
+            "tcp" => Ok(TransportKind::Tcp),
             _ => Err(format!("Unknown transport kind: {}", s)),
         }
     }
 }
 
+impl TransportKind {
+    /// ノード用のトランスポートを作成（Tcp は nyash.toml の `[p2p.tcp.nodes]` を読む）
+    pub fn create(&self, node_id: &str) -> Result<Box<dyn Transport>, TransportError> {
+        Ok(match self {
+            TransportKind::InProcess => Box::new(InProcessTransport::new(node_id.to_string())),
+            TransportKind::Tcp => Box::new(TcpTransport::from_config_file(node_id.to_string())?),
+        })
+    }
+}
+
 impl P2PBox {
     /// 新しいP2PBoxを作成
-    pub fn new(node_id: String, transport_kind: TransportKind) -> Self {
-        let transport: Box<dyn Transport> = match transport_kind {
-            TransportKind::InProcess => Box::new(InProcessTransport::new(node_id.clone())),
-        };
-        
+    pub fn new(node_id: String, transport_kind: TransportKind) -> Result<Self, TransportError> {
+        let transport = transport_kind.create(&node_id)?;
+        Ok(Self::with_transport(node_id, transport))
+    }
+
+    /// 作成済みのトランスポートでノードを作成（受信はトランスポートの受信箱に溜まる）
+    pub fn with_transport(node_id: String, transport: Box<dyn Transport>) -> Self {
+        Self::with_inbox_capacity(node_id, transport, INBOX_CAPACITY)
+    }
+
+    /// 受信箱の上限を指定してノードを作成
+    pub fn with_inbox_capacity(node_id: String, mut transport: Box<dyn Transport>, capacity: usize) -> Self {
+        let pending: PendingRequests = Arc::new(Mutex::new(HashMap::new()));
+        let info = Arc::new(RwLock::new(NodeInfo::new(node_id.clone())));
+        let replies = Arc::clone(&pending);
+        transport.enable_inbox(capacity);
+        // 受信箱を経由しない応答だけがここに届く
+        transport.on_receive(Box::new(move |envelope| {
+            if let Some(Correlation::Reply(id)) = &envelope.correlation {
+                // 待っている FutureBox へ（タイムアウト済みなら捨てる）
+                if let Some(future) = replies.lock().unwrap().remove(id) {
+                    future.set_result(Box::new(envelope.intent));
+                }
+            }
+        }));
         P2PBox {
             base: BoxBase::new(),
-            node_id: RwLock::new(node_id),
-            transport: RwLock::new(transport),
-            handlers: RwLock::new(HashMap::new()),
+            node_id: Arc::new(RwLock::new(node_id)),
+            transport: Arc::new(RwLock::new(transport)),
+            handlers: Arc::new(RwLock::new(HashMap::new())),
+            pending,
+            next_request: Arc::new(AtomicU64::new(1)),
+            info,
         }
     }
     
@@ -111,30 +236,195 @@ impl P2PBox {
     
     /// 特定ノードにメッセージを送信
     pub fn send(&self, to: Box<dyn NyashBox>, intent: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        self.send_with_timeout(to, intent, None)
+    }
+
+    /// タイムアウト（ミリ秒）付きで送信
+    pub fn send_with_timeout(&self, to: Box<dyn NyashBox>, intent: Box<dyn NyashBox>, timeout_ms: Option<u64>) -> Box<dyn NyashBox> {
+        self.send_box(to, intent, SendOpts { timeout_ms, ..Default::default() })
+    }
+
+    /// 優先度付きで送信（大きいほど先に配送）
+    pub fn send_with_priority(&self, to: Box<dyn NyashBox>, intent: Box<dyn NyashBox>, priority: u8) -> Box<dyn NyashBox> {
+        self.send_box(to, intent, SendOpts { priority: Some(priority), ..Default::default() })
+    }
+
+    fn send_box(&self, to: Box<dyn NyashBox>, intent: Box<dyn NyashBox>, opts: SendOpts) -> Box<dyn NyashBox> {
         let to_str = to.to_string_box().value;
         
         // Extract IntentBox from the generic Box
         if let Some(intent_box) = intent.as_any().downcast_ref::<IntentBox>() {
-            let transport = self.transport.read().unwrap();
-            match transport.send(&to_str, intent_box.clone(), Default::default()) {
+            match self.send_intent(&to_str, intent_box.clone(), opts) {
                 Ok(()) => Box::new(BoolBox::new(true)),
-                Err(_) => Box::new(BoolBox::new(false)),
+                Err(e) => {
+                    eprintln!("🚨 P2PBox send to '{}' failed: {:?}", to_str, e);
+                    Box::new(BoolBox::new(false))
+                }
             }
         } else {
             Box::new(BoolBox::new(false))
         }
     }
+
+    /// 要求を送り、応答 IntentBox で完了する FutureBox を返す（失敗・タイムアウト時は ErrorBox）
+    pub fn request(&self, to: Box<dyn NyashBox>, intent: Box<dyn NyashBox>, timeout_ms: Option<u64>) -> Box<dyn NyashBox> {
+        let future = FutureBox::new();
+        let Some(intent_box) = intent.as_any().downcast_ref::<IntentBox>() else {
+            future.set_result(Box::new(ErrorBox::new("TypeError", "request() expects an IntentBox")));
+            return Box::new(future);
+        };
+        let to_str = to.to_string_box().value;
+        let id = format!("{}#{}", self.node_id.read().unwrap(), self.next_request.fetch_add(1, Ordering::Relaxed));
+        self.pending.lock().unwrap().insert(id.clone(), future.clone());
+
+        let opts = SendOpts { timeout_ms, correlation: Some(Correlation::Request(id.clone())), ..Default::default() };
+        if let Err(e) = self.send_intent(&to_str, intent_box.clone(), opts) {
+            self.pending.lock().unwrap().remove(&id);
+            future.set_result(Box::new(ErrorBox::new("TransportError", format!("{:?}", e))));
+            return Box::new(future);
+        }
+        let timeout_ms = timeout_ms.unwrap_or(DEFAULT_REQUEST_TIMEOUT_MS);
+        let message = format!("No reply from '{}' within {}ms", to_str, timeout_ms);
+        REQUEST_TIMER.schedule(
+            Instant::now() + Duration::from_millis(timeout_ms),
+            Deadline { id, pending: Arc::clone(&self.pending), message },
+        );
+        Box::new(future)
+    }
+
+    /// request への応答を送る
+    pub fn reply(&self, to: &str, request_id: String, intent: IntentBox) -> Result<(), TransportError> {
+        let opts = SendOpts { correlation: Some(Correlation::Reply(request_id)), ..Default::default() };
+        self.send_intent(to, intent, opts)
+    }
+
+    /// Rust向け: エラー詳細付きの送信
+    pub fn send_intent(&self, to: &str, intent: IntentBox, opts: SendOpts) -> Result<(), TransportError> {
+        self.transport.read().unwrap().send(to, intent, opts)
+    }
     
     /// イベントハンドラーを登録
     pub fn on(&self, intent_name: Box<dyn NyashBox>, handler: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
         let intent_str = intent_name.to_string_box().value;
-        
-        // For now, we'll store a simplified handler representation
-        // In a full implementation, this would need proper IntentHandler integration
         let mut handlers = self.handlers.write().unwrap();
         handlers.insert(intent_str, handler);
         Box::new(BoolBox::new(true))
     }
+
+    /// intent名に登録されたハンドラー
+    pub fn handler_for(&self, intent_name: &str) -> Option<Box<dyn NyashBox>> {
+        self.handlers.read().unwrap().get(intent_name).map(|h| h.clone_or_share())
+    }
+
+    /// 受信箱に溜まっている分を優先度順に取り出す。空なら最初の1件を `wait` まで待つ
+    pub fn take_received(&self, wait: Duration) -> Vec<IntentEnvelope> {
+        let transport = self.transport.read().unwrap();
+        let deadline = Instant::now() + wait;
+        let mut received = Vec::new();
+        loop {
+            let wait = if received.is_empty() { deadline.saturating_duration_since(Instant::now()) } else { Duration::ZERO };
+            let Some(envelope) = transport.take(wait) else { break };
+            // 購読をやめたトピックへの配信は捨てる
+            if let Some(topic) = &envelope.topic {
+                if !self.info.read().unwrap().topics.contains(topic) {
+                    continue;
+                }
+            }
+            received.push(envelope);
+        }
+        received
+    }
+
+    /// 相手ノードのアドレスを登録（tcp）
+    pub fn add_peer(&self, node_id: Box<dyn NyashBox>, address: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        let transport = self.transport.read().unwrap();
+        match transport.add_peer(&node_id.to_string_box().value, &address.to_string_box().value) {
+            Ok(()) => Box::new(BoolBox::new(true)),
+            Err(e) => {
+                eprintln!("🚨 P2PBox addPeer failed: {:?}", e);
+                Box::new(BoolBox::new(false))
+            }
+        }
+    }
+
+    /// トピックを購読する
+    pub fn subscribe(&self, topic: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        let topic = topic.to_string_box().value;
+        self.update_info(|info| { info.topics.insert(topic); })
+    }
+
+    /// トピックの購読をやめる
+    pub fn unsubscribe(&self, topic: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        let topic = topic.to_string_box().value;
+        self.update_info(|info| { info.topics.remove(&topic); })
+    }
+
+    /// 能力タグを公開する
+    pub fn add_tag(&self, tag: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        let tag = tag.to_string_box().value;
+        self.update_info(|info| { info.tags.insert(tag); })
+    }
+
+    /// 能力タグを取り消す
+    pub fn remove_tag(&self, tag: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        let tag = tag.to_string_box().value;
+        self.update_info(|info| { info.tags.remove(&tag); })
+    }
+
+    /// 公開情報を更新してトランスポートへ announce
+    fn update_info(&self, change: impl FnOnce(&mut NodeInfo)) -> Box<dyn NyashBox> {
+        let info = {
+            let mut info = self.info.write().unwrap();
+            change(&mut info);
+            info.clone()
+        };
+        match self.transport.read().unwrap().announce(info) {
+            Ok(()) => Box::new(BoolBox::new(true)),
+            Err(e) => {
+                eprintln!("🚨 P2PBox announce failed: {:?}", e);
+                Box::new(BoolBox::new(false))
+            }
+        }
+    }
+
+    /// トピックの購読ノード全員へ送信し、送れた数を返す
+    pub fn publish(&self, topic: Box<dyn NyashBox>, intent: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        let topic = topic.to_string_box().value;
+        let Some(intent_box) = intent.as_any().downcast_ref::<IntentBox>() else {
+            return Box::new(IntegerBox::new(0));
+        };
+        let mut delivered = 0;
+        for node in self.known_nodes().into_iter().filter(|n| n.topics.contains(&topic)) {
+            let opts = SendOpts { topic: Some(topic.clone()), ..Default::default() };
+            match self.send_intent(&node.node_id, intent_box.clone(), opts) {
+                Ok(()) => delivered += 1,
+                Err(e) => eprintln!("🚨 P2PBox publish to '{}' failed: {:?}", node.node_id, e),
+            }
+        }
+        Box::new(IntegerBox::new(delivered))
+    }
+
+    /// トランスポートが知っているノード（自ノードを含む、ID順）
+    pub fn known_nodes(&self) -> Vec<NodeInfo> {
+        self.transport.read().unwrap().known_nodes()
+    }
+
+    /// 知っているノードID一覧
+    pub fn get_nodes(&self) -> Box<dyn NyashBox> {
+        Self::node_ids(self.known_nodes())
+    }
+
+    /// タグを持つノードID一覧
+    pub fn find_nodes(&self, tag: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        let tag = tag.to_string_box().value;
+        Self::node_ids(self.known_nodes().into_iter().filter(|n| n.tags.contains(&tag)).collect())
+    }
+
+    fn node_ids(nodes: Vec<NodeInfo>) -> Box<dyn NyashBox> {
+        let ids = nodes.into_iter().map(|n| Box::new(StringBox::new(n.node_id)) as Box<dyn NyashBox>).collect();
+        Box::new(ArrayBox::new_with_elements(ids))
+    }
+
     /// ノードが到達可能かチェック
     pub fn is_reachable(&self, node_id: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
         let node_str = node_id.to_string_box().value;
@@ -152,11 +442,13 @@ impl P2PBox {
 
 
 impl NyashBox for P2PBox {
+    fn is_identity(&self) -> bool { true }
+
     fn clone_box(&self) -> Box<dyn NyashBox> {
         Box::new(self.clone())
     }
     
-    /// 仮実装: clone_boxと同じ（後で修正）
+    /// 状態共有: 同じノードを指す
     fn share_box(&self) -> Box<dyn NyashBox> {
         self.clone_box()
     }
@@ -169,7 +461,7 @@ impl NyashBox for P2PBox {
     
     fn equals(&self, other: &dyn NyashBox) -> BoolBox {
         if let Some(other_p2p) = other.as_any().downcast_ref::<P2PBox>() {
-            BoolBox::new(self.base.id == other_p2p.base.id)
+            BoolBox::new(Arc::ptr_eq(&self.transport, &other_p2p.transport))
         } else {
             BoolBox::new(false)
         }
@@ -0,0 +1,145 @@
+/*! 🖼️ PNG書き出し（依存なしの最小実装）
+ *
+ * QRBox などネイティブで画像を出力するBox向け。
+ * 圧縮は行わず、zlib の無圧縮ブロック（stored）で IDAT を作る。
+ */
+
+/// RGBA 8bit の画素列（`width * height * 4` バイト）を PNG にする
+pub fn encode_rgba(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    assert_eq!(rgba.len(), width as usize * height as usize * 4, "RGBA buffer size mismatch");
+    let row_len = width as usize * 4;
+    let mut raw = Vec::with_capacity((row_len + 1) * height as usize);
+    for row in rgba.chunks(row_len.max(1)).take(height as usize) {
+        raw.push(0); // filter: None
+        raw.extend_from_slice(row);
+    }
+    encode(width, height, 8, 6, None, &raw)
+}
+
+/// パレット形式。`indices` は1画素1バイトのパレット番号（`width * height`）。
+/// ビット深度はパレット数から 1/2/4/8 を選ぶ
+pub fn encode_indexed(width: u32, height: u32, palette: &[[u8; 3]], indices: &[u8]) -> Vec<u8> {
+    assert!(!palette.is_empty() && palette.len() <= 256, "palette must have 1..=256 entries");
+    assert_eq!(indices.len(), width as usize * height as usize, "index buffer size mismatch");
+    let depth: u8 = match palette.len() {
+        0..=2 => 1,
+        3..=4 => 2,
+        5..=16 => 4,
+        _ => 8,
+    };
+    let per_byte = 8 / depth as usize;
+    let mut raw = Vec::new();
+    for row in indices.chunks(width.max(1) as usize).take(height as usize) {
+        raw.push(0);
+        for group in row.chunks(per_byte) {
+            let mut byte = 0u8;
+            for (i, &index) in group.iter().enumerate() {
+                byte |= index << (8 - depth as usize * (i + 1));
+            }
+            raw.push(byte);
+        }
+    }
+    let plte: Vec<u8> = palette.iter().flatten().copied().collect();
+    encode(width, height, depth, 3, Some(&plte), &raw)
+}
+
+fn encode(width: u32, height: u32, depth: u8, color_type: u8, palette: Option<&[u8]>, raw: &[u8]) -> Vec<u8> {
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    ihdr.extend_from_slice(&[depth, color_type, 0, 0, 0]);
+    write_chunk(&mut png, b"IHDR", &ihdr);
+    if let Some(palette) = palette {
+        write_chunk(&mut png, b"PLTE", palette);
+    }
+    write_chunk(&mut png, b"IDAT", &zlib_stored(raw));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// zlib ストリーム（無圧縮ブロックのみ）
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xFFFF).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        out.push(u8::from(blocks.peek().is_none()));
+        let len = block.len() as u16;
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// "#rrggbb" / "#rgb" / 基本色名を RGB に
+pub fn parse_color(color: &str) -> Option<[u8; 3]> {
+    let color = color.trim();
+    if let Some(hex) = color.strip_prefix('#') {
+        let digits: Option<Vec<u8>> = hex.chars().map(|c| c.to_digit(16).map(|d| d as u8)).collect();
+        return match digits?.as_slice() {
+            [r, g, b] => Some([r * 17, g * 17, b * 17]),
+            [r1, r2, g1, g2, b1, b2] => Some([r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2]),
+            _ => None,
+        };
+    }
+    Some(match color.to_ascii_lowercase().as_str() {
+        "black" => [0, 0, 0],
+        "white" => [255, 255, 255],
+        "red" => [255, 0, 0],
+        "green" => [0, 128, 0],
+        "blue" => [0, 0, 255],
+        "yellow" => [255, 255, 0],
+        "cyan" => [0, 255, 255],
+        "magenta" => [255, 0, 255],
+        "gray" | "grey" => [128, 128, 128],
+        "orange" => [255, 165, 0],
+        "purple" => [128, 0, 128],
+        "lime" => [0, 255, 0],
+        "navy" => [0, 0, 128],
+        "teal" => [0, 128, 128],
+        "maroon" => [128, 0, 0],
+        "olive" => [128, 128, 0],
+        "silver" => [192, 192, 192],
+        "brown" => [165, 42, 42],
+        "pink" => [255, 192, 203],
+        _ => return None,
+    })
+}
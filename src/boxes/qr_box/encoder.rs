@@ -0,0 +1,515 @@
+/*! 🔳 QRコードエンコーダ（ISO/IEC 18004, バイトモード）
+ *
+ * - バージョン 1〜40 から、データが入る最小のものを選ぶ
+ * - エラー訂正レベル L/M/Q/H（Reed-Solomon, GF(256) 原始多項式 0x11D）
+ * - 8種類のマスクからペナルティ最小のものを選ぶ
+ */
+
+/// エラー訂正レベル
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EccLevel {
+    L,
+    M,
+    Q,
+    H,
+}
+
+impl EccLevel {
+    /// "L" / "M" / "Q" / "H"（大文字小文字は問わない）
+    pub fn parse(level: &str) -> Option<Self> {
+        match level.trim().to_ascii_uppercase().as_str() {
+            "L" => Some(EccLevel::L),
+            "M" => Some(EccLevel::M),
+            "Q" => Some(EccLevel::Q),
+            "H" => Some(EccLevel::H),
+            _ => None,
+        }
+    }
+
+    fn ordinal(self) -> usize {
+        match self {
+            EccLevel::L => 0,
+            EccLevel::M => 1,
+            EccLevel::Q => 2,
+            EccLevel::H => 3,
+        }
+    }
+
+    /// 形式情報に書く2ビット
+    fn format_bits(self) -> u32 {
+        match self {
+            EccLevel::L => 1,
+            EccLevel::M => 0,
+            EccLevel::Q => 3,
+            EccLevel::H => 2,
+        }
+    }
+}
+
+/// ブロックあたりの誤り訂正コード語数 [レベル][バージョン]
+const ECC_CODEWORDS_PER_BLOCK: [[u8; 41]; 4] = [
+    [0, 7, 10, 15, 20, 26, 18, 20, 24, 30, 18, 20, 24, 26, 30, 22, 24, 28, 30, 28, 28, 28, 28, 30, 30, 26, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+    [0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28],
+    [0, 13, 22, 18, 26, 18, 24, 18, 22, 20, 24, 28, 26, 24, 20, 30, 24, 28, 28, 26, 30, 28, 30, 30, 30, 30, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+    [0, 17, 28, 22, 16, 22, 28, 26, 26, 24, 28, 24, 28, 22, 24, 24, 30, 28, 28, 26, 28, 30, 24, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+];
+
+/// 誤り訂正ブロック数 [レベル][バージョン]
+const NUM_ERROR_CORRECTION_BLOCKS: [[u8; 41]; 4] = [
+    [0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 4, 4, 4, 6, 6, 6, 6, 7, 8, 8, 9, 9, 10, 12, 12, 12, 13, 14, 15, 16, 17, 18, 19, 19, 20, 21, 22, 24, 25],
+    [0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21, 23, 25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49],
+    [0, 1, 1, 2, 2, 4, 4, 6, 6, 8, 8, 8, 10, 12, 16, 12, 17, 16, 18, 21, 20, 23, 23, 25, 27, 29, 34, 34, 35, 38, 40, 43, 45, 48, 51, 53, 56, 59, 62, 65, 68],
+    [0, 1, 1, 2, 4, 4, 4, 5, 6, 8, 8, 11, 11, 16, 16, 18, 16, 19, 21, 25, 25, 25, 34, 30, 32, 35, 37, 40, 42, 45, 48, 51, 54, 57, 60, 63, 66, 70, 74, 77, 81],
+];
+
+const PENALTY_N1: i32 = 3;
+const PENALTY_N2: i32 = 3;
+const PENALTY_N3: i32 = 40;
+const PENALTY_N4: i32 = 10;
+
+/// 符号化済みのQRコード（モジュール行列）
+#[derive(Debug, Clone)]
+pub struct QrCode {
+    version: u8,
+    size: usize,
+    ecc: EccLevel,
+    mask: u8,
+    modules: Vec<bool>,
+    is_function: Vec<bool>,
+}
+
+impl QrCode {
+    /// バイト列をバイトモードで符号化する（入る最小バージョンを選択）
+    pub fn encode_bytes(data: &[u8], ecc: EccLevel) -> Result<QrCode, String> {
+        let version = (1..=40u8)
+            .find(|&v| data_bits_needed(data.len(), v) <= num_data_codewords(v, ecc) * 8)
+            .ok_or_else(|| format!(
+                "data too long for a QR code: {} bytes (max {} at level {:?})",
+                data.len(), (num_data_codewords(40, ecc) * 8 - 4 - 16) / 8, ecc
+            ))?;
+
+        // モード指示子(0100) + 文字数 + データ + 終端 + パディング
+        let capacity = num_data_codewords(version, ecc) * 8;
+        let mut bits = BitBuffer::default();
+        bits.append(0b0100, 4);
+        bits.append(data.len() as u32, if version <= 9 { 8 } else { 16 });
+        for &b in data {
+            bits.append(b as u32, 8);
+        }
+        bits.append(0, (capacity - bits.len()).min(4));
+        bits.append(0, (8 - bits.len() % 8) % 8);
+        for pad in [0xEC, 0x11].iter().cycle() {
+            if bits.len() >= capacity {
+                break;
+            }
+            bits.append(*pad, 8);
+        }
+        let codewords = add_ecc_and_interleave(&bits.to_bytes(), version, ecc);
+
+        let size = version as usize * 4 + 17;
+        let mut qr = QrCode {
+            version,
+            size,
+            ecc,
+            mask: 0,
+            modules: vec![false; size * size],
+            is_function: vec![false; size * size],
+        };
+        qr.draw_function_patterns();
+        qr.draw_codewords(&codewords);
+
+        let mut best = (i32::MAX, 0u8);
+        for mask in 0..8u8 {
+            qr.apply_mask(mask);
+            qr.draw_format_bits(mask);
+            let penalty = qr.penalty_score();
+            if penalty < best.0 {
+                best = (penalty, mask);
+            }
+            qr.apply_mask(mask); // XOR なのでもう一度で元に戻る
+        }
+        qr.mask = best.1;
+        qr.apply_mask(best.1);
+        qr.draw_format_bits(best.1);
+        Ok(qr)
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// 1辺のモジュール数（クワイエットゾーンを含まない）
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn ecc(&self) -> EccLevel {
+        self.ecc
+    }
+
+    pub fn mask(&self) -> u8 {
+        self.mask
+    }
+
+    /// (x, y) が暗モジュールか（範囲外は明）
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        x < self.size && y < self.size && self.modules[y * self.size + x]
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        let i = y * self.size + x;
+        self.modules[i] = dark;
+        self.is_function[i] = true;
+    }
+
+    fn draw_function_patterns(&mut self) {
+        let size = self.size;
+        // タイミングパターン
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+        // 位置検出パターン（分離パターン込み）
+        self.draw_finder(3, 3);
+        self.draw_finder(size - 4, 3);
+        self.draw_finder(3, size - 4);
+        // 位置合わせパターン（位置検出パターンと重なる3箇所を除く）
+        let positions = alignment_positions(self.version);
+        let last = positions.len().saturating_sub(1);
+        for (i, &x) in positions.iter().enumerate() {
+            for (j, &y) in positions.iter().enumerate() {
+                let overlaps_finder = (i, j) == (0, 0) || (i == 0 && j == last) || (i == last && j == 0);
+                if !overlaps_finder {
+                    self.draw_alignment(x, y);
+                }
+            }
+        }
+        // 形式情報の予約（仮にマスク0）とバージョン情報
+        self.draw_format_bits(0);
+        self.draw_version();
+    }
+
+    fn draw_finder(&mut self, cx: usize, cy: usize) {
+        for dy in -4i32..=4 {
+            for dx in -4i32..=4 {
+                let (x, y) = (cx as i32 + dx, cy as i32 + dy);
+                if (0..self.size as i32).contains(&x) && (0..self.size as i32).contains(&y) {
+                    let dist = dx.abs().max(dy.abs());
+                    self.set_function(x as usize, y as usize, dist != 2 && dist != 4);
+                }
+            }
+        }
+    }
+
+    fn draw_alignment(&mut self, cx: usize, cy: usize) {
+        for dy in -2i32..=2 {
+            for dx in -2i32..=2 {
+                let (x, y) = ((cx as i32 + dx) as usize, (cy as i32 + dy) as usize);
+                self.set_function(x, y, dx.abs().max(dy.abs()) != 1);
+            }
+        }
+    }
+
+    /// 形式情報（レベル + マスク, BCH(15,5)）を2箇所に書く
+    fn draw_format_bits(&mut self, mask: u8) {
+        let data = (self.ecc.format_bits() << 3) | mask as u32;
+        let mut rem = data;
+        for _ in 0..10 {
+            rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+        }
+        let bits = ((data << 10) | rem) ^ 0x5412;
+        let bit = |i: usize| (bits >> i) & 1 != 0;
+        let size = self.size;
+
+        for i in 0..=5 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true); // 常に暗
+    }
+
+    /// バージョン情報（7以上, BCH(18,6)）
+    fn draw_version(&mut self) {
+        if self.version < 7 {
+            return;
+        }
+        let mut rem = self.version as u32;
+        for _ in 0..12 {
+            rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
+        }
+        let bits = ((self.version as u32) << 12) | rem;
+        for i in 0..18 {
+            let dark = (bits >> i) & 1 != 0;
+            let (a, b) = (self.size - 11 + i % 3, i / 3);
+            self.set_function(a, b, dark);
+            self.set_function(b, a, dark);
+        }
+    }
+
+    /// 右下から2列ずつ上下にジグザグに配置
+    fn draw_codewords(&mut self, data: &[u8]) {
+        let size = self.size as i32;
+        let total_bits = data.len() * 8;
+        let mut i = 0;
+        let mut right = size - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            for vert in 0..size {
+                for j in 0..2 {
+                    let x = (right - j) as usize;
+                    let upward = ((right + 1) & 2) == 0;
+                    let y = if upward { size - 1 - vert } else { vert } as usize;
+                    let index = y * self.size + x;
+                    if !self.is_function[index] && i < total_bits {
+                        self.modules[index] = (data[i >> 3] >> (7 - (i & 7))) & 1 != 0;
+                        i += 1;
+                    }
+                }
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u8) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let i = y * self.size + x;
+                if invert && !self.is_function[i] {
+                    self.modules[i] = !self.modules[i];
+                }
+            }
+        }
+    }
+
+    fn penalty_score(&self) -> i32 {
+        let size = self.size as i32;
+        let mut result = 0;
+
+        // 同色の連続（行・列）と 1:1:3:1:1 パターン
+        for transpose in [false, true] {
+            for a in 0..self.size {
+                let mut run_color = false;
+                let mut run_len = 0;
+                let mut history = [0i32; 7];
+                for b in 0..self.size {
+                    let dark = if transpose { self.get(a, b) } else { self.get(b, a) };
+                    if dark == run_color {
+                        run_len += 1;
+                        if run_len == 5 {
+                            result += PENALTY_N1;
+                        } else if run_len > 5 {
+                            result += 1;
+                        }
+                    } else {
+                        add_run_history(run_len, &mut history, size);
+                        if !run_color {
+                            result += count_finder_like(&history) * PENALTY_N3;
+                        }
+                        run_color = dark;
+                        run_len = 1;
+                    }
+                }
+                // 行末は明モジュールが続くとみなす
+                if run_color {
+                    add_run_history(run_len, &mut history, size);
+                    run_len = 0;
+                }
+                add_run_history(run_len + size, &mut history, size);
+                result += count_finder_like(&history) * PENALTY_N3;
+            }
+        }
+
+        // 2x2 の同色ブロック
+        for y in 0..self.size - 1 {
+            for x in 0..self.size - 1 {
+                let c = self.get(x, y);
+                if c == self.get(x + 1, y) && c == self.get(x, y + 1) && c == self.get(x + 1, y + 1) {
+                    result += PENALTY_N2;
+                }
+            }
+        }
+
+        // 暗モジュールの割合（50% から 5% 刻みで離れるごとに加算）
+        let dark = self.modules.iter().filter(|&&m| m).count() as i32;
+        let total = size * size;
+        let k = ((dark * 20 - total * 10).abs() + total - 1) / total - 1;
+        result + k * PENALTY_N4
+    }
+}
+
+fn add_run_history(mut run_len: i32, history: &mut [i32; 7], size: i32) {
+    if history[0] == 0 {
+        run_len += size; // 行頭の前も明モジュールとみなす
+    }
+    history.copy_within(0..6, 1);
+    history[0] = run_len;
+}
+
+fn count_finder_like(history: &[i32; 7]) -> i32 {
+    let n = history[1];
+    let core = n > 0 && history[2] == n && history[3] == n * 3 && history[4] == n && history[5] == n;
+    (core && history[0] >= n * 4 && history[6] >= n) as i32 + (core && history[6] >= n * 4 && history[0] >= n) as i32
+}
+
+fn data_bits_needed(len: usize, version: u8) -> usize {
+    let count_bits = if version <= 9 { 8 } else { 16 };
+    if len >= 1 << count_bits {
+        return usize::MAX;
+    }
+    4 + count_bits + len * 8
+}
+
+/// 機能パターン以外のモジュール数（データ + 誤り訂正 + 余りビット）
+fn num_raw_data_modules(version: u8) -> usize {
+    let v = version as usize;
+    let mut result = (16 * v + 128) * v + 64;
+    if v >= 2 {
+        let num_align = v / 7 + 2;
+        result -= (25 * num_align - 10) * num_align - 55;
+        if v >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+fn num_data_codewords(version: u8, ecc: EccLevel) -> usize {
+    let e = ecc.ordinal();
+    let v = version as usize;
+    num_raw_data_modules(version) / 8
+        - ECC_CODEWORDS_PER_BLOCK[e][v] as usize * NUM_ERROR_CORRECTION_BLOCKS[e][v] as usize
+}
+
+fn alignment_positions(version: u8) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+    let v = version as usize;
+    let num_align = v / 7 + 2;
+    let step = (v * 8 + num_align * 3 + 5) / (num_align * 4 - 4) * 2;
+    let size = v * 4 + 17;
+    let mut result: Vec<usize> = (0..num_align - 1).map(|i| size - 7 - i * step).collect();
+    result.push(6);
+    result.reverse();
+    result
+}
+
+/// ブロック分割 → 各ブロックに誤り訂正コード語 → インターリーブ
+fn add_ecc_and_interleave(data: &[u8], version: u8, ecc: EccLevel) -> Vec<u8> {
+    let e = ecc.ordinal();
+    let v = version as usize;
+    let num_blocks = NUM_ERROR_CORRECTION_BLOCKS[e][v] as usize;
+    let block_ecc_len = ECC_CODEWORDS_PER_BLOCK[e][v] as usize;
+    let raw_codewords = num_raw_data_modules(version) / 8;
+    let num_short_blocks = num_blocks - raw_codewords % num_blocks;
+    let short_block_len = raw_codewords / num_blocks;
+
+    let divisor = reed_solomon_divisor(block_ecc_len);
+    let mut blocks: Vec<Vec<u8>> = Vec::with_capacity(num_blocks);
+    let mut k = 0;
+    for i in 0..num_blocks {
+        let data_len = short_block_len - block_ecc_len + usize::from(i >= num_short_blocks);
+        let mut block = data[k..k + data_len].to_vec();
+        k += data_len;
+        let ecc_bytes = reed_solomon_remainder(&block, &divisor);
+        if i < num_short_blocks {
+            block.push(0); // 長いブロックと列をそろえるための詰め物（出力しない）
+        }
+        block.extend_from_slice(&ecc_bytes);
+        blocks.push(block);
+    }
+
+    let mut result = Vec::with_capacity(raw_codewords);
+    for i in 0..=short_block_len {
+        for (j, block) in blocks.iter().enumerate() {
+            if i != short_block_len - block_ecc_len || j >= num_short_blocks {
+                result.push(block[i]);
+            }
+        }
+    }
+    result
+}
+
+fn reed_solomon_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0u8; degree - 1];
+    result.push(1);
+    let mut root = 1u8;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_multiply(result[j], root);
+            if j + 1 < result.len() {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+    result
+}
+
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0u8; divisor.len()];
+    for &b in data {
+        let factor = b ^ result.remove(0);
+        result.push(0);
+        for (r, &d) in result.iter_mut().zip(divisor) {
+            *r ^= gf_multiply(d, factor);
+        }
+    }
+    result
+}
+
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut z: u8 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x1D);
+        z ^= ((y >> i) & 1) * x;
+    }
+    z
+}
+
+#[derive(Default)]
+struct BitBuffer {
+    bits: Vec<bool>,
+}
+
+impl BitBuffer {
+    fn append(&mut self, value: u32, len: usize) {
+        for i in (0..len).rev() {
+            self.bits.push((value >> i) & 1 != 0);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.bits.chunks(8)
+            .map(|chunk| chunk.iter().enumerate().fold(0u8, |acc, (i, &b)| acc | ((b as u8) << (7 - i))))
+            .collect()
+    }
+}
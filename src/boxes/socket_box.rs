@@ -420,6 +420,12 @@ impl SocketBox {
         }
     }
     
+    /// 接続済みストリームを取り出す（HTTPServerBoxのワーカースレッド用）
+    pub(crate) fn take_stream(&self) -> Option<TcpStream> {
+        *self.is_connected.write().unwrap() = false;
+        self.stream.write().unwrap().take()
+    }
+
     /// ソケット閉鎖
     pub fn close(&self) -> Box<dyn NyashBox> {
         *self.stream.write().unwrap() = None;
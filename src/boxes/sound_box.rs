@@ -13,6 +13,16 @@
  * - `playTone(frequency, duration)` - 指定周波数・時間で音生成
  * - `playFile(filename)` - 音声ファイル再生
  * - `setVolume(level)` - 音量設定 (0.0-1.0)
+ *
+ * ### 💾 録音（オフライン合成）
+ * 録音を始めると、以後に鳴らした音が合成タイムラインに記録され、WAV として書き出せる。
+ * 録音は既定では行わない（`record()` か `setPlayback(false)` で始まる）。
+ * - `record()` - 録音を始める
+ * - `setPlayback(enabled)` - false で端末ベルと待ち時間を止め、記録だけ行う（録音も始まる）
+ * - `render(path)` - 記録した音を WAV 保存（ResultBox。何も記録していなければ Err）
+ * - `getSamples()` - 16bit リトルエンディアン PCM の BufferBox
+ * - `getDuration()` - 記録の長さ（ms）
+ * - `clear()` - 記録を消す
  * 
  * ## 💡 使用例
  * ```nyash
@@ -138,29 +148,94 @@
  * - Web環境では制限が多い（ユーザー操作後のみ音声再生可能）
  */
 
-use crate::box_trait::{NyashBox, StringBox, IntegerBox, BoolBox, BoxCore, BoxBase};
+use crate::box_trait::{NyashBox, StringBox, IntegerBox, BoolBox, VoidBox, BoxCore, BoxBase};
+use crate::boxes::buffer::BufferBox;
+use crate::boxes::math_box::FloatBox;
+use crate::boxes::result::NyashResultBox;
+use crate::boxes::synth::{Synth, Waveform};
 use std::fmt::{Debug, Display};
 use std::any::Any;
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::Duration;
 
+/// ビープ音の周波数
+const BEEP_HZ: f64 = 800.0;
+/// ビープ音1回の記録上の長さ（ms）
+const BEEP_MS: f64 = 100.0;
+
 /// 音響効果を提供するBox
 #[derive(Debug, Clone)]
 pub struct SoundBox {
     base: BoxBase,
+    /// 鳴らした音の記録（clone 間で共有）
+    synth: Arc<Mutex<Synth>>,
+    /// false なら端末ベル・外部コマンド・待ち時間を省略して記録だけ行う
+    playback: Arc<AtomicBool>,
+    /// true の間だけタイムラインに記録する（既定 false）
+    recording: Arc<AtomicBool>,
 }
 
 impl SoundBox {
     pub fn new() -> Self {
+        let mut synth = Synth::new();
+        synth.set_waveform(Waveform::Square);
         Self { 
-            base: BoxBase::new() 
+            base: BoxBase::new(),
+            synth: Arc::new(Mutex::new(synth)),
+            playback: Arc::new(AtomicBool::new(true)),
+            recording: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// 記録用の合成エンジン
+    pub fn synth(&self) -> MutexGuard<'_, Synth> {
+        self.synth.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn is_playback_enabled(&self) -> bool {
+        self.playback.load(Ordering::Relaxed)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.load(Ordering::Relaxed)
+    }
+
+    /// 録音中ならタイムラインに1音記録
+    fn record_tone(&self, frequency: f64, duration_ms: f64) {
+        if self.is_recording() {
+            self.synth().play_tone(frequency, duration_ms);
+        }
+    }
+
+    /// 1音鳴らす: 端末ベル（再生時）と記録（録音中）
+    fn ring(&self, frequency: f64, duration_ms: f64) {
+        if self.is_playback_enabled() {
+            print!("\x07");
+        }
+        self.record_tone(frequency, duration_ms);
+    }
+
+    /// 再生時だけ実時間で待つ（記録は進めない）
+    fn wait(&self, duration_ms: u64) {
+        if self.is_playback_enabled() {
+            std::thread::sleep(Duration::from_millis(duration_ms));
         }
     }
+
+    /// 無音区間: 録音中なら記録を進め、再生時は待つ
+    fn pause(&self, duration_ms: u64) {
+        if self.is_recording() {
+            self.synth().rest(duration_ms as f64);
+        }
+        self.wait(duration_ms);
+    }
     
     /// ビープ音を鳴らす（基本）
     pub fn beep(&self) -> Box<dyn NyashBox> {
         // 端末ベル文字を出力
-        print!("\x07");
+        self.ring(BEEP_HZ, BEEP_MS);
         Box::new(StringBox::new("Beep!"))
     }
     
@@ -172,9 +247,9 @@ impl SoundBox {
             }
             
             for i in 0..count_int.value {
-                print!("\x07");
+                self.ring(BEEP_HZ, BEEP_MS);
                 if i < count_int.value - 1 {
-                    std::thread::sleep(Duration::from_millis(100));
+                    self.pause(100);
                 }
             }
             
@@ -194,6 +269,11 @@ impl SoundBox {
                 return Box::new(StringBox::new("Frequency and duration must be positive"));
             }
             
+            self.record_tone(freq_int.value as f64, dur_int.value as f64);
+            if !self.is_playback_enabled() {
+                return Box::new(StringBox::new(format!("Recorded {}Hz for {}ms", freq_int.value, dur_int.value)));
+            }
+            
             // Linuxのbeepコマンドを試行
             match Command::new("beep")
                 .arg("-f")
@@ -219,9 +299,9 @@ impl SoundBox {
     pub fn alert(&self) -> Box<dyn NyashBox> {
         // 3回短いビープ
         for i in 0..3 {
-            print!("\x07");
+            self.ring(1000.0, BEEP_MS);
             if i < 2 {
-                std::thread::sleep(Duration::from_millis(150));
+                self.pause(150);
             }
         }
         Box::new(StringBox::new("Alert sound played"))
@@ -230,18 +310,18 @@ impl SoundBox {
     /// 成功音
     pub fn success(&self) -> Box<dyn NyashBox> {
         // 1回長めのビープ
-        print!("\x07");
-        std::thread::sleep(Duration::from_millis(50));
-        print!("\x07");
+        self.ring(660.0, BEEP_MS);
+        self.pause(50);
+        self.ring(880.0, 200.0);
         Box::new(StringBox::new("Success sound played"))
     }
     
     /// エラー音
     pub fn error(&self) -> Box<dyn NyashBox> {
         // 2回素早いビープ
-        print!("\x07");
-        std::thread::sleep(Duration::from_millis(80));
-        print!("\x07");
+        self.ring(220.0, BEEP_MS);
+        self.pause(80);
+        self.ring(220.0, BEEP_MS);
         Box::new(StringBox::new("Error sound played"))
     }
     
@@ -254,19 +334,19 @@ impl SoundBox {
                 match ch {
                     '.' => {
                         // 短いビープ
-                        print!("\x07");
-                        std::thread::sleep(Duration::from_millis(100));
+                        self.ring(BEEP_HZ, 100.0);
+                        self.wait(100);
                         beep_count += 1;
                     }
                     '-' => {
                         // 長いビープ
-                        print!("\x07");
-                        std::thread::sleep(Duration::from_millis(300));
+                        self.ring(BEEP_HZ, 300.0);
+                        self.wait(300);
                         beep_count += 1;
                     }
                     ' ' => {
                         // 無音（待機）
-                        std::thread::sleep(Duration::from_millis(200));
+                        self.pause(200);
                     }
                     _ => {
                         // その他の文字は無視
@@ -274,7 +354,7 @@ impl SoundBox {
                 }
                 
                 // 文字間の短い間隔
-                std::thread::sleep(Duration::from_millis(50));
+                self.pause(50);
             }
             
             Box::new(StringBox::new(&format!("Played pattern '{}' ({} beeps)", pattern_str.value, beep_count)))
@@ -285,7 +365,7 @@ impl SoundBox {
     
     /// システム音量チェック（簡易）
     pub fn volumeTest(&self) -> Box<dyn NyashBox> {
-        print!("\x07");
+        self.ring(440.0, 500.0);
         Box::new(StringBox::new("Volume test beep - can you hear it?"))
     }
     
@@ -300,9 +380,9 @@ impl SoundBox {
             }
             
             for i in 0..times_int.value {
-                print!("\x07");
+                self.ring(BEEP_HZ, BEEP_MS);
                 if i < times_int.value - 1 {
-                    std::thread::sleep(Duration::from_millis(interval_int.value as u64));
+                    self.pause(interval_int.value as u64);
                 }
             }
             
@@ -311,6 +391,73 @@ impl SoundBox {
             Box::new(StringBox::new("Error: interval() requires two integer inputs (times, interval_ms)"))
         }
     }
+
+    /// 録音を始める
+    pub fn record(&self) -> Box<dyn NyashBox> {
+        self.recording.store(true, Ordering::Relaxed);
+        Box::new(VoidBox::new())
+    }
+
+    /// 実際に鳴らすかどうか（false なら録音を始め、記録のみ）
+    pub fn set_playback(&self, enabled: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        let enabled = match enabled.as_any().downcast_ref::<BoolBox>() {
+            Some(b) => b.value,
+            None => enabled.to_string_box().value == "true",
+        };
+        self.playback.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.recording.store(true, Ordering::Relaxed);
+        }
+        Box::new(VoidBox::new())
+    }
+
+    /// 記録の音量 (0.0-1.0)
+    pub fn set_volume(&self, level: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        let level = if let Some(f) = level.as_any().downcast_ref::<FloatBox>() {
+            Some(f.value)
+        } else if let Some(i) = level.as_any().downcast_ref::<IntegerBox>() {
+            Some(i.value as f64)
+        } else {
+            level.to_string_box().value.trim().parse::<f64>().ok()
+        };
+        match level.filter(|v| v.is_finite()) {
+            Some(level) => {
+                self.synth().set_volume(level);
+                Box::new(VoidBox::new())
+            }
+            None => Box::new(StringBox::new("Error: setVolume() requires a number")),
+        }
+    }
+
+    /// 記録した音を WAV ファイルに書き出す（何も記録していなければ Err、録音は始めない）
+    pub fn render(&self, path: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        let path = path.to_string_box().value;
+        if self.synth().is_empty() {
+            let hint = if self.is_recording() { "" } else { " (call record() or setPlayback(false) before playing sounds)" };
+            return Box::new(NyashResultBox::new_err(Box::new(StringBox::new(format!("Nothing recorded to render to '{}'{}", path, hint)))));
+        }
+        let wav = self.synth().to_wav();
+        match std::fs::write(&path, wav) {
+            Ok(()) => Box::new(NyashResultBox::new_ok(Box::new(BoolBox::new(true)))),
+            Err(e) => Box::new(NyashResultBox::new_err(Box::new(StringBox::new(format!("Failed to write '{}': {}", path, e))))),
+        }
+    }
+
+    /// 記録した音（16bit リトルエンディアン PCM）
+    pub fn get_samples(&self) -> Box<dyn NyashBox> {
+        Box::new(BufferBox::from_vec(self.synth().pcm16_bytes()))
+    }
+
+    /// 記録の長さ（ms）
+    pub fn get_duration(&self) -> Box<dyn NyashBox> {
+        Box::new(FloatBox::new(self.synth().duration_ms()))
+    }
+
+    /// 記録を消す
+    pub fn clear(&self) -> Box<dyn NyashBox> {
+        self.synth().clear();
+        Box::new(VoidBox::new())
+    }
 }
 
 impl NyashBox for SoundBox {
@@ -322,11 +469,13 @@ impl NyashBox for SoundBox {
         StringBox::new("SoundBox()")
     }
     
+    fn is_identity(&self) -> bool { true }
+    
     fn clone_box(&self) -> Box<dyn NyashBox> {
         Box::new(self.clone())
     }
     
-    /// 仮実装: clone_boxと同じ（後で修正）
+    /// 状態共有: 同じ記録を指す
     fn share_box(&self) -> Box<dyn NyashBox> {
         self.clone_box()
     }
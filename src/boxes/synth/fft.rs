@@ -0,0 +1,79 @@
+/*! 📊 FFT（基数2）と Web Audio 互換の周波数バイト列
+ *
+ * AnalyserNode の既定値に合わせる: fftSize 2048、Blackman 窓、
+ * minDecibels -100 / maxDecibels -30 を 0..255 に線形写像。
+ * 1 フレームだけの解析なので時間方向のスムージングは掛けない。
+ */
+
+/// 解析窓のサンプル数
+pub const FFT_SIZE: usize = 2048;
+
+const MIN_DECIBELS: f64 = -100.0;
+const MAX_DECIBELS: f64 = -30.0;
+
+/// インプレースの基数2 FFT。`re.len()` は 2 の冪
+pub fn fft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+    assert!(n.is_power_of_two() && im.len() == n, "FFT length must be a power of two");
+
+    // ビット反転並べ替え
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -std::f64::consts::TAU / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (w_im, w_re) = (angle * k as f64).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// 各周波数ビンの大きさ（dB）。ビン k の中心は `k * sample_rate / len`
+pub fn magnitudes_db(samples: &[f32]) -> Vec<f64> {
+    let n = samples.len();
+    let mut re: Vec<f64> = samples.iter().enumerate()
+        .map(|(i, &s)| s as f64 * blackman(i, n))
+        .collect();
+    let mut im = vec![0.0; n];
+    fft(&mut re, &mut im);
+    (0..n / 2)
+        .map(|k| 20.0 * ((re[k] * re[k] + im[k] * im[k]).sqrt() / n as f64).log10())
+        .collect()
+}
+
+/// getByteFrequencyData と同じ尺度のスペクトル（`samples.len() / 2` バイト）
+pub fn frequency_bytes(samples: &[f32]) -> Vec<u8> {
+    magnitudes_db(samples).iter()
+        .map(|db| {
+            let scaled = 255.0 * (db - MIN_DECIBELS) / (MAX_DECIBELS - MIN_DECIBELS);
+            if scaled.is_nan() { 0 } else { scaled.floor().clamp(0.0, 255.0) as u8 }
+        })
+        .collect()
+}
+
+fn blackman(i: usize, n: usize) -> f64 {
+    let x = std::f64::consts::TAU * i as f64 / n as f64;
+    0.42 - 0.5 * x.cos() + 0.08 * (2.0 * x).cos()
+}
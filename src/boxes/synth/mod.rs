@@ -0,0 +1,310 @@
+/*! 🎹 オフライン音声合成エンジン
+ *
+ * SoundBox / AudioBox のネイティブ実装が共有する、サウンドカード不要のシンセサイザ。
+ * 音をタイムライン（44.1kHz モノラルの f32 サンプル列）に加算合成し、
+ * WAV・PCM バイト列・FFT 解析結果として取り出す。
+ *
+ * - 発振器: サイン / 矩形 / ノコギリ / 三角 / ノイズ
+ * - ADSR エンベロープ（リリースは音の長さの後ろに伸びる）
+ * - 重なった音は加算でミックスし、マスター音量を掛けてから [-1, 1] にクリップ
+ *
+ * ノイズも固定シードの xorshift なので、同じ操作からは常に同じ WAV が得られる。
+ */
+
+mod fft;
+mod wav;
+
+pub use fft::{frequency_bytes, FFT_SIZE};
+pub use wav::encode_wav;
+
+/// サンプリング周波数（Hz）
+pub const SAMPLE_RATE: u32 = 44_100;
+
+/// タイムラインの上限（10 分）。巨大な duration でメモリを使い切らないため
+const MAX_SAMPLES: usize = SAMPLE_RATE as usize * 600;
+
+const NOISE_SEED: u32 = 0x2545_F491;
+
+/// 発振器の波形
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Waveform {
+    Sine,
+    Square,
+    Saw,
+    Triangle,
+    Noise,
+}
+
+impl Waveform {
+    /// "sine" / "square" / "saw"（"sawtooth"）/ "triangle" / "noise"
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "sine" => Some(Waveform::Sine),
+            "square" => Some(Waveform::Square),
+            "saw" | "sawtooth" => Some(Waveform::Saw),
+            "triangle" => Some(Waveform::Triangle),
+            "noise" => Some(Waveform::Noise),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Waveform::Sine => "sine",
+            Waveform::Square => "square",
+            Waveform::Saw => "saw",
+            Waveform::Triangle => "triangle",
+            Waveform::Noise => "noise",
+        }
+    }
+
+    /// 位相 `phase`（0..1）での値。ノイズは呼び出し側で生成する
+    fn sample(&self, phase: f64) -> f64 {
+        match self {
+            Waveform::Sine => (std::f64::consts::TAU * phase).sin(),
+            Waveform::Square => if phase < 0.5 { 1.0 } else { -1.0 },
+            Waveform::Saw => 2.0 * phase - 1.0,
+            Waveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+            Waveform::Noise => 0.0,
+        }
+    }
+}
+
+/// ADSR エンベロープ。時間は秒、sustain は 0..1 のレベル
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Envelope {
+    pub attack: f64,
+    pub decay: f64,
+    pub sustain: f64,
+    pub release: f64,
+}
+
+impl Default for Envelope {
+    /// クリック音を防ぐだけの短い立ち上がり/立ち下がり
+    fn default() -> Self {
+        Self { attack: 0.005, decay: 0.0, sustain: 1.0, release: 0.005 }
+    }
+}
+
+impl Envelope {
+    /// 鍵盤を押している間（`t < gate`）のレベル
+    fn held(&self, t: f64) -> f64 {
+        if t < self.attack {
+            t / self.attack
+        } else if t < self.attack + self.decay {
+            1.0 - (1.0 - self.sustain) * (t - self.attack) / self.decay
+        } else {
+            self.sustain
+        }
+    }
+
+    /// 発音開始から `t` 秒後のレベル（`gate` 秒で鍵盤を離す）
+    pub fn level(&self, t: f64, gate: f64) -> f64 {
+        if t < gate {
+            return self.held(t);
+        }
+        if self.release <= 0.0 {
+            return 0.0;
+        }
+        let released = t - gate;
+        if released >= self.release {
+            0.0
+        } else {
+            self.held(gate) * (1.0 - released / self.release)
+        }
+    }
+}
+
+/// 合成タイムライン
+#[derive(Debug, Clone)]
+pub struct Synth {
+    samples: Vec<f32>,
+    /// createTone などの順次追加で次の音を置く位置（サンプル）
+    cursor: usize,
+    waveform: Waveform,
+    envelope: Envelope,
+    volume: f64,
+    noise: u32,
+}
+
+impl Default for Synth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Synth {
+    pub fn new() -> Self {
+        Self {
+            samples: Vec::new(),
+            cursor: 0,
+            waveform: Waveform::Sine,
+            envelope: Envelope::default(),
+            volume: 1.0,
+            noise: NOISE_SEED,
+        }
+    }
+
+    pub fn waveform(&self) -> Waveform {
+        self.waveform
+    }
+
+    pub fn set_waveform(&mut self, waveform: Waveform) {
+        self.waveform = waveform;
+    }
+
+    pub fn envelope(&self) -> Envelope {
+        self.envelope
+    }
+
+    /// 負の値は 0 に、sustain は 0..1 に丸める
+    pub fn set_envelope(&mut self, envelope: Envelope) {
+        self.envelope = Envelope {
+            attack: envelope.attack.max(0.0),
+            decay: envelope.decay.max(0.0),
+            sustain: envelope.sustain.clamp(0.0, 1.0),
+            release: envelope.release.max(0.0),
+        };
+    }
+
+    pub fn volume(&self) -> f64 {
+        self.volume
+    }
+
+    /// マスター音量（0.0 - 1.0）
+    pub fn set_volume(&mut self, volume: f64) {
+        self.volume = volume.clamp(0.0, 1.0);
+    }
+
+    /// 指定時刻（ms）に音を加算する。長さはリリース分だけ `duration_ms` より伸びる
+    pub fn add_tone(&mut self, waveform: Waveform, frequency: f64, start_ms: f64, duration_ms: f64, gain: f64) {
+        let start = ms_to_samples(start_ms);
+        let gate = duration_ms.max(0.0) / 1000.0;
+        let total = ms_to_samples(duration_ms + self.envelope.release * 1000.0);
+        let end = (start + total).min(MAX_SAMPLES);
+        if end <= start {
+            return;
+        }
+        if self.samples.len() < end {
+            self.samples.resize(end, 0.0);
+        }
+        let rate = SAMPLE_RATE as f64;
+        for n in 0..end - start {
+            let t = n as f64 / rate;
+            let value = if waveform == Waveform::Noise {
+                self.next_noise()
+            } else {
+                waveform.sample((frequency * t).fract())
+            };
+            self.samples[start + n] += (value * gain * self.envelope.level(t, gate)) as f32;
+        }
+    }
+
+    /// 現在の波形で、前の音の後ろに続けて鳴らす
+    pub fn play_tone(&mut self, frequency: f64, duration_ms: f64) {
+        let start = self.cursor_ms();
+        self.add_tone(self.waveform, frequency, start, duration_ms, 1.0);
+        self.advance(duration_ms);
+    }
+
+    /// ホワイトノイズを前の音の後ろに続けて鳴らす
+    pub fn play_noise(&mut self, duration_ms: f64) {
+        let start = self.cursor_ms();
+        self.add_tone(Waveform::Noise, 0.0, start, duration_ms, 1.0);
+        self.advance(duration_ms);
+    }
+
+    /// 無音区間（次の順次追加位置だけ進める）
+    pub fn rest(&mut self, duration_ms: f64) {
+        self.advance(duration_ms);
+        if self.samples.len() < self.cursor {
+            self.samples.resize(self.cursor, 0.0);
+        }
+    }
+
+    fn advance(&mut self, duration_ms: f64) {
+        self.cursor = (self.cursor + ms_to_samples(duration_ms)).min(MAX_SAMPLES);
+    }
+
+    fn cursor_ms(&self) -> f64 {
+        self.cursor as f64 * 1000.0 / SAMPLE_RATE as f64
+    }
+
+    fn next_noise(&mut self) -> f64 {
+        let mut x = self.noise;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.noise = x;
+        x as f64 / u32::MAX as f64 * 2.0 - 1.0
+    }
+
+    /// 音もカーソルもノイズ列も初期状態に戻す（波形・エンベロープ・音量は残す）
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.cursor = 0;
+        self.noise = NOISE_SEED;
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// 全体の長さ（ms）
+    pub fn duration_ms(&self) -> f64 {
+        self.samples.len() as f64 * 1000.0 / SAMPLE_RATE as f64
+    }
+
+    /// マスター音量を掛けてクリップした最終サンプル
+    pub fn output(&self) -> Vec<f32> {
+        let volume = self.volume as f32;
+        self.samples.iter().map(|s| (s * volume).clamp(-1.0, 1.0)).collect()
+    }
+
+    /// 16bit 符号付き PCM
+    pub fn pcm16(&self) -> Vec<i16> {
+        self.output().iter().map(|s| (s * i16::MAX as f32).round() as i16).collect()
+    }
+
+    /// 16bit リトルエンディアン PCM のバイト列（WAV の data チャンクと同じ）
+    pub fn pcm16_bytes(&self) -> Vec<u8> {
+        self.pcm16().iter().flat_map(|s| s.to_le_bytes()).collect()
+    }
+
+    /// モノラル 16bit PCM の WAV ファイル
+    pub fn to_wav(&self) -> Vec<u8> {
+        encode_wav(&self.pcm16(), SAMPLE_RATE)
+    }
+
+    /// `at_ms` から FFT_SIZE サンプルを解析した周波数スペクトル（Web Audio の
+    /// getByteFrequencyData と同じ尺度、FFT_SIZE / 2 バイト）
+    pub fn frequency_data(&self, at_ms: f64) -> Vec<u8> {
+        frequency_bytes(&self.window(at_ms))
+    }
+
+    /// `at_ms` から FFT_SIZE サンプルの波形（128 が無音、getByteTimeDomainData と同じ尺度）
+    pub fn waveform_data(&self, at_ms: f64) -> Vec<u8> {
+        self.window(at_ms).iter()
+            .map(|s| (128.0 * (1.0 + s)).floor().clamp(0.0, 255.0) as u8)
+            .collect()
+    }
+
+    /// 解析窓。範囲外は無音として扱う
+    fn window(&self, at_ms: f64) -> Vec<f32> {
+        let output = self.output();
+        let start = ms_to_samples(at_ms);
+        (start..start + FFT_SIZE).map(|i| output.get(i).copied().unwrap_or(0.0)).collect()
+    }
+}
+
+fn ms_to_samples(ms: f64) -> usize {
+    if ms.is_finite() && ms > 0.0 {
+        ((ms / 1000.0 * SAMPLE_RATE as f64).round() as usize).min(MAX_SAMPLES)
+    } else {
+        0
+    }
+}
@@ -0,0 +1,652 @@
+/*! 🔌 WebSocketBox - WebSocket (RFC 6455) 接続
+ *
+ * ## 📝 概要
+ * std::net の TCP 上で WebSocket を話すBox（`ws://` のみ、TLS なし）。
+ * クライアント接続（`connect(url)`）と、HTTPServerBox の `websocket(path, handler)` で
+ * アップグレードされたサーバー側接続の両方をこのBoxで扱う。
+ * テキストメッセージは StringBox、バイナリメッセージは BufferBox として受け渡す。
+ * Ping には自動で Pong を返し、Close を受けたら Close を返して接続を閉じる。
+ *
+ * ## 🛠️ 利用可能メソッド
+ * - `connect(url)` - ハンドシェイク → ResultBox（Ok(true) / Err(StringBox)）
+ * - `send(data)` - StringBox はテキスト、BufferBox はバイナリとして送信
+ * - `sendBytes(data)` - バイナリとして送信（StringBox は UTF-8 バイト列）
+ * - `receive()` - 次のメッセージ（StringBox / BufferBox）。閉じた・タイムアウト時は void
+ * - `setTimeout(ms)` - `receive()` の待ち時間（0 = 無制限）
+ * - `ping()` - Ping 送信
+ * - `close()` / `close(code, reason)` - Close 送信（既定 1000）
+ * - `isOpen()` / `getCloseCode()`
+ *
+ * ## 💡 使用例
+ * ```nyash
+ * local ws = new WebSocketBox()
+ * local r = ws.connect("ws://127.0.0.1:8080/chat")
+ * if r.isOk() {
+ *     ws.send("hello")
+ *     print(ws.receive())
+ *     ws.close()
+ * }
+ * ```
+ */
+
+use crate::box_trait::{NyashBox, StringBox, BoolBox, IntegerBox, VoidBox, BoxCore, BoxBase};
+use crate::boxes::base64;
+use crate::boxes::buffer::BufferBox;
+use crate::boxes::http::ParsedUrl;
+use crate::boxes::result::NyashResultBox;
+use std::any::Any;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+/// ハンドシェイクで使う固定 GUID（RFC 6455 1.3）
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+/// 1メッセージ（フラグメント結合後）の上限
+const MAX_MESSAGE_BYTES: usize = 16 * 1024 * 1024;
+/// 接続・ハンドシェイクのタイムアウト
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// 自分から Close を送った後、相手の Close を待つ時間
+const CLOSE_WAIT: Duration = Duration::from_secs(1);
+
+const OP_CONTINUATION: u8 = 0x0;
+const OP_TEXT: u8 = 0x1;
+const OP_BINARY: u8 = 0x2;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xA;
+
+/// データメッセージ
+#[derive(Debug, Clone, PartialEq)]
+pub enum WsMessage {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+/// `WsConn::recv` の結果
+#[derive(Debug)]
+pub(crate) enum Received {
+    Message(WsMessage),
+    /// 待ち時間内にフレームが届かなかった（接続は開いたまま）
+    Timeout,
+    Closed,
+}
+
+struct Frame {
+    fin: bool,
+    opcode: u8,
+    payload: Vec<u8>,
+}
+
+/// 確立済みの WebSocket 接続（送信と受信は別ロック）
+#[derive(Debug)]
+pub(crate) struct WsConn {
+    reader: Mutex<BufReader<TcpStream>>,
+    writer: Mutex<TcpStream>,
+    /// クライアント側は送信フレームをマスクし、サーバー側はマスク済みフレームだけを受け付ける
+    client: bool,
+    open: AtomicBool,
+    close_sent: AtomicBool,
+    close_code: Mutex<Option<u16>>,
+}
+
+impl WsConn {
+    fn new(reader: BufReader<TcpStream>, writer: TcpStream, client: bool) -> Self {
+        Self {
+            reader: Mutex::new(reader),
+            writer: Mutex::new(writer),
+            client,
+            open: AtomicBool::new(true),
+            close_sent: AtomicBool::new(false),
+            close_code: Mutex::new(None),
+        }
+    }
+
+    /// クライアントハンドシェイク（`ws://host[:port]/path`）
+    pub(crate) fn connect(url: &str) -> Result<Self, String> {
+        let url = ParsedUrl::parse_with_scheme(url, "ws")?;
+        let mut stream = url.connect(CONNECT_TIMEOUT)?;
+        let key = base64::encode(&random_bytes(16).map_err(|e| e.to_string())?);
+        let request = format!(
+            "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+            url.target, url.host_header(), key
+        );
+        stream.write_all(request.as_bytes()).map_err(|e| e.to_string())?;
+        stream.flush().map_err(|e| e.to_string())?;
+
+        // The reader keeps any frame bytes the server sent right after the 101
+        let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+        let mut status_line = String::new();
+        reader.read_line(&mut status_line).map_err(|e| format!("handshake failed: {}", e))?;
+        let status = status_line.split_whitespace().nth(1).unwrap_or("");
+        if status != "101" {
+            return Err(format!("handshake rejected: {}", status_line.trim_end()));
+        }
+        let mut accept = None;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).map_err(|e| format!("handshake failed: {}", e))? == 0 {
+                return Err("connection closed during handshake".to_string());
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("sec-websocket-accept") {
+                    accept = Some(value.trim().to_string());
+                }
+            }
+        }
+        if accept.as_deref() != Some(accept_key(&key).as_str()) {
+            return Err("handshake failed: invalid Sec-WebSocket-Accept".to_string());
+        }
+        stream.set_read_timeout(None).map_err(|e| e.to_string())?;
+        stream.set_write_timeout(None).map_err(|e| e.to_string())?;
+        Ok(Self::new(reader, stream, true))
+    }
+
+    /// サーバー側: 101 Switching Protocols を返して接続を確立する
+    pub(crate) fn accept(mut stream: TcpStream, key: &str) -> io::Result<Self> {
+        let response = format!(
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+            accept_key(key)
+        );
+        stream.write_all(response.as_bytes())?;
+        stream.flush()?;
+        stream.set_read_timeout(None)?;
+        let reader = BufReader::new(stream.try_clone()?);
+        Ok(Self::new(reader, stream, false))
+    }
+
+    pub(crate) fn is_open(&self) -> bool {
+        self.open.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn close_code(&self) -> Option<u16> {
+        *self.close_code.lock().unwrap()
+    }
+
+    fn write_frame(&self, opcode: u8, payload: &[u8]) -> io::Result<()> {
+        let mut frame = Vec::with_capacity(payload.len() + 14);
+        frame.push(0x80 | opcode);
+        let mask_bit = if self.client { 0x80 } else { 0 };
+        match payload.len() {
+            n if n < 126 => frame.push(mask_bit | n as u8),
+            n if n <= u16::MAX as usize => {
+                frame.push(mask_bit | 126);
+                frame.extend_from_slice(&(n as u16).to_be_bytes());
+            }
+            n => {
+                frame.push(mask_bit | 127);
+                frame.extend_from_slice(&(n as u64).to_be_bytes());
+            }
+        }
+        if self.client {
+            let key = random_bytes(4)?;
+            frame.extend_from_slice(&key);
+            frame.extend(payload.iter().zip(key.iter().cycle()).map(|(b, k)| b ^ k));
+        } else {
+            frame.extend_from_slice(payload);
+        }
+        let mut writer = self.writer.lock().unwrap();
+        writer.write_all(&frame)?;
+        writer.flush()
+    }
+
+    /// データメッセージを送信
+    pub(crate) fn send(&self, message: &WsMessage) -> io::Result<()> {
+        if !self.is_open() || self.close_sent.load(Ordering::SeqCst) {
+            return Err(io::Error::new(io::ErrorKind::NotConnected, "websocket is closed"));
+        }
+        match message {
+            WsMessage::Text(text) => self.write_frame(OP_TEXT, text.as_bytes()),
+            WsMessage::Binary(bytes) => self.write_frame(OP_BINARY, bytes),
+        }
+    }
+
+    pub(crate) fn ping(&self, payload: &[u8]) -> io::Result<()> {
+        if !self.is_open() {
+            return Err(io::Error::new(io::ErrorKind::NotConnected, "websocket is closed"));
+        }
+        self.write_frame(OP_PING, &payload[..payload.len().min(125)])
+    }
+
+    /// Close を送る。受信中のスレッドがいなければ相手の Close を短時間待って切断する
+    pub(crate) fn close(&self, code: u16, reason: &str) {
+        if !self.is_open() {
+            return;
+        }
+        if !self.close_sent.swap(true, Ordering::SeqCst) {
+            let mut payload = code.to_be_bytes().to_vec();
+            payload.extend_from_slice(&reason.as_bytes()[..reason.len().min(123)]);
+            if self.write_frame(OP_CLOSE, &payload).is_err() {
+                self.shutdown();
+                return;
+            }
+        }
+        if let Ok(mut reader) = self.reader.try_lock() {
+            let _ = reader.get_ref().set_read_timeout(Some(CLOSE_WAIT));
+            while let Ok(frame) = read_frame(&mut *reader, !self.client) {
+                if frame.opcode == OP_CLOSE {
+                    break;
+                }
+            }
+            drop(reader);
+            self.shutdown();
+        }
+    }
+
+    fn shutdown(&self) {
+        self.open.store(false, Ordering::SeqCst);
+        let _ = self.writer.lock().unwrap().shutdown(Shutdown::Both);
+    }
+
+    /// プロトコル違反: Close(code) を送って切断
+    fn fail(&self, code: u16, error: io::Error) -> io::Result<Received> {
+        if !self.close_sent.swap(true, Ordering::SeqCst) {
+            let _ = self.write_frame(OP_CLOSE, &code.to_be_bytes());
+        }
+        self.shutdown();
+        Err(error)
+    }
+
+    /// 次のデータメッセージを受信する。Ping/Pong/Close はここで処理する。
+    /// `wait` は最初のフレームが届くまでの待ち時間（None = 無制限）
+    pub(crate) fn recv(&self, wait: Option<Duration>) -> io::Result<Received> {
+        if !self.is_open() {
+            return Ok(Received::Closed);
+        }
+        let mut reader = self.reader.lock().unwrap();
+        if reader.buffer().is_empty() {
+            reader.get_ref().set_read_timeout(wait)?;
+            let ready = reader.fill_buf().map(|buf| !buf.is_empty());
+            reader.get_ref().set_read_timeout(None)?;
+            match ready {
+                Ok(true) => {}
+                Ok(false) => {
+                    drop(reader);
+                    self.shutdown();
+                    return Ok(Received::Closed);
+                }
+                Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {
+                    return Ok(Received::Timeout);
+                }
+                Err(e) => {
+                    drop(reader);
+                    self.shutdown();
+                    return Err(e);
+                }
+            }
+        }
+
+        let mut partial: Option<(u8, Vec<u8>)> = None;
+        loop {
+            let frame = match read_frame(&mut *reader, !self.client) {
+                Ok(frame) => frame,
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                    drop(reader);
+                    return self.fail(1002, e);
+                }
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    drop(reader);
+                    self.shutdown();
+                    return Ok(Received::Closed);
+                }
+                Err(e) => {
+                    drop(reader);
+                    self.shutdown();
+                    return Err(e);
+                }
+            };
+            let (opcode, payload) = match frame.opcode {
+                OP_PING => {
+                    self.write_frame(OP_PONG, &frame.payload)?;
+                    continue;
+                }
+                OP_PONG => continue,
+                OP_CLOSE => {
+                    let code = (frame.payload.len() >= 2).then(|| u16::from_be_bytes([frame.payload[0], frame.payload[1]]));
+                    *self.close_code.lock().unwrap() = Some(code.unwrap_or(1005));
+                    if !self.close_sent.swap(true, Ordering::SeqCst) {
+                        let _ = self.write_frame(OP_CLOSE, &code.unwrap_or(1000).to_be_bytes());
+                    }
+                    drop(reader);
+                    self.shutdown();
+                    return Ok(Received::Closed);
+                }
+                OP_TEXT | OP_BINARY if partial.is_none() => (frame.opcode, frame.payload),
+                OP_CONTINUATION if partial.is_some() => {
+                    let (opcode, mut data) = partial.take().unwrap();
+                    if data.len() + frame.payload.len() > MAX_MESSAGE_BYTES {
+                        drop(reader);
+                        return self.fail(1009, io::Error::new(io::ErrorKind::InvalidData, "message too large"));
+                    }
+                    data.extend_from_slice(&frame.payload);
+                    (opcode, data)
+                }
+                other => {
+                    drop(reader);
+                    return self.fail(1002, io::Error::new(io::ErrorKind::InvalidData, format!("unexpected opcode {:#x}", other)));
+                }
+            };
+            if !frame.fin {
+                partial = Some((opcode, payload));
+                continue;
+            }
+            return match opcode {
+                OP_TEXT => match String::from_utf8(payload) {
+                    Ok(text) => Ok(Received::Message(WsMessage::Text(text))),
+                    Err(_) => {
+                        drop(reader);
+                        self.fail(1007, io::Error::new(io::ErrorKind::InvalidData, "text message is not valid UTF-8"))
+                    }
+                },
+                _ => Ok(Received::Message(WsMessage::Binary(payload))),
+            };
+        }
+    }
+}
+
+/// フレームを1つ読む（`expect_masked`: サーバー側はクライアントのマスクを必須とする）
+fn read_frame<R: Read>(reader: &mut R, expect_masked: bool) -> io::Result<Frame> {
+    let mut head = [0u8; 2];
+    reader.read_exact(&mut head)?;
+    let fin = head[0] & 0x80 != 0;
+    if head[0] & 0x70 != 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "reserved bits set"));
+    }
+    let opcode = head[0] & 0x0F;
+    let masked = head[1] & 0x80 != 0;
+    if masked != expect_masked {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "unexpected frame masking"));
+    }
+    let len = match head[1] & 0x7F {
+        126 => {
+            let mut ext = [0u8; 2];
+            reader.read_exact(&mut ext)?;
+            u16::from_be_bytes(ext) as u64
+        }
+        127 => {
+            let mut ext = [0u8; 8];
+            reader.read_exact(&mut ext)?;
+            u64::from_be_bytes(ext)
+        }
+        n => n as u64,
+    };
+    if len > MAX_MESSAGE_BYTES as u64 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "frame too large"));
+    }
+    if opcode >= OP_CLOSE && (len > 125 || !fin) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid control frame"));
+    }
+    let mut key = [0u8; 4];
+    if masked {
+        reader.read_exact(&mut key)?;
+    }
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload)?;
+    if masked {
+        for (b, k) in payload.iter_mut().zip(key.iter().cycle()) {
+            *b ^= k;
+        }
+    }
+    Ok(Frame { fin, opcode, payload })
+}
+
+/// `Sec-WebSocket-Accept` = base64(SHA-1(key + GUID))
+pub fn accept_key(key: &str) -> String {
+    base64::encode(&sha1(format!("{}{}", key.trim(), WEBSOCKET_GUID).as_bytes()))
+}
+
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut msg = data.to_vec();
+    msg.push(0x80);
+    while msg.len() % 64 != 56 {
+        msg.push(0);
+    }
+    msg.extend_from_slice(&((data.len() as u64).wrapping_mul(8)).to_be_bytes());
+    for block in msg.chunks(64) {
+        let mut w = [0u32; 80];
+        for (word, bytes) in w.iter_mut().zip(block.chunks(4)) {
+            *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let t = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = t;
+        }
+        for (state, v) in h.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(v);
+        }
+    }
+    let mut out = [0u8; 20];
+    for (chunk, v) in out.chunks_mut(4).zip(h) {
+        chunk.copy_from_slice(&v.to_be_bytes());
+    }
+    out
+}
+
+/// マスクキー・ハンドシェイクキー用（RFC 6455 §5.3: 予測不能であること）
+fn random_bytes(len: usize) -> io::Result<Vec<u8>> {
+    let mut out = vec![0u8; len];
+    getrandom::getrandom(&mut out).map_err(|e| io::Error::other(format!("random source: {}", e)))?;
+    Ok(out)
+}
+
+/// WebSocket 接続を提供するBox
+#[derive(Debug, Clone)]
+pub struct WebSocketBox {
+    base: BoxBase,
+    conn: Arc<RwLock<Option<Arc<WsConn>>>>,
+    /// `receive()` の待ち時間（ミリ秒、0 = 無制限）
+    timeout_ms: Arc<RwLock<u64>>,
+}
+
+impl WebSocketBox {
+    pub fn new() -> Self {
+        Self {
+            base: BoxBase::new(),
+            conn: Arc::new(RwLock::new(None)),
+            timeout_ms: Arc::new(RwLock::new(0)),
+        }
+    }
+
+    /// 確立済み接続（HTTPServerBox のアップグレード）から生成
+    pub(crate) fn from_conn(conn: Arc<WsConn>) -> Self {
+        let ws = Self::new();
+        *ws.conn.write().unwrap() = Some(conn);
+        ws
+    }
+
+    fn current(&self) -> Option<Arc<WsConn>> {
+        self.conn.read().unwrap().clone()
+    }
+
+    /// 受信メッセージ → StringBox（テキスト）/ BufferBox（バイナリ）
+    pub fn message_box(message: WsMessage) -> Box<dyn NyashBox> {
+        match message {
+            WsMessage::Text(text) => Box::new(StringBox::new(text)),
+            WsMessage::Binary(bytes) => Box::new(BufferBox::from_vec(bytes)),
+        }
+    }
+
+    /// 送信する値 → メッセージ（void は送らない）
+    pub fn message_from_box(value: &dyn NyashBox) -> Option<WsMessage> {
+        if value.as_any().is::<VoidBox>() {
+            None
+        } else if let Some(buffer) = value.as_any().downcast_ref::<BufferBox>() {
+            Some(WsMessage::Binary(buffer.to_vec()))
+        } else {
+            Some(WsMessage::Text(value.to_string_box().value))
+        }
+    }
+
+    /// サーバーへ接続（`ws://host[:port]/path`）
+    pub fn connect(&self, url: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        match WsConn::connect(&url.to_string_box().value) {
+            Ok(conn) => {
+                if let Some(old) = self.conn.write().unwrap().replace(Arc::new(conn)) {
+                    old.close(1000, "");
+                }
+                Box::new(NyashResultBox::new_ok(Box::new(BoolBox::new(true))))
+            }
+            Err(e) => Box::new(NyashResultBox::new_err(Box::new(StringBox::new(e)))),
+        }
+    }
+
+    fn send_message(&self, message: WsMessage) -> Box<dyn NyashBox> {
+        let sent = match self.current() {
+            Some(conn) => conn.send(&message).map_err(|e| eprintln!("🚨 WebSocketBox send error: {}", e)).is_ok(),
+            None => false,
+        };
+        Box::new(BoolBox::new(sent))
+    }
+
+    /// StringBox はテキスト、BufferBox はバイナリとして送信
+    pub fn send(&self, data: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        match Self::message_from_box(data.as_ref()) {
+            Some(message) => self.send_message(message),
+            None => Box::new(BoolBox::new(false)),
+        }
+    }
+
+    /// バイナリとして送信
+    pub fn send_bytes(&self, data: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        let bytes = match data.as_any().downcast_ref::<BufferBox>() {
+            Some(buffer) => buffer.to_vec(),
+            None => data.to_string_box().value.into_bytes(),
+        };
+        self.send_message(WsMessage::Binary(bytes))
+    }
+
+    /// 次のメッセージを受信（閉じた・タイムアウト時は void）
+    pub fn receive(&self) -> Box<dyn NyashBox> {
+        let Some(conn) = self.current() else {
+            return Box::new(VoidBox::new());
+        };
+        let wait = match *self.timeout_ms.read().unwrap() {
+            0 => None,
+            ms => Some(Duration::from_millis(ms)),
+        };
+        match conn.recv(wait) {
+            Ok(Received::Message(message)) => Self::message_box(message),
+            Ok(_) => Box::new(VoidBox::new()),
+            Err(e) => {
+                eprintln!("🚨 WebSocketBox receive error: {}", e);
+                Box::new(VoidBox::new())
+            }
+        }
+    }
+
+    /// `receive()` の待ち時間（ミリ秒、0 = 無制限）
+    pub fn set_timeout(&self, ms: Box<dyn NyashBox>) -> Box<dyn NyashBox> {
+        let ms = ms.to_string_box().value.trim().parse::<u64>().unwrap_or(0);
+        *self.timeout_ms.write().unwrap() = ms;
+        Box::new(BoolBox::new(true))
+    }
+
+    pub fn ping(&self) -> Box<dyn NyashBox> {
+        let sent = self.current().map(|conn| conn.ping(b"nyash").is_ok()).unwrap_or(false);
+        Box::new(BoolBox::new(sent))
+    }
+
+    /// Close を送信（code 省略時 1000）
+    pub fn close(&self, code: Option<Box<dyn NyashBox>>, reason: Option<Box<dyn NyashBox>>) -> Box<dyn NyashBox> {
+        let Some(conn) = self.current() else {
+            return Box::new(BoolBox::new(false));
+        };
+        let code = code.and_then(|c| c.to_string_box().value.trim().parse::<u16>().ok()).unwrap_or(1000);
+        let reason = reason.map(|r| r.to_string_box().value).unwrap_or_default();
+        conn.close(code, &reason);
+        Box::new(BoolBox::new(true))
+    }
+
+    pub fn is_open(&self) -> Box<dyn NyashBox> {
+        Box::new(BoolBox::new(self.current().map(|conn| conn.is_open()).unwrap_or(false)))
+    }
+
+    /// 相手から受け取った Close コード（未受信なら 0）
+    pub fn get_close_code(&self) -> Box<dyn NyashBox> {
+        let code = self.current().and_then(|conn| conn.close_code()).unwrap_or(0);
+        Box::new(IntegerBox::new(code as i64))
+    }
+}
+
+impl Default for WebSocketBox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NyashBox for WebSocketBox {
+    fn is_identity(&self) -> bool { true }
+
+    fn clone_box(&self) -> Box<dyn NyashBox> {
+        Box::new(self.clone())
+    }
+
+    /// 状態共有: 同じ接続を指す
+    fn share_box(&self) -> Box<dyn NyashBox> {
+        self.clone_box()
+    }
+
+    fn to_string_box(&self) -> StringBox {
+        StringBox::new(format!("{}", self))
+    }
+
+    fn type_name(&self) -> &'static str {
+        "WebSocketBox"
+    }
+
+    fn equals(&self, other: &dyn NyashBox) -> BoolBox {
+        match other.as_any().downcast_ref::<WebSocketBox>() {
+            Some(other) => BoolBox::new(Arc::ptr_eq(&self.conn, &other.conn)),
+            None => BoolBox::new(false),
+        }
+    }
+}
+
+impl BoxCore for WebSocketBox {
+    fn box_id(&self) -> u64 {
+        self.base.id
+    }
+
+    fn parent_type_id(&self) -> Option<std::any::TypeId> {
+        self.base.parent_type_id
+    }
+
+    fn fmt_box(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let open = self.current().map(|conn| conn.is_open()).unwrap_or(false);
+        write!(f, "WebSocket(id: {}, open: {})", self.base.id, open)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl std::fmt::Display for WebSocketBox {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_box(f)
+    }
+}
@@ -15,17 +15,36 @@ pub struct CliConfig {
     pub dump_ast: bool,
     pub dump_mir: bool,
     pub verify_mir: bool,
+    /// Check the ownership forest rules (strong in-degree, strong cycles) and exit
+    pub verify_ownership: bool,
     pub mir_verbose: bool,
     pub mir_verbose_effects: bool,
+    pub run_mir: bool,
     pub no_optimize: bool,
+    pub verify_after_each_pass: bool,
     pub backend: String,
     pub compile_wasm: bool,
+    /// `--compile-wasm` output: "wat" (text module) or "web" (browser bundle)
+    pub wasm_target: String,
     pub compile_native: bool,
     pub output_file: Option<String>,
     pub benchmark: bool,
     pub iterations: u32,
     pub vm_stats: bool,
+    /// Run the VM on MIR lowered to the 25-instruction set
+    pub mir_v2: bool,
     pub vm_stats_json: bool,
+    /// `nyash bid gen` subcommand arguments
+    pub bid_gen: Option<BidGenConfig>,
+}
+
+/// Arguments of `nyash bid gen <BID_FILE>`
+#[derive(Debug, Clone)]
+pub struct BidGenConfig {
+    pub input: String,
+    pub output_dir: String,
+    pub force: bool,
+    pub dry_run: bool,
 }
 
 impl CliConfig {
@@ -72,6 +91,12 @@ impl CliConfig {
                     .help("Verify MIR integrity and exit")
                     .action(clap::ArgAction::SetTrue)
             )
+            .arg(
+                Arg::new("verify-ownership")
+                    .long("verify-ownership")
+                    .help("Check ownership of boxes (single strong owner, no strong cycles) and exit non-zero on violations")
+                    .action(clap::ArgAction::SetTrue)
+            )
             .arg(
                 Arg::new("mir-verbose")
                     .long("mir-verbose")
@@ -84,12 +109,24 @@ impl CliConfig {
                     .help("Show per-instruction effect category (pure/readonly/side)")
                     .action(clap::ArgAction::SetTrue)
             )
+            .arg(
+                Arg::new("run-mir")
+                    .long("run-mir")
+                    .help("Treat the input file as textual MIR (--dump-mir format) and execute it on the VM")
+                    .action(clap::ArgAction::SetTrue)
+            )
             .arg(
                 Arg::new("no-optimize")
                     .long("no-optimize")
                     .help("Disable MIR optimizer passes (dump raw Builder MIR)")
                     .action(clap::ArgAction::SetTrue)
             )
+            .arg(
+                Arg::new("verify-after-each-pass")
+                    .long("verify-after-each-pass")
+                    .help("Verify MIR after every optimizer pass and report the pass that broke it (equivalent to NYASH_OPT_VERIFY_EACH_PASS=1)")
+                    .action(clap::ArgAction::SetTrue)
+            )
             .arg(
                 Arg::new("backend")
                     .long("backend")
@@ -103,6 +140,14 @@ impl CliConfig {
                     .help("Compile to WebAssembly (WAT format) instead of executing")
                     .action(clap::ArgAction::SetTrue)
             )
+            .arg(
+                Arg::new("target")
+                    .long("target")
+                    .value_name("TARGET")
+                    .help("Output of --compile-wasm: 'wat' (default) or 'web' (.wasm, JS loader and index.html in the -o directory)")
+                    .value_parser(["wat", "web"])
+                    .default_value("wat")
+            )
             .arg(
                 Arg::new("compile-native")
                     .long("compile-native")
@@ -147,6 +192,47 @@ impl CliConfig {
                     .help("Output VM statistics in JSON format")
                     .action(clap::ArgAction::SetTrue)
             )
+            .arg(
+                Arg::new("mir-v2")
+                    .long("mir-v2")
+                    .help("Lower MIR to the 25-instruction set and execute that on the VM (--backend vm, --run-mir)")
+                    .action(clap::ArgAction::SetTrue)
+            )
+            .subcommand(
+                Command::new("bid")
+                    .about("BID (Box Interface Definition) tools")
+                    .subcommand_required(true)
+                    .subcommand(
+                        Command::new("gen")
+                            .about("Generate a plugin crate skeleton and nyash.toml entries from a BID file")
+                            .arg(
+                                Arg::new("bid-file")
+                                    .help("BID definition (YAML, or JSON with .json extension)")
+                                    .value_name("BID_FILE")
+                                    .required(true)
+                                    .index(1)
+                            )
+                            .arg(
+                                Arg::new("out-dir")
+                                    .long("out-dir")
+                                    .short('o')
+                                    .value_name("DIR")
+                                    .help("Output directory of the plugin crate (default: plugins/nyash-<name>-plugin)")
+                            )
+                            .arg(
+                                Arg::new("force")
+                                    .long("force")
+                                    .help("Overwrite existing files")
+                                    .action(clap::ArgAction::SetTrue)
+                            )
+                            .arg(
+                                Arg::new("dry-run")
+                                    .long("dry-run")
+                                    .help("Print generated files instead of writing them")
+                                    .action(clap::ArgAction::SetTrue)
+                            )
+                    )
+            )
     }
 
     /// Convert ArgMatches to CliConfig
@@ -157,19 +243,37 @@ impl CliConfig {
             dump_ast: matches.get_flag("dump-ast"),
             dump_mir: matches.get_flag("dump-mir"),
             verify_mir: matches.get_flag("verify"),
+            verify_ownership: matches.get_flag("verify-ownership"),
             mir_verbose: matches.get_flag("mir-verbose"),
             mir_verbose_effects: matches.get_flag("mir-verbose-effects"),
+            run_mir: matches.get_flag("run-mir"),
             no_optimize: matches.get_flag("no-optimize"),
+            verify_after_each_pass: matches.get_flag("verify-after-each-pass"),
             backend: matches.get_one::<String>("backend").unwrap().clone(),
             compile_wasm: matches.get_flag("compile-wasm"),
+            wasm_target: matches.get_one::<String>("target").unwrap().clone(),
             compile_native: matches.get_flag("compile-native") || matches.get_flag("aot"),
             output_file: matches.get_one::<String>("output").cloned(),
             benchmark: matches.get_flag("benchmark"),
             iterations: matches.get_one::<String>("iterations").unwrap().parse().unwrap_or(10),
             vm_stats: matches.get_flag("vm-stats"),
+            mir_v2: matches.get_flag("mir-v2"),
             vm_stats_json: matches.get_flag("vm-stats-json"),
+            bid_gen: Self::bid_gen_from_matches(matches),
         }
     }
+
+    /// Extract `bid gen` subcommand arguments, if present
+    fn bid_gen_from_matches(matches: &ArgMatches) -> Option<BidGenConfig> {
+        let bid = matches.subcommand_matches("bid")?;
+        let gen = bid.subcommand_matches("gen")?;
+        Some(BidGenConfig {
+            input: gen.get_one::<String>("bid-file").cloned().unwrap_or_default(),
+            output_dir: gen.get_one::<String>("out-dir").cloned().unwrap_or_default(),
+            force: gen.get_flag("force"),
+            dry_run: gen.get_flag("dry-run"),
+        })
+    }
 }
 
 /// Parse debug fuel value ("unlimited" or numeric)
@@ -199,20 +303,43 @@ mod tests {
         let config = CliConfig {
             file: None,
             debug_fuel: Some(100000),
+            dump_ast: false,
             dump_mir: false,
             verify_mir: false,
+            verify_ownership: false,
             mir_verbose: false,
+            mir_verbose_effects: false,
+            run_mir: false,
+            no_optimize: false,
+            verify_after_each_pass: false,
             backend: "interpreter".to_string(),
             compile_wasm: false,
+            wasm_target: "wat".to_string(),
             compile_native: false,
             output_file: None,
             benchmark: false,
             iterations: 10,
             vm_stats: false,
+            mir_v2: false,
             vm_stats_json: false,
+            bid_gen: None,
         };
         
         assert_eq!(config.backend, "interpreter");
         assert_eq!(config.iterations, 10);
     }
+
+    #[test]
+    fn test_bid_gen_subcommand() {
+        let matches = CliConfig::build_command()
+            .try_get_matches_from(["nyash", "bid", "gen", "counter.bid.yaml", "-o", "out", "--force"])
+            .unwrap();
+        let config = CliConfig::from_matches(&matches);
+        let gen = config.bid_gen.expect("bid gen config");
+        assert_eq!(gen.input, "counter.bid.yaml");
+        assert_eq!(gen.output_dir, "out");
+        assert!(gen.force);
+        assert!(!gen.dry_run);
+        assert!(config.file.is_none());
+    }
 }
@@ -4,4 +4,4 @@
 
 pub mod nyash_toml_v2;
 
-pub use nyash_toml_v2::{NyashConfigV2, LibraryDefinition, BoxTypeConfig, MethodDefinition};
\ No newline at end of file
+pub use nyash_toml_v2::{NyashConfigV2, LibraryDefinition, BoxTypeConfig, MethodDefinition, PluginIsolation};
\ No newline at end of file
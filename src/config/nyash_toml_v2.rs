@@ -26,6 +26,39 @@ pub struct LibraryDefinition {
     
     /// Path to the shared library
     pub path: String,
+
+    /// Where the library runs: "inprocess" (default) or "process"
+    #[serde(default)]
+    pub isolation: PluginIsolation,
+
+    /// Automatic restarts after a crash (process isolation only)
+    #[serde(default)]
+    pub max_restarts: Option<u32>,
+
+    /// Fuel budget per call (wasm plugins only; 0 = unlimited)
+    #[serde(default)]
+    pub fuel: Option<u64>,
+}
+
+/// Plugin isolation mode (`isolation = "process"` in a library section)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginIsolation {
+    /// Load the shared library into the interpreter process
+    #[default]
+    InProcess,
+    /// Load it in a `nyash-plugin-host` helper and forward calls over a pipe
+    Process,
+}
+
+impl PluginIsolation {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "inprocess" | "in-process" | "none" => Some(Self::InProcess),
+            "process" => Some(Self::Process),
+            _ => None,
+        }
+    }
 }
 
 /// Plugin search paths
@@ -141,9 +174,26 @@ impl NyashConfigV2 {
                         .unwrap_or(lib_name)
                         .to_string();
                     
+                    let isolation = match lib_table.get("isolation").and_then(|v| v.as_str()) {
+                        Some(s) => PluginIsolation::parse(s)
+                            .ok_or_else(|| format!("library {}: unknown isolation '{}'", lib_name, s))?,
+                        None => PluginIsolation::InProcess,
+                    };
+
+                    let max_restarts = lib_table.get("max_restarts")
+                        .and_then(|v| v.as_integer())
+                        .map(|n| n.max(0) as u32);
+
+                    let fuel = lib_table.get("fuel")
+                        .and_then(|v| v.as_integer())
+                        .map(|n| n.max(0) as u64);
+
                     libraries.insert(lib_name.clone(), LibraryDefinition {
                         boxes,
                         path,
+                        isolation,
+                        max_restarts,
+                        fuel,
                     });
                 }
             }
@@ -218,5 +268,24 @@ close = { method_id = 4 }
         let raw: toml::Value = toml::from_str(toml_str).unwrap();
         let box_conf = nyash_config.get_box_config("libnyash_filebox_plugin.so", "FileBox", &raw).unwrap();
         assert_eq!(box_conf.type_id, 6);
+        assert_eq!(lib_def.isolation, PluginIsolation::InProcess);
+    }
+
+    #[test]
+    fn test_parse_process_isolation() {
+        let toml_str = r#"
+[libraries."libnyash_net_plugin.so"]
+boxes = ["HttpServerBox"]
+path = "./libnyash_net_plugin.so"
+isolation = "process"
+max_restarts = 5
+"#;
+        let conf = NyashConfigV2::from_str(toml_str).unwrap();
+        let lib = &conf.libraries["libnyash_net_plugin.so"];
+        assert_eq!(lib.isolation, PluginIsolation::Process);
+        assert_eq!(lib.max_restarts, Some(5));
+
+        let bad = toml_str.replace("\"process\"", "\"thread\"");
+        assert!(NyashConfigV2::from_str(&bad).is_err());
     }
 }
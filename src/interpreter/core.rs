@@ -592,6 +592,10 @@ impl NyashInterpreter {
                 if b.as_any().downcast_ref::<crate::runtime::plugin_loader_v2::PluginBoxV2>().is_some() {
                     return (k.clone(), b.share_box());
                 }
+                // HTTPServerBoxはルート/待受状態をハンドラ呼び出しの前後で保つため共有
+                if b.as_any().downcast_ref::<crate::boxes::HTTPServerBox>().is_some() {
+                    return (k.clone(), b.share_box());
+                }
                 (k.clone(), b.clone_box())
             })
             .collect()
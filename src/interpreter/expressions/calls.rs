@@ -7,7 +7,7 @@ use crate::ast::ASTNode;
 use crate::box_trait::{NyashBox, StringBox, IntegerBox, BoolBox, VoidBox};
 use crate::boxes::{ArrayBox, FloatBox, MapBox, FutureBox};
 use crate::boxes::{BufferBox, JSONBox, HttpClientBox, StreamBox, RegexBox, IntentBox, SocketBox};
-use crate::boxes::{HTTPServerBox, HTTPRequestBox, HTTPResponseBox, MathBox, TimeBox, DateTimeBox};
+use crate::boxes::{HTTPServerBox, HTTPRequestBox, HTTPResponseBox, WebSocketBox, MathBox, TimeBox, DateTimeBox};
 use crate::boxes::{RandomBox, SoundBox, DebugBox};
 use crate::instance_v2::InstanceBox;
 use crate::channel_box::ChannelBox;
@@ -441,6 +441,11 @@ impl NyashInterpreter {
             return self.execute_http_server_method(http_server_box, method, arguments);
         }
         
+        // WebSocketBox method calls
+        if let Some(ws_box) = obj_value.as_any().downcast_ref::<WebSocketBox>() {
+            return self.execute_websocket_method(ws_box, method, arguments);
+        }
+        
         // HTTPRequestBox method calls
         if let Some(http_request_box) = obj_value.as_any().downcast_ref::<HTTPRequestBox>() {
             return self.execute_http_request_method(http_request_box, method, arguments);
@@ -451,10 +456,28 @@ impl NyashInterpreter {
             return self.execute_http_response_method(http_response_box, method, arguments);
         }
         
-        // P2PBox method calls - Temporarily disabled
-        // if let Some(p2p_box) = obj_value.as_any().downcast_ref::<P2PBox>() {
-        //     return self.execute_p2p_box_method(p2p_box, method, arguments);
-        // }
+        // P2PBox method calls
+        if let Some(p2p_box) = obj_value.as_any().downcast_ref::<crate::boxes::P2PBox>() {
+            return self.execute_p2p_box_method(p2p_box, method, arguments);
+        }
+        
+        // QRBox method calls (非WASM環境のみ)
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(qr_box) = obj_value.as_any().downcast_ref::<crate::boxes::QRBox>() {
+            return self.execute_qr_method(qr_box, method, arguments);
+        }
+        
+        // CanvasBox method calls (非WASM環境のみ)
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(canvas_box) = obj_value.as_any().downcast_ref::<crate::boxes::CanvasBox>() {
+            return self.execute_canvas_method(canvas_box, method, arguments);
+        }
+        
+        // AudioBox method calls (非WASM環境のみ)
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(audio_box) = obj_value.as_any().downcast_ref::<crate::boxes::AudioBox>() {
+            return self.execute_audio_method(audio_box, method, arguments);
+        }
         
         // EguiBox method calls (非WASM環境のみ)
         #[cfg(all(feature = "gui", not(target_arch = "wasm32")))]
@@ -6,6 +6,7 @@
 
 use super::super::*;
 use crate::boxes::{SocketBox, HTTPServerBox, HTTPRequestBox, HTTPResponseBox};
+use crate::method_box::MethodBox;
 
 impl NyashInterpreter {
     /// SocketBox methods
@@ -146,6 +147,23 @@ impl NyashInterpreter {
         }
     }
 
+    /// ルートハンドラー呼び出し: MethodBox → handler(request)、ユーザーBox → handle(request)、
+    /// それ以外は固定レスポンス。実行時エラーは 500 レスポンスになる。
+    fn invoke_route_handler(&mut self, handler: &dyn NyashBox, args: Vec<Box<dyn NyashBox>>) -> Result<Box<dyn NyashBox>, String> {
+        let result = if let Some(method_box) = handler.as_any().downcast_ref::<MethodBox>() {
+            self.invoke_method_box(method_box, args)
+        } else if handler.as_any().is::<InstanceBox>() {
+            let method_box = MethodBox::new(handler.clone_or_share(), "handle".to_string());
+            self.invoke_method_box(&method_box, args)
+        } else {
+            Ok(HTTPServerBox::fixed_response(handler))
+        };
+        result.map_err(|e| {
+            eprintln!("🚨 HTTP handler error: {}", e);
+            e.to_string()
+        })
+    }
+
     /// HTTPServerBox methods
     pub(in crate::interpreter) fn execute_http_server_method(
         &mut self, 
@@ -182,7 +200,8 @@ impl NyashInterpreter {
                     });
                 }
                 
-                Ok(server_box.start())
+                // Handlers run here, on the interpreter thread
+                Ok(server_box.start_with_dispatch(&mut |handler, args| self.invoke_route_handler(handler, args)))
             }
             "stop" => {
                 if !arguments.is_empty() {
@@ -204,6 +223,99 @@ impl NyashInterpreter {
                 let handler = self.execute_expression(&arguments[1])?;
                 Ok(server_box.get(path, handler))
             }
+            "route" => {
+                if arguments.len() != 2 {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("route() expects 2 arguments, got {}", arguments.len()),
+                    });
+                }
+                
+                let path = self.execute_expression(&arguments[0])?;
+                let handler = self.execute_expression(&arguments[1])?;
+                Ok(server_box.route(path, handler))
+            }
+            "post" => {
+                if arguments.len() != 2 {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("post() expects 2 arguments, got {}", arguments.len()),
+                    });
+                }
+                
+                let path = self.execute_expression(&arguments[0])?;
+                let handler = self.execute_expression(&arguments[1])?;
+                Ok(server_box.post(path, handler))
+            }
+            "put" => {
+                if arguments.len() != 2 {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("put() expects 2 arguments, got {}", arguments.len()),
+                    });
+                }
+                
+                let path = self.execute_expression(&arguments[0])?;
+                let handler = self.execute_expression(&arguments[1])?;
+                Ok(server_box.put(path, handler))
+            }
+            "delete" => {
+                if arguments.len() != 2 {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("delete() expects 2 arguments, got {}", arguments.len()),
+                    });
+                }
+                
+                let path = self.execute_expression(&arguments[0])?;
+                let handler = self.execute_expression(&arguments[1])?;
+                Ok(server_box.delete(path, handler))
+            }
+            "websocket" => {
+                if arguments.len() != 2 {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("websocket() expects 2 arguments, got {}", arguments.len()),
+                    });
+                }
+                
+                let path = self.execute_expression(&arguments[0])?;
+                let handler = self.execute_expression(&arguments[1])?;
+                Ok(server_box.websocket(path, handler))
+            }
+            "setStaticPath" => {
+                if arguments.len() != 1 {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("setStaticPath() expects 1 argument, got {}", arguments.len()),
+                    });
+                }
+                
+                let path = self.execute_expression(&arguments[0])?;
+                Ok(server_box.set_static_path(path))
+            }
+            "setTimeout" => {
+                if arguments.len() != 1 {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("setTimeout() expects 1 argument, got {}", arguments.len()),
+                    });
+                }
+                
+                let seconds = self.execute_expression(&arguments[0])?;
+                Ok(server_box.set_timeout(seconds))
+            }
+            "isRunning" => {
+                if !arguments.is_empty() {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("isRunning() expects 0 arguments, got {}", arguments.len()),
+                    });
+                }
+                
+                Ok(server_box.is_running())
+            }
+            "getActiveConnections" => {
+                if !arguments.is_empty() {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("getActiveConnections() expects 0 arguments, got {}", arguments.len()),
+                    });
+                }
+                
+                Ok(server_box.get_active_connections())
+            }
             "toString" => {
                 if !arguments.is_empty() {
                     return Err(RuntimeError::InvalidOperation {
@@ -245,6 +357,109 @@ impl NyashInterpreter {
                 
                 Ok(request_box.get_path())
             }
+            "getQueryString" => {
+                if !arguments.is_empty() {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("getQueryString() expects 0 arguments, got {}", arguments.len()),
+                    });
+                }
+                
+                Ok(request_box.get_query_string())
+            }
+            "getQueryParams" => {
+                if !arguments.is_empty() {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("getQueryParams() expects 0 arguments, got {}", arguments.len()),
+                    });
+                }
+                
+                Ok(request_box.get_query_params())
+            }
+            "getQuery" => {
+                if arguments.len() != 1 {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("getQuery() expects 1 argument, got {}", arguments.len()),
+                    });
+                }
+                
+                let name = self.execute_expression(&arguments[0])?;
+                Ok(request_box.get_query(name))
+            }
+            "getParams" => {
+                if !arguments.is_empty() {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("getParams() expects 0 arguments, got {}", arguments.len()),
+                    });
+                }
+                
+                Ok(request_box.get_params())
+            }
+            "getParam" => {
+                if arguments.len() != 1 {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("getParam() expects 1 argument, got {}", arguments.len()),
+                    });
+                }
+                
+                let name = self.execute_expression(&arguments[0])?;
+                Ok(request_box.get_param(name))
+            }
+            "getHeader" => {
+                if arguments.len() != 1 {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("getHeader() expects 1 argument, got {}", arguments.len()),
+                    });
+                }
+                
+                let name = self.execute_expression(&arguments[0])?;
+                Ok(request_box.get_header(name))
+            }
+            "hasHeader" => {
+                if arguments.len() != 1 {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("hasHeader() expects 1 argument, got {}", arguments.len()),
+                    });
+                }
+                
+                let name = self.execute_expression(&arguments[0])?;
+                Ok(request_box.has_header(name))
+            }
+            "getAllHeaders" => {
+                if !arguments.is_empty() {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("getAllHeaders() expects 0 arguments, got {}", arguments.len()),
+                    });
+                }
+                
+                Ok(request_box.get_all_headers())
+            }
+            "getBody" => {
+                if !arguments.is_empty() {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("getBody() expects 0 arguments, got {}", arguments.len()),
+                    });
+                }
+                
+                Ok(request_box.get_body())
+            }
+            "getContentType" => {
+                if !arguments.is_empty() {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("getContentType() expects 0 arguments, got {}", arguments.len()),
+                    });
+                }
+                
+                Ok(request_box.get_content_type())
+            }
+            "getContentLength" => {
+                if !arguments.is_empty() {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("getContentLength() expects 0 arguments, got {}", arguments.len()),
+                    });
+                }
+                
+                Ok(request_box.get_content_length())
+            }
             "toString" => {
                 if !arguments.is_empty() {
                     return Err(RuntimeError::InvalidOperation {
@@ -279,6 +494,93 @@ impl NyashInterpreter {
                 let message = self.execute_expression(&arguments[1])?;
                 Ok(response_box.set_status(code, message))
             }
+            "setHeader" => {
+                if arguments.len() != 2 {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("setHeader() expects 2 arguments, got {}", arguments.len()),
+                    });
+                }
+                
+                let name = self.execute_expression(&arguments[0])?;
+                let value = self.execute_expression(&arguments[1])?;
+                Ok(response_box.set_header(name, value))
+            }
+            "setContentType" => {
+                if arguments.len() != 1 {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("setContentType() expects 1 argument, got {}", arguments.len()),
+                    });
+                }
+                
+                let content_type = self.execute_expression(&arguments[0])?;
+                Ok(response_box.set_content_type(content_type))
+            }
+            "setBody" => {
+                if arguments.len() != 1 {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("setBody() expects 1 argument, got {}", arguments.len()),
+                    });
+                }
+                
+                let content = self.execute_expression(&arguments[0])?;
+                Ok(response_box.set_body(content))
+            }
+            "appendBody" => {
+                if arguments.len() != 1 {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("appendBody() expects 1 argument, got {}", arguments.len()),
+                    });
+                }
+                
+                let content = self.execute_expression(&arguments[0])?;
+                Ok(response_box.append_body(content))
+            }
+            "getStatus" => {
+                if !arguments.is_empty() {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("getStatus() expects 0 arguments, got {}", arguments.len()),
+                    });
+                }
+                
+                Ok(response_box.get_status())
+            }
+            "getStatusMessage" => {
+                if !arguments.is_empty() {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("getStatusMessage() expects 0 arguments, got {}", arguments.len()),
+                    });
+                }
+                
+                Ok(response_box.get_status_message())
+            }
+            "getHeader" => {
+                if arguments.len() != 1 {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("getHeader() expects 1 argument, got {}", arguments.len()),
+                    });
+                }
+                
+                let name = self.execute_expression(&arguments[0])?;
+                Ok(response_box.get_header(name))
+            }
+            "getAllHeaders" => {
+                if !arguments.is_empty() {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("getAllHeaders() expects 0 arguments, got {}", arguments.len()),
+                    });
+                }
+                
+                Ok(response_box.get_all_headers())
+            }
+            "getBody" => {
+                if !arguments.is_empty() {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("getBody() expects 0 arguments, got {}", arguments.len()),
+                    });
+                }
+                
+                Ok(response_box.get_body())
+            }
             "toHttpString" => {
                 if !arguments.is_empty() {
                     return Err(RuntimeError::InvalidOperation {
@@ -0,0 +1,93 @@
+/*!
+ * Media Box Methods Module
+ *
+ * Contains method implementations for media-related Box types:
+ * - QRBox (execute_qr_method) - QR code encoding and PNG output
+ * - CanvasBox (execute_canvas_method) - Headless 2D drawing and PNG output
+ * - AudioBox (execute_audio_method) - Offline synthesis and WAV output
+ */
+
+use super::super::*;
+use crate::box_trait::{BoolBox, IntegerBox, NyashBox, StringBox, VoidBox};
+use crate::boxes::result::NyashResultBox;
+use crate::boxes::{AudioBox, CanvasBox, QRBox};
+
+impl NyashInterpreter {
+    /// QRBoxのメソッド呼び出しを実行
+    pub(in crate::interpreter) fn execute_qr_method(&mut self, qr_box: &QRBox, method: &str, arguments: &[ASTNode])
+        -> Result<Box<dyn NyashBox>, RuntimeError> {
+        let expected = match method {
+            "generate" | "generateURL" | "saveTo" | "setErrorCorrection" => 1,
+            "setSize" | "setColors" => 2,
+            "generateWiFi" | "generateContact" => 3,
+            "getInfo" | "getDataURL" | "getVersion" | "toString" => 0,
+            _ => return Err(RuntimeError::InvalidOperation {
+                message: format!("Unknown method '{}' for QRBox", method),
+            }),
+        };
+        if arguments.len() != expected {
+            return Err(RuntimeError::InvalidOperation {
+                message: format!("{}() expects {} arguments, got {}", method, expected, arguments.len()),
+            });
+        }
+        let mut args = Vec::with_capacity(arguments.len());
+        for arg in arguments {
+            args.push(self.execute_expression(arg)?.to_string_box().value);
+        }
+        let ok_or_err = |result: Result<Box<dyn NyashBox>, String>| -> Box<dyn NyashBox> {
+            match result {
+                Ok(value) => Box::new(NyashResultBox::new_ok(value)),
+                Err(e) => Box::new(NyashResultBox::new_err(Box::new(StringBox::new(e)))),
+            }
+        };
+        Ok(match method {
+            "generate" => Box::new(BoolBox::new(qr_box.generate(&args[0]))),
+            "generateURL" => Box::new(BoolBox::new(qr_box.generate_url(&args[0]))),
+            "generateWiFi" => Box::new(BoolBox::new(qr_box.generate_wifi(&args[0], &args[1], &args[2]))),
+            "generateContact" => Box::new(BoolBox::new(qr_box.generate_contact(&args[0], &args[1], &args[2]))),
+            "setSize" => {
+                let mut size = [0u32; 2];
+                for (slot, text) in size.iter_mut().zip(&args) {
+                    *slot = text.trim().parse().ok().filter(|&v| v > 0).ok_or_else(|| RuntimeError::TypeError {
+                        message: format!("setSize() expects positive integers, got '{}'", text),
+                    })?;
+                }
+                qr_box.set_size(size[0], size[1]);
+                Box::new(VoidBox::new())
+            }
+            "setColors" => Box::new(BoolBox::new(qr_box.set_colors(&args[0], &args[1]))),
+            "setErrorCorrection" => Box::new(BoolBox::new(qr_box.set_error_correction(&args[0]))),
+            "getInfo" => Box::new(StringBox::new(qr_box.get_info())),
+            "getVersion" => match qr_box.encode() {
+                Ok(qr) => Box::new(IntegerBox::new(qr.version() as i64)),
+                Err(message) => return Err(RuntimeError::InvalidOperation { message }),
+            },
+            "getDataURL" => match qr_box.get_data_url() {
+                Ok(url) => Box::new(StringBox::new(url)),
+                Err(message) => return Err(RuntimeError::InvalidOperation { message }),
+            },
+            "saveTo" => ok_or_err(qr_box.save_to(&args[0]).map(|_| Box::new(BoolBox::new(true)) as Box<dyn NyashBox>)),
+            _ => Box::new(qr_box.to_string_box()),
+        })
+    }
+
+    /// CanvasBoxのメソッド呼び出しを実行
+    pub(in crate::interpreter) fn execute_canvas_method(&mut self, canvas_box: &CanvasBox, method: &str, arguments: &[ASTNode])
+        -> Result<Box<dyn NyashBox>, RuntimeError> {
+        let mut args = Vec::with_capacity(arguments.len());
+        for arg in arguments {
+            args.push(self.execute_expression(arg)?);
+        }
+        canvas_box.invoke(method, &args).map_err(|message| RuntimeError::InvalidOperation { message })
+    }
+
+    /// AudioBoxのメソッド呼び出しを実行
+    pub(in crate::interpreter) fn execute_audio_method(&mut self, audio_box: &AudioBox, method: &str, arguments: &[ASTNode])
+        -> Result<Box<dyn NyashBox>, RuntimeError> {
+        let mut args = Vec::with_capacity(arguments.len());
+        for arg in arguments {
+            args.push(self.execute_expression(arg)?);
+        }
+        audio_box.invoke(method, &args).map_err(|message| RuntimeError::InvalidOperation { message })
+    }
+}
@@ -8,6 +8,7 @@
  * - basic_methods: StringBox, IntegerBox, BoolBox, FloatBox 
  * - collection_methods: ArrayBox, MapBox
  * - io_methods: FileBox, ResultBox ✅ IMPLEMENTED
+ * - media_methods: QRBox, CanvasBox
  * Future modules (planned):
  * - system_methods: TimeBox, DateTimeBox, TimerBox, DebugBox
  * - math_methods: MathBox, RandomBox
@@ -23,5 +24,7 @@ pub mod data_methods;       // BufferBox, JSONBox, RegexBox
 pub mod network_methods;    // HttpClientBox, StreamBox
 pub mod p2p_methods;        // IntentBox, P2PBox
 pub mod http_methods;       // SocketBox, HTTPServerBox, HTTPRequestBox, HTTPResponseBox
+#[cfg(not(target_arch = "wasm32"))]
+pub mod media_methods;      // QRBox, CanvasBox
 
 // Re-export methods for easy access
@@ -4,11 +4,12 @@
  * Contains method implementations for network-related Box types:
  * - HttpClientBox (execute_http_method) - HTTP client operations
  * - StreamBox (execute_stream_method) - Stream processing operations
+ * - WebSocketBox (execute_websocket_method) - WebSocket messaging
  */
 
 use super::super::*;
 use crate::box_trait::NyashBox;
-use crate::boxes::{HttpClientBox, StreamBox};
+use crate::boxes::{HttpClientBox, StreamBox, WebSocketBox};
 
 impl NyashInterpreter {
     /// HttpClientBoxのメソッド呼び出しを実行
@@ -64,6 +65,15 @@ impl NyashInterpreter {
                 let options = self.execute_expression(&arguments[2])?;
                 Ok(http_box.request(method_arg, url, options))
             }
+            "setTimeout" => {
+                if arguments.len() != 1 {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("setTimeout() expects 1 argument, got {}", arguments.len()),
+                    });
+                }
+                let timeout_ms = self.execute_expression(&arguments[0])?;
+                Ok(http_box.set_timeout(timeout_ms))
+            }
             _ => Err(RuntimeError::InvalidOperation {
                 message: format!("Unknown method '{}' for HttpClientBox", method),
             })
@@ -121,4 +131,40 @@ impl NyashInterpreter {
             })
         }
     }
-}
\ No newline at end of file
+
+    /// WebSocketBoxのメソッド呼び出しを実行
+    pub(in crate::interpreter) fn execute_websocket_method(&mut self, ws_box: &WebSocketBox, method: &str, arguments: &[ASTNode])
+        -> Result<Box<dyn NyashBox>, RuntimeError> {
+        let expected = match method {
+            "connect" | "send" | "sendBytes" | "setTimeout" => 1,
+            "receive" | "ping" | "isOpen" | "getCloseCode" | "toString" => 0,
+            "close" if arguments.len() <= 2 => arguments.len(),
+            "close" => 2,
+            _ => return Err(RuntimeError::InvalidOperation {
+                message: format!("Unknown method '{}' for WebSocketBox", method),
+            }),
+        };
+        if arguments.len() != expected {
+            return Err(RuntimeError::InvalidOperation {
+                message: format!("{}() expects {} arguments, got {}", method, expected, arguments.len()),
+            });
+        }
+        let mut args = Vec::with_capacity(arguments.len());
+        for arg in arguments {
+            args.push(self.execute_expression(arg)?);
+        }
+        let mut args = args.into_iter();
+        Ok(match method {
+            "connect" => ws_box.connect(args.next().unwrap()),
+            "send" => ws_box.send(args.next().unwrap()),
+            "sendBytes" => ws_box.send_bytes(args.next().unwrap()),
+            "setTimeout" => ws_box.set_timeout(args.next().unwrap()),
+            "receive" => ws_box.receive(),
+            "ping" => ws_box.ping(),
+            "isOpen" => ws_box.is_open(),
+            "getCloseCode" => ws_box.get_close_code(),
+            "close" => ws_box.close(args.next(), args.next()),
+            _ => Box::new(ws_box.to_string_box()),
+        })
+    }
+}
@@ -6,8 +6,18 @@
 use crate::interpreter::core::NyashInterpreter;
 use crate::interpreter::core::RuntimeError;
 use crate::ast::ASTNode;
-use crate::box_trait::{NyashBox, StringBox};
-use crate::boxes::{IntentBox};
+use crate::box_trait::{NyashBox, StringBox, IntegerBox};
+use crate::boxes::{IntentBox, P2PBox};
+use crate::method_box::MethodBox;
+use crate::transport::Correlation;
+use std::time::Duration;
+
+/// 数値引数を読む（範囲外・数値以外は TypeError）
+fn parse_number<T: std::str::FromStr>(method: &str, value: &dyn NyashBox, expected: &str) -> Result<T, RuntimeError> {
+    value.to_string_box().value.trim().parse::<T>().map_err(|_| RuntimeError::TypeError {
+        message: format!("{}() expects {}", method, expected),
+    })
+}
 
 impl NyashInterpreter {
     /// IntentBoxのメソッド実行 (RwLock版)
@@ -39,75 +49,118 @@ impl NyashInterpreter {
         }
     }
     
-    // P2PBoxのメソッド実行 (Arc<Mutex>版) - Temporarily disabled
-    /*
+    /// P2PBoxのメソッド実行
     pub(in crate::interpreter) fn execute_p2p_box_method(
         &mut self,
         p2p_box: &P2PBox,
         method: &str,
         arguments: &[ASTNode],
     ) -> Result<Box<dyn NyashBox>, RuntimeError> {
-        let data = p2p_box.lock().map_err(|_| RuntimeError::UndefinedVariable {
-            name: "Failed to lock P2PBox".to_string(),
-        })?;
-        
+        let expected = match method {
+            "getNodeId" | "getId" | "getTransportType" | "transport" | "getNodes" => 0,
+            "isReachable" | "poll" | "subscribe" | "unsubscribe" | "addTag" | "removeTag" | "findNodes" => 1,
+            "send" | "on" | "addPeer" | "request" | "publish" => 2,
+            "sendTimeout" | "sendPriority" | "requestTimeout" => 3,
+            _ => return Err(RuntimeError::UndefinedVariable {
+                name: format!("P2PBox method '{}' not found", method),
+            }),
+        };
+        if arguments.len() != expected {
+            return Err(RuntimeError::InvalidOperation {
+                message: format!("{}() expects {} arguments, got {}", method, expected, arguments.len()),
+            });
+        }
+        let mut args = Vec::with_capacity(arguments.len());
+        for arg in arguments {
+            args.push(self.execute_expression(arg)?);
+        }
+        let mut args = args.into_iter();
+        let mut next = || args.next().unwrap();
+
         match method {
             // ノードID取得
-            "getNodeId" | "getId" => {
-                Ok(Box::new(StringBox::new(data.get_node_id().to_string())))
-            }
-            
+            "getNodeId" | "getId" => Ok(p2p_box.get_node_id()),
+
             // トランスポート種類取得
-            "getTransportType" | "transport" => {
-                Ok(Box::new(StringBox::new(data.get_transport_type())))
-            }
-            
+            "getTransportType" | "transport" => Ok(p2p_box.get_transport_type()),
+
             // ノード到達可能性確認
-            "isReachable" => {
-                if arguments.is_empty() {
-                    return Err(RuntimeError::UndefinedVariable {
-                        name: "isReachable requires node_id argument".to_string(),
-                    });
-                }
-                
-                let node_id_result = self.execute_expression(&arguments[0])?;
-                let node_id = node_id_result.to_string_box().value;
-                let reachable = data.is_reachable(&node_id);
-                Ok(Box::new(BoolBox::new(reachable)))
-            }
-            
-            // send メソッド実装
+            "isReachable" => Ok(p2p_box.is_reachable(next())),
+
             "send" => {
-                if arguments.len() < 2 {
-                    return Err(RuntimeError::UndefinedVariable {
-                        name: "send requires (to, intent) arguments".to_string(),
-                    });
-                }
-                
-                let to_result = self.execute_expression(&arguments[0])?;
-                let to = to_result.to_string_box().value;
-                
-                let intent_result = self.execute_expression(&arguments[1])?;
-                
-                // IntentBoxかチェック
-                if let Some(intent_box) = intent_result.as_any().downcast_ref::<IntentBox>() {
-                    match data.send(&to, intent_box.clone()) {
-                        Ok(_) => Ok(Box::new(StringBox::new("sent"))),
-                        Err(e) => Err(RuntimeError::UndefinedVariable {
-                            name: format!("Send failed: {:?}", e),
-                        })
+                let (to, intent) = (next(), next());
+                Ok(p2p_box.send(to, intent))
+            }
+            "sendTimeout" => {
+                let (to, intent, timeout) = (next(), next(), next());
+                let timeout_ms = parse_number::<u64>(method, timeout.as_ref(), "a timeout in milliseconds")?;
+                Ok(p2p_box.send_with_timeout(to, intent, Some(timeout_ms)))
+            }
+            "sendPriority" => {
+                let (to, intent, priority) = (next(), next(), next());
+                let priority = parse_number::<u8>(method, priority.as_ref(), "a priority between 0 and 255")?;
+                Ok(p2p_box.send_with_priority(to, intent, priority))
+            }
+
+            // 応答で完了する FutureBox を返す
+            "request" => {
+                let (to, intent) = (next(), next());
+                Ok(p2p_box.request(to, intent, None))
+            }
+            "requestTimeout" => {
+                let (to, intent, timeout) = (next(), next(), next());
+                let timeout_ms = parse_number::<u64>(method, timeout.as_ref(), "a timeout in milliseconds")?;
+                Ok(p2p_box.request(to, intent, Some(timeout_ms)))
+            }
+            "on" => {
+                let (name, handler) = (next(), next());
+                Ok(p2p_box.on(name, handler))
+            }
+            "addPeer" => {
+                let (node_id, address) = (next(), next());
+                Ok(p2p_box.add_peer(node_id, address))
+            }
+
+            // トピック・サービス発見
+            "subscribe" => Ok(p2p_box.subscribe(next())),
+            "unsubscribe" => Ok(p2p_box.unsubscribe(next())),
+            "publish" => {
+                let (topic, intent) = (next(), next());
+                Ok(p2p_box.publish(topic, intent))
+            }
+            "addTag" => Ok(p2p_box.add_tag(next())),
+            "removeTag" => Ok(p2p_box.remove_tag(next())),
+            "getNodes" => Ok(p2p_box.get_nodes()),
+            "findNodes" => Ok(p2p_box.find_nodes(next())),
+
+            // 受信箱のメッセージをこのスレッドでハンドラーへ配送（request には戻り値で応答）
+            "poll" => {
+                let timeout_ms = next().to_string_box().value.trim().parse::<u64>().unwrap_or(0);
+                let mut delivered = 0;
+                for envelope in p2p_box.take_received(Duration::from_millis(timeout_ms)) {
+                    let name = envelope.intent.get_name().to_string_box().value;
+                    let Some(handler) = p2p_box.handler_for(&name) else { continue };
+                    let Some(method_box) = handler.as_any().downcast_ref::<MethodBox>() else {
+                        return Err(RuntimeError::TypeError {
+                            message: format!("P2PBox handler for '{}' must be a MethodBox, got {}", name, handler.type_name()),
+                        });
+                    };
+                    let args: Vec<Box<dyn NyashBox>> = vec![Box::new(envelope.intent), Box::new(StringBox::new(envelope.from.clone()))];
+                    let result = self.invoke_method_box(method_box, args)?;
+                    delivered += 1;
+                    if let Some(Correlation::Request(id)) = envelope.correlation {
+                        let reply = match result.as_any().downcast_ref::<IntentBox>() {
+                            Some(intent) => intent.clone(),
+                            None => IntentBox::from_value(name, result.as_ref()),
+                        };
+                        if let Err(e) = p2p_box.reply(&envelope.from, id, reply) {
+                            eprintln!("🚨 P2PBox reply to '{}' failed: {:?}", envelope.from, e);
+                        }
                     }
-                } else {
-                    Err(RuntimeError::UndefinedVariable {
-                        name: "Second argument must be an IntentBox".to_string(),
-                    })
                 }
+                Ok(Box::new(IntegerBox::new(delivered)))
             }
-            
-            _ => Err(RuntimeError::UndefinedVariable {
-                name: format!("P2PBox method '{}' not found", method),
-            })
+            _ => unreachable!(),
         }
     }
-    */
-}
\ No newline at end of file
+}
@@ -705,6 +705,16 @@ impl NyashInterpreter {
                 let http_server_box = Box::new(HTTPServerBox::new()) as Box<dyn NyashBox>;
                 return Ok(http_server_box);
             }
+            "WebSocketBox" => {
+                // WebSocketBoxは引数なしで作成（connect(url) で接続）
+                if !arguments.is_empty() {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("WebSocketBox constructor expects 0 arguments, got {}", arguments.len()),
+                    });
+                }
+                let websocket_box = Box::new(crate::boxes::WebSocketBox::new()) as Box<dyn NyashBox>;
+                return Ok(websocket_box);
+            }
             "HTTPRequestBox" => {
                 // HTTPRequestBoxは引数なしで作成
                 if !arguments.is_empty() {
@@ -27,6 +27,7 @@ impl NyashInterpreter {
     /// - pattern() - 音パターン再生
     /// - volumeTest() - 音量テスト
     /// - interval() - 間隔付き音再生
+    /// - record(), setPlayback(), setVolume(), render(), getSamples(), getDuration(), clear() - 記録・WAV書き出し
     pub(super) fn execute_sound_method(&mut self, sound_box: &SoundBox, method: &str, arguments: &[ASTNode]) 
         -> Result<Box<dyn NyashBox>, RuntimeError> {
         // 引数を評価
@@ -109,6 +110,32 @@ impl NyashInterpreter {
                 }
                 Ok(sound_box.interval(arg_values[0].clone_box(), arg_values[1].clone_box()))
             }
+            "setPlayback" | "setVolume" | "render" => {
+                if arg_values.len() != 1 {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("{}() expects 1 argument, got {}", method, arg_values.len()),
+                    });
+                }
+                let arg = arg_values[0].clone_box();
+                Ok(match method {
+                    "setPlayback" => sound_box.set_playback(arg),
+                    "setVolume" => sound_box.set_volume(arg),
+                    _ => sound_box.render(arg),
+                })
+            }
+            "record" | "getSamples" | "getDuration" | "clear" => {
+                if !arg_values.is_empty() {
+                    return Err(RuntimeError::InvalidOperation {
+                        message: format!("{}() expects 0 arguments, got {}", method, arg_values.len()),
+                    });
+                }
+                Ok(match method {
+                    "record" => sound_box.record(),
+                    "getSamples" => sound_box.get_samples(),
+                    "getDuration" => sound_box.get_duration(),
+                    _ => sound_box.clear(),
+                })
+            }
             _ => {
                 Err(RuntimeError::InvalidOperation {
                     message: format!("Unknown SoundBox method: {}", method),
@@ -153,7 +180,7 @@ impl NyashInterpreter {
     /// 4. 'me' 変数の設定
     /// 5. メソッド実行
     /// 6. 戻り値処理
-    fn invoke_method_box(&mut self, method_box: &MethodBox, args: Vec<Box<dyn NyashBox>>) 
+    pub(super) fn invoke_method_box(&mut self, method_box: &MethodBox, args: Vec<Box<dyn NyashBox>>) 
         -> Result<Box<dyn NyashBox>, RuntimeError> {
         // インスタンスを取得
         let instance_arc = method_box.get_instance();
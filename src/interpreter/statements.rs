@@ -34,7 +34,7 @@ impl NyashInterpreter {
         let ty = value.type_name();
         let heavy = matches!(ty,
             "FileBox" | "SocketBox" | "SocketServerBox" | "SocketClientBox" | "SocketConnBox" |
-            "HTTPServerBox" | "HTTPRequestBox" | "HTTPResponseBox" | "HttpClientBox"
+            "HTTPServerBox" | "HTTPRequestBox" | "HTTPResponseBox" | "HttpClientBox" | "WebSocketBox"
         );
         if heavy {
             eprintln!("[lint:must_use] Discarded {} value. Consider assigning it or calling fini().", ty);
@@ -0,0 +1,170 @@
+/*! 📬 Mailbox - Bounded Priority Queue
+ *
+ * ## 📝 概要
+ * ノードの受信メッセージを溜める上限付きキュー。BusEndpoint の配送キューと
+ * P2PBox の受信箱（トランスポートの受信キュー）の両方で使う。
+ *
+ * ## 🏗️ 設計
+ * - **Priority**: 大きい優先度から取り出す（同じ優先度なら投函順）
+ * - **Backpressure**: 満杯なら投函側が空きを待つか `MailboxError::Full`
+ * - **Close**: 閉じると未取り出しの分を破棄し、待っている側を全員起こす
+ */
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// 投函に失敗した理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MailboxError {
+    /// 上限に達したまま空かなかった
+    Full,
+    /// 閉じられている
+    Closed,
+}
+
+/// キュー内の1件（同じ優先度なら投函順）
+struct Queued<T> {
+    priority: u8,
+    seq: u64,
+    item: T,
+}
+
+impl<T> PartialEq for Queued<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Queued<T> {}
+
+impl<T> PartialOrd for Queued<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Queued<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority.cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+struct MailboxState<T> {
+    queue: BinaryHeap<Queued<T>>,
+    next_seq: u64,
+    closed: bool,
+}
+
+/// 上限付き優先度キュー
+pub struct Mailbox<T> {
+    state: Mutex<MailboxState<T>>,
+    changed: Condvar,
+    capacity: usize,
+}
+
+impl<T> std::fmt::Debug for Mailbox<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Mailbox")
+            .field("queued", &self.len())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+impl<T> Mailbox<T> {
+    /// 上限（件数、最低1）を指定して作成
+    pub fn new(capacity: usize) -> Self {
+        Mailbox {
+            state: Mutex::new(MailboxState { queue: BinaryHeap::new(), next_seq: 0, closed: false }),
+            changed: Condvar::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// 投函する。満杯なら `wait` まで空きを待ち（`None` は待たない）、それでも満杯なら `Full`
+    pub fn post(&self, item: T, priority: u8, wait: Option<Duration>) -> Result<(), MailboxError> {
+        let deadline = Some(wait.map(|w| Instant::now() + w));
+        self.insert(item, priority, deadline)
+    }
+
+    /// 空きができるまで待って投函する（閉じられたら `Closed`）
+    pub fn push(&self, item: T, priority: u8) -> Result<(), MailboxError> {
+        self.insert(item, priority, None)
+    }
+
+    /// `deadline`: `None` は無期限、`Some(None)` は待たない
+    fn insert(&self, item: T, priority: u8, deadline: Option<Option<Instant>>) -> Result<(), MailboxError> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if state.closed {
+                return Err(MailboxError::Closed);
+            }
+            if state.queue.len() < self.capacity {
+                break;
+            }
+            let now = Instant::now();
+            state = match deadline {
+                None => self.changed.wait(state).unwrap(),
+                Some(Some(deadline)) if now < deadline => {
+                    self.changed.wait_timeout(state, deadline - now).unwrap().0
+                }
+                Some(_) => return Err(MailboxError::Full),
+            };
+        }
+        let seq = state.next_seq;
+        state.next_seq += 1;
+        state.queue.push(Queued { priority, seq, item });
+        self.changed.notify_all();
+        Ok(())
+    }
+
+    /// 最も優先度の高い1件を取り出す。空なら `wait` まで待つ（`None` は届くか閉じられるまで）
+    pub fn take(&self, wait: Option<Duration>) -> Option<T> {
+        let deadline = wait.map(|w| Instant::now() + w);
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if state.closed {
+                return None;
+            }
+            if let Some(queued) = state.queue.pop() {
+                // 空きを待っている投函側を起こす
+                self.changed.notify_all();
+                return Some(queued.item);
+            }
+            let now = Instant::now();
+            state = match deadline {
+                None => self.changed.wait(state).unwrap(),
+                Some(deadline) if now < deadline => {
+                    self.changed.wait_timeout(state, deadline - now).unwrap().0
+                }
+                Some(_) => return None,
+            };
+        }
+    }
+
+    /// 溜まっている件数
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 閉じて、破棄した件数を返す
+    pub fn close(&self) -> usize {
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        let dropped = state.queue.len();
+        state.queue.clear();
+        self.changed.notify_all();
+        dropped
+    }
+}
@@ -9,35 +9,87 @@
  * - **Singleton Pattern**: プロセス内で唯一のインスタンス
  * - **Node Registry**: 登録されたノードの管理
  * - **Handler Management**: イベントハンドラーの管理
+ * - **Mailbox**: ノード毎の上限付きキュー。送信者は投函するだけで、
+ *   ハンドラーはノード専用のワーカースレッドで優先度順に実行される
+ * - **Inbox mode**: ワーカーを持たず、受信側が `take()` で優先度順に取り出す
+ *   （P2PBox の受信箱。応答メッセージだけは投函時に受信ハンドラーへ直接渡す）
  * - **Async Safe**: Arc<Mutex>による並行アクセス対応
  * 
  * ## 🚀 機能
  * - ノードの登録・解除
- * - メッセージルーティング
+ * - ノードのタグ・購読トピックの公開（サービス発見・トピック配信用）
+ * - メッセージルーティング（優先度・バックプレッシャー付き）
  * - イベントハンドラー管理
  * - エラーハンドリング
  */
 
+use super::mailbox::{Mailbox, MailboxError};
 use crate::boxes::IntentBox;
+use crate::transport::{Correlation, NodeInfo};
 use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
+use std::time::Duration;
 use once_cell::sync::Lazy;
 
 /// Intent処理ハンドラーの型
 pub type IntentHandler = Box<dyn Fn(IntentBox, &str) + Send + Sync>;
 
+/// 全受信メッセージを受け取るハンドラーの型（相関IDなどのメタデータ付き）
+pub type MessageHandler = Box<dyn Fn(&BusMessage) + Send + Sync>;
+
+/// メールボックスの既定上限（メッセージ数）
+pub const DEFAULT_MAILBOX_CAPACITY: usize = 1024;
+
+/// バス上を流れるメッセージ
+#[derive(Debug, Clone)]
+pub struct BusMessage {
+    pub from: String,
+    pub intent: IntentBox,
+    /// 大きいほど先に配送される
+    pub priority: u8,
+    pub correlation: Option<Correlation>,
+    pub topic: Option<String>,
+}
+
 /// バスエンドポイント - ノードの通信インターフェース
 #[derive(Clone)]
 pub struct BusEndpoint {
     pub node_id: String,
     pub handlers: Arc<Mutex<HashMap<String, Vec<IntentHandler>>>>,
+    receivers: Arc<Mutex<Vec<MessageHandler>>>,
+    mailbox: Arc<Mailbox<BusMessage>>,
+    /// true ならワーカーなし（`take()` で取り出す）
+    inbox_mode: bool,
 }
 
 impl BusEndpoint {
     pub fn new(node_id: String) -> Self {
+        Self::with_capacity(node_id, DEFAULT_MAILBOX_CAPACITY)
+    }
+
+    /// メールボックス上限を指定して作成し、配送ワーカーを起動
+    pub fn with_capacity(node_id: String, capacity: usize) -> Self {
+        let endpoint = Self::create(node_id, capacity, false);
+        let worker = endpoint.clone();
+        std::thread::Builder::new()
+            .name(format!("nyash-bus-{}", endpoint.node_id))
+            .spawn(move || worker.run())
+            .expect("failed to spawn message bus worker");
+        endpoint
+    }
+
+    /// ワーカーを持たない受信箱として作成（`take()` で取り出す）
+    pub fn inbox(node_id: String, capacity: usize) -> Self {
+        Self::create(node_id, capacity, true)
+    }
+
+    fn create(node_id: String, capacity: usize, inbox_mode: bool) -> Self {
         BusEndpoint {
             node_id,
             handlers: Arc::new(Mutex::new(HashMap::new())),
+            receivers: Arc::new(Mutex::new(Vec::new())),
+            mailbox: Arc::new(Mailbox::new(capacity)),
+            inbox_mode,
         }
     }
     
@@ -48,17 +100,61 @@ impl BusEndpoint {
             .or_insert_with(Vec::new)
             .push(handler);
     }
+
+    /// 全受信メッセージのハンドラーを追加
+    pub fn add_receiver(&self, receiver: MessageHandler) {
+        self.receivers.lock().unwrap().push(receiver);
+    }
+
+    /// メールボックスへ投函する。満杯なら `wait` まで空きを待ち、それでも満杯なら `MailboxFull`
+    pub fn post(&self, message: BusMessage, wait: Option<Duration>) -> Result<(), SendError> {
+        if self.inbox_mode && matches!(message.correlation, Some(Correlation::Reply(_))) {
+            // 応答は受信箱に並べず、待っている側へすぐ渡す
+            self.deliver(&message);
+            return Ok(());
+        }
+        let priority = message.priority;
+        self.mailbox.post(message, priority, wait).map_err(|e| match e {
+            MailboxError::Full => SendError::MailboxFull(format!(
+                "Mailbox of node '{}' is full ({} messages)", self.node_id, self.mailbox.capacity()
+            )),
+            MailboxError::Closed => SendError::MessageDropped(format!("Node '{}' is shutting down", self.node_id)),
+        })
+    }
+
+    /// 受信箱から優先度順に1件取り出す。空なら `wait` まで待つ
+    pub fn take(&self, wait: Duration) -> Option<BusMessage> {
+        self.mailbox.take(Some(wait))
+    }
+
+    /// 配送待ちのメッセージ数
+    pub fn queued(&self) -> usize {
+        self.mailbox.len()
+    }
+
+    /// メールボックスを閉じる。未配送のメッセージは破棄し、ワーカーを終了させる
+    pub fn close(&self) -> usize {
+        self.mailbox.close()
+    }
+
+    /// 配送ワーカー: 優先度順に取り出してハンドラーを呼ぶ
+    fn run(&self) {
+        while let Some(message) = self.mailbox.take(None) {
+            self.deliver(&message);
+        }
+    }
     
-    /// メッセージを配送
-    pub fn deliver(&self, intent: IntentBox, from: &str) {
-        let handlers = self.handlers.lock().unwrap();
-        let intent_name = intent.get_name().to_string_box().value;
-        
-        if let Some(intent_handlers) = handlers.get(&intent_name) {
+    /// メッセージを配送（intent名のハンドラー → 全受信ハンドラーの順）
+    fn deliver(&self, message: &BusMessage) {
+        let intent_name = message.intent.get_name().to_string_box().value;
+        if let Some(intent_handlers) = self.handlers.lock().unwrap().get(&intent_name) {
             for handler in intent_handlers {
-                handler(intent.clone(), from);
+                handler(message.intent.clone(), &message.from);
             }
         }
+        for receiver in self.receivers.lock().unwrap().iter() {
+            receiver(message);
+        }
     }
 }
 
@@ -69,12 +165,18 @@ pub enum SendError {
     MessageDeliveryFailed(String),
     InvalidMessage(String),
     BusError(String),
+    /// 宛先メールボックスが満杯（バックプレッシャー）
+    MailboxFull(String),
+    /// 宛先が停止中で配送できない
+    MessageDropped(String),
 }
 
 /// MessageBus内部データ
 pub struct MessageBusData {
     /// 登録されたノード一覧
     nodes: HashMap<String, BusEndpoint>,
+    /// ノードが公開したタグ・購読トピック
+    registry: HashMap<String, NodeInfo>,
 }
 
 impl std::fmt::Debug for MessageBusData {
@@ -93,6 +195,7 @@ impl MessageBusData {
     fn new() -> Self {
         MessageBusData {
             nodes: HashMap::new(),
+            registry: HashMap::new(),
         }
     }
     
@@ -101,9 +204,16 @@ impl MessageBusData {
         self.nodes.insert(id, endpoint);
     }
     
-    /// ノードを解除
+    /// ノードを解除（メールボックスを閉じる）
     pub fn unregister_node(&mut self, id: &str) -> bool {
-        self.nodes.remove(id).is_some()
+        self.registry.remove(id);
+        match self.nodes.remove(id) {
+            Some(endpoint) => {
+                endpoint.close();
+                true
+            }
+            None => false,
+        }
     }
     
     /// ノードが存在するかチェック
@@ -111,11 +221,15 @@ impl MessageBusData {
         self.nodes.contains_key(id)
     }
     
-    /// メッセージをルーティング
+    /// ノードのエンドポイントを取得
+    pub fn endpoint(&self, id: &str) -> Option<BusEndpoint> {
+        self.nodes.get(id).cloned()
+    }
+    
+    /// メッセージをルーティング（宛先メールボックスへ投函し、すぐに戻る）
     pub fn route(&self, to: &str, intent: IntentBox, from: &str) -> Result<(), SendError> {
         if let Some(endpoint) = self.nodes.get(to) {
-            endpoint.deliver(intent, from);
-            Ok(())
+            endpoint.post(BusMessage { from: from.to_string(), intent, priority: 0, correlation: None, topic: None }, None)
         } else {
             Err(SendError::NodeNotFound(format!("Node '{}' not found", to)))
         }
@@ -125,6 +239,40 @@ impl MessageBusData {
     pub fn get_nodes(&self) -> Vec<String> {
         self.nodes.keys().cloned().collect()
     }
+
+    /// ノードのタグ・購読トピックを公開（登録済みノードのみ）
+    pub fn announce(&mut self, info: NodeInfo) -> Result<(), SendError> {
+        if !self.nodes.contains_key(&info.node_id) {
+            return Err(SendError::NodeNotFound(format!("Node '{}' not found", info.node_id)));
+        }
+        self.registry.insert(info.node_id.clone(), info);
+        Ok(())
+    }
+
+    /// 登録されたノードの公開情報（未公開のノードはタグ・トピック無し）をID順で取得
+    pub fn get_node_info(&self) -> Vec<NodeInfo> {
+        let mut nodes: Vec<NodeInfo> = self.nodes.keys()
+            .map(|id| self.registry.get(id).cloned().unwrap_or_else(|| NodeInfo::new(id.clone())))
+            .collect();
+        nodes.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+        nodes
+    }
+
+    /// タグを持つノード一覧を取得
+    pub fn get_nodes_with_tag(&self, tag: &str) -> Vec<String> {
+        self.get_node_info().into_iter()
+            .filter(|info| info.tags.contains(tag))
+            .map(|info| info.node_id)
+            .collect()
+    }
+
+    /// トピックの購読ノード一覧を取得
+    pub fn subscribers(&self, topic: &str) -> Vec<String> {
+        self.get_node_info().into_iter()
+            .filter(|info| info.topics.contains(topic))
+            .map(|info| info.node_id)
+            .collect()
+    }
 }
 
 /// グローバルMessageBusシングルトン
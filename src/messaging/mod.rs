@@ -4,6 +4,11 @@
  * in Nyash, implementing the MessageBus singleton pattern for local message routing.
  */
 
+pub mod mailbox;
 pub mod message_bus;
 
-pub use message_bus::{MessageBus, MessageBusData, BusEndpoint, IntentHandler, SendError};
\ No newline at end of file
+pub use mailbox::{Mailbox, MailboxError};
+pub use message_bus::{
+    MessageBus, MessageBusData, BusEndpoint, BusMessage, IntentHandler, MessageHandler, SendError,
+    DEFAULT_MAILBOX_CAPACITY,
+};
\ No newline at end of file
@@ -9,10 +9,20 @@ use super::{
     FunctionSignature, ValueId, ConstValue, BinaryOp, UnaryOp, CompareOp,
     MirType, EffectMask, Effect, BasicBlockIdGenerator, ValueIdGenerator
 };
-use crate::ast::{ASTNode, LiteralValue, BinaryOperator};
+use crate::ast::{ASTNode, LiteralValue, BinaryOperator, Span};
 use std::collections::HashMap;
 use std::collections::HashSet;
 
+/// `canvas.method(canvasId, ...)` を `env.canvas` 外部呼び出しにするメソッド（CanvasBox と同じ名前）
+const CANVAS_EXTERN_METHODS: &[&str] = &[
+    "clear", "clearRect", "resize", "getWidth", "getHeight",
+    "setFillStyle", "setStrokeStyle", "setLineWidth",
+    "fillRect", "strokeRect", "fillCircle", "strokeCircle", "drawLine",
+    "beginPath", "moveTo", "lineTo", "rect", "arc", "closePath", "fill", "stroke",
+    "fillText", "measureText", "save", "restore", "translate", "scale", "rotate", "resetTransform",
+    "getPixel", "getDataURL", "saveTo",
+];
+
 fn builder_debug_enabled() -> bool {
     std::env::var("NYASH_BUILDER_DEBUG").is_ok()
 }
@@ -337,7 +347,7 @@ impl MirBuilder {
                 self.build_me_expression()
             },
             
-            ASTNode::MethodCall { object, method, arguments, .. } => {
+            ASTNode::MethodCall { object, method, arguments, span } => {
                 // Early TypeOp lowering for method-style is()/as()
                 if (method == "is" || method == "as") && arguments.len() == 1 {
                     if let Some(type_name) = Self::extract_string_literal(&arguments[0]) {
@@ -349,17 +359,19 @@ impl MirBuilder {
                         return Ok(dst);
                     }
                 }
-                self.build_method_call(*object.clone(), method.clone(), arguments.clone())
+                let result = self.build_method_call(*object.clone(), method.clone(), arguments.clone())?;
+                self.record_value_span(result, span);
+                Ok(result)
             },
             
             ASTNode::FromCall { parent, method, arguments, .. } => {
                 self.build_from_expression(parent.clone(), method.clone(), arguments.clone())
             },
             
-            ASTNode::Assignment { target, value, .. } => {
+            ASTNode::Assignment { target, value, span } => {
                 // Check if target is a field access for RefSet
                 if let ASTNode::FieldAccess { object, field, .. } = target.as_ref() {
-                    self.build_field_assignment(*object.clone(), field.clone(), *value.clone())
+                    self.build_field_assignment(*object.clone(), field.clone(), *value.clone(), span)
                 } else if let ASTNode::Variable { name, .. } = target.as_ref() {
                     // Plain variable assignment - existing behavior
                     self.build_assignment(name.clone(), *value.clone())
@@ -474,8 +486,10 @@ impl MirBuilder {
                 self.build_field_access(*object.clone(), field.clone())
             },
             
-            ASTNode::New { class, arguments, .. } => {
-                self.build_new_expression(class.clone(), arguments.clone())
+            ASTNode::New { class, arguments, span, .. } => {
+                let dst = self.build_new_expression(class.clone(), arguments.clone())?;
+                self.record_value_span(dst, span);
+                Ok(dst)
             },
             
             // Phase 7: Async operations
@@ -1069,14 +1083,14 @@ impl MirBuilder {
             box_val: dst,
             method: "birth".to_string(),
             args: arg_values,
-            effects: EffectMask::READ.add(Effect::ReadHeap),
+            effects: EffectMask::WRITE,
         })?;
         
         Ok(dst)
     }
     
     /// Build field assignment: object.field = value
-    fn build_field_assignment(&mut self, object: ASTNode, field: String, value: ASTNode) -> Result<ValueId, String> {
+    fn build_field_assignment(&mut self, object: ASTNode, field: String, value: ASTNode, span: Span) -> Result<ValueId, String> {
         // Build the object and value expressions
         let object_value = self.build_expression(object)?;
         let mut value_result = self.build_expression(value)?;
@@ -1096,6 +1110,11 @@ impl MirBuilder {
             field: field.clone(),
             value: value_result,
         })?;
+        if span != Span::unknown() {
+            if let Some(ref mut function) = self.current_function {
+                function.metadata.field_store_spans.insert((object_value, field.clone(), value_result), span);
+            }
+        }
 
         // Emit a write barrier for weak fields (PoC)
         if let Some(class_name) = self.value_origin_newbox.get(&object_value).cloned() {
@@ -1115,6 +1134,16 @@ impl MirBuilder {
         Ok(value_result)
     }
     
+    /// Remember the source span that produced `value` (unknown spans are not recorded)
+    fn record_value_span(&mut self, value: ValueId, span: Span) {
+        if span == Span::unknown() {
+            return;
+        }
+        if let Some(ref mut function) = self.current_function {
+            function.metadata.value_spans.insert(value, span);
+        }
+    }
+    
     /// Start a new basic block
     pub(super) fn start_new_block(&mut self, block_id: BasicBlockId) -> Result<(), String> {
         if let Some(ref mut function) = self.current_function {
@@ -1253,17 +1282,17 @@ impl MirBuilder {
                     self.emit_instruction(MirInstruction::Const { dst: void_id, value: ConstValue::Void })?;
                     return Ok(void_id);
                 },
-                ("canvas", "fillRect") | ("canvas", "fillText") => {
+                // ローカル変数 `canvas` があればそちらのメソッド呼び出し
+                ("canvas", m) if !self.variable_map.contains_key("canvas") && CANVAS_EXTERN_METHODS.contains(&m) => {
+                    let result_id = self.value_gen.next();
                     self.emit_instruction(MirInstruction::ExternCall {
-                        dst: None,
+                        dst: Some(result_id),
                         iface_name: "env.canvas".to_string(),
                         method_name: method,
                         args: arg_values,
                         effects: EffectMask::IO,
                     })?;
-                    let void_id = self.value_gen.next();
-                    self.emit_instruction(MirInstruction::Const { dst: void_id, value: ConstValue::Void })?;
-                    return Ok(void_id);
+                    return Ok(result_id);
                 },
                 _ => {}
             }
@@ -1308,7 +1337,7 @@ impl MirBuilder {
                     dst: Some(result_id),
                     func: func_val,
                     args: call_args,
-                    effects: EffectMask::READ.add(Effect::ReadHeap),
+                    effects: EffectMask::WRITE,
                 })?;
                 return Ok(result_id);
             }
@@ -1329,7 +1358,7 @@ impl MirBuilder {
                         dst: Some(result_id),
                         func: func_val,
                         args: call_args,
-                        effects: EffectMask::READ.add(Effect::ReadHeap),
+                        effects: EffectMask::WRITE,
                     })?;
                     return Ok(result_id);
                 }
@@ -1342,7 +1371,7 @@ impl MirBuilder {
             box_val: object_value,
             method,
             args: arg_values,
-            effects: EffectMask::READ.add(Effect::ReadHeap), // Method calls may have side effects
+            effects: EffectMask::WRITE, // Method calls may have side effects (kept even when the result is unused)
         })?;
         Ok(result_id)
     }
@@ -1426,6 +1455,11 @@ impl MirBuilder {
             })?;
         }
 
+        // Record the box and its weak fields in the module metadata
+        if let Some(ref mut module) = self.current_module {
+            module.metadata.box_weak_fields.insert(name.clone(), weak_fields.clone());
+        }
+
         // Record weak fields for this box
         if !weak_fields.is_empty() {
             let set: HashSet<String> = weak_fields.into_iter().collect();
@@ -0,0 +1,215 @@
+/*!
+ * Dominator Tree - Immediate dominators of a MIR function's CFG
+ *
+ * Uses the iterative algorithm of Cooper, Harvey and Kennedy over reverse postorder.
+ * Exception edges (block holding a `Catch` -> its handler) count as CFG edges, matching
+ * the reachability used by the verifier.
+ */
+
+use super::{BasicBlockId, MirFunction, MirInstruction};
+use std::collections::{HashMap, HashSet};
+
+/// Dominator tree of the blocks reachable from a function's entry
+#[derive(Debug, Clone)]
+pub struct DominatorTree {
+    entry: BasicBlockId,
+    /// Immediate dominator of every reachable block (the entry maps to itself)
+    idom: HashMap<BasicBlockId, BasicBlockId>,
+    /// Reachable blocks in reverse postorder
+    rpo: Vec<BasicBlockId>,
+}
+
+impl DominatorTree {
+    /// Compute the dominator tree of `function`
+    pub fn build(function: &MirFunction) -> Self {
+        let entry = function.entry_block;
+        let rpo = reverse_postorder(function);
+        let order: HashMap<BasicBlockId, usize> = rpo.iter().enumerate().map(|(i, b)| (*b, i)).collect();
+        let preds = cfg_predecessors(function);
+
+        let mut idom: HashMap<BasicBlockId, BasicBlockId> = HashMap::new();
+        if rpo.is_empty() {
+            return Self { entry, idom, rpo };
+        }
+        idom.insert(entry, entry);
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &block in rpo.iter().skip(1) {
+                let mut new_idom: Option<BasicBlockId> = None;
+                for pred in preds.get(&block).into_iter().flatten() {
+                    if !idom.contains_key(pred) {
+                        continue;
+                    }
+                    new_idom = Some(match new_idom {
+                        None => *pred,
+                        Some(current) => intersect(&idom, &order, *pred, current),
+                    });
+                }
+                if let Some(new_idom) = new_idom {
+                    if idom.get(&block) != Some(&new_idom) {
+                        idom.insert(block, new_idom);
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        Self { entry, idom, rpo }
+    }
+
+    /// Whether `block` is reachable from the entry
+    pub fn is_reachable(&self, block: BasicBlockId) -> bool {
+        self.idom.contains_key(&block)
+    }
+
+    /// Immediate dominator of `block` (None for the entry and unreachable blocks)
+    pub fn immediate_dominator(&self, block: BasicBlockId) -> Option<BasicBlockId> {
+        if block == self.entry {
+            return None;
+        }
+        self.idom.get(&block).copied()
+    }
+
+    /// Whether `a` dominates `b` (every block dominates itself)
+    pub fn dominates(&self, a: BasicBlockId, b: BasicBlockId) -> bool {
+        if !self.is_reachable(a) || !self.is_reachable(b) {
+            return false;
+        }
+        let mut current = b;
+        loop {
+            if current == a {
+                return true;
+            }
+            match self.immediate_dominator(current) {
+                Some(parent) => current = parent,
+                None => return false,
+            }
+        }
+    }
+
+    /// Reachable blocks in reverse postorder
+    pub fn reverse_postorder(&self) -> &[BasicBlockId] {
+        &self.rpo
+    }
+}
+
+/// Successors of `block` including its exception handlers
+pub fn cfg_successors(function: &MirFunction, block: BasicBlockId) -> Vec<BasicBlockId> {
+    let Some(bb) = function.blocks.get(&block) else {
+        return Vec::new();
+    };
+    let mut successors: Vec<BasicBlockId> = bb.successors.iter().copied().collect();
+    for inst in bb.all_instructions() {
+        if let MirInstruction::Catch { handler_bb, .. } = inst {
+            if !successors.contains(handler_bb) {
+                successors.push(*handler_bb);
+            }
+        }
+    }
+    successors
+}
+
+/// Predecessors of every block, derived from [`cfg_successors`]
+pub fn cfg_predecessors(function: &MirFunction) -> HashMap<BasicBlockId, Vec<BasicBlockId>> {
+    let mut preds: HashMap<BasicBlockId, Vec<BasicBlockId>> = HashMap::new();
+    for &block in function.blocks.keys() {
+        for succ in cfg_successors(function, block) {
+            preds.entry(succ).or_default().push(block);
+        }
+    }
+    preds
+}
+
+fn reverse_postorder(function: &MirFunction) -> Vec<BasicBlockId> {
+    if !function.blocks.contains_key(&function.entry_block) {
+        return Vec::new();
+    }
+    let mut visited = HashSet::new();
+    let mut postorder = Vec::new();
+    // Iterative DFS: (block, successors, next successor index)
+    let mut stack = vec![(function.entry_block, cfg_successors(function, function.entry_block), 0)];
+    visited.insert(function.entry_block);
+    while let Some((block, succs, next)) = stack.last_mut() {
+        if let Some(&succ) = succs.get(*next) {
+            *next += 1;
+            if function.blocks.contains_key(&succ) && visited.insert(succ) {
+                let succ_succs = cfg_successors(function, succ);
+                stack.push((succ, succ_succs, 0));
+            }
+        } else {
+            postorder.push(*block);
+            stack.pop();
+        }
+    }
+    postorder.reverse();
+    postorder
+}
+
+fn intersect(
+    idom: &HashMap<BasicBlockId, BasicBlockId>,
+    order: &HashMap<BasicBlockId, usize>,
+    mut a: BasicBlockId,
+    mut b: BasicBlockId,
+) -> BasicBlockId {
+    while a != b {
+        while order[&a] > order[&b] {
+            a = idom[&a];
+        }
+        while order[&b] > order[&a] {
+            b = idom[&b];
+        }
+    }
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mir::{BasicBlock, ConstValue, EffectMask, FunctionSignature, MirType};
+
+    /// bb0 -> bb1 -> {bb2, bb3}; bb2 -> bb1 (loop); bb3 returns
+    fn loop_function() -> MirFunction {
+        let signature = FunctionSignature {
+            name: "loop".to_string(),
+            params: vec![],
+            return_type: MirType::Void,
+            effects: EffectMask::PURE,
+        };
+        let entry = BasicBlockId::new(0);
+        let mut f = MirFunction::new(signature, entry);
+        let (header, body, exit) = (BasicBlockId::new(1), BasicBlockId::new(2), BasicBlockId::new(3));
+        let cond = f.next_value_id();
+        f.get_block_mut(entry).unwrap().add_instruction(MirInstruction::Jump { target: header });
+
+        let mut h = BasicBlock::new(header);
+        h.add_instruction(MirInstruction::Const { dst: cond, value: ConstValue::Bool(true) });
+        h.add_instruction(MirInstruction::Branch { condition: cond, then_bb: body, else_bb: exit });
+        f.add_block(h);
+        let mut b = BasicBlock::new(body);
+        b.add_instruction(MirInstruction::Jump { target: header });
+        f.add_block(b);
+        let mut e = BasicBlock::new(exit);
+        e.add_instruction(MirInstruction::Return { value: None });
+        f.add_block(e);
+        f.update_cfg();
+        f
+    }
+
+    #[test]
+    fn test_loop_dominators() {
+        let f = loop_function();
+        let tree = DominatorTree::build(&f);
+        let bb = BasicBlockId::new;
+
+        assert_eq!(tree.immediate_dominator(bb(0)), None);
+        assert_eq!(tree.immediate_dominator(bb(1)), Some(bb(0)));
+        assert_eq!(tree.immediate_dominator(bb(2)), Some(bb(1)));
+        assert_eq!(tree.immediate_dominator(bb(3)), Some(bb(1)));
+        assert!(tree.dominates(bb(1), bb(2)));
+        assert!(!tree.dominates(bb(2), bb(1)));
+        assert!(!tree.dominates(bb(2), bb(3)));
+        assert_eq!(tree.reverse_postorder()[0], bb(0));
+    }
+}
@@ -5,6 +5,7 @@
  */
 
 use super::{BasicBlock, BasicBlockId, ValueId, EffectMask, MirType};
+use crate::ast::Span;
 use std::collections::HashMap;
 use std::fmt;
 
@@ -66,6 +67,12 @@ pub struct FunctionMetadata {
     
     /// Optimization hints
     pub optimization_hints: Vec<String>,
+    
+    /// Source spans of `new` expressions and method calls, keyed by the value they produce
+    pub value_spans: HashMap<ValueId, Span>,
+    
+    /// Source spans of field assignments, keyed by the (reference, field, value) of their RefSet
+    pub field_store_spans: HashMap<(ValueId, String, ValueId), Span>,
 }
 
 impl MirFunction {
@@ -281,6 +288,9 @@ pub struct ModuleMetadata {
     
     /// Optimization level used
     pub optimization_level: u32,
+    
+    /// User-defined boxes declared in the module, with their `weak` fields
+    pub box_weak_fields: HashMap<String, Vec<String>>,
 }
 
 impl MirModule {
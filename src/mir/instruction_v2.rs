@@ -1,16 +1,18 @@
 /*!
- * MIR 25-Instruction Specification Implementation
+ * MIR V2: 25-Instruction Hierarchical Set
  * 
- * Complete hierarchical MIR instruction set based on ChatGPT5 + AI Council design
+ * Three tiers as in the Phase 8.5 spec, with the instructions revised so that legacy MIR lowers
+ * without loss (see the "当初案からの変更" section of
+ * docs/development/roadmap/phases/phase-8/phase_8_5_mir_25_instruction_specification.md)
  */
 
 use super::{ValueId, EffectMask, Effect, BasicBlockId};
 use std::fmt;
 
-/// MIR instruction types - exactly 25 instructions per specification
+/// MIR V2 instruction types (9 + 12 + 4 = 25)
 #[derive(Debug, Clone, PartialEq)]
 pub enum MirInstructionV2 {
-    // === TIER-0: UNIVERSAL CORE (8 instructions) ===
+    // === TIER-0: UNIVERSAL CORE (9 instructions) ===
     
     /// Load a constant value (pure)
     /// `%dst = const value`
@@ -28,6 +30,14 @@ pub enum MirInstructionV2 {
         rhs: ValueId,
     },
     
+    /// Unary operation (pure)
+    /// `%dst = op %operand`
+    UnaryOp {
+        dst: ValueId,
+        op: UnaryOp,
+        operand: ValueId,
+    },
+    
     /// Compare two values (pure)
     /// `%dst = %lhs cmp %rhs`
     Compare {
@@ -109,60 +119,67 @@ pub enum MirInstructionV2 {
         effects: EffectMask,
     },
     
-    /// Safepoint for finalization/interrupts (io)
-    /// `safepoint`
-    Safepoint,
-    
-    /// Get reference as value (pure)
-    /// `%dst = ref_get %reference`
-    RefGet {
-        dst: ValueId,
-        reference: ValueId,
+    /// Host interface call, e.g. `env.console.log` (context-dependent)
+    /// `%dst = extern_call iface.method(%args...)`
+    ExternCall {
+        dst: Option<ValueId>,
+        iface_name: String,
+        method_name: String,
+        args: Vec<ValueId>,
+        effects: EffectMask,
     },
     
-    /// Set/replace reference target with ownership validation (mut)
-    /// `ref_set %reference = %new_target`
-    RefSet {
-        reference: ValueId,
-        new_target: ValueId,
+    /// Type check or cast (pure)
+    /// `%dst = typeop check|cast %value Type`
+    TypeOp {
+        dst: ValueId,
+        op: TypeOpKind,
+        value: ValueId,
+        ty: MirType,
     },
     
-    /// Create weak reference handle (non-owning link) (pure)
-    /// `%dst = weak_new %box`
-    WeakNew {
+    /// Create or load a weak reference (non-owning link) (pure/read)
+    /// `%dst = weakref new|load %value`
+    WeakRef {
         dst: ValueId,
-        box_val: ValueId,
+        op: WeakRefOp,
+        value: ValueId,
     },
     
-    /// Load from weak reference with liveness check (returns null if dead) (pure)
-    /// `%dst = weak_load %weak_ref`
-    WeakLoad {
-        dst: ValueId,
-        weak_ref: ValueId,
+    /// Read/write memory barrier (read/mut)
+    /// `barrier read|write %ptr`
+    Barrier {
+        op: BarrierOp,
+        ptr: ValueId,
     },
     
-    /// Check weak reference validity (returns bool) (pure)
-    /// `%dst = weak_check %weak_ref`
-    WeakCheck {
-        dst: ValueId,
-        weak_ref: ValueId,
+    /// Safepoint for finalization/interrupts (io)
+    /// `safepoint`
+    Safepoint,
+    
+    /// Throw an exception (control)
+    /// `throw %exception`
+    Throw {
+        exception: ValueId,
+        effects: EffectMask,
     },
     
-    /// Send message via Bus system (io)
-    /// `send %bus, %message`
-    Send {
-        bus: ValueId,
-        message: ValueId,
+    /// Exception handler setup (control)
+    /// `catch Type %exception_value -> %handler_bb`
+    Catch {
+        exception_type: Option<String>,
+        exception_value: ValueId,
+        handler_bb: BasicBlockId,
     },
     
-    /// Receive message from Bus system (io)
-    /// `%dst = recv %bus`
-    Recv {
+    /// Wait for a future's value (io)
+    /// `%dst = await %future`
+    Await {
         dst: ValueId,
-        bus: ValueId,
+        future: ValueId,
     },
     
-    // === TIER-2: IMPLEMENTATION ASSISTANCE (5 instructions) ===
+    // === TIER-2: IMPLEMENTATION ASSISTANCE (4 instructions) ===
     
     /// Tail call optimization (control)
     /// `tail_call %func(%args...)`
@@ -185,14 +202,6 @@ pub enum MirInstructionV2 {
         reference: ValueId,
     },
     
-    /// Optimized memory copy for structs/arrays (mut)
-    /// `memcopy %dest, %src, %size`
-    MemCopy {
-        dest: ValueId,
-        src: ValueId,
-        size: ValueId,
-    },
-    
     /// Atomic fence for concurrency ordering at Actor/Port boundaries (io)
     /// `atomic_fence %ordering`
     AtomicFence {
@@ -200,35 +209,8 @@ pub enum MirInstructionV2 {
     },
 }
 
-/// Constant values in MIR
-#[derive(Debug, Clone, PartialEq)]
-pub enum ConstValue {
-    Integer(i64),
-    Float(f64),
-    Bool(bool),
-    String(String),
-    Null,
-    Void,
-}
-
-/// Binary operations
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum BinaryOp {
-    // Arithmetic
-    Add, Sub, Mul, Div, Mod,
-    
-    // Bitwise
-    BitAnd, BitOr, BitXor, Shl, Shr,
-    
-    // Logical
-    And, Or,
-}
-
-/// Comparison operations
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum CompareOp {
-    Eq, Ne, Lt, Le, Gt, Ge,
-}
+// Operand kinds are shared with the legacy instruction set so lowering is a direct mapping
+pub use super::instruction::{ConstValue, BinaryOp, UnaryOp, CompareOp, MirType, TypeOpKind, WeakRefOp, BarrierOp};
 
 /// Atomic ordering for AtomicFence instruction
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -248,6 +230,7 @@ impl MirInstructionV2 {
             // Pure operations
             MirInstructionV2::Const { .. } |
             MirInstructionV2::BinOp { .. } |
+            MirInstructionV2::UnaryOp { .. } |
             MirInstructionV2::Compare { .. } |
             MirInstructionV2::Phi { .. } => EffectMask::PURE,
             
@@ -262,23 +245,31 @@ impl MirInstructionV2 {
             // TIER-1: Nyash Semantics
             // Pure operations
             MirInstructionV2::BoxFieldLoad { .. } |
-            MirInstructionV2::RefGet { .. } |
-            MirInstructionV2::WeakNew { .. } |
-            MirInstructionV2::WeakLoad { .. } |
-            MirInstructionV2::WeakCheck { .. } => EffectMask::PURE,
+            MirInstructionV2::TypeOp { .. } => EffectMask::PURE,
+            MirInstructionV2::WeakRef { op, .. } => match op {
+                WeakRefOp::New => EffectMask::PURE,
+                WeakRefOp::Load => EffectMask::READ,
+            },
             
             // Mutable operations
             MirInstructionV2::NewBox { .. } => EffectMask::MUT.add(Effect::Alloc),
-            MirInstructionV2::BoxFieldStore { .. } |
-            MirInstructionV2::RefSet { .. } => EffectMask::MUT,
+            MirInstructionV2::BoxFieldStore { .. } => EffectMask::MUT,
+            MirInstructionV2::Barrier { op, .. } => match op {
+                BarrierOp::Read => EffectMask::READ.add(Effect::Barrier),
+                BarrierOp::Write => EffectMask::WRITE.add(Effect::Barrier),
+            },
             
             // I/O operations
-            MirInstructionV2::Safepoint |
-            MirInstructionV2::Send { .. } |
-            MirInstructionV2::Recv { .. } => EffectMask::IO,
+            MirInstructionV2::Safepoint => EffectMask::IO,
+            MirInstructionV2::Await { .. } => EffectMask::IO.add(Effect::Async),
+            
+            // Control flow operations
+            MirInstructionV2::Catch { .. } => EffectMask::CONTROL,
             
             // Context-dependent operations
-            MirInstructionV2::BoxCall { effects, .. } => *effects,
+            MirInstructionV2::BoxCall { effects, .. } |
+            MirInstructionV2::ExternCall { effects, .. } |
+            MirInstructionV2::Throw { effects, .. } => *effects,
             
             // TIER-2: Implementation Assistance
             // Control flow operations
@@ -286,8 +277,7 @@ impl MirInstructionV2 {
             
             // Mutable operations
             MirInstructionV2::Adopt { .. } |
-            MirInstructionV2::Release { .. } |
-            MirInstructionV2::MemCopy { .. } => EffectMask::MUT,
+            MirInstructionV2::Release { .. } => EffectMask::MUT,
             
             // I/O operations
             MirInstructionV2::AtomicFence { .. } => EffectMask::IO.add(Effect::Barrier),
@@ -299,18 +289,20 @@ impl MirInstructionV2 {
         match self {
             MirInstructionV2::Const { dst, .. } |
             MirInstructionV2::BinOp { dst, .. } |
+            MirInstructionV2::UnaryOp { dst, .. } |
             MirInstructionV2::Compare { dst, .. } |
             MirInstructionV2::Phi { dst, .. } |
             MirInstructionV2::NewBox { dst, .. } |
             MirInstructionV2::BoxFieldLoad { dst, .. } |
-            MirInstructionV2::RefGet { dst, .. } |
-            MirInstructionV2::WeakNew { dst, .. } |
-            MirInstructionV2::WeakLoad { dst, .. } |
-            MirInstructionV2::WeakCheck { dst, .. } |
-            MirInstructionV2::Recv { dst, .. } => Some(*dst),
+            MirInstructionV2::TypeOp { dst, .. } |
+            MirInstructionV2::WeakRef { dst, .. } |
+            MirInstructionV2::Await { dst, .. } => Some(*dst),
+            
+            MirInstructionV2::Catch { exception_value, .. } => Some(*exception_value),
             
             MirInstructionV2::Call { dst, .. } |
-            MirInstructionV2::BoxCall { dst, .. } => *dst,
+            MirInstructionV2::BoxCall { dst, .. } |
+            MirInstructionV2::ExternCall { dst, .. } => *dst,
             
             _ => None,
         }
@@ -324,6 +316,8 @@ impl MirInstructionV2 {
             MirInstructionV2::BinOp { lhs, rhs, .. } |
             MirInstructionV2::Compare { lhs, rhs, .. } => vec![*lhs, *rhs],
             
+            MirInstructionV2::UnaryOp { operand, .. } => vec![*operand],
+            
             MirInstructionV2::Branch { condition, .. } => vec![*condition],
             
             MirInstructionV2::Jump { .. } => vec![],
@@ -342,7 +336,8 @@ impl MirInstructionV2 {
                 value.map(|v| vec![v]).unwrap_or_default()
             },
             
-            MirInstructionV2::NewBox { args, .. } => args.clone(),
+            MirInstructionV2::NewBox { args, .. } |
+            MirInstructionV2::ExternCall { args, .. } => args.clone(),
             
             MirInstructionV2::BoxFieldLoad { box_val, .. } => vec![*box_val],
             
@@ -354,20 +349,17 @@ impl MirInstructionV2 {
                 values
             },
             
-            MirInstructionV2::Safepoint => vec![],
+            MirInstructionV2::TypeOp { value, .. } |
+            MirInstructionV2::WeakRef { value, .. } => vec![*value],
             
-            MirInstructionV2::RefGet { reference, .. } => vec![*reference],
+            MirInstructionV2::Barrier { ptr, .. } => vec![*ptr],
             
-            MirInstructionV2::RefSet { reference, new_target, .. } => vec![*reference, *new_target],
-            
-            MirInstructionV2::WeakNew { box_val, .. } => vec![*box_val],
-            
-            MirInstructionV2::WeakLoad { weak_ref, .. } |
-            MirInstructionV2::WeakCheck { weak_ref, .. } => vec![*weak_ref],
+            MirInstructionV2::Safepoint |
+            MirInstructionV2::Catch { .. } => vec![],
             
-            MirInstructionV2::Send { bus, message, .. } => vec![*bus, *message],
+            MirInstructionV2::Throw { exception, .. } => vec![*exception],
             
-            MirInstructionV2::Recv { bus, .. } => vec![*bus],
+            MirInstructionV2::Await { future, .. } => vec![*future],
             
             MirInstructionV2::TailCall { func, args, .. } => {
                 let mut values = vec![*func];
@@ -379,18 +371,29 @@ impl MirInstructionV2 {
             
             MirInstructionV2::Release { reference, .. } => vec![*reference],
             
-            MirInstructionV2::MemCopy { dest, src, size, .. } => vec![*dest, *src, *size],
-            
             MirInstructionV2::AtomicFence { .. } => vec![],
         }
     }
     
+    /// Whether this instruction ends a basic block
+    pub fn is_terminator(&self) -> bool {
+        matches!(
+            self,
+            MirInstructionV2::Branch { .. } |
+            MirInstructionV2::Jump { .. } |
+            MirInstructionV2::Return { .. } |
+            MirInstructionV2::Throw { .. } |
+            MirInstructionV2::TailCall { .. }
+        )
+    }
+    
     /// Get the instruction tier (0, 1, or 2)
     pub fn tier(&self) -> u8 {
         match self {
             // Tier-0: Universal Core
             MirInstructionV2::Const { .. } |
             MirInstructionV2::BinOp { .. } |
+            MirInstructionV2::UnaryOp { .. } |
             MirInstructionV2::Compare { .. } |
             MirInstructionV2::Branch { .. } |
             MirInstructionV2::Jump { .. } |
@@ -403,30 +406,61 @@ impl MirInstructionV2 {
             MirInstructionV2::BoxFieldLoad { .. } |
             MirInstructionV2::BoxFieldStore { .. } |
             MirInstructionV2::BoxCall { .. } |
-            MirInstructionV2::Safepoint { .. } |
-            MirInstructionV2::RefGet { .. } |
-            MirInstructionV2::RefSet { .. } |
-            MirInstructionV2::WeakNew { .. } |
-            MirInstructionV2::WeakLoad { .. } |
-            MirInstructionV2::WeakCheck { .. } |
-            MirInstructionV2::Send { .. } |
-            MirInstructionV2::Recv { .. } => 1,
+            MirInstructionV2::ExternCall { .. } |
+            MirInstructionV2::TypeOp { .. } |
+            MirInstructionV2::WeakRef { .. } |
+            MirInstructionV2::Barrier { .. } |
+            MirInstructionV2::Safepoint |
+            MirInstructionV2::Throw { .. } |
+            MirInstructionV2::Catch { .. } |
+            MirInstructionV2::Await { .. } => 1,
             
             // Tier-2: Implementation Assistance
             MirInstructionV2::TailCall { .. } |
             MirInstructionV2::Adopt { .. } |
             MirInstructionV2::Release { .. } |
-            MirInstructionV2::MemCopy { .. } |
             MirInstructionV2::AtomicFence { .. } => 2,
         }
     }
     
+    /// Instruction name, used for statistics
+    pub fn name(&self) -> &'static str {
+        match self {
+            MirInstructionV2::Const { .. } => "Const",
+            MirInstructionV2::BinOp { .. } => "BinOp",
+            MirInstructionV2::UnaryOp { .. } => "UnaryOp",
+            MirInstructionV2::Compare { .. } => "Compare",
+            MirInstructionV2::Branch { .. } => "Branch",
+            MirInstructionV2::Jump { .. } => "Jump",
+            MirInstructionV2::Phi { .. } => "Phi",
+            MirInstructionV2::Call { .. } => "Call",
+            MirInstructionV2::Return { .. } => "Return",
+            MirInstructionV2::NewBox { .. } => "NewBox",
+            MirInstructionV2::BoxFieldLoad { .. } => "BoxFieldLoad",
+            MirInstructionV2::BoxFieldStore { .. } => "BoxFieldStore",
+            MirInstructionV2::BoxCall { .. } => "BoxCall",
+            MirInstructionV2::ExternCall { .. } => "ExternCall",
+            MirInstructionV2::TypeOp { .. } => "TypeOp",
+            MirInstructionV2::WeakRef { .. } => "WeakRef",
+            MirInstructionV2::Barrier { .. } => "Barrier",
+            MirInstructionV2::Safepoint => "Safepoint",
+            MirInstructionV2::Throw { .. } => "Throw",
+            MirInstructionV2::Catch { .. } => "Catch",
+            MirInstructionV2::Await { .. } => "Await",
+            MirInstructionV2::TailCall { .. } => "TailCall",
+            MirInstructionV2::Adopt { .. } => "Adopt",
+            MirInstructionV2::Release { .. } => "Release",
+            MirInstructionV2::AtomicFence { .. } => "AtomicFence",
+        }
+    }
+    
     /// Get a human-readable description of the instruction
     pub fn description(&self) -> &'static str {
         match self {
             // Tier-0
             MirInstructionV2::Const { .. } => "Load constant value",
             MirInstructionV2::BinOp { .. } => "Binary arithmetic operation",
+            MirInstructionV2::UnaryOp { .. } => "Unary operation",
             MirInstructionV2::Compare { .. } => "Compare two values",
             MirInstructionV2::Branch { .. } => "Conditional branch",
             MirInstructionV2::Jump { .. } => "Unconditional jump",
@@ -439,20 +473,19 @@ impl MirInstructionV2 {
             MirInstructionV2::BoxFieldLoad { .. } => "Load Box field value",
             MirInstructionV2::BoxFieldStore { .. } => "Store to Box field",
             MirInstructionV2::BoxCall { .. } => "Box method invocation",
+            MirInstructionV2::ExternCall { .. } => "Host interface call",
+            MirInstructionV2::TypeOp { .. } => "Type check or cast",
+            MirInstructionV2::WeakRef { .. } => "Create or load weak reference",
+            MirInstructionV2::Barrier { .. } => "Memory barrier",
             MirInstructionV2::Safepoint => "Finalization/interrupt safepoint",
-            MirInstructionV2::RefGet { .. } => "Get reference as value",
-            MirInstructionV2::RefSet { .. } => "Set reference target",
-            MirInstructionV2::WeakNew { .. } => "Create weak reference",
-            MirInstructionV2::WeakLoad { .. } => "Load from weak reference",
-            MirInstructionV2::WeakCheck { .. } => "Check weak reference validity",
-            MirInstructionV2::Send { .. } => "Send Bus message",
-            MirInstructionV2::Recv { .. } => "Receive Bus message",
+            MirInstructionV2::Throw { .. } => "Throw exception",
+            MirInstructionV2::Catch { .. } => "Exception handler setup",
+            MirInstructionV2::Await { .. } => "Await future",
             
             // Tier-2
             MirInstructionV2::TailCall { .. } => "Tail call optimization",
             MirInstructionV2::Adopt { .. } => "Transfer ownership",
             MirInstructionV2::Release { .. } => "Release ownership",
-            MirInstructionV2::MemCopy { .. } => "Optimized memory copy",
             MirInstructionV2::AtomicFence { .. } => "Atomic memory fence",
         }
     }
@@ -473,9 +506,9 @@ mod tests {
     fn test_instruction_count() {
         // Verify we have exactly 25 instruction variants
         // This is a compile-time verification
-        let _tier0_count = 8; // Const, BinOp, Compare, Branch, Jump, Phi, Call, Return
-        let _tier1_count = 12; // NewBox, BoxFieldLoad/Store, BoxCall, Safepoint, RefGet/Set, WeakNew/Load/Check, Send, Recv
-        let _tier2_count = 5; // TailCall, Adopt, Release, MemCopy, AtomicFence
+        let _tier0_count = 9; // Const, BinOp, UnaryOp, Compare, Branch, Jump, Phi, Call, Return
+        let _tier1_count = 12; // NewBox, BoxFieldLoad/Store, BoxCall, ExternCall, TypeOp, WeakRef, Barrier, Safepoint, Throw, Catch, Await
+        let _tier2_count = 4; // TailCall, Adopt, Release, AtomicFence
         let _total = _tier0_count + _tier1_count + _tier2_count;
         assert_eq!(_total, 25, "MIR instruction set must have exactly 25 instructions");
     }
@@ -503,12 +536,12 @@ mod tests {
         assert_eq!(store_inst.tier(), 1, "BoxFieldStore should be Tier-1");
         
         // Test io operations
-        let send_inst = MirInstructionV2::Send {
-            bus: value_gen.next(),
-            message: value_gen.next(),
+        let await_inst = MirInstructionV2::Await {
+            dst: value_gen.next(),
+            future: value_gen.next(),
         };
-        assert!(send_inst.effects().is_io(), "Send should be io");
-        assert_eq!(send_inst.tier(), 1, "Send should be Tier-1");
+        assert!(await_inst.effects().is_io(), "Await should be io");
+        assert_eq!(await_inst.tier(), 1, "Await should be Tier-1");
         
         // Test control operations
         let branch_inst = MirInstructionV2::Branch {
@@ -533,11 +566,12 @@ mod tests {
         assert_eq!(adopt_inst.tier(), 2, "Adopt should be Tier-2");
         
         // Test weak reference operations
-        let weak_check = MirInstructionV2::WeakCheck {
+        let weak_new = MirInstructionV2::WeakRef {
             dst: value_gen.next(),
-            weak_ref: value_gen.next(),
+            op: WeakRefOp::New,
+            value: value_gen.next(),
         };
-        assert!(weak_check.effects().is_pure(), "WeakCheck should be pure");
-        assert_eq!(weak_check.tier(), 1, "WeakCheck should be Tier-1");
+        assert!(weak_new.effects().is_pure(), "WeakRef new should be pure");
+        assert_eq!(weak_new.tier(), 1, "WeakRef should be Tier-1");
     }
 }
\ No newline at end of file
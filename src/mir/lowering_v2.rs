@@ -0,0 +1,342 @@
+/*!
+ * Legacy MIR -> MIR V2 Lowering
+ *
+ * Rewrites a module built with the legacy `MirInstruction` set into the 25-instruction
+ * `MirInstructionV2` set:
+ * - TypeCheck/Cast/TypeOp -> TypeOp
+ * - WeakNew/WeakLoad/WeakRef -> WeakRef
+ * - BarrierRead/BarrierWrite/Barrier -> Barrier
+ * - ArrayGet/ArraySet -> BoxCall `get`/`set`
+ * - Print -> ExternCall `env.console.log`
+ * - FutureNew/FutureSet -> ExternCall `env.future.new`/`env.future.set`
+ * - RefGet/RefSet -> BoxFieldLoad/BoxFieldStore
+ * - Copy/RefNew are removed by substituting the source value at every use
+ * - Debug/Nop are dropped
+ *
+ * Block and value ids are kept, so a lowered function can be compared with its source.
+ */
+
+use super::{
+    BarrierOp, BasicBlockId, ConstValue, EffectMask, Effect, FunctionSignature, MirFunction,
+    MirInstruction, MirInstructionV2, MirModule, MirType, TypeOpKind, ValueId, WeakRefOp,
+};
+use std::collections::HashMap;
+
+/// A module in the V2 instruction set
+#[derive(Debug, Clone)]
+pub struct MirModuleV2 {
+    pub name: String,
+    pub functions: HashMap<String, MirFunctionV2>,
+    pub globals: HashMap<String, ConstValue>,
+}
+
+impl MirModuleV2 {
+    pub fn get_function(&self, name: &str) -> Option<&MirFunctionV2> {
+        self.functions.get(name)
+    }
+}
+
+/// A function in the V2 instruction set
+#[derive(Debug, Clone)]
+pub struct MirFunctionV2 {
+    pub signature: FunctionSignature,
+    pub params: Vec<ValueId>,
+    pub entry_block: BasicBlockId,
+    pub blocks: HashMap<BasicBlockId, BasicBlockV2>,
+}
+
+impl MirFunctionV2 {
+    pub fn get_block(&self, id: BasicBlockId) -> Option<&BasicBlockV2> {
+        self.blocks.get(&id)
+    }
+}
+
+/// A basic block in the V2 instruction set; the terminator is the last instruction
+#[derive(Debug, Clone)]
+pub struct BasicBlockV2 {
+    pub id: BasicBlockId,
+    pub instructions: Vec<MirInstructionV2>,
+}
+
+/// Lower every function of `module` to the V2 instruction set
+pub fn lower_module(module: &MirModule) -> Result<MirModuleV2, String> {
+    let mut functions = HashMap::new();
+    for (name, function) in &module.functions {
+        functions.insert(name.clone(), lower_function(function)?);
+    }
+    Ok(MirModuleV2 {
+        name: module.name.clone(),
+        functions,
+        globals: module.globals.clone(),
+    })
+}
+
+/// Lower a single function to the V2 instruction set
+pub fn lower_function(function: &MirFunction) -> Result<MirFunctionV2, String> {
+    let aliases = collect_aliases(function);
+    let resolve = |value: ValueId| -> ValueId {
+        let mut current = value;
+        // Copy chains are acyclic in SSA form; the bound only guards malformed input
+        for _ in 0..=aliases.len() {
+            match aliases.get(&current) {
+                Some(src) => current = *src,
+                None => break,
+            }
+        }
+        current
+    };
+
+    let mut blocks = HashMap::new();
+    for (id, block) in &function.blocks {
+        let mut instructions = Vec::with_capacity(block.instructions.len() + 1);
+        for instruction in block.all_instructions() {
+            if let Some(lowered) = lower_instruction(instruction, &resolve)
+                .map_err(|e| format!("{} in {} ({}): {}", e, function.signature.name, id, instruction))?
+            {
+                instructions.push(lowered);
+            }
+        }
+        blocks.insert(*id, BasicBlockV2 { id: *id, instructions });
+    }
+
+    Ok(MirFunctionV2 {
+        signature: function.signature.clone(),
+        params: function.params.clone(),
+        entry_block: function.entry_block,
+        blocks,
+    })
+}
+
+/// Values defined by Copy/RefNew, mapped to the value they stand for
+fn collect_aliases(function: &MirFunction) -> HashMap<ValueId, ValueId> {
+    let mut aliases = HashMap::new();
+    for block in function.blocks.values() {
+        for instruction in block.all_instructions() {
+            match instruction {
+                MirInstruction::Copy { dst, src } => { aliases.insert(*dst, *src); }
+                MirInstruction::RefNew { dst, box_val } => { aliases.insert(*dst, *box_val); }
+                _ => {}
+            }
+        }
+    }
+    aliases
+}
+
+fn lower_instruction(
+    instruction: &MirInstruction,
+    resolve: &impl Fn(ValueId) -> ValueId,
+) -> Result<Option<MirInstructionV2>, String> {
+    let r = |v: &ValueId| resolve(*v);
+    let rs = |vs: &[ValueId]| vs.iter().map(|v| resolve(*v)).collect::<Vec<_>>();
+
+    let lowered = match instruction {
+        // Carried over unchanged (operands resolved)
+        MirInstruction::Const { dst, value } => MirInstructionV2::Const { dst: *dst, value: value.clone() },
+        MirInstruction::BinOp { dst, op, lhs, rhs } => {
+            MirInstructionV2::BinOp { dst: *dst, op: *op, lhs: r(lhs), rhs: r(rhs) }
+        }
+        MirInstruction::UnaryOp { dst, op, operand } => {
+            MirInstructionV2::UnaryOp { dst: *dst, op: *op, operand: r(operand) }
+        }
+        MirInstruction::Compare { dst, op, lhs, rhs } => {
+            MirInstructionV2::Compare { dst: *dst, op: *op, lhs: r(lhs), rhs: r(rhs) }
+        }
+        MirInstruction::Branch { condition, then_bb, else_bb } => {
+            MirInstructionV2::Branch { condition: r(condition), then_bb: *then_bb, else_bb: *else_bb }
+        }
+        MirInstruction::Jump { target } => MirInstructionV2::Jump { target: *target },
+        MirInstruction::Return { value } => MirInstructionV2::Return { value: value.as_ref().map(r) },
+        MirInstruction::Phi { dst, inputs } => MirInstructionV2::Phi {
+            dst: *dst,
+            inputs: inputs.iter().map(|(bb, v)| (*bb, r(v))).collect(),
+        },
+        MirInstruction::Call { dst, func, args, effects } => {
+            MirInstructionV2::Call { dst: *dst, func: r(func), args: rs(args), effects: *effects }
+        }
+        MirInstruction::BoxCall { dst, box_val, method, args, effects } => MirInstructionV2::BoxCall {
+            dst: *dst,
+            box_val: r(box_val),
+            method: method.clone(),
+            args: rs(args),
+            effects: *effects,
+        },
+        MirInstruction::NewBox { dst, box_type, args } => {
+            MirInstructionV2::NewBox { dst: *dst, box_type: box_type.clone(), args: rs(args) }
+        }
+        MirInstruction::ExternCall { dst, iface_name, method_name, args, effects } => MirInstructionV2::ExternCall {
+            dst: *dst,
+            iface_name: iface_name.clone(),
+            method_name: method_name.clone(),
+            args: rs(args),
+            effects: *effects,
+        },
+        MirInstruction::Safepoint => MirInstructionV2::Safepoint,
+        MirInstruction::Throw { exception, effects } => {
+            MirInstructionV2::Throw { exception: r(exception), effects: *effects }
+        }
+        MirInstruction::Catch { exception_type, exception_value, handler_bb } => MirInstructionV2::Catch {
+            exception_type: exception_type.clone(),
+            exception_value: *exception_value,
+            handler_bb: *handler_bb,
+        },
+        MirInstruction::Await { dst, future } => MirInstructionV2::Await { dst: *dst, future: r(future) },
+
+        // Type operations
+        MirInstruction::TypeOp { dst, op, value, ty } => {
+            MirInstructionV2::TypeOp { dst: *dst, op: *op, value: r(value), ty: ty.clone() }
+        }
+        MirInstruction::TypeCheck { dst, value, expected_type } => MirInstructionV2::TypeOp {
+            dst: *dst,
+            op: TypeOpKind::Check,
+            value: r(value),
+            ty: MirType::Box(expected_type.clone()),
+        },
+        MirInstruction::Cast { dst, value, target_type } => MirInstructionV2::TypeOp {
+            dst: *dst,
+            op: TypeOpKind::Cast,
+            value: r(value),
+            ty: target_type.clone(),
+        },
+
+        // Weak references and barriers
+        MirInstruction::WeakRef { dst, op, value } => MirInstructionV2::WeakRef { dst: *dst, op: *op, value: r(value) },
+        MirInstruction::WeakNew { dst, box_val } => {
+            MirInstructionV2::WeakRef { dst: *dst, op: WeakRefOp::New, value: r(box_val) }
+        }
+        MirInstruction::WeakLoad { dst, weak_ref } => {
+            MirInstructionV2::WeakRef { dst: *dst, op: WeakRefOp::Load, value: r(weak_ref) }
+        }
+        MirInstruction::Barrier { op, ptr } => MirInstructionV2::Barrier { op: *op, ptr: r(ptr) },
+        MirInstruction::BarrierRead { ptr } => MirInstructionV2::Barrier { op: BarrierOp::Read, ptr: r(ptr) },
+        MirInstruction::BarrierWrite { ptr } => MirInstructionV2::Barrier { op: BarrierOp::Write, ptr: r(ptr) },
+
+        // Arrays are ordinary boxes
+        MirInstruction::ArrayGet { dst, array, index } => MirInstructionV2::BoxCall {
+            dst: Some(*dst),
+            box_val: r(array),
+            method: "get".to_string(),
+            args: vec![r(index)],
+            effects: EffectMask::READ,
+        },
+        MirInstruction::ArraySet { array, index, value } => MirInstructionV2::BoxCall {
+            dst: None,
+            box_val: r(array),
+            method: "set".to_string(),
+            args: vec![r(index), r(value)],
+            effects: EffectMask::WRITE,
+        },
+
+        // Fields
+        MirInstruction::RefGet { dst, reference, field } => {
+            MirInstructionV2::BoxFieldLoad { dst: *dst, box_val: r(reference), field: field.clone() }
+        }
+        MirInstruction::RefSet { reference, field, value } => {
+            MirInstructionV2::BoxFieldStore { box_val: r(reference), field: field.clone(), value: r(value) }
+        }
+
+        // Host services
+        MirInstruction::Print { value, effects } => MirInstructionV2::ExternCall {
+            dst: None,
+            iface_name: "env.console".to_string(),
+            method_name: "log".to_string(),
+            args: vec![r(value)],
+            effects: *effects,
+        },
+        MirInstruction::FutureNew { dst, value } => MirInstructionV2::ExternCall {
+            dst: Some(*dst),
+            iface_name: "env.future".to_string(),
+            method_name: "new".to_string(),
+            args: vec![r(value)],
+            effects: EffectMask::PURE.add(Effect::Alloc),
+        },
+        MirInstruction::FutureSet { future, value } => MirInstructionV2::ExternCall {
+            dst: None,
+            iface_name: "env.future".to_string(),
+            method_name: "set".to_string(),
+            args: vec![r(future), r(value)],
+            effects: EffectMask::WRITE,
+        },
+
+        // Eliminated
+        MirInstruction::Copy { .. } | MirInstruction::RefNew { .. } |
+        MirInstruction::Debug { .. } | MirInstruction::Nop => return Ok(None),
+
+        MirInstruction::Load { .. } | MirInstruction::Store { .. } => {
+            return Err("raw Load/Store has no V2 equivalent".to_string());
+        }
+    };
+    Ok(Some(lowered))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mir::{BasicBlock, MirParser};
+
+    fn lower_text(text: &str) -> MirFunctionV2 {
+        let module = MirParser::new().parse_module(text).expect("parse MIR");
+        let lowered = lower_module(&module).expect("lower");
+        lowered.get_function("main").expect("main").clone()
+    }
+
+    fn entry_instructions(function: &MirFunctionV2) -> &[MirInstructionV2] {
+        &function.get_block(function.entry_block).unwrap().instructions
+    }
+
+    #[test]
+    fn test_lowering_maps_legacy_ops() {
+        let signature = FunctionSignature {
+            name: "main".to_string(),
+            params: vec![],
+            return_type: MirType::Void,
+            effects: EffectMask::PURE,
+        };
+        let entry = BasicBlockId::new(0);
+        let mut function = MirFunction::new(signature, entry);
+        let v = ValueId::new;
+        let block: &mut BasicBlock = function.get_block_mut(entry).unwrap();
+        block.add_instruction(MirInstruction::NewBox { dst: v(0), box_type: "ArrayBox".to_string(), args: vec![] });
+        block.add_instruction(MirInstruction::Copy { dst: v(1), src: v(0) });
+        block.add_instruction(MirInstruction::Const { dst: v(2), value: ConstValue::Integer(0) });
+        block.add_instruction(MirInstruction::ArraySet { array: v(1), index: v(2), value: v(2) });
+        block.add_instruction(MirInstruction::ArrayGet { dst: v(3), array: v(1), index: v(2) });
+        block.add_instruction(MirInstruction::WeakNew { dst: v(4), box_val: v(1) });
+        block.add_instruction(MirInstruction::BarrierRead { ptr: v(4) });
+        block.add_instruction(MirInstruction::Cast { dst: v(5), value: v(3), target_type: MirType::Integer });
+        block.add_instruction(MirInstruction::Print { value: v(5), effects: EffectMask::IO });
+        block.add_instruction(MirInstruction::Nop);
+        block.add_instruction(MirInstruction::Return { value: None });
+
+        let lowered = lower_function(&function).expect("lower");
+        let instructions = entry_instructions(&lowered);
+        assert_eq!(instructions.len(), 9);
+        assert!(matches!(&instructions[2], MirInstructionV2::BoxCall { box_val, method, args, .. }
+            if *box_val == v(0) && method == "set" && args == &vec![v(2), v(2)]));
+        assert!(matches!(&instructions[3], MirInstructionV2::BoxCall { dst: Some(d), method, .. } if *d == v(3) && method == "get"));
+        assert!(matches!(&instructions[4], MirInstructionV2::WeakRef { op: WeakRefOp::New, value, .. } if *value == v(0)));
+        assert!(matches!(&instructions[5], MirInstructionV2::Barrier { op: BarrierOp::Read, .. }));
+        assert!(matches!(&instructions[6], MirInstructionV2::TypeOp { op: TypeOpKind::Cast, ty: MirType::Integer, .. }));
+        assert!(matches!(&instructions[7], MirInstructionV2::ExternCall { iface_name, method_name, .. }
+            if iface_name == "env.console" && method_name == "log"));
+        assert!(instructions[8].is_terminator());
+    }
+
+    #[test]
+    fn test_copies_are_resolved_in_phi_inputs() {
+        let function = lower_text(r#"
+define i64 @main() {
+bb0:
+    %0 = const 1
+    %1 = copy %0
+    br label bb1
+
+bb1:
+    %2 = phi [%1, bb0]
+    ret %2
+}
+"#);
+        let header = &function.get_block(BasicBlockId::new(1)).unwrap().instructions;
+        assert_eq!(header[0], MirInstructionV2::Phi { dst: ValueId::new(2), inputs: vec![(BasicBlockId::new(0), ValueId::new(0))] });
+        assert!(entry_instructions(&function).iter().all(|i| i.dst_value() != Some(ValueId::new(1))));
+    }
+}
@@ -7,13 +7,17 @@
 
 pub mod instruction;
 pub mod instruction_v2; // New 25-instruction specification
+pub mod lowering_v2; // Legacy MIR -> 25-instruction set
 pub mod basic_block;
 pub mod function;
 pub mod builder;
 pub mod loop_builder; // SSA loop construction with phi nodes
 pub mod verification;
+pub mod dominators; // Dominator tree used by the SSA verifier
 pub mod ownership_verifier_simple; // Simple ownership forest verification for current MIR
+pub mod ownership_verifier; // Module-wide ownership forest verification (`--verify-ownership`)
 pub mod printer;
+pub mod parser; // Textual MIR reader (round-trips MirPrinter output)
 pub mod value_id;
 pub mod effect;
 pub mod optimizer;
@@ -21,15 +25,18 @@ pub mod optimizer;
 // Re-export main types for easy access
 pub use instruction::{MirInstruction, BinaryOp, CompareOp, UnaryOp, ConstValue, MirType, TypeOpKind, WeakRefOp, BarrierOp};
 pub use instruction_v2::{MirInstructionV2, AtomicOrdering}; // New 25-instruction set
+pub use lowering_v2::{lower_module as lower_module_v2, MirModuleV2, MirFunctionV2, BasicBlockV2};
 pub use basic_block::{BasicBlock, BasicBlockId, BasicBlockIdGenerator};
 pub use function::{MirFunction, MirModule, FunctionSignature};
 pub use builder::MirBuilder;
 pub use verification::{MirVerifier, VerificationError};
+pub use dominators::DominatorTree;
 pub use ownership_verifier_simple::{OwnershipVerifier, OwnershipError, OwnershipStats}; // Simple ownership forest verification
 pub use printer::MirPrinter;
+pub use parser::{MirParser, MirParseError};
 pub use value_id::{ValueId, LocalId, ValueIdGenerator};
 pub use effect::{EffectMask, Effect};
-pub use optimizer::MirOptimizer;
+pub use optimizer::{MirOptimizer, PassVerificationFailure};
 
 /// MIR compilation result
 #[derive(Debug, Clone)]
@@ -70,10 +77,17 @@ impl MirCompiler {
         
         if self.optimize {
             let mut optimizer = MirOptimizer::new();
+            // Opt-in: pinpoint the pass that breaks the MIR (`--verify-after-each-pass`)
+            if std::env::var("NYASH_OPT_VERIFY_EACH_PASS").is_ok() {
+                optimizer = optimizer.with_verify_after_each_pass();
+            }
             let stats = optimizer.optimize_module(&mut module);
             if std::env::var("NYASH_OPT_DIAG_FAIL").is_ok() && stats.diagnostics_reported > 0 {
                 return Err(format!("Diagnostic failure: {} unlowered type-op calls detected", stats.diagnostics_reported));
             }
+            if let Some(failure) = optimizer.pass_failures().first() {
+                return Err(format!("MIR verification failed {}", failure));
+            }
         }
         
         // Verify the generated MIR
@@ -228,4 +242,17 @@ mod tests {
         let mir_dump = compiler.dump_mir(&compile_result.module);
         assert!(mir_dump.contains("catch"), "MIR should contain catch instruction");
     }
+
+    #[test]
+    fn test_unused_method_call_survives_optimization() {
+        // `f.write(x)` is called for its side effect; the unused result must not let DCE drop it
+        let ast = crate::parser::NyashParser::parse_from_string("local f\nf = new FileBox(\"out.txt\")\nf.write(\"x\")\nreturn 0\n").unwrap();
+        let result = MirCompiler::new().compile(ast).unwrap();
+
+        let main = result.module.get_function("main").unwrap();
+        let kept = main.blocks.values()
+            .flat_map(|b| b.all_instructions())
+            .any(|i| matches!(i, MirInstruction::BoxCall { method, .. } if method == "write"));
+        assert!(kept, "unused BoxCall write was eliminated:\n{}", MirPrinter::new().print_module(&result.module));
+    }
 }
@@ -8,13 +8,24 @@
  * - Dead code elimination
  */
 
-use super::{MirModule, MirFunction, MirInstruction, ValueId, MirType, TypeOpKind};
+use super::{MirModule, MirFunction, MirInstruction, ValueId, MirType, TypeOpKind, MirVerifier, VerificationError};
 use std::collections::{HashMap, HashSet};
 
 /// MIR optimization passes
 pub struct MirOptimizer {
     /// Enable debug output for optimization passes
     debug: bool,
+    /// Run the verifier after every pass
+    verify_after_each_pass: bool,
+    /// Errors introduced by a pass (filled in when verify_after_each_pass is on)
+    pass_failures: Vec<PassVerificationFailure>,
+}
+
+/// Verification errors that first appeared after an optimizer pass
+#[derive(Debug, Clone)]
+pub struct PassVerificationFailure {
+    pub pass: &'static str,
+    pub errors: Vec<VerificationError>,
 }
 
 impl MirOptimizer {
@@ -22,6 +33,8 @@ impl MirOptimizer {
     pub fn new() -> Self {
         Self {
             debug: false,
+            verify_after_each_pass: false,
+            pass_failures: Vec::new(),
         }
     }
     
@@ -31,10 +44,24 @@ impl MirOptimizer {
         self
     }
     
+    /// Verify the module after every pass; errors a pass introduces are recorded
+    /// in `pass_failures()` (errors already present in the input are not blamed on it)
+    pub fn with_verify_after_each_pass(mut self) -> Self {
+        self.verify_after_each_pass = true;
+        self
+    }
+    
+    /// Passes that broke the MIR during the last `optimize_module`
+    pub fn pass_failures(&self) -> &[PassVerificationFailure] {
+        &self.pass_failures
+    }
+    
     
     /// Run all optimization passes on a MIR module
     pub fn optimize_module(&mut self, module: &mut MirModule) -> OptimizationStats {
         let mut stats = OptimizationStats::new();
+        self.pass_failures.clear();
+        let mut known_errors = if self.verify_after_each_pass { verify_errors(module) } else { Vec::new() };
         
         if self.debug {
             println!("🚀 Starting MIR optimization passes");
@@ -42,20 +69,25 @@ impl MirOptimizer {
         
         // Pass 1: Dead code elimination
         stats.merge(self.eliminate_dead_code(module));
+        self.verify_after_pass("dead_code_elimination", module, &mut known_errors);
         
         // Pass 2: Pure instruction CSE (Common Subexpression Elimination)
         stats.merge(self.common_subexpression_elimination(module));
+        self.verify_after_pass("common_subexpression_elimination", module, &mut known_errors);
         
         // Pass 3: Pure instruction reordering for better locality
         stats.merge(self.reorder_pure_instructions(module));
+        self.verify_after_pass("reorder_pure_instructions", module, &mut known_errors);
         
         // Pass 4: Intrinsic function optimization
         stats.merge(self.optimize_intrinsic_calls(module));
+        self.verify_after_pass("optimize_intrinsic_calls", module, &mut known_errors);
 
         // Safety-net passesは削除（Phase 2: 変換の一本化）。診断のみ後段で実施。
         
         // Pass 5: BoxField dependency optimization
         stats.merge(self.optimize_boxfield_operations(module));
+        self.verify_after_pass("optimize_boxfield_operations", module, &mut known_errors);
         
         if self.debug {
             println!("✅ Optimization complete: {}", stats);
@@ -67,6 +99,23 @@ impl MirOptimizer {
         stats
     }
     
+    /// Verify after `pass` (when enabled) and record errors not present before it
+    fn verify_after_pass(&mut self, pass: &'static str, module: &MirModule, known_errors: &mut Vec<VerificationError>) {
+        if !self.verify_after_each_pass {
+            return;
+        }
+        let errors = verify_errors(module);
+        let introduced: Vec<VerificationError> = errors.iter()
+            .filter(|e| !known_errors.contains(e))
+            .cloned()
+            .collect();
+        if !introduced.is_empty() {
+            opt_debug(&format!("verification failed after {}: {} new error(s)", pass, introduced.len()));
+            self.pass_failures.push(PassVerificationFailure { pass, errors: introduced });
+        }
+        *known_errors = errors;
+    }
+    
     /// Eliminate dead code (unused values)
     fn eliminate_dead_code(&mut self, module: &mut MirModule) -> OptimizationStats {
         let mut stats = OptimizationStats::new();
@@ -301,6 +350,10 @@ fn map_type_name(name: &str) -> MirType {
     }
 }
 
+fn verify_errors(module: &MirModule) -> Vec<VerificationError> {
+    MirVerifier::new().verify_module(module).err().unwrap_or_default()
+}
+
 fn opt_debug_enabled() -> bool { std::env::var("NYASH_OPT_DEBUG").is_ok() }
 fn opt_debug(msg: &str) { if opt_debug_enabled() { eprintln!("[OPT] {}", msg); } }
 
@@ -376,6 +429,16 @@ impl OptimizationStats {
     }
 }
 
+impl std::fmt::Display for PassVerificationFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "after pass {}:", self.pass)?;
+        for error in &self.errors {
+            write!(f, "\n  • {}", error)?;
+        }
+        Ok(())
+    }
+}
+
 impl std::fmt::Display for OptimizationStats {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, 
@@ -449,6 +512,27 @@ mod tests {
         assert!(debug_optimizer.debug);
     }
     
+    #[test]
+    fn test_verify_after_each_pass_blames_only_new_errors() {
+        let signature = FunctionSignature {
+            name: "main".to_string(),
+            params: vec![],
+            return_type: MirType::Void,
+            effects: super::super::effect::EffectMask::PURE,
+        };
+        let entry = BasicBlockId::new(0);
+        let mut func = MirFunction::new(signature, entry);
+        // Already broken before optimization: no terminator
+        let v = func.next_value_id();
+        func.get_block_mut(entry).unwrap().add_instruction(MirInstruction::Const { dst: v, value: ConstValue::Integer(1) });
+        let mut module = MirModule::new("test".to_string());
+        module.add_function(func);
+
+        let mut optimizer = MirOptimizer::new().with_verify_after_each_pass();
+        optimizer.optimize_module(&mut module);
+        assert!(optimizer.pass_failures().is_empty(), "{:?}", optimizer.pass_failures());
+    }
+
     #[test]
     fn test_optimization_stats() {
         let mut stats = OptimizationStats::new();
@@ -511,7 +595,7 @@ mod tests {
 
         // Ensure TypeOp remains in bb0
         let f = module.get_function("main").unwrap();
-        let block = f.get_block(&bb0).unwrap();
+        let block = f.get_block(bb0).unwrap();
         let has_typeop = block.all_instructions().any(|i| matches!(i, MirInstruction::TypeOp { .. }));
         assert!(has_typeop, "TypeOp should not be dropped by DCE when used by print");
     }
@@ -1,409 +1,697 @@
 /*!
  * Ownership Forest Verification System
- * 
- * Implements ownership forest validation rules per ChatGPT5 specification:
- * - Ownership forest: strong in-degree ≤ 1 
- * - Strong cycle prohibition: strong edges form DAG (forest)
- * - Weak/strong interaction: bidirectional strong → error
- * - RefSet safety: strong→strong requires Release of old target
- * - WeakLoad/WeakCheck deterministic behavior: null/false on expiration
+ *
+ * Checks the ownership forest rules on a built MIR module:
+ * - Ownership forest: strong in-degree ≤ 1
+ * - Strong cycle prohibition: strong field edges form a DAG (forest)
+ * - RefSet safety: a strong field is not overwritten while the box it held was never read.
+ *   Dropping the old box is legal (e.g. re-initializing a field), so this is only a warning
+ *
+ * Boxes are abstracted by allocation site (one node per `new` of a user-defined box,
+ * ArrayBox or MapBox). Box values are followed through locals, fields, container
+ * elements, user-box method calls and returns, so a cycle that is closed inside a
+ * method such as `setParent(me)` is still attributed to the boxes and fields that
+ * form it. Diagnostics carry the source spans the builder recorded in the function
+ * metadata and name boxes and fields instead of ValueIds.
  */
 
-use super::{MirInstruction, ValueId, MirFunction, MirModule};
-use std::collections::{HashMap, HashSet, VecDeque};
+use super::{MirInstruction, MirFunction, MirModule, BasicBlock, ValueId, ConstValue, TypeOpKind, WeakRefOp};
+use crate::ast::Span;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt;
+
+/// Pseudo field name for the elements held by an ArrayBox/MapBox
+const ELEMENT_FIELD: &str = "[]";
+
+/// A `new` expression; every box created there is one node of the ownership graph
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoxSite {
+    pub box_type: String,
+    /// Function containing the `new` (methods are named `Box.method/N`)
+    pub function: String,
+    pub span: Option<Span>,
+}
+
+/// A strong store `owner.field = target`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldStore {
+    /// Box type of the owner
+    pub owner: String,
+    /// Field name, or `[]` for the elements of an ArrayBox/MapBox
+    pub field: String,
+    /// Box type of the stored box
+    pub target: String,
+    /// Function containing the store
+    pub function: String,
+    pub span: Option<Span>,
+}
+
+impl FieldStore {
+    /// `Parent.child`, or `ArrayBox element` for container stores
+    pub fn edge_name(&self) -> String {
+        if self.is_element() {
+            format!("{} element", self.owner)
+        } else {
+            format!("{}.{}", self.owner, self.field)
+        }
+    }
+
+    fn is_element(&self) -> bool {
+        self.field == ELEMENT_FIELD
+    }
+
+    fn describe(&self) -> String {
+        format!("{} = {} in {} ({})", self.edge_name(), self.target, self.function, location(&self.span))
+    }
+}
 
 /// Ownership forest verification errors
 #[derive(Debug, Clone, PartialEq)]
 pub enum OwnershipError {
-    /// Strong reference has multiple owners (violates forest constraint)
+    /// A box is strongly held by more than one owner field (violates forest constraint)
     MultipleStrongOwners {
-        target: ValueId,
-        owners: Vec<ValueId>,
+        target: BoxSite,
+        owners: Vec<FieldStore>,
     },
-    
-    /// Strong reference cycle detected (violates DAG constraint) 
+
+    /// Strong fields form a cycle (violates DAG constraint); its boxes are never freed
     StrongCycle {
-        cycle: Vec<ValueId>,
-    },
-    
-    /// Bidirectional strong references (should be strong + weak)
-    BidirectionalStrong {
-        first: ValueId,
-        second: ValueId,
+        edges: Vec<FieldStore>,
     },
-    
-    /// RefSet without proper Release of old target
+
+    /// A strong field is overwritten before the box it held was ever read (warning only)
     UnsafeRefSet {
-        reference: ValueId,
-        old_target: ValueId,
-        new_target: ValueId,
-    },
-    
-    /// WeakLoad on expired reference (should return null deterministically)
-    WeakLoadExpired {
-        weak_ref: ValueId,
-        dead_target: ValueId,
-    },
-    
-    /// Use after Release (accessing released ownership)
-    UseAfterRelease {
-        value: ValueId,
-        released_at: String,
-    },
-    
-    /// Invalid ownership transfer via Adopt
-    InvalidAdopt {
-        parent: ValueId,
-        child: ValueId,
-        reason: String,
+        previous: FieldStore,
+        store: FieldStore,
     },
 }
 
+impl OwnershipError {
+    /// Concrete fix to suggest, if there is one
+    pub fn suggestion(&self) -> Option<String> {
+        match self {
+            // Break the cycle at the edge that closes it back to the box created first
+            OwnershipError::StrongCycle { edges } => edges.iter().rev().find(|e| !e.is_element()).map(|e| format!(
+                "declare `{}` as weak in box {} (`init {{ weak {} }}`) so the cycle no longer keeps itself alive",
+                e.field, e.owner, e.field
+            )),
+            OwnershipError::MultipleStrongOwners { owners, .. } => owners.iter().rev().find(|e| !e.is_element()).map(|e| format!(
+                "keep a single strong owner, e.g. declare `{}` as weak in box {} (`init {{ weak {} }}`)",
+                e.field, e.owner, e.field
+            )),
+            OwnershipError::UnsafeRefSet { .. } => None,
+        }
+    }
+
+    /// Source positions involved, with a label for each
+    fn locations(&self) -> Vec<(String, Option<Span>)> {
+        match self {
+            OwnershipError::MultipleStrongOwners { target, owners } => {
+                let mut locations = vec![(
+                    format!("new {} in {} ({})", target.box_type, target.function, location(&target.span)),
+                    target.span,
+                )];
+                locations.extend(owners.iter().map(|o| (o.describe(), o.span)));
+                locations
+            }
+            OwnershipError::StrongCycle { edges } => edges.iter().map(|e| (e.describe(), e.span)).collect(),
+            OwnershipError::UnsafeRefSet { previous, store } => vec![
+                (previous.describe(), previous.span),
+                (store.describe(), store.span),
+            ],
+        }
+    }
+
+    /// Full report; with `source`, each location is followed by the offending line
+    pub fn render(&self, source: Option<&str>) -> String {
+        let mut out = match self {
+            OwnershipError::MultipleStrongOwners { target, owners } => format!(
+                "{} has {} strong owners: {}",
+                target.box_type,
+                owners.len(),
+                owners.iter().map(|o| o.edge_name()).collect::<Vec<_>>().join(", ")
+            ),
+            OwnershipError::StrongCycle { edges } => format!(
+                "strong reference cycle: {} -> {}",
+                edges.iter().map(|e| e.edge_name()).collect::<Vec<_>>().join(" -> "),
+                edges.first().map(|e| e.owner.as_str()).unwrap_or("?")
+            ),
+            OwnershipError::UnsafeRefSet { previous, store } => format!(
+                "{} is overwritten in {} before the {} stored in it was read; that box is dropped",
+                store.edge_name(), store.function, previous.target
+            ),
+        };
+        for (label, span) in self.locations() {
+            out.push_str(&format!("\n  at {}", label));
+            if let (Some(source), Some(span)) = (source, span) {
+                out.push('\n');
+                out.push_str(span.error_context(source).trim_end());
+            }
+        }
+        if let Some(help) = self.suggestion() {
+            out.push_str(&format!("\n  help: {}", help));
+        }
+        out
+    }
+}
+
+impl fmt::Display for OwnershipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(None))
+    }
+}
+
+fn location(span: &Option<Span>) -> String {
+    span.map(|s| s.location_string()).unwrap_or_else(|| "unknown location".to_string())
+}
+
+fn is_container(box_type: &str) -> bool {
+    matches!(box_type, "ArrayBox" | "MapBox")
+}
+
+/// Argument stored as an element by a container method call
+fn stored_element<'a>(box_type: &str, method: &str, args: &'a [ValueId]) -> Option<&'a ValueId> {
+    match (box_type, method) {
+        ("ArrayBox", "push") => args.first(),
+        ("ArrayBox", "set") | ("ArrayBox", "insert") | ("MapBox", "set") => args.get(1),
+        _ => None,
+    }
+}
+
+/// Field and stored value of `inst` when its receiver is a box of `owner_type`
+fn stored_field(inst: &MirInstruction, owner_type: &str) -> Option<(String, ValueId)> {
+    match inst {
+        MirInstruction::RefSet { field, value, .. } => Some((field.clone(), *value)),
+        MirInstruction::ArraySet { value, .. } => Some((ELEMENT_FIELD.to_string(), *value)),
+        MirInstruction::BoxCall { method, args, .. } => {
+            stored_element(owner_type, method, args).map(|v| (ELEMENT_FIELD.to_string(), *v))
+        }
+        _ => None,
+    }
+}
+
+fn sorted(set: HashSet<usize>) -> Vec<usize> {
+    let mut v: Vec<usize> = set.into_iter().collect();
+    v.sort_unstable();
+    v
+}
+
+/// `main` first, then the remaining functions by name, so sites get stable ids
+fn ordered_functions(module: &MirModule) -> Vec<&MirFunction> {
+    let mut functions: Vec<&MirFunction> = module.functions.values().collect();
+    functions.sort_by(|a, b| {
+        (a.signature.name != "main", &a.signature.name).cmp(&(b.signature.name != "main", &b.signature.name))
+    });
+    functions
+}
+
+fn ordered_blocks(function: &MirFunction) -> Vec<&BasicBlock> {
+    let mut blocks: Vec<&BasicBlock> = function.blocks.values().collect();
+    blocks.sort_by_key(|b| b.id);
+    blocks
+}
+
+/// Per-function facts the analysis consults repeatedly
+#[derive(Default)]
+struct FunctionFacts {
+    /// String constants (targets of `Call`)
+    strings: HashMap<ValueId, String>,
+    /// Copy/RefNew aliases: dst -> src
+    copies: HashMap<ValueId, ValueId>,
+    /// Values produced by WeakNew / WeakRef(New)
+    weak_values: HashSet<ValueId>,
+    /// Values used by anything other than their own `birth` call or as a stored field value
+    escaping: HashSet<ValueId>,
+}
+
+impl FunctionFacts {
+    fn collect(function: &MirFunction) -> Self {
+        let mut facts = Self::default();
+        for block in function.blocks.values() {
+            for inst in block.all_instructions() {
+                match inst {
+                    MirInstruction::Const { dst, value: ConstValue::String(s) } => { facts.strings.insert(*dst, s.clone()); }
+                    MirInstruction::Copy { dst, src } | MirInstruction::RefNew { dst, box_val: src } => { facts.copies.insert(*dst, *src); }
+                    MirInstruction::WeakNew { dst, .. } | MirInstruction::WeakRef { dst, op: WeakRefOp::New, .. } => { facts.weak_values.insert(*dst); }
+                    _ => {}
+                }
+                match inst {
+                    MirInstruction::BoxCall { method, args, .. } if method == "birth" => facts.escaping.extend(args.iter().copied()),
+                    MirInstruction::RefSet { reference, .. } => { facts.escaping.insert(*reference); }
+                    other => facts.escaping.extend(other.used_values()),
+                }
+            }
+        }
+        facts
+    }
+
+    fn resolve(&self, mut value: ValueId) -> ValueId {
+        for _ in 0..=self.copies.len() {
+            match self.copies.get(&value) {
+                Some(src) => value = *src,
+                None => break,
+            }
+        }
+        value
+    }
+}
+
+/// A deduplicated strong store between two sites
+struct StrongEdge {
+    owner: usize,
+    target: usize,
+    store: FieldStore,
+    /// `x.f = x` on the very same value (not merely two boxes from the same `new`)
+    self_store: bool,
+}
+
 /// Ownership forest verifier
 pub struct OwnershipVerifier {
-    /// Strong ownership edges: child -> parent
-    strong_edges: HashMap<ValueId, ValueId>,
-    
-    /// Weak reference edges: weak_ref -> target
-    weak_edges: HashMap<ValueId, ValueId>,
-    
-    /// Released references (no longer valid for ownership)
-    released: HashSet<ValueId>,
-    
-    /// Track live weak references for liveness checking
-    live_weak_refs: HashSet<ValueId>,
-    
-    /// Track dead targets for WeakLoad/WeakCheck determinism
-    dead_targets: HashSet<ValueId>,
+    /// Allocation sites in creation order (`main` first)
+    sites: Vec<BoxSite>,
+
+    /// Site of each tracked NewBox: (function, dst) -> site
+    site_at: HashMap<(String, ValueId), usize>,
+
+    /// Sites each value may hold, per function
+    points_to: HashMap<String, HashMap<ValueId, HashSet<usize>>>,
+
+    /// Sites reachable through (site, field)
+    heap: HashMap<(usize, String), HashSet<usize>>,
+
+    /// Sites each function may return
+    returns: HashMap<String, HashSet<usize>>,
+
+    /// Findings of the last `verify_module` that do not fail verification
+    warnings: Vec<OwnershipError>,
 }
 
 impl OwnershipVerifier {
     /// Create a new ownership verifier
     pub fn new() -> Self {
         Self {
-            strong_edges: HashMap::new(),
-            weak_edges: HashMap::new(),
-            released: HashSet::new(),
-            live_weak_refs: HashSet::new(),
-            dead_targets: HashSet::new(),
+            sites: Vec::new(),
+            site_at: HashMap::new(),
+            points_to: HashMap::new(),
+            heap: HashMap::new(),
+            returns: HashMap::new(),
+            warnings: Vec::new(),
         }
     }
-    
-    /// Verify ownership forest properties for an entire module
+
+    /// Verify ownership forest properties for an entire module.
+    /// Overwritten-before-read stores are collected into `warnings()` instead of failing
     pub fn verify_module(&mut self, module: &MirModule) -> Result<(), Vec<OwnershipError>> {
-        let mut errors = Vec::new();
-        
-        for function in module.functions.values() {
-            if let Err(mut function_errors) = self.verify_function(function) {
-                errors.append(&mut function_errors);
+        *self = Self::new();
+        let functions = ordered_functions(module);
+        let facts: HashMap<&str, FunctionFacts> = functions
+            .iter()
+            .map(|f| (f.signature.name.as_str(), FunctionFacts::collect(f)))
+            .collect();
+        self.collect_sites(module, &functions);
+
+        // Propagate box values through locals, fields, calls and returns until nothing changes
+        loop {
+            let mut changed = false;
+            for function in &functions {
+                let function_facts = &facts[function.signature.name.as_str()];
+                for block in ordered_blocks(function) {
+                    for inst in block.all_instructions() {
+                        changed |= self.flow(module, function, function_facts, inst);
+                    }
+                }
+            }
+            if !changed {
+                break;
             }
         }
-        
+
+        let edges = self.strong_edges(module, &functions, &facts);
+        let mut errors = self.find_cycles(&edges);
+        errors.extend(self.find_shared_targets(&edges));
+        self.warnings = self.find_unsafe_ref_sets(module, &functions, &facts);
+
         if errors.is_empty() {
             Ok(())
         } else {
             Err(errors)
         }
     }
-    
-    /// Verify ownership forest properties for a single function
-    pub fn verify_function(&mut self, function: &MirFunction) -> Result<(), Vec<OwnershipError>> {
-        let mut errors = Vec::new();
-        
-        // Reset state for this function
-        self.strong_edges.clear();
-        self.weak_edges.clear();
-        self.released.clear();
-        self.live_weak_refs.clear();
-        self.dead_targets.clear();
-        
-        // Process all instructions to build ownership graph
-        for block in function.blocks.values() {
-            for instruction in block.all_instructions() {
-                if let Err(mut inst_errors) = self.process_instruction(instruction) {
-                    errors.append(&mut inst_errors);
+
+    /// Warnings of the last `verify_module` (strong fields overwritten before being read)
+    pub fn warnings(&self) -> &[OwnershipError] {
+        &self.warnings
+    }
+
+    /// Number of allocation sites tracked by the last `verify_module`
+    pub fn site_count(&self) -> usize {
+        self.sites.len()
+    }
+
+    /// One site per NewBox of a user-defined box or container
+    fn collect_sites(&mut self, module: &MirModule, functions: &[&MirFunction]) {
+        for function in functions {
+            for block in ordered_blocks(function) {
+                for inst in block.all_instructions() {
+                    if let MirInstruction::NewBox { dst, box_type, .. } = inst {
+                        if !module.metadata.box_weak_fields.contains_key(box_type) && !is_container(box_type) {
+                            continue;
+                        }
+                        self.site_at.insert((function.signature.name.clone(), *dst), self.sites.len());
+                        self.sites.push(BoxSite {
+                            box_type: box_type.clone(),
+                            function: function.signature.name.clone(),
+                            span: function.metadata.value_spans.get(dst).copied(),
+                        });
+                    }
                 }
             }
         }
-        
-        // Verify global ownership forest properties
-        if let Err(mut forest_errors) = self.verify_ownership_forest() {
-            errors.append(&mut forest_errors);
+    }
+
+    fn pts(&self, function: &str, value: ValueId) -> HashSet<usize> {
+        self.points_to.get(function).and_then(|m| m.get(&value)).cloned().unwrap_or_default()
+    }
+
+    fn add(&mut self, function: &str, value: ValueId, sites: &HashSet<usize>) -> bool {
+        if sites.is_empty() {
+            return false;
         }
-        
-        if errors.is_empty() {
-            Ok(())
-        } else {
-            Err(errors)
+        let set = self.points_to.entry(function.to_string()).or_default().entry(value).or_default();
+        let before = set.len();
+        set.extend(sites);
+        set.len() != before
+    }
+
+    fn load(&self, function: &str, reference: ValueId, field: &str) -> HashSet<usize> {
+        let mut loaded = HashSet::new();
+        for owner in self.pts(function, reference) {
+            if let Some(targets) = self.heap.get(&(owner, field.to_string())) {
+                loaded.extend(targets);
+            }
         }
+        loaded
     }
-    
-    /// Process a single instruction and update ownership state
-    fn process_instruction(&mut self, instruction: &MirInstruction) -> Result<(), Vec<OwnershipError>> {
-        let mut errors = Vec::new();
-        
-        match instruction {
-            // NewBox creates a new ownership root
-            MirInstructionV2::NewBox { dst, .. } => {
-                // New boxes are ownership roots (no parent)
-                // Clear any existing ownership for this value
-                self.strong_edges.remove(dst);
-            },
-            
-            // RefSet changes ownership relationships
-            MirInstructionV2::RefSet { reference, new_target } => {
-                // Check if the reference currently has a strong target
-                if let Some(old_target) = self.strong_edges.get(reference) {
-                    // Strong→Strong replacement requires explicit Release
-                    if !self.released.contains(old_target) {
-                        errors.push(OwnershipError::UnsafeRefSet {
-                            reference: *reference,
-                            old_target: *old_target,
-                            new_target: *new_target,
-                        });
-                    }
-                }
-                
-                // Set new strong ownership
-                self.strong_edges.insert(*reference, *new_target);
-                
-                // Verify no multiple strong owners after this change
-                if let Err(mut multiple_errors) = self.check_multiple_owners(*new_target) {
-                    errors.append(&mut multiple_errors);
-                }
+
+    fn store(&mut self, owner: usize, field: &str, targets: &HashSet<usize>) -> bool {
+        if targets.is_empty() {
+            return false;
+        }
+        let set = self.heap.entry((owner, field.to_string())).or_default();
+        let before = set.len();
+        set.extend(targets);
+        set.len() != before
+    }
+
+    /// Bind `incoming` to the callee's parameters and its returns to `dst`
+    fn flow_call(&mut self, caller: &str, callee: &MirFunction, incoming: Vec<HashSet<usize>>, dst: Option<ValueId>) -> bool {
+        let callee_name = callee.signature.name.as_str();
+        let mut changed = false;
+        for (param, sites) in callee.params.iter().zip(incoming.iter()) {
+            changed |= self.add(callee_name, *param, sites);
+        }
+        if let Some(dst) = dst {
+            let returned = self.returns.get(callee_name).cloned().unwrap_or_default();
+            changed |= self.add(caller, dst, &returned);
+        }
+        changed
+    }
+
+    /// Transfer function of one instruction; true if any set grew
+    fn flow(&mut self, module: &MirModule, function: &MirFunction, facts: &FunctionFacts, inst: &MirInstruction) -> bool {
+        let name = function.signature.name.as_str();
+        match inst {
+            MirInstruction::NewBox { dst, .. } => match self.site_at.get(&(name.to_string(), *dst)) {
+                Some(&site) => self.add(name, *dst, &HashSet::from([site])),
+                None => false,
             },
-            
-            // Adopt transfers ownership
-            MirInstructionV2::Adopt { parent, child } => {
-                // Verify the adoption is valid
-                if self.released.contains(child) {
-                    errors.push(OwnershipError::InvalidAdopt {
-                        parent: *parent,
-                        child: *child,
-                        reason: "Cannot adopt released reference".to_string(),
-                    });
+            MirInstruction::Copy { dst, src }
+            | MirInstruction::RefNew { dst, box_val: src }
+            | MirInstruction::WeakNew { dst, box_val: src }
+            | MirInstruction::WeakLoad { dst, weak_ref: src }
+            | MirInstruction::WeakRef { dst, value: src, .. }
+            | MirInstruction::Cast { dst, value: src, .. }
+            | MirInstruction::TypeOp { dst, op: TypeOpKind::Cast, value: src, .. } => {
+                let sites = self.pts(name, *src);
+                self.add(name, *dst, &sites)
+            }
+            MirInstruction::Phi { dst, inputs } => {
+                let mut sites = HashSet::new();
+                for (_, value) in inputs {
+                    sites.extend(self.pts(name, *value));
                 }
-                
-                // Check for cycle creation
-                if self.would_create_cycle(*parent, *child) {
-                    errors.push(OwnershipError::InvalidAdopt {
-                        parent: *parent,
-                        child: *child,
-                        reason: "Would create strong cycle".to_string(),
-                    });
+                self.add(name, *dst, &sites)
+            }
+            MirInstruction::RefGet { dst, reference, field } => {
+                let sites = self.load(name, *reference, field);
+                self.add(name, *dst, &sites)
+            }
+            MirInstruction::ArrayGet { dst, array, .. } => {
+                let sites = self.load(name, *array, ELEMENT_FIELD);
+                self.add(name, *dst, &sites)
+            }
+            MirInstruction::RefSet { reference, field, value } => {
+                let targets = self.pts(name, *value);
+                let mut changed = false;
+                for owner in self.pts(name, *reference) {
+                    changed |= self.store(owner, field, &targets);
                 }
-                
-                // Establish strong ownership
-                self.strong_edges.insert(*child, *parent);
-            },
-            
-            // Release removes ownership
-            MirInstructionV2::Release { reference } => {
-                self.strong_edges.remove(reference);
-                self.released.insert(*reference);
-                
-                // Mark any targets of this reference as potentially dead
-                if let Some(target) = self.weak_edges.get(reference) {
-                    self.dead_targets.insert(*target);
+                changed
+            }
+            MirInstruction::ArraySet { array, value, .. } => {
+                let targets = self.pts(name, *value);
+                let mut changed = false;
+                for owner in self.pts(name, *array) {
+                    changed |= self.store(owner, ELEMENT_FIELD, &targets);
                 }
-            },
-            
-            // WeakNew creates weak reference
-            MirInstructionV2::WeakNew { dst, box_val } => {
-                self.weak_edges.insert(*dst, *box_val);
-                self.live_weak_refs.insert(*dst);
-            },
-            
-            // WeakLoad checks liveness
-            MirInstructionV2::WeakLoad { weak_ref, .. } => {
-                if let Some(target) = self.weak_edges.get(weak_ref) {
-                    if self.dead_targets.contains(target) {
-                        // This is actually expected behavior - WeakLoad should return null
-                        // We track this for deterministic behavior verification
+                changed
+            }
+            MirInstruction::BoxCall { dst, box_val, method, args, .. } => {
+                let mut changed = false;
+                for site in sorted(self.pts(name, *box_val)) {
+                    let box_type = self.sites[site].box_type.clone();
+                    if let Some(callee) = module.functions.get(&format!("{}.{}/{}", box_type, method, args.len())) {
+                        let mut incoming = vec![HashSet::from([site])];
+                        incoming.extend(args.iter().map(|a| self.pts(name, *a)));
+                        changed |= self.flow_call(name, callee, incoming, *dst);
+                    } else if let Some(value) = stored_element(&box_type, method, args) {
+                        let targets = self.pts(name, *value);
+                        changed |= self.store(site, ELEMENT_FIELD, &targets);
+                    } else if let Some(dst) = dst {
+                        let elements = self.heap.get(&(site, ELEMENT_FIELD.to_string())).cloned().unwrap_or_default();
+                        changed |= self.add(name, *dst, &elements);
                     }
                 }
-            },
-            
-            // WeakCheck verifies liveness
-            MirInstructionV2::WeakCheck { weak_ref, .. } => {
-                if let Some(target) = self.weak_edges.get(weak_ref) {
-                    if self.dead_targets.contains(target) {
-                        // This is expected - WeakCheck should return false
+                changed
+            }
+            MirInstruction::Call { dst, func, args, .. } => {
+                match facts.strings.get(&facts.resolve(*func)).and_then(|callee| module.functions.get(callee)) {
+                    Some(callee) => {
+                        let incoming = args.iter().map(|a| self.pts(name, *a)).collect();
+                        self.flow_call(name, callee, incoming, *dst)
                     }
+                    None => false,
                 }
-            },
-            
-            // Other instructions don't affect ownership
-            _ => {},
-        }
-        
-        if errors.is_empty() {
-            Ok(())
-        } else {
-            Err(errors)
+            }
+            MirInstruction::Return { value: Some(value) } => {
+                let sites = self.pts(name, *value);
+                if sites.is_empty() {
+                    return false;
+                }
+                let returned = self.returns.entry(name.to_string()).or_default();
+                let before = returned.len();
+                returned.extend(sites);
+                returned.len() != before
+            }
+            _ => false,
         }
     }
-    
-    /// Verify global ownership forest properties
-    fn verify_ownership_forest(&self) -> Result<(), Vec<OwnershipError>> {
-        let mut errors = Vec::new();
-        
-        // Check for multiple strong owners (violates forest constraint)
-        let mut target_owners: HashMap<ValueId, Vec<ValueId>> = HashMap::new();
-        for (child, parent) in &self.strong_edges {
-            target_owners.entry(*parent).or_insert_with(Vec::new).push(*child);
+
+    fn is_strong(module: &MirModule, facts: &FunctionFacts, owner_type: &str, field: &str, value: ValueId) -> bool {
+        if facts.weak_values.contains(&value) {
+            return false;
         }
-        
-        for (target, owners) in target_owners {
-            if owners.len() > 1 {
-                errors.push(OwnershipError::MultipleStrongOwners { target, owners });
+        !module.metadata.box_weak_fields.get(owner_type).is_some_and(|weak| weak.iter().any(|w| w == field))
+    }
+
+    /// Span recorded for the store performed by `inst`
+    fn store_span(function: &MirFunction, inst: &MirInstruction) -> Option<Span> {
+        match inst {
+            MirInstruction::RefSet { reference, field, value } => {
+                function.metadata.field_store_spans.get(&(*reference, field.clone(), *value)).copied()
             }
+            MirInstruction::BoxCall { dst: Some(dst), .. } => function.metadata.value_spans.get(dst).copied(),
+            _ => None,
         }
-        
-        // Check for strong cycles (violates DAG constraint)
-        if let Some(cycle) = self.find_strong_cycle() {
-            errors.push(OwnershipError::StrongCycle { cycle });
+    }
+
+    fn field_store(&self, function: &MirFunction, owner: usize, field: &str, target: usize, span: Option<Span>) -> FieldStore {
+        FieldStore {
+            owner: self.sites[owner].box_type.clone(),
+            field: field.to_string(),
+            target: self.sites[target].box_type.clone(),
+            function: function.signature.name.clone(),
+            span,
         }
-        
-        // Check for bidirectional strong edges
-        for (child, parent) in &self.strong_edges {
-            if let Some(grandparent) = self.strong_edges.get(parent) {
-                if grandparent == child {
-                    errors.push(OwnershipError::BidirectionalStrong {
-                        first: *child,
-                        second: *parent,
-                    });
+    }
+
+    /// Every strong (owner site, field, target site) store, first occurrence wins
+    fn strong_edges(&self, module: &MirModule, functions: &[&MirFunction], facts: &HashMap<&str, FunctionFacts>) -> Vec<StrongEdge> {
+        let mut seen = HashSet::new();
+        let mut edges = Vec::new();
+        for function in functions {
+            let name = function.signature.name.as_str();
+            let function_facts = &facts[name];
+            for block in ordered_blocks(function) {
+                for inst in block.all_instructions() {
+                    let reference = match inst {
+                        MirInstruction::RefSet { reference, .. } => *reference,
+                        MirInstruction::ArraySet { array, .. } => *array,
+                        MirInstruction::BoxCall { box_val, .. } => *box_val,
+                        _ => continue,
+                    };
+                    for owner in sorted(self.pts(name, reference)) {
+                        let owner_type = self.sites[owner].box_type.clone();
+                        let Some((field, value)) = stored_field(inst, &owner_type) else { continue };
+                        if !Self::is_strong(module, function_facts, &owner_type, &field, value) {
+                            continue;
+                        }
+                        for target in sorted(self.pts(name, value)) {
+                            if !seen.insert((owner, field.clone(), target)) {
+                                continue;
+                            }
+                            edges.push(StrongEdge {
+                                owner,
+                                target,
+                                store: self.field_store(function, owner, &field, target, Self::store_span(function, inst)),
+                                self_store: owner == target && function_facts.resolve(reference) == function_facts.resolve(value),
+                            });
+                        }
+                    }
                 }
             }
         }
-        
-        if errors.is_empty() {
-            Ok(())
-        } else {
-            Err(errors)
-        }
+        edges
     }
-    
-    /// Check if a value has multiple strong owners
-    fn check_multiple_owners(&self, target: ValueId) -> Result<(), Vec<OwnershipError>> {
-        let owners: Vec<ValueId> = self.strong_edges
-            .iter()
-            .filter(|(_, &parent)| parent == target)
-            .map(|(&child, _)| child)
-            .collect();
-            
-        if owners.len() > 1 {
-            Err(vec![OwnershipError::MultipleStrongOwners { target, owners }])
-        } else {
-            Ok(())
+
+    /// One shortest cycle per group of sites, rooted at the site created first
+    fn find_cycles(&self, edges: &[StrongEdge]) -> Vec<OwnershipError> {
+        let mut successors: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (index, edge) in edges.iter().enumerate() {
+            // Two boxes from the same `new` (e.g. list nodes built in a loop) are not a cycle
+            if edge.owner == edge.target && !edge.self_store {
+                continue;
+            }
+            successors.entry(edge.owner).or_default().push(index);
         }
-    }
-    
-    /// Check if adding an edge would create a cycle
-    fn would_create_cycle(&self, parent: ValueId, child: ValueId) -> bool {
-        // DFS to see if parent is reachable from child through strong edges
-        let mut visited = HashSet::new();
-        let mut stack = vec![child];
-        
-        while let Some(current) = stack.pop() {
-            if current == parent {
-                return true; // Cycle detected
+
+        let mut in_cycle = HashSet::new();
+        let mut errors = Vec::new();
+        for &root in successors.keys() {
+            if in_cycle.contains(&root) {
+                continue;
             }
-            
-            if visited.insert(current) {
-                // Add all strong children of current to stack
-                for (&potential_child, &potential_parent) in &self.strong_edges {
-                    if potential_parent == current {
-                        stack.push(potential_child);
+            // BFS from root until an edge leads back to it
+            let mut reached_by: HashMap<usize, usize> = HashMap::new();
+            let mut queue = VecDeque::from([root]);
+            let mut closing = None;
+            'search: while let Some(node) = queue.pop_front() {
+                for &index in successors.get(&node).map(|v| v.as_slice()).unwrap_or(&[]) {
+                    let target = edges[index].target;
+                    if target == root {
+                        closing = Some(index);
+                        break 'search;
+                    }
+                    if in_cycle.contains(&target) || reached_by.contains_key(&target) {
+                        continue;
                     }
+                    reached_by.insert(target, index);
+                    queue.push_back(target);
                 }
             }
+            let Some(closing) = closing else { continue };
+
+            let mut path = vec![closing];
+            let mut node = edges[closing].owner;
+            while node != root {
+                let index = reached_by[&node];
+                path.push(index);
+                node = edges[index].owner;
+            }
+            path.reverse();
+            for &index in &path {
+                in_cycle.insert(edges[index].owner);
+            }
+            errors.push(OwnershipError::StrongCycle {
+                edges: path.iter().map(|&index| edges[index].store.clone()).collect(),
+            });
         }
-        
-        false
-    }
-    
-    /// Find any strong cycle in the ownership graph
-    fn find_strong_cycle(&self) -> Option<Vec<ValueId>> {
-        let mut visited = HashSet::new();
-        let mut rec_stack = HashSet::new();
-        let mut path = Vec::new();
-        
-        // Get all nodes in the graph
-        let mut all_nodes = HashSet::new();
-        for (&child, &parent) in &self.strong_edges {
-            all_nodes.insert(child);
-            all_nodes.insert(parent);
-        }
-        
-        // DFS from each unvisited node
-        for &node in &all_nodes {
-            if !visited.contains(&node) {
-                if let Some(cycle) = self.dfs_cycle(node, &mut visited, &mut rec_stack, &mut path) {
-                    return Some(cycle);
-                }
+        errors
+    }
+
+    /// Sites stored strongly into more than one (owner, field)
+    fn find_shared_targets(&self, edges: &[StrongEdge]) -> Vec<OwnershipError> {
+        let mut owners: BTreeMap<usize, Vec<&StrongEdge>> = BTreeMap::new();
+        for edge in edges {
+            if edge.owner == edge.target && !edge.self_store {
+                continue;
             }
+            owners.entry(edge.target).or_default().push(edge);
         }
-        
-        None
-    }
-    
-    /// DFS cycle detection helper
-    fn dfs_cycle(
-        &self,
-        node: ValueId,
-        visited: &mut HashSet<ValueId>,
-        rec_stack: &mut HashSet<ValueId>,
-        path: &mut Vec<ValueId>,
-    ) -> Option<Vec<ValueId>> {
-        visited.insert(node);
-        rec_stack.insert(node);
-        path.push(node);
-        
-        // Visit all strong children
-        for (&child, &parent) in &self.strong_edges {
-            if parent == node {
-                if rec_stack.contains(&child) {
-                    // Found cycle - return path from child to current
-                    let cycle_start = path.iter().position(|&x| x == child).unwrap();
-                    return Some(path[cycle_start..].to_vec());
-                }
-                
-                if !visited.contains(&child) {
-                    if let Some(cycle) = self.dfs_cycle(child, visited, rec_stack, path) {
-                        return Some(cycle);
+        owners
+            .into_iter()
+            .filter(|(_, stores)| stores.len() > 1)
+            .map(|(target, stores)| OwnershipError::MultipleStrongOwners {
+                target: self.sites[target].clone(),
+                owners: stores.iter().map(|e| e.store.clone()).collect(),
+            })
+            .collect()
+    }
+
+    /// Strong field stores that replace a box nobody read or kept, within one block
+    fn find_unsafe_ref_sets(&self, module: &MirModule, functions: &[&MirFunction], facts: &HashMap<&str, FunctionFacts>) -> Vec<OwnershipError> {
+        let mut errors = Vec::new();
+        for function in functions {
+            let name = function.signature.name.as_str();
+            let function_facts = &facts[name];
+            for block in ordered_blocks(function) {
+                let mut pending: HashMap<(ValueId, String), (ValueId, FieldStore)> = HashMap::new();
+                for inst in block.all_instructions() {
+                    match inst {
+                        MirInstruction::RefSet { reference, field, value } => {
+                            let key = (function_facts.resolve(*reference), field.clone());
+                            let owner = sorted(self.pts(name, *reference)).into_iter().next();
+                            let target = sorted(self.pts(name, *value)).into_iter().next();
+                            let store = match (owner, target) {
+                                (Some(owner), Some(target)) if Self::is_strong(module, function_facts, &self.sites[owner].box_type, field, *value) => {
+                                    self.field_store(function, owner, field, target, Self::store_span(function, inst))
+                                }
+                                _ => {
+                                    pending.remove(&key);
+                                    continue;
+                                }
+                            };
+                            if let Some((previous_value, previous)) = pending.insert(key, (*value, store.clone())) {
+                                if !function_facts.escaping.contains(&previous_value) {
+                                    errors.push(OwnershipError::UnsafeRefSet { previous, store });
+                                }
+                            }
+                        }
+                        MirInstruction::RefGet { reference, field, .. } => {
+                            pending.remove(&(function_facts.resolve(*reference), field.clone()));
+                        }
+                        // A constructor of a fresh box cannot have read the old value; anything else might
+                        MirInstruction::BoxCall { method, .. } if method == "birth" => {}
+                        MirInstruction::Call { .. } | MirInstruction::BoxCall { .. } => pending.clear(),
+                        _ => {}
                     }
                 }
             }
         }
-        
-        rec_stack.remove(&node);
-        path.pop();
-        None
-    }
-    
-    /// Get ownership statistics for debugging
-    pub fn ownership_stats(&self) -> OwnershipStats {
-        OwnershipStats {
-            strong_edges: self.strong_edges.len(),
-            weak_edges: self.weak_edges.len(),
-            released_count: self.released.len(),
-            live_weak_refs: self.live_weak_refs.len(),
-            dead_targets: self.dead_targets.len(),
-        }
+        errors
     }
 }
 
-/// Ownership statistics for debugging and analysis
-#[derive(Debug, Clone, PartialEq)]
-pub struct OwnershipStats {
-    pub strong_edges: usize,
-    pub weak_edges: usize,
-    pub released_count: usize,
-    pub live_weak_refs: usize,
-    pub dead_targets: usize,
-}
-
 impl Default for OwnershipVerifier {
     fn default() -> Self {
         Self::new()
@@ -413,147 +701,126 @@ impl Default for OwnershipVerifier {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::mir::{ValueIdGenerator, ConstValue};
-    
+    use crate::mir::MirCompiler;
+    use crate::parser::NyashParser;
+
+    fn verify(code: &str) -> Result<(), Vec<OwnershipError>> {
+        let ast = NyashParser::parse_from_string(code).expect("parse");
+        let module = MirCompiler::new().compile(ast).expect("compile").module;
+        OwnershipVerifier::new().verify_module(&module)
+    }
+
+    const PARENT_CHILD: &str = r#"
+box Child {
+  init { PARENT_FIELD }
+  setParent(p) { me.parent = p }
+}
+box Parent {
+  init { child }
+  birth() {
+    me.child = new Child()
+    me.child.setParent(me)
+  }
+}
+local p = new Parent()
+"#;
+
     #[test]
-    fn test_ownership_forest_basic() {
-        let mut verifier = OwnershipVerifier::new();
-        let mut value_gen = ValueIdGenerator::new();
-        
-        let parent = value_gen.next();
-        let child = value_gen.next();
-        
-        // Create ownership relationship
-        let adopt = MirInstructionV2::Adopt { parent, child };
-        assert!(verifier.process_instruction(&adopt).is_ok());
-        
-        // Verify forest properties
-        assert!(verifier.verify_ownership_forest().is_ok());
-        
-        let stats = verifier.ownership_stats();
-        assert_eq!(stats.strong_edges, 1);
-    }
-    
+    fn test_parent_child_cycle_through_method() {
+        let errors = verify(&PARENT_CHILD.replace("PARENT_FIELD", "parent")).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        let OwnershipError::StrongCycle { edges } = &errors[0] else { panic!("{:?}", errors) };
+        let names: Vec<String> = edges.iter().map(|e| e.edge_name()).collect();
+        assert_eq!(names, ["Parent.child", "Child.parent"]);
+        assert_eq!(edges[0].function, "Parent.birth/0");
+        assert_eq!(edges[0].span.map(|s| s.line), Some(9));
+        assert_eq!(edges[1].span.map(|s| s.line), Some(4));
+        assert!(errors[0].suggestion().unwrap().contains("declare `parent` as weak in box Child"));
+    }
+
     #[test]
-    fn test_multiple_owners_error() {
-        let mut verifier = OwnershipVerifier::new();
-        let mut value_gen = ValueIdGenerator::new();
-        
-        let parent1 = value_gen.next();
-        let parent2 = value_gen.next();
-        let child = value_gen.next();
-        
-        // Create multiple ownership (invalid)
-        verifier.strong_edges.insert(child, parent1);
-        verifier.strong_edges.insert(child, parent2); // This overwrites, but we'll manually create conflict
-        
-        // Manually create the conflicting state for testing
-        verifier.strong_edges.clear();
-        verifier.strong_edges.insert(parent1, child); // parent1 -> child
-        verifier.strong_edges.insert(parent2, child); // parent2 -> child (multiple owners of child)
-        
-        let result = verifier.verify_ownership_forest();
-        assert!(result.is_err());
-        
-        if let Err(errors) = result {
-            assert!(errors.iter().any(|e| matches!(e, OwnershipError::MultipleStrongOwners { .. })));
-        }
+    fn test_weak_back_reference_passes() {
+        assert!(verify(&PARENT_CHILD.replace("PARENT_FIELD", "weak parent")).is_ok());
     }
-    
+
     #[test]
-    fn test_strong_cycle_detection() {
-        let mut verifier = OwnershipVerifier::new();
-        let mut value_gen = ValueIdGenerator::new();
-        
-        let a = value_gen.next();
-        let b = value_gen.next();
-        let c = value_gen.next();
-        
-        // Create cycle: a -> b -> c -> a
-        verifier.strong_edges.insert(b, a);
-        verifier.strong_edges.insert(c, b);
-        verifier.strong_edges.insert(a, c);
-        
-        let result = verifier.verify_ownership_forest();
-        assert!(result.is_err());
-        
-        if let Err(errors) = result {
-            assert!(errors.iter().any(|e| matches!(e, OwnershipError::StrongCycle { .. })));
-        }
+    fn test_linked_list_in_loop_is_not_a_cycle() {
+        let code = r#"
+box Node {
+  init { next }
+}
+local head = new Node()
+local i = 0
+loop(i < 3) {
+  local n = new Node()
+  n.next = head
+  head = n
+  i = i + 1
+}
+"#;
+        assert!(verify(code).is_ok());
     }
-    
+
     #[test]
-    fn test_weak_reference_safety() {
-        let mut verifier = OwnershipVerifier::new();
-        let mut value_gen = ValueIdGenerator::new();
-        
-        let target = value_gen.next();
-        let weak_ref = value_gen.next();
-        
-        // Create weak reference
-        let weak_new = MirInstructionV2::WeakNew {
-            dst: weak_ref,
-            box_val: target,
-        };
-        assert!(verifier.process_instruction(&weak_new).is_ok());
-        
-        // Release the target
-        let release = MirInstructionV2::Release {
-            reference: target,
-        };
-        assert!(verifier.process_instruction(&release).is_ok());
-        
-        // WeakLoad should handle expired reference gracefully
-        let weak_load = MirInstructionV2::WeakLoad {
-            dst: value_gen.next(),
-            weak_ref,
-        };
-        assert!(verifier.process_instruction(&weak_load).is_ok());
-        
-        let stats = verifier.ownership_stats();
-        assert_eq!(stats.weak_edges, 1);
-        assert_eq!(stats.dead_targets, 1);
+    fn test_multiple_strong_owners() {
+        let code = r#"
+box Item {
+  init { }
+}
+box Holder {
+  init { item }
+}
+local shared = new Item()
+local a = new Holder()
+local b = new Holder()
+a.item = shared
+local list = new ArrayBox()
+list.push(shared)
+"#;
+        let errors = verify(code).unwrap_err();
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        let OwnershipError::MultipleStrongOwners { target, owners } = &errors[0] else { panic!("{:?}", errors) };
+        assert_eq!(target.box_type, "Item");
+        assert_eq!(target.span.map(|s| s.line), Some(8));
+        let names: Vec<String> = owners.iter().map(|e| e.edge_name()).collect();
+        assert_eq!(names, ["Holder.item", "ArrayBox element"]);
     }
-    
+
     #[test]
-    fn test_unsafe_ref_set() {
+    fn test_overwritten_strong_field() {
+        let code = r#"
+box Item {
+  init { }
+}
+box Holder {
+  init { item }
+  birth() {
+    me.item = new Item()
+    me.item = new Item()
+  }
+}
+local h = new Holder()
+"#;
+        let ast = NyashParser::parse_from_string(code).expect("parse");
+        let module = MirCompiler::new().compile(ast).expect("compile").module;
         let mut verifier = OwnershipVerifier::new();
-        let mut value_gen = ValueIdGenerator::new();
-        
-        let reference = value_gen.next();
-        let old_target = value_gen.next();
-        let new_target = value_gen.next();
-        
-        // Set initial strong ownership
-        verifier.strong_edges.insert(reference, old_target);
-        
-        // Try to change without Release (should error)
-        let ref_set = MirInstructionV2::RefSet { reference, new_target };
-        let result = verifier.process_instruction(&ref_set);
-        
-        assert!(result.is_err());
-        if let Err(errors) = result {
-            assert!(errors.iter().any(|e| matches!(e, OwnershipError::UnsafeRefSet { .. })));
-        }
+        // Dropping the first Item is legal, so it is reported without failing
+        assert!(verifier.verify_module(&module).is_ok());
+        let warnings = verifier.warnings();
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        let OwnershipError::UnsafeRefSet { previous, store } = &warnings[0] else { panic!("{:?}", warnings) };
+        assert_eq!(store.edge_name(), "Holder.item");
+        assert_eq!(previous.span.map(|s| s.line), Some(8));
+        assert_eq!(store.span.map(|s| s.line), Some(9));
     }
-    
+
     #[test]
-    fn test_safe_ref_set_with_release() {
-        let mut verifier = OwnershipVerifier::new();
-        let mut value_gen = ValueIdGenerator::new();
-        
-        let reference = value_gen.next();
-        let old_target = value_gen.next();
-        let new_target = value_gen.next();
-        
-        // Set initial strong ownership
-        verifier.strong_edges.insert(reference, old_target);
-        
-        // Release old target first
-        verifier.released.insert(old_target);
-        
-        // Now RefSet should be safe
-        let ref_set = MirInstructionV2::RefSet { reference, new_target };
-        assert!(verifier.process_instruction(&ref_set).is_ok());
-    }
-}
\ No newline at end of file
+    fn test_render_shows_source_line() {
+        let source = PARENT_CHILD.replace("PARENT_FIELD", "parent");
+        let errors = verify(&source).unwrap_err();
+        let report = errors[0].render(Some(&source));
+        assert!(report.starts_with("strong reference cycle: Parent.child -> Child.parent -> Parent"), "{}", report);
+        assert!(report.contains("setParent(p) { me.parent = p }"), "{}", report);
+        assert!(report.contains("help: declare `parent` as weak"), "{}", report);
+    }
+}
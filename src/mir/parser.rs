@@ -0,0 +1,761 @@
+/*!
+ * MIR Parser - Read the textual MIR produced by `MirPrinter`
+ *
+ * Turns `--dump-mir` output (or hand-written MIR in the same format) back into a
+ * `MirModule`, so modules can be round-tripped and fed straight to the VM.
+ * Comments (`; ...`), line numbers (`  3: `) and verbose annotations are ignored.
+ */
+
+use super::printer::{default_call_effects, default_print_effects, default_throw_effects};
+use super::{
+    BasicBlock, BasicBlockId, BinaryOp, BarrierOp, CompareOp, ConstValue, Effect, EffectMask,
+    FunctionSignature, MirFunction, MirInstruction, MirModule, MirType, TypeOpKind, UnaryOp,
+    ValueId, WeakRefOp,
+};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Error while reading MIR text
+#[derive(Debug, Clone, PartialEq)]
+pub struct MirParseError {
+    /// 1-based line number in the input
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for MirParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for MirParseError {}
+
+/// Parser for the `MirPrinter` text format
+#[derive(Debug, Default)]
+pub struct MirParser;
+
+impl MirParser {
+    /// Create a new MIR parser
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Parse a complete module (`; MIR Module:` header, globals and functions).
+    /// Anything before the module header line, such as the banner printed by
+    /// `nyash --dump-mir`, is skipped.
+    pub fn parse_module(&self, text: &str) -> Result<MirModule, MirParseError> {
+        let lines: Vec<(usize, &str)> = text.lines().enumerate().map(|(i, l)| (i + 1, l)).collect();
+        let start = lines.iter()
+            .position(|(_, l)| l.trim_start().starts_with("; MIR Module:"))
+            .unwrap_or(0);
+
+        let mut module = MirModule::new("main".to_string());
+        let mut i = start;
+        while i < lines.len() {
+            let (number, raw) = lines[i];
+            let trimmed = raw.trim();
+            if let Some(name) = trimmed.strip_prefix("; MIR Module:") {
+                module.name = name.trim().to_string();
+            } else if let Some(source) = trimmed.strip_prefix("; Source:") {
+                module.metadata.source_file = Some(source.trim().to_string());
+            }
+            let code = strip_comment(raw).trim();
+            i += 1;
+            if code.is_empty() {
+                continue;
+            }
+            if code.starts_with("global ") {
+                let (name, value) = self.global(code).map_err(|message| MirParseError { line: number, message })?;
+                module.add_global(name, value);
+            } else if code.starts_with("define ") {
+                let end = lines[i..].iter()
+                    .position(|(_, l)| strip_comment(l).trim() == "}")
+                    .map(|offset| i + offset)
+                    .ok_or_else(|| MirParseError { line: number, message: "function body is not closed with '}'".to_string() })?;
+                let function = self.function_lines(number, code, &lines[i..end])?;
+                let name = function.signature.name.clone();
+                if module.functions.contains_key(&name) {
+                    return Err(MirParseError { line: number, message: format!("duplicate function @{}", name) });
+                }
+                module.add_function(function);
+                i = end + 1;
+            } else {
+                return Err(MirParseError { line: number, message: format!("expected 'define' or 'global', found '{}'", code) });
+            }
+        }
+        Ok(module)
+    }
+
+    /// Parse a single function (`define ... { ... }`)
+    pub fn parse_function(&self, text: &str) -> Result<MirFunction, MirParseError> {
+        let module = self.parse_module(text)?;
+        let mut functions = module.functions.into_values();
+        match (functions.next(), functions.next()) {
+            (Some(function), None) => Ok(function),
+            _ => Err(MirParseError { line: 1, message: "expected exactly one function".to_string() }),
+        }
+    }
+
+    /// `global @name = <const>`
+    fn global(&self, code: &str) -> Result<(String, ConstValue), String> {
+        let mut c = Cursor::new(code);
+        c.keyword("global")?;
+        c.expect("@")?;
+        let name = c.take_until(|ch| ch.is_whitespace() || ch == '=').to_string();
+        c.expect("=")?;
+        let value = c.const_value()?;
+        c.end()?;
+        Ok((name, value))
+    }
+
+    fn function_lines(&self, header_line: usize, header: &str, body: &[(usize, &str)]) -> Result<MirFunction, MirParseError> {
+        let signature = self.signature(header).map_err(|message| MirParseError { line: header_line, message })?;
+        let param_count = signature.params.len();
+
+        let mut blocks: Vec<BasicBlock> = Vec::new();
+        let mut block_lines: HashMap<BasicBlockId, usize> = HashMap::new();
+        for &(number, raw) in body {
+            let code = strip_comment(raw).trim();
+            if code.is_empty() {
+                continue;
+            }
+            let err = |message: String| MirParseError { line: number, message };
+            if let Some(label) = code.strip_suffix(':').filter(|l| l.starts_with("bb")) {
+                let id = parse_block_id(label).map_err(err)?;
+                if block_lines.insert(id, number).is_some() {
+                    return Err(err(format!("duplicate block {}", id)));
+                }
+                blocks.push(BasicBlock::new(id));
+                continue;
+            }
+            let block = blocks.last_mut().ok_or_else(|| err("instruction outside of a block".to_string()))?;
+            let instruction = self.instruction(strip_line_number(code)).map_err(err)?;
+            if block.terminator.is_some() {
+                return Err(err(format!("instruction after the terminator of {}", block.id)));
+            }
+            block.add_instruction(instruction);
+        }
+
+        let entry = blocks.first().map(|b| b.id)
+            .ok_or_else(|| MirParseError { line: header_line, message: format!("function @{} has no blocks", signature.name) })?;
+        for block in &blocks {
+            let targets: Vec<BasicBlockId> = match block.all_instructions().last() {
+                Some(MirInstruction::Jump { target }) => vec![*target],
+                Some(MirInstruction::Branch { then_bb, else_bb, .. }) => vec![*then_bb, *else_bb],
+                _ => Vec::new(),
+            };
+            let phi_sources = block.instructions.iter().flat_map(|inst| match inst {
+                MirInstruction::Phi { inputs, .. } => inputs.iter().map(|(bb, _)| *bb).collect(),
+                MirInstruction::Catch { handler_bb, .. } => vec![*handler_bb],
+                _ => Vec::new(),
+            });
+            if let Some(missing) = targets.into_iter().chain(phi_sources).find(|id| !block_lines.contains_key(id)) {
+                return Err(MirParseError { line: block_lines[&block.id], message: format!("{} refers to unknown block {}", block.id, missing) });
+            }
+        }
+
+        let mut function = MirFunction::new(signature, entry);
+        function.blocks = blocks.into_iter().map(|b| (b.id, b)).collect();
+        function.params = (0..param_count as u32).map(ValueId::new).collect();
+        let max_value = function.blocks.values()
+            .flat_map(|b| b.all_instructions())
+            .flat_map(|inst| inst.dst_value().into_iter().chain(inst.used_values()))
+            .map(|v| v.as_u32() + 1)
+            .max()
+            .unwrap_or(0);
+        function.next_value_id = max_value.max(param_count as u32);
+        function.update_cfg();
+        function.mark_reachable_blocks();
+        Ok(function)
+    }
+
+    /// `define <ret> @<name>(<type> %0, ...) [effects(...)] {`
+    fn signature(&self, header: &str) -> Result<FunctionSignature, String> {
+        let mut c = Cursor::new(header);
+        c.keyword("define")?;
+        let return_type = c.mir_type()?;
+        c.expect("@")?;
+        let name = c.take_until(|ch| ch == '(').trim().to_string();
+        c.expect("(")?;
+        let mut params = Vec::new();
+        if !c.eat(")") {
+            loop {
+                params.push(c.mir_type()?);
+                let value = c.value()?;
+                if value.as_u32() as usize != params.len() - 1 {
+                    return Err(format!("parameters must be numbered %0, %1, ... (found {})", value));
+                }
+                if c.eat(")") {
+                    break;
+                }
+                c.expect(",")?;
+            }
+        }
+        let effects = if c.eat("effects(") {
+            let effects = parse_effects(c.take_until(|ch| ch == ')'))?;
+            c.expect(")")?;
+            effects
+        } else {
+            EffectMask::PURE
+        };
+        c.expect("{")?;
+        c.end()?;
+        Ok(FunctionSignature { name, params, return_type, effects })
+    }
+
+    /// Parse one instruction line (without line number and comment)
+    fn instruction(&self, code: &str) -> Result<MirInstruction, String> {
+        let mut c = Cursor::new(code);
+        if c.peek() == Some('%') {
+            let first = c.value()?;
+            if c.eat("[") {
+                // %array[%index] = %value
+                let index = c.value()?;
+                c.expect("]")?;
+                c.expect("=")?;
+                let value = c.value()?;
+                c.end()?;
+                return Ok(MirInstruction::ArraySet { array: first, index, value });
+            }
+            c.expect("=")?;
+            let instruction = self.assignment(first, &mut c)?;
+            c.end()?;
+            return Ok(instruction);
+        }
+
+        let op = c.word();
+        let instruction = match op {
+            "store" => {
+                let value = c.value()?;
+                c.expect("->")?;
+                MirInstruction::Store { value, ptr: c.value()? }
+            }
+            "call" => self.call(None, &mut c)?,
+            "extern_call" => self.extern_call(None, &mut c)?,
+            "br" => {
+                if c.keyword_opt("label") {
+                    MirInstruction::Jump { target: c.block()? }
+                } else {
+                    let condition = c.value()?;
+                    c.expect(",")?;
+                    c.keyword("label")?;
+                    let then_bb = c.block()?;
+                    c.expect(",")?;
+                    c.keyword("label")?;
+                    MirInstruction::Branch { condition, then_bb, else_bb: c.block()? }
+                }
+            }
+            "ret" => {
+                if c.keyword_opt("void") {
+                    MirInstruction::Return { value: None }
+                } else {
+                    MirInstruction::Return { value: Some(c.value()?) }
+                }
+            }
+            "debug" => {
+                let value = c.value()?;
+                MirInstruction::Debug { value, message: c.string()? }
+            }
+            "print" => {
+                let value = c.value()?;
+                MirInstruction::Print { value, effects: c.effects_suffix(default_print_effects())? }
+            }
+            "throw" => {
+                let exception = c.value()?;
+                MirInstruction::Throw { exception, effects: c.effects_suffix(default_throw_effects())? }
+            }
+            "catch" => {
+                let exception_type = if c.eat("*") {
+                    None
+                } else {
+                    Some(c.word().to_string())
+                };
+                let exception_value = c.value()?;
+                c.expect("->")?;
+                MirInstruction::Catch { exception_type, exception_value, handler_bb: c.block()? }
+            }
+            "nop" => MirInstruction::Nop,
+            "safepoint" => MirInstruction::Safepoint,
+            "ref_set" => {
+                let reference = c.value()?;
+                c.expect(".")?;
+                let field = c.take_until(|ch| ch.is_whitespace() || ch == '=').to_string();
+                c.expect("=")?;
+                MirInstruction::RefSet { reference, field, value: c.value()? }
+            }
+            "barrier_read" => MirInstruction::BarrierRead { ptr: c.value()? },
+            "barrier_write" => MirInstruction::BarrierWrite { ptr: c.value()? },
+            "barrier" => {
+                let op = match c.word() {
+                    "read" => BarrierOp::Read,
+                    "write" => BarrierOp::Write,
+                    other => return Err(format!("unknown barrier kind '{}'", other)),
+                };
+                MirInstruction::Barrier { op, ptr: c.value()? }
+            }
+            "future_set" => {
+                let future = c.value()?;
+                c.expect("=")?;
+                MirInstruction::FutureSet { future, value: c.value()? }
+            }
+            "" => return Err(format!("cannot parse instruction '{}'", code)),
+            other => return Err(format!("unknown instruction '{}'", other)),
+        };
+        c.end()?;
+        Ok(instruction)
+    }
+
+    /// Right-hand side of `%dst = ...`
+    fn assignment(&self, dst: ValueId, c: &mut Cursor) -> Result<MirInstruction, String> {
+        if c.peek() == Some('%') {
+            let lhs = c.value()?;
+            if c.eat("[") {
+                let index = c.value()?;
+                c.expect("]")?;
+                return Ok(MirInstruction::ArrayGet { dst, array: lhs, index });
+            }
+            let op = parse_binary_op(c.word())?;
+            return Ok(MirInstruction::BinOp { dst, op, lhs, rhs: c.value()? });
+        }
+
+        let op = c.word();
+        Ok(match op {
+            "const" => MirInstruction::Const { dst, value: c.const_value()? },
+            "icmp" => {
+                let op = parse_compare_op(c.word())?;
+                let lhs = c.value()?;
+                c.expect(",")?;
+                MirInstruction::Compare { dst, op, lhs, rhs: c.value()? }
+            }
+            "Neg" => MirInstruction::UnaryOp { dst, op: UnaryOp::Neg, operand: c.value()? },
+            "Not" => MirInstruction::UnaryOp { dst, op: UnaryOp::Not, operand: c.value()? },
+            "BitNot" => MirInstruction::UnaryOp { dst, op: UnaryOp::BitNot, operand: c.value()? },
+            "load" => MirInstruction::Load { dst, ptr: c.value()? },
+            "copy" => MirInstruction::Copy { dst, src: c.value()? },
+            "call" => self.call(Some(dst), c)?,
+            "extern_call" => self.extern_call(Some(dst), c)?,
+            "phi" => {
+                let mut inputs = Vec::new();
+                loop {
+                    c.expect("[")?;
+                    let value = c.value()?;
+                    c.expect(",")?;
+                    let block = c.block()?;
+                    c.expect("]")?;
+                    inputs.push((block, value));
+                    if !c.eat(",") {
+                        break;
+                    }
+                }
+                MirInstruction::Phi { dst, inputs }
+            }
+            "new" => {
+                let box_type = c.take_until(|ch| ch == '(').trim().to_string();
+                MirInstruction::NewBox { dst, box_type, args: c.args()? }
+            }
+            "type_check" => {
+                let value = c.value()?;
+                c.keyword("is")?;
+                MirInstruction::TypeCheck { dst, value, expected_type: c.word().to_string() }
+            }
+            "cast" => {
+                let value = c.value()?;
+                c.keyword("to")?;
+                MirInstruction::Cast { dst, value, target_type: c.debug_type()? }
+            }
+            "typeop" => {
+                let op = match c.word() {
+                    "check" => TypeOpKind::Check,
+                    "cast" => TypeOpKind::Cast,
+                    other => return Err(format!("unknown typeop '{}'", other)),
+                };
+                let value = c.value()?;
+                MirInstruction::TypeOp { dst, op, value, ty: c.debug_type()? }
+            }
+            "ref_new" => MirInstruction::RefNew { dst, box_val: c.value()? },
+            "ref_get" => {
+                let reference = c.value()?;
+                c.expect(".")?;
+                MirInstruction::RefGet { dst, reference, field: c.word().to_string() }
+            }
+            "weak_new" => MirInstruction::WeakNew { dst, box_val: c.value()? },
+            "weak_load" => MirInstruction::WeakLoad { dst, weak_ref: c.value()? },
+            "weakref" => {
+                let op = match c.word() {
+                    "new" => WeakRefOp::New,
+                    "load" => WeakRefOp::Load,
+                    other => return Err(format!("unknown weakref op '{}'", other)),
+                };
+                MirInstruction::WeakRef { dst, op, value: c.value()? }
+            }
+            "future_new" => MirInstruction::FutureNew { dst, value: c.value()? },
+            "await" => MirInstruction::Await { dst, future: c.value()? },
+            "" => return Err("missing right-hand side".to_string()),
+            other => return Err(format!("unknown instruction '{}'", other)),
+        })
+    }
+
+    /// `call %func(args)` or `call %box.method(args)`, with optional `[effects: ...]`
+    fn call(&self, dst: Option<ValueId>, c: &mut Cursor) -> Result<MirInstruction, String> {
+        let target = c.value()?;
+        if c.eat(".") {
+            let method = c.take_until(|ch| ch == '(').trim().to_string();
+            let args = c.args()?;
+            let effects = c.effects_suffix(default_call_effects())?;
+            Ok(MirInstruction::BoxCall { dst, box_val: target, method, args, effects })
+        } else {
+            let args = c.args()?;
+            let effects = c.effects_suffix(default_call_effects())?;
+            Ok(MirInstruction::Call { dst, func: target, args, effects })
+        }
+    }
+
+    /// `extern_call iface.name.method(args) [effects: ...]`
+    fn extern_call(&self, dst: Option<ValueId>, c: &mut Cursor) -> Result<MirInstruction, String> {
+        let path = c.take_until(|ch| ch == '(').trim();
+        let (iface_name, method_name) = path.rsplit_once('.')
+            .ok_or_else(|| format!("extern_call target '{}' must be interface.method", path))?;
+        let (iface_name, method_name) = (iface_name.to_string(), method_name.to_string());
+        let args = c.args()?;
+        let effects = c.effects_suffix(EffectMask::IO)?;
+        Ok(MirInstruction::ExternCall { dst, iface_name, method_name, args, effects })
+    }
+}
+
+/// Cut a `;` comment that is not inside a string literal
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, ch) in line.char_indices() {
+        if in_string {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if ch == '"' {
+            in_string = true;
+        } else if ch == ';' {
+            return &line[..i];
+        }
+    }
+    line
+}
+
+/// Drop the `  12: ` prefix printed with line numbers
+fn strip_line_number(code: &str) -> &str {
+    let digits = code.len() - code.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits > 0 && code[digits..].starts_with(':') {
+        code[digits + 1..].trim_start()
+    } else {
+        code
+    }
+}
+
+fn parse_block_id(text: &str) -> Result<BasicBlockId, String> {
+    text.strip_prefix("bb")
+        .and_then(|n| n.parse::<u32>().ok())
+        .map(BasicBlockId::new)
+        .ok_or_else(|| format!("expected a block label like bb0, found '{}'", text))
+}
+
+fn parse_binary_op(name: &str) -> Result<BinaryOp, String> {
+    Ok(match name {
+        "Add" => BinaryOp::Add,
+        "Sub" => BinaryOp::Sub,
+        "Mul" => BinaryOp::Mul,
+        "Div" => BinaryOp::Div,
+        "Mod" => BinaryOp::Mod,
+        "BitAnd" => BinaryOp::BitAnd,
+        "BitOr" => BinaryOp::BitOr,
+        "BitXor" => BinaryOp::BitXor,
+        "Shl" => BinaryOp::Shl,
+        "Shr" => BinaryOp::Shr,
+        "And" => BinaryOp::And,
+        "Or" => BinaryOp::Or,
+        other => return Err(format!("unknown binary operator '{}'", other)),
+    })
+}
+
+fn parse_compare_op(name: &str) -> Result<CompareOp, String> {
+    Ok(match name {
+        "Eq" => CompareOp::Eq,
+        "Ne" => CompareOp::Ne,
+        "Lt" => CompareOp::Lt,
+        "Le" => CompareOp::Le,
+        "Gt" => CompareOp::Gt,
+        "Ge" => CompareOp::Ge,
+        other => return Err(format!("unknown comparison '{}'", other)),
+    })
+}
+
+/// `pure|io|read` (the `EffectMask` display format); `none` is the empty mask
+fn parse_effects(text: &str) -> Result<EffectMask, String> {
+    let mut mask = EffectMask::new();
+    for name in text.split('|').map(str::trim) {
+        let effect = match name {
+            "none" => continue,
+            "pure" => Effect::Pure,
+            "mut" => Effect::Mut,
+            "io" => Effect::Io,
+            "control" => Effect::Control,
+            "read" => Effect::ReadHeap,
+            "write" => Effect::WriteHeap,
+            "p2p" => Effect::P2P,
+            "ffi" => Effect::FFI,
+            "panic" => Effect::Panic,
+            "alloc" => Effect::Alloc,
+            "global" => Effect::Global,
+            "async" => Effect::Async,
+            "unsafe" => Effect::Unsafe,
+            "debug" => Effect::Debug,
+            "barrier" => Effect::Barrier,
+            other => return Err(format!("unknown effect '{}'", other)),
+        };
+        mask = mask.add(effect);
+    }
+    Ok(mask)
+}
+
+/// Character cursor over one line of MIR text
+struct Cursor<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { rest: text.trim() }
+    }
+
+    fn skip_ws(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_ws();
+        self.rest.chars().next()
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_ws();
+        if let Some(rest) = self.rest.strip_prefix(token) {
+            self.rest = rest;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(format!("expected '{}' at '{}'", token, self.rest))
+        }
+    }
+
+    /// A whole word (not a prefix of a longer identifier)
+    fn keyword_opt(&mut self, word: &str) -> bool {
+        self.skip_ws();
+        let saved = self.rest;
+        if self.word() == word {
+            true
+        } else {
+            self.rest = saved;
+            false
+        }
+    }
+
+    fn keyword(&mut self, word: &str) -> Result<(), String> {
+        if self.keyword_opt(word) {
+            Ok(())
+        } else {
+            Err(format!("expected '{}' at '{}'", word, self.rest))
+        }
+    }
+
+    fn take_until(&mut self, stop: impl Fn(char) -> bool) -> &'a str {
+        self.skip_ws();
+        let end = self.rest.find(stop).unwrap_or(self.rest.len());
+        let (taken, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        taken
+    }
+
+    /// Identifier-like token: letters, digits, `_`, `.`, `/`, `:`
+    fn word(&mut self) -> &'a str {
+        self.take_until(|ch| !(ch.is_alphanumeric() || matches!(ch, '_' | '.' | '/' | ':')))
+    }
+
+    fn value(&mut self) -> Result<ValueId, String> {
+        self.skip_ws();
+        let at = self.rest;
+        self.expect("%")?;
+        self.take_until(|ch| !ch.is_ascii_digit()).parse::<u32>()
+            .map(ValueId::new)
+            .map_err(|_| format!("expected a value like %0 at '{}'", at))
+    }
+
+    fn block(&mut self) -> Result<BasicBlockId, String> {
+        let label = self.take_until(|ch| !ch.is_ascii_alphanumeric());
+        parse_block_id(label)
+    }
+
+    /// `(%1, %2, ...)`
+    fn args(&mut self) -> Result<Vec<ValueId>, String> {
+        self.expect("(")?;
+        let mut args = Vec::new();
+        if self.eat(")") {
+            return Ok(args);
+        }
+        loop {
+            args.push(self.value()?);
+            if self.eat(")") {
+                return Ok(args);
+            }
+            self.expect(",")?;
+        }
+    }
+
+    /// Optional `[effects: a|b]`
+    fn effects_suffix(&mut self, assumed: EffectMask) -> Result<EffectMask, String> {
+        if !self.eat("[effects:") {
+            return Ok(assumed);
+        }
+        let effects = parse_effects(self.take_until(|ch| ch == ']'))?;
+        self.expect("]")?;
+        Ok(effects)
+    }
+
+    /// String literal with Rust-style escapes
+    fn string(&mut self) -> Result<String, String> {
+        self.expect("\"")?;
+        let mut out = String::new();
+        let mut chars = self.rest.char_indices();
+        while let Some((i, ch)) = chars.next() {
+            match ch {
+                '"' => {
+                    self.rest = &self.rest[i + 1..];
+                    return Ok(out);
+                }
+                '\\' => {
+                    let escaped = match chars.next().map(|(_, c)| c) {
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('0') => '\0',
+                        Some('\\') => '\\',
+                        Some('"') => '"',
+                        Some('\'') => '\'',
+                        Some('u') => {
+                            let hex: String = chars.by_ref().map(|(_, c)| c).skip_while(|&c| c == '{').take_while(|&c| c != '}').collect();
+                            u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32)
+                                .ok_or_else(|| format!("invalid unicode escape '\\u{{{}}}'", hex))?
+                        }
+                        other => return Err(format!("invalid escape '\\{}'", other.map(String::from).unwrap_or_default())),
+                    };
+                    out.push(escaped);
+                }
+                _ => out.push(ch),
+            }
+        }
+        Err("unterminated string literal".to_string())
+    }
+
+    fn const_value(&mut self) -> Result<ConstValue, String> {
+        if self.peek() == Some('"') {
+            return Ok(ConstValue::String(self.string()?));
+        }
+        let token = self.take_until(char::is_whitespace);
+        Ok(match token {
+            "true" => ConstValue::Bool(true),
+            "false" => ConstValue::Bool(false),
+            "null" => ConstValue::Null,
+            "void" => ConstValue::Void,
+            _ => match token.parse::<i64>() {
+                Ok(n) => ConstValue::Integer(n),
+                Err(_) => ConstValue::Float(token.parse::<f64>().map_err(|_| format!("invalid constant '{}'", token))?),
+            },
+        })
+    }
+
+    /// Type in signature syntax: `i64`, `f64`, `i1`, `str`, `void`, `?`, `box<Name>`, `[T]`, `future<T>`
+    fn mir_type(&mut self) -> Result<MirType, String> {
+        if self.eat("[") {
+            let elem = self.mir_type()?;
+            self.expect("]")?;
+            return Ok(MirType::Array(Box::new(elem)));
+        }
+        if self.eat("box<") {
+            let mut depth = 0;
+            let end = self.rest.char_indices()
+                .find(|&(_, ch)| {
+                    match ch {
+                        '<' => depth += 1,
+                        '>' if depth == 0 => return true,
+                        '>' => depth -= 1,
+                        _ => {}
+                    }
+                    false
+                })
+                .map(|(i, _)| i)
+                .ok_or("unterminated box<...> type")?;
+            let name = self.rest[..end].to_string();
+            self.rest = &self.rest[end + 1..];
+            return Ok(MirType::Box(name));
+        }
+        if self.eat("future<") {
+            let inner = self.mir_type()?;
+            self.expect(">")?;
+            return Ok(MirType::Future(Box::new(inner)));
+        }
+        if self.eat("?") {
+            return Ok(MirType::Unknown);
+        }
+        Ok(match self.word() {
+            "i64" => MirType::Integer,
+            "f64" => MirType::Float,
+            "i1" => MirType::Bool,
+            "str" => MirType::String,
+            "void" => MirType::Void,
+            other => return Err(format!("unknown type '{}'", other)),
+        })
+    }
+
+    /// Type in `Debug` syntax as printed by `cast`/`typeop`: `Integer`, `Box("Name")`, `Array(T)`, ...
+    fn debug_type(&mut self) -> Result<MirType, String> {
+        let ty = match self.word() {
+            "Integer" => MirType::Integer,
+            "Float" => MirType::Float,
+            "Bool" => MirType::Bool,
+            "String" => MirType::String,
+            "Void" => MirType::Void,
+            "Unknown" => MirType::Unknown,
+            "Box" => {
+                self.expect("(")?;
+                let name = self.string()?;
+                self.expect(")")?;
+                return Ok(MirType::Box(name));
+            }
+            wrapper @ ("Array" | "Future") => {
+                self.expect("(")?;
+                let inner = Box::new(self.debug_type()?);
+                self.expect(")")?;
+                return Ok(if wrapper == "Array" { MirType::Array(inner) } else { MirType::Future(inner) });
+            }
+            other => return Err(format!("unknown type '{}'", other)),
+        };
+        Ok(ty)
+    }
+
+    fn end(&mut self) -> Result<(), String> {
+        self.skip_ws();
+        if self.rest.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("unexpected trailing text '{}'", self.rest))
+        }
+    }
+}
@@ -4,9 +4,24 @@
  * Implements pretty-printing for MIR modules and functions
  */
 
-use super::{MirModule, MirFunction, BasicBlock, MirInstruction};
+use super::{MirModule, MirFunction, BasicBlock, MirInstruction, ConstValue, EffectMask, Effect};
 use std::fmt::Write;
 
+/// Effects assumed for `call` when no `[effects: ...]` suffix is printed
+pub(super) fn default_call_effects() -> EffectMask {
+    EffectMask::READ
+}
+
+/// Effects assumed for `print` when no suffix is printed
+pub(super) fn default_print_effects() -> EffectMask {
+    EffectMask::PURE.add(Effect::Io)
+}
+
+/// Effects assumed for `throw` when no suffix is printed
+pub(super) fn default_throw_effects() -> EffectMask {
+    EffectMask::PANIC
+}
+
 /// MIR printer for debug output and visualization
 pub struct MirPrinter {
     /// Indentation level
@@ -88,15 +103,19 @@ impl MirPrinter {
         // Global constants
         if !module.globals.is_empty() {
             writeln!(output, "; Global Constants:").unwrap();
-            for (name, value) in &module.globals {
-                writeln!(output, "global @{} = {}", name, value).unwrap();
+            let mut names: Vec<_> = module.globals.keys().collect();
+            names.sort();
+            for name in names {
+                writeln!(output, "global @{} = {}", name, self.format_const(&module.globals[name])).unwrap();
             }
             writeln!(output).unwrap();
         }
         
-        // Functions
-        for (_name, function) in &module.functions {
-            output.push_str(&self.print_function(function));
+        // Functions (sorted by name for stable output)
+        let mut names: Vec<_> = module.functions.keys().collect();
+        names.sort();
+        for name in names {
+            output.push_str(&self.print_function(&module.functions[name]));
             output.push('\n');
         }
         
@@ -121,7 +140,7 @@ impl MirPrinter {
         write!(output, ")").unwrap();
         
         // Effects
-        if !function.signature.effects.is_pure() {
+        if function.signature.effects != EffectMask::PURE {
             write!(output, " effects({})", function.signature.effects).unwrap();
         }
         
@@ -232,7 +251,9 @@ impl MirPrinter {
         
         // Predecessors
         if !block.predecessors.is_empty() && self.verbose {
-            let preds: Vec<String> = block.predecessors.iter()
+            let mut preds: Vec<_> = block.predecessors.iter().collect();
+            preds.sort();
+            let preds: Vec<String> = preds.into_iter()
                 .map(|p| format!("{}", p))
                 .collect();
             write!(output, "  ; preds({})", preds.join(", ")).unwrap();
@@ -271,7 +292,7 @@ impl MirPrinter {
     fn format_instruction(&self, instruction: &MirInstruction) -> String {
         match instruction {
             MirInstruction::Const { dst, value } => {
-                format!("{} = const {}", dst, value)
+                format!("{} = const {}", dst, self.format_const(value))
             },
             
             MirInstruction::BinOp { dst, op, lhs, rhs } => {
@@ -294,30 +315,32 @@ impl MirPrinter {
                 format!("store {} -> {}", value, ptr)
             },
             
-            MirInstruction::Call { dst, func, args, effects: _ } => {
+            MirInstruction::Call { dst, func, args, effects } => {
                 let args_str = args.iter()
                     .map(|v| format!("{}", v))
                     .collect::<Vec<_>>()
                     .join(", ");
                 
-                if let Some(dst) = dst {
+                let call = if let Some(dst) = dst {
                     format!("{} = call {}({})", dst, func, args_str)
                 } else {
                     format!("call {}({})", func, args_str)
-                }
+                };
+                call + &self.format_effects_suffix(*effects, default_call_effects())
             },
             
-            MirInstruction::BoxCall { dst, box_val, method, args, effects: _ } => {
+            MirInstruction::BoxCall { dst, box_val, method, args, effects } => {
                 let args_str = args.iter()
                     .map(|v| format!("{}", v))
                     .collect::<Vec<_>>()
                     .join(", ");
                 
-                if let Some(dst) = dst {
+                let call = if let Some(dst) = dst {
                     format!("{} = call {}.{}({})", dst, box_val, method, args_str)
                 } else {
                     format!("call {}.{}({})", box_val, method, args_str)
-                }
+                };
+                call + &self.format_effects_suffix(*effects, default_call_effects())
             },
             
             MirInstruction::Branch { condition, then_bb, else_bb } => {
@@ -378,11 +401,11 @@ impl MirPrinter {
             },
             
             MirInstruction::Debug { value, message } => {
-                format!("debug {} \"{}\"", value, message)
+                format!("debug {} {:?}", value, message)
             },
             
-            MirInstruction::Print { value, effects: _ } => {
-                format!("print {}", value)
+            MirInstruction::Print { value, effects } => {
+                format!("print {}{}", value, self.format_effects_suffix(*effects, default_print_effects()))
             },
             
             MirInstruction::Nop => {
@@ -390,8 +413,8 @@ impl MirPrinter {
             },
             
             // Phase 5: Control flow & exception handling
-            MirInstruction::Throw { exception, effects: _ } => {
-                format!("throw {}", exception)
+            MirInstruction::Throw { exception, effects } => {
+                format!("throw {}{}", exception, self.format_effects_suffix(*effects, default_throw_effects()))
             },
             
             MirInstruction::Catch { exception_type, exception_value, handler_bb } => {
@@ -470,6 +493,25 @@ impl MirPrinter {
         }
     }
     
+    /// Format a constant so that it parses back to the same value:
+    /// floats always carry a fraction or exponent, strings are escaped
+    fn format_const(&self, value: &ConstValue) -> String {
+        match value {
+            ConstValue::Float(f) => format!("{:?}", f),
+            ConstValue::String(s) => format!("{:?}", s),
+            other => other.to_string(),
+        }
+    }
+    
+    /// ` [effects: ...]` when an instruction's effects differ from what the text format assumes
+    fn format_effects_suffix(&self, effects: EffectMask, assumed: EffectMask) -> String {
+        if effects == assumed {
+            String::new()
+        } else {
+            format!(" [effects: {}]", effects)
+        }
+    }
+    
     /// Format a MIR type
     fn format_type(&self, mir_type: &super::MirType) -> String {
         match mir_type {
@@ -1,10 +1,15 @@
 /*!
  * MIR Verification - SSA form and semantic verification
  * 
- * Implements dominance checking, SSA verification, and semantic analysis
+ * Implements dominance checking, SSA verification, and semantic analysis:
+ * - every use is dominated by its definition (phi inputs by the end of their predecessor)
+ * - phi inputs correspond exactly to the block's predecessors
+ * - every block ends in a terminator
+ * - effect masks agree with the opcode (no "pure" print, throw or extern call)
  */
 
-use super::{MirModule, MirFunction, BasicBlockId, ValueId};
+use super::{MirModule, MirFunction, MirInstruction, BasicBlockId, ValueId};
+use super::dominators::{cfg_predecessors, DominatorTree};
 use std::collections::{HashSet, HashMap};
 
 /// Verification error types
@@ -54,6 +59,25 @@ pub enum VerificationError {
         merge_block: BasicBlockId,
         pred_block: BasicBlockId,
     },
+
+    /// Value used earlier in the block than its definition
+    UseBeforeDefinition {
+        value: ValueId,
+        block: BasicBlockId,
+        instruction_index: usize,
+    },
+
+    /// Block without a terminator instruction
+    MissingTerminator {
+        block: BasicBlockId,
+    },
+
+    /// Effect mask inconsistent with the instruction's opcode
+    InvalidEffect {
+        block: BasicBlockId,
+        instruction_index: usize,
+        reason: String,
+    },
 }
 
 /// MIR verifier for SSA form and semantic correctness
@@ -113,6 +137,14 @@ impl MirVerifier {
         if let Err(mut merge_errors) = self.verify_merge_uses(function) {
             local_errors.append(&mut merge_errors);
         }
+        // 5. Check phi placement and inputs against predecessors
+        if let Err(mut phi_errors) = self.verify_phis(function) {
+            local_errors.append(&mut phi_errors);
+        }
+        // 6. Check effect masks against opcodes
+        if let Err(mut effect_errors) = self.verify_effects(function) {
+            local_errors.append(&mut effect_errors);
+        }
         
         if local_errors.is_empty() {
             Ok(())
@@ -125,6 +157,10 @@ impl MirVerifier {
     fn verify_ssa_form(&self, function: &MirFunction) -> Result<(), Vec<VerificationError>> {
         let mut errors = Vec::new();
         let mut definitions = HashMap::new();
+        // Parameters are defined on entry
+        for param in &function.params {
+            definitions.insert(*param, (function.entry_block, 0));
+        }
         
         // Check that each value is defined exactly once
         for (block_id, block) in &function.blocks {
@@ -163,28 +199,46 @@ impl MirVerifier {
         }
     }
     
-    /// Verify dominance relations (def must dominate use across blocks)
+    /// Verify dominance relations: a def must dominate each use. Within a block the def must
+    /// come first; a phi input must dominate the end of the predecessor it flows from.
     fn verify_dominance(&self, function: &MirFunction) -> Result<(), Vec<VerificationError>> {
         let mut errors = Vec::new();
-
-        // Build def -> block map and dominators
-        let def_block = self.compute_def_blocks(function);
-        let dominators = self.compute_dominators(function);
+        let dom_tree = DominatorTree::build(function);
+        let def_sites = self.compute_def_sites(function);
 
         for (use_block_id, block) in &function.blocks {
-            for instruction in block.all_instructions() {
+            // Unreachable blocks are reported by the control flow check
+            if !dom_tree.is_reachable(*use_block_id) { continue; }
+            for (inst_idx, instruction) in block.all_instructions().enumerate() {
+                if let MirInstruction::Phi { inputs, .. } = instruction {
+                    for (pred, value) in inputs {
+                        let Some(&(def_bb, _)) = def_sites.get(value) else { continue };
+                        if dom_tree.is_reachable(*pred) && !dom_tree.dominates(def_bb, *pred) {
+                            errors.push(VerificationError::DominatorViolation {
+                                value: *value,
+                                use_block: *pred,
+                                def_block: def_bb,
+                            });
+                        }
+                    }
+                    continue;
+                }
                 for used_value in instruction.used_values() {
-                    if let Some(&def_bb) = def_block.get(&used_value) {
-                        if def_bb != *use_block_id {
-                            let doms = dominators.get(use_block_id).unwrap();
-                            if !doms.contains(&def_bb) {
-                                errors.push(VerificationError::DominatorViolation {
-                                    value: used_value,
-                                    use_block: *use_block_id,
-                                    def_block: def_bb,
-                                });
-                            }
+                    let Some(&(def_bb, def_idx)) = def_sites.get(&used_value) else { continue };
+                    if def_bb == *use_block_id {
+                        if matches!(def_idx, Some(idx) if idx >= inst_idx) {
+                            errors.push(VerificationError::UseBeforeDefinition {
+                                value: used_value,
+                                block: *use_block_id,
+                                instruction_index: inst_idx,
+                            });
                         }
+                    } else if !dom_tree.dominates(def_bb, *use_block_id) {
+                        errors.push(VerificationError::DominatorViolation {
+                            value: used_value,
+                            use_block: *use_block_id,
+                            def_block: def_bb,
+                        });
                     }
                 }
             }
@@ -209,6 +263,13 @@ impl MirVerifier {
             }
         }
         
+        // Check that every block ends in a terminator
+        for (block_id, block) in &function.blocks {
+            if block.terminator.is_none() {
+                errors.push(VerificationError::MissingTerminator { block: *block_id });
+            }
+        }
+        
         // Check that all blocks are reachable from entry
         let reachable = self.compute_reachable_blocks(function);
         for block_id in function.blocks.keys() {
@@ -232,7 +293,7 @@ impl MirVerifier {
         let mut errors = Vec::new();
         let preds = self.compute_predecessors(function);
         let def_block = self.compute_def_blocks(function);
-        let dominators = self.compute_dominators(function);
+        let dom_tree = DominatorTree::build(function);
         // Helper: collect phi dsts in a block
         let mut phi_dsts_in_block: std::collections::HashMap<BasicBlockId, std::collections::HashSet<ValueId>> = std::collections::HashMap::new();
         for (bid, block) in &function.blocks {
@@ -246,13 +307,13 @@ impl MirVerifier {
             let Some(pred_list) = preds.get(bid) else { continue };
             if pred_list.len() < 2 { continue; }
             let phi_dsts = phi_dsts_in_block.get(bid);
-            let doms_of_block = dominators.get(bid).unwrap();
-            // check instructions including terminator
+            // check instructions including terminator; phi inputs are checked against their predecessor
             for inst in block.all_instructions() {
+                if matches!(inst, MirInstruction::Phi { .. }) { continue; }
                 for used in inst.used_values() {
                     if let Some(&db) = def_block.get(&used) {
                         // If def doesn't dominate merge block, it must be routed via phi
-                        if !doms_of_block.contains(&db) {
+                        if !dom_tree.dominates(db, *bid) {
                             let is_phi_dst = phi_dsts.map(|s| s.contains(&used)).unwrap_or(false);
                             if !is_phi_dst {
                                 errors.push(VerificationError::MergeUsesPredecessorValue {
@@ -270,6 +331,85 @@ impl MirVerifier {
         if errors.is_empty() { Ok(()) } else { Err(errors) }
     }
     
+    /// Verify phi placement and that phi inputs correspond exactly to the block's predecessors.
+    /// Edges from unreachable predecessors never execute, so their inputs may be omitted.
+    fn verify_phis(&self, function: &MirFunction) -> Result<(), Vec<VerificationError>> {
+        let mut errors = Vec::new();
+        let preds = cfg_predecessors(function);
+        let dom_tree = DominatorTree::build(function);
+
+        for (block_id, block) in &function.blocks {
+            if !dom_tree.is_reachable(*block_id) { continue; }
+            let block_preds: HashSet<BasicBlockId> = preds.get(block_id).into_iter().flatten().copied().collect();
+            let mut seen_non_phi = false;
+            for inst in block.all_instructions() {
+                let MirInstruction::Phi { dst, inputs } = inst else {
+                    seen_non_phi = true;
+                    continue;
+                };
+                let mut invalid = |reason: String| errors.push(VerificationError::InvalidPhi {
+                    phi_value: *dst,
+                    block: *block_id,
+                    reason,
+                });
+                if seen_non_phi {
+                    invalid("phi must precede all non-phi instructions".to_string());
+                }
+                let mut incoming = HashSet::new();
+                for (pred, _) in inputs {
+                    if !incoming.insert(*pred) {
+                        invalid(format!("duplicate input for predecessor {}", pred));
+                    }
+                    if !block_preds.contains(pred) {
+                        invalid(format!("input from {} which is not a predecessor", pred));
+                    }
+                }
+                let mut missing: Vec<_> = block_preds.iter()
+                    .filter(|p| dom_tree.is_reachable(**p) && !incoming.contains(*p))
+                    .collect();
+                missing.sort();
+                for pred in missing {
+                    invalid(format!("no input for predecessor {}", pred));
+                }
+            }
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    /// Verify that effect masks agree with opcodes: side-effecting instructions must not be
+    /// declared pure, since the optimizer freely removes and reorders pure instructions
+    fn verify_effects(&self, function: &MirFunction) -> Result<(), Vec<VerificationError>> {
+        let mut errors = Vec::new();
+
+        for (block_id, block) in &function.blocks {
+            for (inst_idx, inst) in block.all_instructions().enumerate() {
+                if !inst.effects().is_pure() { continue; }
+                let reason = match inst {
+                    MirInstruction::Print { .. } => "print is declared pure".to_string(),
+                    MirInstruction::Throw { .. } => "throw is declared pure".to_string(),
+                    MirInstruction::ExternCall { iface_name, method_name, .. } => {
+                        format!("extern call {}.{} is declared pure", iface_name, method_name)
+                    }
+                    MirInstruction::BoxCall { dst: None, method, .. } => {
+                        format!("call to {} is declared pure but has no result", method)
+                    }
+                    MirInstruction::Call { dst: None, .. } => {
+                        "call is declared pure but has no result".to_string()
+                    }
+                    _ => continue,
+                };
+                errors.push(VerificationError::InvalidEffect {
+                    block: *block_id,
+                    instruction_index: inst_idx,
+                    reason,
+                });
+            }
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+    
     /// Compute reachable blocks from entry
     fn compute_reachable_blocks(&self, function: &MirFunction) -> HashSet<BasicBlockId> {
         let mut reachable = HashSet::new();
@@ -341,46 +481,18 @@ impl MirVerifier {
         def_block
     }
 
-    /// Compute dominator sets per block using standard iterative algorithm
-    fn compute_dominators(&self, function: &MirFunction) -> HashMap<BasicBlockId, HashSet<BasicBlockId>> {
-        let all_blocks: HashSet<BasicBlockId> = function.blocks.keys().copied().collect();
-        let preds = self.compute_predecessors(function);
-        let mut dom: HashMap<BasicBlockId, HashSet<BasicBlockId>> = HashMap::new();
-
-        for &b in function.blocks.keys() {
-            if b == function.entry_block {
-                let mut set = HashSet::new();
-                set.insert(b);
-                dom.insert(b, set);
-            } else {
-                dom.insert(b, all_blocks.clone());
-            }
-        }
-
-        let mut changed = true;
-        while changed {
-            changed = false;
-            for &b in function.blocks.keys() {
-                if b == function.entry_block { continue; }
-                let mut new_set: HashSet<BasicBlockId> = all_blocks.clone();
-                if let Some(ps) = preds.get(&b) {
-                    if !ps.is_empty() {
-                        for (i, p) in ps.iter().enumerate() {
-                            if let Some(p_set) = dom.get(p) {
-                                if i == 0 { new_set = p_set.clone(); }
-                                else { new_set = new_set.intersection(p_set).copied().collect(); }
-                            }
-                        }
-                    }
-                }
-                new_set.insert(b);
-                if let Some(old) = dom.get(&b) {
-                    if &new_set != old { dom.insert(b, new_set); changed = true; }
-                }
+    /// Build a map from ValueId to its defining block and instruction index
+    /// (`None` for function parameters, which are defined on entry)
+    fn compute_def_sites(&self, function: &MirFunction) -> HashMap<ValueId, (BasicBlockId, Option<usize>)> {
+        let mut sites: HashMap<ValueId, (BasicBlockId, Option<usize>)> = function.params.iter()
+            .map(|param| (*param, (function.entry_block, None)))
+            .collect();
+        for (bid, block) in &function.blocks {
+            for (idx, inst) in block.all_instructions().enumerate() {
+                if let Some(dst) = inst.dst_value() { sites.insert(dst, (*bid, Some(idx))); }
             }
         }
-
-        dom
+        sites
     }
 }
 
@@ -419,6 +531,17 @@ impl std::fmt::Display for VerificationError {
                 write!(f, "Merge block {} uses predecessor-defined value {} from block {} without Phi",
                        merge_block, value, pred_block)
             },
+            VerificationError::UseBeforeDefinition { value, block, instruction_index } => {
+                write!(f, "Value {} used in block {} at instruction {} before its definition",
+                       value, block, instruction_index)
+            },
+            VerificationError::MissingTerminator { block } => {
+                write!(f, "Block {} has no terminator", block)
+            },
+            VerificationError::InvalidEffect { block, instruction_index, reason } => {
+                write!(f, "Invalid effect mask in block {} at instruction {}: {}",
+                       block, instruction_index, reason)
+            },
         }
     }
 }
@@ -426,8 +549,46 @@ impl std::fmt::Display for VerificationError {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::mir::{MirFunction, FunctionSignature, MirType, EffectMask, BasicBlock, MirBuilder, MirPrinter};
+    use crate::mir::{MirFunction, FunctionSignature, MirType, EffectMask, BasicBlock, MirBuilder, MirPrinter, ConstValue, BinaryOp, Effect};
     use crate::ast::{ASTNode, Span, LiteralValue};
+
+    fn empty_function(name: &str) -> MirFunction {
+        let signature = FunctionSignature {
+            name: name.to_string(),
+            params: vec![],
+            return_type: MirType::Void,
+            effects: EffectMask::PURE,
+        };
+        MirFunction::new(signature, BasicBlockId::new(0))
+    }
+
+    /// bb0: %0 = 0; jump bb1
+    /// bb1: %1 = phi [%0, bb0], [%2, bb2]; br %1, bb2, bb3
+    /// bb2: %2 = %1 + %1; jump bb1
+    /// bb3: ret %1
+    fn loop_function(phi_inputs: impl FnOnce(ValueId, ValueId) -> Vec<(BasicBlockId, ValueId)>) -> MirFunction {
+        let mut f = empty_function("loop");
+        let (entry, header, body, exit) = (BasicBlockId::new(0), BasicBlockId::new(1), BasicBlockId::new(2), BasicBlockId::new(3));
+        let (init, phi, next) = (f.next_value_id(), f.next_value_id(), f.next_value_id());
+        {
+            let b0 = f.get_block_mut(entry).unwrap();
+            b0.add_instruction(MirInstruction::Const { dst: init, value: ConstValue::Integer(0) });
+            b0.add_instruction(MirInstruction::Jump { target: header });
+        }
+        let mut b1 = BasicBlock::new(header);
+        b1.add_instruction(MirInstruction::Phi { dst: phi, inputs: phi_inputs(init, next) });
+        b1.add_instruction(MirInstruction::Branch { condition: phi, then_bb: body, else_bb: exit });
+        f.add_block(b1);
+        let mut b2 = BasicBlock::new(body);
+        b2.add_instruction(MirInstruction::BinOp { dst: next, op: BinaryOp::Add, lhs: phi, rhs: phi });
+        b2.add_instruction(MirInstruction::Jump { target: header });
+        f.add_block(b2);
+        let mut b3 = BasicBlock::new(exit);
+        b3.add_instruction(MirInstruction::Return { value: Some(phi) });
+        f.add_block(b3);
+        f.update_cfg();
+        f
+    }
     
     #[test]
     fn test_valid_function_verification() {
@@ -439,7 +600,8 @@ mod tests {
         };
         
         let entry_block = BasicBlockId::new(0);
-        let function = MirFunction::new(signature, entry_block);
+        let mut function = MirFunction::new(signature, entry_block);
+        function.get_block_mut(entry_block).unwrap().add_instruction(MirInstruction::Return { value: None });
         
         let mut verifier = MirVerifier::new();
         let result = verifier.verify_function(&function);
@@ -447,6 +609,62 @@ mod tests {
         assert!(result.is_ok(), "Valid function should pass verification");
     }
     
+    #[test]
+    fn test_loop_phi_backedge_is_valid() {
+        let f = loop_function(|init, next| vec![(BasicBlockId::new(0), init), (BasicBlockId::new(2), next)]);
+        let res = MirVerifier::new().verify_function(&f);
+        assert!(res.is_ok(), "Loop phi with back-edge input should verify: {:?}", res);
+    }
+
+    #[test]
+    fn test_phi_inputs_must_match_predecessors() {
+        // Missing back-edge input, and an input from a block that is not a predecessor
+        let f = loop_function(|init, _| vec![(BasicBlockId::new(0), init), (BasicBlockId::new(3), init)]);
+        let errs = MirVerifier::new().verify_function(&f).unwrap_err();
+        let reasons: Vec<&str> = errs.iter().filter_map(|e| match e {
+            VerificationError::InvalidPhi { reason, .. } => Some(reason.as_str()),
+            _ => None,
+        }).collect();
+        assert!(reasons.iter().any(|r| r.contains("not a predecessor")), "{:?}", errs);
+        assert!(reasons.iter().any(|r| r.contains("no input for predecessor bb2")), "{:?}", errs);
+    }
+
+    #[test]
+    fn test_use_before_definition_detected() {
+        let mut f = empty_function("use_before_def");
+        let (a, b) = (f.next_value_id(), f.next_value_id());
+        let b0 = f.get_block_mut(BasicBlockId::new(0)).unwrap();
+        b0.add_instruction(MirInstruction::BinOp { dst: b, op: BinaryOp::Add, lhs: a, rhs: a });
+        b0.add_instruction(MirInstruction::Const { dst: a, value: ConstValue::Integer(1) });
+        b0.add_instruction(MirInstruction::Return { value: Some(b) });
+
+        let errs = MirVerifier::new().verify_function(&f).unwrap_err();
+        assert!(errs.contains(&VerificationError::UseBeforeDefinition {
+            value: a,
+            block: BasicBlockId::new(0),
+            instruction_index: 0,
+        }), "{:?}", errs);
+    }
+
+    #[test]
+    fn test_missing_terminator_and_pure_print_detected() {
+        let mut f = empty_function("no_terminator");
+        let v = f.next_value_id();
+        let b0 = f.get_block_mut(BasicBlockId::new(0)).unwrap();
+        b0.add_instruction(MirInstruction::Const { dst: v, value: ConstValue::Integer(1) });
+        b0.add_instruction(MirInstruction::Print { value: v, effects: EffectMask::PURE });
+
+        let errs = MirVerifier::new().verify_function(&f).unwrap_err();
+        assert!(errs.contains(&VerificationError::MissingTerminator { block: BasicBlockId::new(0) }), "{:?}", errs);
+        assert!(errs.iter().any(|e| matches!(e, VerificationError::InvalidEffect { instruction_index: 1, .. })), "{:?}", errs);
+
+        // The same print with an I/O effect is fine
+        let b0 = f.get_block_mut(BasicBlockId::new(0)).unwrap();
+        b0.instructions[1] = MirInstruction::Print { value: v, effects: EffectMask::PURE.add(Effect::Io) };
+        b0.add_instruction(MirInstruction::Return { value: None });
+        assert!(MirVerifier::new().verify_function(&f).is_ok());
+    }
+
     #[test]
     fn test_undefined_value_detection() {
         // This test would create a function with undefined value usage
@@ -109,7 +109,6 @@ pub trait ParserUtils {
     }
     
     /// 現在のトークンからSpanを作成
-    #[allow(dead_code)]
     fn current_span(&self) -> Span {
         let token = self.current_token();
         Span {
@@ -202,6 +202,7 @@ impl NyashParser {
     
     /// 関数・メソッド呼び出しをパース
     fn parse_call(&mut self) -> Result<ASTNode, ParseError> {
+        let call_span = self.current_span();
         let mut expr = self.parse_primary()?;
         
         loop {
@@ -236,7 +237,7 @@ impl NyashParser {
                             object: Box::new(expr),
                             method: method_name,
                             arguments,
-                            span: Span::unknown(),
+                            span: call_span,
                         };
                     } else {
                         // フィールドアクセス: obj.field
@@ -378,6 +379,7 @@ impl NyashParser {
             }
             
             TokenType::NEW => {
+                let new_span = self.current_span();
                 self.advance();
                 
                 if let TokenType::IDENTIFIER(class_name) = &self.current_token().token_type {
@@ -433,7 +435,7 @@ impl NyashParser {
                         class: class_name,
                         arguments,
                         type_arguments,
-                        span: Span::unknown(),
+                        span: new_span,
                     })
                 } else {
                     let line = self.current_token().line;
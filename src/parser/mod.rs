@@ -193,6 +193,7 @@ impl NyashParser {
     
     /// 代入文または関数呼び出しをパース
     fn parse_assignment_or_function_call(&mut self) -> Result<ASTNode, ParseError> {
+        let span = self.current_span();
         
         // まず左辺を式としてパース
         let expr = self.parse_expression()?;
@@ -209,7 +210,7 @@ impl NyashParser {
                     Ok(ASTNode::Assignment {
                         target: Box::new(expr),
                         value,
-                        span: Span::unknown(),
+                        span,
                     })
                 }
                 _ => {
@@ -5,14 +5,17 @@
  * separated from CLI parsing and the main entry point.
  */
 
-use nyash_rust::cli::CliConfig;
+use nyash_rust::cli::{CliConfig, BidGenConfig};
+use nyash_rust::bid::schema::BidDefinition;
+use nyash_rust::bid::codegen::{self, CodeGenOptions, PluginNames};
 use nyash_rust::{
     box_trait::{NyashBox, StringBox, IntegerBox, BoolBox, VoidBox, AddBox, BoxCore},
     tokenizer::{NyashTokenizer},
     ast::ASTNode,
     parser::NyashParser,
     interpreter::NyashInterpreter,
-    mir::{MirCompiler, MirPrinter, MirInstruction},
+    mir::{MirCompiler, MirPrinter, MirParser, MirVerifier, MirInstruction, MirModule, lower_module_v2},
+    mir::ownership_verifier::OwnershipVerifier,
     backend::VM,
 };
 use nyash_rust::runtime::{NyashRuntime, NyashRuntimeBuilder};
@@ -23,7 +26,7 @@ use nyash_rust::core::model::BoxDeclaration as CoreBoxDecl;
 use std::sync::Arc;
 
 #[cfg(feature = "wasm-backend")]
-use nyash_rust::backend::{wasm::WasmBackend, aot::AotBackend};
+use nyash_rust::backend::{wasm::{WasmBackend, WebBundle}, aot::AotBackend};
 
 #[cfg(feature = "llvm")]
 use nyash_rust::backend::{llvm_compile_and_execute};
@@ -46,6 +49,12 @@ impl NyashRunner {
 
     /// Run Nyash based on the configuration
     pub fn run(&self) {
+        // `nyash bid gen` is a pure code generator - no runtime needed
+        if let Some(ref gen) = self.config.bid_gen {
+            self.execute_bid_gen_mode(gen);
+            return;
+        }
+
         // 🏭 Phase 9.78b: Initialize unified registry
         runtime::init_global_unified_registry();
         
@@ -60,6 +69,10 @@ impl NyashRunner {
             // Prefer explicit JSON flag over any default
             std::env::set_var("NYASH_VM_STATS_JSON", "1");
         }
+        // Optional: verify MIR after every optimizer pass
+        if self.config.verify_after_each_pass {
+            std::env::set_var("NYASH_OPT_VERIFY_EACH_PASS", "1");
+        }
         // Benchmark mode - can run without a file
         if self.config.benchmark {
             println!("📊 Nyash Performance Benchmark Suite");
@@ -111,6 +124,54 @@ impl NyashRunner {
         }
     }
 
+    /// Generate a plugin crate skeleton and nyash.toml entries from a BID file
+    fn execute_bid_gen_mode(&self, gen: &BidGenConfig) {
+        let input = std::path::Path::new(&gen.input);
+        let bid = match BidDefinition::load_from_file(input) {
+            Ok(bid) => bid,
+            Err(e) => {
+                eprintln!("❌ BID error in {}: {}", gen.input, e);
+                process::exit(1);
+            }
+        };
+
+        let output_dir = if gen.output_dir.is_empty() {
+            std::path::Path::new("plugins").join(PluginNames::from_bid(&bid).crate_name)
+        } else {
+            std::path::PathBuf::from(&gen.output_dir)
+        };
+        let options = CodeGenOptions::new(output_dir)
+            .with_force(gen.force)
+            .with_dry_run(gen.dry_run);
+        let source_name = input.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| gen.input.clone());
+
+        let files = match codegen::generate_plugin(&bid, &source_name, &options) {
+            Ok(files) => files,
+            Err(e) => {
+                eprintln!("❌ Code generation failed: {}", e);
+                process::exit(1);
+            }
+        };
+
+        if options.dry_run {
+            for file in &files {
+                println!("// ===== {} =====", file.path.display());
+                println!("{}", file.content);
+            }
+            return;
+        }
+
+        if let Err(e) = codegen::write_files(&files, &options) {
+            eprintln!("❌ {}", e);
+            process::exit(1);
+        }
+        println!("✅ Generated plugin '{}' ({} files):", bid.name(), files.len());
+        for file in &files {
+            println!("  📄 {}", file.path.display());
+        }
+        println!("👉 Merge {} into nyash.toml under [libraries]", options.output_dir.join("nyash.toml").display());
+    }
+
     /// Execute file-based mode with backend selection
     fn execute_file_mode(&self, filename: &str) {
         if self.config.dump_ast {
@@ -132,7 +193,10 @@ impl NyashRunner {
             println!("{:#?}", ast);
             return;
         }
-        if self.config.dump_mir || self.config.verify_mir {
+        if self.config.run_mir {
+            println!("🚀 Nyash VM Backend - Executing MIR file: {} 🚀", filename);
+            self.execute_mir_file_mode(filename);
+        } else if self.config.dump_mir || self.config.verify_mir || self.config.verify_ownership {
             println!("🚀 Nyash MIR Compiler - Processing file: {} 🚀", filename);
             self.execute_mir_mode(filename);
         } else if self.config.compile_wasm {
@@ -315,6 +379,26 @@ impl NyashRunner {
             }
         }
 
+        // Check ownership forest rules if requested
+        if self.config.verify_ownership {
+            println!("🔍 Verifying ownership...");
+            let mut verifier = OwnershipVerifier::new();
+            let result = verifier.verify_module(&compile_result.module);
+            for warning in verifier.warnings() {
+                eprintln!("⚠️  Ownership warning: {}", warning.render(Some(&code)));
+            }
+            match result {
+                Ok(()) => println!("✅ Ownership verification passed!"),
+                Err(errors) => {
+                    eprintln!("❌ Ownership verification failed ({} error(s)):", errors.len());
+                    for error in &errors {
+                        eprintln!("  • {}", error.render(Some(&code)));
+                    }
+                    process::exit(1);
+                }
+            }
+        }
+
         // Dump MIR if requested
         if self.config.dump_mir {
             let mut printer = if self.config.mir_verbose { MirPrinter::verbose() } else { MirPrinter::new() };
@@ -373,7 +457,7 @@ impl NyashRunner {
 
         // Execute with VM using prepared runtime
         let mut vm = VM::with_runtime(runtime);
-        match vm.execute_module(&compile_result.module) {
+        match self.run_on_vm(&mut vm, &compile_result.module) {
             Ok(result) => {
                 println!("✅ VM execution completed successfully!");
                 println!("Result: {:?}", result);
@@ -385,6 +469,62 @@ impl NyashRunner {
         }
     }
 
+    /// Execute a textual MIR file (as written by --dump-mir) on the VM
+    fn execute_mir_file_mode(&self, filename: &str) {
+        let text = match fs::read_to_string(filename) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("❌ Error reading file {}: {}", filename, e);
+                process::exit(1);
+            }
+        };
+
+        let module = match MirParser::new().parse_module(&text) {
+            Ok(module) => module,
+            Err(e) => {
+                eprintln!("❌ MIR parse error in {}: {}", filename, e);
+                process::exit(1);
+            }
+        };
+
+        if self.config.verify_mir {
+            let mut verifier = MirVerifier::new();
+            if let Err(errors) = verifier.verify_module(&module) {
+                eprintln!("❌ MIR verification failed:");
+                for error in errors {
+                    eprintln!("  • {}", error);
+                }
+                process::exit(1);
+            }
+            println!("✅ MIR verification passed!");
+        }
+
+        let runtime = NyashRuntimeBuilder::new()
+            .with_builtin_groups(BuiltinGroups::native_full())
+            .build();
+        let mut vm = VM::with_runtime(runtime);
+        match self.run_on_vm(&mut vm, &module) {
+            Ok(result) => {
+                println!("✅ VM execution completed successfully!");
+                println!("Result: {:?}", result);
+            },
+            Err(e) => {
+                eprintln!("❌ VM execution error: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+
+    /// Run `module` on the VM, lowered to the 25-instruction set first with `--mir-v2`
+    fn run_on_vm(&self, vm: &mut VM, module: &MirModule) -> Result<Box<dyn NyashBox>, String> {
+        if self.config.mir_v2 {
+            let module_v2 = lower_module_v2(module).map_err(|e| format!("MIR V2 lowering failed: {}", e))?;
+            vm.execute_module_v2(&module_v2).map_err(|e| e.to_string())
+        } else {
+            vm.execute_module(module).map_err(|e| e.to_string())
+        }
+    }
+
     /// Collect Box declarations from AST and register into runtime
     fn collect_box_declarations(&self, ast: &ASTNode, runtime: &NyashRuntime) {
         fn walk(node: &ASTNode, runtime: &NyashRuntime) {
@@ -459,6 +599,11 @@ impl NyashRunner {
             }
         };
 
+        if self.config.wasm_target == "web" {
+            self.write_web_bundle(filename, compile_result.module);
+            return;
+        }
+
         // Compile to WASM (Phase 9.77a fix: use compile_to_wat instead of compile_module)
         let mut wasm_backend = WasmBackend::new();
         let wat_text = match wasm_backend.compile_to_wat(compile_result.module) {
@@ -495,6 +640,33 @@ impl NyashRunner {
         }
     }
 
+    /// Write the browser bundle of `--compile-wasm --target web` into the `-o` directory
+    #[cfg(feature = "wasm-backend")]
+    fn write_web_bundle(&self, filename: &str, module: nyash_rust::mir::MirModule) {
+        let name = std::path::Path::new(filename)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("main");
+        let output_dir = self.config.output_file.as_deref().unwrap_or("dist");
+
+        let mut wasm_backend = WasmBackend::new();
+        let written = WebBundle::build(&mut wasm_backend, module, name)
+            .and_then(|bundle| bundle.write_to(output_dir));
+        match written {
+            Ok(files) => {
+                println!("✅ WASM web bundle written to: {}", output_dir);
+                for file in files {
+                    println!("  {}", file.display());
+                }
+                println!("Serve the directory over HTTP (e.g. `python3 -m http.server`) and open index.html");
+            }
+            Err(e) => {
+                eprintln!("❌ WASM web bundle error: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+
     /// Execute AOT compilation mode
     #[cfg(feature = "wasm-backend")]
     fn execute_aot_mode(&self, filename: &str) {
@@ -942,17 +1114,26 @@ mod tests {
         let config = CliConfig {
             file: None,
             debug_fuel: Some(100000),
+            dump_ast: false,
             dump_mir: false,
             verify_mir: false,
+            verify_ownership: false,
             mir_verbose: false,
+            mir_verbose_effects: false,
+            run_mir: false,
+            no_optimize: false,
+            verify_after_each_pass: false,
             backend: "interpreter".to_string(),
             compile_wasm: false,
+            wasm_target: "wat".to_string(),
             compile_native: false,
             output_file: None,
             benchmark: false,
             iterations: 10,
             vm_stats: false,
+            mir_v2: false,
             vm_stats_json: false,
+            bid_gen: None,
         };
         
         let runner = NyashRunner::new(config);
@@ -5,6 +5,10 @@
 pub mod plugin_config;
 pub mod box_registry;
 pub mod plugin_loader_v2;
+#[cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
+pub mod plugin_host;
+#[cfg(all(feature = "plugins", feature = "wasm-backend", not(target_arch = "wasm32")))]
+pub mod plugin_wasm;
 pub mod leak_tracker;
 pub mod unified_registry;
 pub mod nyash_runtime;
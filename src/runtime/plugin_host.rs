@@ -0,0 +1,522 @@
+//! Out-of-process plugin host (`isolation = "process"`)
+//!
+//! The loader spawns `nyash-plugin-host <lib>` and forwards every
+//! `nyash_plugin_invoke` call over the child's stdin/stdout. Arguments and
+//! results stay BID-1 TLV; the pipe only adds a fixed little-endian frame:
+//!
+//! - request : type_id u32, method_id u32, instance_id u32, out_cap u32, args_len u32, args
+//! - response: rc i32, out_len u32, out (present only when rc == 0)
+//!
+//! On startup the helper writes an i32 (0 = library loaded and
+//! `nyash_plugin_init` succeeded), followed on success by the library's
+//! self-description: abi u32 (0 = `nyash_plugin_abi` not exported) and
+//! manifest_len u32 + manifest bytes (0 = no `nyash_plugin_manifest`).
+//!
+//! Instance ids seen by the loader are host-side ids mapped to
+//! (generation, type_id, plugin id); plugins may number instances per box
+//! type, so the type is part of the key. A crash fails the in-flight call with
+//! `BidError::PluginError`; the next call respawns the helper (up to
+//! `max_restarts`) and any handle from the previous generation answers
+//! `BidError::InvalidHandle` instead of aliasing a fresh instance. Singletons
+//! are the exception: the loader compares `generation()` and re-births them.
+//!
+//! Calls into one library are serialized: the helper serves one frame at a
+//! time, so the pipe is held for the whole round trip and a method that
+//! blocks inside the plugin (e.g. `accept`) stalls every other call into
+//! the same library until it returns.
+
+use crate::bid::BidError;
+use std::collections::HashMap;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::Mutex;
+
+/// Default number of automatic restarts after a crash
+pub const DEFAULT_MAX_RESTARTS: u32 = 3;
+
+/// Environment variable overriding the helper binary location
+pub const PLUGIN_HOST_ENV: &str = "NYASH_PLUGIN_HOST";
+
+const HOST_BIN_NAME: &str = "nyash-plugin-host";
+
+struct HostProcess {
+    child: Child,
+    stdin: BufWriter<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+}
+
+struct HostState {
+    process: Option<HostProcess>,
+    generation: u64,
+    restarts: u32,
+    /// host-side instance id -> (generation, type_id, plugin-side instance id)
+    instances: HashMap<u32, (u64, u32, u32)>,
+    /// (generation, type_id, plugin-side id) -> host-side id
+    reverse: HashMap<(u64, u32, u32), u32>,
+    next_instance: u32,
+    /// Reported by the helper at startup
+    abi_version: Option<u32>,
+    manifest: Option<Vec<u8>>,
+}
+
+/// Client side of an isolated plugin library
+pub struct ProcessPluginHost {
+    lib_path: String,
+    host_bin: PathBuf,
+    max_restarts: u32,
+    /// Held for the whole request/response round trip (calls are serialized)
+    state: Mutex<HostState>,
+}
+
+impl std::fmt::Debug for ProcessPluginHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ProcessPluginHost({})", self.lib_path)
+    }
+}
+
+impl ProcessPluginHost {
+    /// Spawn the helper for `lib_path` and wait for its load handshake
+    pub fn spawn(lib_path: &str, host_bin: Option<&str>, max_restarts: u32) -> Result<Self, BidError> {
+        let host_bin = match host_bin {
+            Some(p) => PathBuf::from(p),
+            None => find_host_binary().ok_or_else(|| {
+                eprintln!("[PluginHost] {} not found (set {})", HOST_BIN_NAME, PLUGIN_HOST_ENV);
+                BidError::PluginError
+            })?,
+        };
+        let host = Self {
+            lib_path: lib_path.to_string(),
+            host_bin,
+            max_restarts,
+            state: Mutex::new(HostState {
+                process: None,
+                generation: 0,
+                restarts: 0,
+                instances: HashMap::new(),
+                reverse: HashMap::new(),
+                next_instance: 1,
+                abi_version: None,
+                manifest: None,
+            }),
+        };
+        {
+            let mut st = host.state.lock().unwrap();
+            host.start(&mut st)?;
+        }
+        Ok(host)
+    }
+
+    /// Number of restarts performed so far
+    pub fn restart_count(&self) -> u32 {
+        self.state.lock().unwrap().restarts
+    }
+
+    /// Incremented each time a helper process is started (including automatic restarts)
+    pub fn generation(&self) -> u64 {
+        self.state.lock().unwrap().generation
+    }
+
+    /// `nyash_plugin_abi` of the hosted library, if exported
+    pub fn abi_version(&self) -> Option<u32> {
+        self.state.lock().unwrap().abi_version
+    }
+
+    /// Raw `nyash_plugin_manifest` output of the hosted library, if exported
+    pub fn manifest_bytes(&self) -> Option<Vec<u8>> {
+        self.state.lock().unwrap().manifest.clone()
+    }
+
+    /// Whether the helper process is currently running
+    pub fn is_alive(&self) -> bool {
+        let mut st = self.state.lock().unwrap();
+        match st.process.as_mut() {
+            Some(p) => matches!(p.child.try_wait(), Ok(None)),
+            None => false,
+        }
+    }
+
+    /// Kill the current helper and start a fresh one (handles become stale)
+    pub fn restart(&self) -> Result<(), BidError> {
+        let mut st = self.state.lock().unwrap();
+        Self::kill(&mut st);
+        st.restarts += 1;
+        self.start(&mut st)
+    }
+
+    /// Forward one `nyash_plugin_invoke` call. Same contract as the C ABI:
+    /// `out_len` carries the buffer capacity in and the written/required size out.
+    /// Blocks while another call into this library is in flight.
+    pub fn invoke(&self, type_id: u32, method_id: u32, instance_id: u32, args: &[u8], out: &mut [u8], out_len: &mut usize) -> i32 {
+        let mut st = self.state.lock().unwrap();
+
+        if st.process.is_none() {
+            if st.restarts >= self.max_restarts {
+                return BidError::PluginError as i32;
+            }
+            st.restarts += 1;
+            eprintln!("[PluginHost] restarting {} (restart {}/{})", self.lib_path, st.restarts, self.max_restarts);
+            if self.start(&mut st).is_err() {
+                return BidError::PluginError as i32;
+            }
+        }
+
+        // Map host-side ids to the current generation's plugin ids
+        let generation = st.generation;
+        let plugin_instance = if instance_id == 0 {
+            0
+        } else {
+            match st.instances.get(&instance_id) {
+                Some((g, t, id)) if *g == generation && *t == type_id => *id,
+                _ => return BidError::InvalidHandle as i32,
+            }
+        };
+        let args = match rewrite_handles(args, |t, h| {
+            st.instances.get(&h).and_then(|(g, ht, id)| (*g == generation && *ht == t).then_some(*id))
+        }) {
+            Some(a) => a,
+            None => return BidError::InvalidHandle as i32,
+        };
+
+        let out_cap = (*out_len).min(out.len());
+        let response = match st.process.as_mut() {
+            Some(p) => Self::round_trip(p, type_id, method_id, plugin_instance, &args, out_cap),
+            None => Err(()),
+        };
+        let (rc, payload, reported_len) = match response {
+            Ok(r) => r,
+            Err(()) => {
+                eprintln!("[PluginHost] {} crashed during type_id={} method_id={}", self.lib_path, type_id, method_id);
+                Self::kill(&mut st);
+                return BidError::PluginError as i32;
+            }
+        };
+
+        if rc != 0 {
+            *out_len = reported_len;
+            if method_id == u32::MAX && instance_id != 0 {
+                Self::forget(&mut st, instance_id);
+            }
+            return rc;
+        }
+
+        // birth returns a raw u32 instance id
+        let payload = if method_id == 0 && payload.len() >= 4 {
+            let pid = u32::from_le_bytes([payload[0], payload[1], payload[2], payload[3]]);
+            let hid = Self::register(&mut st, type_id, pid);
+            let mut p = payload;
+            p[0..4].copy_from_slice(&hid.to_le_bytes());
+            p
+        } else {
+            let gen = st.generation;
+            rewrite_handles(&payload, |t, pid| Some(Self::register_in(&mut st, gen, t, pid))).unwrap_or(payload)
+        };
+
+        if method_id == u32::MAX && instance_id != 0 {
+            Self::forget(&mut st, instance_id);
+        }
+
+        if payload.len() > out.len() {
+            *out_len = payload.len();
+            return BidError::ShortBuffer as i32;
+        }
+        out[..payload.len()].copy_from_slice(&payload);
+        *out_len = payload.len();
+        0
+    }
+
+    fn start(&self, st: &mut HostState) -> Result<(), BidError> {
+        let mut child = Command::new(&self.host_bin)
+            .arg(&self.lib_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| {
+                eprintln!("[PluginHost] failed to spawn {}: {}", self.host_bin.display(), e);
+                BidError::PluginError
+            })?;
+        let stdin = BufWriter::new(child.stdin.take().ok_or(BidError::PluginError)?);
+        let mut stdout = BufReader::new(child.stdout.take().ok_or(BidError::PluginError)?);
+        let rc = read_i32(&mut stdout).map_err(|_| {
+            let _ = child.kill();
+            let _ = child.wait();
+            BidError::PluginError
+        })?;
+        if rc != 0 {
+            let _ = child.wait();
+            eprintln!("[PluginHost] helper failed to load {} (rc={})", self.lib_path, rc);
+            return Err(BidError::from_raw(rc));
+        }
+        let describe = read_u32(&mut stdout).and_then(|abi| {
+            let len = read_u32(&mut stdout)? as usize;
+            let mut manifest = vec![0u8; len];
+            stdout.read_exact(&mut manifest)?;
+            Ok((abi, manifest))
+        });
+        let Ok((abi, manifest)) = describe else {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(BidError::PluginError);
+        };
+        st.abi_version = (abi != 0).then_some(abi);
+        st.manifest = (!manifest.is_empty()).then_some(manifest);
+        st.generation += 1;
+        st.process = Some(HostProcess { child, stdin, stdout });
+        Ok(())
+    }
+
+    fn kill(st: &mut HostState) {
+        if let Some(mut p) = st.process.take() {
+            let _ = p.child.kill();
+            let _ = p.child.wait();
+        }
+    }
+
+    fn round_trip(p: &mut HostProcess, type_id: u32, method_id: u32, instance_id: u32, args: &[u8], out_cap: usize) -> Result<(i32, Vec<u8>, usize), ()> {
+        let mut frame = Vec::with_capacity(20 + args.len());
+        for v in [type_id, method_id, instance_id, out_cap as u32, args.len() as u32] {
+            frame.extend_from_slice(&v.to_le_bytes());
+        }
+        frame.extend_from_slice(args);
+        p.stdin.write_all(&frame).map_err(|_| ())?;
+        p.stdin.flush().map_err(|_| ())?;
+
+        let rc = read_i32(&mut p.stdout).map_err(|_| ())?;
+        let len = read_u32(&mut p.stdout).map_err(|_| ())? as usize;
+        if rc != 0 {
+            return Ok((rc, Vec::new(), len));
+        }
+        let mut payload = vec![0u8; len];
+        p.stdout.read_exact(&mut payload).map_err(|_| ())?;
+        Ok((rc, payload, len))
+    }
+
+    fn register(st: &mut HostState, type_id: u32, plugin_id: u32) -> u32 {
+        let gen = st.generation;
+        Self::register_in(st, gen, type_id, plugin_id)
+    }
+
+    fn register_in(st: &mut HostState, generation: u64, type_id: u32, plugin_id: u32) -> u32 {
+        let key = (generation, type_id, plugin_id);
+        if let Some(h) = st.reverse.get(&key) {
+            return *h;
+        }
+        let h = st.next_instance;
+        st.next_instance = st.next_instance.wrapping_add(1).max(1);
+        st.instances.insert(h, key);
+        st.reverse.insert(key, h);
+        h
+    }
+
+    fn forget(st: &mut HostState, host_id: u32) {
+        if let Some(key) = st.instances.remove(&host_id) {
+            st.reverse.remove(&key);
+        }
+    }
+}
+
+impl Drop for ProcessPluginHost {
+    fn drop(&mut self) {
+        if let Ok(mut st) = self.state.lock() {
+            // Closing stdin lets the helper exit on its own; kill as a fallback
+            if let Some(mut p) = st.process.take() {
+                drop(p.stdin);
+                let _ = p.child.kill();
+                let _ = p.child.wait();
+            }
+        }
+    }
+}
+
+/// Locate `nyash-plugin-host`: $NYASH_PLUGIN_HOST, next to the current
+/// executable, or one directory up (test binaries live in target/*/deps).
+pub fn find_host_binary() -> Option<PathBuf> {
+    if let Ok(p) = std::env::var(PLUGIN_HOST_ENV) {
+        return Some(PathBuf::from(p));
+    }
+    let exe = std::env::current_exe().ok()?;
+    let name = format!("{}{}", HOST_BIN_NAME, std::env::consts::EXE_SUFFIX);
+    let mut dir = exe.parent();
+    for _ in 0..2 {
+        let d = dir?;
+        let candidate = d.join(&name);
+        if candidate.exists() {
+            return Some(candidate);
+        }
+        dir = d.parent();
+    }
+    None
+}
+
+/// Copy a TLV buffer, mapping every Handle (tag 8) instance id through `map(type_id, instance_id)`.
+/// Returns None when a handle cannot be mapped; non-TLV buffers are copied as-is.
+fn rewrite_handles(buf: &[u8], mut map: impl FnMut(u32, u32) -> Option<u32>) -> Option<Vec<u8>> {
+    let mut out = buf.to_vec();
+    if buf.len() < 4 {
+        return Some(out);
+    }
+    let argc = u16::from_le_bytes([buf[2], buf[3]]) as usize;
+    let mut off = 4;
+    for _ in 0..argc {
+        if off + 4 > buf.len() {
+            break;
+        }
+        let tag = buf[off];
+        let size = u16::from_le_bytes([buf[off + 2], buf[off + 3]]) as usize;
+        off += 4;
+        if off + size > buf.len() {
+            break;
+        }
+        if tag == 8 && size == 8 {
+            let type_id = u32::from_le_bytes([buf[off], buf[off + 1], buf[off + 2], buf[off + 3]]);
+            let inst = u32::from_le_bytes([buf[off + 4], buf[off + 5], buf[off + 6], buf[off + 7]]);
+            let mapped = map(type_id, inst)?;
+            out[off + 4..off + 8].copy_from_slice(&mapped.to_le_bytes());
+        }
+        off += size;
+    }
+    Some(out)
+}
+
+fn read_u32(r: &mut impl Read) -> std::io::Result<u32> {
+    let mut b = [0u8; 4];
+    r.read_exact(&mut b)?;
+    Ok(u32::from_le_bytes(b))
+}
+
+fn read_i32(r: &mut impl Read) -> std::io::Result<i32> {
+    read_u32(r).map(|v| v as i32)
+}
+
+/// Helper-process side: load `lib_path` and serve invoke frames on stdin/stdout
+/// until stdin closes. Returns the process exit code.
+pub fn serve_plugin_host(lib_path: &str) -> i32 {
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    let mut input = BufReader::new(stdin.lock());
+    let mut output = BufWriter::new(stdout.lock());
+
+    let lib = match unsafe { libloading::Library::new(lib_path) } {
+        Ok(lib) => lib,
+        Err(e) => {
+            eprintln!("[nyash-plugin-host] failed to load {}: {}", lib_path, e);
+            let _ = output.write_all(&(BidError::PluginError as i32).to_le_bytes());
+            let _ = output.flush();
+            return 1;
+        }
+    };
+    type InvokeFn = unsafe extern "C" fn(u32, u32, u32, *const u8, usize, *mut u8, *mut usize) -> i32;
+    let invoke: InvokeFn = match unsafe { lib.get::<InvokeFn>(b"nyash_plugin_invoke") } {
+        Ok(sym) => *sym,
+        Err(e) => {
+            eprintln!("[nyash-plugin-host] missing nyash_plugin_invoke: {}", e);
+            let _ = output.write_all(&(BidError::InvalidMethod as i32).to_le_bytes());
+            let _ = output.flush();
+            return 1;
+        }
+    };
+    let init_rc = match unsafe { lib.get::<unsafe extern "C" fn() -> i32>(b"nyash_plugin_init") } {
+        Ok(init) => unsafe { init() },
+        Err(_) => 0,
+    };
+    if init_rc != 0 {
+        let _ = output.write_all(&init_rc.to_le_bytes()).and_then(|_| output.flush());
+        return 1;
+    }
+
+    let abi = match unsafe { lib.get::<unsafe extern "C" fn() -> u32>(crate::bid::PLUGIN_ABI_SYMBOL.as_bytes()) } {
+        Ok(f) => unsafe { f() },
+        Err(_) => 0,
+    };
+    let manifest = match unsafe { lib.get::<crate::bid::manifest::ManifestFn>(crate::bid::PLUGIN_MANIFEST_SYMBOL.as_bytes()) } {
+        Ok(f) => crate::bid::manifest::fetch_manifest_bytes(|out, len| unsafe { f(out.as_mut_ptr(), len) }).unwrap_or_else(|e| {
+            eprintln!("[nyash-plugin-host] {}", e);
+            Vec::new()
+        }),
+        Err(_) => Vec::new(),
+    };
+    let mut hello = Vec::with_capacity(12 + manifest.len());
+    hello.extend_from_slice(&init_rc.to_le_bytes());
+    hello.extend_from_slice(&abi.to_le_bytes());
+    hello.extend_from_slice(&(manifest.len() as u32).to_le_bytes());
+    hello.extend_from_slice(&manifest);
+    if output.write_all(&hello).and_then(|_| output.flush()).is_err() {
+        return 1;
+    }
+
+    loop {
+        let mut header = [0u32; 5];
+        for h in header.iter_mut() {
+            match read_u32(&mut input) {
+                Ok(v) => *h = v,
+                Err(_) => return 0, // parent closed the pipe
+            }
+        }
+        let [type_id, method_id, instance_id, out_cap, args_len] = header;
+        let mut args = vec![0u8; args_len as usize];
+        if input.read_exact(&mut args).is_err() {
+            return 0;
+        }
+        let mut out = vec![0u8; out_cap as usize];
+        let mut out_len = out.len();
+        let out_ptr = if out.is_empty() { std::ptr::null_mut() } else { out.as_mut_ptr() };
+        let rc = unsafe { invoke(type_id, method_id, instance_id, args.as_ptr(), args.len(), out_ptr, &mut out_len) };
+
+        let frame = response_frame(rc, &out, out_len);
+        if output.write_all(&frame).and_then(|_| output.flush()).is_err() {
+            return 0;
+        }
+    }
+}
+
+/// Encode one response. The body length always matches the header: a plugin that reports
+/// success with more bytes than the buffer holds is answered as `ShortBuffer` with no body.
+fn response_frame(rc: i32, out: &[u8], out_len: usize) -> Vec<u8> {
+    let rc = if rc == 0 && out_len > out.len() { BidError::ShortBuffer as i32 } else { rc };
+    let body = if rc == 0 { &out[..out_len] } else { &[][..] };
+    let mut frame = Vec::with_capacity(8 + body.len());
+    frame.extend_from_slice(&rc.to_le_bytes());
+    frame.extend_from_slice(&(out_len as u32).to_le_bytes());
+    frame.extend_from_slice(body);
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_frame_never_claims_unsent_bytes() {
+        let out = [1u8, 2, 3, 4];
+        let ok = response_frame(0, &out, 2);
+        assert_eq!(ok, [0, 0, 0, 0, 2, 0, 0, 0, 1, 2]);
+
+        // rc == 0 but out_len beyond the buffer: ShortBuffer with the required size, no body
+        let short = response_frame(0, &out, 9);
+        assert_eq!(read_i32(&mut &short[..4]).unwrap(), BidError::ShortBuffer as i32);
+        assert_eq!(read_u32(&mut &short[4..8]).unwrap(), 9);
+        assert_eq!(short.len(), 8);
+
+        let err = response_frame(BidError::InvalidMethod as i32, &out, 4);
+        assert_eq!(err.len(), 8);
+    }
+
+    #[test]
+    fn test_rewrite_handles_maps_only_handle_entries() {
+        // ver=1, argc=2: I32(7), Handle(type=6, inst=5)
+        let mut buf = vec![1, 0, 2, 0];
+        buf.extend_from_slice(&[2, 0, 4, 0]);
+        buf.extend_from_slice(&7i32.to_le_bytes());
+        buf.extend_from_slice(&[8, 0, 8, 0]);
+        buf.extend_from_slice(&6u32.to_le_bytes());
+        buf.extend_from_slice(&5u32.to_le_bytes());
+
+        let out = rewrite_handles(&buf, |t, id| Some(id + t * 100)).unwrap();
+        assert_eq!(&out[8..12], &7i32.to_le_bytes());
+        assert_eq!(&out[16..20], &6u32.to_le_bytes());
+        assert_eq!(&out[20..24], &605u32.to_le_bytes());
+
+        assert!(rewrite_handles(&buf, |_, _| None).is_none());
+    }
+}
@@ -8,7 +8,11 @@
 mod enabled {
     use crate::bid::{BidResult, BidError};
     use crate::box_trait::{NyashBox, BoxCore, StringBox, IntegerBox};
-    use crate::config::nyash_toml_v2::{NyashConfigV2, LibraryDefinition};
+    use crate::config::nyash_toml_v2::{NyashConfigV2, LibraryDefinition, PluginIsolation, BoxTypeConfig};
+    use crate::bid::manifest::{self, PluginManifest};
+    use crate::runtime::plugin_host::ProcessPluginHost;
+    #[cfg(feature = "wasm-backend")]
+    use crate::runtime::plugin_wasm::WasmPluginModule;
     use std::collections::HashMap;
     use std::sync::{Arc, RwLock};
     // use std::ffi::c_void; // unused
@@ -17,10 +21,16 @@ mod enabled {
     use crate::runtime::leak_tracker;
     fn dbg_on() -> bool { std::env::var("NYASH_DEBUG_PLUGIN").unwrap_or_default() == "1" }
 
+    /// `path` names a wasm plugin module rather than a shared library
+    fn is_wasm_plugin_path(path: &str) -> bool {
+        let lower = path.to_ascii_lowercase();
+        lower.ends_with(".wasm") || lower.ends_with(".wat")
+    }
+
 /// Loaded plugin information
     pub struct LoadedPluginV2 {
-    /// Library handle
-    _lib: Arc<libloading::Library>,
+    /// Library handle (None when the library lives in a helper process or is a wasm module)
+    _lib: Option<Arc<libloading::Library>>,
     
     /// Box types provided by this plugin
     #[allow(dead_code)]
@@ -30,15 +40,55 @@ mod enabled {
     #[allow(dead_code)]
     init_fn: Option<unsafe extern "C" fn() -> i32>,
     
-    /// Required invoke function  
-    invoke_fn: unsafe extern "C" fn(u32, u32, u32, *const u8, usize, *mut u8, *mut usize) -> i32,
+    /// Required invoke entry (native symbol or isolated host)
+    invoker: PluginInvoker,
 }
 
+    /// Raw `nyash_plugin_invoke` symbol
+    pub type InvokeFn = unsafe extern "C" fn(u32, u32, u32, *const u8, usize, *mut u8, *mut usize) -> i32;
+
+    /// How a library's `nyash_plugin_invoke` is reached
+    #[derive(Clone)]
+    pub enum PluginInvoker {
+        /// Shared library loaded into this process
+        Native(InvokeFn),
+        /// `isolation = "process"`: calls are forwarded to a nyash-plugin-host child
+        Process(Arc<ProcessPluginHost>),
+        /// `path = "*.wasm"`: sandboxed module instantiated with wasmtime
+        #[cfg(feature = "wasm-backend")]
+        Wasm(Arc<WasmPluginModule>),
+    }
+
+    impl PluginInvoker {
+        /// Call `nyash_plugin_invoke`; `out_len` is the capacity in, written/required size out
+        pub fn invoke(&self, type_id: u32, method_id: u32, instance_id: u32, args: &[u8], out: &mut [u8], out_len: &mut usize) -> i32 {
+            match self {
+                PluginInvoker::Native(f) => unsafe {
+                    f(type_id, method_id, instance_id, args.as_ptr(), args.len(), out.as_mut_ptr(), out_len)
+                },
+                PluginInvoker::Process(host) => host.invoke(type_id, method_id, instance_id, args, out, out_len),
+                #[cfg(feature = "wasm-backend")]
+                PluginInvoker::Wasm(module) => module.invoke(type_id, method_id, instance_id, args, out, out_len),
+            }
+        }
+    }
+
+    impl std::fmt::Debug for PluginInvoker {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                PluginInvoker::Native(func) => write!(f, "Native({:p})", *func as *const ()),
+                PluginInvoker::Process(host) => write!(f, "{:?}", host),
+                #[cfg(feature = "wasm-backend")]
+                PluginInvoker::Wasm(module) => write!(f, "{:?}", module),
+            }
+        }
+    }
+
 /// v2 Plugin Box wrapper - temporary implementation
 #[derive(Debug)]
     pub struct PluginHandleInner {
         pub type_id: u32,
-        pub invoke_fn: unsafe extern "C" fn(u32, u32, u32, *const u8, usize, *mut u8, *mut usize) -> i32,
+        pub invoker: PluginInvoker,
         pub instance_id: u32,
         pub fini_method_id: Option<u32>,
         finalized: std::sync::atomic::AtomicBool,
@@ -52,17 +102,7 @@ mod enabled {
                     let tlv_args: [u8; 4] = [1, 0, 0, 0];
                     let mut out: [u8; 4] = [0; 4];
                     let mut out_len: usize = out.len();
-                    unsafe {
-                        (self.invoke_fn)(
-                            self.type_id,
-                            fini_id,
-                            self.instance_id,
-                            tlv_args.as_ptr(),
-                            tlv_args.len(),
-                            out.as_mut_ptr(),
-                            &mut out_len,
-                        );
-                    }
+                    self.invoker.invoke(self.type_id, fini_id, self.instance_id, &tlv_args, &mut out, &mut out_len);
                 }
             }
         }
@@ -77,17 +117,7 @@ mod enabled {
                     let tlv_args: [u8; 4] = [1, 0, 0, 0];
                     let mut out: [u8; 4] = [0; 4];
                     let mut out_len: usize = out.len();
-                    unsafe {
-                        (self.invoke_fn)(
-                            self.type_id,
-                            fini_id,
-                            self.instance_id,
-                            tlv_args.as_ptr(),
-                            tlv_args.len(),
-                            out.as_mut_ptr(),
-                            &mut out_len,
-                        );
-                    }
+                    self.invoker.invoke(self.type_id, fini_id, self.instance_id, &tlv_args, &mut out, &mut out_len);
                 }
             }
         }
@@ -138,17 +168,7 @@ mod enabled {
         let mut output_len = output_buffer.len();
         let tlv_args = [1u8, 0, 0, 0]; // version=1, argc=0
 
-        let result = unsafe {
-            (self.inner.invoke_fn)(
-                self.inner.type_id,
-                0,                 // method_id=0 (birth)
-                0,                 // instance_id=0 (static call)
-                tlv_args.as_ptr(),
-                tlv_args.len(),
-                output_buffer.as_mut_ptr(),
-                &mut output_len,
-            )
-        };
+        let result = self.inner.invoker.invoke(self.inner.type_id, 0, 0, &tlv_args, &mut output_buffer, &mut output_len);
 
         if result == 0 && output_len >= 4 {
             let new_instance_id = u32::from_le_bytes([
@@ -159,7 +179,7 @@ mod enabled {
                 box_type: self.box_type.clone(),
                 inner: std::sync::Arc::new(PluginHandleInner {
                     type_id: self.inner.type_id,
-                    invoke_fn: self.inner.invoke_fn,
+                    invoker: self.inner.invoker.clone(),
                     instance_id: new_instance_id,
                     fini_method_id: self.inner.fini_method_id,
                     finalized: std::sync::atomic::AtomicBool::new(false),
@@ -197,6 +217,9 @@ impl PluginBoxV2 {
 }
 
 /// Plugin loader v2
+    /// (lib_name, box_type) -> (shared handle, helper generation at birth)
+    type SingletonMap = HashMap<(String, String), (std::sync::Arc<PluginHandleInner>, u64)>;
+
     pub struct PluginLoaderV2 {
     /// Loaded plugins (library name -> plugin info)
     plugins: RwLock<HashMap<String, Arc<LoadedPluginV2>>>,
@@ -206,8 +229,8 @@ impl PluginBoxV2 {
     /// Path to the loaded nyash.toml (absolute), used for consistent re-reads
     config_path: Option<String>,
 
-    /// Singleton instances: (lib_name, box_type) -> shared handle
-    singletons: RwLock<HashMap<(String,String), std::sync::Arc<PluginHandleInner>>>,
+    /// Singleton instances, re-birthed when an isolated library's helper restarts
+    singletons: RwLock<SingletonMap>,
 }
 
     impl PluginLoaderV2 {
@@ -276,8 +299,35 @@ impl PluginBoxV2 {
         Ok(())
     }
 
+    /// Helper generation of an isolated library (0 for in-process and wasm plugins)
+    fn plugin_generation(invoker: &PluginInvoker) -> u64 {
+        match invoker {
+            PluginInvoker::Process(host) => host.generation(),
+            _ => 0,
+        }
+    }
+
+    /// Drop a cached singleton whose helper process died or was restarted since its birth
+    fn evict_stale_singleton(&self, lib_name: &str, box_type: &str) {
+        let key = (lib_name.to_string(), box_type.to_string());
+        let plugins = self.plugins.read().unwrap();
+        let Some(PluginInvoker::Process(host)) = plugins.get(lib_name).map(|p| &p.invoker) else { return };
+        let mut map = self.singletons.write().unwrap();
+        let stale = match map.get(&key) {
+            Some((_, generation)) => *generation != host.generation() || !host.is_alive(),
+            None => false,
+        };
+        if stale {
+            if let Some((inner, _)) = map.remove(&key) {
+                // The instance went down with the old process; there is nothing to fini
+                inner.finalized.store(true, std::sync::atomic::Ordering::SeqCst);
+            }
+        }
+    }
+
     /// Ensure a singleton handle is created and stored
     fn ensure_singleton_handle(&self, lib_name: &str, box_type: &str) -> BidResult<()> {
+        self.evict_stale_singleton(lib_name, box_type);
         // Fast path: already present
         if self.singletons.read().unwrap().contains_key(&(lib_name.to_string(), box_type.to_string())) {
             return Ok(());
@@ -295,20 +345,20 @@ impl PluginBoxV2 {
         let mut output_buffer = vec![0u8; 1024];
         let mut output_len = output_buffer.len();
         let tlv_args = vec![1u8, 0, 0, 0];
-        let birth_result = unsafe {
-            (plugin.invoke_fn)(type_id, 0, 0, tlv_args.as_ptr(), tlv_args.len(), output_buffer.as_mut_ptr(), &mut output_len)
-        };
+        let birth_result = plugin.invoker.invoke(type_id, 0, 0, &tlv_args, &mut output_buffer, &mut output_len);
         if birth_result != 0 || output_len < 4 { return Err(BidError::PluginError); }
         let instance_id = u32::from_le_bytes([output_buffer[0], output_buffer[1], output_buffer[2], output_buffer[3]]);
         let fini_id = box_conf.methods.get("fini").map(|m| m.method_id);
         let handle = std::sync::Arc::new(PluginHandleInner {
             type_id,
-            invoke_fn: plugin.invoke_fn,
+            invoker: plugin.invoker.clone(),
             instance_id,
             fini_method_id: fini_id,
             finalized: std::sync::atomic::AtomicBool::new(false),
         });
-        self.singletons.write().unwrap().insert((lib_name.to_string(), box_type.to_string()), handle);
+        // Read after birth: an automatic restart during birth starts a new generation
+        let generation = Self::plugin_generation(&plugin.invoker);
+        self.singletons.write().unwrap().insert((lib_name.to_string(), box_type.to_string()), (handle, generation));
         Ok(())
     }
 
@@ -328,8 +378,10 @@ impl PluginBoxV2 {
                 Ok(None)
             }
             ("env.canvas", _) => {
-                eprintln!("[env.canvas] {} invoked (stub)", method_name);
-                Ok(None)
+                crate::boxes::CanvasBox::extern_call(method_name, args).map_err(|e| {
+                    eprintln!("[env.canvas] {}", e);
+                    BidError::InvalidArgs
+                })
             }
             _ => {
                 // Future: route to plugin-defined extern interfaces via config
@@ -367,12 +419,23 @@ impl PluginBoxV2 {
             // Find plugin and type_id
             let config = self.config.as_ref().ok_or(BidError::PluginError)?;
             let (lib_name, _lib_def) = config.find_library_for_box(box_type).ok_or(BidError::InvalidType)?;
-            let plugins = self.plugins.read().unwrap();
-            let plugin = plugins.get(lib_name).ok_or(BidError::PluginError)?;
             let cfg_path = self.config_path.as_ref().map(|s| s.as_str()).unwrap_or("nyash.toml");
             let toml_content = std::fs::read_to_string(cfg_path).map_err(|_| BidError::PluginError)?;
             let toml_value: toml::Value = toml::from_str(&toml_content).map_err(|_| BidError::PluginError)?;
             let box_conf = config.get_box_config(lib_name, box_type, &toml_value).ok_or(BidError::InvalidType)?;
+            // A singleton type has one live instance; calls through a handle from before a
+            // helper restart go to the re-birthed one
+            let instance_id = if box_conf.singleton && instance_id != 0 {
+                self.ensure_singleton_handle(lib_name, box_type)?;
+                self.singletons.read().unwrap()
+                    .get(&(lib_name.to_string(), box_type.to_string()))
+                    .map(|(inner, _)| inner.instance_id)
+                    .unwrap_or(instance_id)
+            } else {
+                instance_id
+            };
+            let plugins = self.plugins.read().unwrap();
+            let plugin = plugins.get(lib_name).ok_or(BidError::PluginError)?;
             let type_id = box_conf.type_id;
             let returns_result = box_conf.methods.get(method_name).map(|m| m.returns_result).unwrap_or(false);
             eprintln!("[PluginLoaderV2] Invoke {}.{}: resolving and encoding args (argc={})", box_type, method_name, args.len());
@@ -503,17 +566,7 @@ impl PluginBoxV2 {
             }
             let mut out = vec![0u8; 1024];
             let mut out_len: usize = out.len();
-            let rc = unsafe {
-                (plugin.invoke_fn)(
-                    type_id,
-                    method_id,
-                    instance_id,
-                    tlv_args.as_ptr(),
-                    tlv_args.len(),
-                    out.as_mut_ptr(),
-                    &mut out_len,
-                )
-            };
+            let rc = plugin.invoker.invoke(type_id, method_id, instance_id, &tlv_args, &mut out, &mut out_len);
             if rc != 0 {
                 let be = BidError::from_raw(rc);
                 if dbg_on() { eprintln!("[PluginLoaderV2] invoke rc={} ({}) for {}.{}", rc, be.message(), box_type, method_name); }
@@ -565,7 +618,7 @@ impl PluginBoxV2 {
                                         box_type: ret_box.to_string(),
                                         inner: std::sync::Arc::new(PluginHandleInner {
                                             type_id: r_type,
-                                            invoke_fn: ret_plugin.invoke_fn,
+                                            invoker: ret_plugin.invoker.clone(),
                                             instance_id: r_inst,
                                             fini_method_id: fini_id,
                                             finalized: std::sync::atomic::AtomicBool::new(false),
@@ -591,13 +644,9 @@ impl PluginBoxV2 {
                     6 | 7 => { // String/Bytes
                         let s = String::from_utf8_lossy(payload).to_string();
                         if dbg_on() { eprintln!("[Plugin→VM] return str/bytes len={} (returns_result={})", size, returns_result); }
-                        if returns_result {
-                            // Heuristic: for Result-returning methods, string payload represents an error message
-                            let err = crate::exception_box::ErrorBox::new(&s);
-                            Some(Box::new(crate::boxes::result::NyashResultBox::new_err(Box::new(err))) as Box<dyn NyashBox>)
-                        } else {
-                            Some(Box::new(StringBox::new(s)) as Box<dyn NyashBox>)
-                        }
+                        let val: Box<dyn NyashBox> = Box::new(StringBox::new(s));
+                        // Errors arrive as rc != 0, so a string payload is always the Ok value
+                        if returns_result { Some(Box::new(crate::boxes::result::NyashResultBox::new_ok(val)) as Box<dyn NyashBox>) } else { Some(val) }
                     }
                     9 => {
                         if dbg_on() { eprintln!("[Plugin→VM] return void (returns_result={})", returns_result); }
@@ -619,6 +668,45 @@ impl PluginBoxV2 {
             }
         }
         
+        // *.wasm / *.wat: sandboxed module, already isolated from this process
+        if is_wasm_plugin_path(&lib_def.path) {
+            #[cfg(feature = "wasm-backend")]
+            {
+                let fuel = lib_def.fuel.unwrap_or(crate::runtime::plugin_wasm::DEFAULT_FUEL_PER_CALL);
+                let module = WasmPluginModule::load(&lib_def.path, fuel)?;
+                self.check_plugin_contract(lib_name, lib_def, module.abi_version(), module.manifest_bytes())?;
+                let plugin = Arc::new(LoadedPluginV2 {
+                    _lib: None,
+                    box_types: lib_def.boxes.clone(),
+                    init_fn: None,
+                    invoker: PluginInvoker::Wasm(Arc::new(module)),
+                });
+                self.plugins.write().unwrap().insert(lib_name.to_string(), plugin);
+                return Ok(());
+            }
+            #[cfg(not(feature = "wasm-backend"))]
+            {
+                eprintln!("[PluginLoaderV2] {} is a wasm plugin; rebuild with --features wasm-backend", lib_def.path);
+                return Err(BidError::PluginError);
+            }
+        }
+
+        // isolation = "process": the library is loaded by a helper process instead
+        if lib_def.isolation == PluginIsolation::Process {
+            let max_restarts = lib_def.max_restarts.unwrap_or(crate::runtime::plugin_host::DEFAULT_MAX_RESTARTS);
+            let host = ProcessPluginHost::spawn(&lib_def.path, None, max_restarts)?;
+            self.check_plugin_contract(lib_name, lib_def, host.abi_version(), host.manifest_bytes().map(Ok))?;
+            if dbg_on() { eprintln!("[PluginLoaderV2] {} isolated in helper process", lib_name); }
+            let plugin = Arc::new(LoadedPluginV2 {
+                _lib: None,
+                box_types: lib_def.boxes.clone(),
+                init_fn: None,
+                invoker: PluginInvoker::Process(Arc::new(host)),
+            });
+            self.plugins.write().unwrap().insert(lib_name.to_string(), plugin);
+            return Ok(());
+        }
+
         // Load library
         let lib = unsafe {
             libloading::Library::new(&lib_def.path)
@@ -630,7 +718,7 @@ impl PluginBoxV2 {
         
         // Get required invoke function and dereference it
         let invoke_fn = unsafe {
-            let symbol: libloading::Symbol<unsafe extern "C" fn(u32, u32, u32, *const u8, usize, *mut u8, *mut usize) -> i32> = 
+            let symbol: libloading::Symbol<InvokeFn> = 
                 lib.get(b"nyash_plugin_invoke")
                     .map_err(|e| {
                         eprintln!("Missing nyash_plugin_invoke: {}", e);
@@ -656,14 +744,26 @@ impl PluginBoxV2 {
         } else {
             eprintln!("[PluginLoaderV2] nyash_plugin_init not found for {} (optional)", lib_name);
         }
+
+        // Optional self-description: ABI version and manifest
+        let abi_version = unsafe {
+            lib.get::<unsafe extern "C" fn() -> u32>(crate::bid::PLUGIN_ABI_SYMBOL.as_bytes()).ok().map(|f| f())
+        };
+        let manifest_bytes = unsafe {
+            lib.get::<manifest::ManifestFn>(crate::bid::PLUGIN_MANIFEST_SYMBOL.as_bytes()).ok().map(|f| {
+                let f = *f;
+                manifest::fetch_manifest_bytes(|out, len| f(out.as_mut_ptr(), len))
+            })
+        };
+        self.check_plugin_contract(lib_name, lib_def, abi_version, manifest_bytes)?;
         
         // Store plugin with Arc-wrapped library
         let lib_arc = Arc::new(lib);
         let plugin = Arc::new(LoadedPluginV2 {
-            _lib: lib_arc,
+            _lib: Some(lib_arc),
             box_types: lib_def.boxes.clone(),
             init_fn,
-            invoke_fn,
+            invoker: PluginInvoker::Native(invoke_fn),
         });
         
         let mut plugins = self.plugins.write().unwrap();
@@ -672,6 +772,47 @@ impl PluginBoxV2 {
         Ok(())
     }
 
+    /// Validate nyash.toml against what the library reports about itself.
+    /// ABI mismatches fail with `VersionMismatch`, id/arg drift with `InvalidMethod`.
+    fn check_plugin_contract(&self, lib_name: &str, lib_def: &LibraryDefinition, abi_version: Option<u32>, manifest_bytes: Option<Result<Vec<u8>, String>>) -> BidResult<()> {
+        let Some(config) = self.config.as_ref() else { return Ok(()) };
+        let cfg_path = self.config_path.as_deref().unwrap_or("nyash.toml");
+        let Some(toml_value) = std::fs::read_to_string(cfg_path).ok().and_then(|c| toml::from_str::<toml::Value>(&c).ok()) else {
+            return Ok(());
+        };
+        let confs: Vec<(&str, Option<BoxTypeConfig>)> = lib_def.boxes.iter()
+            .map(|b| (b.as_str(), config.get_box_config(lib_name, b, &toml_value)))
+            .collect();
+        let view: Vec<(&str, Option<&BoxTypeConfig>)> = confs.iter().map(|(b, c)| (*b, c.as_ref())).collect();
+
+        let manifest = match manifest_bytes.map(|r| r.and_then(|b| PluginManifest::from_bytes(&b))) {
+            Some(Ok(m)) => Some(m),
+            Some(Err(e)) => {
+                eprintln!("[PluginLoaderV2] {}: ignoring manifest: {}", lib_name, e);
+                None
+            }
+            None => None,
+        };
+        let abi_diags = match abi_version.or(manifest.as_ref().map(|m| m.abi_version)) {
+            Some(abi) => manifest::check_abi_version(abi, &view),
+            None => Vec::new(),
+        };
+        let diags = manifest.as_ref().map(|m| m.validate(&view)).unwrap_or_default();
+        for d in abi_diags.iter().chain(diags.iter()) {
+            eprintln!("[PluginLoaderV2] {}: {}", lib_name, d);
+        }
+        if abi_diags.iter().any(|d| d.is_error()) {
+            return Err(BidError::VersionMismatch);
+        }
+        if diags.iter().any(|d| d.is_error()) {
+            return Err(BidError::InvalidMethod);
+        }
+        if dbg_on() && manifest.is_some() {
+            eprintln!("[PluginLoaderV2] {}: nyash.toml matches plugin manifest", lib_name);
+        }
+        Ok(())
+    }
+
     /// Create a Box instance
     pub fn create_box(&self, box_type: &str, _args: &[Box<dyn NyashBox>]) -> BidResult<Box<dyn NyashBox>> {
         eprintln!("🔍 create_box called for: {}", box_type);
@@ -696,7 +837,7 @@ impl PluginBoxV2 {
                     if bc.singleton {
                         // ensure created
                         let _ = self.ensure_singleton_handle(lib_name, box_type);
-                        if let Some(inner) = self.singletons.read().unwrap().get(&(lib_name.to_string(), box_type.to_string())) {
+                        if let Some((inner, _)) = self.singletons.read().unwrap().get(&(lib_name.to_string(), box_type.to_string())) {
                             let plugin_box = PluginBoxV2 { box_type: box_type.to_string(), inner: inner.clone() };
                             return Ok(Box::new(plugin_box));
                         }
@@ -747,22 +888,12 @@ impl PluginBoxV2 {
         
         // Create TLV-encoded empty arguments (version=1, argc=0)
         let tlv_args = vec![1u8, 0, 0, 0]; // version=1, argc=0
-        eprintln!("🔍 Output buffer allocated, about to call plugin invoke_fn...");
+        eprintln!("🔍 Output buffer allocated, about to call plugin invoke...");
         
-        let birth_result = unsafe {
-            eprintln!("🔍 Calling invoke_fn(type_id={}, method_id=0, instance_id=0, tlv_args={:?}, output_buf, output_size={})", type_id, tlv_args, output_buffer.len());
-            (plugin.invoke_fn)(
-                type_id,           // Box type ID
-                0,                 // method_id for birth
-                0,                 // instance_id = 0 for birth (static call)
-                tlv_args.as_ptr(), // TLV-encoded input data
-                tlv_args.len(),    // input size
-                output_buffer.as_mut_ptr(), // output buffer
-                &mut output_len,   // output buffer size (mutable)
-            )
-        };
+        eprintln!("🔍 Calling invoke(type_id={}, method_id=0, instance_id=0, tlv_args={:?}, output_buf, output_size={})", type_id, tlv_args, output_buffer.len());
+        let birth_result = plugin.invoker.invoke(type_id, 0, 0, &tlv_args, &mut output_buffer, &mut output_len);
         
-        eprintln!("🔍 invoke_fn returned with result: {}", birth_result);
+        eprintln!("🔍 invoke returned with result: {}", birth_result);
         
         if birth_result != 0 {
             eprintln!("birth() failed with code: {}", birth_result);
@@ -784,7 +915,7 @@ impl PluginBoxV2 {
             box_type: box_type.to_string(),
             inner: std::sync::Arc::new(PluginHandleInner {
                 type_id,
-                invoke_fn: plugin.invoke_fn,
+                invoker: plugin.invoker.clone(),
                 instance_id,
                 fini_method_id,
                 finalized: std::sync::atomic::AtomicBool::new(false),
@@ -796,15 +927,43 @@ impl PluginBoxV2 {
         Ok(Box::new(plugin_box))
     }
 
+    /// Restart an isolated (`isolation = "process"`) library's helper process.
+    /// Handles created before the restart answer `InvalidHandle` afterwards.
+    pub fn restart_plugin(&self, lib_name: &str) -> BidResult<()> {
+        let plugins = self.plugins.read().unwrap();
+        let plugin = plugins.get(lib_name).ok_or(BidError::PluginError)?;
+        match &plugin.invoker {
+            PluginInvoker::Process(host) => {
+                host.restart()?;
+                // Singletons of this library lived in the old process
+                self.singletons.write().unwrap().retain(|(lib, _), _| lib != lib_name);
+                Ok(())
+            }
+            _ => Err(BidError::PluginError),
+        }
+    }
+
     /// Shutdown singletons: finalize and clear all singleton handles
     pub fn shutdown_singletons(&self) {
         let mut map = self.singletons.write().unwrap();
-        for (_, handle) in map.drain() {
+        for (_, (handle, _)) in map.drain() {
             handle.finalize_now();
         }
     }
 }
 
+    impl Drop for PluginLoaderV2 {
+        fn drop(&mut self) {
+            // Fields drop in declaration order, so `plugins` (and the libraries it keeps
+            // loaded) would go before `singletons`; finalize those while the code is mapped
+            if let Ok(mut map) = self.singletons.write() {
+                for (_, (handle, _)) in map.drain() {
+                    handle.finalize_now();
+                }
+            }
+        }
+    }
+
 // Global loader instance
     static GLOBAL_LOADER_V2: Lazy<Arc<RwLock<PluginLoaderV2>>> =
         Lazy::new(|| Arc::new(RwLock::new(PluginLoaderV2::new())));
@@ -0,0 +1,250 @@
+//! WebAssembly plugin modules (`path = "....wasm"`)
+//!
+//! A `.wasm` (or `.wat`) library is instantiated with wasmtime instead of
+//! being dlopen'ed. The module gets no imports, so it can only touch its own
+//! linear memory. The exports mirror the native C ABI with pointers replaced
+//! by offsets into that memory:
+//!
+//! - `memory`
+//! - `nyash_plugin_invoke(type_id, method_id, instance_id, args_ptr, args_len, out_ptr, out_len_ptr) -> i32`
+//! - `nyash_plugin_alloc(size) -> ptr` / `nyash_plugin_free(ptr, size)` (free is optional)
+//! - `nyash_plugin_init() -> i32` (optional)
+//! - `nyash_plugin_abi() -> i32` / `nyash_plugin_manifest(out_ptr, out_len_ptr) -> i32` (optional)
+//!
+//! For each call the host allocates one guest buffer laid out as
+//! `[out_len u32][args][out]`, so `out_len_ptr` behaves like the native
+//! `*mut usize` (capacity in, written/required size out). Arguments and
+//! results stay BID-1 TLV, which keeps the nyash.toml method tables unchanged.
+//!
+//! A trap (e.g. a Rust panic inside the guest) fails the call with
+//! `BidError::PluginError` and poisons the module: guest state after a trap is
+//! not trusted, so later calls fail the same way.
+//!
+//! Every call runs on a fuel budget (`fuel = N` in the library section,
+//! `DEFAULT_FUEL_PER_CALL` otherwise, 0 = unlimited). A guest that loops
+//! forever runs out of fuel and traps instead of hanging the interpreter.
+
+use crate::bid::BidError;
+use std::sync::Mutex;
+use wasmtime::{Config, Engine, Instance, Memory, Module, Store, TypedFunc};
+
+/// Fuel per call when nyash.toml sets none (roughly one unit per wasm instruction)
+pub const DEFAULT_FUEL_PER_CALL: u64 = 1_000_000_000;
+
+type InvokeFunc = TypedFunc<(i32, i32, i32, i32, i32, i32, i32), i32>;
+
+struct WasmState {
+    store: Store<()>,
+    memory: Memory,
+    invoke: InvokeFunc,
+    alloc: TypedFunc<i32, i32>,
+    free: Option<TypedFunc<(i32, i32), ()>>,
+    manifest: Option<TypedFunc<(i32, i32), i32>>,
+    /// Budget granted at the start of each call (0 = unlimited)
+    fuel: u64,
+    poisoned: bool,
+}
+
+impl WasmState {
+    fn refuel(&mut self) {
+        let fuel = if self.fuel == 0 { u64::MAX } else { self.fuel };
+        // Only fails when fuel consumption is disabled on the engine
+        let _ = self.store.set_fuel(fuel);
+    }
+}
+
+/// An instantiated wasm plugin library; calls are serialized on one instance
+pub struct WasmPluginModule {
+    path: String,
+    abi_version: Option<u32>,
+    state: Mutex<WasmState>,
+}
+
+impl std::fmt::Debug for WasmPluginModule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "WasmPluginModule({})", self.path)
+    }
+}
+
+fn load_err(path: &str, what: impl std::fmt::Display) -> BidError {
+    eprintln!("[PluginLoaderV2] wasm plugin {}: {}", path, what);
+    BidError::PluginError
+}
+
+impl WasmPluginModule {
+    /// Compile and instantiate `path`, then run `nyash_plugin_init` if exported.
+    /// `fuel` is the per-call budget (0 = unlimited).
+    pub fn load(path: &str, fuel: u64) -> Result<Self, BidError> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|e| load_err(path, e))?;
+        let module = Module::from_file(&engine, path).map_err(|e| load_err(path, e))?;
+        let mut store = Store::new(&engine, ());
+        store.set_fuel(if fuel == 0 { u64::MAX } else { fuel }).map_err(|e| load_err(path, e))?;
+        // No imports: a plugin that needs host functions fails here
+        let instance = Instance::new(&mut store, &module, &[]).map_err(|e| load_err(path, e))?;
+
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| load_err(path, "missing exported memory"))?;
+        let invoke = instance
+            .get_typed_func::<(i32, i32, i32, i32, i32, i32, i32), i32>(&mut store, "nyash_plugin_invoke")
+            .map_err(|e| {
+                eprintln!("[PluginLoaderV2] wasm plugin {}: nyash_plugin_invoke: {}", path, e);
+                BidError::InvalidMethod
+            })?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, "nyash_plugin_alloc")
+            .map_err(|e| load_err(path, format!("nyash_plugin_alloc: {}", e)))?;
+        let free = instance.get_typed_func::<(i32, i32), ()>(&mut store, "nyash_plugin_free").ok();
+        let manifest = instance.get_typed_func::<(i32, i32), i32>(&mut store, "nyash_plugin_manifest").ok();
+        let abi_version = match instance.get_typed_func::<(), i32>(&mut store, "nyash_plugin_abi") {
+            Ok(f) => Some(f.call(&mut store, ()).map_err(|e| load_err(path, format!("nyash_plugin_abi trapped: {}", e)))? as u32),
+            Err(_) => None,
+        };
+
+        if let Ok(init) = instance.get_typed_func::<(), i32>(&mut store, "nyash_plugin_init") {
+            let rc = init.call(&mut store, ()).map_err(|e| load_err(path, format!("nyash_plugin_init trapped: {}", e)))?;
+            eprintln!("[PluginLoaderV2] nyash_plugin_init rc={} for {}", rc, path);
+            if rc != 0 {
+                return Err(BidError::PluginError);
+            }
+        }
+
+        Ok(Self {
+            path: path.to_string(),
+            abi_version,
+            state: Mutex::new(WasmState { store, memory, invoke, alloc, free, manifest, fuel, poisoned: false }),
+        })
+    }
+
+    /// `nyash_plugin_abi` of the module, if exported
+    pub fn abi_version(&self) -> Option<u32> {
+        self.abi_version
+    }
+
+    /// Raw `nyash_plugin_manifest` output, if exported
+    pub fn manifest_bytes(&self) -> Option<Result<Vec<u8>, String>> {
+        self.state.lock().ok()?.manifest.as_ref()?;
+        Some(crate::bid::manifest::fetch_manifest_bytes(|out, len| self.call_manifest(out, len)))
+    }
+
+    fn call_manifest(&self, out: &mut [u8], out_len: &mut usize) -> i32 {
+        let Ok(mut guard) = self.state.lock() else {
+            return BidError::PluginError as i32;
+        };
+        let st = &mut *guard;
+        let Some(manifest) = st.manifest.clone() else {
+            return BidError::InvalidMethod as i32;
+        };
+        if st.poisoned {
+            return BidError::PluginError as i32;
+        }
+        st.refuel();
+        let cap = (*out_len).min(out.len());
+        let Ok(total) = i32::try_from(4 + cap) else {
+            return BidError::InvalidArgs as i32;
+        };
+        let base = match st.alloc.call(&mut st.store, total) {
+            Ok(p) if p > 0 => p as u32 as usize,
+            Ok(_) => return BidError::PluginError as i32,
+            Err(e) => return self.trapped(st, "nyash_plugin_alloc", e),
+        };
+        if st.memory.write(&mut st.store, base, &(cap as u32).to_le_bytes()).is_err() {
+            self.release(st, base, total);
+            return BidError::PluginError as i32;
+        }
+        let rc = match manifest.call(&mut st.store, ((base + 4) as i32, base as i32)) {
+            Ok(rc) => rc,
+            Err(e) => return self.trapped(st, "nyash_plugin_manifest", e),
+        };
+        let rc = self.read_out(st, rc, base, base + 4, cap, out, out_len);
+        self.release(st, base, total);
+        rc
+    }
+
+    /// Same contract as the native `nyash_plugin_invoke`:
+    /// `out_len` is the capacity in, written/required size out
+    pub fn invoke(&self, type_id: u32, method_id: u32, instance_id: u32, args: &[u8], out: &mut [u8], out_len: &mut usize) -> i32 {
+        let mut guard = match self.state.lock() {
+            Ok(g) => g,
+            Err(_) => return BidError::PluginError as i32,
+        };
+        let st = &mut *guard;
+        if st.poisoned {
+            return BidError::PluginError as i32;
+        }
+        st.refuel();
+        let cap = (*out_len).min(out.len());
+        let total = 4 + args.len() + cap;
+        let Ok(total_i32) = i32::try_from(total) else {
+            return BidError::InvalidArgs as i32;
+        };
+
+        let base = match st.alloc.call(&mut st.store, total_i32) {
+            Ok(p) if p > 0 => p as u32 as usize,
+            Ok(_) => return BidError::PluginError as i32,
+            Err(e) => return self.trapped(st, "nyash_plugin_alloc", e),
+        };
+        let args_ptr = base + 4;
+        let out_ptr = args_ptr + args.len();
+        let staged = st
+            .memory
+            .write(&mut st.store, base, &(cap as u32).to_le_bytes())
+            .and_then(|_| st.memory.write(&mut st.store, args_ptr, args));
+        if staged.is_err() {
+            self.release(st, base, total_i32);
+            return BidError::PluginError as i32;
+        }
+
+        let params = (
+            type_id as i32,
+            method_id as i32,
+            instance_id as i32,
+            args_ptr as i32,
+            args.len() as i32,
+            out_ptr as i32,
+            base as i32,
+        );
+        let rc = match st.invoke.call(&mut st.store, params) {
+            Ok(rc) => rc,
+            Err(e) => return self.trapped(st, "nyash_plugin_invoke", e),
+        };
+
+        let rc = self.read_out(st, rc, base, out_ptr, cap, out, out_len);
+        self.release(st, base, total_i32);
+        rc
+    }
+
+    /// Copy `*out_len_ptr` and (on success) the result bytes back to the host
+    #[allow(clippy::too_many_arguments)]
+    fn read_out(&self, st: &mut WasmState, rc: i32, len_ptr: usize, out_ptr: usize, cap: usize, out: &mut [u8], out_len: &mut usize) -> i32 {
+        let mut len_bytes = [0u8; 4];
+        if st.memory.read(&st.store, len_ptr, &mut len_bytes).is_err() {
+            return BidError::PluginError as i32;
+        }
+        let written = u32::from_le_bytes(len_bytes) as usize;
+        if rc == 0 {
+            let n = written.min(cap);
+            if st.memory.read(&st.store, out_ptr, &mut out[..n]).is_err() {
+                return BidError::PluginError as i32;
+            }
+        }
+        *out_len = written;
+        rc
+    }
+
+    fn release(&self, st: &mut WasmState, ptr: usize, size: i32) {
+        if let Some(free) = st.free.clone() {
+            if let Err(e) = free.call(&mut st.store, (ptr as i32, size)) {
+                self.trapped(st, "nyash_plugin_free", e);
+            }
+        }
+    }
+
+    fn trapped(&self, st: &mut WasmState, func: &str, err: impl std::fmt::Display) -> i32 {
+        eprintln!("[PluginLoaderV2] wasm plugin {} trapped in {}: {}", self.path, func, err);
+        st.poisoned = true;
+        BidError::PluginError as i32
+    }
+}
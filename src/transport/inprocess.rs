@@ -8,20 +8,21 @@
  * - **MessageBus Integration**: グローバルMessageBusを使用
  * - **Zero-Copy**: プロセス内での直接参照渡し
  * - **Event-Driven**: コールバックベースの受信処理
+ * - **Mailbox**: 送信は宛先メールボックスへの投函のみ（受信側ワーカーで配送）
+ * - **Inbox**: `enable_inbox` 後はメールボックスがそのまま受信箱になり、`take` で取り出す
  * - **Thread-Safe**: 並行アクセス対応
  */
 
-use super::{Transport, IntentEnvelope, SendOpts, TransportError};
-use crate::messaging::{MessageBus, MessageBusData, BusEndpoint, SendError, IntentHandler};
+use super::{Transport, IntentEnvelope, NodeInfo, SendOpts, TransportError};
+use crate::messaging::{MessageBus, MessageBusData, BusEndpoint, BusMessage, SendError, IntentHandler, DEFAULT_MAILBOX_CAPACITY};
+use std::time::Duration;
 use crate::boxes::IntentBox;
-use std::sync::{Arc, Mutex};
 
 /// InProcessTransport - プロセス内通信実装
 pub struct InProcessTransport {
     node_id: String,
     bus: MessageBus,
     endpoint: BusEndpoint,
-    receive_callback: Arc<Mutex<Option<Box<dyn Fn(IntentEnvelope) + Send + Sync>>>>,
 }
 
 impl std::fmt::Debug for InProcessTransport {
@@ -30,7 +31,6 @@ impl std::fmt::Debug for InProcessTransport {
             .field("node_id", &self.node_id)
             .field("bus", &"MessageBus")
             .field("endpoint", &"BusEndpoint")
-            .field("receive_callback", &"<callback>")
             .finish()
     }
 }
@@ -38,8 +38,13 @@ impl std::fmt::Debug for InProcessTransport {
 impl InProcessTransport {
     /// 新しいInProcessTransportを作成
     pub fn new(node_id: String) -> Self {
+        Self::with_mailbox_capacity(node_id, DEFAULT_MAILBOX_CAPACITY)
+    }
+
+    /// メールボックス上限（メッセージ数）を指定して作成
+    pub fn with_mailbox_capacity(node_id: String, capacity: usize) -> Self {
         let bus = MessageBusData::global();
-        let endpoint = BusEndpoint::new(node_id.clone());
+        let endpoint = BusEndpoint::with_capacity(node_id.clone(), capacity);
         
         // ノードをバスに登録
         {
@@ -51,7 +56,6 @@ impl InProcessTransport {
             node_id,
             bus,
             endpoint,
-            receive_callback: Arc::new(Mutex::new(None)),
         }
     }
     
@@ -66,33 +70,39 @@ impl Transport for InProcessTransport {
         &self.node_id
     }
     
-    fn send(&self, to: &str, intent: IntentBox, _opts: SendOpts) -> Result<(), TransportError> {
-        let bus = self.bus.lock().unwrap();
-        
-        match bus.route(to, intent.clone(), &self.node_id) {
-            Ok(_) => {
-                // 受信コールバックがある場合は実行
-                if let Some(callback) = self.receive_callback.lock().unwrap().as_ref() {
-                    let envelope = IntentEnvelope {
-                        from: self.node_id.clone(),
-                        to: to.to_string(),
-                        intent,
-                        timestamp: std::time::Instant::now(),
-                    };
-                    callback(envelope);
-                }
-                Ok(())
-            }
-            Err(SendError::NodeNotFound(msg)) => Err(TransportError::NodeNotFound(msg)),
-            Err(SendError::MessageDeliveryFailed(msg)) => Err(TransportError::NetworkError(msg)),
-            Err(SendError::InvalidMessage(msg)) => Err(TransportError::SerializationError(msg)),
-            Err(SendError::BusError(msg)) => Err(TransportError::NetworkError(msg)),
-        }
+    /// 宛先メールボックスへ投函する。満杯なら `timeout_ms` まで空きを待つ
+    fn send(&self, to: &str, intent: IntentBox, opts: SendOpts) -> Result<(), TransportError> {
+        // 空き待ちの間にバス全体をロックし続けないよう、エンドポイントだけ取り出す
+        let endpoint = self.bus.lock().unwrap().endpoint(to)
+            .ok_or_else(|| TransportError::NodeNotFound(format!("Node '{}' not found", to)))?;
+        let message = BusMessage {
+            from: self.node_id.clone(),
+            intent,
+            priority: opts.priority.unwrap_or(0),
+            correlation: opts.correlation,
+            topic: opts.topic,
+        };
+        endpoint.post(message, opts.timeout_ms.map(Duration::from_millis)).map_err(transport_error)
     }
     
+    /// 自ノード宛てに配送された全メッセージを受け取る
     fn on_receive(&mut self, callback: Box<dyn Fn(IntentEnvelope) + Send + Sync>) {
-        let mut receive_callback = self.receive_callback.lock().unwrap();
-        *receive_callback = Some(callback);
+        let node_id = self.node_id.clone();
+        self.endpoint.add_receiver(Box::new(move |message| {
+            callback(envelope(&node_id, message));
+        }));
+    }
+
+    /// ワーカー付きのエンドポイントを受信箱モードのものに差し替える
+    fn enable_inbox(&mut self, capacity: usize) {
+        let inbox = BusEndpoint::inbox(self.node_id.clone(), capacity);
+        self.bus.lock().unwrap().register_node(self.node_id.clone(), inbox.clone());
+        let old = std::mem::replace(&mut self.endpoint, inbox);
+        old.close();
+    }
+
+    fn take(&self, wait: Duration) -> Option<IntentEnvelope> {
+        self.endpoint.take(wait).map(|message| envelope(&self.node_id, &message))
     }
     
     fn is_reachable(&self, node_id: &str) -> bool {
@@ -103,6 +113,39 @@ impl Transport for InProcessTransport {
     fn transport_type(&self) -> &'static str {
         "inprocess"
     }
+
+    /// MessageBus のノード情報に登録する
+    fn announce(&self, info: NodeInfo) -> Result<(), TransportError> {
+        self.bus.lock().unwrap().announce(info).map_err(transport_error)
+    }
+
+    /// 同じプロセスのバスに登録された全ノード
+    fn known_nodes(&self) -> Vec<NodeInfo> {
+        self.bus.lock().unwrap().get_node_info()
+    }
+}
+
+fn envelope(node_id: &str, message: &BusMessage) -> IntentEnvelope {
+    IntentEnvelope {
+        from: message.from.clone(),
+        to: node_id.to_string(),
+        intent: message.intent.clone(),
+        timestamp: std::time::Instant::now(),
+        priority: message.priority,
+        correlation: message.correlation.clone(),
+        topic: message.topic.clone(),
+    }
+}
+
+fn transport_error(e: SendError) -> TransportError {
+    match e {
+        SendError::NodeNotFound(msg) => TransportError::NodeNotFound(msg),
+        SendError::MessageDeliveryFailed(msg) => TransportError::NetworkError(msg),
+        SendError::InvalidMessage(msg) => TransportError::SerializationError(msg),
+        SendError::BusError(msg) => TransportError::NetworkError(msg),
+        SendError::MailboxFull(msg) => TransportError::MailboxFull(msg),
+        SendError::MessageDropped(msg) => TransportError::MessageDropped(msg),
+    }
 }
 
 impl Drop for InProcessTransport {
@@ -1,12 +1,15 @@
 /*! 🚀 Transport Module - Communication Layer Abstraction
  * 
  * This module defines the Transport trait and implementations for different
- * communication methods (InProcess, WebSocket, WebRTC, etc.)
+ * communication methods (InProcess, Tcp, WebSocket, WebRTC, etc.)
  */
 
 pub mod inprocess;
+pub mod tcp;
 
 use crate::boxes::IntentBox;
+use std::collections::BTreeSet;
+use std::time::Duration;
 
 /// Envelope containing message with metadata
 #[derive(Debug, Clone)]
@@ -15,13 +18,61 @@ pub struct IntentEnvelope {
     pub to: String,
     pub intent: IntentBox,
     pub timestamp: std::time::Instant,
+    /// Higher values are taken from the inbox first
+    pub priority: u8,
+    pub correlation: Option<Correlation>,
+    /// Set when the message was published to a topic
+    pub topic: Option<String>,
+}
+
+/// Request/response correlation carried alongside an intent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Correlation {
+    /// The sender waits for a reply tagged with this id
+    Request(String),
+    /// Reply to the request with this id
+    Reply(String),
 }
 
 /// Options for sending messages
 #[derive(Debug, Clone, Default)]
 pub struct SendOpts {
     pub timeout_ms: Option<u64>,
+    /// Higher values are delivered first (default 0)
     pub priority: Option<u8>,
+    pub correlation: Option<Correlation>,
+    pub topic: Option<String>,
+}
+
+/// What a node advertises for discovery: capability tags and topic subscriptions
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeInfo {
+    pub node_id: String,
+    pub tags: BTreeSet<String>,
+    pub topics: BTreeSet<String>,
+}
+
+impl NodeInfo {
+    pub fn new(node_id: impl Into<String>) -> Self {
+        NodeInfo { node_id: node_id.into(), ..Default::default() }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({ "node": self.node_id, "tags": self.tags, "topics": self.topics })
+    }
+
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        let strings = |key: &str| -> BTreeSet<String> {
+            value.get(key).and_then(|v| v.as_array())
+                .map(|items| items.iter().filter_map(|i| i.as_str().map(str::to_string)).collect())
+                .unwrap_or_default()
+        };
+        Some(NodeInfo {
+            node_id: value.get("node")?.as_str()?.to_string(),
+            tags: strings("tags"),
+            topics: strings("topics"),
+        })
+    }
 }
 
 /// Transport errors
@@ -31,6 +82,10 @@ pub enum TransportError {
     NetworkError(String),
     Timeout(String),
     SerializationError(String),
+    /// The receiver's mailbox stayed full (backpressure)
+    MailboxFull(String),
+    /// The message was accepted but can no longer be delivered
+    MessageDropped(String),
 }
 
 /// Abstract transport trait for different communication methods
@@ -43,12 +98,38 @@ pub trait Transport: Send + Sync + std::fmt::Debug {
     
     /// Register a callback for receiving messages
     fn on_receive(&mut self, callback: Box<dyn Fn(IntentEnvelope) + Send + Sync>);
+
+    /// Queue received messages in a bounded priority inbox instead of passing them to the
+    /// `on_receive` callback; replies still go to the callback. Senders see a full inbox as
+    /// backpressure. Call before `on_receive`.
+    fn enable_inbox(&mut self, capacity: usize);
+
+    /// Take the highest-priority message from the inbox, waiting up to `wait` for one
+    fn take(&self, wait: Duration) -> Option<IntentEnvelope>;
     
     /// Check if a node is reachable
     fn is_reachable(&self, node_id: &str) -> bool;
     
     /// Get transport type identifier
     fn transport_type(&self) -> &'static str;
+
+    /// Register the network address of a peer node (networked transports only)
+    fn add_peer(&self, node_id: &str, _address: &str) -> Result<(), TransportError> {
+        Err(TransportError::NetworkError(format!(
+            "{} transport does not take peer addresses (peer '{}')", self.transport_type(), node_id
+        )))
+    }
+
+    /// Publish this node's tags and topic subscriptions to the nodes it can reach
+    fn announce(&self, _info: NodeInfo) -> Result<(), TransportError> {
+        Err(TransportError::NetworkError(format!("{} transport does not support discovery", self.transport_type())))
+    }
+
+    /// Nodes this transport knows about (including itself once announced)
+    fn known_nodes(&self) -> Vec<NodeInfo> {
+        Vec::new()
+    }
 }
 
-pub use inprocess::InProcessTransport;
\ No newline at end of file
+pub use inprocess::InProcessTransport;
+pub use tcp::{TcpTransport, TcpTransportConfig};
\ No newline at end of file
@@ -0,0 +1,499 @@
+/*! 🌐 TcpTransport - Cross-process Communication over TCP
+ *
+ * ## 📝 概要
+ * TcpTransportは、別プロセス（別マシン）のP2PBoxノードとTCPで通信します。
+ * 相手ノードのアドレスは設定（`[p2p.tcp.nodes]`）または `add_peer` で与えます。
+ *
+ * ## 🏗️ 設計
+ * - **Framing**: 4バイト長（big-endian）+ JSON 本文
+ * - **Handshake**: 接続直後に双方が `{"hello": node_id, "info": {"node", "tags", "topics"}}` を送る
+ * - **Message**: `{"from", "to", "intent": {"name", "payload"}}` + 任意の `"request"` / `"reply"`（相関ID）・`"topic"`・`"priority"`
+ * - **Announce**: タグ・購読トピックの変更は `{"announce": {"node", "tags", "topics"}}` で接続中の相手へ
+ * - **Ordering**: 接続ごとに送信順で届く。受信箱（`enable_inbox`）からは優先度順に取り出される
+ * - **Backpressure**: 受信箱が満杯の間は受信スレッドが読むのを止めるので、相手の書き込みが詰まって送信タイムアウトになる
+ * - **Connections**: 相手ノードごとに1本を送受信で共有（どちらから張った接続でも良い）
+ * - **Reachability**: 生きている接続がある相手だけを到達可能とみなす
+ *
+ * ## ⚙️ 設定例（nyash.toml）
+ * ```toml
+ * [p2p.tcp.nodes]
+ * alice = "127.0.0.1:9101"
+ * bob = "127.0.0.1:9102"
+ * ```
+ * 自ノードのエントリが待受アドレス、それ以外が接続先になる。
+ */
+
+use super::{Correlation, Transport, IntentEnvelope, NodeInfo, SendOpts, TransportError};
+use crate::boxes::IntentBox;
+use crate::messaging::{Mailbox, MailboxError};
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// SendOpts.timeout_ms 未指定時の接続・送信タイムアウト
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+/// 受け付けた接続が hello を送るまでの猶予
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+/// 受け付けるフレームの上限
+const MAX_FRAME_BYTES: usize = 16 * 1024 * 1024;
+/// 待受スレッドが停止要求を確認する間隔
+const ACCEPT_POLL: Duration = Duration::from_millis(20);
+/// announce 時に未接続の設定済みノードへ接続を試みるタイムアウト
+const ANNOUNCE_DIAL_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// TcpTransport の設定
+#[derive(Debug, Clone, Default)]
+pub struct TcpTransportConfig {
+    /// 待受アドレス（None なら待ち受けず、自分から張った接続でのみ通信する）
+    pub listen: Option<String>,
+    /// 相手ノードID → "host:port"
+    pub peers: HashMap<String, String>,
+}
+
+impl TcpTransportConfig {
+    /// nyash.toml（`NYASH_P2P_CONFIG` で別ファイルを指定可能）の `[p2p.tcp.nodes]` を読む。
+    /// ファイルやセクションが無ければ空の設定
+    pub fn load(node_id: &str) -> Result<Self, TransportError> {
+        let path = std::env::var("NYASH_P2P_CONFIG").unwrap_or_else(|_| "nyash.toml".to_string());
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::from_toml_str(node_id, &text),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn from_toml_str(node_id: &str, text: &str) -> Result<Self, TransportError> {
+        let value: toml::Value = toml::from_str(text)
+            .map_err(|e| TransportError::SerializationError(format!("invalid p2p config: {}", e)))?;
+        let mut config = Self::default();
+        let Some(nodes) = value.get("p2p").and_then(|p| p.get("tcp")).and_then(|t| t.get("nodes")).and_then(|n| n.as_table()) else {
+            return Ok(config);
+        };
+        for (id, address) in nodes {
+            let address = address.as_str().ok_or_else(|| {
+                TransportError::SerializationError(format!("p2p.tcp.nodes.{} must be a \"host:port\" string", id))
+            })?;
+            if id == node_id {
+                config.listen = Some(address.to_string());
+            } else {
+                config.peers.insert(id.clone(), address.to_string());
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// 相手ノードとの1本の接続（書き込み側）
+struct PeerConn {
+    id: u64,
+    writer: Mutex<TcpStream>,
+}
+
+/// 受信した Intent を渡すコールバック
+type ReceiveCallback = Box<dyn Fn(IntentEnvelope) + Send + Sync>;
+
+/// 待受・受信スレッドと共有する状態
+struct Shared {
+    node_id: String,
+    peers: RwLock<HashMap<String, String>>,
+    connections: Mutex<HashMap<String, Arc<PeerConn>>>,
+    receive_callback: RwLock<Option<ReceiveCallback>>,
+    /// 応答以外の受信メッセージを溜める受信箱（`enable_inbox` 後）
+    inbox: RwLock<Option<Arc<Mailbox<IntentEnvelope>>>>,
+    /// 自ノードが公開しているタグ・購読トピック
+    local_info: RwLock<NodeInfo>,
+    /// 接続中の相手が公開したタグ・購読トピック
+    remote_info: RwLock<HashMap<String, NodeInfo>>,
+    running: AtomicBool,
+    next_conn_id: AtomicU64,
+}
+
+/// TcpTransport - TCP 通信実装
+pub struct TcpTransport {
+    shared: Arc<Shared>,
+    local_addr: Option<SocketAddr>,
+}
+
+impl std::fmt::Debug for TcpTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TcpTransport")
+            .field("node_id", &self.shared.node_id)
+            .field("local_addr", &self.local_addr)
+            .field("connections", &self.connected_peers())
+            .field("receive_callback", &"<callback>")
+            .finish()
+    }
+}
+
+fn io_error(e: io::Error) -> TransportError {
+    match e.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => TransportError::Timeout(e.to_string()),
+        _ => TransportError::NetworkError(e.to_string()),
+    }
+}
+
+fn write_frame(stream: &mut TcpStream, value: &serde_json::Value) -> io::Result<()> {
+    let body = serde_json::to_vec(value).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let mut frame = (body.len() as u32).to_be_bytes().to_vec();
+    frame.extend_from_slice(&body);
+    stream.write_all(&frame)?;
+    stream.flush()
+}
+
+fn read_frame(stream: &mut TcpStream) -> io::Result<serde_json::Value> {
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_BYTES {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "frame too large"));
+    }
+    let mut body = vec![0u8; len];
+    stream.read_exact(&mut body)?;
+    serde_json::from_slice(&body).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn hello_frame(shared: &Shared) -> serde_json::Value {
+    serde_json::json!({ "hello": shared.node_id, "info": shared.local_info.read().unwrap().to_json() })
+}
+
+/// 相手の hello を読む（ノードIDと、あれば公開情報）
+fn read_hello(stream: &mut TcpStream) -> io::Result<(String, Option<NodeInfo>)> {
+    let hello = read_frame(stream)?;
+    match hello.get("hello").and_then(|h| h.as_str()) {
+        Some(id) if !id.is_empty() => {
+            let info = hello.get("info").and_then(NodeInfo::from_json).filter(|info| info.node_id == id);
+            Ok((id.to_string(), info))
+        }
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "expected hello frame")),
+    }
+}
+
+fn read_topic(frame: &serde_json::Value) -> Option<String> {
+    frame.get("topic").and_then(|t| t.as_str()).map(str::to_string)
+}
+
+fn read_priority(frame: &serde_json::Value) -> u8 {
+    frame.get("priority").and_then(|p| p.as_u64()).map(|p| p.min(u8::MAX as u64) as u8).unwrap_or(0)
+}
+
+fn read_correlation(frame: &serde_json::Value) -> Option<Correlation> {
+    let id = |key: &str| frame.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    id("request").map(Correlation::Request).or_else(|| id("reply").map(Correlation::Reply))
+}
+
+impl TcpTransport {
+    /// 新しいTcpTransportを作成（`listen` があれば待受スレッドを起動）
+    pub fn new(node_id: String, config: TcpTransportConfig) -> Result<Self, TransportError> {
+        let shared = Arc::new(Shared {
+            local_info: RwLock::new(NodeInfo::new(node_id.clone())),
+            node_id,
+            peers: RwLock::new(config.peers),
+            connections: Mutex::new(HashMap::new()),
+            receive_callback: RwLock::new(None),
+            inbox: RwLock::new(None),
+            remote_info: RwLock::new(HashMap::new()),
+            running: AtomicBool::new(true),
+            next_conn_id: AtomicU64::new(1),
+        });
+        let mut local_addr = None;
+        if let Some(listen) = &config.listen {
+            let listener = TcpListener::bind(listen)
+                .map_err(|e| TransportError::NetworkError(format!("cannot listen on {}: {}", listen, e)))?;
+            listener.set_nonblocking(true).map_err(io_error)?;
+            local_addr = listener.local_addr().ok();
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || Self::accept_loop(listener, shared));
+        }
+        Ok(TcpTransport { shared, local_addr })
+    }
+
+    /// nyash.toml の `[p2p.tcp.nodes]` から作成
+    pub fn from_config_file(node_id: String) -> Result<Self, TransportError> {
+        let config = TcpTransportConfig::load(&node_id)?;
+        Self::new(node_id, config)
+    }
+
+    /// 実際の待受アドレス（ポート0で待ち受けた場合の確認用）
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
+    /// 現在接続中の相手ノード
+    pub fn connected_peers(&self) -> Vec<String> {
+        let mut peers: Vec<String> = self.shared.connections.lock().unwrap().keys().cloned().collect();
+        peers.sort();
+        peers
+    }
+
+    fn accept_loop(listener: TcpListener, shared: Arc<Shared>) {
+        while shared.running.load(Ordering::SeqCst) {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    let shared = Arc::clone(&shared);
+                    thread::spawn(move || {
+                        let _ = Self::accept_peer(stream, shared);
+                    });
+                }
+                Err(_) => thread::sleep(ACCEPT_POLL),
+            }
+        }
+    }
+
+    /// 受け付けた接続: hello を交換して登録し、受信ループへ
+    fn accept_peer(mut stream: TcpStream, shared: Arc<Shared>) -> io::Result<()> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+        let (peer, info) = read_hello(&mut stream)?;
+        write_frame(&mut stream, &hello_frame(&shared))?;
+        stream.set_read_timeout(None)?;
+        let conn_id = Self::register(&shared, &peer, &stream, info)?;
+        Self::reader_loop(stream, peer, conn_id, shared);
+        Ok(())
+    }
+
+    fn register(shared: &Shared, peer: &str, stream: &TcpStream, info: Option<NodeInfo>) -> io::Result<u64> {
+        let id = shared.next_conn_id.fetch_add(1, Ordering::SeqCst);
+        let info = info.unwrap_or_else(|| NodeInfo::new(peer));
+        shared.remote_info.write().unwrap().insert(peer.to_string(), info);
+        let conn = Arc::new(PeerConn { id, writer: Mutex::new(stream.try_clone()?) });
+        if let Some(old) = shared.connections.lock().unwrap().insert(peer.to_string(), conn) {
+            let _ = old.writer.lock().unwrap().shutdown(Shutdown::Both);
+        }
+        Ok(id)
+    }
+
+    /// 受信ループ: 切断されたら（より新しい接続に置き換わっていなければ）登録を外す
+    fn reader_loop(mut stream: TcpStream, peer: String, conn_id: u64, shared: Arc<Shared>) {
+        while let Ok(frame) = read_frame(&mut stream) {
+            if let Some(info) = frame.get("announce").and_then(NodeInfo::from_json) {
+                if info.node_id == peer {
+                    shared.remote_info.write().unwrap().insert(peer.clone(), info);
+                }
+                continue;
+            }
+            let Some(intent) = frame.get("intent").and_then(IntentBox::from_json) else {
+                continue;
+            };
+            let envelope = IntentEnvelope {
+                from: frame.get("from").and_then(|f| f.as_str()).unwrap_or(&peer).to_string(),
+                to: shared.node_id.clone(),
+                intent,
+                timestamp: Instant::now(),
+                priority: read_priority(&frame),
+                correlation: read_correlation(&frame),
+                topic: read_topic(&frame),
+            };
+            // 受信箱が空くまで待つ間はこの接続を読まない（相手への背圧になる）
+            if shared.receive(envelope, None).is_err() {
+                break;
+            }
+        }
+        let mut connections = shared.connections.lock().unwrap();
+        if connections.get(&peer).map(|c| c.id) == Some(conn_id) {
+            connections.remove(&peer);
+            shared.remote_info.write().unwrap().remove(&peer);
+        }
+    }
+
+    /// 設定済みアドレスへ接続し、hello を交換する
+    fn dial(&self, to: &str, timeout: Duration) -> Result<Arc<PeerConn>, TransportError> {
+        let address = self.shared.peers.read().unwrap().get(to).cloned()
+            .ok_or_else(|| TransportError::NodeNotFound(format!("Node '{}' has no known address", to)))?;
+        let addrs: Vec<SocketAddr> = address.to_socket_addrs()
+            .map_err(|e| TransportError::NetworkError(format!("cannot resolve {}: {}", address, e)))?
+            .collect();
+        let mut last_error = TransportError::NetworkError(format!("no address for {}", address));
+        let mut stream = None;
+        for addr in addrs {
+            match TcpStream::connect_timeout(&addr, timeout) {
+                Ok(s) => { stream = Some(s); break; }
+                Err(e) => last_error = io_error(e),
+            }
+        }
+        let mut stream = stream.ok_or(last_error)?;
+        stream.set_read_timeout(Some(timeout)).map_err(io_error)?;
+        stream.set_write_timeout(Some(timeout)).map_err(io_error)?;
+        write_frame(&mut stream, &hello_frame(&self.shared)).map_err(io_error)?;
+        let (peer, info) = read_hello(&mut stream).map_err(io_error)?;
+        if peer != to {
+            return Err(TransportError::NodeNotFound(format!("{} is node '{}', not '{}'", address, peer, to)));
+        }
+        stream.set_read_timeout(None).map_err(io_error)?;
+        let conn_id = Self::register(&self.shared, to, &stream, info).map_err(io_error)?;
+        let shared = Arc::clone(&self.shared);
+        let reader = stream.try_clone().map_err(io_error)?;
+        let peer_id = to.to_string();
+        thread::spawn(move || Self::reader_loop(reader, peer_id, conn_id, shared));
+        self.shared.connections.lock().unwrap().get(to).cloned()
+            .ok_or_else(|| TransportError::NetworkError(format!("connection to '{}' closed", to)))
+    }
+
+    /// 1フレーム送信。失敗した接続は閉じて登録を外す
+    fn write_to(&self, to: &str, conn: &PeerConn, message: &serde_json::Value, timeout: Duration) -> Result<(), TransportError> {
+        let mut writer = conn.writer.lock().unwrap();
+        let result = writer.set_write_timeout(Some(timeout)).and_then(|_| write_frame(&mut writer, message));
+        if let Err(e) = result {
+            let _ = writer.shutdown(Shutdown::Both);
+            drop(writer);
+            let mut connections = self.shared.connections.lock().unwrap();
+            if connections.get(to).map(|c| c.id) == Some(conn.id) {
+                connections.remove(to);
+            }
+            return Err(io_error(e));
+        }
+        Ok(())
+    }
+
+    /// 自ノード宛て: 受信箱が満杯なら `timeout_ms` まで空きを待つ
+    fn deliver_local(&self, intent: IntentBox, opts: SendOpts) -> Result<(), TransportError> {
+        let envelope = IntentEnvelope {
+            from: self.shared.node_id.clone(),
+            to: self.shared.node_id.clone(),
+            intent,
+            timestamp: Instant::now(),
+            priority: opts.priority.unwrap_or(0),
+            correlation: opts.correlation,
+            topic: opts.topic,
+        };
+        let wait = Some(opts.timeout_ms.map(Duration::from_millis));
+        self.shared.receive(envelope, wait).map_err(|e| match e {
+            MailboxError::Full => TransportError::MailboxFull(format!("Inbox of node '{}' is full", self.shared.node_id)),
+            MailboxError::Closed => TransportError::MessageDropped(format!("Node '{}' is shutting down", self.shared.node_id)),
+        })
+    }
+}
+
+impl Shared {
+    /// 応答はコールバックへ、それ以外は受信箱へ（`wait`: `None` は空くまで待つ、`Some(None)` は待たない）
+    fn receive(&self, envelope: IntentEnvelope, wait: Option<Option<Duration>>) -> Result<(), MailboxError> {
+        let inbox = self.inbox.read().unwrap().clone();
+        match inbox {
+            Some(inbox) if !matches!(envelope.correlation, Some(Correlation::Reply(_))) => {
+                let priority = envelope.priority;
+                match wait {
+                    None => inbox.push(envelope, priority),
+                    Some(wait) => inbox.post(envelope, priority, wait),
+                }
+            }
+            _ => {
+                if let Some(callback) = self.receive_callback.read().unwrap().as_ref() {
+                    callback(envelope);
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Transport for TcpTransport {
+    fn node_id(&self) -> &str {
+        &self.shared.node_id
+    }
+
+    fn send(&self, to: &str, intent: IntentBox, opts: SendOpts) -> Result<(), TransportError> {
+        if to == self.shared.node_id {
+            return self.deliver_local(intent, opts);
+        }
+        let timeout = opts.timeout_ms.filter(|ms| *ms > 0).map(Duration::from_millis).unwrap_or(DEFAULT_TIMEOUT);
+        let mut message = serde_json::json!({
+            "from": self.shared.node_id,
+            "to": to,
+            "intent": intent.to_json(),
+        });
+        match opts.correlation {
+            Some(Correlation::Request(id)) => message["request"] = id.into(),
+            Some(Correlation::Reply(id)) => message["reply"] = id.into(),
+            None => {}
+        }
+        if let Some(topic) = opts.topic {
+            message["topic"] = topic.into();
+        }
+        if let Some(priority) = opts.priority.filter(|p| *p > 0) {
+            message["priority"] = priority.into();
+        }
+        // A stale connection gets one retry over a fresh one
+        let mut stale_error = None;
+        if let Some(conn) = self.shared.connections.lock().unwrap().get(to).cloned() {
+            match self.write_to(to, &conn, &message, timeout) {
+                Ok(()) => return Ok(()),
+                Err(e) => stale_error = Some(e),
+            }
+        }
+        match self.dial(to, timeout) {
+            Ok(conn) => self.write_to(to, &conn, &message, timeout),
+            // No address to reconnect to: report the failed write rather than "not found"
+            Err(TransportError::NodeNotFound(msg)) => Err(stale_error.unwrap_or(TransportError::NodeNotFound(msg))),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn on_receive(&mut self, callback: Box<dyn Fn(IntentEnvelope) + Send + Sync>) {
+        *self.shared.receive_callback.write().unwrap() = Some(callback);
+    }
+
+    fn enable_inbox(&mut self, capacity: usize) {
+        *self.shared.inbox.write().unwrap() = Some(Arc::new(Mailbox::new(capacity)));
+    }
+
+    fn take(&self, wait: Duration) -> Option<IntentEnvelope> {
+        let inbox = self.shared.inbox.read().unwrap().clone()?;
+        inbox.take(Some(wait))
+    }
+
+    fn is_reachable(&self, node_id: &str) -> bool {
+        node_id == self.shared.node_id || self.shared.connections.lock().unwrap().contains_key(node_id)
+    }
+
+    fn transport_type(&self) -> &'static str {
+        "tcp"
+    }
+
+    fn add_peer(&self, node_id: &str, address: &str) -> Result<(), TransportError> {
+        self.shared.peers.write().unwrap().insert(node_id.to_string(), address.to_string());
+        Ok(())
+    }
+
+    /// 設定済みの相手へ接続を試み（失敗は無視）、接続中の全員へ公開情報を送る
+    fn announce(&self, info: NodeInfo) -> Result<(), TransportError> {
+        *self.shared.local_info.write().unwrap() = NodeInfo { node_id: self.shared.node_id.clone(), ..info };
+        let peers: Vec<String> = self.shared.peers.read().unwrap().keys().cloned().collect();
+        for peer in peers {
+            if !self.is_reachable(&peer) {
+                let _ = self.dial(&peer, ANNOUNCE_DIAL_TIMEOUT);
+            }
+        }
+        let message = serde_json::json!({ "announce": self.shared.local_info.read().unwrap().to_json() });
+        let connections: Vec<(String, Arc<PeerConn>)> = self.shared.connections.lock().unwrap()
+            .iter().map(|(peer, conn)| (peer.clone(), Arc::clone(conn))).collect();
+        for (peer, conn) in connections {
+            let _ = self.write_to(&peer, &conn, &message, DEFAULT_TIMEOUT);
+        }
+        Ok(())
+    }
+
+    /// 自ノードと接続中の相手（ID順）
+    fn known_nodes(&self) -> Vec<NodeInfo> {
+        let mut nodes = vec![self.shared.local_info.read().unwrap().clone()];
+        let connected = self.connected_peers();
+        let remote = self.shared.remote_info.read().unwrap();
+        nodes.extend(connected.iter().filter_map(|peer| remote.get(peer).cloned()));
+        nodes.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+        nodes
+    }
+}
+
+impl Drop for TcpTransport {
+    fn drop(&mut self) {
+        // 待受スレッドを止め、受信箱と全接続を閉じる（受信スレッドはそれで終了する）
+        self.shared.running.store(false, Ordering::SeqCst);
+        if let Some(inbox) = self.shared.inbox.read().unwrap().as_ref() {
+            inbox.close();
+        }
+        for (_, conn) in self.shared.connections.lock().unwrap().drain() {
+            let _ = conn.writer.lock().unwrap().shutdown(Shutdown::Both);
+        }
+    }
+}
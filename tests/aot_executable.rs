@@ -0,0 +1,37 @@
+#![cfg(feature = "wasm-backend")]
+//! AOT executables: runner stub + appended precompiled module, no Rust toolchain involved
+
+use nyash_rust::backend::aot::AotBackend;
+use nyash_rust::mir::MirCompiler;
+use nyash_rust::parser::NyashParser;
+use std::process::Command;
+
+#[test]
+fn compiled_executable_runs_the_program() {
+    let code = r#"
+local i = 0
+local sum = 0
+loop(i < 10) {
+    i = i + 1
+    sum = sum + i
+}
+print("sum is " + sum)
+return 7
+"#;
+    let ast = NyashParser::parse_from_string(code).expect("parse");
+    let module = MirCompiler::new().compile(ast).expect("compile").module;
+
+    let dir = std::env::temp_dir().join(format!("nyash_aot_exe_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let exe = dir.join("sum");
+
+    AotBackend::new().expect("backend")
+        .compile_to_executable_with_runner(module, &exe, Some(env!("CARGO_BIN_EXE_nyash-aot-runner")))
+        .expect("build executable");
+
+    let output = Command::new(&exe).output().expect("run executable");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(output.status.code(), Some(7), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("sum is 55"));
+}
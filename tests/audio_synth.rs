@@ -0,0 +1,277 @@
+//! Offline audio synthesis: WAV output, PCM buffers and FFT analysis for AudioBox/SoundBox
+
+use nyash_rust::box_trait::{BoolBox, IntegerBox, NyashBox, StringBox};
+use nyash_rust::boxes::synth::{encode_wav, frequency_bytes, Envelope, Synth, Waveform, FFT_SIZE, SAMPLE_RATE};
+use nyash_rust::boxes::result::NyashResultBox;
+use nyash_rust::boxes::{AudioBox, BufferBox, FloatBox, SoundBox};
+use nyash_rust::interpreter::NyashInterpreter;
+use nyash_rust::parser::NyashParser;
+
+fn n(v: f64) -> Box<dyn NyashBox> {
+    Box::new(FloatBox::new(v))
+}
+
+fn i(v: i64) -> Box<dyn NyashBox> {
+    Box::new(IntegerBox::new(v))
+}
+
+fn s(v: &str) -> Box<dyn NyashBox> {
+    Box::new(StringBox::new(v))
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes(bytes[at..at + 2].try_into().unwrap())
+}
+
+fn argmax(data: &[u8]) -> usize {
+    (0..data.len()).max_by_key(|&k| (data[k], std::cmp::Reverse(k))).unwrap()
+}
+
+#[test]
+fn wav_header_and_pcm_layout() {
+    let wav = encode_wav(&[0, 1, -1, i16::MAX, i16::MIN], 8000);
+    assert_eq!(&wav[0..4], b"RIFF");
+    assert_eq!(u32_at(&wav, 4) as usize, wav.len() - 8);
+    assert_eq!(&wav[8..16], b"WAVEfmt ");
+    assert_eq!((u32_at(&wav, 16), u16_at(&wav, 20), u16_at(&wav, 22)), (16, 1, 1));
+    assert_eq!((u32_at(&wav, 24), u32_at(&wav, 28)), (8000, 16000));
+    assert_eq!((u16_at(&wav, 32), u16_at(&wav, 34)), (2, 16));
+    assert_eq!(&wav[36..40], b"data");
+    assert_eq!(u32_at(&wav, 40), 10);
+    assert_eq!(&wav[44..], &[0, 0, 1, 0, 0xff, 0xff, 0xff, 0x7f, 0x00, 0x80]);
+
+    // 250ms of tone plus the default 5ms release tail
+    let mut synth = Synth::new();
+    synth.play_tone(440.0, 250.0);
+    assert_eq!(synth.len(), 11025 + 221);
+    let wav = synth.to_wav();
+    assert_eq!(u32_at(&wav, 24), SAMPLE_RATE);
+    assert_eq!(wav.len(), 44 + synth.len() * 2);
+    assert_eq!(&wav[44..], synth.pcm16_bytes().as_slice());
+}
+
+#[test]
+fn oscillators_envelopes_and_mixing() {
+    let mut synth = Synth::new();
+    synth.set_envelope(Envelope { attack: 0.0, decay: 0.0, sustain: 1.0, release: 0.0 });
+    // 441Hz has exactly 100 samples per period
+    synth.add_tone(Waveform::Square, 441.0, 0.0, 10.0, 0.5);
+    let out = synth.output();
+    assert_eq!(out.len(), 441);
+    assert!(out[..50].iter().all(|&v| v == 0.5) && out[50..100].iter().all(|&v| v == -0.5));
+    synth.clear();
+    synth.add_tone(Waveform::Saw, 441.0, 0.0, 10.0, 1.0);
+    let out = synth.output();
+    assert_eq!((out[0], out[50], out[75]), (-1.0, 0.0, 0.5));
+    synth.clear();
+    synth.add_tone(Waveform::Sine, 441.0, 0.0, 10.0, 1.0);
+    let out = synth.output();
+    assert!(out[0].abs() < 1e-6 && (out[25] - 1.0).abs() < 1e-6 && (out[75] + 1.0).abs() < 1e-6);
+
+    // Overlapping tones add up, then the master volume scales and clips
+    synth.clear();
+    synth.add_tone(Waveform::Square, 441.0, 0.0, 10.0, 0.75);
+    synth.add_tone(Waveform::Square, 441.0, 0.0, 10.0, 0.75);
+    assert_eq!(synth.output()[0], 1.0);
+    synth.set_volume(0.5);
+    assert_eq!(synth.output()[0], 0.75);
+    assert_eq!(synth.pcm16()[0], (0.75 * 32767.0f32).round() as i16);
+
+    // ADSR: 10ms attack, 10ms decay to 0.5, held until 50ms, 20ms release
+    let env = Envelope { attack: 0.01, decay: 0.01, sustain: 0.5, release: 0.02 };
+    let gate = 0.05;
+    assert_eq!(env.level(0.0, gate), 0.0);
+    assert!((env.level(0.005, gate) - 0.5).abs() < 1e-9);
+    assert!((env.level(0.01, gate) - 1.0).abs() < 1e-9);
+    assert!((env.level(0.015, gate) - 0.75).abs() < 1e-9);
+    assert!((env.level(0.03, gate) - 0.5).abs() < 1e-9);
+    assert!((env.level(0.06, gate) - 0.25).abs() < 1e-9);
+    assert_eq!(env.level(0.07, gate), 0.0);
+    // Released during the attack: fades from the level reached so far
+    assert!((env.level(0.005, 0.004) - 0.4 * 0.95).abs() < 1e-9);
+
+    // Square at sustain level 0.5 traces the envelope exactly
+    let mut synth = Synth::new();
+    synth.set_waveform(Waveform::Square);
+    synth.set_envelope(env);
+    synth.play_tone(441.0, 50.0);
+    let out = synth.output();
+    assert_eq!(out.len(), 441 * 7);
+    assert!((out[441 * 3] - 0.5).abs() < 1e-6);
+    assert!((out[441 * 6] - 0.25).abs() < 1e-6);
+
+    // Noise is bounded and deterministic
+    let mut a = Synth::new();
+    a.play_noise(20.0);
+    let mut b = Synth::new();
+    b.play_noise(20.0);
+    assert_eq!(a.pcm16(), b.pcm16());
+    assert!(a.output().iter().all(|v| v.abs() <= 1.0));
+    assert!(a.output().iter().any(|&v| v > 0.5) && a.output().iter().any(|&v| v < -0.5));
+}
+
+#[test]
+fn frequency_data_peaks_at_the_tone_bin() {
+    // Bin 64 of a 2048-point FFT at 44.1kHz is centred on 1378.125Hz
+    let bin = 64;
+    let frequency = bin as f64 * SAMPLE_RATE as f64 / FFT_SIZE as f64;
+    let mut synth = Synth::new();
+    synth.add_tone(Waveform::Sine, frequency, 0.0, 200.0, 0.01);
+    let data = synth.frequency_data(50.0);
+    assert_eq!(data.len(), FFT_SIZE / 2);
+    assert_eq!(argmax(&data), bin);
+    assert!(data[bin] > data[bin - 1] && data[bin] > data[bin + 1]);
+    assert!(data[bin] > 150, "{}", data[bin]);
+    assert!(data[..bin - 4].iter().chain(&data[bin + 5..]).all(|&v| v == 0));
+
+    // Two tones show two peaks; silence is all zeros
+    let mut synth = Synth::new();
+    synth.add_tone(Waveform::Sine, 100.0 * SAMPLE_RATE as f64 / FFT_SIZE as f64, 0.0, 100.0, 0.01);
+    synth.add_tone(Waveform::Sine, 300.0 * SAMPLE_RATE as f64 / FFT_SIZE as f64, 0.0, 100.0, 0.005);
+    let data = synth.frequency_data(10.0);
+    assert_eq!(argmax(&data), 100);
+    assert_eq!(argmax(&data[200..]) + 200, 300);
+    assert!(frequency_bytes(&[0.0; 64]).iter().all(|&v| v == 0));
+
+    // Time-domain bytes centre on 128
+    let data = synth.waveform_data(1000.0);
+    assert_eq!(data.len(), FFT_SIZE);
+    assert!(data.iter().all(|&v| v == 128));
+}
+
+#[test]
+fn audio_box_methods_share_one_timeline() {
+    let audio = AudioBox::new();
+    let copy = audio.clone();
+    audio.invoke("setEnvelope", &[i(0), i(0), i(1), i(0)]).unwrap();
+    audio.invoke("createTone", &[i(441), i(100)]).unwrap();
+    copy.invoke("rest", &[i(50)]).unwrap();
+    copy.invoke("createNoise", &[i(50)]).unwrap();
+    audio.invoke("addTone", &[s("square"), i(882), i(0), i(10), n(0.25)]).unwrap();
+    assert_eq!(audio.invoke("getDuration", &[]).unwrap().to_string_box().value, "200");
+    assert_eq!(audio.invoke("getSampleRate", &[]).unwrap().to_string_box().value, "44100");
+
+    let samples = audio.invoke("getSamples", &[]).unwrap();
+    let samples = samples.as_any().downcast_ref::<BufferBox>().unwrap().to_vec();
+    assert_eq!(samples.len(), 8820 * 2);
+    assert_eq!(samples, audio.synth().pcm16_bytes());
+    // The noise section starts after 150ms of tone and silence
+    assert!(samples[6615 * 2 - 200..6615 * 2].iter().all(|&b| b == 0));
+
+    let freq = audio.invoke("getFrequencyData", &[]).unwrap();
+    assert_eq!(freq.as_any().downcast_ref::<BufferBox>().unwrap().len(), 1024);
+    assert_eq!(audio.get_frequency_data().len(), 1024);
+    assert_eq!(audio.get_waveform_data().len(), 2048);
+
+    audio.invoke("setVolume", &[n(0.5)]).unwrap();
+    assert_eq!(copy.invoke("getVolume", &[]).unwrap().to_string_box().value, "0.5");
+    copy.invoke("clear", &[]).unwrap();
+    assert_eq!(audio.invoke("getDuration", &[]).unwrap().to_string_box().value, "0");
+
+    assert!(audio.invoke("setWaveform", &[s("kazoo")]).is_err());
+    assert!(audio.invoke("createTone", &[i(-1), i(10)]).is_err());
+    assert!(audio.invoke("createTone", &[i(440)]).is_err());
+    assert!(audio.invoke("sing", &[]).is_err());
+}
+
+#[test]
+fn sound_box_records_only_after_opting_in() {
+    let sound = SoundBox::new();
+    sound.beep();
+    sound.alert();
+    assert!(!sound.is_recording());
+    assert_eq!(sound.get_duration().to_string_box().value, "0");
+    assert!(sound.synth().pcm16_bytes().is_empty());
+
+    let duration = |sound: &SoundBox| sound.get_duration().to_string_box().value.parse::<f64>().unwrap();
+    sound.record();
+    sound.beep();
+    // 100ms beep plus the release tail
+    assert!((100.0..110.0).contains(&duration(&sound)), "{}", duration(&sound));
+
+    // Turning playback off starts recording on its own
+    let silent = SoundBox::new();
+    silent.set_playback(Box::new(BoolBox::new(false)));
+    silent.pattern(s(" "));
+    assert_eq!(duration(&silent), 250.0);
+}
+
+#[test]
+fn sound_box_render_without_recording_is_an_error() {
+    let path = std::env::temp_dir().join(format!("nyash_sound_empty_{}.wav", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let sound = SoundBox::new();
+    sound.beep();
+
+    let rendered = sound.render(s(&path.to_string_lossy()));
+    let rendered = rendered.as_any().downcast_ref::<NyashResultBox>().expect("ResultBox");
+    assert!(!rendered.is_ok_bool());
+    assert!(rendered.get_error().to_string_box().value.contains("record()"));
+    assert!(!path.exists(), "no WAV for an empty recording");
+    // render() must not opt in to recording as a side effect
+    assert!(!sound.is_recording());
+    sound.beep();
+    assert_eq!(sound.get_duration().to_string_box().value, "0");
+
+    sound.record();
+    sound.beep();
+    let rendered = sound.render(s(&path.to_string_lossy()));
+    assert!(rendered.as_any().downcast_ref::<NyashResultBox>().unwrap().is_ok_bool());
+    assert!(std::fs::metadata(&path).unwrap().len() > 44);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn nyash_scripts_render_identical_wav_files() {
+    let dir = std::env::temp_dir().join(format!("nyash_audio_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let run = |code: String| {
+        let ast = NyashParser::parse_from_string(&code).expect("parse");
+        let mut interpreter = NyashInterpreter::new();
+        interpreter.execute(ast).expect("execute");
+        interpreter.get_variable("result").expect("result").to_string_box().value
+    };
+    let melody = |path: &std::path::Path| format!(r#"
+local audio = new AudioBox()
+audio.setWaveform("triangle")
+audio.setEnvelope(10, 20, 0.6, 40)
+audio.createTone(523, 120)
+audio.createTone(659, 120)
+audio.createTone(784, 200)
+audio.addTone("noise", 0, 0, 30, 0.2)
+audio.setVolume(0.8)
+local saved = audio.render("{}")
+result = saved.isOk().toString() + " " + audio.getDuration().toString() + " " + audio.getSamples().length().toString()
+"#, path.display());
+    let a = dir.join("a.wav");
+    let b = dir.join("b.wav");
+    let first = run(melody(&a));
+    run(melody(&b));
+    let (wav_a, wav_b) = (std::fs::read(&a).unwrap(), std::fs::read(&b).unwrap());
+
+    // SoundBox records its effects without touching the terminal when playback is off
+    let sfx = dir.join("sfx.wav");
+    let sound = run(format!(r#"
+local sound = new SoundBox()
+sound.setPlayback(false)
+sound.beeps(2)
+sound.tone(440, 100)
+sound.pattern(". -")
+local saved = sound.render("{}")
+result = saved.isOk().toString() + " " + sound.getDuration().toString()
+"#, sfx.display()));
+    let sfx_len = std::fs::read(&sfx).unwrap().len();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    // 120 + 120 + 200ms of notes plus the 40ms release of the last one
+    assert_eq!(first, "true 480 42336");
+    assert_eq!(wav_a, wav_b);
+    assert_eq!(wav_a.len(), 44 + 42336);
+    // beeps: 100 + 100 + 100, tone: 100, pattern: 100 + 50 + 200 + 50 + 300 + 50
+    assert_eq!(sound, "true 1150");
+    assert_eq!(sfx_len, 44 + 2 * (1150 * 441 / 10));
+}
@@ -0,0 +1,173 @@
+//! Round-trip checks for `nyash bid gen` against the hand-written CounterBox plugin
+
+use nyash_rust::bid::codegen::{self, CodeGenOptions, PluginNames};
+use nyash_rust::bid::schema::BidDefinition;
+use nyash_rust::config::nyash_toml_v2::NyashConfigV2;
+use std::path::{Path, PathBuf};
+
+const COUNTER_BID: &str = "plugins/nyash-counter-plugin/counter.bid.yaml";
+const COUNTER_DIR: &str = "plugins/nyash-counter-plugin";
+
+fn generate_counter() -> Vec<codegen::GeneratedFile> {
+    let bid = BidDefinition::load_from_file(Path::new(COUNTER_BID)).expect("load counter BID");
+    let options = CodeGenOptions::new(PathBuf::from(COUNTER_DIR)).with_dry_run(true);
+    codegen::generate_plugin(&bid, "counter.bid.yaml", &options).expect("generate")
+}
+
+fn file<'a>(files: &'a [codegen::GeneratedFile], name: &str) -> &'a str {
+    &files.iter().find(|f| f.path.ends_with(name)).unwrap_or_else(|| panic!("{} not generated", name)).content
+}
+
+/// `const NAME: u32 = value;` pairs from a Rust source
+fn u32_consts(src: &str) -> Vec<(String, u32)> {
+    src.lines()
+        .filter_map(|l| {
+            let l = l.trim().strip_prefix("const ")?;
+            let (name, rest) = l.split_once(": u32 = ")?;
+            let value = rest.split(';').next()?.trim();
+            let v = if value == "u32::MAX" { u32::MAX } else { value.parse().ok()? };
+            Some((name.to_string(), v))
+        })
+        .collect()
+}
+
+#[test]
+fn bid_gen_counter_toml_matches_repo_nyash_toml() {
+    let files = generate_counter();
+    let generated = format!("[libraries]\n{}", file(&files, "nyash.toml"));
+    let gen_conf = NyashConfigV2::from_str(&generated).expect("generated toml parses");
+    let gen_raw: toml::Value = toml::from_str(&generated).unwrap();
+
+    let repo_src = std::fs::read_to_string("nyash.toml").expect("repo nyash.toml");
+    let repo_conf = NyashConfigV2::from_str(&repo_src).unwrap();
+    let repo_raw: toml::Value = toml::from_str(&repo_src).unwrap();
+
+    let (gen_lib, gen_def) = gen_conf.find_library_for_box("CounterBox").expect("generated CounterBox lib");
+    let (repo_lib, repo_def) = repo_conf.find_library_for_box("CounterBox").expect("repo CounterBox lib");
+    assert_eq!(gen_lib, repo_lib);
+    assert_eq!(gen_def.boxes, repo_def.boxes);
+    assert_eq!(gen_def.path, repo_def.path);
+
+    let gen_box = gen_conf.get_box_config(gen_lib, "CounterBox", &gen_raw).unwrap();
+    let repo_box = repo_conf.get_box_config(repo_lib, "CounterBox", &repo_raw).unwrap();
+    assert_eq!(gen_box.type_id, repo_box.type_id);
+    assert_eq!(gen_box.singleton, repo_box.singleton);
+    assert_eq!(gen_box.methods.len(), repo_box.methods.len());
+    for (name, m) in &repo_box.methods {
+        let g = gen_box.methods.get(name).unwrap_or_else(|| panic!("method {} missing", name));
+        assert_eq!(g.method_id, m.method_id, "method id of {}", name);
+        assert_eq!(g.returns_result, m.returns_result, "returns_result of {}", name);
+    }
+}
+
+#[test]
+fn bid_gen_counter_lib_rs_matches_plugin_ids() {
+    let files = generate_counter();
+    let lib_rs = file(&files, "lib.rs");
+    assert!(lib_rs.contains("pub unsafe extern \"C\" fn nyash_plugin_invoke("));
+    assert!(lib_rs.contains("pub extern \"C\" fn nyash_plugin_abi() -> u32 { 1 }"));
+
+    let plugin_src = std::fs::read_to_string(format!("{}/src/lib.rs", COUNTER_DIR)).unwrap();
+    let plugin = u32_consts(&plugin_src);
+    let generated = u32_consts(lib_rs);
+    let lookup = |consts: &[(String, u32)], name: &str| consts.iter().find(|(n, _)| n == name).map(|(_, v)| *v);
+
+    assert_eq!(lookup(&generated, "TYPE_ID_COUNTER_BOX"), lookup(&plugin, "TYPE_ID_COUNTER"));
+    assert_eq!(lookup(&generated, "METHOD_BIRTH"), lookup(&plugin, "METHOD_BIRTH"));
+    assert_eq!(lookup(&generated, "COUNTER_BOX_INC"), lookup(&plugin, "METHOD_INC"));
+    assert_eq!(lookup(&generated, "COUNTER_BOX_GET"), lookup(&plugin, "METHOD_GET"));
+    assert_eq!(lookup(&generated, "METHOD_FINI"), lookup(&plugin, "METHOD_FINI"));
+
+    // Fixed-width i32 results negotiate the buffer before touching state, like the plugin
+    assert!(lib_rs.contains("if preflight(result, result_len, 12) { return NYB_E_SHORT_BUFFER; }"));
+
+    let cargo = file(&files, "Cargo.toml");
+    assert!(cargo.contains(&format!("name = \"{}\"", PluginNames::from_bid(
+        &BidDefinition::load_from_file(Path::new(COUNTER_BID)).unwrap()).crate_name)));
+    assert!(cargo.contains("crate-type = [\"cdylib\"]"));
+}
+
+/// Two boxes exercising both birth paths, a string parameter and a string result
+#[cfg(feature = "plugins")]
+const GENCHECK_BID: &str = r#"
+version: 1
+metadata: { name: gencheck }
+interfaces:
+  - name: gencheck.counter
+    box: GenCounterBox
+    type_id: 91
+    singleton: true
+    methods:
+      - name: inc
+        returns: i32
+  - name: gencheck.note
+    box: GenNoteBox
+    type_id: 92
+    methods:
+      - name: setText
+        params: [ { string: text } ]
+      - name: text
+        returns: string
+"#;
+
+#[cfg(feature = "plugins")]
+#[test]
+fn bid_gen_skeleton_builds_and_loads() {
+    use nyash_rust::box_trait::{NyashBox, StringBox};
+    use nyash_rust::runtime::plugin_loader_v2::{PluginBoxV2, PluginLoaderV2};
+
+    let dir = std::env::temp_dir().join(format!("nyash_bid_gen_build_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let bid = BidDefinition::from_yaml_str(GENCHECK_BID).expect("parse BID");
+    let names = PluginNames::from_bid(&bid);
+    let lib = dir.join("target").join("debug").join(&names.lib_file);
+    let mut options = CodeGenOptions::new(dir.clone());
+    options.lib_path = Some(lib.to_string_lossy().replace('\\', "/"));
+    let files = codegen::generate_plugin(&bid, "gencheck.bid.yaml", &options).expect("generate");
+    codegen::write_files(&files, &options).expect("write skeleton");
+
+    // The generated crate gets its own lockfile under temp_dir(), so `--offline` resolves
+    // once_cell from the local registry cache (normally filled by building this workspace)
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let Ok(out) = std::process::Command::new(cargo)
+        .args(["build", "--offline", "--quiet"])
+        .current_dir(&dir)
+        .env("CARGO_TARGET_DIR", dir.join("target"))
+        .output()
+    else {
+        eprintln!("cargo unavailable; skipping");
+        return;
+    };
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    if !out.status.success() && stderr.contains("offline mode") {
+        eprintln!("once_cell not in the local registry cache; skipping:\n{}", stderr);
+        let _ = std::fs::remove_dir_all(&dir);
+        return;
+    }
+    assert!(out.status.success(), "generated crate does not build:\n{}", stderr);
+
+    // Load it the way nyash.toml would, so the exported manifest is checked too
+    let toml_path = dir.join("nyash.loader.toml");
+    std::fs::write(&toml_path, format!("[libraries]\n{}", file(&files, "nyash.toml"))).unwrap();
+    let mut loader = PluginLoaderV2::new();
+    loader.load_config(toml_path.to_str().unwrap()).unwrap();
+    loader.load_all_plugins().expect("load generated plugin");
+
+    let id_of = |b: &dyn NyashBox| b.as_any().downcast_ref::<PluginBoxV2>().unwrap().instance_id();
+    let c1 = loader.create_box("GenCounterBox", &[]).expect("birth GenCounterBox");
+    let c2 = loader.create_box("GenCounterBox", &[]).expect("birth GenCounterBox again");
+    assert_eq!(id_of(c1.as_ref()), id_of(c2.as_ref()), "singleton shares one instance");
+    let v = loader.invoke_instance_method("GenCounterBox", "inc", id_of(c1.as_ref()), &[]).expect("inc");
+    assert_eq!(v.map(|v| v.to_string_box().value).as_deref(), Some("0"), "stub returns the default");
+
+    let n1 = loader.create_box("GenNoteBox", &[]).expect("birth GenNoteBox");
+    let n2 = loader.create_box("GenNoteBox", &[]).expect("birth second GenNoteBox");
+    assert_ne!(id_of(n1.as_ref()), id_of(n2.as_ref()), "non-singleton boxes get their own instances");
+    let arg: Box<dyn NyashBox> = Box::new(StringBox::new("hello"));
+    loader.invoke_instance_method("GenNoteBox", "setText", id_of(n1.as_ref()), &[arg]).expect("string argument decodes");
+    let text = loader.invoke_instance_method("GenNoteBox", "text", id_of(n1.as_ref()), &[]).expect("text");
+    assert_eq!(text.map(|v| v.to_string_box().value).as_deref(), Some(""));
+
+    drop((c1, c2, n1, n2));
+    let _ = std::fs::remove_dir_all(&dir);
+}
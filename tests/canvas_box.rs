@@ -0,0 +1,229 @@
+//! Native CanvasBox: software rasterizer, PNG export and the env.canvas extern
+
+use nyash_rust::backend::VM;
+use nyash_rust::box_trait::{IntegerBox, NyashBox, StringBox};
+use nyash_rust::boxes::CanvasBox;
+use nyash_rust::interpreter::NyashInterpreter;
+use nyash_rust::mir::MirCompiler;
+use nyash_rust::parser::NyashParser;
+
+fn n(v: f64) -> Box<dyn NyashBox> {
+    Box::new(nyash_rust::boxes::FloatBox::new(v))
+}
+
+fn i(v: i64) -> Box<dyn NyashBox> {
+    Box::new(IntegerBox::new(v))
+}
+
+fn s(v: &str) -> Box<dyn NyashBox> {
+    Box::new(StringBox::new(v))
+}
+
+fn call(canvas: &CanvasBox, method: &str, args: Vec<Box<dyn NyashBox>>) -> String {
+    canvas.invoke(method, &args).unwrap_or_else(|e| panic!("{}: {}", method, e)).to_string_box().value
+}
+
+/// Renders the canvas as one character per pixel: '.' for transparent, '#' otherwise
+fn ascii(canvas: &CanvasBox) -> Vec<String> {
+    let surface = canvas.surface();
+    (0..surface.height())
+        .map(|y| (0..surface.width()).map(|x| if surface.pixel(x, y).unwrap()[3] == 0 { '.' } else { '#' }).collect())
+        .collect()
+}
+
+fn count(canvas: &CanvasBox, color: [u8; 4]) -> usize {
+    canvas.surface().pixels().chunks(4).filter(|p| *p == color).count()
+}
+
+#[test]
+fn rectangles_lines_and_paths_cover_pixel_centres() {
+    let canvas = CanvasBox::new(8, 6);
+    call(&canvas, "fillRect", vec![i(1), i(1), i(3), i(2), s("red")]);
+    call(&canvas, "strokeRect", vec![n(4.5), n(0.5), i(3), i(4), s("#00f")]);
+    assert_eq!(ascii(&canvas), [
+        "....####",
+        ".####..#",
+        ".####..#",
+        "....#..#",
+        "....####",
+        "........",
+    ]);
+    assert_eq!(call(&canvas, "getPixel", vec![i(2), i(2)]), "#ff0000");
+    assert_eq!(call(&canvas, "getPixel", vec![i(5), i(0)]), "#0000ff");
+    assert_eq!(call(&canvas, "getPixel", vec![i(0), i(0)]), "#00000000");
+
+    // A path triangle with the fill style, then clearRect punches a hole
+    let canvas = CanvasBox::new(6, 6);
+    call(&canvas, "setFillStyle", vec![s("rgb(0, 255, 0)")]);
+    call(&canvas, "beginPath", vec![]);
+    call(&canvas, "moveTo", vec![i(0), i(0)]);
+    call(&canvas, "lineTo", vec![i(6), i(0)]);
+    call(&canvas, "lineTo", vec![i(0), i(6)]);
+    call(&canvas, "closePath", vec![]);
+    call(&canvas, "fill", vec![]);
+    call(&canvas, "clearRect", vec![i(0), i(0), i(1), i(1)]);
+    assert_eq!(ascii(&canvas), [".#####", "#####.", "####..", "###...", "##....", "#....."]);
+
+    // Thick lines use butt caps
+    let canvas = CanvasBox::new(10, 5);
+    call(&canvas, "drawLine", vec![i(2), n(2.5), i(8), n(2.5), s("black"), i(3)]);
+    assert_eq!(ascii(&canvas), ["..........", "..######..", "..######..", "..######..", ".........."]);
+}
+
+#[test]
+fn circles_arcs_and_transforms() {
+    let canvas = CanvasBox::new(40, 40);
+    call(&canvas, "fillCircle", vec![i(20), i(20), n(15.2), s("black")]);
+    let area = count(&canvas, [0, 0, 0, 255]) as f64;
+    assert!((area - std::f64::consts::PI * 15.2 * 15.2).abs() < 25.0, "{}", area);
+    // Symmetric under reflection
+    let rows = ascii(&canvas);
+    for (y, row) in rows.iter().enumerate() {
+        assert_eq!(row.chars().rev().collect::<String>(), *row);
+        assert_eq!(rows[39 - y], *row);
+    }
+
+    // A ring leaves the centre empty
+    let canvas = CanvasBox::new(41, 41);
+    call(&canvas, "strokeCircle", vec![n(20.5), n(20.5), i(15), s("black"), i(2)]);
+    assert_eq!(call(&canvas, "getPixel", vec![i(20), i(20)]), "#00000000");
+    assert_eq!(call(&canvas, "getPixel", vec![i(35), i(20)]), "#000000");
+
+    // Half circle via arc: only the lower half is filled
+    let canvas = CanvasBox::new(20, 20);
+    call(&canvas, "beginPath", vec![]);
+    call(&canvas, "arc", vec![i(10), i(10), i(8), i(0), n(std::f64::consts::PI)]);
+    call(&canvas, "fill", vec![s("black")]);
+    let rows = ascii(&canvas);
+    assert!(rows[..10].iter().all(|r| !r.contains('#')));
+    assert!(rows[12].contains('#'));
+
+    // translate + rotate by 90 degrees maps the x axis onto the y axis
+    let canvas = CanvasBox::new(10, 10);
+    call(&canvas, "save", vec![]);
+    call(&canvas, "translate", vec![i(5), i(0)]);
+    call(&canvas, "rotate", vec![n(std::f64::consts::FRAC_PI_2)]);
+    call(&canvas, "fillRect", vec![i(0), i(0), i(4), i(2), s("black")]);
+    call(&canvas, "restore", vec![]);
+    call(&canvas, "fillRect", vec![i(9), i(9), i(1), i(1), s("black")]);
+    assert_eq!(&ascii(&canvas)[..5], ["...##.....", "...##.....", "...##.....", "...##.....", ".........."]);
+    assert_eq!(call(&canvas, "getPixel", vec![i(9), i(9)]), "#000000");
+}
+
+#[test]
+fn text_images_and_blending() {
+    let canvas = CanvasBox::new(12, 9);
+    call(&canvas, "fillText", vec![s("IT"), i(0), i(8), s("8px monospace"), s("black")]);
+    assert_eq!(ascii(&canvas), [
+        "............",
+        ".###..#####.",
+        "..#.....#...",
+        "..#.....#...",
+        "..#.....#...",
+        "..#.....#...",
+        "..#.....#...",
+        ".###....#...",
+        "............",
+    ]);
+    assert_eq!(call(&canvas, "measureText", vec![s("Hello"), s("bold 16px sans-serif")]), "60");
+
+    // drawImage scales with nearest-neighbour sampling
+    let sprite = CanvasBox::new(2, 1);
+    call(&sprite, "fillRect", vec![i(0), i(0), i(1), i(1), s("red")]);
+    let canvas = CanvasBox::new(4, 2);
+    canvas.invoke("drawImage", &[Box::new(sprite.clone()) as Box<dyn NyashBox>, i(0), i(0), i(4), i(2)]).unwrap();
+    assert_eq!(ascii(&canvas), ["##..", "##.."]);
+
+    // Semi-transparent colours composite source-over
+    let canvas = CanvasBox::new(1, 1);
+    call(&canvas, "fillRect", vec![i(0), i(0), i(1), i(1), s("white")]);
+    call(&canvas, "fillRect", vec![i(0), i(0), i(1), i(1), s("rgba(0, 0, 255, 0.5)")]);
+    assert_eq!(call(&canvas, "getPixel", vec![i(0), i(0)]), "#7f7fff");
+    call(&canvas, "clear", vec![]);
+    call(&canvas, "fillRect", vec![i(0), i(0), i(1), i(1), s("#ff000080")]);
+    assert_eq!(call(&canvas, "getPixel", vec![i(0), i(0)]), "#ff000080");
+
+    assert!(canvas.invoke("fillRect", &[i(0), i(0), i(1), i(1), s("no-such-colour")]).is_err());
+    assert!(canvas.invoke("fillRect", &[i(0), i(0)]).is_err());
+    assert!(canvas.invoke("warp", &[]).is_err());
+}
+
+#[test]
+fn png_export_round_trips_pixels() {
+    let canvas = CanvasBox::new(33, 17);
+    call(&canvas, "fillRect", vec![i(0), i(0), i(33), i(17), s("navy")]);
+    call(&canvas, "fillCircle", vec![i(16), i(8), i(6), s("rgba(255, 255, 0, 0.75)")]);
+    call(&canvas, "fillText", vec![s("ok"), i(1), i(15), s("8px"), s("white")]);
+
+    let png = canvas.to_png();
+    let mut decoder = png::Decoder::new(std::io::Cursor::new(&png));
+    decoder.set_transformations(png::Transformations::IDENTITY);
+    let mut reader = decoder.read_info().unwrap();
+    let mut rgba = vec![0; reader.output_buffer_size().unwrap()];
+    let info = reader.next_frame(&mut rgba).unwrap();
+    assert_eq!((info.width, info.height, info.color_type), (33, 17, png::ColorType::Rgba));
+    assert_eq!(rgba, canvas.surface().pixels());
+}
+
+#[test]
+fn nyash_script_renders_deterministic_png() {
+    let dir = std::env::temp_dir().join(format!("nyash_canvas_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let render = |name: &str| {
+        let path = dir.join(name);
+        let code = format!(r#"
+local canvas = new CanvasBox(64, 48)
+canvas.fillRect(0, 0, 64, 48, "black")
+canvas.setStrokeStyle("lime")
+canvas.setLineWidth(2)
+canvas.strokeRect(4, 4, 56, 40)
+canvas.fillCircle(32, 24, 10, "red")
+canvas.fillText("Nyash", 14, 44, "8px monospace", "white")
+local saved = canvas.saveTo("{}")
+result = saved.isOk().toString() + " " + canvas.getWidth().toString() + "x" + canvas.getHeight().toString() + " " + canvas.getPixel(32, 24)
+"#, path.display());
+        let ast = NyashParser::parse_from_string(&code).expect("parse");
+        let mut interpreter = NyashInterpreter::new();
+        interpreter.execute(ast).expect("execute");
+        let result = interpreter.get_variable("result").expect("result").to_string_box().value;
+        (result, std::fs::read(&path).unwrap())
+    };
+    let (first, png_a) = render("a.png");
+    let (_, png_b) = render("b.png");
+    let _ = std::fs::remove_dir_all(&dir);
+    assert_eq!(first, "true 64x48 #ff0000");
+    assert_eq!(png_a, png_b);
+}
+
+#[test]
+fn env_canvas_extern_draws_on_registered_canvas() {
+    let code = r##"
+canvas.resize("vm-canvas", 40, 30)
+canvas.fillRect("vm-canvas", 2, 3, 10, 5, "red")
+canvas.fillText("vm-canvas", "Hi", 20, 20, "8px monospace", "#00ff00")
+return canvas.getPixel("vm-canvas", 5, 5)
+"##;
+    let ast = NyashParser::parse_from_string(code).expect("parse");
+    let module = MirCompiler::new().compile(ast).expect("mir").module;
+    let result = VM::new().execute_module(&module).expect("vm");
+    assert_eq!(result.to_string_box().value, "#ff0000");
+
+    // The interpreter sees the same canvas by id
+    let canvas = CanvasBox::by_id("vm-canvas");
+    {
+        let surface = canvas.surface();
+        assert_eq!((surface.width(), surface.height()), (40, 30));
+        assert_eq!(surface.pixel(20, 14), Some([0, 255, 0, 255]));
+    }
+    assert_eq!(count(&canvas, [255, 0, 0, 255]), 50);
+
+    // A local named `canvas` is an ordinary box, not the extern
+    let code = r#"
+local canvas = new CanvasBox(3, 3)
+canvas.fillRect(0, 0, 2, 2, "blue")
+return canvas.getPixel(1, 1)
+"#;
+    let module = MirCompiler::new().compile(NyashParser::parse_from_string(code).unwrap()).expect("mir").module;
+    let dump = format!("{:?}", module);
+    assert!(dump.contains("getPixel") && !dump.contains("env.canvas"), "{}", dump);
+}
@@ -0,0 +1,172 @@
+#![cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
+
+use nyash_rust::parser::NyashParser;
+use nyash_rust::runtime::plugin_loader_v2::{init_global_loader_v2, get_global_loader_v2};
+use nyash_rust::runtime::box_registry::get_global_registry;
+use nyash_rust::runtime::PluginConfig;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+fn try_init_plugins() -> bool {
+    if !std::path::Path::new("nyash.toml").exists() { return false; }
+    if let Err(e) = init_global_loader_v2("nyash.toml") { eprintln!("init failed: {:?}", e); return false; }
+    let loader = get_global_loader_v2();
+    let loader = loader.read().unwrap();
+    if let Some(conf) = &loader.config {
+        let mut map = std::collections::HashMap::new();
+        for (lib, def) in &conf.libraries { for b in &def.boxes { map.insert(b.clone(), lib.clone()); } }
+        get_global_registry().apply_plugin_config(&PluginConfig { plugins: map });
+        true
+    } else { false }
+}
+
+fn connect(port: u16) -> TcpStream {
+    for _ in 0..200 {
+        if let Ok(s) = TcpStream::connect(("127.0.0.1", port)) {
+            s.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+            return s;
+        }
+        std::thread::sleep(Duration::from_millis(25));
+    }
+    panic!("server on port {} did not come up", port);
+}
+
+/// Reads one response head; returns (head text, lowercased header lookup)
+fn read_head(r: &mut BufReader<TcpStream>) -> (String, Vec<(String, String)>) {
+    let mut head = String::new();
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        r.read_line(&mut line).unwrap();
+        head.push_str(&line);
+        let line = line.trim_end();
+        if line.is_empty() { return (head, headers); }
+        if let Some((k, v)) = line.split_once(':') { headers.push((k.trim().to_lowercase(), v.trim().to_string())); }
+    }
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+}
+
+#[test]
+fn e2e_http_keep_alive_chunked_and_streaming() {
+    std::env::set_var("NYASH_NET_LOG", "1");
+    std::env::set_var("NYASH_NET_LOG_FILE", "net_plugin_stream.log");
+    if !try_init_plugins() { return; }
+
+    // Raw client: two requests on one TCP connection, the second with a chunked upload
+    let client = std::thread::spawn(|| {
+        let stream = connect(8110);
+        let mut w = stream.try_clone().unwrap();
+        let mut r = BufReader::new(stream);
+
+        w.write_all(b"GET /first HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let (head1, h1) = read_head(&mut r);
+        let len: usize = header(&h1, "content-length").unwrap().parse().unwrap();
+        let mut body1 = vec![0u8; len];
+        r.read_exact(&mut body1).unwrap();
+
+        w.write_all(b"POST /upload HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6;x=y\r\n world\r\n0\r\n\r\n").unwrap();
+        let (head2, h2) = read_head(&mut r);
+        // Chunked response: read raw framing up to the terminating chunk
+        let mut raw = String::new();
+        while !raw.ends_with("0\r\n\r\n") {
+            let mut line = String::new();
+            if r.read_line(&mut line).unwrap() == 0 { break; }
+            raw.push_str(&line);
+        }
+        // Connection stays usable until the server closes it after `Connection: close`
+        w.write_all(b"GET /last HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").unwrap();
+        let mut rest = String::new();
+        r.read_to_string(&mut rest).unwrap();
+        (head1, String::from_utf8(body1).unwrap(), head2, h2, raw, rest)
+    });
+
+    let code = r#"
+local srv, req, resp, got, part
+srv = new HttpServerBox()
+srv.start(8110)
+
+req = srv.accept().get_value()
+resp = new HttpResponseBox()
+resp.write("first:" + req.path())
+req.respond(resp)
+
+req = srv.accept().get_value()
+got = ""
+part = req.readChunk()
+loop(part.length() > 0) {
+    got = got + part
+    part = req.readChunk()
+}
+resp = new HttpResponseBox()
+resp.setHeader("Content-Type", "text/event-stream")
+resp.writeChunk("got=" + got)
+req.respond(resp)
+resp.writeChunk("|more")
+resp.end()
+
+req = srv.accept().get_value()
+resp = new HttpResponseBox()
+resp.write("bye")
+req.respond(resp)
+srv.stop()
+got
+"#;
+    let ast = NyashParser::parse_from_string(code).expect("parse failed");
+    let mut interpreter = nyash_rust::interpreter::NyashInterpreter::new();
+    let result = interpreter.execute(ast).expect("exec failed");
+    assert_eq!(result.to_string_box().value, "hello world");
+
+    let (head1, body1, head2, h2, raw, rest) = client.join().unwrap();
+    assert!(head1.starts_with("HTTP/1.1 200 OK\r\n"), "{}", head1);
+    assert!(head1.contains("Connection: keep-alive\r\n"), "{}", head1);
+    assert_eq!(body1, "first:/first");
+
+    assert_eq!(header(&h2, "transfer-encoding"), Some("chunked"), "{}", head2);
+    assert_eq!(header(&h2, "content-type"), Some("text/event-stream"));
+    assert!(header(&h2, "content-length").is_none());
+    assert_eq!(raw, "f\r\ngot=hello world\r\n5\r\n|more\r\n0\r\n\r\n");
+
+    assert!(rest.starts_with("HTTP/1.1 200 OK\r\n"), "{}", rest);
+    assert!(rest.contains("Connection: close\r\n"));
+    assert!(rest.ends_with("\r\n\r\nbye"));
+}
+
+#[test]
+fn e2e_http_client_reads_chunked_and_reuses_connection() {
+    std::env::set_var("NYASH_NET_LOG", "1");
+    std::env::set_var("NYASH_NET_LOG_FILE", "net_plugin_stream.log");
+    if !try_init_plugins() { return; }
+
+    let code = r#"
+local srv, cli, r1, r2, req, resp, a, b
+srv = new HttpServerBox()
+srv.start(8111)
+cli = new HttpClientBox()
+
+r1 = cli.get("http://127.0.0.1:8111/one")
+req = srv.accept().get_value()
+resp = new HttpResponseBox()
+resp.writeChunk("chunk-")
+resp.writeChunk("body")
+resp.end()
+req.respond(resp)
+a = r1.get_value().readBody()
+
+r2 = cli.post("http://127.0.0.1:8111/two", "payload")
+req = srv.accept().get_value()
+resp = new HttpResponseBox()
+resp.write(req.path() + ":" + req.readBody())
+req.respond(resp)
+b = r2.get_value().readBody()
+srv.stop()
+a + "|" + b
+"#;
+    let ast = NyashParser::parse_from_string(code).expect("parse failed");
+    let mut interpreter = nyash_rust::interpreter::NyashInterpreter::new();
+    let result = interpreter.execute(ast).expect("exec failed");
+    assert_eq!(result.to_string_box().value, "chunk-body|/two:payload");
+}
@@ -0,0 +1,289 @@
+//! HttpClientBox: real HTTP/1.1 requests against local HTTPServerBox / SocketBox servers
+
+use nyash_rust::box_trait::{IntegerBox, NyashBox, StringBox};
+use nyash_rust::boxes::result::NyashResultBox;
+use nyash_rust::boxes::{HTTPResponseBox, HttpClientBox, MapBox, SocketBox};
+use nyash_rust::interpreter::NyashInterpreter;
+use nyash_rust::parser::NyashParser;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
+
+fn s(v: &str) -> Box<dyn NyashBox> {
+    Box::new(StringBox::new(v))
+}
+
+fn wait_for(port: u16) {
+    for _ in 0..100 {
+        if TcpStream::connect(("127.0.0.1", port)).is_ok() {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    panic!("server on port {} did not come up", port);
+}
+
+/// HTTPServerBox with Nyash handlers: echo, redirects and a redirect loop
+fn spawn_http_server(port: u16) {
+    let code = format!(r#"
+box Api {{
+    init {{ dummy }}
+    echo(req) {{
+        return req.getMethod() + " " + req.getPath() + " token=" + req.getHeader("x-token") + " body=" + req.getBody()
+    }}
+    hello(req) {{
+        local r = new HTTPResponseBox()
+        r.setStatus(200, "OK")
+        r.setHeader("X-Server", "nyash")
+        r.setBody("hello")
+        return r
+    }}
+    moved(req) {{
+        local r = new HTTPResponseBox()
+        r.setStatus(302, "Found")
+        r.setHeader("Location", "/hello")
+        return r
+    }}
+    seeOther(req) {{
+        local r = new HTTPResponseBox()
+        r.setStatus(303, "See Other")
+        r.setHeader("Location", "echo")
+        return r
+    }}
+    cycle(req) {{
+        local r = new HTTPResponseBox()
+        r.setStatus(302, "Found")
+        r.setHeader("Location", "/loop")
+        return r
+    }}
+}}
+
+static box Main {{
+    main() {{
+        local server = new HTTPServerBox()
+        server.bind("127.0.0.1", {port})
+        local api = new Api()
+        server.route("/echo", new MethodBox(api, "echo"))
+        server.get("/hello", new MethodBox(api, "hello"))
+        server.get("/moved", new MethodBox(api, "moved"))
+        server.post("/submit", new MethodBox(api, "seeOther"))
+        server.get("/loop", new MethodBox(api, "cycle"))
+        server.start()
+        return 0
+    }}
+}}
+"#);
+    std::thread::spawn(move || {
+        let ast = NyashParser::parse_from_string(&code).expect("parse");
+        let mut interpreter = NyashInterpreter::new();
+        let _ = interpreter.execute(ast);
+    });
+    wait_for(port);
+}
+
+/// SocketBox server answering each connection with one raw response (None: never answer)
+fn spawn_raw_server(port: u16, raw: Option<&'static str>) {
+    let server = SocketBox::new();
+    server.bind(s("127.0.0.1"), Box::new(IntegerBox::new(port as i64)));
+    std::thread::spawn(move || loop {
+        let client = server.accept();
+        let Some(client) = client.as_any().downcast_ref::<SocketBox>() else { continue };
+        client.read_http_request();
+        match raw {
+            Some(raw) => {
+                client.write(s(raw));
+                client.close();
+            }
+            None => std::thread::sleep(Duration::from_secs(5)),
+        }
+    });
+    wait_for(port);
+}
+
+/// Answers each request with its own head (request line and headers) as the body
+fn spawn_head_echo_server(port: u16) {
+    let listener = TcpListener::bind(("127.0.0.1", port)).unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut head = String::new();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                    break;
+                }
+                head.push_str(&line);
+            }
+            let mut stream = stream;
+            let _ = write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}", head.len(), head);
+        }
+    });
+    wait_for(port);
+}
+
+fn ok_response(result: Box<dyn NyashBox>) -> HTTPResponseBox {
+    match result.as_any().downcast_ref::<NyashResultBox>() {
+        Some(NyashResultBox::Ok(value)) => value.as_any().downcast_ref::<HTTPResponseBox>().expect("HTTPResponseBox").clone(),
+        other => panic!("expected Ok(HTTPResponseBox), got {:?}", other),
+    }
+}
+
+fn err_message(result: Box<dyn NyashBox>) -> String {
+    match result.as_any().downcast_ref::<NyashResultBox>() {
+        Some(NyashResultBox::Err(e)) => e.to_string_box().value,
+        other => panic!("expected Err, got {:?}", other),
+    }
+}
+
+#[test]
+fn http_client_talks_to_local_servers() {
+    let port = free_port();
+    spawn_http_server(port);
+    let base = format!("http://127.0.0.1:{}", port);
+    let client = HttpClientBox::new();
+
+    // Status, headers and body on the response box
+    let r = ok_response(client.http_get(s(&format!("{}/hello", base))));
+    assert_eq!(r.get_status().to_string_box().value, "200");
+    assert_eq!(r.get_header(s("x-server")).to_string_box().value, "nyash");
+    assert_eq!(r.get_body().to_string_box().value, "hello");
+
+    // Request bodies and methods
+    let r = ok_response(client.post(s(&format!("{}/echo", base)), s("a=1")));
+    assert_eq!(r.get_body().to_string_box().value, "POST /echo token= body=a=1");
+    let r = ok_response(client.put(s(&format!("{}/echo", base)), s("x")));
+    assert_eq!(r.get_body().to_string_box().value, "PUT /echo token= body=x");
+    let r = ok_response(client.delete(s(&format!("{}/echo", base))));
+    assert_eq!(r.get_body().to_string_box().value, "DELETE /echo token= body=");
+
+    // request() with custom headers and body
+    let headers = MapBox::new();
+    headers.set(s("X-Token"), s("secret"));
+    let options = MapBox::new();
+    options.set(s("headers"), Box::new(headers));
+    options.set(s("body"), s("payload"));
+    let r = ok_response(client.request(s("patch"), s(&format!("{}/echo", base)), Box::new(options)));
+    assert_eq!(r.get_body().to_string_box().value, "PATCH /echo token=secret body=payload");
+
+    // Redirects: 302 keeps GET, 303 after POST becomes a GET on a relative Location
+    let r = ok_response(client.http_get(s(&format!("{}/moved", base))));
+    assert_eq!(r.get_body().to_string_box().value, "hello");
+    let r = ok_response(client.post(s(&format!("{}/submit", base)), s("form")));
+    assert_eq!(r.get_body().to_string_box().value, "GET /echo token= body=");
+    let options = MapBox::new();
+    options.set(s("followRedirects"), Box::new(nyash_rust::box_trait::BoolBox::new(false)));
+    let r = ok_response(client.request(s("GET"), s(&format!("{}/moved", base)), Box::new(options)));
+    assert_eq!(r.get_status().to_string_box().value, "302");
+    assert_eq!(r.get_header(s("Location")).to_string_box().value, "/hello");
+    assert!(err_message(client.http_get(s(&format!("{}/loop", base)))).contains("too many redirects"));
+
+    // Unmatched route is still a response, not an error
+    let r = ok_response(client.http_get(s(&format!("{}/nope", base))));
+    assert_eq!(r.get_status().to_string_box().value, "404");
+}
+
+#[test]
+fn http_client_decodes_chunked_bodies() {
+    let port = free_port();
+    spawn_raw_server(port, Some(
+        "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nX-Kind: chunked\r\n\r\n5\r\nhello\r\n7;ext=1\r\n, world\r\n0\r\nX-Trailer: t\r\n\r\n",
+    ));
+    let r = ok_response(HttpClientBox::new().http_get(s(&format!("http://127.0.0.1:{}/", port))));
+    assert_eq!(r.get_body().to_string_box().value, "hello, world");
+    assert_eq!(r.get_header(s("X-Kind")).to_string_box().value, "chunked");
+
+    let port = free_port();
+    spawn_raw_server(port, Some("HTTP/1.0 200 OK\r\nContent-Type: text/plain\r\n\r\nuntil close"));
+    let r = ok_response(HttpClientBox::new().http_get(s(&format!("http://127.0.0.1:{}/", port))));
+    assert_eq!(r.get_body().to_string_box().value, "until close");
+}
+
+#[test]
+fn http_client_reports_errors_as_result_err() {
+    let client = HttpClientBox::new();
+    assert!(err_message(client.http_get(s("https://example.com/"))).contains("only http://"));
+    assert!(err_message(client.http_get(s("not a url"))).contains("invalid URL"));
+
+    // Nothing listening
+    let port = free_port();
+    assert!(err_message(client.http_get(s(&format!("http://127.0.0.1:{}/", port)))).contains("connect"));
+
+    // Server accepts but never answers
+    let port = free_port();
+    spawn_raw_server(port, None);
+    client.set_timeout(Box::new(IntegerBox::new(200)));
+    assert!(err_message(client.http_get(s(&format!("http://127.0.0.1:{}/", port)))).contains("timed out"));
+}
+
+#[test]
+fn http_client_from_nyash_returns_result_box() {
+    let port = free_port();
+    spawn_raw_server(port, Some("HTTP/1.1 201 Created\r\nContent-Length: 2\r\n\r\nok"));
+    let code = format!(r#"
+local client = new HTTPClientBox()
+local res = client.get("http://127.0.0.1:{port}/")
+local bad = client.get("ftp://nowhere/")
+local resp = res.getValue()
+result = res.isOk().toString() + " " + resp.getStatus() + " " + resp.getStatusMessage() + " " + resp.getBody() + " " + bad.isOk().toString()
+"#);
+    let ast = NyashParser::parse_from_string(&code).expect("parse");
+    let mut interpreter = NyashInterpreter::new();
+    interpreter.execute(ast).expect("execute");
+    let result = interpreter.get_variable("result").expect("result");
+    assert_eq!(result.to_string_box().value, "true 201 Created ok false");
+}
+
+#[test]
+fn http_client_rejects_line_breaks_in_the_request_head() {
+    let port = free_port();
+    spawn_head_echo_server(port);
+    let base = format!("http://127.0.0.1:{}", port);
+    let client = HttpClientBox::new();
+    let with_header = |name: &str, value: &str| {
+        let headers = MapBox::new();
+        headers.set(s(name), s(value));
+        let options = MapBox::new();
+        options.set(s("headers"), Box::new(headers));
+        client.request(s("GET"), s(&format!("{}/", base)), Box::new(options))
+    };
+
+    assert!(err_message(with_header("X-A", "v\r\nX-Injected: 1")).contains("invalid value for header X-A"));
+    assert!(err_message(with_header("X-A\r\nX-Injected", "1")).contains("invalid header name"));
+    assert!(err_message(client.request(s("GET / HTTP/1.1\r\nX-Injected: 1\r\n\r\nGET"), s(&base), Box::new(MapBox::new())))
+        .contains("invalid method"));
+    assert!(err_message(client.http_get(s(&format!("{}/a\r\nX-Injected: 1", base)))).contains("invalid request target"));
+
+    // Well-formed heads still go through
+    let r = ok_response(with_header("X-A", "plain\tvalue"));
+    assert!(r.get_body().to_string_box().value.contains("X-A: plain\tvalue\r\n"));
+}
+
+#[test]
+fn http_client_drops_credentials_on_cross_origin_redirect() {
+    let target = free_port();
+    spawn_head_echo_server(target);
+    let redirect = format!("HTTP/1.1 302 Found\r\nLocation: http://127.0.0.1:{}/landed\r\nContent-Length: 0\r\n\r\n", target);
+    let origin = free_port();
+    spawn_raw_server(origin, Some(Box::leak(redirect.into_boxed_str())));
+
+    let headers = MapBox::new();
+    headers.set(s("Authorization"), s("Bearer secret"));
+    headers.set(s("Cookie"), s("session=1"));
+    headers.set(s("Host"), s("origin.example"));
+    headers.set(s("X-Token"), s("kept"));
+    let options = MapBox::new();
+    options.set(s("headers"), Box::new(headers));
+    let r = ok_response(HttpClientBox::new().request(s("GET"), s(&format!("http://127.0.0.1:{}/", origin)), Box::new(options)));
+    let head = r.get_body().to_string_box().value;
+
+    assert!(head.starts_with("GET /landed HTTP/1.1\r\n"), "{}", head);
+    assert!(head.contains(&format!("Host: 127.0.0.1:{}\r\n", target)), "{}", head);
+    assert!(head.contains("X-Token: kept\r\n"), "{}", head);
+    for leaked in ["Authorization", "Cookie", "origin.example"] {
+        assert!(!head.contains(leaked), "{} forwarded: {}", leaked, head);
+    }
+}
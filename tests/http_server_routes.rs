@@ -0,0 +1,115 @@
+//! HTTPServerBox: Nyash route handlers are invoked with an HTTPRequestBox
+
+use nyash_rust::interpreter::NyashInterpreter;
+use nyash_rust::parser::NyashParser;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
+
+/// Starts `server.start()` on an interpreter thread; the server runs until the test process exits
+fn spawn_server(port: u16, static_dir: &str) {
+    let code = format!(r#"
+box Api {{
+    init {{ hits }}
+    birth() {{ me.hits = 0 }}
+    showUser(req) {{
+        me.hits = me.hits + 1
+        return "user " + req.getParam("id") + " q=" + req.getQuery("q") + " hits=" + me.hits
+    }}
+    create(req) {{
+        local r = new HTTPResponseBox()
+        r.setStatus(201, "Created")
+        r.setContentType("application/json")
+        r.setBody("{{\"name\":\"" + req.getBody() + "\"}}")
+        return r
+    }}
+    handle(req) {{
+        return "any " + req.getMethod() + " " + req.getPath()
+    }}
+    broken(req) {{
+        return me.missingMethod()
+    }}
+}}
+
+static box Main {{
+    main() {{
+        local server = new HTTPServerBox()
+        server.bind("127.0.0.1", {port})
+        local api = new Api()
+        server.get("/users/:id", new MethodBox(api, "showUser"))
+        server.post("/users", new MethodBox(api, "create"))
+        server.get("/broken", new MethodBox(api, "broken"))
+        server.route("/any", api)
+        server.setStaticPath("{static_dir}")
+        server.start()
+        return 0
+    }}
+}}
+"#);
+    std::thread::spawn(move || {
+        let ast = NyashParser::parse_from_string(&code).expect("parse");
+        let mut interpreter = NyashInterpreter::new();
+        let _ = interpreter.execute(ast);
+    });
+}
+
+fn request(port: u16, raw: &str) -> String {
+    for _ in 0..100 {
+        if let Ok(mut stream) = TcpStream::connect(("127.0.0.1", port)) {
+            stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+            stream.write_all(raw.as_bytes()).unwrap();
+            let mut out = String::new();
+            stream.read_to_string(&mut out).unwrap();
+            return out;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    panic!("server on port {} did not come up", port);
+}
+
+fn get(port: u16, path: &str) -> String {
+    request(port, &format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path))
+}
+
+#[test]
+fn http_server_invokes_nyash_route_handlers() {
+    let dir = std::env::temp_dir().join(format!("nyash_http_static_{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("css")).unwrap();
+    std::fs::write(dir.join("index.html"), "<h1>home</h1>").unwrap();
+    std::fs::write(dir.join("css/site.css"), "body{}").unwrap();
+    let port = free_port();
+    spawn_server(port, dir.to_str().unwrap());
+
+    // Path parameter + query, handler state persists across requests
+    let r = get(port, "/users/42?q=hello%20world");
+    assert!(r.starts_with("HTTP/1.1 200 OK\r\n"), "{}", r);
+    assert!(r.ends_with("user 42 q=hello world hits=1"), "{}", r);
+    assert!(get(port, "/users/7").ends_with("user 7 q= hits=2"));
+
+    // HTTPResponseBox from the handler is sent as built (body read via Content-Length)
+    let r = request(port, "POST /users HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\n\r\nalice");
+    assert!(r.starts_with("HTTP/1.1 201 Created\r\n"), "{}", r);
+    assert!(r.contains("Content-Type: application/json\r\n"), "{}", r);
+    assert!(r.ends_with(r#"{"name":"alice"}"#), "{}", r);
+
+    // User box instance: its handle(request) method is called, for any method
+    assert!(request(port, "DELETE /any HTTP/1.1\r\n\r\n").ends_with("any DELETE /any"));
+
+    // Handler runtime errors become 500 without killing the server
+    assert!(get(port, "/broken").starts_with("HTTP/1.1 500 "));
+    assert!(get(port, "/users/1").contains("hits=3"));
+
+    // Static files for unmatched GETs; traversal is refused
+    let r = get(port, "/");
+    assert!(r.contains("Content-Type: text/html") && r.ends_with("<h1>home</h1>"), "{}", r);
+    assert!(get(port, "/css/site.css").contains("Content-Type: text/css"));
+    assert!(get(port, "/../etc/passwd").starts_with("HTTP/1.1 403 "));
+    assert!(get(port, "/missing.txt").starts_with("HTTP/1.1 404 "));
+    assert!(request(port, "POST /missing HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 404 "));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,180 @@
+//! Textual MIR: parse `--dump-mir` output back into a module and run it on the VM
+
+use nyash_rust::backend::VM;
+use nyash_rust::mir::{ConstValue, MirCompiler, MirInstruction, MirModule, MirParser, MirPrinter, MirType};
+use nyash_rust::parser::NyashParser;
+
+fn compile(code: &str) -> MirModule {
+    let ast = NyashParser::parse_from_string(code).expect("parse");
+    MirCompiler::new().compile(ast).expect("compile").module
+}
+
+fn run(module: &MirModule) -> String {
+    VM::new().execute_module(module).expect("vm").to_string_box().value
+}
+
+fn reparse(text: &str) -> MirModule {
+    MirParser::new().parse_module(text).unwrap_or_else(|e| panic!("{}\n{}", e, text))
+}
+
+/// print -> parse -> print must reproduce the text exactly
+fn assert_round_trip(module: &MirModule) -> MirModule {
+    let mut plain = MirPrinter::new();
+    plain.set_show_line_numbers(false);
+    for printer in [MirPrinter::new(), plain] {
+        let text = printer.print_module(module);
+        assert_eq!(printer.print_module(&reparse(&text)), text);
+    }
+
+    // Verbose statistics reflect the recomputed CFG and value counter, so the
+    // verbose text is only required to be stable once it has been re-read
+    let verbose = MirPrinter::verbose();
+    let text = verbose.print_module(&reparse(&verbose.print_module(module)));
+    assert_eq!(verbose.print_module(&reparse(&text)), text);
+
+    reparse(&MirPrinter::new().print_module(module))
+}
+
+#[test]
+fn snapshots_round_trip() {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/mir_snapshots");
+    let mut count = 0;
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|e| e.to_str()) != Some("mir") {
+            continue;
+        }
+        let text = std::fs::read_to_string(&path).unwrap();
+        let module = MirParser::new().parse_module(&text).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        assert_eq!(MirPrinter::new().print_module(&module).trim_end(), text.trim_end(), "{}", path.display());
+        count += 1;
+    }
+    assert!(count >= 3);
+}
+
+#[test]
+fn compiled_programs_round_trip_and_run_the_same() {
+    let programs = [
+        ("local i = 0\nlocal sum = 0\nloop(i < 10) {\n  i = i + 1\n  sum = sum + i\n}\nreturn sum", "55"),
+        ("local x = 7\nlocal r = 0\nif x > 5 {\n  r = x * 2\n} else {\n  r = 0 - x\n}\nreturn r", "14"),
+        (r#"local s = "say \"hi\"; ok\n\ttab"
+return s.length()"#, "17"),
+        ("local big = 100000000000000000000.0\nlocal f = 0.1\nreturn f", "0.1"),
+        (r#"local s = new StringBox("nyash")
+return s.length() + 2"#, "7"),
+    ];
+    for (code, expected) in programs {
+        let module = compile(code);
+        let reparsed = assert_round_trip(&module);
+        assert_eq!(run(&module), expected, "{}", code);
+        assert_eq!(run(&reparsed), expected, "{}", code);
+    }
+}
+
+#[test]
+fn parses_blocks_phis_effects_and_types() {
+    let text = r#"
+; MIR Module: handwritten
+
+global @greeting = "hello ; world"
+
+define i64 @twice(i64 %0) effects(read) {
+bb0:
+    %1 = const 2
+    %2 = %0 Mul %1
+    ret %2
+}
+
+define void @main() {
+bb0:
+    %0 = const 0
+    %1 = const 1
+    br label bb1
+
+bb1:  ; preds(bb0, bb2)
+    %2 = phi [%0, bb0], [%4, bb2]
+    %3 = phi [%1, bb0], [%5, bb2]
+    %6 = const 10
+    %7 = icmp Le %3, %6
+    br %7, label bb2, label bb3
+
+bb2:
+    %4 = %2 Add %3    ; eff: pure
+    %8 = const 1
+    %5 = %3 Add %8
+    br label bb1
+
+bb3:
+    %9 = const "twice"
+    %10 = call %9(%2) [effects: read|io]
+    %11 = cast %10 to Box("IntegerBox")
+    print %11
+    ret %10
+}
+"#;
+    let module = MirParser::new().parse_module(text).expect("parse");
+    assert_eq!(module.name, "handwritten");
+    assert_eq!(module.globals["greeting"], ConstValue::String("hello ; world".to_string()));
+
+    let twice = &module.functions["twice"];
+    assert_eq!(twice.signature.params, vec![MirType::Integer]);
+    assert_eq!(twice.params.len(), 1);
+    assert_eq!(twice.signature.effects.to_string(), "read");
+
+    let main = &module.functions["main"];
+    assert_eq!(main.blocks.len(), 4);
+    assert_eq!(main.next_value_id, 12);
+    let header = &main.blocks[&nyash_rust::mir::BasicBlockId::new(1)];
+    assert_eq!(header.predecessors.len(), 2);
+    assert_eq!(header.phi_instructions().count(), 2);
+    let call = main.blocks.values()
+        .flat_map(|b| b.all_instructions())
+        .find_map(|i| match i {
+            MirInstruction::Call { effects, .. } => Some(effects.to_string()),
+            _ => None,
+        });
+    assert_eq!(call.as_deref(), Some("io|read"));
+
+    // sum(1..10) = 55, doubled by @twice
+    assert_eq!(run(&module), "110");
+    assert_round_trip(&module);
+}
+
+#[test]
+fn parse_errors_report_the_line() {
+    let cases = [
+        ("; MIR Module: m\ndefine void @main() {\nbb0:\n    %0 = frobnicate %1\n}\n", 4, "unknown instruction"),
+        ("; MIR Module: m\ndefine void @main() {\nbb0:\n    ret void\n    nop\n}\n", 5, "after the terminator"),
+        ("; MIR Module: m\ndefine void @main() {\nbb0:\n    br label bb7\n}\n", 3, "unknown block bb7"),
+        ("; MIR Module: m\ndefine void @main(i64 %1) {\nbb0:\n    ret void\n}\n", 2, "numbered"),
+        ("; MIR Module: m\ndefine void @main() {\nbb0:\n    ret void\n", 2, "not closed"),
+        ("; MIR Module: m\ndefine void @main() {\nbb0:\n    debug %0 \"open\n}\n", 4, "unterminated"),
+    ];
+    for (text, line, message) in cases {
+        let err = MirParser::new().parse_module(text).unwrap_err();
+        assert_eq!(err.line, line, "{}", err);
+        assert!(err.message.contains(message), "{}", err);
+        assert!(err.to_string().starts_with(&format!("line {}: ", line)));
+    }
+}
+
+#[test]
+fn cli_runs_dumped_mir() {
+    let dir = std::env::temp_dir().join(format!("nyash_run_mir_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let source = dir.join("prog.nyash");
+    std::fs::write(&source, "local i = 0\nlocal sum = 0\nloop(i < 4) {\n  i = i + 1\n  sum = sum + i * i\n}\nprint(sum)\nreturn sum\n").unwrap();
+
+    let nyash = env!("CARGO_BIN_EXE_nyash");
+    let dump = std::process::Command::new(nyash).arg("--dump-mir").arg(&source).output().unwrap();
+    assert!(dump.status.success());
+    let mir = dir.join("prog.mir");
+    std::fs::write(&mir, &dump.stdout).unwrap();
+
+    let out = std::process::Command::new(nyash).arg("--run-mir").arg(&mir).output().unwrap();
+    let stdout = String::from_utf8_lossy(&out.stdout).to_string();
+    let _ = std::fs::remove_dir_all(&dir);
+    assert!(out.status.success(), "{}{}", stdout, String::from_utf8_lossy(&out.stderr));
+    assert!(stdout.lines().any(|l| l.trim() == "30"), "{}", stdout);
+    assert!(stdout.contains("VM execution completed"), "{}", stdout);
+}
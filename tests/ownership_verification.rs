@@ -0,0 +1,76 @@
+//! `--verify-ownership`: a strong parent/child cycle fails the run (for CI) and points at the
+//! field to make weak; the same program with a weak back reference passes, and re-assigning a
+//! strong field only warns
+use std::process::{Command, Output};
+
+const PROGRAM: &str = r#"box Child {
+  init { PARENT_FIELD, name }
+  birth(n) { me.name = n }
+  setParent(p) { me.parent = p }
+}
+box Parent {
+  init { name, child }
+  birth(n) {
+    me.name = n
+    me.child = new Child("kid")
+    me.child.setParent(me)
+  }
+}
+local p = new Parent("root")
+"#;
+
+const REASSIGNED: &str = r#"box Item {
+  init { }
+}
+box Holder {
+  init { item }
+  birth() {
+    me.item = new Item()
+    me.item = new Item()
+  }
+}
+local h = new Holder()
+"#;
+
+fn verify_ownership(parent_field: &str) -> Output {
+    verify_source(&PROGRAM.replace("PARENT_FIELD", parent_field), parent_field.len())
+}
+
+fn verify_source(program: &str, tag: usize) -> Output {
+    let dir = std::env::temp_dir().join(format!("nyash_verify_ownership_{}_{}", std::process::id(), tag));
+    std::fs::create_dir_all(&dir).unwrap();
+    let source = dir.join("tree.nyash");
+    std::fs::write(&source, program).unwrap();
+    let out = Command::new(env!("CARGO_BIN_EXE_nyash")).arg("--verify-ownership").arg(&source).output().unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+    out
+}
+
+#[test]
+fn strong_cycle_fails_with_field_names_and_source_lines() {
+    let out = verify_ownership("parent");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert_eq!(out.status.code(), Some(1), "{}", stderr);
+    assert!(stderr.contains("strong reference cycle: Parent.child -> Child.parent -> Parent"), "{}", stderr);
+    assert!(stderr.contains("Child.parent = Parent in Child.setParent/1 (line 4, column 18)"), "{}", stderr);
+    assert!(stderr.contains("  4 |   setParent(p) { me.parent = p }"), "{}", stderr);
+    assert!(stderr.contains("help: declare `parent` as weak in box Child"), "{}", stderr);
+}
+
+#[test]
+fn weak_back_reference_passes() {
+    let out = verify_ownership("weak parent");
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(out.status.success(), "{}{}", stdout, String::from_utf8_lossy(&out.stderr));
+    assert!(stdout.contains("Ownership verification passed"), "{}", stdout);
+}
+
+#[test]
+fn reassigned_strong_field_only_warns() {
+    let out = verify_source(REASSIGNED, 0);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(out.status.success(), "{}{}", stdout, stderr);
+    assert!(stdout.contains("Ownership verification passed"), "{}", stdout);
+    assert!(stderr.contains("Ownership warning: Holder.item is overwritten"), "{}", stderr);
+}
@@ -0,0 +1,212 @@
+//! MessageBus mailboxes: async delivery, priority, backpressure and request/response
+
+use nyash_rust::box_trait::StringBox;
+use nyash_rust::boxes::{FutureBox, IntentBox, P2PBox};
+use nyash_rust::interpreter::NyashInterpreter;
+use nyash_rust::parser::NyashParser;
+use nyash_rust::transport::{
+    Correlation, InProcessTransport, SendOpts, TcpTransport, TcpTransportConfig, Transport, TransportError,
+};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+fn intent(name: &str) -> IntentBox {
+    IntentBox::new(name.to_string(), serde_json::Value::Null)
+}
+
+/// Node whose worker blocks inside the "gate" handler until released; records delivery order
+fn gated_node(id: &str, capacity: usize) -> (InProcessTransport, Receiver<()>, Sender<()>, Receiver<String>) {
+    let mut node = InProcessTransport::with_mailbox_capacity(id.to_string(), capacity);
+    let (started_tx, started_rx) = channel();
+    let (release_tx, release_rx) = channel::<()>();
+    let (started_tx, release_rx) = (Mutex::new(started_tx), Mutex::new(release_rx));
+    node.add_handler("gate", Box::new(move |_, _| {
+        started_tx.lock().unwrap().send(()).unwrap();
+        let _ = release_rx.lock().unwrap().recv();
+    }));
+    let (seen_tx, seen_rx) = channel();
+    let seen_tx = Mutex::new(seen_tx);
+    node.on_receive(Box::new(move |envelope| {
+        let _ = seen_tx.lock().unwrap().send(envelope.intent.get_name().to_string_box().value);
+    }));
+    (node, started_rx, release_tx, seen_rx)
+}
+
+fn priority(p: u8) -> SendOpts {
+    SendOpts { priority: Some(p), ..Default::default() }
+}
+
+#[test]
+fn send_returns_before_slow_handler_runs() {
+    let slow = InProcessTransport::new("bus-slow".to_string());
+    slow.add_handler("work", Box::new(|_, _| std::thread::sleep(Duration::from_millis(300))));
+    let sender = InProcessTransport::new("bus-slow-sender".to_string());
+    let start = Instant::now();
+    for _ in 0..3 {
+        sender.send("bus-slow", intent("work"), SendOpts::default()).unwrap();
+    }
+    assert!(start.elapsed() < Duration::from_millis(200), "{:?}", start.elapsed());
+    assert!(matches!(sender.send("bus-nobody", intent("work"), SendOpts::default()), Err(TransportError::NodeNotFound(_))));
+}
+
+#[test]
+fn higher_priority_is_delivered_first() {
+    let (_sink, started, release, seen) = gated_node("bus-prio", 16);
+    let sender = InProcessTransport::new("bus-prio-sender".to_string());
+    sender.send("bus-prio", intent("gate"), SendOpts::default()).unwrap();
+    started.recv_timeout(Duration::from_secs(5)).unwrap();
+
+    for (name, p) in [("low", 1), ("high-a", 9), ("mid", 5), ("high-b", 9), ("default", 0)] {
+        sender.send("bus-prio", intent(name), priority(p)).unwrap();
+    }
+    release.send(()).unwrap();
+    let order: Vec<String> = (0..6).map(|_| seen.recv_timeout(Duration::from_secs(5)).unwrap()).collect();
+    assert_eq!(order, ["gate", "high-a", "high-b", "mid", "low", "default"]);
+}
+
+#[test]
+fn full_mailbox_reports_backpressure() {
+    let (sink, started, release, seen) = gated_node("bus-full", 2);
+    let sender = InProcessTransport::new("bus-full-sender".to_string());
+    sender.send("bus-full", intent("gate"), SendOpts::default()).unwrap();
+    started.recv_timeout(Duration::from_secs(5)).unwrap();
+
+    sender.send("bus-full", intent("a"), SendOpts::default()).unwrap();
+    sender.send("bus-full", intent("b"), SendOpts::default()).unwrap();
+    let err = sender.send("bus-full", intent("c"), SendOpts::default());
+    assert!(matches!(err, Err(TransportError::MailboxFull(_))), "{:?}", err);
+
+    // With a timeout the sender waits for space instead
+    let start = Instant::now();
+    let err = sender.send("bus-full", intent("c"), SendOpts { timeout_ms: Some(100), ..Default::default() });
+    assert!(matches!(err, Err(TransportError::MailboxFull(_))), "{:?}", err);
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    let releaser = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(100));
+        release.send(()).unwrap();
+    });
+    sender.send("bus-full", intent("c"), SendOpts { timeout_ms: Some(5000), ..Default::default() }).unwrap();
+    releaser.join().unwrap();
+    let order: Vec<String> = (0..4).map(|_| seen.recv_timeout(Duration::from_secs(5)).unwrap()).collect();
+    assert_eq!(order, ["gate", "a", "b", "c"]);
+    drop(sink);
+}
+
+#[test]
+fn request_resolves_future_with_handler_result() {
+    let code = r#"
+box Calc {
+    init { calls }
+    birth() {
+        me.calls = 0
+    }
+    onAdd(intent, sender) {
+        me.calls = me.calls + 1
+        return "{\"sum\": 3, \"from\": \"" + sender + "\"}"
+    }
+    onEcho(intent, sender) {
+        return new IntentBox("echo.done", intent.getPayload())
+    }
+}
+
+local alice = new P2PBox("rr-alice", "inprocess")
+local bob = new P2PBox("rr-bob", "inprocess")
+local calc = new Calc()
+bob.on("calc.add", new MethodBox(calc, "onAdd"))
+bob.on("echo", new MethodBox(calc, "onEcho"))
+
+local sum = alice.request("rr-bob", new IntentBox("calc.add", "{\"a\": 1, \"b\": 2}"))
+local echo = alice.request("rr-bob", new IntentBox("echo", "\"hi\""))
+local handled = bob.poll(5000)
+handled = handled + bob.poll(100)
+local r1 = await sum
+local r2 = await echo
+
+local missing = alice.request("rr-nobody", new IntentBox("calc.add", "{}"))
+local m = await missing
+local silent = alice.requestTimeout("rr-bob", new IntentBox("unanswered", "{}"), 100)
+local s = await silent
+
+nowait later = 40 + 2
+local l = await later
+
+result = handled.toString() + " " + r1.getName() + " " + r1.getPayload() + " " + r2.getName() + " " + r2.getPayload()
+result = result + " " + isType(m, "ErrorBox") + " " + isType(s, "ErrorBox") + " " + l
+"#;
+    let ast = NyashParser::parse_from_string(code).expect("parse");
+    let mut interpreter = NyashInterpreter::new();
+    interpreter.execute(ast).expect("execute");
+    let result = interpreter.get_variable("result").expect("result").to_string_box().value;
+    assert_eq!(result, r#"2 calc.add {"from":"rr-alice","sum":3} echo.done "hi" true true 42"#);
+}
+
+#[test]
+fn p2pbox_inbox_is_priority_ordered_and_bounded() {
+    let code = r#"
+box Log {
+    init { seen }
+    birth() {
+        me.seen = ""
+    }
+    onMsg(intent, sender) {
+        me.seen = me.seen + intent.getPayload() + ","
+    }
+}
+
+local alice = new P2PBox("inbox-alice", "inprocess")
+local bob = new P2PBox("inbox-bob", "inprocess")
+local log = new Log()
+bob.on("msg", new MethodBox(log, "onMsg"))
+
+alice.send("inbox-bob", new IntentBox("msg", "\"low\""))
+alice.sendPriority("inbox-bob", new IntentBox("msg", "\"high\""), 9)
+alice.sendPriority("inbox-bob", new IntentBox("msg", "\"mid\""), 5)
+local handled = bob.poll(1000)
+
+local accepted = 0
+local i = 0
+loop(i < 1024) {
+    if alice.send("inbox-bob", new IntentBox("fill", "{}")) {
+        accepted = accepted + 1
+    }
+    i = i + 1
+}
+local overflow = alice.send("inbox-bob", new IntentBox("fill", "{}"))
+local drained = bob.poll(0)
+local after = alice.send("inbox-bob", new IntentBox("fill", "{}"))
+
+result = handled.toString() + " " + log.seen + " " + accepted + " " + overflow + " " + drained + " " + after
+"#;
+    let ast = NyashParser::parse_from_string(code).expect("parse");
+    let mut interpreter = NyashInterpreter::new();
+    interpreter.execute(ast).expect("execute");
+    let result = interpreter.get_variable("result").expect("result").to_string_box().value;
+    // "fill" has no handler, so poll drains it without counting
+    assert_eq!(result, r#"3 "high","mid","low", 1024 false 0 true"#);
+}
+
+#[test]
+fn request_reply_over_tcp_uses_correlation() {
+    let node = |id: &str| {
+        let config = TcpTransportConfig { listen: Some("127.0.0.1:0".to_string()), ..Default::default() };
+        let transport = TcpTransport::new(id.to_string(), config).unwrap();
+        let address = transport.local_addr().unwrap().to_string();
+        (P2PBox::with_transport(id.to_string(), Box::new(transport)), address)
+    };
+    let (alice, _) = node("alice");
+    let (bob, bob_address) = node("bob");
+    alice.add_peer(Box::new(StringBox::new("bob")), Box::new(StringBox::new(bob_address)));
+
+    let future = alice.request(Box::new(StringBox::new("bob")), Box::new(intent("ping")), Some(5000));
+    let received = bob.take_received(Duration::from_secs(5));
+    let Some(Correlation::Request(id)) = received[0].correlation.clone() else { panic!("{:?}", received[0]) };
+    bob.reply("alice", id, IntentBox::new("pong".to_string(), serde_json::json!(1))).unwrap();
+
+    let future = future.as_any().downcast_ref::<FutureBox>().unwrap();
+    let reply = future.get();
+    let reply = reply.as_any().downcast_ref::<IntentBox>().expect("reply intent");
+    assert_eq!(reply.get_name().to_string_box().value, "pong");
+    // Replies never reach the inbox
+    assert!(alice.take_received(Duration::from_millis(100)).is_empty());
+}
@@ -0,0 +1,191 @@
+//! P2PBox over TcpTransport: in-process transports on loopback and two nyash processes
+
+use nyash_rust::box_trait::{NyashBox, StringBox};
+use nyash_rust::boxes::{IntentBox, P2PBox};
+use nyash_rust::transport::{SendOpts, TcpTransport, TcpTransportConfig, Transport, TransportError};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+fn s(v: &str) -> Box<dyn NyashBox> {
+    Box::new(StringBox::new(v))
+}
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
+
+fn listening_node(id: &str) -> (P2PBox, String) {
+    let transport = TcpTransport::new(id.to_string(), TcpTransportConfig {
+        listen: Some("127.0.0.1:0".to_string()),
+        ..Default::default()
+    }).unwrap();
+    let address = transport.local_addr().unwrap().to_string();
+    (P2PBox::with_transport(id.to_string(), Box::new(transport)), address)
+}
+
+fn wait_until(mut cond: impl FnMut() -> bool) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(5) {
+        if cond() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    false
+}
+
+#[test]
+fn tcp_nodes_exchange_intents_and_track_connections() {
+    let (alice, _) = listening_node("alice");
+    let (bob, bob_address) = listening_node("bob");
+    assert_eq!(alice.get_transport_type().to_string_box().value, "tcp");
+
+    // Not reachable until a connection exists
+    assert_eq!(alice.is_reachable(s("bob")).to_string_box().value, "false");
+    assert_eq!(alice.add_peer(s("bob"), s(&bob_address)).to_string_box().value, "true");
+
+    let intent = IntentBox::new("chat.message".to_string(), serde_json::json!({ "text": "hello", "n": 1 }));
+    assert_eq!(alice.send(s("bob"), Box::new(intent)).to_string_box().value, "true");
+    let received = bob.take_received(Duration::from_secs(5));
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].from, "alice");
+    assert_eq!(received[0].to, "bob");
+    assert_eq!(received[0].intent.get_name().to_string_box().value, "chat.message");
+    assert_eq!(received[0].intent.to_json()["payload"], serde_json::json!({ "text": "hello", "n": 1 }));
+
+    // Both sides see the live connection; bob replies without knowing alice's address
+    assert_eq!(alice.is_reachable(s("bob")).to_string_box().value, "true");
+    assert_eq!(bob.is_reachable(s("alice")).to_string_box().value, "true");
+    let reply = IntentBox::new("chat.reply".to_string(), serde_json::json!("hi"));
+    assert_eq!(bob.send(s("alice"), Box::new(reply)).to_string_box().value, "true");
+    let received = alice.take_received(Duration::from_secs(5));
+    assert_eq!(received[0].intent.get_name().to_string_box().value, "chat.reply");
+
+    // Clones are the same node: handlers and transport are shared
+    let clone = bob.clone();
+    clone.on(s("chat.message"), s("handler"));
+    assert!(bob.handler_for("chat.message").is_some());
+    assert_eq!(clone.is_reachable(s("alice")).to_string_box().value, "true");
+
+    // Dropping bob closes the connection and alice notices
+    drop(clone);
+    drop(bob);
+    assert!(wait_until(|| alice.is_reachable(s("bob")).to_string_box().value == "false"));
+    let err = alice.send_intent("bob", IntentBox::new("x".to_string(), serde_json::Value::Null), SendOpts::default());
+    assert!(matches!(err, Err(TransportError::NetworkError(_)) | Err(TransportError::Timeout(_))), "{:?}", err);
+}
+
+#[test]
+fn tcp_send_reports_unknown_nodes_and_honors_timeouts() {
+    let transport = TcpTransport::new("solo".to_string(), TcpTransportConfig::default()).unwrap();
+    let intent = || IntentBox::new("ping".to_string(), serde_json::Value::Null);
+    assert!(matches!(transport.send("nobody", intent(), SendOpts::default()), Err(TransportError::NodeNotFound(_))));
+
+    // A listener that never answers the handshake
+    let silent = TcpListener::bind("127.0.0.1:0").unwrap();
+    transport.add_peer("silent", &silent.local_addr().unwrap().to_string()).unwrap();
+    let start = Instant::now();
+    let result = transport.send("silent", intent(), SendOpts { timeout_ms: Some(200), ..Default::default() });
+    assert!(matches!(result, Err(TransportError::Timeout(_))), "{:?}", result);
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert!(!transport.is_reachable("silent"));
+
+    // A node answering with a different id is not the requested peer
+    let (_other, other_address) = listening_node("other");
+    transport.add_peer("expected", &other_address).unwrap();
+    assert!(matches!(transport.send("expected", intent(), SendOpts::default()), Err(TransportError::NodeNotFound(_))));
+}
+
+#[test]
+fn config_assigns_listen_address_and_peers_by_node_id() {
+    let text = "[p2p.tcp.nodes]\nalice = \"127.0.0.1:9101\"\nbob = \"127.0.0.1:9102\"\n";
+    let config = TcpTransportConfig::from_toml_str("alice", text).unwrap();
+    assert_eq!(config.listen.as_deref(), Some("127.0.0.1:9101"));
+    assert_eq!(config.peers.get("bob").map(String::as_str), Some("127.0.0.1:9102"));
+    assert!(!config.peers.contains_key("alice"));
+    assert!(TcpTransportConfig::from_toml_str("alice", "[p2p.tcp.nodes]\nalice = 1\n").is_err());
+}
+
+#[test]
+fn two_nyash_processes_exchange_intents() {
+    let dir = std::env::temp_dir().join(format!("nyash_p2p_tcp_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (alice_port, bob_port) = (free_port(), free_port());
+    let config = dir.join("p2p.toml");
+    std::fs::write(&config, format!(
+        "[p2p.tcp.nodes]\nalice = \"127.0.0.1:{}\"\nbob = \"127.0.0.1:{}\"\n", alice_port, bob_port
+    )).unwrap();
+
+    let bob_script = dir.join("bob.nyash");
+    std::fs::write(&bob_script, r#"
+box Greeter {
+    init { node }
+    birth(node) {
+        me.node = node
+    }
+    greet(intent, sender) {
+        print("got " + intent.getPayload() + " from " + sender)
+        me.node.send(sender, new IntentBox("greet.reply", "hi " + sender))
+    }
+}
+
+static box Main {
+    main() {
+        local bob = new P2PBox("bob", "tcp")
+        bob.on("greet", new MethodBox(new Greeter(bob), "greet"))
+        bob.poll(20000)
+        return 0
+    }
+}
+"#).unwrap();
+    let alice_script = dir.join("alice.nyash");
+    std::fs::write(&alice_script, r#"
+box Inbox {
+    init { text }
+    birth() {
+        me.text = "none"
+    }
+    onReply(intent, sender) {
+        me.text = sender + ":" + intent.getPayload()
+    }
+}
+
+static box Main {
+    main() {
+        local alice = new P2PBox("alice", "tcp")
+        local inbox = new Inbox()
+        alice.on("greet.reply", new MethodBox(inbox, "onReply"))
+        local sent = alice.sendTimeout("bob", new IntentBox("greet", "hello"), 2000)
+        alice.poll(20000)
+        print("sent=" + sent + " reply=" + inbox.text)
+        return 0
+    }
+}
+"#).unwrap();
+
+    let nyash = env!("CARGO_BIN_EXE_nyash");
+    let mut bob = Command::new(nyash)
+        .arg(&bob_script)
+        .env("NYASH_P2P_CONFIG", &config)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    assert!(wait_until(|| TcpStream::connect(("127.0.0.1", bob_port)).is_ok()), "bob did not start listening");
+
+    let alice = Command::new(nyash)
+        .arg(&alice_script)
+        .env("NYASH_P2P_CONFIG", &config)
+        .stderr(Stdio::null())
+        .output()
+        .unwrap();
+    let bob_status = bob.wait().unwrap();
+    let bob_out = std::io::read_to_string(bob.stdout.take().unwrap()).unwrap();
+    let alice_out = String::from_utf8_lossy(&alice.stdout).to_string();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert!(bob_status.success());
+    assert!(bob_out.contains("got \"hello\" from alice"), "{}", bob_out);
+    assert!(alice_out.contains("sent=true reply=bob:\"hi alice\""), "{}", alice_out);
+}
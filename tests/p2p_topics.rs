@@ -0,0 +1,121 @@
+//! P2PBox topics and node discovery over the in-process bus and TCP
+
+use nyash_rust::box_trait::{NyashBox, StringBox};
+use nyash_rust::boxes::{IntentBox, P2PBox};
+use nyash_rust::interpreter::NyashInterpreter;
+use nyash_rust::parser::NyashParser;
+use nyash_rust::transport::{SendOpts, TcpTransport, TcpTransportConfig};
+use std::time::{Duration, Instant};
+
+fn s(v: &str) -> Box<dyn NyashBox> {
+    Box::new(StringBox::new(v))
+}
+
+fn wait_until(mut cond: impl FnMut() -> bool) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(5) {
+        if cond() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    false
+}
+
+#[test]
+fn inprocess_publish_reaches_subscribers_only() {
+    let code = r#"
+box Reader {
+    init { got }
+    birth() {
+        me.got = ""
+    }
+    onNews(intent, sender) {
+        me.got = me.got + intent.getPayload() + "@" + sender + ";"
+    }
+}
+
+local a = new P2PBox("topic-a", "inprocess")
+local b = new P2PBox("topic-b", "inprocess")
+local c = new P2PBox("topic-c", "inprocess")
+local rb = new Reader()
+local rc = new Reader()
+b.on("news", new MethodBox(rb, "onNews"))
+c.on("news", new MethodBox(rc, "onNews"))
+b.subscribe("topic-test/news")
+c.subscribe("topic-test/news")
+b.addTag("topic-test/gpu")
+c.addTag("topic-test/gpu")
+c.removeTag("topic-test/gpu")
+
+local first = a.publish("topic-test/news", new IntentBox("news", "1"))
+b.poll(5000)
+c.poll(5000)
+c.unsubscribe("topic-test/news")
+local second = a.publish("topic-test/news", new IntentBox("news", "2"))
+b.poll(5000)
+c.poll(100)
+local none = a.publish("topic-test/nobody", new IntentBox("news", "3"))
+
+local gpu = a.findNodes("topic-test/gpu")
+local nodes = a.getNodes()
+result = first.toString() + " " + second.toString() + " " + none.toString() + " " + rb.got + " " + rc.got + " " + gpu.length().toString() + ":" + gpu.get(0)
+known = nodes.contains("topic-a").toString() + nodes.contains("topic-b").toString() + nodes.contains("topic-c").toString()
+"#;
+    let ast = NyashParser::parse_from_string(code).expect("parse");
+    let mut interpreter = NyashInterpreter::new();
+    interpreter.execute(ast).expect("execute");
+    let result = interpreter.get_variable("result").expect("result").to_string_box().value;
+    assert_eq!(result, "2 1 0 1@topic-a;2@topic-a; 1@topic-a; 1:topic-b");
+    let known = interpreter.get_variable("known").expect("known").to_string_box().value;
+    assert_eq!(known, "truetruetrue");
+}
+
+#[test]
+fn tcp_nodes_exchange_subscriptions_and_tags() {
+    let node = |id: &str, peers: &[(&str, String)]| {
+        let config = TcpTransportConfig {
+            listen: Some("127.0.0.1:0".to_string()),
+            peers: peers.iter().map(|(p, a)| (p.to_string(), a.clone())).collect(),
+        };
+        let transport = TcpTransport::new(id.to_string(), config).unwrap();
+        let address = transport.local_addr().unwrap().to_string();
+        (P2PBox::with_transport(id.to_string(), Box::new(transport)), address)
+    };
+    let (hub, hub_address) = node("hub", &[]);
+    let (bob, _) = node("bob", &[("hub", hub_address.clone())]);
+    let (carol, _) = node("carol", &[("hub", hub_address)]);
+
+    // Announcing connects to configured peers and shares the node info
+    assert_eq!(bob.subscribe(s("news")).to_string_box().value, "true");
+    assert_eq!(carol.add_tag(s("storage")).to_string_box().value, "true");
+    assert!(wait_until(|| {
+        let nodes = hub.known_nodes();
+        nodes.iter().any(|n| n.node_id == "bob" && n.topics.contains("news"))
+            && nodes.iter().any(|n| n.node_id == "carol" && n.tags.contains("storage"))
+    }));
+    assert_eq!(hub.find_nodes(s("storage")).to_string_box().value, "[carol]");
+    assert_eq!(hub.get_nodes().to_string_box().value, "[bob, carol, hub]");
+    assert_eq!(bob.known_nodes().iter().map(|n| n.node_id.as_str()).collect::<Vec<_>>(), ["bob", "hub"]);
+
+    let news = || Box::new(IntentBox::new("news".to_string(), serde_json::json!("hello")));
+    assert_eq!(hub.publish(s("news"), news()).to_string_box().value, "1");
+    let received = bob.take_received(Duration::from_secs(5));
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].topic.as_deref(), Some("news"));
+    assert!(carol.take_received(Duration::from_millis(100)).is_empty());
+
+    // After unsubscribing, topic deliveries that were already addressed to bob are dropped
+    bob.unsubscribe(s("news"));
+    assert!(wait_until(|| hub.publish(s("news"), news()).to_string_box().value == "0"));
+    let opts = SendOpts { topic: Some("news".to_string()), ..Default::default() };
+    hub.send_intent("bob", IntentBox::new("news".to_string(), serde_json::Value::Null), opts).unwrap();
+    hub.send_intent("bob", IntentBox::new("direct".to_string(), serde_json::Value::Null), SendOpts::default()).unwrap();
+    let received = bob.take_received(Duration::from_secs(5));
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].intent.get_name().to_string_box().value, "direct");
+
+    // Disconnected nodes drop out of discovery
+    drop(carol);
+    assert!(wait_until(|| hub.find_nodes(s("storage")).to_string_box().value == "[]"));
+}
@@ -0,0 +1,210 @@
+#![cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
+//! isolation = "process": a crashing plugin must not take the interpreter down
+
+use nyash_rust::bid::BidError;
+use nyash_rust::box_trait::NyashBox;
+use nyash_rust::runtime::plugin_loader_v2::{PluginBoxV2, PluginLoaderV2};
+use std::path::{Path, PathBuf};
+
+/// Minimal counter plugin; method 9 aborts the process like a segfault would
+const CRASHY_PLUGIN: &str = r#"
+use std::sync::Mutex;
+static COUNTS: Mutex<Vec<i32>> = Mutex::new(Vec::new());
+
+#[no_mangle]
+pub extern "C" fn nyash_plugin_init() -> i32 { 0 }
+
+#[no_mangle]
+pub unsafe extern "C" fn nyash_plugin_invoke(type_id: u32, method_id: u32, instance_id: u32,
+    _args: *const u8, _args_len: usize, result: *mut u8, result_len: *mut usize) -> i32 {
+    if type_id != 70 { return -2; }
+    let mut counts = COUNTS.lock().unwrap();
+    match method_id {
+        0 => {
+            if result.is_null() || *result_len < 4 { *result_len = 4; return -1; }
+            counts.push(0);
+            let id = counts.len() as u32;
+            std::ptr::copy_nonoverlapping(id.to_le_bytes().as_ptr(), result, 4);
+            *result_len = 4;
+            0
+        }
+        1 => {
+            let Some(c) = counts.get_mut(instance_id as usize - 1) else { return -8 };
+            *c += 1;
+            let mut out = vec![1u8, 0, 1, 0, 2, 0, 4, 0];
+            out.extend_from_slice(&c.to_le_bytes());
+            if result.is_null() || *result_len < out.len() { *result_len = out.len(); return -1; }
+            std::ptr::copy_nonoverlapping(out.as_ptr(), result, out.len());
+            *result_len = out.len();
+            0
+        }
+        9 => std::process::abort(),
+        u32::MAX => 0,
+        _ => -3,
+    }
+}
+"#;
+
+fn build_plugin(dir: &Path) -> Option<PathBuf> {
+    let src = dir.join("crashy.rs");
+    std::fs::write(&src, CRASHY_PLUGIN).ok()?;
+    let lib = dir.join(format!("{}crashy{}", std::env::consts::DLL_PREFIX, std::env::consts::DLL_SUFFIX));
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let status = std::process::Command::new(rustc)
+        .args(["--crate-type", "cdylib", "--edition", "2021", "-o"])
+        .arg(&lib)
+        .arg(&src)
+        .status()
+        .ok()?;
+    status.success().then_some(lib)
+}
+
+fn loader_for(dir: &Path, lib: &Path, singleton: bool) -> PluginLoaderV2 {
+    let toml = format!(r#"
+[libraries."libcrashy"]
+boxes = ["CrashyBox"]
+path = "{}"
+isolation = "process"
+max_restarts = 2
+
+[libraries."libcrashy".CrashyBox]
+type_id = 70
+singleton = {}
+
+[libraries."libcrashy".CrashyBox.methods]
+birth = {{ method_id = 0 }}
+inc = {{ method_id = 1 }}
+crash = {{ method_id = 9 }}
+fini = {{ method_id = 4294967295 }}
+"#, lib.display(), singleton);
+    let cfg = dir.join("nyash.toml");
+    std::fs::write(&cfg, toml).unwrap();
+    let mut loader = PluginLoaderV2::new();
+    loader.load_config(cfg.to_str().unwrap()).unwrap();
+    loader.load_all_plugins().unwrap();
+    loader
+}
+
+fn inc(loader: &PluginLoaderV2, b: &dyn NyashBox) -> Result<String, BidError> {
+    let p = b.as_any().downcast_ref::<PluginBoxV2>().unwrap();
+    loader
+        .invoke_instance_method("CrashyBox", "inc", p.instance_id(), &[])
+        .map(|v| v.map(|v| v.to_string_box().value).unwrap_or_default())
+}
+
+#[test]
+fn process_isolated_plugin_survives_crash_and_restarts() {
+    std::env::set_var("NYASH_PLUGIN_HOST", env!("CARGO_BIN_EXE_nyash-plugin-host"));
+    let dir = std::env::temp_dir().join(format!("nyash_isolation_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let Some(lib) = build_plugin(&dir) else {
+        eprintln!("rustc unavailable; skipping");
+        return;
+    };
+    let loader = loader_for(&dir, &lib, false);
+
+    let a = loader.create_box("CrashyBox", &[]).expect("birth through helper");
+    assert_eq!(inc(&loader, a.as_ref()).unwrap(), "1");
+    assert_eq!(inc(&loader, a.as_ref()).unwrap(), "2");
+
+    // The helper aborts; the call fails but this process keeps running
+    let pa = a.as_any().downcast_ref::<PluginBoxV2>().unwrap();
+    let crash = loader.invoke_instance_method("CrashyBox", "crash", pa.instance_id(), &[]);
+    assert_eq!(crash.err(), Some(BidError::PluginError));
+
+    // Next call restarts the helper; the old handle belongs to the dead process
+    assert_eq!(inc(&loader, a.as_ref()).err(), Some(BidError::InvalidHandle));
+    let b = loader.create_box("CrashyBox", &[]).expect("birth after restart");
+    assert_eq!(inc(&loader, b.as_ref()).unwrap(), "1");
+
+    // A fresh instance must not alias the stale handle's id
+    let pb = b.as_any().downcast_ref::<PluginBoxV2>().unwrap();
+    assert_ne!(pa.instance_id(), pb.instance_id());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn process_isolated_singleton_is_reborn_after_crash() {
+    std::env::set_var("NYASH_PLUGIN_HOST", env!("CARGO_BIN_EXE_nyash-plugin-host"));
+    let dir = std::env::temp_dir().join(format!("nyash_isolation_singleton_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let Some(lib) = build_plugin(&dir) else {
+        eprintln!("rustc unavailable; skipping");
+        return;
+    };
+    let loader = loader_for(&dir, &lib, true);
+
+    let a = loader.create_box("CrashyBox", &[]).expect("pre-birthed singleton");
+    assert_eq!(inc(&loader, a.as_ref()).unwrap(), "1");
+    assert_eq!(inc(&loader, a.as_ref()).unwrap(), "2");
+
+    let pa = a.as_any().downcast_ref::<PluginBoxV2>().unwrap();
+    let crash = loader.invoke_instance_method("CrashyBox", "crash", pa.instance_id(), &[]);
+    assert_eq!(crash.err(), Some(BidError::PluginError));
+
+    // The helper restarts on the next call; the singleton is re-birthed rather than left stale
+    assert_eq!(inc(&loader, a.as_ref()).unwrap(), "1");
+    let b = loader.create_box("CrashyBox", &[]).expect("singleton after restart");
+    assert_eq!(inc(&loader, b.as_ref()).unwrap(), "2");
+    assert_eq!(inc(&loader, a.as_ref()).unwrap(), "3");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn process_isolated_handles_are_keyed_by_box_type() {
+    // The net plugin numbers instances per box type, so both births below get plugin id 1
+    let lib = Path::new(env!("CARGO_MANIFEST_DIR")).join("plugins/nyash-net-plugin/target/release/libnyash_net_plugin.so");
+    if !lib.exists() {
+        eprintln!("net plugin not built; skipping");
+        return;
+    }
+    std::env::set_var("NYASH_PLUGIN_HOST", env!("CARGO_BIN_EXE_nyash-plugin-host"));
+    let dir = std::env::temp_dir().join(format!("nyash_isolation_net_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let toml = format!(r#"
+[libraries."libnyash_net_plugin.so"]
+boxes = ["HttpResponseBox", "HttpRequestBox"]
+path = "{}"
+isolation = "process"
+
+[libraries."libnyash_net_plugin.so".HttpResponseBox]
+type_id = 22
+
+[libraries."libnyash_net_plugin.so".HttpResponseBox.methods]
+birth = {{ method_id = 0 }}
+setStatus = {{ method_id = 1 }}
+getStatus = {{ method_id = 5 }}
+fini = {{ method_id = 4294967295 }}
+
+[libraries."libnyash_net_plugin.so".HttpRequestBox]
+type_id = 21
+
+[libraries."libnyash_net_plugin.so".HttpRequestBox.methods]
+birth = {{ method_id = 0 }}
+path = {{ method_id = 1 }}
+fini = {{ method_id = 4294967295 }}
+"#, lib.display());
+    let cfg = dir.join("nyash.toml");
+    std::fs::write(&cfg, toml).unwrap();
+    let mut loader = PluginLoaderV2::new();
+    loader.load_config(cfg.to_str().unwrap()).unwrap();
+    loader.load_all_plugins().unwrap();
+
+    let id_of = |b: &dyn NyashBox| b.as_any().downcast_ref::<PluginBoxV2>().unwrap().instance_id();
+    let resp = loader.create_box("HttpResponseBox", &[]).expect("birth HttpResponseBox");
+    let req = loader.create_box("HttpRequestBox", &[]).expect("birth HttpRequestBox");
+    assert_ne!(id_of(resp.as_ref()), id_of(req.as_ref()), "same plugin id, different types");
+
+    let status: Box<dyn NyashBox> = Box::new(nyash_rust::box_trait::IntegerBox::new(201));
+    loader.invoke_instance_method("HttpResponseBox", "setStatus", id_of(resp.as_ref()), &[status]).expect("setStatus");
+    loader.invoke_instance_method("HttpRequestBox", "path", id_of(req.as_ref()), &[]).expect("path");
+
+    // fini of the request must not drop the response's mapping
+    req.as_any().downcast_ref::<PluginBoxV2>().unwrap().finalize_now();
+    let got = loader.invoke_instance_method("HttpResponseBox", "getStatus", id_of(resp.as_ref()), &[]).expect("response survives");
+    assert_eq!(got.map(|v| v.to_string_box().value).as_deref(), Some("201"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
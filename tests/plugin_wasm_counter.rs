@@ -0,0 +1,231 @@
+#![cfg(all(feature = "plugins", feature = "wasm-backend", not(target_arch = "wasm32")))]
+//! wasm plugin modules: the CounterBox port runs through the same nyash.toml tables
+
+use nyash_rust::bid::BidError;
+use nyash_rust::box_trait::NyashBox;
+use nyash_rust::runtime::plugin_loader_v2::{PluginBoxV2, PluginLoaderV2};
+use nyash_rust::config::nyash_toml_v2::NyashConfigV2;
+use std::path::{Path, PathBuf};
+
+fn temp_dir(tag: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("nyash_wasm_plugin_{}_{}", tag, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn loader_for(dir: &Path, module: &Path, extra_methods: &str) -> PluginLoaderV2 {
+    let toml = format!(r#"
+[libraries."counter.wasm"]
+boxes = ["CounterBox"]
+path = "{}"
+
+[libraries."counter.wasm".CounterBox]
+type_id = 7
+
+[libraries."counter.wasm".CounterBox.methods]
+birth = {{ method_id = 0 }}
+inc = {{ method_id = 1 }}
+get = {{ method_id = 2 }}
+{}
+fini = {{ method_id = 4294967295 }}
+"#, module.display(), extra_methods);
+    let cfg = dir.join("nyash.toml");
+    std::fs::write(&cfg, toml).unwrap();
+    let mut loader = PluginLoaderV2::new();
+    loader.load_config(cfg.to_str().unwrap()).unwrap();
+    loader.load_all_plugins().unwrap();
+    loader
+}
+
+fn call(loader: &PluginLoaderV2, method: &str, b: &dyn NyashBox) -> Result<String, BidError> {
+    let p = b.as_any().downcast_ref::<PluginBoxV2>().unwrap();
+    loader
+        .invoke_instance_method("CounterBox", method, p.instance_id(), &[])
+        .map(|v| v.map(|v| v.to_string_box().value).unwrap_or_default())
+}
+
+#[test]
+fn wasm_counter_plugin_matches_native_behaviour() {
+    let module = Path::new(env!("CARGO_MANIFEST_DIR")).join("plugins/nyash-counter-plugin/counter.wat");
+    let dir = temp_dir("counter");
+    let loader = loader_for(&dir, &module, "");
+
+    let a = loader.create_box("CounterBox", &[]).expect("birth inside wasm module");
+    let b = loader.create_box("CounterBox", &[]).expect("second instance");
+    assert_eq!(call(&loader, "inc", a.as_ref()).unwrap(), "1");
+    assert_eq!(call(&loader, "inc", a.as_ref()).unwrap(), "2");
+    assert_eq!(call(&loader, "get", a.as_ref()).unwrap(), "2");
+    assert_eq!(call(&loader, "get", b.as_ref()).unwrap(), "0");
+
+    // fini goes through the same invoker; the guest then rejects the handle
+    let pa = a.as_any().downcast_ref::<PluginBoxV2>().unwrap();
+    pa.finalize_now();
+    assert_eq!(call(&loader, "get", a.as_ref()).err(), Some(BidError::InvalidHandle));
+    assert_eq!(call(&loader, "inc", b.as_ref()).unwrap(), "1");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn wasm_plugin_trap_fails_call_without_crashing_host() {
+    // Counter port with an extra method 9 that traps (like a guest panic)
+    let src = std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("plugins/nyash-counter-plugin/counter.wat")).unwrap();
+    let trap = src.replacen(
+        "    ;; birth: returns the new instance id as 4 raw bytes",
+        "    (if (i32.eq (local.get $method_id) (i32.const 9)) (then unreachable))\n    ;; birth: returns the new instance id as 4 raw bytes",
+        1,
+    );
+    // No manifest: `boom` is deliberately not part of the plugin's declared tables
+    let trap = trap.replacen("(export \"nyash_plugin_manifest\")", "", 1);
+    assert_ne!(src, trap);
+    let dir = temp_dir("trap");
+    let module = dir.join("trap.wat");
+    std::fs::write(&module, trap).unwrap();
+    let loader = loader_for(&dir, &module, "boom = { method_id = 9 }");
+
+    let a = loader.create_box("CounterBox", &[]).unwrap();
+    assert_eq!(call(&loader, "inc", a.as_ref()).unwrap(), "1");
+    assert_eq!(call(&loader, "boom", a.as_ref()).err(), Some(BidError::PluginError));
+    // Guest state is no longer trusted after a trap
+    assert_eq!(call(&loader, "get", a.as_ref()).err(), Some(BidError::PluginError));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn wasm_plugin_manifest_rejects_drifted_method_ids() {
+    let module = Path::new(env!("CARGO_MANIFEST_DIR")).join("plugins/nyash-counter-plugin/counter.wat");
+    let dir = temp_dir("manifest");
+    let cfg = dir.join("nyash.toml");
+    let write_cfg = |inc: u32, get: u32| {
+        std::fs::write(&cfg, format!(r#"
+[libraries."counter.wasm"]
+boxes = ["CounterBox"]
+path = "{}"
+
+[libraries."counter.wasm".CounterBox]
+type_id = 7
+
+[libraries."counter.wasm".CounterBox.methods]
+birth = {{ method_id = 0 }}
+inc = {{ method_id = {} }}
+get = {{ method_id = {} }}
+fini = {{ method_id = 4294967295 }}
+"#, module.display(), inc, get)).unwrap();
+    };
+    let load = || {
+        let mut loader = PluginLoaderV2::new();
+        loader.load_config(cfg.to_str().unwrap()).unwrap();
+        let conf = NyashConfigV2::from_file(cfg.to_str().unwrap()).unwrap();
+        loader.load_plugin("counter.wasm", &conf.libraries["counter.wasm"])
+    };
+
+    // inc/get swapped relative to the plugin's manifest
+    write_cfg(2, 1);
+    assert_eq!(load().err(), Some(BidError::InvalidMethod));
+    write_cfg(1, 2);
+    assert_eq!(load(), Ok(()));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn wasm_plugin_out_of_fuel_fails_call() {
+    // Method 10 never returns; the per-call fuel budget turns it into a trap
+    let src = std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("plugins/nyash-counter-plugin/counter.wat")).unwrap();
+    let spin = src.replacen(
+        "    ;; birth: returns the new instance id as 4 raw bytes",
+        "    (if (i32.eq (local.get $method_id) (i32.const 10)) (then (loop $spin (br $spin))))\n    ;; birth: returns the new instance id as 4 raw bytes",
+        1,
+    );
+    let spin = spin.replacen("(export \"nyash_plugin_manifest\")", "", 1);
+    let dir = temp_dir("fuel");
+    let module = dir.join("spin.wat");
+    std::fs::write(&module, spin).unwrap();
+    let cfg = dir.join("nyash.toml");
+    std::fs::write(&cfg, format!(r#"
+[libraries."counter.wasm"]
+boxes = ["CounterBox"]
+path = "{}"
+fuel = 100000
+
+[libraries."counter.wasm".CounterBox]
+type_id = 7
+
+[libraries."counter.wasm".CounterBox.methods]
+birth = {{ method_id = 0 }}
+inc = {{ method_id = 1 }}
+spin = {{ method_id = 10 }}
+"#, module.display())).unwrap();
+    let mut loader = PluginLoaderV2::new();
+    loader.load_config(cfg.to_str().unwrap()).unwrap();
+    loader.load_all_plugins().unwrap();
+
+    // The budget is per call, so ordinary calls keep working before the runaway one
+    let a = loader.create_box("CounterBox", &[]).unwrap();
+    for n in 1..=3 {
+        assert_eq!(call(&loader, "inc", a.as_ref()).unwrap(), n.to_string());
+    }
+    assert_eq!(call(&loader, "spin", a.as_ref()).err(), Some(BidError::PluginError));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Build the real counter plugin for wasm32; None when the target is not installed
+fn build_counter_wasm(target_dir: &Path) -> Option<PathBuf> {
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let sysroot = std::process::Command::new(rustc).args(["--print", "sysroot"]).output().ok()?;
+    let sysroot = PathBuf::from(String::from_utf8_lossy(&sysroot.stdout).trim());
+    if !sysroot.join("lib/rustlib/wasm32-unknown-unknown").exists() {
+        return None;
+    }
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let status = std::process::Command::new(cargo)
+        .args(["build", "--release", "--offline", "--quiet", "--target", "wasm32-unknown-unknown"])
+        .current_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("plugins/nyash-counter-plugin"))
+        .env("CARGO_TARGET_DIR", target_dir)
+        .status()
+        .ok()?;
+    assert!(status.success(), "counter plugin failed to build for wasm32");
+    Some(target_dir.join("wasm32-unknown-unknown/release/nyash_counter_plugin.wasm"))
+}
+
+#[test]
+fn wasm_build_of_native_counter_plugin_loads() {
+    let dir = temp_dir("native_port");
+    let Some(module) = build_counter_wasm(&dir.join("target")) else {
+        eprintln!("wasm32-unknown-unknown target not installed; skipping");
+        let _ = std::fs::remove_dir_all(&dir);
+        return;
+    };
+    // The plugin's own manifest declares CounterBox as a singleton
+    let cfg = dir.join("nyash.toml");
+    std::fs::write(&cfg, format!(r#"
+[libraries."counter.wasm"]
+boxes = ["CounterBox"]
+path = "{}"
+
+[libraries."counter.wasm".CounterBox]
+type_id = 7
+singleton = true
+
+[libraries."counter.wasm".CounterBox.methods]
+birth = {{ method_id = 0 }}
+inc = {{ method_id = 1 }}
+get = {{ method_id = 2 }}
+fini = {{ method_id = 4294967295 }}
+"#, module.display())).unwrap();
+    let mut loader = PluginLoaderV2::new();
+    loader.load_config(cfg.to_str().unwrap()).unwrap();
+    loader.load_all_plugins().expect("manifest of the wasm build matches nyash.toml");
+
+    let a = loader.create_box("CounterBox", &[]).unwrap();
+    let b = loader.create_box("CounterBox", &[]).unwrap();
+    assert_eq!(call(&loader, "inc", a.as_ref()).unwrap(), "1");
+    assert_eq!(call(&loader, "inc", b.as_ref()).unwrap(), "2");
+    assert_eq!(call(&loader, "get", a.as_ref()).unwrap(), "2");
+
+    drop((a, b));
+    drop(loader);
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,142 @@
+//! QRBox encoding checked against a reference encoder, by decoding the PNG it writes
+
+use nyash_rust::boxes::qr_box::{EccLevel, QrCode};
+use nyash_rust::boxes::QRBox;
+use nyash_rust::interpreter::NyashInterpreter;
+use nyash_rust::parser::NyashParser;
+use qrcodegen::{QrCodeEcc, QrSegment, Version};
+
+struct Image {
+    width: usize,
+    height: usize,
+    rgb: Vec<u8>,
+}
+
+impl Image {
+    fn decode(png: &[u8]) -> Image {
+        let mut decoder = png::Decoder::new(std::io::Cursor::new(png));
+        decoder.set_transformations(png::Transformations::EXPAND);
+        let mut reader = decoder.read_info().expect("png header");
+        let mut rgb = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut rgb).expect("png data");
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        Image { width: info.width as usize, height: info.height as usize, rgb }
+    }
+
+    fn pixel(&self, x: usize, y: usize) -> [u8; 3] {
+        let i = (y * self.width + x) * 3;
+        [self.rgb[i], self.rgb[i + 1], self.rgb[i + 2]]
+    }
+}
+
+fn reference(data: &[u8], level: &str) -> qrcodegen::QrCode {
+    let ecc = match level {
+        "L" => QrCodeEcc::Low,
+        "M" => QrCodeEcc::Medium,
+        "Q" => QrCodeEcc::Quartile,
+        _ => QrCodeEcc::High,
+    };
+    let segments = [QrSegment::make_bytes(data)];
+    qrcodegen::QrCode::encode_segments_advanced(&segments, ecc, Version::MIN, Version::MAX, None, false).unwrap()
+}
+
+/// Samples the centre of every module and compares it with the reference symbol
+fn assert_png_matches(image: &Image, expected: &qrcodegen::QrCode, fg: [u8; 3], bg: [u8; 3]) {
+    let n = expected.size() as usize;
+    let scale = (image.width.min(image.height) / (n + 8)).max(1);
+    let (left, top) = ((image.width - n * scale) / 2, (image.height - n * scale) / 2);
+    for y in 0..n {
+        for x in 0..n {
+            let dark = expected.get_module(x as i32, y as i32);
+            let pixel = image.pixel(left + x * scale + scale / 2, top + y * scale + scale / 2);
+            assert_eq!(pixel, if dark { fg } else { bg }, "module ({}, {})", x, y);
+        }
+    }
+    // Quiet zone stays background
+    for i in 0..4 * scale {
+        assert_eq!(image.pixel(left - 1 - i.min(left - 1), top), bg);
+        assert_eq!(image.pixel(left, top - 1 - i.min(top - 1)), bg);
+    }
+}
+
+#[test]
+fn encoder_matches_reference_across_versions_and_levels() {
+    let long: Vec<u8> = (0..1200u32).map(|i| (i * 7 + 3) as u8).collect();
+    let cases: [&[u8]; 6] = [b"", b"A", b"https://example.com/nyash", "日本語のテキスト".as_bytes(), &long[..300], &long];
+    for data in cases {
+        for level in ["L", "M", "Q", "H"] {
+            let expected = reference(data, level);
+            let Ok(qr) = QrCode::encode_bytes(data, EccLevel::parse(level).unwrap()) else {
+                panic!("encode failed for {} bytes at {}", data.len(), level);
+            };
+            assert_eq!(qr.version(), expected.version().value(), "{} bytes at {}", data.len(), level);
+            assert_eq!(qr.mask(), expected.mask().value(), "{} bytes at {}", data.len(), level);
+            let n = expected.size() as usize;
+            assert_eq!(qr.size(), n);
+            for y in 0..n {
+                for x in 0..n {
+                    assert_eq!(qr.get(x, y), expected.get_module(x as i32, y as i32), "module ({}, {})", x, y);
+                }
+            }
+        }
+    }
+    // Byte-mode capacity of version 40-H is 1273 bytes
+    assert!(QrCode::encode_bytes(&[0; 1273], EccLevel::H).is_ok());
+    assert!(QrCode::encode_bytes(&[0; 1274], EccLevel::H).is_err());
+}
+
+#[test]
+fn png_output_decodes_to_the_configured_symbol() {
+    let qr = QRBox::new();
+    assert!(qr.generate("Hello, Nyash!"));
+    let image = Image::decode(&qr.to_png().unwrap());
+    assert_eq!((image.width, image.height), (200, 200));
+    assert_png_matches(&image, &reference(b"Hello, Nyash!", "M"), [0, 0, 0], [255, 255, 255]);
+
+    // Colours, non-square size and a higher ECC level
+    assert!(qr.set_colors("#203040", "yellow"));
+    assert!(!qr.set_colors("not-a-colour", "white"));
+    assert!(qr.set_error_correction("h"));
+    assert!(!qr.set_error_correction("X"));
+    qr.set_size(333, 250);
+    let image = Image::decode(&qr.to_png().unwrap());
+    assert_eq!((image.width, image.height), (333, 250));
+    assert_png_matches(&image, &reference(b"Hello, Nyash!", "H"), [0x20, 0x30, 0x40], [255, 255, 0]);
+
+    // Too small a size still renders one pixel per module
+    qr.set_size(10, 10);
+    let image = Image::decode(&qr.to_png().unwrap());
+    assert_eq!(image.width, reference(b"Hello, Nyash!", "H").size() as usize + 8);
+    assert_png_matches(&image, &reference(b"Hello, Nyash!", "H"), [0x20, 0x30, 0x40], [255, 255, 0]);
+
+    assert!(QRBox::new().to_png().is_err());
+}
+
+#[test]
+fn nyash_script_saves_png_and_reports_version() {
+    let dir = std::env::temp_dir().join(format!("nyash_qr_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("wifi.png");
+    let code = format!(r#"
+local qr = new QRBox()
+qr.generateWiFi("MyNetwork", "secret", "WPA")
+qr.setErrorCorrection("Q")
+qr.setSize(300, 300)
+local saved = qr.saveTo("{}")
+local bad = qr.saveTo("{}")
+local url = qr.getDataURL()
+result = saved.isOk().toString() + " " + bad.isOk().toString() + " " + qr.getVersion().toString() + " " + url.substring(0, 22)
+"#, path.display(), dir.join("missing").join("x.png").display());
+    let ast = NyashParser::parse_from_string(&code).expect("parse");
+    let mut interpreter = NyashInterpreter::new();
+    interpreter.execute(ast).expect("execute");
+    let result = interpreter.get_variable("result").expect("result").to_string_box().value;
+
+    let data = b"WIFI:T:WPA;S:MyNetwork;P:secret;H:false;;";
+    let expected = reference(data, "Q");
+    assert_eq!(result, format!("true false {} data:image/png;base64,", expected.version().value()));
+    let image = Image::decode(&std::fs::read(&path).unwrap());
+    let _ = std::fs::remove_dir_all(&dir);
+    assert_eq!((image.width, image.height), (300, 300));
+    assert_png_matches(&image, &expected, [0, 0, 0], [255, 255, 255]);
+}
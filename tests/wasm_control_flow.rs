@@ -0,0 +1,213 @@
+#![cfg(feature = "wasm-backend")]
+//! WASM backend control flow: loops, branches, phi nodes, calls and user box methods
+
+use nyash_rust::backend::wasm::{WasmBackend, WasmValue};
+use nyash_rust::backend::VM;
+use nyash_rust::mir::{MirCompiler, MirModule, MirParser};
+use nyash_rust::parser::NyashParser;
+
+fn compile(code: &str) -> MirModule {
+    let ast = NyashParser::parse_from_string(code).expect("parse");
+    MirCompiler::new().compile(ast).expect("compile").module
+}
+
+fn run_wasm(module: MirModule) -> WasmValue {
+    let mut backend = WasmBackend::new();
+    let bytes = backend.compile_module(module).unwrap_or_else(|e| panic!("compile: {}", e));
+    backend.execute_wasm(&bytes).unwrap_or_else(|e| panic!("execute: {}", e)).value
+}
+
+fn run_mir_text(text: &str) -> WasmValue {
+    run_wasm(MirParser::new().parse_module(text).expect("mir"))
+}
+
+fn assert_matches_vm(code: &str, expected: &str) {
+    let module = compile(code);
+    let vm = VM::new().execute_module(&module).expect("vm").to_string_box().value;
+    assert_eq!(vm, expected, "VM result for {}", code);
+    assert_eq!(run_wasm(module).to_string(), vm, "{}", code);
+}
+
+#[test]
+fn loops_and_branches_match_the_vm() {
+    assert_matches_vm(
+        "local i = 0\nlocal sum = 0\nloop(i < 10) {\n    i = i + 1\n    sum = sum + i\n}\nreturn sum",
+        "55",
+    );
+    assert_matches_vm(
+        "local x = 7\nlocal y = 0\nif x > 5 {\n    y = x * 2\n} else {\n    y = x + 100\n}\nreturn y",
+        "14",
+    );
+    assert_matches_vm(
+        "local i = 0\nlocal total = 0\nloop(i < 3) {\n    local j = 0\n    loop(j < 3) {\n        total = total + i + j\n        j = j + 1\n    }\n    i = i + 1\n}\nreturn total",
+        "18",
+    );
+}
+
+#[test]
+fn user_box_methods_use_field_offsets() {
+    let code = r#"
+box Point {
+    init { x, y }
+    birth(a, b) {
+        me.x = a
+        me.y = b
+    }
+    sum() {
+        return me.x + me.y
+    }
+}
+local p = new Point(3, 4)
+return p.sum()
+"#;
+    assert_eq!(run_wasm(compile(code)), WasmValue::Integer(7));
+
+    let wat = WasmBackend::new().compile_to_wat(compile(code)).unwrap();
+    assert!(wat.contains("(func $Point.sum/0"), "{}", wat);
+    assert!(wat.contains("call $Point.birth/2"), "{}", wat);
+    assert!(wat.contains("call $alloc_point"), "{}", wat);
+    assert!(wat.contains("i64.store offset=12"), "{}", wat);
+}
+
+#[test]
+fn recursive_user_box_methods() {
+    let code = r#"
+box Calc {
+    init { base }
+    birth(b) {
+        me.base = b
+    }
+    fib(n) {
+        if n < 2 {
+            return n
+        }
+        return me.fib(n - 1) + me.fib(n - 2)
+    }
+}
+local c = new Calc(0)
+return c.fib(10)
+"#;
+    assert_eq!(run_wasm(compile(code)), WasmValue::Integer(55));
+}
+
+#[test]
+fn array_box_grows_and_indexes() {
+    // Push 20 squares (growing past the initial capacity), overwrite one, read two back
+    let text = r#"; MIR Module: m
+define void @main() {
+bb0:
+    %0 = new ArrayBox()
+    %1 = const 0
+    %2 = const 20
+    %3 = const 1
+    br label bb1
+
+bb1:
+    %4 = phi [%1, bb0], [%6, bb2]
+    %5 = icmp Lt %4, %2
+    br %5, label bb2, label bb3
+
+bb2:
+    %7 = %4 Mul %4
+    call %0.push(%7)
+    %6 = %4 Add %3
+    br label bb1
+
+bb3:
+    %8 = const 100
+    %0[%1] = %8
+    %9 = %0[%1]
+    %10 = const 17
+    %11 = call %0.get(%10)
+    %12 = call %0.length()
+    %13 = %9 Add %11
+    %14 = %13 Add %12
+    ret %14
+}
+"#;
+    assert_eq!(run_mir_text(text), WasmValue::Integer(409));
+}
+
+#[test]
+fn direct_calls_and_recursion() {
+    let text = r#"; MIR Module: m
+define i64 @fact/1(i64 %0) {
+bb0:
+    %1 = const 1
+    %2 = icmp Le %0, %1
+    br %2, label bb1, label bb2
+
+bb1:
+    ret %1
+
+bb2:
+    %3 = %0 Sub %1
+    %4 = const "fact/1"
+    %5 = call %4(%3)
+    %6 = %0 Mul %5
+    ret %6
+}
+
+define void @main() {
+bb0:
+    %0 = const 20
+    %1 = const "fact/1"
+    %2 = call %1(%0)
+    ret %2
+}
+"#;
+    assert_eq!(run_mir_text(text), WasmValue::Integer(2_432_902_008_176_640_000));
+}
+
+#[test]
+fn phi_nodes_are_assigned_in_parallel() {
+    // Fibonacci by swapping two phis: a, b = b, a + b
+    let text = r#"; MIR Module: m
+define void @main() {
+bb0:
+    %0 = const 0
+    %1 = const 1
+    %2 = const 0
+    %3 = const 10
+    br label bb1
+
+bb1:
+    %4 = phi [%0, bb0], [%5, bb2]
+    %5 = phi [%1, bb0], [%7, bb2]
+    %6 = phi [%2, bb0], [%8, bb2]
+    %9 = icmp Lt %6, %3
+    br %9, label bb2, label bb3
+
+bb2:
+    %7 = %4 Add %5
+    %10 = const 1
+    %8 = %6 Add %10
+    br label bb1
+
+bb3:
+    ret %4
+}
+"#;
+    assert_eq!(run_mir_text(text), WasmValue::Integer(55));
+}
+
+#[test]
+fn irreducible_control_flow_is_rejected() {
+    // bb1 and bb2 jump into each other and are both entered from bb0
+    let text = r#"; MIR Module: m
+define void @main() {
+bb0:
+    %0 = const true
+    br %0, label bb1, label bb2
+
+bb1:
+    br label bb2
+
+bb2:
+    br label bb1
+}
+"#;
+    let module = MirParser::new().parse_module(text).expect("mir");
+    let error = WasmBackend::new().compile_to_wat(module).unwrap_err();
+    assert!(error.to_string().contains("Irreducible control flow"), "{}", error);
+}
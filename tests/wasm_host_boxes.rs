@@ -0,0 +1,76 @@
+#![cfg(feature = "wasm-backend")]
+//! WASM host boxes: boxes without a wasm layout live in the host's handle table and are
+//! reached through `env.box_new` / `env.box_call`
+
+use nyash_rust::backend::wasm::{link_host_functions, HostState, WasmBackend};
+use nyash_rust::mir::{MirCompiler, MirModule};
+use nyash_rust::parser::NyashParser;
+use wasmtime::{Engine, Linker, Module, Store, Val};
+
+fn compile(code: &str) -> MirModule {
+    let ast = NyashParser::parse_from_string(code).expect("parse");
+    MirCompiler::new().compile(ast).expect("compile").module
+}
+
+fn run_output(code: &str) -> Vec<String> {
+    let mut backend = WasmBackend::new();
+    let bytes = backend.compile_module(compile(code)).unwrap_or_else(|e| panic!("compile: {}", e));
+    backend.execute_wasm(&bytes).unwrap_or_else(|e| panic!("execute: {}", e)).output
+}
+
+#[test]
+fn map_box_methods_run_on_the_host() {
+    let code = r#"
+local m = new MapBox()
+m.set("name", "nyash")
+m.set("answer", 42)
+print(m.get("name"))
+print(m.get("answer"))
+print(m.size())
+print(m.has("missing"))
+print("size " + m.size().toString())
+"#;
+    assert_eq!(run_output(code), vec!["nyash", "42", "2", "false", "size 2"]);
+}
+
+#[test]
+fn host_boxes_are_passed_as_arguments() {
+    // The inner map is shared with the host, not copied
+    let code = r#"
+local outer = new MapBox()
+local inner = new MapBox()
+outer.set("inner", inner)
+inner.set("k", "v")
+print(outer.get("inner").get("k"))
+"#;
+    assert_eq!(run_output(code), vec!["v"]);
+}
+
+#[test]
+fn collected_proxies_release_their_handles() {
+    let code = r#"
+local i = 0
+local m = new MapBox()
+loop(i < 20000) {
+    m = new MapBox()
+    m.set("i", i)
+    i = i + 1
+}
+print(m.get("i"))
+"#;
+    let bytes = WasmBackend::new().compile_module(compile(code)).expect("compile");
+
+    let engine = Engine::default();
+    let module = Module::new(&engine, &bytes).expect("module");
+    let mut store = Store::new(&engine, HostState::new(false));
+    let mut linker = Linker::new(&engine);
+    link_host_functions(&mut linker).expect("host functions");
+    let instance = linker.instantiate(&mut store, &module).expect("instantiate");
+    let main = instance.get_func(&mut store, "main").expect("main");
+    let mut results: Vec<Val> = main.ty(&store).results().map(|_| Val::I32(0)).collect();
+    main.call(&mut store, &[], &mut results).expect("run");
+
+    assert_eq!(store.data().output, vec!["19999"]);
+    let live = store.data().handles.len();
+    assert!(live < 5000, "{} host handles still live", live);
+}
@@ -13,7 +13,7 @@ use nyash_rust::mir::{
     MirModule, MirFunction, FunctionSignature, MirType, EffectMask,
     BasicBlock, BasicBlockId, ValueId, MirInstruction, ConstValue, BinaryOp
 };
-use nyash_rust::backend::wasm::WasmBackend;
+use nyash_rust::backend::wasm::{WasmBackend, WasmValue};
 
 #[test]
 fn test_wasm_poc1_basic_arithmetic() {
@@ -40,9 +40,9 @@ fn test_wasm_poc1_basic_arithmetic() {
     assert!(wat_text.contains("memory"), "Should contain memory declaration");
     assert!(wat_text.contains("import"), "Should contain imports");
     assert!(wat_text.contains("$main"), "Should contain main function");
-    assert!(wat_text.contains("i32.const 42"), "Should contain constant 42");
-    assert!(wat_text.contains("i32.const 8"), "Should contain constant 8");
-    assert!(wat_text.contains("i32.add"), "Should contain addition operation");
+    assert!(wat_text.contains("i64.const 42"), "Should contain 64-bit constant 42");
+    assert!(wat_text.contains("i64.const 8"), "Should contain 64-bit constant 8");
+    assert!(wat_text.contains("i64.add"), "Should contain 64-bit addition");
     assert!(wat_text.contains("call $print"), "Should contain print call");
     assert!(wat_text.contains("return"), "Should contain return instruction");
     
@@ -61,7 +61,7 @@ fn test_wasm_poc1_basic_arithmetic() {
     assert!(execution_result.is_ok(), "WASM execution should succeed");
     
     let return_value = execution_result.unwrap();
-    assert_eq!(return_value, 50, "Should return 42 + 8 = 50");
+    assert_eq!(return_value.value, WasmValue::Integer(50), "Should return 42 + 8 = 50");
 }
 
 #[test]  
@@ -74,7 +74,7 @@ fn test_wasm_poc1_multiplication() {
     assert!(wasm_result.is_ok(), "WASM compilation should succeed");
     
     let return_value = backend.execute_wasm(&wasm_result.unwrap()).unwrap();
-    assert_eq!(return_value, 42, "Should return 6 * 7 = 42");
+    assert_eq!(return_value.value, WasmValue::Integer(42), "Should return 6 * 7 = 42");
 }
 
 #[test]
@@ -87,7 +87,7 @@ fn test_wasm_poc1_subtraction() {
     assert!(wasm_result.is_ok(), "WASM compilation should succeed");
     
     let return_value = backend.execute_wasm(&wasm_result.unwrap()).unwrap();
-    assert_eq!(return_value, 42, "Should return 50 - 8 = 42");
+    assert_eq!(return_value.value, WasmValue::Integer(42), "Should return 50 - 8 = 42");
 }
 
 /// Build MIR module for: 42 + 8
@@ -15,7 +15,7 @@ use nyash_rust::mir::{
     MirModule, MirFunction, FunctionSignature, MirType, EffectMask,
     BasicBlock, BasicBlockId, ValueId, MirInstruction, ConstValue
 };
-use nyash_rust::backend::wasm::WasmBackend;
+use nyash_rust::backend::wasm::{WasmBackend, WasmValue};
 
 #[test]
 fn test_wasm_poc2_refnew_basic() {
@@ -40,7 +40,7 @@ fn test_wasm_poc2_refnew_basic() {
     assert!(wat_text.contains("$malloc"), "Should contain malloc function");
     assert!(wat_text.contains("$alloc_databox"), "Should contain DataBox allocator");
     assert!(wat_text.contains("call $alloc_databox"), "Should call DataBox allocator");
-    assert!(wat_text.contains("i32.store"), "Should store field values");
+    assert!(wat_text.contains("i64.store"), "Should store the 64-bit field value");
     
     // Compile to WASM binary and execute
     let wasm_result = backend.compile_module(mir_module);
@@ -56,9 +56,9 @@ fn test_wasm_poc2_refnew_basic() {
     let execution_result = backend.execute_wasm(&wasm_bytes);
     assert!(execution_result.is_ok(), "WASM execution should succeed");
     
-    let return_value = execution_result.unwrap();
+    let return_value = execution_result.unwrap().value;
     // Should return a valid pointer (greater than heap start 0x800)
-    assert!(return_value >= 0x800, "Should return valid Box pointer: {}", return_value);
+    assert!(matches!(return_value, WasmValue::Box(ptr) if ptr >= 0x800), "Should return valid Box pointer: {:?}", return_value);
 }
 
 #[test]
@@ -79,7 +79,7 @@ fn test_wasm_poc2_refget_refset() {
     assert!(wasm_result.is_ok(), "WASM compilation should succeed");
     
     let return_value = backend.execute_wasm(&wasm_result.unwrap()).unwrap();
-    assert_eq!(return_value, 42, "Should return updated field value");
+    assert_eq!(return_value.value, WasmValue::Integer(42), "Should return updated field value");
 }
 
 #[test]
@@ -105,7 +105,7 @@ fn test_wasm_poc2_complete_box_workflow() {
     assert!(wasm_result.is_ok(), "WASM compilation should succeed");
     
     let return_value = backend.execute_wasm(&wasm_result.unwrap()).unwrap();
-    assert_eq!(return_value, 300, "Should return sum of Box values");
+    assert_eq!(return_value.value, WasmValue::Integer(300), "Should return sum of Box values");
 }
 
 /// Build MIR module for basic RefNew test
@@ -0,0 +1,146 @@
+#![cfg(feature = "wasm-backend")]
+//! WASM backend values: i64/f64 arithmetic and StringBox strings, checked against the VM
+
+use nyash_rust::backend::wasm::{WasmBackend, WasmExecution, WasmValue};
+use nyash_rust::backend::VM;
+use nyash_rust::mir::{MirCompiler, MirModule, MirParser};
+use nyash_rust::parser::NyashParser;
+
+fn compile(code: &str) -> MirModule {
+    let ast = NyashParser::parse_from_string(code).expect("parse");
+    MirCompiler::new().compile(ast).expect("compile").module
+}
+
+fn run_wasm(module: MirModule) -> WasmExecution {
+    let mut backend = WasmBackend::new();
+    let bytes = backend.compile_module(module).unwrap_or_else(|e| panic!("compile: {}", e));
+    backend.execute_wasm(&bytes).unwrap_or_else(|e| panic!("execute: {}", e))
+}
+
+fn run_mir_text(text: &str) -> WasmValue {
+    run_wasm(MirParser::new().parse_module(text).expect("mir")).value
+}
+
+#[test]
+fn programs_match_the_vm() {
+    let programs = [
+        "local a = 3000000000\nlocal b = a * 4\nreturn b",
+        "return 7 - 10 * 3",
+        "local x = 100\nreturn x / 7",
+        "local big = 9000000000000000000\nreturn big - 1",
+        "return 5 < 9",
+        "local a = 4000000000\nreturn a >= 4000000001",
+        r#"local s = "n=" + 12000000000
+return s"#,
+        r#"return "a" + "b" + "c""#,
+        r#"return "x" + true"#,
+        r#"local s = "count: " + (0 - 42)
+return s"#,
+        r#"local a = "abc"
+local b = "abc"
+return a == b"#,
+        r#"local s = "hello" + " " + "world"
+return s.length()"#,
+    ];
+    for code in programs {
+        let module = compile(code);
+        let vm = VM::new().execute_module(&module).expect("vm").to_string_box().value;
+        let wasm = run_wasm(module).value;
+        assert_eq!(wasm.to_string(), vm, "{}", code);
+    }
+}
+
+#[test]
+fn integers_are_not_truncated() {
+    let value = run_wasm(compile("local a = 3000000000\nreturn a + a")).value;
+    assert_eq!(value, WasmValue::Integer(6_000_000_000));
+
+    let wat = WasmBackend::new().compile_to_wat(compile("return 3000000000")).unwrap();
+    assert!(wat.contains("i64.const 3000000000"), "{}", wat);
+    assert!(wat.contains("(result i64)"), "{}", wat);
+}
+
+#[test]
+fn print_uses_the_value_type() {
+    let code = r#"local n = 5000000000
+print(n)
+print(n > 1)
+print("total " + n)
+local f = 1.5
+print(f)
+return 0"#;
+    let execution = run_wasm(compile(code));
+    assert_eq!(execution.output, vec!["5000000000", "true", "total 5000000000", "1.5"]);
+    assert_eq!(execution.value, WasmValue::Integer(0));
+}
+
+#[test]
+fn float_arithmetic_and_mixed_operands() {
+    let module = |body: &str| format!("; MIR Module: m\ndefine void @main() {{\nbb0:\n{}\n}}\n", body);
+    let cases = [
+        ("    %0 = const 7.5\n    %1 = const 2\n    %2 = %0 Mod %1\n    ret %2", WasmValue::Float(1.5)),
+        ("    %0 = const -7.5\n    %1 = const 2.0\n    %2 = %0 Mod %1\n    ret %2", WasmValue::Float(-1.5)),
+        ("    %0 = const 1.5\n    %1 = const 2\n    %2 = %0 Mul %1\n    ret %2", WasmValue::Float(3.0)),
+        ("    %0 = const 1\n    %1 = const 4.0\n    %2 = %0 Div %1\n    ret %2", WasmValue::Float(0.25)),
+        ("    %0 = const 2.5\n    %1 = Neg %0\n    ret %1", WasmValue::Float(-2.5)),
+        ("    %0 = const 2\n    %1 = const 2.5\n    %2 = icmp Lt %0, %1\n    ret %2", WasmValue::Bool(true)),
+    ];
+    for (body, expected) in cases {
+        assert_eq!(run_mir_text(&module(body)), expected, "{}", body);
+    }
+    assert_eq!(WasmValue::Float(3.0).to_string(), "3");
+}
+
+#[test]
+fn integer_and_bitwise_operators() {
+    let module = |body: &str| format!("; MIR Module: m\ndefine void @main() {{\nbb0:\n{}\n}}\n", body);
+    let binop = |lhs: i64, op: &str, rhs: i64| {
+        run_mir_text(&module(&format!("    %0 = const {}\n    %1 = const {}\n    %2 = %0 {} %1\n    ret %2", lhs, rhs, op)))
+    };
+    assert_eq!(binop(-17, "Mod", 5), WasmValue::Integer(-2));
+    assert_eq!(binop(-17, "Div", 5), WasmValue::Integer(-3));
+    assert_eq!(binop(0b1100, "BitAnd", 0b1010), WasmValue::Integer(0b1000));
+    assert_eq!(binop(0b1100, "BitOr", 0b1010), WasmValue::Integer(0b1110));
+    assert_eq!(binop(0b1100, "BitXor", 0b1010), WasmValue::Integer(0b0110));
+    assert_eq!(binop(1, "Shl", 40), WasmValue::Integer(1 << 40));
+    assert_eq!(binop(-64, "Shr", 3), WasmValue::Integer(-8));
+
+    assert_eq!(run_mir_text(&module("    %0 = const 5\n    %1 = BitNot %0\n    ret %1")), WasmValue::Integer(!5));
+    assert_eq!(run_mir_text(&module("    %0 = const 5\n    %1 = Neg %0\n    ret %1")), WasmValue::Integer(-5));
+    assert_eq!(run_mir_text(&module("    %0 = const true\n    %1 = Not %0\n    ret %1")), WasmValue::Bool(false));
+    assert_eq!(
+        run_mir_text(&module("    %0 = const true\n    %1 = const false\n    %2 = %0 Or %1\n    ret %2")),
+        WasmValue::Bool(true)
+    );
+}
+
+#[test]
+fn string_comparison_orders_by_bytes() {
+    let module = |lhs: &str, op: &str, rhs: &str| format!(
+        "; MIR Module: m\ndefine void @main() {{\nbb0:\n    %0 = const \"{}\"\n    %1 = const \"{}\"\n    %2 = icmp {} %0, %1\n    ret %2\n}}\n",
+        lhs, rhs, op
+    );
+    let cases = [
+        ("abc", "Lt", "abd", true),
+        ("abc", "Lt", "ab", false),
+        ("ab", "Lt", "abc", true),
+        ("abc", "Eq", "abc", true),
+        ("abc", "Ne", "abc", false),
+        ("b", "Gt", "abc", true),
+        ("", "Le", "", true),
+    ];
+    for (lhs, op, rhs, expected) in cases {
+        assert_eq!(run_mir_text(&module(lhs, op, rhs)), WasmValue::Bool(expected), "{:?} {} {:?}", lhs, op, rhs);
+    }
+}
+
+#[test]
+fn concatenation_does_not_clobber_literals() {
+    // Enough allocations to run past the string data; the literal must survive
+    let mut code = String::from("local s = \"x\"\n");
+    for _ in 0..300 {
+        code.push_str("s = \"ab\" + 1234567\n");
+    }
+    code.push_str("return \"lit\" + s");
+    assert_eq!(run_wasm(compile(&code)).value, WasmValue::String("litab1234567".to_string()));
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "wasm-backend")]
+//! `--compile-wasm --target web`: the bundled JS loader must implement every `env.*` import
+//! of the module with the module's signature
+
+use nyash_rust::backend::wasm::{loader_imports, WasmBackend, WebBundle, LOADER_NAME, LOADER_SOURCE};
+use nyash_rust::mir::{MirCompiler, MirModule};
+use nyash_rust::parser::NyashParser;
+use wasmtime::{Engine, ExternType, Module};
+
+fn compile(code: &str) -> MirModule {
+    let ast = NyashParser::parse_from_string(code).expect("parse");
+    MirCompiler::new().compile(ast).expect("compile").module
+}
+
+fn bundle(code: &str) -> WebBundle {
+    WebBundle::build(&mut WasmBackend::new(), compile(code), "app").unwrap_or_else(|e| panic!("bundle: {}", e))
+}
+
+#[test]
+fn loader_implements_every_env_import() {
+    let bundle = bundle(r#"
+local m = new MapBox()
+m.set("k", 1)
+print(m.get("k"))
+print("done")
+"#);
+    let engine = Engine::default();
+    let module = Module::new(&engine, &bundle.wasm).expect("module");
+    let implemented = loader_imports(LOADER_SOURCE);
+
+    let mut checked = 0;
+    for import in module.imports() {
+        assert_eq!(import.module(), "env", "unexpected import module for {}", import.name());
+        let ExternType::Func(func) = import.ty() else {
+            panic!("env.{} is not a function import", import.name());
+        };
+        let params = implemented.get(import.name())
+            .unwrap_or_else(|| panic!("{} does not implement env.{}", LOADER_NAME, import.name()));
+        assert_eq!(*params, func.params().len(), "parameter count of env.{}", import.name());
+        checked += 1;
+    }
+    assert!(checked > 0);
+    for name in ["box_new", "box_call", "box_release", "box_to_string", "canvas_fillRect"] {
+        assert!(module.imports().any(|i| i.name() == name), "module does not import env.{}", name);
+    }
+}
+
+#[test]
+fn bundle_files_and_function_map() {
+    let bundle = bundle("print(1 + 2)");
+    assert!(bundle.index_html().contains("\"./app.wasm\""));
+
+    let map: serde_json::Value = serde_json::from_str(&bundle.source_map).expect("map json");
+    assert_eq!(map["file"], "app.wasm");
+    let functions = map["functions"].as_array().expect("functions");
+    let main = functions.iter().find(|f| f["name"] == "main").expect("main in map");
+    let (start, end) = (main["start"].as_u64().unwrap(), main["end"].as_u64().unwrap());
+    assert!(start < end && end as usize <= bundle.wasm.len());
+
+    let dir = std::env::temp_dir().join(format!("nyash_web_bundle_{}", std::process::id()));
+    let files = bundle.write_to(&dir).expect("write bundle");
+    let names: Vec<_> = files.iter().map(|f| f.file_name().unwrap().to_string_lossy().into_owned()).collect();
+    assert_eq!(names, vec!["app.wasm", "app.wasm.map", "nyash.js", "index.html"]);
+    let _ = std::fs::remove_dir_all(dir);
+}
@@ -0,0 +1,197 @@
+//! WebSocket: HTTPServerBox upgrade routes and the WebSocketBox client
+
+use nyash_rust::box_trait::{IntegerBox, NyashBox, StringBox};
+use nyash_rust::boxes::websocket_box::accept_key;
+use nyash_rust::boxes::{BufferBox, WebSocketBox};
+use nyash_rust::interpreter::NyashInterpreter;
+use nyash_rust::parser::NyashParser;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
+
+fn s(v: &str) -> Box<dyn NyashBox> {
+    Box::new(StringBox::new(v))
+}
+
+fn wait_for(port: u16) {
+    for _ in 0..100 {
+        if TcpStream::connect(("127.0.0.1", port)).is_ok() {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    panic!("server on port {} did not come up", port);
+}
+
+/// HTTPServerBox with an echo WebSocket route and a plain HTTP route
+fn spawn_ws_server(port: u16) {
+    let code = format!(r#"
+box Chat {{
+    init {{ count }}
+    birth() {{
+        me.count = 0
+    }}
+    onMessage(msg, socket) {{
+        me.count = me.count + 1
+        if msg == "bye" {{
+            socket.close(4000, "requested")
+            return
+        }}
+        if isType(msg, "BufferBox") {{
+            return msg
+        }}
+        socket.send("ack " + me.count)
+        return "echo:" + msg
+    }}
+    hello(req) {{
+        return "plain http"
+    }}
+}}
+
+static box Main {{
+    main() {{
+        local server = new HTTPServerBox()
+        server.bind("127.0.0.1", {port})
+        local chat = new Chat()
+        server.websocket("/chat", new MethodBox(chat, "onMessage"))
+        server.get("/hello", new MethodBox(chat, "hello"))
+        server.start()
+        return 0
+    }}
+}}
+"#);
+    std::thread::spawn(move || {
+        let ast = NyashParser::parse_from_string(&code).expect("parse");
+        let mut interpreter = NyashInterpreter::new();
+        let _ = interpreter.execute(ast);
+    });
+    wait_for(port);
+}
+
+#[test]
+fn accept_key_matches_rfc_sample() {
+    assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+}
+
+#[test]
+fn websocket_client_talks_to_server_route() {
+    let port = free_port();
+    spawn_ws_server(port);
+    let ws = WebSocketBox::new();
+    assert!(ws.connect(s(&format!("ws://127.0.0.1:{}/chat", port))).to_string_box().value.contains("Ok"));
+    ws.set_timeout(Box::new(IntegerBox::new(5000)));
+
+    // Text: the handler pushes one message itself and returns another
+    assert_eq!(ws.send(s("hi")).to_string_box().value, "true");
+    assert_eq!(ws.receive().to_string_box().value, "ack 1");
+    assert_eq!(ws.receive().to_string_box().value, "echo:hi");
+
+    // Binary round trip as BufferBox
+    assert_eq!(ws.send(Box::new(BufferBox::from_vec(vec![0, 1, 2, 255]))).to_string_box().value, "true");
+    let reply = ws.receive();
+    let buffer = reply.as_any().downcast_ref::<BufferBox>().expect("binary reply");
+    assert_eq!(buffer.to_vec(), vec![0, 1, 2, 255]);
+
+    // Large text exercises the 16-bit and 64-bit length encodings
+    let big = "x".repeat(70_000);
+    ws.send(s(&big));
+    assert_eq!(ws.receive().to_string_box().value, "ack 3");
+    assert_eq!(ws.receive().to_string_box().value, format!("echo:{}", big));
+
+    assert_eq!(ws.ping().to_string_box().value, "true");
+
+    // Server-initiated close
+    ws.send(s("bye"));
+    assert_eq!(ws.receive().type_name(), "VoidBox");
+    assert_eq!(ws.is_open().to_string_box().value, "false");
+    assert_eq!(ws.get_close_code().to_string_box().value, "4000");
+    assert_eq!(ws.send(s("late")).to_string_box().value, "false");
+
+    // Unknown paths and plain routes are not upgraded
+    let ws = WebSocketBox::new();
+    let result = ws.connect(s(&format!("ws://127.0.0.1:{}/nope", port)));
+    assert!(result.to_string_box().value.contains("404"), "{}", result.to_string_box().value);
+    let result = ws.connect(s(&format!("ws://127.0.0.1:{}/hello", port)));
+    assert!(result.to_string_box().value.contains("404"), "{}", result.to_string_box().value);
+    assert!(ws.connect(s("wss://127.0.0.1/")).to_string_box().value.contains("only ws://"));
+}
+
+#[test]
+fn websocket_server_handles_raw_frames() {
+    let port = free_port();
+    spawn_ws_server(port);
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    stream.write_all(b"GET /chat HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n").unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut head = String::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        head.push_str(&line);
+        if line == "\r\n" {
+            break;
+        }
+    }
+    assert!(head.starts_with("HTTP/1.1 101 "), "{}", head);
+    assert!(head.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"), "{}", head);
+
+    let masked = |opcode: u8, fin: bool, payload: &[u8]| {
+        let key = [1u8, 2, 3, 4];
+        let mut frame = vec![if fin { 0x80 } else { 0 } | opcode, 0x80 | payload.len() as u8];
+        frame.extend_from_slice(&key);
+        frame.extend(payload.iter().zip(key.iter().cycle()).map(|(b, k)| b ^ k));
+        frame
+    };
+    let mut read_frame = || {
+        let mut h = [0u8; 2];
+        reader.read_exact(&mut h).unwrap();
+        assert_eq!(h[1] & 0x80, 0, "server frames must not be masked");
+        let mut payload = vec![0u8; (h[1] & 0x7f) as usize];
+        reader.read_exact(&mut payload).unwrap();
+        (h[0], payload)
+    };
+
+    // Ping is answered with a Pong carrying the same payload
+    stream.write_all(&masked(0x9, true, b"are you there")).unwrap();
+    assert_eq!(read_frame(), (0x8A, b"are you there".to_vec()));
+
+    // A fragmented text message (with a ping in between) reaches the handler as one message
+    stream.write_all(&masked(0x1, false, b"frag")).unwrap();
+    stream.write_all(&masked(0x9, true, b"")).unwrap();
+    stream.write_all(&masked(0x0, true, b"mented")).unwrap();
+    assert_eq!(read_frame(), (0x8A, vec![]));
+    assert_eq!(read_frame(), (0x81, b"ack 1".to_vec()));
+    assert_eq!(read_frame(), (0x81, b"echo:fragmented".to_vec()));
+
+    // Client-initiated close is echoed with the same code
+    stream.write_all(&masked(0x8, true, &1000u16.to_be_bytes())).unwrap();
+    assert_eq!(read_frame(), (0x88, 1000u16.to_be_bytes().to_vec()));
+    let mut rest = Vec::new();
+    assert_eq!(reader.read_to_end(&mut rest).unwrap(), 0);
+}
+
+#[test]
+fn websocket_from_nyash_client() {
+    let port = free_port();
+    spawn_ws_server(port);
+    let code = format!(r#"
+local ws = new WebSocketBox()
+local r = ws.connect("ws://127.0.0.1:{port}/chat")
+ws.setTimeout(5000)
+ws.send("from nyash")
+local first = ws.receive()
+local second = ws.receive()
+ws.close()
+result = r.isOk().toString() + " " + first + " " + second + " " + ws.isOpen().toString()
+"#);
+    let ast = NyashParser::parse_from_string(&code).expect("parse");
+    let mut interpreter = NyashInterpreter::new();
+    interpreter.execute(ast).expect("execute");
+    let result = interpreter.get_variable("result").expect("result");
+    assert_eq!(result.to_string_box().value, "true ack 1 echo:from nyash false");
+}
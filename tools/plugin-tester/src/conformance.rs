@@ -0,0 +1,271 @@
+//! conformance: 宣言された全メソッドを呼び、BID-1のエラーコード規約を検査する
+//!
+//! - E_SHORT: 結果バッファ無しの呼び出しは -1 + 必要サイズ、そのサイズで再呼び出しすると成功
+//! - 未知のmethod_id → -3、未知のtype_id → -2
+//! - fini は冪等（2回目も 0）、fini後のハンドルは -8
+//! - singleton: fini後の再birthで新しい生存インスタンスが得られる
+
+use crate::{resolve_targets, tlv_encode, tlv_validate, BoxTarget, InvokeFn, MethodDefinition, TargetArgs};
+use colored::*;
+use libloading::Library;
+use std::sync::mpsc;
+use std::time::Duration;
+
+pub(crate) const KNOWN_CODES: [i32; 10] = [0, -1, -2, -3, -4, -5, -6, -7, -8, -9];
+pub(crate) const METHOD_FINI: u32 = u32::MAX;
+const RESULT_CAP: usize = 64 * 1024;
+
+struct Outcome {
+    rc: i32,
+    out: Vec<u8>,
+    out_len: usize,
+}
+
+/// Call through a helper thread so a blocking method cannot stall the run.
+/// `cap = None` passes a null result buffer with `*result_len = 0`.
+fn call(invoke: InvokeFn, type_id: u32, method_id: u32, instance_id: u32, args: &[u8], cap: Option<usize>, timeout: Duration) -> Option<Outcome> {
+    let args = args.to_vec();
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut out = vec![0u8; cap.unwrap_or(0)];
+        let mut out_len = out.len();
+        let out_ptr = if cap.is_some() { out.as_mut_ptr() } else { std::ptr::null_mut() };
+        let rc = unsafe { invoke(type_id, method_id, instance_id, args.as_ptr(), args.len(), out_ptr, &mut out_len) };
+        let _ = tx.send(Outcome { rc, out, out_len });
+    });
+    rx.recv_timeout(timeout).ok()
+}
+
+#[derive(Default)]
+struct Report {
+    passed: usize,
+    failures: Vec<String>,
+}
+
+impl Report {
+    fn check(&mut self, ok: bool, what: impl Into<String>) -> bool {
+        let what = what.into();
+        if ok {
+            println!("    {} {}", "✓".green(), what);
+            self.passed += 1;
+        } else {
+            println!("    {} {}", "✗".red(), what);
+            self.failures.push(what);
+        }
+        ok
+    }
+
+    fn note(&self, what: impl AsRef<str>) {
+        println!("    {} {}", "-".yellow(), what.as_ref());
+    }
+}
+
+/// Schema-conforming TLV arguments for a declared method
+fn build_args(method: &MethodDefinition, type_id: u32, peer_instance: u32) -> Vec<u8> {
+    let mut entries: Vec<(u8, Vec<u8>)> = Vec::new();
+    for decl in method.args.as_deref().unwrap_or(&[]) {
+        let kind = match decl {
+            toml::Value::Table(t) => t.get("kind").and_then(|k| k.as_str()).unwrap_or("string").to_string(),
+            _ => "string".to_string(),
+        };
+        entries.push(match kind.as_str() {
+            "bool" => (1, vec![1]),
+            "i32" | "int" => (2, 1i32.to_le_bytes().to_vec()),
+            "i64" => (3, 1i64.to_le_bytes().to_vec()),
+            "f64" => (5, 1.0f64.to_le_bytes().to_vec()),
+            "bytes" => (7, b"nyash".to_vec()),
+            "box" => {
+                let mut h = type_id.to_le_bytes().to_vec();
+                h.extend_from_slice(&peer_instance.to_le_bytes());
+                (8, h)
+            }
+            _ => (6, b"nyash".to_vec()),
+        });
+    }
+    tlv_encode(&entries)
+}
+
+fn birth(invoke: InvokeFn, type_id: u32, timeout: Duration) -> Option<u32> {
+    let o = call(invoke, type_id, 0, 0, &tlv_encode(&[]), Some(1024), timeout)?;
+    if o.rc != 0 || o.out_len < 4 {
+        return None;
+    }
+    let id = u32::from_le_bytes([o.out[0], o.out[1], o.out[2], o.out[3]]);
+    (id != 0).then_some(id)
+}
+
+fn check_box(invoke: InvokeFn, target: &BoxTarget, skip: &[String], timeout: Duration, report: &mut Report) {
+    let type_id = target.config.type_id;
+    let name = &target.box_name;
+    println!("\n  {}: {} (type_id={}{})", "Box Type".bold(), name.cyan(), type_id, if target.config.singleton { ", singleton" } else { "" });
+
+    let mut methods: Vec<(&String, &MethodDefinition)> = target
+        .config
+        .methods
+        .iter()
+        .filter(|(n, m)| m.method_id != 0 && m.method_id != METHOD_FINI && !skip.contains(n))
+        .collect();
+    methods.sort_by_key(|(_, m)| m.method_id);
+    for s in skip {
+        if target.config.methods.contains_key(s) {
+            report.note(format!("{}.{} skipped", name, s));
+        }
+    }
+
+    // 1. birth: E_SHORT negotiation, then a real instance
+    match call(invoke, type_id, 0, 0, &tlv_encode(&[]), None, timeout) {
+        Some(o) => {
+            report.check(o.rc == -1 && o.out_len >= 4, format!("{}.birth without buffer -> E_SHORT with size >= 4 (rc={}, len={})", name, o.rc, o.out_len));
+        }
+        None => {
+            report.check(false, format!("{}.birth timed out", name));
+            return;
+        }
+    }
+    let Some(instance) = birth(invoke, type_id, timeout) else {
+        report.check(false, format!("{}.birth returns a non-zero instance id", name));
+        return;
+    };
+    report.check(true, format!("{}.birth -> instance {}", name, instance));
+    let peer = birth(invoke, type_id, timeout).unwrap_or(instance);
+
+    // 2. every declared method: known rc, E_SHORT negotiation, well-formed TLV
+    for (mname, m) in &methods {
+        let args = build_args(m, type_id, peer);
+        let label = format!("{}.{}", name, mname);
+        let Some(probe) = call(invoke, type_id, m.method_id, instance, &args, None, timeout) else {
+            report.check(false, format!("{} timed out (blocking? use --skip {})", label, mname));
+            continue;
+        };
+        if !report.check(KNOWN_CODES.contains(&probe.rc), format!("{} returns a BID-1 code (rc={})", label, probe.rc)) {
+            continue;
+        }
+        let outcome = match probe.rc {
+            -1 => {
+                if !report.check(probe.out_len > 0 && probe.out_len <= RESULT_CAP, format!("{} E_SHORT reports required size ({})", label, probe.out_len)) {
+                    continue;
+                }
+                match call(invoke, type_id, m.method_id, instance, &args, Some(probe.out_len), timeout) {
+                    Some(o) => {
+                        report.check(o.rc != -1 || o.out_len > probe.out_len, format!("{} succeeds with the negotiated size (rc={})", label, o.rc));
+                        o
+                    }
+                    None => {
+                        report.check(false, format!("{} timed out on retry", label));
+                        continue;
+                    }
+                }
+            }
+            0 => {
+                report.check(probe.out_len == 0, format!("{} without buffer writes nothing (len={})", label, probe.out_len));
+                probe
+            }
+            rc => {
+                report.note(format!("{} -> rc={} with generated args", label, rc));
+                probe
+            }
+        };
+        if outcome.rc == 0 && outcome.out_len > 0 {
+            let body = &outcome.out[..outcome.out_len.min(outcome.out.len())];
+            match tlv_validate(body) {
+                Ok(()) => report.check(true, format!("{} result is well-formed TLV", label)),
+                Err(e) => report.check(false, format!("{} result TLV: {}", label, e)),
+            };
+        }
+    }
+
+    // 3. unknown method / type
+    if let Some(o) = call(invoke, type_id, 0x7fff_fff0, instance, &tlv_encode(&[]), Some(1024), timeout) {
+        report.check(o.rc == -3, format!("{} unknown method_id -> E_INV_METHOD (rc={})", name, o.rc));
+    }
+    if let Some(o) = call(invoke, u32::MAX - 1, 0, 0, &tlv_encode(&[]), Some(1024), timeout) {
+        report.check(o.rc == -2, format!("{} unknown type_id -> E_INV_TYPE (rc={})", name, o.rc));
+    }
+
+    // 4. fini is idempotent; the handle is dead afterwards
+    for round in ["fini", "second fini"] {
+        match call(invoke, type_id, METHOD_FINI, instance, &tlv_encode(&[]), Some(1024), timeout) {
+            Some(o) => report.check(o.rc == 0, format!("{}.{} -> 0 (rc={})", name, round, o.rc)),
+            None => report.check(false, format!("{}.{} timed out", name, round)),
+        };
+    }
+    for (mname, m) in &methods {
+        let args = build_args(m, type_id, peer);
+        if let Some(o) = call(invoke, type_id, m.method_id, instance, &args, Some(RESULT_CAP), timeout) {
+            report.check(o.rc == -8, format!("{}.{} after fini -> E_INV_HANDLE (rc={})", name, mname, o.rc));
+        }
+    }
+
+    // 5. singleton: the loader re-births after shutdown, which must yield a live instance
+    if target.config.singleton {
+        match birth(invoke, type_id, timeout) {
+            Some(again) => {
+                report.check(again != instance, format!("{} re-birth after fini yields a fresh id ({})", name, again));
+                if let Some((mname, m)) = methods.first() {
+                    if let Some(o) = call(invoke, type_id, m.method_id, again, &build_args(m, type_id, peer), Some(RESULT_CAP), timeout) {
+                        report.check(o.rc != -8, format!("{}.{} on the re-birthed instance is live (rc={})", name, mname, o.rc));
+                    }
+                }
+                let _ = call(invoke, type_id, METHOD_FINI, again, &tlv_encode(&[]), Some(1024), timeout);
+            }
+            None => {
+                report.check(false, format!("{} re-birth after fini", name));
+            }
+        }
+    }
+    if peer != instance {
+        let _ = call(invoke, type_id, METHOD_FINI, peer, &tlv_encode(&[]), Some(1024), timeout);
+    }
+}
+
+pub fn run(args: &TargetArgs, skip: &[String]) -> bool {
+    println!("{}", "=== Plugin Conformance (BID-1 contract) ===".bold());
+    let targets = match resolve_targets(args) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("{}: {}", "ERROR".red(), e);
+            return false;
+        }
+    };
+    let timeout = Duration::from_millis(args.timeout_ms);
+    let mut report = Report::default();
+    let mut loaded: Vec<(std::path::PathBuf, Library)> = Vec::new();
+
+    for target in &targets {
+        if !loaded.iter().any(|(p, _)| p == &target.lib_path) {
+            match unsafe { Library::new(&target.lib_path) } {
+                Ok(lib) => {
+                    println!("\n{}: {}", "Library".bold(), target.lib_path.display());
+                    if let Ok(init) = unsafe { lib.get::<unsafe extern "C" fn() -> i32>(b"nyash_plugin_init") } {
+                        let rc = unsafe { init() };
+                        report.check(rc == 0, format!("nyash_plugin_init -> 0 (rc={})", rc));
+                    }
+                    loaded.push((target.lib_path.clone(), lib));
+                }
+                Err(e) => {
+                    report.check(false, format!("load {}: {}", target.lib_path.display(), e));
+                    continue;
+                }
+            }
+        }
+        let lib = &loaded.iter().find(|(p, _)| p == &target.lib_path).unwrap().1;
+        let invoke: InvokeFn = match unsafe { lib.get::<InvokeFn>(b"nyash_plugin_invoke") } {
+            Ok(f) => *f,
+            Err(_) => {
+                report.check(false, "nyash_plugin_invoke exported");
+                continue;
+            }
+        };
+        check_box(invoke, target, skip, timeout, &mut report);
+    }
+
+    println!("\n{}: {} passed, {} failed", "Conformance".bold(), report.passed, report.failures.len());
+    for f in &report.failures {
+        println!("  {} {}", "✗".red(), f);
+    }
+    // Blocked helper threads may still be inside the plugin; do not unload it under them
+    for (_, lib) in loaded {
+        std::mem::forget(lib);
+    }
+    report.failures.is_empty()
+}
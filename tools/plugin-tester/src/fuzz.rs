@@ -0,0 +1,336 @@
+//! fuzz: 壊れたTLVを全メソッドに送り、クラッシュと規約違反を報告する
+//!
+//! ケースは子プロセス（隠しサブコマンド `fuzz-worker`）で実行する。
+//! 子は各呼び出しの前に `CASE <idx>` を出力するので、プラグインが落ちた/固まった
+//! ケースを親が特定でき、次のケースから子を起動し直して続行する。
+//! 同じ seed なら同じケース列になる（再現用）。
+
+use crate::conformance::{KNOWN_CODES, METHOD_FINI};
+use crate::{resolve_targets, tlv_encode, InvokeFn, TargetArgs};
+use colored::*;
+use libloading::Library;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+const MARK: &str = "@@nyash-fuzz";
+const RESULT_CAP: usize = 4096;
+const CANARY: usize = 64;
+
+/// Deterministic xorshift64 (no rand dependency)
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+fn entry(tag: u8, size: u16, payload: &[u8]) -> Vec<u8> {
+    let mut e = vec![tag, 0];
+    e.extend_from_slice(&size.to_le_bytes());
+    e.extend_from_slice(payload);
+    e
+}
+
+fn with_header(ver: u16, argc: u16, body: &[u8]) -> Vec<u8> {
+    let mut buf = ver.to_le_bytes().to_vec();
+    buf.extend_from_slice(&argc.to_le_bytes());
+    buf.extend_from_slice(body);
+    buf
+}
+
+/// Fixed malformed set followed by `iterations` random buffers
+fn fuzz_cases(seed: u64, iterations: usize) -> Vec<(String, Vec<u8>)> {
+    let mut cases: Vec<(String, Vec<u8>)> = vec![
+        ("empty args".into(), vec![]),
+        ("short header".into(), vec![1, 0]),
+        ("wrong version".into(), with_header(2, 0, &[])),
+        ("version 0".into(), with_header(0, 1, &entry(6, 1, b"a"))),
+        ("argc without entries".into(), with_header(1, 3, &[])),
+        ("huge argc".into(), with_header(1, u16::MAX, &entry(6, 1, b"a"))),
+        ("truncated entry header".into(), with_header(1, 1, &[6, 0])),
+        ("size past end".into(), with_header(1, 1, &entry(6, u16::MAX, b"a"))),
+        ("second entry truncated".into(), with_header(1, 2, &[entry(6, 1, b"a"), entry(6, 8, b"ab")].concat())),
+        ("trailing garbage".into(), [tlv_encode(&[]), vec![0xde, 0xad, 0xbe, 0xef]].concat()),
+        ("string not utf-8".into(), with_header(1, 1, &entry(6, 2, &[0xff, 0xfe]))),
+        ("i32 zero size".into(), with_header(1, 1, &entry(2, 0, &[]))),
+        ("bogus handle".into(), with_header(1, 1, &entry(8, 8, &[0xef, 0xbe, 0xad, 0xde, 0xff, 0xff, 0xff, 0x7f]))),
+        ("handle type 0 instance 0".into(), with_header(1, 1, &entry(8, 8, &[0; 8]))),
+    ];
+    // Every tag (known and unknown) with a size that fits none of the fixed-width kinds
+    for tag in [0u8, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 0x7f, 0xff] {
+        cases.push((format!("tag {} size 3", tag), with_header(1, 1, &entry(tag, 3, &[1, 2, 3]))));
+    }
+
+    let mut rng = XorShift(seed | 1);
+    for i in 0..iterations {
+        let len = rng.below(64) as usize;
+        let body: Vec<u8> = (0..len).map(|_| rng.next() as u8).collect();
+        let buf = if rng.below(2) == 0 {
+            // Plausible header so the plugin gets past the first check
+            with_header(1, rng.below(4) as u16, &body)
+        } else {
+            body
+        };
+        cases.push((format!("random #{}", i), buf));
+    }
+    cases
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" ")
+}
+
+/// (box, method, type_id, method_id) per library, in the order the worker runs them
+struct LibPlan {
+    path: PathBuf,
+    methods: Vec<(String, String, u32, u32)>,
+}
+
+enum Finding {
+    Crash(String),
+    Hang,
+    Violation(String),
+}
+
+pub fn run(args: &TargetArgs, skip: &[String], seed: u64, iterations: usize) -> bool {
+    println!("{}", "=== Plugin Fuzz (malformed TLV) ===".bold());
+    let targets = match resolve_targets(args) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("{}: {}", "ERROR".red(), e);
+            return false;
+        }
+    };
+    let mut plans: Vec<LibPlan> = Vec::new();
+    for t in targets {
+        let idx = match plans.iter().position(|p| p.path == t.lib_path) {
+            Some(i) => i,
+            None => {
+                plans.push(LibPlan { path: t.lib_path.clone(), methods: Vec::new() });
+                plans.len() - 1
+            }
+        };
+        let mut methods: Vec<_> = t.config.methods.iter().filter(|(n, m)| m.method_id != METHOD_FINI && !skip.contains(n)).collect();
+        methods.sort_by_key(|(_, m)| m.method_id);
+        for (name, m) in methods {
+            plans[idx].methods.push((t.box_name.clone(), name.clone(), t.config.type_id, m.method_id));
+        }
+    }
+
+    let cases = fuzz_cases(seed, iterations);
+    let timeout = Duration::from_millis(args.timeout_ms);
+    let mut findings: Vec<(String, String, Finding, Option<usize>)> = Vec::new();
+    let mut total = 0usize;
+
+    for plan in &plans {
+        println!("\n{}: {} ({} methods x {} cases, seed={:#x})", "Library".bold(), plan.path.display(), plan.methods.len(), cases.len(), seed);
+        let count = plan.methods.len() * cases.len();
+        total += count;
+        let spec: Vec<String> = plan.methods.iter().map(|(_, _, t, m)| format!("{}:{}", t, m)).collect();
+        let locate = |idx: usize| {
+            let (b, m, _, _) = &plan.methods[idx / cases.len()];
+            format!("{}.{}", b, m)
+        };
+
+        let mut start = 0;
+        while start < count {
+            let (current, outcome) = match run_worker(&plan.path, &spec, seed, iterations, start, timeout, &mut |idx, msg| {
+                findings.push((locate(idx), cases[idx % cases.len()].0.clone(), Finding::Violation(msg), Some(idx)));
+            }) {
+                Ok(r) => r,
+                Err(e) => {
+                    eprintln!("{}: {}", "ERROR".red(), e);
+                    return false;
+                }
+            };
+            match (outcome, current) {
+                (None, _) => break,
+                (Some(f), Some(idx)) => {
+                    findings.push((locate(idx), cases[idx % cases.len()].0.clone(), f, Some(idx)));
+                    start = idx + 1;
+                }
+                (Some(f), None) => {
+                    // Died before the first case: birth or library load itself failed
+                    findings.push((plan.path.display().to_string(), "setup".into(), f, None));
+                    break;
+                }
+            }
+        }
+    }
+
+    let crashes = findings.iter().filter(|f| !matches!(f.2, Finding::Violation(_))).count();
+    println!("\n{}: {} cases, {} crashes/hangs, {} violations", "Fuzz".bold(), total, crashes, findings.len() - crashes);
+    for (at, label, finding, idx) in &findings {
+        let what = match finding {
+            Finding::Crash(status) => format!("{} ({})", "CRASH".red().bold(), status),
+            Finding::Hang => "HANG".red().bold().to_string(),
+            Finding::Violation(msg) => format!("{} {}", "VIOLATION".yellow(), msg),
+        };
+        println!("  {} {} [{}]", what, at, label);
+        if let Some(idx) = idx {
+            println!("      args: [{}]", hex(&cases[idx % cases.len()].1));
+        }
+    }
+    findings.is_empty()
+}
+
+/// Run one worker from case `start`; returns the last announced case and how it ended
+/// (`None` = ran to completion)
+fn run_worker(
+    lib: &Path,
+    spec: &[String],
+    seed: u64,
+    iterations: usize,
+    start: usize,
+    timeout: Duration,
+    on_violation: &mut dyn FnMut(usize, String),
+) -> Result<(Option<usize>, Option<Finding>), String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let mut child = Command::new(exe)
+        .arg("fuzz-worker")
+        .arg(lib)
+        .arg("--targets")
+        .arg(spec.join(","))
+        .args(["--seed", &seed.to_string(), "--iterations", &iterations.to_string(), "--start", &start.to_string()])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("failed to spawn fuzz worker: {}", e))?;
+
+    let stdout = child.stdout.take().ok_or("worker has no stdout")?;
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut current = None;
+    let mut done = false;
+    loop {
+        match rx.recv_timeout(timeout) {
+            Ok(line) => {
+                // Plugins may print to stdout too; only our marked lines count
+                let Some(pos) = line.find(MARK) else { continue };
+                let mut parts = line[pos + MARK.len()..].trim().splitn(3, ' ');
+                match (parts.next(), parts.next().and_then(|n| n.parse::<usize>().ok())) {
+                    (Some("CASE"), Some(idx)) => current = Some(idx),
+                    (Some("BAD"), Some(idx)) => on_violation(idx, parts.next().unwrap_or("").to_string()),
+                    (Some("DONE"), _) => done = true,
+                    _ => {}
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Ok((current, Some(Finding::Hang)));
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    let status = child.wait().map_err(|e| e.to_string())?;
+    if done && status.success() {
+        Ok((current, None))
+    } else {
+        Ok((current, Some(Finding::Crash(status.to_string()))))
+    }
+}
+
+fn call(invoke: InvokeFn, type_id: u32, method_id: u32, instance_id: u32, args: &[u8], out: &mut [u8], cap: usize) -> (i32, usize) {
+    let mut out_len = cap;
+    let rc = unsafe { invoke(type_id, method_id, instance_id, args.as_ptr(), args.len(), out.as_mut_ptr(), &mut out_len) };
+    (rc, out_len)
+}
+
+/// Child side of `run`; exit code 0 after `DONE`
+pub fn worker(lib_path: &Path, targets: &[String], seed: u64, iterations: usize, start: usize) -> i32 {
+    let lib = match unsafe { Library::new(lib_path) } {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("failed to load {}: {}", lib_path.display(), e);
+            return 2;
+        }
+    };
+    let invoke: InvokeFn = match unsafe { lib.get::<InvokeFn>(b"nyash_plugin_invoke") } {
+        Ok(f) => *f,
+        Err(_) => return 2,
+    };
+    if let Ok(init) = unsafe { lib.get::<unsafe extern "C" fn() -> i32>(b"nyash_plugin_init") } {
+        unsafe { init() };
+    }
+    let specs: Vec<(u32, u32)> = targets
+        .iter()
+        .filter_map(|t| t.split_once(':'))
+        .filter_map(|(t, m)| Some((t.parse().ok()?, m.parse().ok()?)))
+        .collect();
+
+    // One live instance per type so instance methods get past the handle check
+    let mut out = vec![0u8; RESULT_CAP + CANARY];
+    let mut instances: HashMap<u32, u32> = HashMap::new();
+    for &(type_id, _) in &specs {
+        if let std::collections::hash_map::Entry::Vacant(slot) = instances.entry(type_id) {
+            let (rc, len) = call(invoke, type_id, 0, 0, &tlv_encode(&[]), &mut out, RESULT_CAP);
+            let id = if rc == 0 && len >= 4 { u32::from_le_bytes([out[0], out[1], out[2], out[3]]) } else { 0 };
+            slot.insert(id);
+        }
+    }
+
+    let cases = fuzz_cases(seed, iterations);
+    let stdout = std::io::stdout();
+    let mut idx = 0usize;
+    for &(type_id, method_id) in &specs {
+        let instance = if method_id == 0 { 0 } else { instances[&type_id] };
+        for (_, args) in &cases {
+            if idx >= start {
+                let _ = writeln!(stdout.lock(), "{} CASE {}", MARK, idx);
+                let _ = stdout.lock().flush();
+                out.fill(0xa5);
+                let (rc, len) = call(invoke, type_id, method_id, instance, args, &mut out, RESULT_CAP);
+                let mut bad = Vec::new();
+                if !KNOWN_CODES.contains(&rc) {
+                    bad.push(format!("unknown rc {}", rc));
+                }
+                if rc == 0 && len > RESULT_CAP {
+                    bad.push(format!("claims {} bytes in a {}-byte buffer", len, RESULT_CAP));
+                }
+                if out[RESULT_CAP..].iter().any(|&b| b != 0xa5) {
+                    bad.push("wrote past the end of the result buffer".to_string());
+                }
+                if !bad.is_empty() {
+                    let _ = writeln!(stdout.lock(), "{} BAD {} {}", MARK, idx, bad.join("; "));
+                }
+                // Garbage that still births an instance: release it again
+                if method_id == 0 && rc == 0 && len >= 4 {
+                    let id = u32::from_le_bytes([out[0], out[1], out[2], out[3]]);
+                    call(invoke, type_id, METHOD_FINI, id, &tlv_encode(&[]), &mut out, RESULT_CAP);
+                }
+            }
+            idx += 1;
+        }
+    }
+
+    for (type_id, id) in instances {
+        if id != 0 {
+            call(invoke, type_id, METHOD_FINI, id, &tlv_encode(&[]), &mut out, RESULT_CAP);
+        }
+    }
+    let _ = writeln!(stdout.lock(), "{} DONE", MARK);
+    let _ = stdout.lock().flush();
+    0
+}
@@ -4,12 +4,16 @@
 //! - Host VTable廃止
 //! - nyash_plugin_invokeのみ使用
 //! - すべてのメタ情報はnyash.tomlから取得
+//! - nyash_plugin_manifest を公開するプラグインはTOML無しでもcheck可能
+
+mod conformance;
+mod fuzz;
 
 use clap::{Parser, Subcommand};
 use colored::*;
 use libloading::{Library, Symbol};
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -32,6 +36,8 @@ struct BoxTypeConfig {
     #[serde(default = "default_abi_version")]
     abi_version: u32,
     methods: HashMap<String, MethodDefinition>,
+    #[serde(default)]
+    singleton: bool,
 }
 
 fn default_abi_version() -> u32 { 1 }
@@ -39,6 +45,86 @@ fn default_abi_version() -> u32 { 1 }
 #[derive(Debug, Deserialize)]
 struct MethodDefinition {
     method_id: u32,
+    /// `["name"]` (string) or `[{ kind = "i32" }]` style declarations
+    #[serde(default)]
+    args: Option<Vec<toml::Value>>,
+}
+
+// ============ Plugin manifest (nyash_plugin_manifest) ============
+
+/// Same box tables as nyash.toml, without the `libraries."<lib>"` prefix
+struct PluginManifest {
+    abi_version: u32,
+    boxes: BTreeMap<String, BoxTypeConfig>,
+    text: String,
+}
+
+type ManifestFn = unsafe extern "C" fn(*mut u8, *mut usize) -> i32;
+
+fn read_plugin_abi(library: &Library) -> Option<u32> {
+    unsafe { library.get::<Symbol<unsafe extern "C" fn() -> u32>>(b"nyash_plugin_abi").ok().map(|f| f()) }
+}
+
+fn read_plugin_manifest(library: &Library) -> Option<Result<PluginManifest, String>> {
+    let func: ManifestFn = unsafe { *library.get::<ManifestFn>(b"nyash_plugin_manifest").ok()? };
+    let mut buf = vec![0u8; 4096];
+    loop {
+        let mut len = buf.len();
+        let rc = unsafe { func(buf.as_mut_ptr(), &mut len) };
+        match rc {
+            0 => {
+                buf.truncate(len.min(buf.len()));
+                break;
+            }
+            -1 if len > buf.len() && len <= 1 << 20 => buf.resize(len, 0),
+            _ => return Some(Err(format!("nyash_plugin_manifest rc={}", rc))),
+        }
+    }
+    Some(parse_manifest(buf))
+}
+
+fn parse_manifest(buf: Vec<u8>) -> Result<PluginManifest, String> {
+    let text = String::from_utf8(buf).map_err(|_| "manifest is not UTF-8".to_string())?;
+    let value: toml::Value = toml::from_str(&text).map_err(|e| e.to_string())?;
+    let table = value.as_table().ok_or("manifest must be a table")?;
+    let mut abi_version = 1;
+    let mut boxes = BTreeMap::new();
+    for (key, v) in table {
+        if key == "abi_version" {
+            abi_version = v.as_integer().unwrap_or(1) as u32;
+        } else {
+            let conf = v.clone().try_into::<BoxTypeConfig>().map_err(|e| format!("{}: {}", key, e))?;
+            boxes.insert(key.clone(), conf);
+        }
+    }
+    Ok(PluginManifest { abi_version, boxes, text })
+}
+
+/// nyash.toml ⇔ manifest mismatches (empty = consistent)
+fn compare_with_manifest(manifest: &PluginManifest, box_name: &str, config: &BoxTypeConfig) -> Vec<String> {
+    let mut problems = Vec::new();
+    let Some(exported) = manifest.boxes.get(box_name) else {
+        return vec![format!("{} is not exported by the plugin", box_name)];
+    };
+    if exported.type_id != config.type_id {
+        problems.push(format!("type_id {} in nyash.toml, plugin exports {}", config.type_id, exported.type_id));
+    }
+    if exported.abi_version != config.abi_version {
+        problems.push(format!("abi_version {} in nyash.toml, plugin exports {}", config.abi_version, exported.abi_version));
+    }
+    let mut names: Vec<&String> = config.methods.keys().collect();
+    names.sort();
+    for name in names {
+        match exported.methods.get(name) {
+            None => problems.push(format!("{}: not exported by the plugin", name)),
+            Some(m) if m.method_id != config.methods[name].method_id => problems.push(format!(
+                "{}: method_id {} in nyash.toml, plugin exports {}",
+                name, config.methods[name].method_id, m.method_id
+            )),
+            _ => {}
+        }
+    }
+    problems
 }
 
 // ============ CLI ============
@@ -62,6 +148,10 @@ enum Commands {
         /// Library name (e.g., "libnyash_filebox_plugin.so")
         #[arg(short, long)]
         library: Option<String>,
+
+        /// Check a plugin file directly from its manifest (no nyash.toml needed)
+        #[arg(short, long, conflicts_with_all = ["config", "library"])]
+        plugin: Option<PathBuf>,
     },
     /// Test Box lifecycle with nyash.toml v2
     Lifecycle {
@@ -78,6 +168,64 @@ enum Commands {
         #[arg(short, long, default_value = "../../nyash.toml")]
         config: PathBuf,
     },
+    /// Call every declared method and check the BID-1 error-code contract
+    Conformance {
+        #[command(flatten)]
+        target: TargetArgs,
+
+        /// Methods to leave out (e.g. blocking calls): "accept,acceptTimeout"
+        #[arg(long, value_delimiter = ',')]
+        skip: Vec<String>,
+    },
+    /// Send malformed TLV to every method and report crashes/contract violations
+    Fuzz {
+        #[command(flatten)]
+        target: TargetArgs,
+
+        /// PRNG seed (same seed = same cases)
+        #[arg(long, default_value_t = 0x4e59_4153)]
+        seed: u64,
+
+        /// Random cases per method on top of the fixed malformed set
+        #[arg(long, default_value_t = 200)]
+        iterations: usize,
+
+        /// Methods to leave out (blocking calls would hang on every case)
+        #[arg(long, value_delimiter = ',')]
+        skip: Vec<String>,
+    },
+    /// Internal: executes fuzz cases in a child process
+    #[command(hide = true)]
+    FuzzWorker {
+        lib_path: PathBuf,
+        #[arg(long, value_delimiter = ',')]
+        targets: Vec<String>,
+        #[arg(long)]
+        seed: u64,
+        #[arg(long)]
+        iterations: usize,
+        #[arg(long, default_value_t = 0)]
+        start: usize,
+    },
+}
+
+/// Which box types `conformance` / `fuzz` exercise
+#[derive(clap::Args)]
+struct TargetArgs {
+    /// Path to nyash.toml file
+    #[arg(short, long, default_value = "../../nyash.toml")]
+    config: PathBuf,
+
+    /// Use the plugin's own manifest instead of nyash.toml
+    #[arg(short, long)]
+    plugin: Option<PathBuf>,
+
+    /// Box type to test (default: every box)
+    box_type: Option<String>,
+
+    /// Per-call timeout in milliseconds (blocking methods are reported, not waited on)
+    #[arg(long, default_value_t = 2000)]
+    timeout_ms: u64,
 }
 
 // ============ TLV Helpers ============
@@ -135,15 +283,75 @@ fn tlv_decode_u32(data: &[u8]) -> Result<u32, String> {
     }
 }
 
+/// Header + (tag, payload) entries
+fn tlv_encode(entries: &[(u8, Vec<u8>)]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&1u16.to_le_bytes()); // ver
+    buf.extend_from_slice(&(entries.len() as u16).to_le_bytes()); // argc
+    for (tag, payload) in entries {
+        buf.push(*tag);
+        buf.push(0u8);
+        buf.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+        buf.extend_from_slice(payload);
+    }
+    buf
+}
+
+/// Structural check of a result buffer: version, argc entries in bounds, known tags, fixed sizes
+fn tlv_validate(data: &[u8]) -> Result<(), String> {
+    if data.len() < 4 {
+        return Err(format!("{} bytes is shorter than the header", data.len()));
+    }
+    let ver = u16::from_le_bytes([data[0], data[1]]);
+    if ver != 1 {
+        return Err(format!("version {} (expected 1)", ver));
+    }
+    let argc = u16::from_le_bytes([data[2], data[3]]) as usize;
+    let mut pos = 4;
+    for i in 0..argc {
+        if pos + 4 > data.len() {
+            return Err(format!("entry {} header truncated", i));
+        }
+        let tag = data[pos];
+        let size = u16::from_le_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        pos += 4;
+        if pos + size > data.len() {
+            return Err(format!("entry {} size {} overruns buffer", i, size));
+        }
+        let expected = match tag {
+            1 => Some(1),
+            2 | 4 => Some(4),
+            3 | 5 | 8 => Some(8),
+            9 => Some(0),
+            6 | 7 => None,
+            _ => return Err(format!("entry {} has unknown tag {}", i, tag)),
+        };
+        if expected.is_some_and(|e| e != size) {
+            return Err(format!("entry {} tag {} has size {}", i, tag, size));
+        }
+        if tag == 6 && std::str::from_utf8(&data[pos..pos + size]).is_err() {
+            return Err(format!("entry {} string is not UTF-8", i));
+        }
+        pos += size;
+    }
+    Ok(())
+}
+
 // ============ Main Functions ============
 
 fn main() {
     let args = Args::parse();
     
     match args.command {
-        Commands::Check { config, library } => check_v2(&config, library.as_deref()),
+        Commands::Check { plugin: Some(plugin), .. } => check_manifest(&plugin),
+        Commands::Check { config, library, .. } => check_v2(&config, library.as_deref()),
         Commands::Lifecycle { config, box_type } => test_lifecycle_v2(&config, &box_type),
         Commands::ValidateAll { config } => validate_all(&config),
+        Commands::Conformance { target, skip } => exit_with(conformance::run(&target, &skip)),
+        Commands::Fuzz { target, seed, iterations, skip } => exit_with(fuzz::run(&target, &skip, seed, iterations)),
+        Commands::FuzzWorker { lib_path, targets, seed, iterations, start } => {
+            std::process::exit(fuzz::worker(&lib_path, &targets, seed, iterations, start))
+        }
     }
 }
 
@@ -217,6 +425,24 @@ fn check_v2(config_path: &PathBuf, library_filter: Option<&str>) {
                 continue;
             }
         }
+
+        if let Some(abi) = read_plugin_abi(&library) {
+            println!("  ABI version (plugin): {}", abi);
+        }
+        let manifest = match read_plugin_manifest(&library) {
+            Some(Ok(m)) => {
+                println!("  {}: manifest exported ({} box types)", "✓".green(), m.boxes.len());
+                Some(m)
+            }
+            Some(Err(e)) => {
+                eprintln!("  {}: invalid manifest: {}", "WARNING".yellow(), e);
+                None
+            }
+            None => {
+                println!("  {}: no nyash_plugin_manifest (ids cannot be cross-checked)", "NOTE".yellow());
+                None
+            }
+        };
         
         // Check each box type from nyash.toml
         for box_name in &lib_def.boxes {
@@ -244,6 +470,16 @@ fn check_v2(config_path: &PathBuf, library_filter: Option<&str>) {
                         method_type
                     );
                 }
+
+                if let Some(m) = &manifest {
+                    let problems = compare_with_manifest(m, box_name, &config);
+                    if problems.is_empty() {
+                        println!("    {}: matches plugin manifest", "✓".green());
+                    }
+                    for p in problems {
+                        eprintln!("    {}: {}", "MISMATCH".red(), p);
+                    }
+                }
             } else {
                 eprintln!("    {}: No configuration found for this box type", "WARNING".yellow());
             }
@@ -253,6 +489,78 @@ fn check_v2(config_path: &PathBuf, library_filter: Option<&str>) {
     println!("\n{}", "Check completed!".green().bold());
 }
 
+fn check_manifest(plugin_path: &Path) {
+    println!("{}", "=== Plugin Check (manifest) ===".bold());
+    println!("Plugin: {}", plugin_path.display());
+
+    let library = match unsafe { Library::new(plugin_path) } {
+        Ok(lib) => lib,
+        Err(e) => {
+            eprintln!("{}: Failed to load: {}", "ERROR".red(), e);
+            return;
+        }
+    };
+    if unsafe { library.get::<Symbol<unsafe extern "C" fn()>>(b"nyash_plugin_invoke") }.is_err() {
+        eprintln!("{}: nyash_plugin_invoke NOT FOUND - not a valid v2 plugin!", "ERROR".red());
+        return;
+    }
+    println!("{}: nyash_plugin_invoke found", "✓".green());
+
+    let abi = read_plugin_abi(&library);
+    let manifest = match read_plugin_manifest(&library) {
+        Some(Ok(m)) => m,
+        Some(Err(e)) => {
+            eprintln!("{}: invalid manifest: {}", "ERROR".red(), e);
+            return;
+        }
+        None => {
+            eprintln!("{}: plugin does not export nyash_plugin_manifest; use --config instead", "ERROR".red());
+            return;
+        }
+    };
+    match abi {
+        Some(v) if v != manifest.abi_version => eprintln!(
+            "{}: nyash_plugin_abi() = {} but manifest says {}",
+            "WARNING".yellow(), v, manifest.abi_version
+        ),
+        _ => println!("ABI version: {}", manifest.abi_version),
+    }
+
+    for (box_name, config) in &manifest.boxes {
+        println!("\n  {}: {}", "Box Type".bold(), box_name.cyan());
+        println!("    Type ID: {}", config.type_id);
+        let mut methods: Vec<_> = config.methods.iter().collect();
+        methods.sort_by_key(|(_, m)| m.method_id);
+        for (name, m) in methods {
+            println!("    - {}: method_id={}", name, m.method_id);
+        }
+        if !config.methods.contains_key("birth") || !config.methods.contains_key("fini") {
+            eprintln!("    {}: birth/fini not declared", "WARNING".yellow());
+        }
+    }
+
+    // Ready-to-paste nyash.toml section
+    let lib_name = plugin_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    println!("\n{}", "# nyash.toml".bold());
+    println!("[libraries.\"{}\"]", lib_name);
+    println!("boxes = [{}]", manifest.boxes.keys().map(|b| format!("\"{}\"", b)).collect::<Vec<_>>().join(", "));
+    println!("path = \"{}\"", plugin_path.display());
+    // Top-level keys (abi_version) precede the first table and are not part of nyash.toml
+    let mut in_tables = false;
+    for line in manifest.text.lines() {
+        match line.strip_prefix('[') {
+            Some(rest) => {
+                in_tables = true;
+                println!("\n[libraries.\"{}\".{}", lib_name, rest);
+            }
+            None if in_tables && !line.trim().is_empty() => println!("{}", line),
+            None => {}
+        }
+    }
+
+    println!("\n{}", "Check completed!".green().bold());
+}
+
 fn test_lifecycle_v2(config_path: &PathBuf, box_type: &str) {
     println!("{}", "=== Lifecycle Test v2 ===".bold());
     println!("Box type: {}", box_type.cyan());
@@ -485,6 +793,61 @@ fn validate_all(config_path: &PathBuf) {
 
 // ============ Helper Functions ============
 
+/// Exit code for release gating: 0 only when no violation was found
+fn exit_with(ok: bool) {
+    if !ok {
+        std::process::exit(1);
+    }
+}
+
+type InvokeFn = unsafe extern "C" fn(u32, u32, u32, *const u8, usize, *mut u8, *mut usize) -> i32;
+
+/// One box type to exercise, from nyash.toml or the plugin's manifest
+struct BoxTarget {
+    lib_path: PathBuf,
+    box_name: String,
+    config: BoxTypeConfig,
+}
+
+fn resolve_targets(args: &TargetArgs) -> Result<Vec<BoxTarget>, String> {
+    let wanted = |name: &str| args.box_type.as_deref().is_none_or(|b| b == name);
+    let mut targets = Vec::new();
+
+    if let Some(plugin) = &args.plugin {
+        let library = unsafe { Library::new(plugin) }.map_err(|e| format!("failed to load {}: {}", plugin.display(), e))?;
+        let manifest = read_plugin_manifest(&library)
+            .ok_or("plugin does not export nyash_plugin_manifest; use --config")??;
+        for (box_name, config) in manifest.boxes {
+            if wanted(&box_name) {
+                targets.push(BoxTarget { lib_path: plugin.clone(), box_name, config });
+            }
+        }
+    } else {
+        let content = fs::read_to_string(&args.config).map_err(|e| format!("failed to read config: {}", e))?;
+        let config: NyashConfigV2 = toml::from_str(&content).map_err(|e| format!("failed to parse nyash.toml: {}", e))?;
+        let raw: toml::Value = toml::from_str(&content).map_err(|e| e.to_string())?;
+        let base = args.config.parent().unwrap_or(Path::new("."));
+        let mut libs: Vec<_> = config.libraries.iter().collect();
+        libs.sort_by_key(|(name, _)| name.as_str());
+        for (lib_name, lib_def) in libs {
+            let lib_path = if Path::new(&lib_def.path).is_absolute() { PathBuf::from(&lib_def.path) } else { base.join(&lib_def.path) };
+            for box_name in lib_def.boxes.iter().filter(|b| wanted(b)) {
+                let config = get_box_config(&raw, lib_name, box_name)
+                    .ok_or_else(|| format!("no [libraries.\"{}\".{}] table", lib_name, box_name))?;
+                targets.push(BoxTarget { lib_path: lib_path.clone(), box_name: box_name.clone(), config });
+            }
+        }
+    }
+
+    if targets.is_empty() {
+        return Err(match &args.box_type {
+            Some(b) => format!("box type '{}' not found", b),
+            None => "no box types found".to_string(),
+        });
+    }
+    Ok(targets)
+}
+
 fn find_library_for_box<'a>(config: &'a NyashConfigV2, box_type: &str) -> Option<(&'a str, &'a LibraryDefinition)> {
     config.libraries.iter()
         .find(|(_, lib)| lib.boxes.contains(&box_type.to_string()))